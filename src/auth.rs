@@ -3,8 +3,25 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum AuthMethod {
-    Basic { email: String, token: String },
-    Bearer { token: String },
+    Basic {
+        email: String,
+        token: String,
+    },
+    Bearer {
+        token: String,
+    },
+    /// A Server/Data Center personal access token, sent as `Authorization: Bearer`.
+    Pat {
+        token: String,
+    },
+    /// A Server/Data Center username and password, exchanged for a session cookie through
+    /// `rest/auth/1/session`; for older sites that disallow tokens.
+    Cookie {
+        username: String,
+        password: String,
+    },
+    /// No credentials (`--anonymous`), for sites with public access.
+    Anonymous,
 }
 
 impl AuthMethod {
@@ -12,6 +29,22 @@ impl AuthMethod {
         match self {
             AuthMethod::Basic { .. } => "basic",
             AuthMethod::Bearer { .. } => "bearer",
+            AuthMethod::Pat { .. } => "pat",
+            AuthMethod::Cookie { .. } => "cookie",
+            AuthMethod::Anonymous => "anonymous",
+        }
+    }
+
+    /// The secret part of the credentials, if there is one.
+    pub fn token_mut(&mut self) -> Option<&mut String> {
+        match self {
+            AuthMethod::Basic { token, .. }
+            | AuthMethod::Bearer { token }
+            | AuthMethod::Pat { token }
+            | AuthMethod::Cookie {
+                password: token, ..
+            } => Some(token),
+            AuthMethod::Anonymous => None,
         }
     }
 }
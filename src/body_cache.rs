@@ -0,0 +1,184 @@
+//! Opt-in local cache of page bodies (`page body --cache`), for agents that read the same
+//! pages over and over. Entries are keyed by site, page id, and body format, and record the
+//! version they were fetched at; a hit costs one metadata request to check that version.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use confcli::client::ApiClient;
+use serde_json::Value;
+
+/// `<cache dir>/confcli/pages`, e.g. `~/.cache/confcli/pages` on Linux.
+fn cache_root() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("confcli").join("pages"))
+}
+
+/// The page with `body-format=<format>` (the v2 page JSON). With `use_cache`, it comes from
+/// the cache when the page is still at the cached version; cache failures fall back to the
+/// network.
+pub async fn page_with_body(
+    client: &ApiClient,
+    page_id: &str,
+    format: &str,
+    use_cache: bool,
+) -> Result<Value> {
+    match cache_root() {
+        Some(root) if use_cache => page_with_body_in(&root, client, page_id, format).await,
+        _ => fetch(client, page_id, format).await,
+    }
+}
+
+async fn page_with_body_in(
+    root: &Path,
+    client: &ApiClient,
+    page_id: &str,
+    format: &str,
+) -> Result<Value> {
+    let path = entry_path(root, client.base_url(), page_id, format);
+    if let Some(cached) = read_entry(&path) {
+        let (meta, _) = client
+            .get_json(client.v2_url(&format!("/pages/{page_id}")))
+            .await?;
+        if version_of(&meta).is_some() && version_of(&meta) == version_of(&cached) {
+            tracing::debug!("Page {page_id} ({format}) served from {}", path.display());
+            return Ok(cached);
+        }
+    }
+    let page = fetch(client, page_id, format).await?;
+    if let Err(err) = write_entry(&path, &page) {
+        tracing::warn!("Failed to cache page {page_id}: {err:#}");
+    }
+    Ok(page)
+}
+
+async fn fetch(client: &ApiClient, page_id: &str, format: &str) -> Result<Value> {
+    let url = client.v2_url(&format!("/pages/{page_id}?body-format={format}"));
+    let (json, _) = client.get_json(url).await?;
+    Ok(json)
+}
+
+fn version_of(page: &Value) -> Option<i64> {
+    page.get("version")?.get("number")?.as_i64()
+}
+
+/// `<root>/<site>/<page id>.<format>.json`; the site is the base URL's host and path, so
+/// profiles for different sites never share entries.
+fn entry_path(root: &Path, base_url: &str, page_id: &str, format: &str) -> PathBuf {
+    let site = base_url
+        .split_once("://")
+        .map_or(base_url, |(_, rest)| rest)
+        .trim_end_matches('/');
+    let site: String = site
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    root.join(site).join(format!("{page_id}.{format}.json"))
+}
+
+/// A readable entry, or `None` (missing, or unreadable and about to be replaced).
+fn read_entry(path: &Path) -> Option<Value> {
+    let data = std::fs::read(path).ok()?;
+    serde_json::from_slice(&data).ok()
+}
+
+/// Replace the entry atomically, so a concurrent reader never sees half a page.
+fn write_entry(path: &Path, page: &Value) -> Result<()> {
+    let dir = path.parent().context("Cache entry without a directory")?;
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let mut tmp = tempfile::NamedTempFile::new_in(dir)
+        .with_context(|| format!("Failed to create temp file in {}", dir.display()))?;
+    tmp.write_all(&serde_json::to_vec(page)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    tmp.persist(path)
+        .map_err(|e| e.error)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::http_server::start_server;
+    use confcli::auth::AuthMethod;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicI64, Ordering};
+
+    #[test]
+    fn entries_are_keyed_by_site_page_and_format() {
+        let root = Path::new("/cache");
+        assert_eq!(
+            entry_path(root, "https://example.atlassian.net/wiki", "42", "view"),
+            Path::new("/cache/example.atlassian.net_wiki/42.view.json")
+        );
+        assert_eq!(
+            entry_path(root, "http://127.0.0.1:8080/", "42", "storage"),
+            Path::new("/cache/127.0.0.1_8080/42.storage.json")
+        );
+    }
+
+    #[tokio::test]
+    async fn refetches_the_body_only_when_the_version_changed() {
+        let version = Arc::new(AtomicI64::new(3));
+        let served = version.clone();
+        let srv = start_server(move |_hit, target| {
+            let number = served.load(Ordering::SeqCst);
+            let page = if target.contains("body-format=storage") {
+                serde_json::json!({
+                    "id": "42",
+                    "version": { "number": number },
+                    "body": { "storage": { "value": format!("<p>v{number}</p>") } },
+                })
+            } else {
+                assert_eq!(target, "/pages/42");
+                serde_json::json!({ "id": "42", "version": { "number": number } })
+            };
+            (
+                200,
+                vec![("content-type".to_string(), "application/json".to_string())],
+                page.to_string().into_bytes(),
+            )
+        })
+        .await;
+        let client = ApiClient::new(
+            srv.base_url.clone(),
+            srv.base_url.clone(),
+            srv.base_url.clone(),
+            AuthMethod::Bearer {
+                token: "test".to_string(),
+            },
+            0,
+        )
+        .unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let body = |page: &Value| page["body"]["storage"]["value"].clone();
+
+        let first = page_with_body_in(root.path(), &client, "42", "storage")
+            .await
+            .unwrap();
+        assert_eq!(body(&first), "<p>v3</p>");
+        assert_eq!(srv.hits.load(Ordering::SeqCst), 1);
+
+        // Same version: only the metadata request goes out.
+        let cached = page_with_body_in(root.path(), &client, "42", "storage")
+            .await
+            .unwrap();
+        assert_eq!(body(&cached), "<p>v3</p>");
+        assert_eq!(srv.hits.load(Ordering::SeqCst), 2);
+
+        version.store(4, Ordering::SeqCst);
+        let fresh = page_with_body_in(root.path(), &client, "42", "storage")
+            .await
+            .unwrap();
+        assert_eq!(body(&fresh), "<p>v4</p>");
+        assert_eq!(srv.hits.load(Ordering::SeqCst), 4);
+
+        let _ = srv.shutdown.send(());
+    }
+}
@@ -0,0 +1,14 @@
+use clap::Args;
+use confcli::output::OutputFormat;
+use std::path::PathBuf;
+
+#[derive(Args, Debug)]
+#[command(
+    after_help = "Re-running a spec only changes what drifted.\n\nSPEC:\n  space: OPS\n  title: On-call roster\n  parent: OPS:Runbooks\n  body_file: roster.xml\n  labels: [oncall]\n  properties:\n    owner: team-sre\n\nEXAMPLES:\n  confcli apply oncall.yaml --dry-run\n  confcli apply oncall.yaml -o json\n"
+)]
+pub struct ApplyArgs {
+    #[arg(help = "Path to the YAML page spec")]
+    pub spec: PathBuf,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+}
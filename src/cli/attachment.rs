@@ -12,6 +12,11 @@ pub enum AttachmentCommand {
     Get(AttachmentGetArgs),
     #[command(about = "Download an attachment")]
     Download(AttachmentDownloadArgs),
+    #[command(
+        about = "Incrementally copy a space's attachments to a directory or object storage",
+        after_help = MIRROR_AFTER_HELP
+    )]
+    Mirror(AttachmentMirrorArgs),
     #[cfg(feature = "write")]
     #[command(about = "Upload an attachment")]
     Upload(AttachmentUploadArgs),
@@ -24,6 +29,12 @@ pub enum AttachmentCommand {
 pub struct AttachmentListArgs {
     #[arg(help = "Page id, URL, or SPACE:Title (omit to list all attachments)")]
     pub page: Option<String>,
+    #[arg(
+        long,
+        conflicts_with = "page",
+        help = "List the attachments of every page in this space (key or id), with page title and path"
+    )]
+    pub space: Option<String>,
     #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
     pub output: OutputFormat,
     #[arg(short = 'a', long, help = "Fetch all pages of results")]
@@ -46,6 +57,35 @@ pub struct AttachmentGetArgs {
     pub output: OutputFormat,
 }
 
+#[cfg(feature = "s3")]
+const MIRROR_AFTER_HELP: &str = "EXAMPLES:\n  confcli attachment mirror --space ENG --dest ./backup\n  confcli attachment mirror --space ENG --dest s3://backups/confluence --verify\n  confcli attachment mirror --space ENG --dest s3://backups/confluence --endpoint https://minio.internal:9000\n\nCopies land at <dest>/<SPACE>/<page id>/<file name>. A .confcli-mirror.json manifest at <dest> records each attachment's version and SHA-256; re-runs only download attachments with a new version, and only copy them when the content changed. Deleted attachments are kept. S3 credentials come from AWS_ACCESS_KEY_ID, AWS_SECRET_ACCESS_KEY, and AWS_SESSION_TOKEN.\n";
+#[cfg(not(feature = "s3"))]
+const MIRROR_AFTER_HELP: &str = "EXAMPLES:\n  confcli attachment mirror --space ENG --dest ./backup\n  confcli attachment mirror --space ENG --dest ./backup --verify\n\nCopies land at <dest>/<SPACE>/<page id>/<file name>. A .confcli-mirror.json manifest at <dest> records each attachment's version and SHA-256; re-runs only download attachments with a new version, and only copy them when the content changed. Deleted attachments are kept. s3:// destinations need a build with the `s3` feature.\n";
+
+#[derive(Args, Debug)]
+pub struct AttachmentMirrorArgs {
+    #[arg(long, help = "Space key or id whose attachments are mirrored")]
+    pub space: String,
+    #[arg(long, help = "Directory, or s3://bucket/prefix (with the s3 feature)")]
+    pub dest: String,
+    #[arg(
+        long,
+        help = "Re-check every copy against its recorded SHA-256 and restore missing or changed ones"
+    )]
+    pub verify: bool,
+    #[cfg(feature = "s3")]
+    #[arg(long, help = "S3 region (default: AWS_REGION, then us-east-1)")]
+    pub region: Option<String>,
+    #[cfg(feature = "s3")]
+    #[arg(
+        long,
+        help = "S3-compatible endpoint URL, e.g. MinIO (default: AWS_ENDPOINT_URL; AWS otherwise)"
+    )]
+    pub endpoint: Option<String>,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+}
+
 #[derive(Args, Debug)]
 pub struct AttachmentDownloadArgs {
     #[arg(help = "Attachment id")]
@@ -65,13 +105,36 @@ pub struct AttachmentUploadArgs {
     pub comment: Option<String>,
     #[arg(
         long,
-        default_value = "4",
-        value_parser = parse_positive_limit,
-        help = "Max concurrent uploads"
+        value_enum,
+        help = "What to do when the page already has an attachment with the file's name"
     )]
-    pub concurrency: usize,
+    pub if_exists: Option<IfExists>,
+    #[arg(
+        short = 'y',
+        long,
+        help = "Upload large files without asking (see confirm.upload_size in the config)"
+    )]
+    pub yes: bool,
     #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
     pub output: OutputFormat,
+    #[arg(
+        long,
+        conflicts_with_all = ["output"],
+        help = "Print only the attachment ids on stdout, one per line; other messages go to stderr"
+    )]
+    pub print_id: bool,
+}
+
+/// How `attachment upload --if-exists` treats a file whose name is already attached.
+#[cfg(feature = "write")]
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IfExists {
+    /// Leave the existing attachment alone and don't upload the file.
+    Skip,
+    /// Upload the file as a new version of the existing attachment.
+    Replace,
+    /// Upload the file under a free name: `report (1).pdf`, `report (2).pdf`, ...
+    Rename,
 }
 
 #[cfg(feature = "write")]
@@ -1,15 +1,58 @@
-use clap::{Args, Subcommand};
+use clap::{Args, Subcommand, ValueEnum};
+use confcli::output::OutputFormat;
+
+use super::DoctorArgs;
 
 #[derive(Subcommand, Debug)]
 pub enum AuthCommand {
     #[command(about = "Log in and store credentials")]
-    Login(AuthLoginArgs),
-    #[command(about = "Show current authentication status")]
-    Status,
+    Login(Box<AuthLoginArgs>),
+    #[command(
+        about = "Show current authentication status: site, account, and OAuth token scopes and expiry",
+        after_help = "EXAMPLES:\n  confcli auth status\n  confcli auth status --all-profiles   # check every stored profile at once\n"
+    )]
+    Status(AuthStatusArgs),
+    #[command(
+        about = "List stored profiles with their sites and auth types",
+        after_help = "EXAMPLES:\n  confcli auth list\n  confcli auth list -o json\n"
+    )]
+    List(AuthListArgs),
+    #[command(
+        about = "Make a stored profile the one commands use without --profile",
+        after_help = "EXAMPLES:\n  confcli auth switch work\n  confcli auth switch default   # back to config.json\n"
+    )]
+    Switch(AuthSwitchArgs),
+    #[command(
+        about = "Check DNS, TLS, API reachability, read and write access, and rate limits, with suggested fixes (same as confcli doctor)"
+    )]
+    Doctor(DoctorArgs),
     #[command(about = "Clear stored credentials")]
     Logout,
 }
 
+#[derive(Args, Debug)]
+pub struct AuthListArgs {
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+pub struct AuthSwitchArgs {
+    #[arg(help = "Profile name (`default` is config.json)")]
+    pub name: String,
+}
+
+#[derive(Args, Debug)]
+pub struct AuthStatusArgs {
+    #[arg(
+        long,
+        help = "Check every stored profile concurrently and print a health table"
+    )]
+    pub all_profiles: bool,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+}
+
 #[derive(Args, Debug)]
 pub struct AuthLoginArgs {
     #[arg(
@@ -27,6 +70,22 @@ pub struct AuthLoginArgs {
         help = "API token for basic auth (also accepts CONFLUENCE_API_TOKEN)"
     )]
     pub token: Option<String>,
+    #[arg(
+        long,
+        value_name = "COMMAND",
+        conflicts_with_all = ["token", "password", "bearer", "pat", "keyring", "encrypt"],
+        help = "Shell command that prints the API token (e.g. from a password manager); run each time instead of storing the token"
+    )]
+    pub token_cmd: Option<String>,
+    #[arg(
+        long,
+        value_enum,
+        requires = "token_cmd",
+        conflicts_with = "username",
+        default_value_t = TokenCmdAuth::Basic,
+        help = "What --token-cmd prints: an API token for basic auth (with --email), a personal access token (Server/Data Center), or an OAuth bearer token"
+    )]
+    pub auth_type: TokenCmdAuth,
     #[arg(
         long,
         env = "CONFLUENCE_API_PATH",
@@ -35,6 +94,19 @@ pub struct AuthLoginArgs {
     pub api_path: Option<String>,
     #[arg(long, help = "Override v2 API path (e.g. /wiki/api/v2 or /api/v2)")]
     pub api_v2_path: Option<String>,
+    #[arg(
+        long,
+        env = "CONFLUENCE_CLOUD_ID",
+        conflicts_with_all = ["api_path", "api_v2_path"],
+        help = "Send API requests through https://api.atlassian.com/ex/confluence/<ID> (required by OAuth and scoped tokens)"
+    )]
+    pub cloud_id: Option<String>,
+    #[arg(
+        long,
+        conflicts_with_all = ["api_path", "api_v2_path", "cloud_id"],
+        help = "Like --cloud-id, looking the site's cloud id up"
+    )]
+    pub gateway: bool,
     #[arg(
         long,
         env = "CONFLUENCE_BEARER_TOKEN",
@@ -42,4 +114,51 @@ pub struct AuthLoginArgs {
         help = "Bearer token for OAuth"
     )]
     pub bearer: Option<String>,
+    #[arg(
+        long,
+        env = "CONFLUENCE_PAT",
+        hide_env_values = true,
+        conflicts_with = "bearer",
+        help = "Personal access token for Confluence Server/Data Center"
+    )]
+    pub pat: Option<String>,
+    #[arg(
+        long,
+        conflicts_with_all = ["email", "token", "bearer", "pat"],
+        help = "Username for a Server/Data Center session-cookie login (for sites that disallow tokens)"
+    )]
+    pub username: Option<String>,
+    #[arg(
+        long,
+        requires = "username",
+        help = "Password for --username (prompted for when missing)"
+    )]
+    pub password: Option<String>,
+    #[arg(
+        long,
+        conflicts_with = "no_keyring",
+        help = "Store the token in the OS keyring (macOS Keychain, Windows Credential Manager, Secret Service) instead of the config file"
+    )]
+    pub keyring: bool,
+    #[arg(
+        long,
+        help = "Store the token in the config file, moving it out of the OS keyring"
+    )]
+    pub no_keyring: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["keyring", "no_encrypt"],
+        help = "Encrypt the token in the config file with a passphrase (read from CONFCLI_PASSPHRASE or prompted for on use)"
+    )]
+    pub encrypt: bool,
+    #[arg(long, help = "Store the token in the config file unencrypted")]
+    pub no_encrypt: bool,
+}
+
+/// The kind of token a `--token-cmd` prints.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenCmdAuth {
+    Pat,
+    Bearer,
+    Basic,
 }
@@ -0,0 +1,13 @@
+use clap::Args;
+use confcli::output::OutputFormat;
+
+#[derive(Args, Debug)]
+#[command(
+    after_help = "EXAMPLES:\n  confcli check-titles --space MFS\n  confcli check-titles --space MFS -o json   # exits non-zero when it finds problems\n"
+)]
+pub struct CheckTitlesArgs {
+    #[arg(long, help = "Space key")]
+    pub space: String,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+}
@@ -73,6 +73,12 @@ pub struct CommentAddArgs {
     pub body_format: String,
     #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
     pub output: OutputFormat,
+    #[arg(
+        long,
+        conflicts_with_all = ["output"],
+        help = "Print only the new comment id on stdout; other messages go to stderr"
+    )]
+    pub print_id: bool,
 }
 
 #[cfg(feature = "write")]
@@ -1,5 +1,5 @@
 #[cfg(feature = "write")]
-pub(super) fn parse_space_key(s: &str) -> Result<String, String> {
+pub(crate) fn parse_space_key(s: &str) -> Result<String, String> {
     let s = s.trim();
     if s.is_empty() {
         return Err("space key cannot be empty".to_string());
@@ -28,3 +28,14 @@ pub(super) fn parse_positive_limit(s: &str) -> Result<usize, String> {
     }
     Ok(value)
 }
+
+pub(super) fn parse_ratio(s: &str) -> Result<f64, String> {
+    let value = s
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| "value must be a number between 0 and 1".to_string())?;
+    if !(0.0..=1.0).contains(&value) {
+        return Err("value must be between 0 and 1".to_string());
+    }
+    Ok(value)
+}
@@ -1,5 +1,8 @@
 use clap::Args;
 use confcli::output::OutputFormat;
+use confcli::schedule::RunWindow;
+use regex::{Regex, RegexBuilder};
+use std::path::PathBuf;
 
 #[cfg(feature = "write")]
 #[derive(Args, Debug)]
@@ -16,6 +19,19 @@ pub struct CopyTreeArgs {
         help = "Suffix appended to copied page titles"
     )]
     pub copy_suffix: String,
+    #[arg(
+        long,
+        default_value = "",
+        help = "Prefix prepended to copied page titles"
+    )]
+    pub prefix: String,
+    #[arg(
+        long = "rename",
+        value_name = "s/REGEX/REPLACEMENT/[gi]",
+        value_parser = parse_title_rename,
+        help = "Rewrite copied titles sed-style before prefix/suffix are added (repeatable, applied in order)"
+    )]
+    pub renames: Vec<TitleRename>,
     #[arg(
         long,
         help = "Exclude pages whose titles match this glob (case-insensitive)"
@@ -23,14 +39,127 @@ pub struct CopyTreeArgs {
     pub exclude: Option<String>,
     #[arg(long, default_value = "0", help = "Max depth to copy (0 = unlimited)")]
     pub max_depth: usize,
-    #[arg(long, default_value = "0", help = "Delay between create requests (ms)")]
+    #[arg(
+        long,
+        default_value = "0",
+        help = "Delay between create requests of one sibling group (ms)"
+    )]
     pub delay_ms: u64,
     #[arg(
         long,
-        default_value = "8",
-        help = "Max concurrent fetches for source bodies"
+        value_name = "PER_MINUTE",
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Start at most this many API requests per minute, evenly spread across all workers"
     )]
-    pub concurrency: usize,
+    pub pace: Option<u32>,
+    #[arg(
+        long,
+        value_name = "HH:MM-HH:MM",
+        help = "Only send requests inside this daily UTC window (e.g. 22:00-06:00); pauses outside it and resumes when it opens"
+    )]
+    pub run_window: Option<RunWindow>,
+    #[arg(
+        long,
+        help = "Check that you may create pages in the target space before copying anything"
+    )]
+    pub preflight: bool,
+    #[arg(
+        long,
+        value_name = "PROFILE",
+        help = "Create the copy on another site: TARGET_PARENT is resolved and pages are created with this stored profile"
+    )]
+    pub target_profile: Option<String>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Record each created page in FILE (JSON lines) for `confcli rollback`"
+    )]
+    pub journal: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Trash every page this run created if the copy fails partway"
+    )]
+    pub rollback_on_error: bool,
     #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
     pub output: OutputFormat,
 }
+
+/// A sed-style `s/REGEX/REPLACEMENT/[gi]` title rewrite.
+#[derive(Debug, Clone)]
+pub struct TitleRename {
+    pub regex: Regex,
+    /// In `regex` replacement syntax (`\1` is accepted and converted to `${1}`).
+    pub replacement: String,
+    pub global: bool,
+}
+
+impl TitleRename {
+    pub fn apply(&self, title: &str) -> String {
+        if self.global {
+            self.regex.replace_all(title, self.replacement.as_str())
+        } else {
+            self.regex.replace(title, self.replacement.as_str())
+        }
+        .into_owned()
+    }
+}
+
+fn parse_title_rename(s: &str) -> Result<TitleRename, String> {
+    let usage = "expected s/REGEX/REPLACEMENT/ with optional g and i flags";
+    let mut chars = s.chars();
+    if chars.next() != Some('s') {
+        return Err(usage.to_string());
+    }
+    let delim = chars
+        .next()
+        .filter(|c| !c.is_alphanumeric() && *c != '\\')
+        .ok_or_else(|| usage.to_string())?;
+    // Split on unescaped delimiters; `\<delim>` stands for a literal delimiter.
+    let mut parts = vec![String::new()];
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(next) if next == delim => parts.last_mut().unwrap().push(next),
+                Some(next) => {
+                    let part = parts.last_mut().unwrap();
+                    part.push('\\');
+                    part.push(next);
+                }
+                None => return Err(usage.to_string()),
+            },
+            c if c == delim => parts.push(String::new()),
+            c => parts.last_mut().unwrap().push(c),
+        }
+    }
+    let [pattern, replacement, flags] =
+        <[String; 3]>::try_from(parts).map_err(|_| usage.to_string())?;
+    if let Some(bad) = flags.chars().find(|c| !matches!(c, 'g' | 'i')) {
+        return Err(format!("unknown flag '{bad}' ({usage})"));
+    }
+    let regex = RegexBuilder::new(&pattern)
+        .case_insensitive(flags.contains('i'))
+        .build()
+        .map_err(|err| format!("invalid regex: {err}"))?;
+    let replacement = sed_backrefs(&replacement);
+    Ok(TitleRename {
+        regex,
+        replacement,
+        global: flags.contains('g'),
+    })
+}
+
+/// `\1` -> `${1}`; other text passes through (so `$1` and `${name}` also work).
+fn sed_backrefs(replacement: &str) -> String {
+    let mut out = String::new();
+    let mut chars = replacement.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\'
+            && let Some(digit) = chars.next_if(|c| c.is_ascii_digit())
+        {
+            out.push_str(&format!("${{{digit}}}"));
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
@@ -0,0 +1,63 @@
+use clap::{Args, ValueEnum};
+use std::path::PathBuf;
+
+#[derive(Args, Debug)]
+#[command(
+    after_help = "EXAMPLES:\n  confcli dump --space MFS > mfs.ndjson\n  confcli dump --space MFS --fields id,title,url,markdownBody\n  confcli dump --space MFS --state mfs.state.json >> mfs.ndjson   # only pages changed since the last run\n  confcli dump --space MFS --since 7d --fields id,lastModified\n"
+)]
+pub struct DumpArgs {
+    #[arg(long, help = "Space key")]
+    pub space: String,
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        help = "Fields of each document, comma-separated (default: all)"
+    )]
+    pub fields: Vec<DumpField>,
+    #[arg(
+        long,
+        help = "Only pages last modified on or after this date: Nd, Nw, or YYYY-MM-DD"
+    )]
+    pub since: Option<String>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Incremental dump: skip pages not modified since the run that wrote FILE, then record this run in it"
+    )]
+    pub state: Option<PathBuf>,
+    #[arg(
+        short = 'o',
+        long,
+        value_enum,
+        default_value_t = DumpFormat::Ndjson,
+        help = "Output format: ndjson (one document per line, streamed) or json (one array)"
+    )]
+    pub output: DumpFormat,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpField {
+    #[value(name = "id")]
+    Id,
+    #[value(name = "title")]
+    Title,
+    #[value(name = "url")]
+    Url,
+    #[value(name = "space")]
+    Space,
+    #[value(name = "parentId")]
+    ParentId,
+    #[value(name = "version")]
+    Version,
+    #[value(name = "lastModified")]
+    LastModified,
+    #[value(name = "markdownBody")]
+    MarkdownBody,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpFormat {
+    Ndjson,
+    Json,
+}
@@ -1,25 +1,146 @@
-use clap::Args;
+use clap::{Args, Subcommand, ValueEnum};
+use confcli::markdown::{CaptionMode, MarkdownFlavor, TableMode};
 use confcli::output::OutputFormat;
+use confcli::schedule::RunWindow;
 use std::path::PathBuf;
 
+use super::common::parse_positive_limit;
+
 #[derive(Args, Debug)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct ExportArgs {
-    #[arg(help = "Page id, URL, or SPACE:Title")]
-    pub page: String,
+    #[command(subcommand)]
+    pub command: Option<ExportCommand>,
+    #[arg(required = true, num_args = 1.., help = "Page id(s), URL(s), or SPACE:Title")]
+    pub pages: Vec<String>,
+    #[arg(short = 'r', long, help = "Also export all descendants of each page")]
+    pub recursive: bool,
     #[arg(long, default_value = ".", help = "Destination directory")]
     pub dest: PathBuf,
     #[arg(long, default_value = "md", help = "Content format: md, storage, adf")]
     pub format: String,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = ExportSite::Folders,
+        help = "Layout: folders (one folder per page) or obsidian (a vault with [[wiki-links]], assets/, and front matter; implies --format md --flavor obsidian)"
+    )]
+    pub site: ExportSite,
     #[arg(long, help = "Only export attachments matching this glob (e.g. *.png)")]
     pub pattern: Option<String>,
+    #[arg(long, help = "Remove emoji and emoticons from markdown content")]
+    pub strip_emoji: bool,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = TableMode::Gfm,
+        help = "Markdown tables: gfm (pipe tables), html, or auto (HTML only where pipe tables lose structure)"
+    )]
+    pub table_mode: TableMode,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = CaptionMode::Title,
+        help = "Image captions: title (markdown image title), line (italic line below), or drop"
+    )]
+    pub captions: CaptionMode,
+    #[arg(long, help = "Render expand macros as collapsible <details> blocks")]
+    pub expand_as_details: bool,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = MarkdownFlavor::Gfm,
+        help = "Markdown flavor: gfm, commonmark, or obsidian (task lists, callouts, wiki-links, line breaks)"
+    )]
+    pub flavor: MarkdownFlavor,
+    #[arg(
+        long,
+        help = "Inline the content of include/excerpt-include macros (md and storage formats)"
+    )]
+    pub resolve_includes: bool,
+    #[arg(
+        long,
+        default_value = "5",
+        value_parser = parse_positive_limit,
+        requires = "resolve_includes",
+        help = "Maximum nesting depth for --resolve-includes"
+    )]
+    pub include_depth: usize,
+    #[arg(
+        long,
+        help = "Commit the exported files in --dest's git repository (message names site, spaces, and time)"
+    )]
+    pub git_commit: bool,
     #[arg(long, help = "Skip downloading attachments")]
     pub skip_attachments: bool,
     #[arg(
         long,
-        default_value = "4",
-        help = "Max concurrent attachment downloads"
+        conflicts_with = "git_commit",
+        help = "Only estimate: count pages, versions, and attachment bytes and the requests an export would make, from metadata"
+    )]
+    pub estimate: bool,
+    #[arg(
+        long,
+        value_name = "PER_MINUTE",
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Start at most this many API requests per minute, evenly spread across all workers"
+    )]
+    pub pace: Option<u32>,
+    #[arg(
+        long,
+        value_name = "HH:MM-HH:MM",
+        help = "Only send requests inside this daily UTC window (e.g. 22:00-06:00); pauses outside it and resumes when it opens"
+    )]
+    pub run_window: Option<RunWindow>,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ExportCommand {
+    #[command(
+        about = "Summarize an export destination and what re-exporting it would change",
+        after_help = "EXAMPLES:\n  confcli export status ./docs\n  confcli export status ./docs --offline -o json\n"
+    )]
+    Status(ExportStatusArgs),
+    #[command(
+        about = "Check an export destination's files against the hashes in its manifest",
+        after_help = "EXAMPLES:\n  confcli export verify ./docs\n  confcli export verify ./backup --live   # also compare attachment sizes with the site\n"
+    )]
+    Verify(ExportVerifyArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ExportStatusArgs {
+    #[arg(help = "Export destination directory")]
+    pub dest: PathBuf,
+    #[arg(
+        long,
+        help = "Only summarize the snapshot; skip comparing it with the live site"
     )]
-    pub concurrency: usize,
+    pub offline: bool,
     #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
     pub output: OutputFormat,
 }
+
+#[derive(Args, Debug)]
+pub struct ExportVerifyArgs {
+    #[arg(help = "Export destination directory")]
+    pub dest: PathBuf,
+    #[arg(
+        long,
+        help = "Also compare downloaded attachments' sizes with the attachments on the site"
+    )]
+    pub live: bool,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+}
+
+/// How exported pages are laid out on disk.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportSite {
+    /// `<Title>--<id>/` folders holding `meta.json`, the content file, and `attachments/`.
+    Folders,
+    /// A flat Obsidian vault: `<Title>.md` notes and `assets/<id>/` attachment folders.
+    Obsidian,
+}
@@ -0,0 +1,41 @@
+use clap::{Args, Subcommand};
+use confcli::output::OutputFormat;
+
+use super::common::parse_positive_limit;
+
+#[derive(Subcommand, Debug)]
+pub enum FavoriteCommand {
+    #[command(about = "List your saved-for-later pages")]
+    List(FavoriteListArgs),
+    #[cfg(feature = "write")]
+    #[command(about = "Save a page for later")]
+    Add(FavoriteArgs),
+    #[cfg(feature = "write")]
+    #[command(about = "Remove a page from your saved-for-later list")]
+    Remove(FavoriteArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct FavoriteListArgs {
+    #[arg(long, help = "Filter by space key")]
+    pub space: Option<String>,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+    #[arg(short = 'a', long, help = "Fetch all pages of results")]
+    pub all: bool,
+    #[arg(
+        short = 'n',
+        long,
+        default_value = "50",
+        value_parser = parse_positive_limit,
+        help = "Maximum number of results"
+    )]
+    pub limit: usize,
+}
+
+#[cfg(feature = "write")]
+#[derive(Args, Debug)]
+pub struct FavoriteArgs {
+    #[arg(help = "Page id, URL, or SPACE:Title")]
+    pub page: String,
+}
@@ -0,0 +1,199 @@
+use clap::{Args, Subcommand, ValueEnum};
+use confcli::output::OutputFormat;
+
+#[derive(Subcommand, Debug)]
+pub enum GenerateCommand {
+    #[cfg(feature = "write")]
+    #[command(
+        about = "Build or refresh an index page listing a space's pages by label",
+        after_help = "EXAMPLES:\n  confcli generate index --space ENG --out \"ENG:Index by label\" --dry-run\n  confcli generate index --space ENG --out \"ENG:Runbooks\" --label runbook --label postmortem\n  confcli generate index --space ENG --out 12345 --include-unlabeled\n\nRe-running updates the page only when the listing changed. An existing page that confcli did not generate is only replaced with --force.\n"
+    )]
+    Index(GenerateIndexArgs),
+    #[command(
+        about = "Compile labeled pages into release notes (markdown, or a page)",
+        after_help = CHANGELOG_AFTER_HELP
+    )]
+    Changelog(GenerateChangelogArgs),
+    #[command(
+        about = "Tabulate content properties across a space's pages (table, CSV, or a page)",
+        after_help = REPORT_AFTER_HELP
+    )]
+    Report(GenerateReportArgs),
+}
+
+#[cfg(feature = "write")]
+const CHANGELOG_AFTER_HELP: &str = "EXAMPLES:\n  confcli generate changelog --label release-note --since 30d\n  confcli generate changelog --label release-note --space ENG --since 2025-01-01 --out CHANGES.md --title \"Release notes\"\n  confcli generate changelog --label release-note --section \"What's new\" --out \"ENG:Release notes\"\n\n--out is a page when it is an id, a URL, or SPACE:Title, and a file otherwise (always for .md files).\n";
+#[cfg(not(feature = "write"))]
+const CHANGELOG_AFTER_HELP: &str = "EXAMPLES:\n  confcli generate changelog --label release-note --since 30d\n  confcli generate changelog --label release-note --space ENG --since 2025-01-01 --out CHANGES.md --title \"Release notes\"\n";
+
+#[cfg(feature = "write")]
+const REPORT_AFTER_HELP: &str = "EXAMPLES:\n  confcli generate report --property team --space ENG\n  confcli generate report --property team --property owner --space ENG --label service --out services.csv\n  confcli generate report --property team --space ENG --out \"ENG:Service owners\" --dry-run\n\n--out is a page when it is an id, a URL, or SPACE:Title, and a CSV file otherwise (- for stdout). Object values get one column per field (team.name).\n";
+#[cfg(not(feature = "write"))]
+const REPORT_AFTER_HELP: &str = "EXAMPLES:\n  confcli generate report --property team --space ENG\n  confcli generate report --property team --property owner --space ENG --label service --out services.csv\n\nObject values get one column per field (team.name).\n";
+
+#[cfg(feature = "write")]
+#[derive(Args, Debug)]
+pub struct GenerateIndexArgs {
+    #[arg(long, help = "Space key or id whose pages are listed")]
+    pub space: String,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = IndexGroupBy::Label,
+        help = "How pages are grouped: label"
+    )]
+    pub group_by: IndexGroupBy,
+    #[arg(
+        long,
+        help = "Index page: id, URL, or SPACE:Title (a SPACE:Title that does not exist is created)"
+    )]
+    pub out: String,
+    #[arg(
+        long,
+        help = "Parent for a newly created index page (id, URL, or SPACE:Title)"
+    )]
+    pub parent: Option<String>,
+    #[arg(
+        long = "label",
+        help = "Only list these labels, in this order (repeatable; default: every label)"
+    )]
+    pub labels: Vec<String>,
+    #[arg(long, help = "Add an Unlabeled section for pages without labels")]
+    pub include_unlabeled: bool,
+    #[arg(long, help = "Replace an existing page that confcli did not generate")]
+    pub force: bool,
+    #[arg(short = 'o', long, help = "Output format: json, table, or markdown")]
+    pub output: Option<OutputFormat>,
+}
+
+/// Grouping of `generate index`.
+#[cfg(feature = "write")]
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexGroupBy {
+    Label,
+}
+
+#[derive(Args, Debug)]
+pub struct GenerateChangelogArgs {
+    #[arg(long, help = "Label the release-note pages and blog posts carry")]
+    pub label: String,
+    #[arg(long, help = "Only content in this space (key or id)")]
+    pub space: Option<String>,
+    #[arg(
+        long,
+        help = "Only content from this date on: 30d, 2w, or YYYY-MM-DD (see --date)"
+    )]
+    pub since: Option<String>,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = ChangelogDate::Created,
+        help = "Date entries are filtered and ordered by (newest first): created or modified"
+    )]
+    pub date: ChangelogDate,
+    #[arg(
+        long,
+        help = "Only the section under this heading of each page; pages without it are skipped"
+    )]
+    pub section: Option<String>,
+    #[arg(long, help = "Heading at the top of markdown output")]
+    pub title: Option<String>,
+    #[arg(
+        long,
+        help = "Markdown file, or (with the write feature) a page: id, URL, or SPACE:Title (created if missing). Default: stdout"
+    )]
+    pub out: Option<String>,
+    #[cfg(feature = "write")]
+    #[arg(
+        long,
+        help = "Parent for a newly created page (id, URL, or SPACE:Title)"
+    )]
+    pub parent: Option<String>,
+    #[cfg(feature = "write")]
+    #[arg(long, help = "Replace an existing page that confcli did not generate")]
+    pub force: bool,
+    #[arg(
+        short = 'o',
+        long,
+        help = "Output format of the result when writing to a file or page: json, table, or markdown"
+    )]
+    pub output: Option<OutputFormat>,
+}
+
+impl GenerateChangelogArgs {
+    /// Whether `--out` names a page rather than a markdown file.
+    pub fn out_is_page(&self) -> bool {
+        out_names_page(self.out.as_deref(), &[".md", ".markdown"])
+    }
+}
+
+/// Whether an `--out` value names a page rather than a file: an id, an http(s) URL, or
+/// `SPACE:Title`. Paths with one of `file_extensions` are always files.
+fn out_names_page(out: Option<&str>, file_extensions: &[&str]) -> bool {
+    let Some(out) = out.map(str::trim) else {
+        return false;
+    };
+    let lower = out.to_ascii_lowercase();
+    if file_extensions.iter().any(|ext| lower.ends_with(ext)) {
+        return false;
+    }
+    if out.chars().all(|c| c.is_ascii_digit())
+        || lower.starts_with("https://")
+        || lower.starts_with("http://")
+    {
+        return true;
+    }
+    out.split_once(':').is_some_and(|(space, title)| {
+        !space.is_empty()
+            && !title.trim().is_empty()
+            && space
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '~' || c == '_')
+    })
+}
+
+/// Date `generate changelog` filters and orders by.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangelogDate {
+    Created,
+    Modified,
+}
+
+#[derive(Args, Debug)]
+pub struct GenerateReportArgs {
+    #[arg(
+        long = "property",
+        required = true,
+        help = "Content property key to report (repeatable; one column each)"
+    )]
+    pub properties: Vec<String>,
+    #[arg(long, help = "Space key or id whose pages are reported")]
+    pub space: String,
+    #[arg(long, help = "Only pages with this label")]
+    pub label: Option<String>,
+    #[arg(long, help = "Also list pages that have none of the properties")]
+    pub include_missing: bool,
+    #[arg(
+        long,
+        help = "CSV file (- for stdout), or (with the write feature) a page: id, URL, or SPACE:Title (created if missing). Default: print the report"
+    )]
+    pub out: Option<String>,
+    #[cfg(feature = "write")]
+    #[arg(
+        long,
+        help = "Parent for a newly created page (id, URL, or SPACE:Title)"
+    )]
+    pub parent: Option<String>,
+    #[cfg(feature = "write")]
+    #[arg(long, help = "Replace an existing page that confcli did not generate")]
+    pub force: bool,
+    #[arg(short = 'o', long, help = "Output format: json, table, or markdown")]
+    pub output: Option<OutputFormat>,
+}
+
+impl GenerateReportArgs {
+    /// Whether `--out` names a page rather than a CSV file.
+    pub fn out_is_page(&self) -> bool {
+        self.out.as_deref() != Some("-") && out_names_page(self.out.as_deref(), &[".csv"])
+    }
+}
@@ -0,0 +1,24 @@
+use clap::{Args, ValueEnum};
+
+#[derive(Args, Debug)]
+#[command(
+    after_help = "EXAMPLES:\n  confcli graph --space MFS | dot -Tsvg > mfs.svg\n  confcli graph --space MFS -o json | jq '.clusters | length'\n"
+)]
+pub struct GraphArgs {
+    #[arg(long, help = "Space key or id")]
+    pub space: String,
+    #[arg(
+        short = 'o',
+        long,
+        value_enum,
+        default_value_t = GraphFormat::Dot,
+        help = "Output format: dot (Graphviz) or json (adjacency lists and clusters)"
+    )]
+    pub output: GraphFormat,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    Dot,
+    Json,
+}
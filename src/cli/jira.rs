@@ -0,0 +1,40 @@
+use clap::{Args, Subcommand};
+use confcli::output::OutputFormat;
+
+#[derive(Subcommand, Debug)]
+pub enum JiraCommand {
+    #[cfg(feature = "write")]
+    #[command(
+        about = "Add a link to a page on Jira issues (shown under the issue's Confluence links)",
+        after_help = "EXAMPLES:\n  confcli jira link MFS:Runbook OPS-123\n  confcli jira link 12345 OPS-123 OPS-124 --dry-run\n\nLinking again updates the existing link (for example after a rename) instead of adding a second one.\n"
+    )]
+    Link(JiraLinkArgs),
+    #[command(
+        about = "List the Jira issues linked to a page with `jira link`",
+        after_help = "EXAMPLES:\n  confcli jira links MFS:Runbook\n  confcli jira links 12345 -o json\n"
+    )]
+    Links(JiraLinksArgs),
+}
+
+#[cfg(feature = "write")]
+#[derive(Args, Debug)]
+pub struct JiraLinkArgs {
+    #[arg(help = "Page id, URL, or SPACE:Title")]
+    pub page: String,
+    #[arg(
+        required = true,
+        value_name = "ISSUE",
+        help = "Jira issue keys, e.g. OPS-123"
+    )]
+    pub issues: Vec<String>,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+pub struct JiraLinksArgs {
+    #[arg(help = "Page id, URL, or SPACE:Title")]
+    pub page: String,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+}
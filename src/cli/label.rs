@@ -13,6 +13,9 @@ pub enum LabelCommand {
     #[cfg(feature = "write")]
     #[command(about = "Remove a label from a page")]
     Remove(LabelRemoveArgs),
+    #[cfg(feature = "write")]
+    #[command(about = "Apply a page's labels to all pages below it")]
+    Propagate(LabelPropagateArgs),
     #[command(about = "List pages with a label")]
     Pages(LabelPagesArgs),
 }
@@ -53,10 +56,30 @@ pub struct LabelRemoveArgs {
     pub labels: Vec<String>,
 }
 
+#[cfg(feature = "write")]
+#[derive(Args, Debug)]
+#[command(
+    after_help = "EXAMPLES:\n  confcli label propagate MFS:Payments --dry-run\n  confcli label propagate MFS:Payments --labels team-payments,pci\n\nPages that already have every label are skipped; labels are only added, never removed.\n"
+)]
+pub struct LabelPropagateArgs {
+    #[arg(help = "Page id, URL, or SPACE:Title whose descendants get the labels")]
+    pub page: String,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Labels to apply (comma-separated); defaults to the page's own labels"
+    )]
+    pub labels: Vec<String>,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+}
+
 #[derive(Args, Debug)]
 pub struct LabelPagesArgs {
     #[arg(help = "Label name")]
     pub label: String,
+    #[arg(long, help = "Add a URL column with each result's web link")]
+    pub show_url: bool,
     #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
     pub output: OutputFormat,
     #[arg(short = 'a', long, help = "Fetch all pages of results")]
@@ -1,27 +1,61 @@
 use clap::{Args, Parser, Subcommand, ValueEnum};
+use confcli::output::{ColorChoice, OutputFormat, TableLayout};
+use std::path::PathBuf;
 
+#[cfg(feature = "write")]
+mod apply;
 mod attachment;
 mod auth;
+mod check_titles;
 mod comment;
 mod common;
 #[cfg(feature = "write")]
 mod copy_tree;
+mod dump;
 mod export;
+mod favorite;
+mod generate;
+mod graph;
+mod jira;
 mod label;
 mod page;
+mod question;
+mod report;
+#[cfg(feature = "write")]
+mod rollback;
 mod search;
+mod sitemap;
 mod space;
+mod template;
+
+use common::parse_positive_limit;
+#[cfg(feature = "write")]
+pub(crate) use common::parse_space_key;
 
+#[cfg(feature = "write")]
+pub use apply::*;
 pub use attachment::*;
 pub use auth::*;
+pub use check_titles::*;
 pub use comment::*;
 #[cfg(feature = "write")]
 pub use copy_tree::*;
+pub use dump::*;
 pub use export::*;
+pub use favorite::*;
+pub use generate::*;
+pub use graph::*;
+pub use jira::*;
 pub use label::*;
 pub use page::*;
+pub use question::*;
+pub use report::*;
+#[cfg(feature = "write")]
+pub use rollback::*;
 pub use search::*;
+pub use sitemap::*;
 pub use space::*;
+pub use template::*;
 
 #[cfg(feature = "write")]
 const CLI_AFTER_HELP: &str = "EXAMPLES:\n  confcli auth login --domain yourcompany.atlassian.net --email you@example.com --token <token>\n  confcli space list --all\n  confcli space pages MFS --tree\n  confcli page get MFS:Overview\n  confcli search \"confluence\"\n  echo '<p>Hello</p>' | confcli page create --space MFS --title Hello --body-file -\n";
@@ -49,6 +83,17 @@ const COMMENT_ABOUT: &str = "List, add, and delete comments";
 #[cfg(not(feature = "write"))]
 const COMMENT_ABOUT: &str = "List comments";
 
+#[cfg(feature = "write")]
+const GENERATE_ABOUT: &str =
+    "Generate index pages, release notes, and property reports from space content";
+#[cfg(not(feature = "write"))]
+const GENERATE_ABOUT: &str = "Generate release notes and property reports from space content";
+
+#[cfg(feature = "write")]
+const FAVORITE_ABOUT: &str = "List, add, and remove saved-for-later pages";
+#[cfg(not(feature = "write"))]
+const FAVORITE_ABOUT: &str = "List saved-for-later pages";
+
 #[derive(Parser, Debug)]
 #[command(
     name = "confcli",
@@ -63,6 +108,118 @@ pub struct Cli {
     pub verbose: u8,
     #[arg(long, global = true, help = "Show what would happen without executing")]
     pub dry_run: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "Print request count, elapsed time, and compressed vs. decompressed bytes to stderr"
+    )]
+    pub timing: bool,
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t = ProgressMode::Bar,
+        help = "Progress display: bar (default), json (NDJSON events on stderr), or none"
+    )]
+    pub progress: ProgressMode,
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t = LogFormat::Text,
+        help = "Diagnostics format on stderr: text or json (one object per line); filter with CONFCLI_LOG or RUST_LOG"
+    )]
+    pub log_format: LogFormat,
+    #[arg(
+        long,
+        global = true,
+        help = "When SPACE:Title has no exact match, search similar titles and pick one"
+    )]
+    pub fuzzy: bool,
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t = ColorChoice::Auto,
+        help = "Color and emphasis: auto (terminal without NO_COLOR), always, or never"
+    )]
+    pub color: ColorChoice,
+    #[arg(
+        long,
+        global = true,
+        help = "Show table cells in full instead of wrapping them to the terminal width"
+    )]
+    pub wide: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "0",
+        conflicts_with = "wide",
+        help = "Cut long table cells with … so rows fit the terminal, or at N characters (--truncate=N)"
+    )]
+    pub truncate: Option<usize>,
+    #[arg(
+        long,
+        global = true,
+        help = "Leave out decorations such as the \"N results\" footer (always printed to stderr)"
+    )]
+    pub no_summary: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "When the command finishes, send a desktop notification (or run the command/webhook from the config's notify section) with the outcome and duration"
+    )]
+    pub notify: bool,
+    #[arg(
+        id = "max_concurrent_requests",
+        long = "concurrency",
+        global = true,
+        value_name = "N",
+        value_parser = parse_positive_limit,
+        help = "Cap API requests in flight at once across all workers (overrides max_concurrent_requests in the config); bulk commands also run N workers instead of their default"
+    )]
+    pub concurrency: Option<usize>,
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        help = "Write results to this file (replaced atomically on success) instead of stdout"
+    )]
+    pub out: Option<String>,
+    #[arg(
+        long,
+        global = true,
+        help = "Send no credentials, for sites with public access (read commands only; the site comes from --profile, CONFLUENCE_BASE_URL, or the active profile)"
+    )]
+    pub anonymous: bool,
+    #[arg(
+        long,
+        global = true,
+        env = "CONFCLI_PROFILE",
+        value_name = "NAME",
+        help = "Use the credentials stored under this profile (see auth login --profile) instead of config.json or the environment"
+    )]
+    pub profile: Option<String>,
+    #[arg(
+        long,
+        global = true,
+        env = "CONFLUENCE_CLIENT_CERT",
+        value_name = "PATH",
+        help = "PEM client certificate for sites behind a mutual-TLS gateway (auth login stores it in the profile)"
+    )]
+    pub client_cert: Option<PathBuf>,
+    #[arg(
+        long,
+        global = true,
+        env = "CONFLUENCE_CLIENT_KEY",
+        value_name = "PATH",
+        requires = "client_cert",
+        help = "PEM private key for --client-cert, unless the certificate file holds it"
+    )]
+    pub client_key: Option<PathBuf>,
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -77,21 +234,243 @@ pub enum Commands {
     Page(PageCommand),
     #[command(about = "Search content (CQL or plain text)")]
     Search(SearchCommand),
+    #[command(about = "Search and open the best match in the browser, or pick from the top hits")]
+    Goto(GotoArgs),
     #[command(subcommand, about = ATTACHMENT_ABOUT)]
     Attachment(AttachmentCommand),
     #[command(subcommand, about = LABEL_ABOUT)]
     Label(LabelCommand),
     #[command(subcommand, about = COMMENT_ABOUT)]
     Comment(CommentCommand),
+    #[command(subcommand, about = FAVORITE_ABOUT)]
+    Favorite(FavoriteCommand),
     #[command(about = "Export a page and its attachments to a folder")]
     Export(ExportArgs),
     #[cfg(feature = "write")]
     #[command(about = "Copy a page tree to a new parent")]
     CopyTree(CopyTreeArgs),
+    #[cfg(feature = "write")]
+    #[command(about = "Trash the pages recorded in a copy-tree journal")]
+    Rollback(RollbackArgs),
+    #[cfg(feature = "write")]
+    #[command(
+        about = "Create or update a page to match a YAML spec (space, parent, title, labels, properties, body)"
+    )]
+    Apply(ApplyArgs),
+    #[command(subcommand, about = "Inspect content templates")]
+    Template(TemplateCommand),
+    #[command(subcommand, about = "Read Confluence Questions and their answers")]
+    Question(QuestionCommand),
+    #[command(subcommand, about = "Link pages to Jira issues")]
+    Jira(JiraCommand),
+    #[command(about = "List pages you recently viewed or edited")]
+    Recent(RecentArgs),
+    #[command(subcommand, about = "Content lifecycle reports")]
+    Report(ReportCommand),
+    #[command(about = "Print the page-to-page link graph of a space")]
+    Graph(GraphArgs),
+    #[command(
+        about = "List the URL and last-modified time of every page, blog post, and attachment"
+    )]
+    Sitemap(SitemapArgs),
+    #[command(
+        about = "Report duplicate page titles and titles that collide once turned into export file names"
+    )]
+    CheckTitles(CheckTitlesArgs),
+    #[command(
+        about = "Stream every page of a space as NDJSON documents with markdown bodies, for embedding and RAG pipelines"
+    )]
+    Dump(DumpArgs),
+    #[command(subcommand, about = GENERATE_ABOUT)]
+    Generate(GenerateCommand),
+    #[command(about = "Resolve a page or space reference to its numeric id")]
+    Resolve(ResolveArgs),
+    #[command(
+        about = "Check the configured site: DNS, TLS, proxy, API bases, credentials, read and write access, clock skew, and rate limits"
+    )]
+    Doctor(DoctorArgs),
+    #[command(about = "Print the JSON Schema of a command's -o json output")]
+    Schema(SchemaArgs),
     #[command(about = "Generate shell completions")]
     Completions(CompletionsArgs),
 }
 
+impl Cli {
+    pub fn table_layout(&self) -> TableLayout {
+        match (self.wide, self.truncate) {
+            (true, _) => TableLayout::Wide,
+            (false, Some(0)) => TableLayout::Truncate(None),
+            (false, Some(n)) => TableLayout::Truncate(Some(n)),
+            (false, None) => TableLayout::Wrap,
+        }
+    }
+}
+
+impl Commands {
+    /// Whether the command has its own `--out`, which then shadows the global one.
+    pub fn owns_out(&self) -> bool {
+        matches!(self, Commands::Generate(_))
+    }
+
+    /// Whether the command changes content on the site, so a read-only profile must refuse it.
+    pub fn is_write(&self) -> bool {
+        match self {
+            #[cfg(feature = "write")]
+            Commands::Space(cmd) => matches!(
+                cmd,
+                SpaceCommand::Create(_)
+                    | SpaceCommand::Delete(_)
+                    | SpaceCommand::Bootstrap(_)
+                    | SpaceCommand::Shortcuts(
+                        SpaceShortcutsCommand::Add(_) | SpaceShortcutsCommand::Remove(_)
+                    )
+            ),
+            #[cfg(feature = "write")]
+            Commands::Page(cmd) => matches!(
+                cmd,
+                PageCommand::Edit(_)
+                    | PageCommand::Create(_)
+                    | PageCommand::Update(_)
+                    | PageCommand::Delete(_)
+                    | PageCommand::Reorder(_)
+                    | PageCommand::Like(_)
+                    | PageCommand::Unlike(_)
+                    | PageCommand::SaveAsTemplate(_)
+                    | PageCommand::Expire(_)
+                    | PageCommand::SetStatus(_)
+                    | PageCommand::Owner(PageOwnerCommand::Set(_))
+                    | PageCommand::Table(PageTableArgs {
+                        command: Some(PageTableCommand::Update(_)),
+                        ..
+                    })
+            ),
+            #[cfg(feature = "write")]
+            Commands::Attachment(cmd) => matches!(
+                cmd,
+                AttachmentCommand::Upload(_) | AttachmentCommand::Delete(_)
+            ),
+            #[cfg(feature = "write")]
+            Commands::Label(cmd) => matches!(
+                cmd,
+                LabelCommand::Add(_) | LabelCommand::Remove(_) | LabelCommand::Propagate(_)
+            ),
+            #[cfg(feature = "write")]
+            Commands::Comment(cmd) => {
+                matches!(cmd, CommentCommand::Add(_) | CommentCommand::Delete(_))
+            }
+            #[cfg(feature = "write")]
+            Commands::Favorite(cmd) => {
+                matches!(cmd, FavoriteCommand::Add(_) | FavoriteCommand::Remove(_))
+            }
+            // A cross-site copy only writes through `--target-profile`, and `rollback` through
+            // the profile of the journal's site; both check that client themselves.
+            #[cfg(feature = "write")]
+            Commands::CopyTree(args) => args.target_profile.is_none(),
+            #[cfg(feature = "write")]
+            Commands::Report(ReportCommand::Expiring(args)) => args.apply,
+            #[cfg(feature = "write")]
+            Commands::Generate(GenerateCommand::Index(_)) => true,
+            #[cfg(feature = "write")]
+            Commands::Jira(JiraCommand::Link(_)) => true,
+            #[cfg(feature = "write")]
+            Commands::Apply(_) => true,
+            Commands::Generate(GenerateCommand::Changelog(args)) => args.out_is_page(),
+            Commands::Generate(GenerateCommand::Report(args)) => args.out_is_page(),
+            _ => false,
+        }
+    }
+
+    /// Whether `--dry-run -o json` prints a plan of the writes instead of the command's output.
+    ///
+    /// Besides [`Commands::is_write`], this covers copy-tree and rollback, which check their
+    /// target profile themselves.
+    pub fn has_plan(&self) -> bool {
+        match self {
+            #[cfg(feature = "write")]
+            Commands::CopyTree(_) | Commands::Rollback(_) => true,
+            _ => self.is_write(),
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+#[command(
+    after_help = "EXAMPLES:\n  confcli doctor\n  confcli doctor -o json | jq '.checks[] | select(.status != \"ok\")'\n\nExits non-zero when a check fails.\n"
+)]
+pub struct DoctorArgs {
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+#[command(
+    after_help = "EXAMPLES:\n  confcli resolve MFS:Overview\n  confcli resolve MFS\n  PAGE=$(confcli resolve \"https://example.atlassian.net/wiki/spaces/MFS/pages/12345/Title\")\n"
+)]
+pub struct ResolveArgs {
+    #[arg(help = "Page id, URL, SPACE:Title, or space key")]
+    pub reference: String,
+    #[arg(
+        long = "type",
+        value_enum,
+        default_value_t = ResolveKind::Auto,
+        help = "What the reference points to (auto: bare keys are spaces, everything else is a page)"
+    )]
+    pub kind: ResolveKind,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json (id + type), table, or markdown (id only)")]
+    pub output: OutputFormat,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressMode {
+    Bar,
+    Json,
+    None,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolveKind {
+    Auto,
+    Page,
+    Space,
+}
+
+/// Content types in a page tree, for `--type` on tree listings.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentType {
+    Page,
+    Folder,
+    Whiteboard,
+    /// Every type, including databases and Smart Links.
+    All,
+}
+
+impl ContentType {
+    /// Whether an item of the API's `type` matches.
+    pub fn matches(self, content_type: &str) -> bool {
+        match self {
+            Self::Page => content_type == "page",
+            Self::Folder => content_type == "folder",
+            Self::Whiteboard => content_type == "whiteboard",
+            Self::All => true,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct SchemaArgs {
+    #[arg(
+        num_args = 0..,
+        help = "Command path, e.g. `page list` (omit to list commands with a schema)"
+    )]
+    pub command: Vec<String>,
+}
+
 #[derive(Args, Debug)]
 pub struct CompletionsArgs {
     #[arg(value_enum, help = "Shell to generate completions for")]
@@ -1,8 +1,11 @@
 use clap::{Args, Subcommand};
+use confcli::markdown::{CaptionMode, MarkdownFlavor, SourceLink, TableMode};
 use confcli::output::OutputFormat;
+use confcli::sanitize::HtmlSanitize;
 #[cfg(feature = "write")]
 use std::path::PathBuf;
 
+use super::ContentType;
 use super::common::parse_positive_limit;
 
 #[derive(Subcommand, Debug)]
@@ -13,6 +16,16 @@ pub enum PageCommand {
     Get(PageGetArgs),
     #[command(about = "Show only the page body (markdown by default)")]
     Body(PageBodyArgs),
+    #[command(
+        about = "Extract tables from a page body as CSV or JSON",
+        after_help = "EXAMPLES:\n  confcli page table MFS:Inventory\n  confcli page table MFS:Inventory --index 2 -o csv > inventory.csv\n  confcli page table 12345 -o json | jq '.[0].rows[] | select(.Status == \"Done\")'\n"
+    )]
+    Table(PageTableArgs),
+    #[command(
+        about = "Count the node types in a page's ADF body and flag unknown or extension nodes",
+        after_help = "EXAMPLES:\n  confcli page adf-stats MFS:Overview\n  confcli page adf-stats 12345 -o json | jq '.unknown'\n"
+    )]
+    AdfStats(PageAdfStatsArgs),
     #[cfg(feature = "write")]
     #[command(about = "Edit a page body in $EDITOR")]
     Edit(PageEditArgs),
@@ -27,10 +40,37 @@ pub enum PageCommand {
     Delete(PageDeleteArgs),
     #[command(about = "List children or descendants of a page")]
     Children(PageChildrenArgs),
+    #[cfg(feature = "write")]
+    #[command(about = "Move a page among its siblings (sidebar order)")]
+    Reorder(PageReorderArgs),
     #[command(about = "Show page version history")]
     History(PageHistoryArgs),
     #[command(about = "Open a page in the browser")]
     Open(PageOpenArgs),
+    #[command(about = "Print a version-pinned URL for citing an exact revision of a page")]
+    Permalink(PagePermalinkArgs),
+    #[command(subcommand, about = "Show or change page ownership")]
+    Owner(PageOwnerCommand),
+    #[cfg(feature = "write")]
+    #[command(about = "Like a page as the current user")]
+    Like(PageLikeArgs),
+    #[cfg(feature = "write")]
+    #[command(about = "Remove the current user's like from a page")]
+    Unlike(PageLikeArgs),
+    #[command(about = "List users who liked a page")]
+    Likes(PageLikesArgs),
+    #[cfg(feature = "write")]
+    #[command(about = "Create a content template from a page's body")]
+    SaveAsTemplate(PageSaveAsTemplateArgs),
+    #[cfg(feature = "write")]
+    #[command(about = "Set or clear a page's expiry date for `report expiring`")]
+    Expire(PageExpireArgs),
+    #[cfg(feature = "write")]
+    #[command(
+        about = "Archive pages or make them current again, by reference or --cql",
+        after_help = "EXAMPLES:\n  confcli page set-status MFS:Old-Plan MFS:Old-Notes --status archived\n  confcli page set-status --cql \"space = MFS AND lastmodified < startOfQuarter()\" --status archived --dry-run\n  confcli page set-status 12345 --status current\n"
+    )]
+    SetStatus(PageSetStatusArgs),
 }
 
 #[derive(Args, Debug)]
@@ -41,6 +81,19 @@ pub struct PageListArgs {
     pub status: Option<String>,
     #[arg(long, help = "Filter by page title")]
     pub title: Option<String>,
+    #[arg(long, help = "Add a URL column with each result's web link")]
+    pub show_url: bool,
+    #[arg(
+        long,
+        help = "Add comment count and last-comment time per page, counting footer and inline comments (two extra lookups per page)"
+    )]
+    pub show_activity: bool,
+    #[arg(
+        long,
+        value_name = "PAGE",
+        help = "Only the given page and its descendants (walks the subtree; fetches every result page)"
+    )]
+    pub under: Option<String>,
     #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
     pub output: OutputFormat,
     #[arg(short = 'a', long, help = "Fetch all pages of results")]
@@ -69,24 +122,212 @@ pub struct PageGetArgs {
     pub version: Option<i64>,
     #[arg(long, help = "Preserve empty list items in markdown output")]
     pub keep_empty_list_items: bool,
+    #[arg(long, help = "Remove emoji and emoticons from markdown output")]
+    pub strip_emoji: bool,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = SourceLink::Comment,
+        help = "How markdown output links back to the page: comment, front-matter, reference, or none"
+    )]
+    pub source_link: SourceLink,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = TableMode::Gfm,
+        help = "Markdown tables: gfm (pipe tables), html, or auto (HTML only where pipe tables lose structure)"
+    )]
+    pub table_mode: TableMode,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = CaptionMode::Title,
+        help = "Image captions: title (markdown image title), line (italic line below), or drop"
+    )]
+    pub captions: CaptionMode,
+    #[arg(long, help = "Render expand macros as collapsible <details> blocks")]
+    pub expand_as_details: bool,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = MarkdownFlavor::Gfm,
+        help = "Markdown flavor: gfm, commonmark, or obsidian (task lists, callouts, wiki-links, line breaks)"
+    )]
+    pub flavor: MarkdownFlavor,
     #[arg(long, help = "Show the page body in table output (can be very large)")]
     pub show_body: bool,
+    #[arg(
+        long,
+        help = "With -o markdown: copy the markdown to the system clipboard instead of printing it"
+    )]
+    pub clipboard: bool,
+    #[arg(
+        long,
+        help = "With -o json: add request-id, rate-limit, and cache-validator response headers under _meta"
+    )]
+    pub include_headers: bool,
     #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: table, json, or markdown")]
     pub output: OutputFormat,
 }
 
+#[derive(Args, Debug)]
+#[cfg_attr(
+    feature = "write",
+    command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)
+)]
+pub struct PageTableArgs {
+    #[cfg(feature = "write")]
+    #[command(subcommand)]
+    pub command: Option<PageTableCommand>,
+    #[arg(required = true, help = "Page id, URL, or SPACE:Title")]
+    pub page: Option<String>,
+    #[arg(
+        long,
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Only the Nth table on the page (1-based); required for csv and table output when the page has several"
+    )]
+    pub index: Option<u32>,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = TableSource::View,
+        help = "Body to read tables from: view (rendered, macros and mentions resolved) or storage"
+    )]
+    pub body_format: TableSource,
+    #[arg(
+        short = 'o',
+        long,
+        value_enum,
+        default_value_t = TableOutput::Table,
+        help = "Output format: table, csv, or json (header-keyed row objects)"
+    )]
+    pub output: TableOutput,
+}
+
+#[cfg(feature = "write")]
+#[derive(Subcommand, Debug)]
+pub enum PageTableCommand {
+    #[command(
+        about = "Replace a table in the page body with rows from a CSV or JSON file",
+        after_help = "EXAMPLES:\n  confcli page table MFS:Inventory --body-format storage --index 2 -o csv > inventory.csv\n  confcli page table update MFS:Inventory --index 2 --from inventory.csv\n  confcli page table MFS:Inventory --body-format storage -o json | jq '.[0] | .rows |= map(.Owner = \"ops\")' | confcli page table update MFS:Inventory --from - --format json\n\nTables are counted in the storage body, so read them with --body-format storage for a round trip. Cells whose text is unchanged keep their original markup (links, mentions, macros, colours); other cells become plain text.\n"
+    )]
+    Update(PageTableUpdateArgs),
+}
+
+#[cfg(feature = "write")]
+#[derive(Args, Debug)]
+pub struct PageTableUpdateArgs {
+    #[arg(help = "Page id, URL, or SPACE:Title")]
+    pub page: String,
+    #[arg(
+        long,
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Table to replace (1-based, in storage order); required when the page has several"
+    )]
+    pub index: Option<u32>,
+    #[arg(
+        long,
+        help = "CSV (header row first) or JSON file with the new rows, or '-' to read from stdin"
+    )]
+    pub from: PathBuf,
+    #[arg(
+        long,
+        value_enum,
+        help = "Format of --from: csv or json (default: from the file extension, else csv)"
+    )]
+    pub format: Option<TableDataFormat>,
+    #[arg(long, help = "Version message")]
+    pub message: Option<String>,
+    #[arg(short = 'o', long, help = "Output format: json, table, or markdown")]
+    pub output: Option<OutputFormat>,
+}
+
+/// Input formats of `page table update`.
+#[cfg(feature = "write")]
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableDataFormat {
+    Csv,
+    Json,
+}
+
+/// Page body `page table` parses.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableSource {
+    View,
+    Storage,
+}
+
+/// Output formats of `page table`.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableOutput {
+    Table,
+    Csv,
+    Json,
+}
+
 #[derive(Args, Debug)]
 pub struct PageBodyArgs {
     #[arg(help = "Page id, URL, or SPACE:Title")]
     pub page: String,
     #[arg(long, help = "Preserve empty list items in markdown output")]
     pub keep_empty_list_items: bool,
+    #[arg(long, help = "Remove emoji and emoticons from markdown output")]
+    pub strip_emoji: bool,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = SourceLink::Comment,
+        help = "How markdown output links back to the page: comment, front-matter, reference, or none"
+    )]
+    pub source_link: SourceLink,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = TableMode::Gfm,
+        help = "Markdown tables: gfm (pipe tables), html, or auto (HTML only where pipe tables lose structure)"
+    )]
+    pub table_mode: TableMode,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = CaptionMode::Title,
+        help = "Image captions: title (markdown image title), line (italic line below), or drop"
+    )]
+    pub captions: CaptionMode,
+    #[arg(long, help = "Render expand macros as collapsible <details> blocks")]
+    pub expand_as_details: bool,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = MarkdownFlavor::Gfm,
+        help = "Markdown flavor: gfm, commonmark, or obsidian (task lists, callouts, wiki-links, line breaks)"
+    )]
+    pub flavor: MarkdownFlavor,
     #[arg(
         long,
         default_value = "markdown",
         help = "Body format: markdown, view, storage, atlas_doc_format, adf"
     )]
     pub format: String,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = HtmlSanitize::None,
+        help = "With --format view: none (HTML as rendered), basic (drop scripts, <style>, event handlers, javascript: URLs), or strict (also style/data-* attributes and iframes/embeds)"
+    )]
+    pub sanitize: HtmlSanitize,
+    #[arg(
+        long,
+        help = "Copy the body to the system clipboard instead of printing it"
+    )]
+    pub clipboard: bool,
+    #[arg(
+        long,
+        env = "CONFCLI_CACHE",
+        value_parser = clap::builder::BoolishValueParser::new(),
+        help = "Keep bodies in a local cache and refetch only when the page version changed (one metadata request per hit)"
+    )]
+    pub cache: bool,
     #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown (json wraps body in a JSON object)")]
     pub output: OutputFormat,
 }
@@ -123,6 +364,19 @@ pub struct PageCreateArgs {
     pub body_file: Option<PathBuf>,
     #[arg(long, help = "Inline body content (for small pages)")]
     pub body: Option<String>,
+    #[arg(
+        long,
+        value_name = "URL",
+        conflicts_with_all = ["body", "body_file", "body_format"],
+        help = "Import an external HTML page: keep its main content and cite the source (title defaults to the page's)"
+    )]
+    pub from_url: Option<String>,
+    #[arg(
+        long,
+        conflicts_with_all = ["body", "body_file", "from_url"],
+        help = "Read the body from the system clipboard"
+    )]
+    pub body_clipboard: bool,
     #[arg(
         long,
         default_value = "storage",
@@ -131,6 +385,12 @@ pub struct PageCreateArgs {
     pub body_format: String,
     #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
     pub output: OutputFormat,
+    #[arg(
+        long,
+        conflicts_with_all = ["output"],
+        help = "Print only the new page id on stdout; other messages go to stderr"
+    )]
+    pub print_id: bool,
 }
 
 #[cfg(feature = "write")]
@@ -156,6 +416,13 @@ pub struct PageUpdateArgs {
     pub body_format: String,
     #[arg(long, help = "Version message")]
     pub message: Option<String>,
+    #[arg(
+        long,
+        help = "Show a diff of the new body against the page's current body and prompt before saving"
+    )]
+    pub diff: bool,
+    #[arg(short = 'y', long, help = "Skip the --diff confirmation prompt")]
+    pub yes: bool,
     #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
     pub output: OutputFormat,
 }
@@ -181,6 +448,13 @@ pub struct PageChildrenArgs {
     pub page: String,
     #[arg(long, help = "List all descendants instead of direct children")]
     pub recursive: bool,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = ContentType::All,
+        help = "Only list content of this type"
+    )]
+    pub r#type: ContentType,
     #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
     pub output: OutputFormat,
     #[arg(short = 'a', long, help = "Fetch all pages of results")]
@@ -195,6 +469,33 @@ pub struct PageChildrenArgs {
     pub limit: usize,
 }
 
+#[cfg(feature = "write")]
+#[derive(Args, Debug)]
+#[command(
+    group = clap::ArgGroup::new("target").required(true).args(["position", "before", "after"]),
+    after_help = "EXAMPLES:\n  confcli page reorder MFS:Changelog --position 1\n  confcli page reorder MFS:Setup --after MFS:Overview\n"
+)]
+pub struct PageReorderArgs {
+    #[arg(help = "Page id, URL, or SPACE:Title")]
+    pub page: String,
+    #[arg(long, value_parser = parse_positive_limit, help = "New position among siblings (1 = first)")]
+    pub position: Option<usize>,
+    #[arg(long, help = "Place the page directly before this sibling")]
+    pub before: Option<String>,
+    #[arg(long, help = "Place the page directly after this sibling")]
+    pub after: Option<String>,
+    #[arg(short = 'o', long, help = "Output format: json, table, or markdown")]
+    pub output: Option<OutputFormat>,
+}
+
+#[derive(Args, Debug)]
+pub struct PageAdfStatsArgs {
+    #[arg(help = "Page id, URL, or SPACE:Title")]
+    pub page: String,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+}
+
 #[derive(Args, Debug)]
 pub struct PageHistoryArgs {
     #[arg(help = "Page id, URL, or SPACE:Title")]
@@ -206,9 +507,18 @@ pub struct PageHistoryArgs {
         long,
         default_value = "25",
         value_parser = parse_positive_limit,
-        help = "Number of versions to show"
+        help = "Number of versions to show (per request with --all)"
     )]
     pub limit: usize,
+    #[arg(short = 'a', long, help = "Fetch every version, following pagination")]
+    pub all: bool,
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = parse_positive_limit,
+        help = "Stop after this many versions (implies --all)"
+    )]
+    pub max_results: Option<usize>,
 }
 
 #[derive(Args, Debug)]
@@ -216,3 +526,170 @@ pub struct PageOpenArgs {
     #[arg(help = "Page id, URL, or SPACE:Title")]
     pub page: String,
 }
+
+#[derive(Args, Debug)]
+#[command(
+    after_help = "EXAMPLES:\n  confcli page permalink MFS:Runbook\n  confcli page permalink MFS:Runbook --version 7 --markdown\n  confcli page permalink 12345 -o json\n"
+)]
+pub struct PagePermalinkArgs {
+    #[arg(help = "Page id, URL, or SPACE:Title")]
+    pub page: String,
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Version to pin (default: the current version)"
+    )]
+    pub version: Option<i64>,
+    #[arg(
+        long,
+        help = "Print a markdown link, `[Title (v7)](url)`, instead of the bare URL"
+    )]
+    pub markdown: bool,
+    #[arg(
+        short = 'o',
+        long,
+        help = "Output format: json adds id, title, and version"
+    )]
+    pub output: Option<OutputFormat>,
+}
+
+#[cfg(feature = "write")]
+#[derive(Args, Debug)]
+pub struct PageLikeArgs {
+    #[arg(help = "Page id, URL, or SPACE:Title")]
+    pub page: String,
+    #[arg(short = 'o', long, help = "Output format: json, table, or markdown")]
+    pub output: Option<OutputFormat>,
+}
+
+#[cfg(feature = "write")]
+#[derive(Args, Debug)]
+#[command(
+    after_help = "EXAMPLES:\n  confcli page save-as-template MFS:Runbook --name \"Runbook\"\n  confcli page save-as-template 12345 --name \"Meeting notes\" --global\n"
+)]
+pub struct PageSaveAsTemplateArgs {
+    #[arg(help = "Page id, URL, or SPACE:Title")]
+    pub page: String,
+    #[arg(long, help = "Template name")]
+    pub name: String,
+    #[arg(long, help = "Template description")]
+    pub description: Option<String>,
+    #[arg(
+        long,
+        help = "Create a global template instead of one in the page's space"
+    )]
+    pub global: bool,
+    #[arg(short = 'o', long, help = "Output format: json, table, or markdown")]
+    pub output: Option<OutputFormat>,
+}
+
+#[cfg(feature = "write")]
+#[derive(Args, Debug)]
+#[command(
+    after_help = "The date and action are stored in the page's `confcli-expiry` content property, and the page gets a `confcli-expiry` label so reports can find it.\n\nEXAMPLES:\n  confcli page expire MFS:Runbook --on 2025-12-31 --action label:stale\n  confcli page expire 12345 --clear\n"
+)]
+pub struct PageExpireArgs {
+    #[arg(help = "Page id, URL, or SPACE:Title")]
+    pub page: String,
+    #[arg(
+        long,
+        value_name = "YYYY-MM-DD",
+        required_unless_present = "clear",
+        help = "Date the page expires"
+    )]
+    pub on: Option<String>,
+    #[arg(
+        long,
+        default_value = "none",
+        help = "Action for `report expiring --apply` once expired: label:<name> or none"
+    )]
+    pub action: String,
+    #[arg(long, conflicts_with_all = ["on", "action"], help = "Remove the page's expiry")]
+    pub clear: bool,
+    #[arg(short = 'o', long, help = "Output format: json, table, or markdown")]
+    pub output: Option<OutputFormat>,
+}
+
+#[cfg(feature = "write")]
+#[derive(Args, Debug)]
+pub struct PageSetStatusArgs {
+    #[arg(
+        value_name = "PAGE",
+        required_unless_present = "cql",
+        conflicts_with = "cql",
+        help = "Page id(s), URL(s), or SPACE:Title"
+    )]
+    pub pages: Vec<String>,
+    #[arg(long, help = "Change every page matched by this CQL query")]
+    pub cql: Option<String>,
+    #[arg(long, value_enum, help = "New status")]
+    pub status: PageStatus,
+    #[arg(short = 'y', long, help = "Skip confirmation prompt")]
+    pub yes: bool,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+}
+
+/// Status `page set-status` moves pages to.
+#[cfg(feature = "write")]
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageStatus {
+    Current,
+    Archived,
+}
+
+#[derive(Args, Debug)]
+pub struct PageLikesArgs {
+    #[arg(help = "Page id, URL, or SPACE:Title")]
+    pub page: String,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+    #[arg(short = 'a', long, help = "Fetch all pages of results")]
+    pub all: bool,
+    #[arg(
+        short = 'n',
+        long,
+        default_value = "50",
+        value_parser = parse_positive_limit,
+        help = "Maximum number of results"
+    )]
+    pub limit: usize,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PageOwnerCommand {
+    #[command(about = "Show the owner of a page")]
+    Get(PageOwnerGetArgs),
+    #[cfg(feature = "write")]
+    #[command(
+        about = "Change the owner of a page (or of every page matched by --cql)",
+        after_help = "EXAMPLES:\n  confcli page owner set MFS:Overview 5b10ac8d82e05b22cc7d4ef5\n  confcli page owner set --cql \"space = MFS AND creator = 5b10a2844c20165700ede21g\" 5b10ac8d82e05b22cc7d4ef5\n"
+    )]
+    Set(PageOwnerSetArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct PageOwnerGetArgs {
+    #[arg(help = "Page id, URL, or SPACE:Title")]
+    pub page: String,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+}
+
+#[cfg(feature = "write")]
+#[derive(Args, Debug)]
+pub struct PageOwnerSetArgs {
+    #[arg(
+        value_name = "PAGE",
+        help = "Page id, URL, or SPACE:Title (omit when using --cql)"
+    )]
+    pub page: Option<String>,
+    #[arg(value_name = "USER", help = "New owner account id, or 'me'")]
+    pub user: Option<String>,
+    #[arg(long, help = "Reassign every page matched by this CQL query")]
+    pub cql: Option<String>,
+    #[arg(short = 'y', long, help = "Skip confirmation prompt")]
+    pub yes: bool,
+    #[arg(short = 'o', long, help = "Output format: json, table, or markdown")]
+    pub output: Option<OutputFormat>,
+}
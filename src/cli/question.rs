@@ -0,0 +1,46 @@
+use clap::{Args, Subcommand};
+use confcli::output::OutputFormat;
+
+use super::common::parse_positive_limit;
+
+#[derive(Subcommand, Debug)]
+pub enum QuestionCommand {
+    #[command(
+        about = "List Confluence Questions (needs the Questions add-on)",
+        after_help = "EXAMPLES:\n  confcli question list\n  confcli question list --space ENG --all -o json\n"
+    )]
+    List(QuestionListArgs),
+    #[command(
+        about = "Show a question with its answers",
+        after_help = "EXAMPLES:\n  confcli question get 123456\n  confcli question get 123456 -o json\n"
+    )]
+    Get(QuestionGetArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct QuestionListArgs {
+    #[arg(long, help = "Filter by space key")]
+    pub space: Option<String>,
+    #[arg(long, help = "Only questions with this topic (label)")]
+    pub topic: Option<String>,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+    #[arg(short = 'a', long, help = "Fetch all pages of results")]
+    pub all: bool,
+    #[arg(
+        short = 'n',
+        long,
+        default_value = "50",
+        value_parser = parse_positive_limit,
+        help = "Maximum number of results"
+    )]
+    pub limit: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct QuestionGetArgs {
+    #[arg(help = "Question id")]
+    pub id: String,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+}
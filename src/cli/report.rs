@@ -0,0 +1,64 @@
+use clap::{Args, Subcommand};
+use confcli::output::OutputFormat;
+
+use super::common::parse_ratio;
+
+#[derive(Subcommand, Debug)]
+pub enum ReportCommand {
+    #[command(
+        about = "List pages whose expiry (set with `page expire`) has passed or is coming up",
+        after_help = "EXAMPLES:\n  confcli report expiring\n  confcli report expiring --space MFS --within 30\n  confcli report expiring --apply   # e.g. nightly from cron\n"
+    )]
+    Expiring(ReportExpiringArgs),
+    #[command(
+        about = "Find pages of a space with near-identical content or titles",
+        after_help = "EXAMPLES:\n  confcli report duplicates --space MFS\n  confcli report duplicates --space MFS --threshold 0.6 -o json\n"
+    )]
+    Duplicates(ReportDuplicatesArgs),
+    #[command(
+        about = "Find attachment references in page bodies whose file no longer exists",
+        after_help = "EXAMPLES:\n  confcli report missing-attachments --space MFS\n  confcli report missing-attachments --space MFS -o json\n"
+    )]
+    MissingAttachments(ReportMissingAttachmentsArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ReportExpiringArgs {
+    #[arg(long, help = "Filter by space key")]
+    pub space: Option<String>,
+    #[arg(
+        long,
+        value_name = "DAYS",
+        default_value = "0",
+        help = "Also include pages expiring within this many days"
+    )]
+    pub within: u32,
+    #[cfg(feature = "write")]
+    #[arg(long, help = "Run each expired page's action (e.g. add its label)")]
+    pub apply: bool,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+pub struct ReportDuplicatesArgs {
+    #[arg(long, help = "Space key or id")]
+    pub space: String,
+    #[arg(
+        long,
+        default_value = "0.8",
+        value_parser = parse_ratio,
+        help = "Minimum similarity score (0-1) for a pair to be reported"
+    )]
+    pub threshold: f64,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+pub struct ReportMissingAttachmentsArgs {
+    #[arg(long, help = "Space key or id")]
+    pub space: String,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+}
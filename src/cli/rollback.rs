@@ -0,0 +1,16 @@
+use clap::Args;
+use confcli::output::OutputFormat;
+use std::path::PathBuf;
+
+#[derive(Args, Debug)]
+#[command(
+    after_help = "EXAMPLES:\n  confcli copy-tree MFS:Overview OPS:Archive --journal copy.jsonl\n  confcli rollback copy.jsonl\n  confcli rollback copy.jsonl --yes -o json\n"
+)]
+pub struct RollbackArgs {
+    #[arg(help = "Journal written by `copy-tree --journal`")]
+    pub journal: PathBuf,
+    #[arg(short = 'y', long, help = "Skip confirmation prompt")]
+    pub yes: bool,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+}
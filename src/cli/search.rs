@@ -6,17 +6,24 @@ use super::common::parse_positive_limit;
 #[derive(Args, Debug)]
 #[command(
     about = "Search content (CQL or plain text)",
-    after_help = "EXAMPLES:\n  confcli search \"confluence\"\n  confcli search \"type=page AND title ~ \\\"Template\\\"\"\n"
+    after_help = "EXAMPLES:\n  confcli search \"confluence\"\n  confcli search \"type=page AND title ~ \\\"Template\\\"\"\n  confcli search --all-profiles \"onboarding\"\n"
 )]
 pub struct SearchCommand {
     #[arg(help = "Search query. If no CQL operators are detected, defaults to text ~ \"query\"")]
     pub query: String,
     #[arg(long, help = "Filter by space key")]
     pub space: Option<String>,
+    #[arg(long, help = "Add a URL column with each result's web link")]
+    pub show_url: bool,
     #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
     pub output: OutputFormat,
     #[arg(short = 'a', long, help = "Fetch all pages of results")]
     pub all: bool,
+    #[arg(
+        long,
+        help = "Search every stored profile (site) concurrently and merge results with a Site column"
+    )]
+    pub all_profiles: bool,
     #[arg(
         short = 'n',
         long,
@@ -26,3 +33,44 @@ pub struct SearchCommand {
     )]
     pub limit: usize,
 }
+
+#[derive(Args, Debug)]
+#[command(
+    after_help = "EXAMPLES:\n  confcli goto \"Incident runbook\"\n  confcli goto onboarding --space MFS\n  confcli goto \"deploy checklist\" --dry-run\n"
+)]
+pub struct GotoArgs {
+    #[arg(help = "Search query (plain text or CQL, as for `search`)")]
+    pub query: String,
+    #[arg(long, help = "Filter by space key")]
+    pub space: Option<String>,
+    #[arg(
+        short = 'n',
+        long,
+        default_value = "10",
+        value_parser = parse_positive_limit,
+        help = "Number of hits to pick from"
+    )]
+    pub limit: usize,
+}
+
+#[derive(Args, Debug)]
+#[command(after_help = "EXAMPLES:\n  confcli recent\n  confcli recent --mine --space MFS -n 10\n")]
+pub struct RecentArgs {
+    #[arg(
+        long,
+        help = "Pages you recently edited instead of pages you recently viewed"
+    )]
+    pub mine: bool,
+    #[arg(long, help = "Filter by space key")]
+    pub space: Option<String>,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+    #[arg(
+        short = 'n',
+        long,
+        default_value = "20",
+        value_parser = parse_positive_limit,
+        help = "Maximum number of results"
+    )]
+    pub limit: usize,
+}
@@ -0,0 +1,24 @@
+use clap::{Args, ValueEnum};
+
+#[derive(Args, Debug)]
+#[command(
+    after_help = "EXAMPLES:\n  confcli sitemap --space MFS > sitemap.xml\n  confcli sitemap -o json | jq -r '.[].url'\n"
+)]
+pub struct SitemapArgs {
+    #[arg(long, help = "Space key (default: every space you can see)")]
+    pub space: Option<String>,
+    #[arg(
+        short = 'o',
+        long,
+        value_enum,
+        default_value_t = SitemapFormat::Xml,
+        help = "Output format: xml (sitemaps.org urlset) or json"
+    )]
+    pub output: SitemapFormat,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SitemapFormat {
+    Xml,
+    Json,
+}
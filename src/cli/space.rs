@@ -1,6 +1,7 @@
 use clap::{Args, Subcommand};
 use confcli::output::OutputFormat;
 
+use super::ContentType;
 use super::common::parse_positive_limit;
 #[cfg(feature = "write")]
 use super::common::parse_space_key;
@@ -19,6 +20,66 @@ pub enum SpaceCommand {
     #[cfg(feature = "write")]
     #[command(about = "Delete a space")]
     Delete(SpaceDeleteArgs),
+    #[cfg(feature = "write")]
+    #[command(about = "Create a space, its pages, labels, and permissions from a YAML manifest")]
+    Bootstrap(SpaceBootstrapArgs),
+    #[command(subcommand, about = SHORTCUTS_ABOUT)]
+    Shortcuts(SpaceShortcutsCommand),
+}
+
+#[cfg(feature = "write")]
+const SHORTCUTS_ABOUT: &str = "List, add, or remove the shortcuts in a space's sidebar";
+#[cfg(not(feature = "write"))]
+const SHORTCUTS_ABOUT: &str = "List the shortcuts in a space's sidebar";
+
+#[derive(Subcommand, Debug)]
+pub enum SpaceShortcutsCommand {
+    #[command(about = "List a space's sidebar shortcuts")]
+    List(SpaceShortcutsListArgs),
+    #[cfg(feature = "write")]
+    #[command(
+        about = "Add a sidebar shortcut (skipped if the space already links the same URL or page)",
+        after_help = "EXAMPLES:\n  confcli space shortcuts add ENG --url https://status.example.com --title \"Status\"\n  confcli space shortcuts add ENG --page ENG:Onboarding\n  for s in ENG OPS QA; do confcli space shortcuts add \"$s\" --url https://handbook.example.com --title Handbook; done\n"
+    )]
+    Add(SpaceShortcutsAddArgs),
+    #[cfg(feature = "write")]
+    #[command(about = "Remove a sidebar shortcut by id, title, or URL")]
+    Remove(SpaceShortcutsRemoveArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct SpaceShortcutsListArgs {
+    #[arg(help = "Space key or id")]
+    pub space: String,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+}
+
+#[cfg(feature = "write")]
+#[derive(Args, Debug)]
+#[command(group = clap::ArgGroup::new("target").required(true).args(["url", "page"]))]
+pub struct SpaceShortcutsAddArgs {
+    #[arg(help = "Space key or id")]
+    pub space: String,
+    #[arg(long, help = "Link to this URL")]
+    pub url: Option<String>,
+    #[arg(long, help = "Link to this page (id, URL, or SPACE:Title)")]
+    pub page: Option<String>,
+    #[arg(long, help = "Shortcut title (defaults to the page title or the URL)")]
+    pub title: Option<String>,
+    #[arg(short = 'o', long, help = "Output format: json, table, or markdown")]
+    pub output: Option<OutputFormat>,
+}
+
+#[cfg(feature = "write")]
+#[derive(Args, Debug)]
+pub struct SpaceShortcutsRemoveArgs {
+    #[arg(help = "Space key or id")]
+    pub space: String,
+    #[arg(help = "Shortcut id, title, or URL")]
+    pub shortcut: String,
+    #[arg(short = 'o', long, help = "Output format: json, table, or markdown")]
+    pub output: Option<OutputFormat>,
 }
 
 #[derive(Args, Debug)]
@@ -59,12 +120,32 @@ pub struct SpacePagesArgs {
     pub space: String,
     #[arg(long, default_value = "all", help = "Depth filter: all or root")]
     pub depth: String,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = ContentType::Page,
+        help = "Content type to list; types other than page walk the page tree (fetches every result page)"
+    )]
+    pub r#type: ContentType,
     #[arg(long, help = "Render a tree view")]
     pub tree: bool,
     #[arg(long, help = "Filter by page status")]
     pub status: Option<String>,
     #[arg(long, help = "Filter by page title")]
     pub title: Option<String>,
+    #[arg(long, help = "Add a URL column with each result's web link")]
+    pub show_url: bool,
+    #[arg(
+        long,
+        help = "Add comment count and last-comment time per page, counting footer and inline comments (two extra lookups per page; with --tree, JSON output only)"
+    )]
+    pub show_activity: bool,
+    #[arg(
+        long,
+        value_name = "PAGE",
+        help = "Only the given page and its descendants (walks the subtree; fetches every result page)"
+    )]
+    pub under: Option<String>,
     #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
     pub output: OutputFormat,
     #[arg(short = 'a', long, help = "Fetch all pages of results")]
@@ -95,6 +176,12 @@ pub struct SpaceCreateArgs {
     pub compact_json: bool,
     #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
     pub output: OutputFormat,
+    #[arg(
+        long,
+        conflicts_with_all = ["output", "compact_json"],
+        help = "Print only the new space id on stdout; other messages go to stderr"
+    )]
+    pub print_id: bool,
 }
 
 #[cfg(feature = "write")]
@@ -107,3 +194,15 @@ pub struct SpaceDeleteArgs {
     #[arg(short = 'o', long, help = "Output format: json, table, or markdown")]
     pub output: Option<OutputFormat>,
 }
+
+#[cfg(feature = "write")]
+#[derive(Args, Debug)]
+#[command(
+    after_help = "Re-running a manifest only creates what is missing.\n\nEXAMPLES:\n  confcli space bootstrap space.yaml --dry-run\n  confcli space bootstrap space.yaml -o json\n"
+)]
+pub struct SpaceBootstrapArgs {
+    #[arg(help = "Path to the YAML manifest")]
+    pub manifest: std::path::PathBuf,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown")]
+    pub output: OutputFormat,
+}
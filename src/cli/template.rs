@@ -0,0 +1,25 @@
+use clap::{Args, Subcommand};
+use confcli::output::OutputFormat;
+
+#[derive(Subcommand, Debug)]
+pub enum TemplateCommand {
+    #[command(about = "Show a content template body")]
+    Get(TemplateGetArgs),
+}
+
+#[derive(Args, Debug)]
+#[command(
+    after_help = "EXAMPLES:\n  confcli template get 123456\n  confcli template get 123456 --format storage > template.xml\n"
+)]
+pub struct TemplateGetArgs {
+    #[arg(help = "Content template id")]
+    pub id: String,
+    #[arg(
+        long,
+        default_value = "markdown",
+        help = "Body format: markdown or storage"
+    )]
+    pub format: String,
+    #[arg(short = 'o', long, default_value_t = OutputFormat::Table, help = "Output format: json, table, or markdown (json wraps body in a JSON object)")]
+    pub output: OutputFormat,
+}
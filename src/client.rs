@@ -1,22 +1,258 @@
 use crate::auth::AuthMethod;
+use crate::config::{ATLASSIAN_GATEWAY, ATLASSIAN_JIRA_GATEWAY, gateway_cloud_id};
 use crate::pagination::{next_link_from_body, next_link_from_headers};
+use crate::schedule::{RunWindow, Schedule};
 use anyhow::{Context, Result, anyhow, bail};
 use base64::Engine;
-use reqwest::header::HeaderMap;
+use reqwest::header::{ACCEPT_ENCODING, CONTENT_ENCODING, COOKIE, HeaderMap, SET_COOKIE};
 #[cfg(feature = "write")]
 use reqwest::{Body, multipart};
 use reqwest::{Client as HttpClient, Method, Response};
 use serde_json::Value;
-#[cfg(feature = "write")]
-use std::path::Path;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::Mutex as StdMutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::Duration;
+use tokio::sync::{Semaphore, SemaphorePermit};
 #[cfg(feature = "write")]
 use tokio_util::io::ReaderStream;
 use url::Url;
 
 const MAX_ATTEMPTS: u32 = 3;
 const API_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
-const USER_AGENT: &str = concat!("confcli/", env!("CARGO_PKG_VERSION"));
+/// `User-Agent` sent unless a profile configures its own (see [`ApiClient::with_user_agent`]).
+pub const DEFAULT_USER_AGENT: &str = concat!("confcli/", env!("CARGO_PKG_VERSION"));
+/// Upper bound of the per-task delay added when resuming after a shared rate-limit pause.
+const BACKOFF_STAGGER: Duration = Duration::from_millis(500);
+
+/// A TLS client certificate, for Data Center sites behind gateways that terminate mutual TLS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientCert {
+    /// Certificate chain and private key, PEM-encoded.
+    pem: Vec<u8>,
+}
+
+impl ClientCert {
+    /// Read a PEM certificate and its private key, which may sit in the certificate file
+    /// itself when `key` is `None`.
+    pub fn from_files(cert: &Path, key: Option<&Path>) -> Result<Self> {
+        let mut pem = std::fs::read(cert)
+            .with_context(|| format!("Failed to read client certificate {}", cert.display()))?;
+        if let Some(key) = key {
+            pem.push(b'\n');
+            pem.extend(
+                std::fs::read(key)
+                    .with_context(|| format!("Failed to read client key {}", key.display()))?,
+            );
+        }
+        let client_cert = Self { pem };
+        client_cert.identity().with_context(|| {
+            format!(
+                "{} does not hold a PEM certificate and private key{}",
+                cert.display(),
+                if key.is_some() {
+                    " (with --client-key)"
+                } else {
+                    ""
+                }
+            )
+        })?;
+        Ok(client_cert)
+    }
+
+    fn identity(&self) -> Result<reqwest::Identity> {
+        Ok(reqwest::Identity::from_pem(&self.pem)?)
+    }
+}
+
+/// Connection-pool tuning for the underlying HTTP client.
+///
+/// Bulk commands (export, copy-tree, label remove, ...) issue many requests against one host,
+/// so idle connections are kept warm to avoid repeated TLS handshakes. HTTP/2 is negotiated via
+/// ALPN when the server supports it, multiplexing concurrent requests over one connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolSettings {
+    /// Idle connections kept per host (`CONFCLI_POOL_MAX_IDLE_PER_HOST`).
+    pub max_idle_per_host: usize,
+    /// How long an idle connection is kept (`CONFCLI_POOL_IDLE_TIMEOUT`, seconds).
+    pub idle_timeout: Duration,
+    /// TCP and HTTP/2 keep-alive interval (`CONFCLI_KEEPALIVE`, seconds; 0 disables).
+    pub keep_alive: Option<Duration>,
+}
+
+impl Default for PoolSettings {
+    fn default() -> Self {
+        Self {
+            max_idle_per_host: 16,
+            idle_timeout: Duration::from_secs(90),
+            keep_alive: Some(Duration::from_secs(30)),
+        }
+    }
+}
+
+impl PoolSettings {
+    pub fn from_env() -> Result<Self> {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let parse = |key: &str| -> Result<Option<u64>> {
+            lookup(key)
+                .map(|raw| {
+                    raw.trim()
+                        .parse::<u64>()
+                        .with_context(|| format!("{key} must be a non-negative integer"))
+                })
+                .transpose()
+        };
+        let mut settings = Self::default();
+        if let Some(n) = parse("CONFCLI_POOL_MAX_IDLE_PER_HOST")? {
+            settings.max_idle_per_host = n as usize;
+        }
+        if let Some(secs) = parse("CONFCLI_POOL_IDLE_TIMEOUT")? {
+            settings.idle_timeout = Duration::from_secs(secs);
+        }
+        if let Some(secs) = parse("CONFCLI_KEEPALIVE")? {
+            settings.keep_alive = (secs > 0).then(|| Duration::from_secs(secs));
+        }
+        Ok(settings)
+    }
+}
+
+/// Process-wide transfer counters, reported by `--timing`.
+static REQUESTS: AtomicU64 = AtomicU64::new(0);
+static WIRE_BYTES: AtomicU64 = AtomicU64::new(0);
+static BODY_BYTES: AtomicU64 = AtomicU64::new(0);
+static RETRIES: AtomicU64 = AtomicU64::new(0);
+static RATE_LIMITED: AtomicU64 = AtomicU64::new(0);
+static BACKOFF_MICROS: AtomicU64 = AtomicU64::new(0);
+/// The backoff warning is printed once per process.
+static BACKOFF_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Total backoff after which a warning is logged, unless `CONFCLI_BACKOFF_WARN` says otherwise.
+pub const DEFAULT_BACKOFF_WARNING: Duration = Duration::from_secs(60);
+
+/// Snapshot of the HTTP traffic made by every [`ApiClient`] in this process.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransferStats {
+    /// Requests sent, including retries.
+    pub requests: u64,
+    /// JSON response bytes as received (compressed when the server compressed them).
+    pub wire_bytes: u64,
+    /// The same responses after decompression.
+    pub body_bytes: u64,
+    /// Requests repeated after a 429, a server error, or a request error.
+    pub retries: u64,
+    /// Retries caused by a 429.
+    pub rate_limited: u64,
+    /// Time spent waiting before retries, summed over concurrent tasks.
+    pub backoff: Duration,
+}
+
+pub fn transfer_stats() -> TransferStats {
+    TransferStats {
+        requests: REQUESTS.load(Ordering::Relaxed),
+        wire_bytes: WIRE_BYTES.load(Ordering::Relaxed),
+        body_bytes: BODY_BYTES.load(Ordering::Relaxed),
+        retries: RETRIES.load(Ordering::Relaxed),
+        rate_limited: RATE_LIMITED.load(Ordering::Relaxed),
+        backoff: Duration::from_micros(BACKOFF_MICROS.load(Ordering::Relaxed)),
+    }
+}
+
+/// The backoff warning threshold from `CONFCLI_BACKOFF_WARN` (seconds; 0 disables).
+pub fn backoff_warning_from_env() -> Result<Option<Duration>> {
+    backoff_warning_from_lookup(|key| std::env::var(key).ok())
+}
+
+fn backoff_warning_from_lookup(
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<Option<Duration>> {
+    let Some(raw) = lookup("CONFCLI_BACKOFF_WARN") else {
+        return Ok(Some(DEFAULT_BACKOFF_WARNING));
+    };
+    let secs = raw
+        .trim()
+        .parse::<u64>()
+        .context("CONFCLI_BACKOFF_WARN must be a non-negative integer")?;
+    Ok((secs > 0).then(|| Duration::from_secs(secs)))
+}
+
+/// Count one retry that waited `waited`, warning once when the total crosses `threshold`.
+fn record_retry(rate_limited: bool, waited: Duration, threshold: Option<Duration>) {
+    RETRIES.fetch_add(1, Ordering::Relaxed);
+    if rate_limited {
+        RATE_LIMITED.fetch_add(1, Ordering::Relaxed);
+    }
+    let waited = u64::try_from(waited.as_micros()).unwrap_or(u64::MAX);
+    let total = BACKOFF_MICROS.fetch_add(waited, Ordering::Relaxed) + waited;
+    let total = Duration::from_micros(total);
+    if threshold.is_some_and(|threshold| total >= threshold)
+        && !BACKOFF_WARNED.swap(true, Ordering::Relaxed)
+    {
+        let stats = transfer_stats();
+        tracing::warn!(
+            retries = stats.retries,
+            rate_limited = stats.rate_limited,
+            backoff_ms = total.as_millis() as u64,
+            "Spent {total:.0?} backing off before retries; consider lowering --concurrency or spreading out runs"
+        );
+    }
+}
+
+/// Rate-limit pause shared by every clone of an [`ApiClient`].
+///
+/// When one request receives a 429, all concurrent tasks (export, upload, copy-tree, ...) hold
+/// off until the server's Retry-After has elapsed, then resume with a jittered stagger instead
+/// of retrying in lockstep.
+#[derive(Debug, Default)]
+struct RateLimitBackoff {
+    until: StdMutex<Option<tokio::time::Instant>>,
+}
+
+impl RateLimitBackoff {
+    /// Pause everyone for at least `wait` from now (never shortens an existing pause).
+    fn extend(&self, wait: Duration) {
+        let candidate = tokio::time::Instant::now() + wait;
+        let mut until = self.until.lock().unwrap_or_else(|e| e.into_inner());
+        if until.is_none_or(|current| current < candidate) {
+            *until = Some(candidate);
+        }
+    }
+
+    fn remaining(&self) -> Option<Duration> {
+        let until = *self.until.lock().unwrap_or_else(|e| e.into_inner());
+        until
+            .map(|until| until.saturating_duration_since(tokio::time::Instant::now()))
+            .filter(|d| !d.is_zero())
+    }
+
+    /// Sleep until the shared pause (if any) is over, plus a per-task stagger.
+    async fn wait(&self) {
+        // Loop: another task may extend the pause while we sleep.
+        let mut waited = false;
+        while let Some(remaining) = self.remaining() {
+            tokio::time::sleep(remaining).await;
+            waited = true;
+        }
+        if waited {
+            tokio::time::sleep(jitter(BACKOFF_STAGGER)).await;
+        }
+    }
+}
+
+/// The session cookie of [`AuthMethod::Cookie`], shared by every clone of a client; logins
+/// take turns so concurrent requests log in once.
+#[derive(Debug, Default)]
+struct CookieSession {
+    /// `name=value`, as sent in the `Cookie` header.
+    cookie: StdMutex<Option<String>>,
+    login: tokio::sync::Mutex<()>,
+    /// Where the cookie is kept between runs (see [`ApiClient::with_session_file`]).
+    file: Option<PathBuf>,
+}
 
 #[derive(Debug, Clone)]
 pub struct ApiClient {
@@ -28,9 +264,28 @@ pub struct ApiClient {
     api_base_v2: String,
     /// Scheme+host(+port), used for absolute URLs from pagination/link headers.
     origin: String,
+    /// The site URL's path on the api.atlassian.com gateway (e.g.
+    /// `https://api.atlassian.com/ex/confluence/<cloudId>/wiki`) when the API bases go
+    /// through it; site-relative links in API responses resolve against it instead.
+    gateway_site: Option<String>,
     auth: AuthMethod,
     http: HttpClient,
     verbose: u8,
+    pool: PoolSettings,
+    user_agent: String,
+    client_cert: Option<ClientCert>,
+    /// Decompress JSON responses ourselves so compressed sizes can be reported.
+    timing: bool,
+    /// Set from a profile's `read_only` flag; write methods fail before sending anything.
+    read_only: bool,
+    backoff: Arc<RateLimitBackoff>,
+    /// Total backoff that triggers a warning; `None` never warns.
+    backoff_warning: Option<Duration>,
+    /// Ceiling on requests in flight across every clone of this client; `None` is unlimited.
+    slots: Option<Arc<Semaphore>>,
+    /// `--pace` and `--run-window`, shared by every clone of this client.
+    schedule: Arc<Schedule>,
+    session: Arc<CookieSession>,
 }
 
 impl ApiClient {
@@ -45,21 +300,161 @@ impl ApiClient {
         let api_base_v1 = api_base_v1.trim_end_matches('/').to_string();
         let api_base_v2 = api_base_v2.trim_end_matches('/').to_string();
         let origin = origin_from_url(&site_url)?;
-        let http = HttpClient::builder()
-            .user_agent(USER_AGENT)
-            .connect_timeout(Duration::from_secs(10))
-            .build()?;
+        let gateway_site = gateway_cloud_id(&api_base_v1).map(|cloud_id| {
+            format!(
+                "{ATLASSIAN_GATEWAY}{cloud_id}{}",
+                site_path(&site_url).unwrap_or_default()
+            )
+        });
+        let pool = PoolSettings::default();
+        let http = build_http(&pool, true, DEFAULT_USER_AGENT, None)?;
         Ok(Self {
             site_url,
             api_base_v1,
             api_base_v2,
             origin,
+            gateway_site,
             auth,
             http,
             verbose,
+            pool,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            client_cert: None,
+            timing: false,
+            read_only: false,
+            backoff: Arc::new(RateLimitBackoff::default()),
+            backoff_warning: Some(DEFAULT_BACKOFF_WARNING),
+            slots: None,
+            schedule: Arc::new(Schedule::default()),
+            session: Arc::new(CookieSession::default()),
         })
     }
 
+    /// Measure compressed vs. decompressed JSON response sizes (see [`transfer_stats`]).
+    ///
+    /// reqwest strips `Content-Encoding` when it decompresses transparently, so with timing
+    /// enabled the client asks for gzip/deflate itself and decodes JSON bodies by hand.
+    pub fn with_timing(mut self, enabled: bool) -> Result<Self> {
+        if enabled != self.timing {
+            self.http = build_http(
+                &self.pool,
+                !enabled,
+                &self.user_agent,
+                self.client_cert.as_ref(),
+            )?;
+            self.timing = enabled;
+        }
+        Ok(self)
+    }
+
+    /// Warn once the time spent backing off reaches `threshold` (`None` never warns).
+    pub fn with_backoff_warning(mut self, threshold: Option<Duration>) -> Self {
+        self.backoff_warning = threshold;
+        self
+    }
+
+    /// Allow at most `limit` requests in flight at once, shared by every clone of this client
+    /// (`None` or `0` is unlimited).
+    ///
+    /// Bulk commands size their worker pools independently; this caps the combined load they
+    /// put on the site, whatever their own `--concurrency`.
+    pub fn with_max_concurrent_requests(mut self, limit: Option<usize>) -> Self {
+        self.slots = limit
+            .filter(|limit| *limit > 0)
+            .map(|limit| Arc::new(Semaphore::new(limit)));
+        self
+    }
+
+    /// Start at most `pace` requests per minute, evenly spread, and hold requests while the
+    /// clock is outside `window`, across every clone of this client (`None` for no limit).
+    ///
+    /// For long unattended jobs that must stay under org-level throttles or off-hours.
+    pub fn with_schedule(mut self, pace: Option<u32>, window: Option<RunWindow>) -> Self {
+        self.schedule = Arc::new(Schedule::new(pace, window));
+        self
+    }
+
+    /// Wait for a free request slot (see [`ApiClient::with_max_concurrent_requests`]).
+    ///
+    /// Requests made through `ApiClient` do this automatically; callers that send requests
+    /// via [`ApiClient::http`] directly should hold the permit until the response is consumed.
+    pub async fn request_slot(&self) -> Option<SemaphorePermit<'_>> {
+        match &self.slots {
+            Some(slots) => Some(
+                slots
+                    .acquire()
+                    .await
+                    .expect("request slots are never closed"),
+            ),
+            None => None,
+        }
+    }
+
+    /// Refuse all writes through this client (see [`ApiClient::ensure_writable`]).
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Fail with a clear message when the client belongs to a read-only profile.
+    pub fn ensure_writable(&self) -> Result<()> {
+        if self.read_only {
+            return Err(anyhow::anyhow!(
+                "This profile is read-only (\"read_only\": true in its config); write commands are disabled"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Replace the connection-pool settings (see [`PoolSettings::from_env`]).
+    pub fn with_pool_settings(mut self, pool: PoolSettings) -> Result<Self> {
+        if pool != self.pool {
+            self.http = build_http(
+                &pool,
+                !self.timing,
+                &self.user_agent,
+                self.client_cert.as_ref(),
+            )?;
+            self.pool = pool;
+        }
+        Ok(self)
+    }
+
+    /// Send `user_agent` as the `User-Agent` of every request.
+    pub fn with_user_agent(mut self, user_agent: String) -> Result<Self> {
+        if user_agent != self.user_agent {
+            reqwest::header::HeaderValue::from_str(&user_agent)
+                .with_context(|| format!("Invalid User-Agent '{user_agent}'"))?;
+            self.http = build_http(
+                &self.pool,
+                !self.timing,
+                &user_agent,
+                self.client_cert.as_ref(),
+            )?;
+            self.user_agent = user_agent;
+        }
+        Ok(self)
+    }
+
+    /// Present `client_cert` in TLS handshakes (`None` presents none).
+    pub fn with_client_cert(mut self, client_cert: Option<ClientCert>) -> Result<Self> {
+        if client_cert != self.client_cert {
+            self.http = build_http(
+                &self.pool,
+                !self.timing,
+                &self.user_agent,
+                client_cert.as_ref(),
+            )?;
+            self.client_cert = client_cert;
+        }
+        Ok(self)
+    }
+
+    /// The effective `User-Agent`.
+    pub fn user_agent(&self) -> &str {
+        &self.user_agent
+    }
+
     pub fn base_url(&self) -> &str {
         &self.site_url
     }
@@ -68,6 +463,36 @@ impl ApiClient {
         &self.origin
     }
 
+    /// Base for site-relative links in API responses, such as attachment downloads: the site
+    /// URL, or its gateway equivalent when the API is routed through api.atlassian.com. Web
+    /// links keep using [`ApiClient::base_url`].
+    pub fn api_site_url(&self) -> &str {
+        self.gateway_site.as_deref().unwrap_or(&self.site_url)
+    }
+
+    /// A Jira REST API v3 URL on the same site: `{origin}/rest/api/3{path}`, or its gateway
+    /// equivalent when the API is routed through api.atlassian.com (gateway tokens are not
+    /// accepted by the site itself).
+    pub fn jira_api_url(&self, path: &str) -> String {
+        match gateway_cloud_id(&self.api_base_v1) {
+            Some(cloud_id) => format!("{ATLASSIAN_JIRA_GATEWAY}{cloud_id}/rest/api/3{path}"),
+            None => format!("{}/rest/api/3{path}", self.origin.trim_end_matches('/')),
+        }
+    }
+
+    /// Through the gateway, responses still link relative to the site (`/wiki/api/v2/...`),
+    /// which would resolve against api.atlassian.com itself.
+    fn resolve_next_page(&self, current_url: &str, next: &str) -> Result<String> {
+        match &self.gateway_site {
+            Some(gateway_site) if next.starts_with('/') => {
+                let path = site_path(&self.site_url).unwrap_or_default();
+                let next = next.strip_prefix(path.as_str()).unwrap_or(next);
+                Ok(format!("{gateway_site}{next}"))
+            }
+            _ => resolve_next_page_url(current_url, next),
+        }
+    }
+
     pub fn http(&self) -> &HttpClient {
         &self.http
     }
@@ -80,6 +505,8 @@ impl ApiClient {
         format!("{}{}", self.api_base_v1, path)
     }
 
+    /// Add the credentials to a request. With cookie auth this is the current session, so
+    /// callers sending requests via [`ApiClient::http`] call [`ApiClient::ensure_session`] first.
     pub fn apply_auth(&self, builder: reqwest::RequestBuilder) -> Result<reqwest::RequestBuilder> {
         match &self.auth {
             AuthMethod::Basic { email, token } => {
@@ -87,10 +514,103 @@ impl ApiClient {
                 let encoded = base64::engine::general_purpose::STANDARD.encode(raw);
                 Ok(builder.header("Authorization", format!("Basic {encoded}")))
             }
-            AuthMethod::Bearer { token } => {
+            AuthMethod::Bearer { token } | AuthMethod::Pat { token } => {
                 Ok(builder.header("Authorization", format!("Bearer {token}")))
             }
+            AuthMethod::Cookie { .. } => Ok(match self.session_cookie() {
+                Some(cookie) => builder.header(COOKIE, cookie),
+                None => builder,
+            }),
+            AuthMethod::Anonymous => Ok(builder),
+        }
+    }
+
+    /// The session cookie sent with cookie auth, once logged in.
+    pub fn session_cookie(&self) -> Option<String> {
+        self.session
+            .cookie
+            .lock()
+            .expect("session lock poisoned")
+            .clone()
+    }
+
+    /// Keep the cookie-auth session in `path` between runs: start from the cookie saved there
+    /// and save each new one, so a run only logs in once the saved session has expired.
+    pub fn with_session_file(mut self, path: PathBuf) -> Self {
+        let cookie = std::fs::read_to_string(&path)
+            .ok()
+            .map(|cookie| cookie.trim().to_string())
+            .filter(|cookie| !cookie.is_empty());
+        self.session = Arc::new(CookieSession {
+            cookie: StdMutex::new(cookie),
+            login: tokio::sync::Mutex::default(),
+            file: Some(path),
+        });
+        self
+    }
+
+    /// Log in for cookie auth unless a session exists (nothing to do for other auth).
+    pub async fn ensure_session(&self) -> Result<()> {
+        if matches!(self.auth, AuthMethod::Cookie { .. }) && self.session_cookie().is_none() {
+            self.renew_session(None).await?;
         }
+        Ok(())
+    }
+
+    /// Log in again after a 401 to a request sent with session cookie `stale`, unless another
+    /// request already did. `false` without cookie auth, where a 401 is final.
+    pub async fn renew_session(&self, stale: Option<&str>) -> Result<bool> {
+        let AuthMethod::Cookie { username, password } = &self.auth else {
+            return Ok(false);
+        };
+        let _login = self.session.login.lock().await;
+        if self.session_cookie().as_deref() != stale {
+            return Ok(true);
+        }
+        let url = format!("{}/rest/auth/1/session", self.site_url);
+        tracing::debug!(%url, "session login");
+        REQUESTS.fetch_add(1, Ordering::Relaxed);
+        let response = self
+            .http
+            .post(&url)
+            .timeout(API_REQUEST_TIMEOUT)
+            .json(&serde_json::json!({ "username": username, "password": password }))
+            .send()
+            .await
+            .with_context(|| format!("Failed to log in at {url}"))?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            bail!(
+                "Failed to log in at {url}: {}",
+                friendly_error(status, &body)
+            );
+        }
+        let cookie = session_cookie_from(&headers, &body)
+            .with_context(|| format!("The login at {url} returned no session cookie"))?;
+        if let Some(path) = &self.session.file
+            && let Err(err) = save_session_file(path, &cookie)
+        {
+            tracing::warn!("Failed to save the session to {}: {err:#}", path.display());
+        }
+        *self.session.cookie.lock().expect("session lock poisoned") = Some(cookie);
+        Ok(true)
+    }
+
+    /// Wait for this request's turn (see [`ApiClient::with_schedule`]), then wait out a shared
+    /// rate-limit pause started by any clone of this client.
+    ///
+    /// Requests made through `ApiClient` do this automatically; callers that send requests
+    /// via [`ApiClient::http`] directly (e.g. downloads) should call it before each attempt.
+    pub async fn wait_for_rate_limit(&self) {
+        self.schedule.wait().await;
+        self.backoff.wait().await;
+    }
+
+    /// Record a 429 so that every clone of this client pauses for `wait`.
+    pub fn note_rate_limited(&self, wait: Duration) {
+        self.backoff.extend(wait);
     }
 
     /// Parse a Retry-After header value (integer seconds), falling back to
@@ -105,20 +625,27 @@ impl ApiClient {
         Duration::from_secs(2u64.pow(attempt - 1)) + jitter(Duration::from_millis(250))
     }
 
-    async fn send_impl<F>(&self, method: Method, url: String, mut configure: F) -> Result<Response>
+    /// Send with retries. The request slot comes back with the response: keep it until the
+    /// body is read, so downloading a large body counts against the request ceiling too.
+    async fn send_impl<F>(
+        &self,
+        method: Method,
+        url: String,
+        mut configure: F,
+    ) -> Result<(Response, Option<SemaphorePermit<'_>>)>
     where
         F: FnMut(reqwest::RequestBuilder) -> reqwest::RequestBuilder,
     {
         let mut attempts = 0;
+        let mut renewed = false;
 
         loop {
-            if self.verbose > 0 {
-                if attempts > 0 {
-                    eprintln!("{} {} (retry {})", method, url, attempts);
-                } else {
-                    eprintln!("{} {}", method, url);
-                }
-            }
+            tracing::debug!(%method, %url, retry = attempts, "request");
+
+            self.ensure_session().await?;
+            self.wait_for_rate_limit().await;
+            let slot = self.request_slot().await;
+            let session = self.session_cookie();
 
             let start = std::time::Instant::now();
             let builder = self
@@ -128,27 +655,35 @@ impl ApiClient {
             let builder = configure(builder);
             let builder = self.apply_auth(builder)?;
 
+            REQUESTS.fetch_add(1, Ordering::Relaxed);
             match builder.send().await {
                 Ok(response) => {
-                    if self.verbose > 1 {
-                        eprintln!("<- {} ({:?})", response.status(), start.elapsed());
-                        if let Some(id) = request_id(response.headers()) {
-                            eprintln!("<- request-id: {id}");
-                        }
-                    }
+                    tracing::trace!(
+                        status = %response.status(),
+                        elapsed = ?start.elapsed(),
+                        request_id = request_id(response.headers()),
+                        "response"
+                    );
 
                     if response.status().is_success() {
-                        return Ok(response);
+                        return Ok((response, slot));
                     }
 
                     let status = response.status();
+                    if status == reqwest::StatusCode::UNAUTHORIZED
+                        && !renewed
+                        && self.renew_session(session.as_deref()).await?
+                    {
+                        tracing::debug!("session expired; logged in again");
+                        renewed = true;
+                        continue;
+                    }
                     if attempts < MAX_ATTEMPTS && (status == 429 || status.is_server_error()) {
                         attempts += 1;
                         let wait = Self::retry_wait_from_headers(response.headers(), attempts);
-                        if self.verbose > 0 {
-                            eprintln!("Received {}, retrying in {:?}...", status, wait);
-                        }
-                        tokio::time::sleep(wait).await;
+                        tracing::debug!(%status, ?wait, "retrying request");
+                        drop(slot);
+                        self.back_off(Some(status), wait).await;
                         continue;
                     }
 
@@ -164,10 +699,9 @@ impl ApiClient {
                         attempts += 1;
                         // No response headers on request errors; still use the same backoff+jitter.
                         let wait = Self::retry_wait_from_headers(&HeaderMap::new(), attempts);
-                        if self.verbose > 0 {
-                            eprintln!("Request error: {}, retrying in {:?}...", e, wait);
-                        }
-                        tokio::time::sleep(wait).await;
+                        tracing::debug!(error = %e, ?wait, "retrying request");
+                        drop(slot);
+                        self.back_off(None, wait).await;
                         continue;
                     }
                     return Err(e.into());
@@ -176,7 +710,26 @@ impl ApiClient {
         }
     }
 
-    async fn send(&self, method: Method, url: String) -> Result<Response> {
+    /// Wait before retrying after `status` (`None` for a request error) and count the retry
+    /// in [`transfer_stats`]. 429s pause every task sharing this client; other retryable
+    /// errors only delay this one.
+    pub async fn back_off(&self, status: Option<reqwest::StatusCode>, wait: Duration) {
+        let start = tokio::time::Instant::now();
+        let rate_limited = status == Some(reqwest::StatusCode::TOO_MANY_REQUESTS);
+        if rate_limited {
+            self.backoff.extend(wait);
+            self.backoff.wait().await;
+        } else {
+            tokio::time::sleep(wait).await;
+        }
+        record_retry(rate_limited, start.elapsed(), self.backoff_warning);
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        url: String,
+    ) -> Result<(Response, Option<SemaphorePermit<'_>>)> {
         self.send_impl(method, url, |b| b).await
     }
 
@@ -187,19 +740,41 @@ impl ApiClient {
         method: Method,
         url: String,
         body: &Value,
-    ) -> Result<Response> {
+    ) -> Result<(Response, Option<SemaphorePermit<'_>>)> {
         self.send_impl(method, url, |b| b.json(body)).await
     }
 
     pub async fn get_json(&self, url: String) -> Result<(Value, HeaderMap)> {
-        let response = self.send(Method::GET, url).await?;
+        let (response, _slot) = if self.timing {
+            self.send_impl(Method::GET, url, |b| {
+                b.header(ACCEPT_ENCODING, "gzip, deflate")
+            })
+            .await?
+        } else {
+            self.send(Method::GET, url).await?
+        };
         let headers = response.headers().clone();
-        let json = response.json::<Value>().await?;
+        let raw = response.bytes().await?;
+        let body = decode_body(&headers, &raw)?;
+        WIRE_BYTES.fetch_add(raw.len() as u64, Ordering::Relaxed);
+        BODY_BYTES.fetch_add(body.len() as u64, Ordering::Relaxed);
+        let json = serde_json::from_slice::<Value>(&body).context("Invalid JSON response")?;
         Ok((json, headers))
     }
 
     pub async fn get_paginated_results(&self, url: String, all: bool) -> Result<Vec<Value>> {
-        self.get_paginated_results_with_limit(url, all, 10_000)
+        self.get_paginated_results_with_limit(url, all, 10_000, None)
+            .await
+    }
+
+    /// Follow `next` links like `get_paginated_results(url, true)`, but stop once
+    /// `max_results` items have been collected (the result is truncated to that many).
+    pub async fn get_paginated_results_up_to(
+        &self,
+        url: String,
+        max_results: usize,
+    ) -> Result<Vec<Value>> {
+        self.get_paginated_results_with_limit(url, true, 10_000, Some(max_results))
             .await
     }
 
@@ -208,6 +783,7 @@ impl ApiClient {
         url: String,
         all: bool,
         max_pages: usize,
+        max_results: Option<usize>,
     ) -> Result<Vec<Value>> {
         let mut results = Vec::new();
         let mut next_url: Option<String> = Some(url);
@@ -232,34 +808,79 @@ impl ApiClient {
                 bail!("Unexpected response shape: missing results array");
             }
 
+            if let Some(max) = max_results
+                && results.len() >= max
+            {
+                results.truncate(max);
+                break;
+            }
             if !all {
                 break;
             }
 
             let next = next_link_from_headers(&headers).or_else(|| next_link_from_body(&json));
             next_url = match next {
-                Some(next) => Some(resolve_next_page_url(&url, &next)?),
+                Some(next) => Some(self.resolve_next_page(&url, &next)?),
                 None => None,
             };
         }
         Ok(results)
     }
 
+    /// Render a storage-format body to view HTML through `/contentbody/convert/view`, with
+    /// macros resolved in the context of page `page_id`.
+    ///
+    /// A POST that changes nothing, so it is available in read-only builds and profiles; the
+    /// binary crate calls it, which is why it can't be `pub(crate)`.
+    pub async fn convert_storage_to_view(&self, page_id: &str, storage: &str) -> Result<String> {
+        let mut url = Url::parse(&self.v1_url("/contentbody/convert/view"))
+            .context("Invalid API base URL")?;
+        url.query_pairs_mut()
+            .append_pair("contentIdContext", page_id);
+        let body = serde_json::json!({ "value": storage, "representation": "storage" });
+        let (response, _slot) = self
+            .send_impl(Method::POST, url.to_string(), |b| b.json(&body))
+            .await?;
+        let json = response.json::<Value>().await?;
+        Ok(crate::json_util::json_str(&json, "value"))
+    }
+
     #[cfg(feature = "write")]
     pub async fn post_json(&self, url: String, body: Value) -> Result<Value> {
-        let response = self.send_with_json_body(Method::POST, url, &body).await?;
+        self.ensure_writable()?;
+        let (response, _slot) = self.send_with_json_body(Method::POST, url, &body).await?;
         Ok(response.json::<Value>().await?)
     }
 
     #[cfg(feature = "write")]
     pub async fn put_json(&self, url: String, body: Value) -> Result<Value> {
-        let response = self.send_with_json_body(Method::PUT, url, &body).await?;
+        self.ensure_writable()?;
+        let (response, _slot) = self.send_with_json_body(Method::PUT, url, &body).await?;
         Ok(response.json::<Value>().await?)
     }
 
+    /// POST a JSON body to an endpoint whose response body is irrelevant (or empty).
+    #[cfg(feature = "write")]
+    pub async fn post_json_no_content(&self, url: String, body: Value) -> Result<()> {
+        self.ensure_writable()?;
+        let (response, _slot) = self.send_with_json_body(Method::POST, url, &body).await?;
+        drop(response);
+        Ok(())
+    }
+
+    /// PUT a JSON body to an endpoint that answers with `204 No Content`.
+    #[cfg(feature = "write")]
+    pub async fn put_json_no_content(&self, url: String, body: Value) -> Result<()> {
+        self.ensure_writable()?;
+        let (response, _slot) = self.send_with_json_body(Method::PUT, url, &body).await?;
+        drop(response);
+        Ok(())
+    }
+
     #[cfg(feature = "write")]
     pub async fn delete(&self, url: String) -> Result<()> {
-        let response = self.send(Method::DELETE, url).await?;
+        self.ensure_writable()?;
+        let (response, _slot) = self.send(Method::DELETE, url).await?;
         drop(response);
         Ok(())
     }
@@ -274,23 +895,56 @@ impl ApiClient {
         page_id: &str,
         file_path: &Path,
         comment: Option<String>,
+    ) -> Result<Value> {
+        let file_name = attachment_file_name(file_path)?;
+        self.upload_attachment_as(page_id, file_path, &file_name, comment)
+            .await
+    }
+
+    /// Upload an attachment under `file_name` instead of the file's own name.
+    #[cfg(feature = "write")]
+    pub async fn upload_attachment_as(
+        &self,
+        page_id: &str,
+        file_path: &Path,
+        file_name: &str,
+        comment: Option<String>,
     ) -> Result<Value> {
         let url = self.v1_url(&format!("/content/{}/child/attachment", page_id));
-        let file_name = file_path
-            .file_name()
-            .and_then(|v| v.to_str())
-            .context("Invalid file name")?
-            .to_string();
+        self.send_attachment(url, file_path, file_name, comment)
+            .await
+    }
 
+    /// Upload a file as a new version of an existing attachment.
+    #[cfg(feature = "write")]
+    pub async fn update_attachment_data(
+        &self,
+        page_id: &str,
+        attachment_id: &str,
+        file_path: &Path,
+        comment: Option<String>,
+    ) -> Result<Value> {
+        let url = self.v1_url(&format!(
+            "/content/{page_id}/child/attachment/{attachment_id}/data"
+        ));
+        let file_name = attachment_file_name(file_path)?;
+        self.send_attachment(url, file_path, &file_name, comment)
+            .await
+    }
+
+    #[cfg(feature = "write")]
+    async fn send_attachment(
+        &self,
+        url: String,
+        file_path: &Path,
+        file_name: &str,
+        comment: Option<String>,
+    ) -> Result<Value> {
+        self.ensure_writable()?;
         let mut attempts = 0;
+        let mut renewed = false;
         loop {
-            if self.verbose > 0 {
-                if attempts > 0 {
-                    eprintln!("POST {} (upload retry {})", url, attempts);
-                } else {
-                    eprintln!("POST {} (upload)", url);
-                }
-            }
+            tracing::debug!(method = "POST", %url, retry = attempts, "upload");
 
             let file = tokio::fs::File::open(file_path)
                 .await
@@ -300,13 +954,19 @@ impl ApiClient {
 
             let stream = ReaderStream::new(file);
             let body = Body::wrap_stream(stream);
-            let part = multipart::Part::stream_with_length(body, size).file_name(file_name.clone());
+            let part =
+                multipart::Part::stream_with_length(body, size).file_name(file_name.to_string());
 
             let mut form = multipart::Form::new().part("file", part);
             if let Some(comment) = comment.clone() {
                 form = form.text("comment", comment);
             }
 
+            self.ensure_session().await?;
+            self.wait_for_rate_limit().await;
+            let slot = self.request_slot().await;
+            let session = self.session_cookie();
+
             let builder = self
                 .http
                 .post(url.clone())
@@ -321,13 +981,19 @@ impl ApiClient {
                     }
 
                     let status = response.status();
+                    if status == reqwest::StatusCode::UNAUTHORIZED
+                        && !renewed
+                        && self.renew_session(session.as_deref()).await?
+                    {
+                        renewed = true;
+                        continue;
+                    }
                     if attempts < MAX_ATTEMPTS && (status == 429 || status.is_server_error()) {
                         attempts += 1;
                         let wait = Self::retry_wait_from_headers(response.headers(), attempts);
-                        if self.verbose > 0 {
-                            eprintln!("Upload received {}, retrying in {:?}...", status, wait);
-                        }
-                        tokio::time::sleep(wait).await;
+                        tracing::debug!(%status, ?wait, "retrying upload");
+                        drop(slot);
+                        self.back_off(Some(status), wait).await;
                         continue;
                     }
 
@@ -344,10 +1010,9 @@ impl ApiClient {
                     if attempts < MAX_ATTEMPTS {
                         attempts += 1;
                         let wait = Self::retry_wait_from_headers(&HeaderMap::new(), attempts);
-                        if self.verbose > 0 {
-                            eprintln!("Upload request error: {}, retrying in {:?}...", e, wait);
-                        }
-                        tokio::time::sleep(wait).await;
+                        tracing::debug!(error = %e, ?wait, "retrying upload");
+                        drop(slot);
+                        self.back_off(None, wait).await;
                         continue;
                     }
                     return Err(e.into());
@@ -357,6 +1022,58 @@ impl ApiClient {
     }
 }
 
+#[cfg(feature = "write")]
+fn attachment_file_name(file_path: &Path) -> Result<String> {
+    file_path
+        .file_name()
+        .and_then(|v| v.to_str())
+        .map(str::to_string)
+        .context("Invalid file name")
+}
+
+/// `name=value` of the session a `rest/auth/1/session` login returned: from its JSON body
+/// (`{"session": {"name": "JSESSIONID", "value": "..."}}`), else its first `Set-Cookie`.
+fn session_cookie_from(headers: &HeaderMap, body: &str) -> Option<String> {
+    let from_body = serde_json::from_str::<Value>(body).ok().and_then(|json| {
+        let session = json.get("session")?;
+        Some(format!(
+            "{}={}",
+            session.get("name")?.as_str()?,
+            session.get("value")?.as_str()?
+        ))
+    });
+    from_body.or_else(|| {
+        let set_cookie = headers.get(SET_COOKIE)?.to_str().ok()?;
+        let pair = set_cookie.split(';').next()?.trim();
+        pair.contains('=').then(|| pair.to_string())
+    })
+}
+
+/// Write a session cookie readable by the owner only; it grants the same access as the
+/// password.
+fn save_session_file(path: &Path, cookie: &str) -> Result<()> {
+    use std::io::Write;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(cookie.as_bytes())?;
+    Ok(())
+}
+
+/// The path of a site URL without a trailing slash, e.g. `/wiki`.
+fn site_path(site_url: &str) -> Option<String> {
+    let url = Url::parse(site_url).ok()?;
+    Some(url.path().trim_end_matches('/').to_string())
+}
+
 fn resolve_next_page_url(current_url: &str, next: &str) -> Result<String> {
     if let Ok(abs) = Url::parse(next) {
         return Ok(abs.to_string());
@@ -381,11 +1098,84 @@ fn origin_from_url(site_url: &str) -> Result<String> {
     Ok(format!("{}://{}{}", url.scheme(), host, port))
 }
 
+fn build_http(
+    pool: &PoolSettings,
+    auto_decompress: bool,
+    user_agent: &str,
+    client_cert: Option<&ClientCert>,
+) -> Result<HttpClient> {
+    let builder = HttpClient::builder()
+        .user_agent(user_agent)
+        .connect_timeout(Duration::from_secs(10))
+        .pool_max_idle_per_host(pool.max_idle_per_host)
+        .pool_idle_timeout(pool.idle_timeout)
+        .tcp_keepalive(pool.keep_alive)
+        .http2_adaptive_window(true);
+    let builder = match pool.keep_alive {
+        Some(interval) => builder
+            .http2_keep_alive_interval(interval)
+            .http2_keep_alive_while_idle(true),
+        None => builder,
+    };
+    let builder = if auto_decompress {
+        builder
+    } else {
+        builder.no_gzip().no_deflate().no_brotli()
+    };
+    let builder = match client_cert {
+        Some(client_cert) => builder.identity(client_cert.identity()?),
+        None => builder,
+    };
+    Ok(builder.build()?)
+}
+
+/// Decode a response body according to its `Content-Encoding` (already-decoded bodies pass
+/// through, since reqwest removes the header when it decompresses).
+fn decode_body(headers: &HeaderMap, raw: &[u8]) -> Result<Vec<u8>> {
+    let encoding = headers
+        .get(CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("identity")
+        .trim()
+        .to_ascii_lowercase();
+    let mut out = Vec::new();
+    match encoding.as_str() {
+        "" | "identity" => return Ok(raw.to_vec()),
+        "gzip" | "x-gzip" => {
+            flate2::read::GzDecoder::new(raw)
+                .read_to_end(&mut out)
+                .context("Failed to decompress gzip response")?;
+        }
+        "deflate" => {
+            // "deflate" is zlib-wrapped per RFC 9110, but some servers send raw deflate.
+            if flate2::read::ZlibDecoder::new(raw)
+                .read_to_end(&mut out)
+                .is_err()
+            {
+                out.clear();
+                flate2::read::DeflateDecoder::new(raw)
+                    .read_to_end(&mut out)
+                    .context("Failed to decompress deflate response")?;
+            }
+        }
+        other => bail!("Unsupported response Content-Encoding: {other}"),
+    }
+    Ok(out)
+}
+
+static JITTER_COUNTER: AtomicU64 = AtomicU64::new(0);
+
 fn jitter(max: Duration) -> Duration {
+    // Mix in a counter so tasks asking at the same instant still get different delays.
     let nanos = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
         .subsec_nanos() as u64;
+    let nanos = nanos.wrapping_add(
+        JITTER_COUNTER
+            .fetch_add(1, Ordering::Relaxed)
+            .wrapping_mul(0x9E37_79B9_7F4A_7C15),
+    );
     let max_ms = max.as_millis() as u64;
     if max_ms == 0 {
         return Duration::from_millis(0);
@@ -538,6 +1328,126 @@ mod tests {
         assert!(d2 >= Duration::from_secs(2) && d2 < Duration::from_millis(2250));
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn rate_limit_backoff_is_shared_between_clones() {
+        let client = test_client("http://127.0.0.1:9");
+        let clone = client.clone();
+
+        client.note_rate_limited(Duration::from_secs(5));
+        // A shorter pause must not cut an existing one short.
+        clone.note_rate_limited(Duration::from_secs(1));
+        let remaining = clone.backoff.remaining().unwrap();
+        assert!(remaining > Duration::from_secs(4));
+
+        let start = tokio::time::Instant::now();
+        clone.wait_for_rate_limit().await;
+        let waited = start.elapsed();
+        assert!(waited >= Duration::from_secs(5));
+        assert!(waited < Duration::from_secs(5) + BACKOFF_STAGGER + Duration::from_millis(1));
+        assert!(client.backoff.remaining().is_none());
+    }
+
+    #[tokio::test]
+    async fn request_slots_are_shared_between_clones() {
+        let client = test_client("http://127.0.0.1:9").with_max_concurrent_requests(Some(1));
+        let clone = client.clone();
+
+        let held = client.request_slot().await;
+        assert!(held.is_some());
+        let waiting = tokio::time::timeout(Duration::from_millis(50), clone.request_slot()).await;
+        assert!(waiting.is_err());
+        drop(held);
+        assert!(clone.request_slot().await.is_some());
+
+        let unlimited = test_client("http://127.0.0.1:9").with_max_concurrent_requests(Some(0));
+        assert!(unlimited.request_slot().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn responses_keep_their_request_slot_until_read() {
+        let srv = start_server(|_hit, _path| (200, vec![], br#"{"ok":true}"#.to_vec())).await;
+        let client = test_client(&srv.base_url).with_max_concurrent_requests(Some(1));
+
+        let (response, slot) = client
+            .send(Method::GET, srv.url_string("/x"))
+            .await
+            .unwrap();
+        assert!(slot.is_some());
+        let waiting = tokio::time::timeout(Duration::from_millis(50), client.request_slot()).await;
+        assert!(waiting.is_err());
+        response.bytes().await.unwrap();
+        drop(slot);
+        assert!(client.request_slot().await.is_some());
+
+        let _ = srv.shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn timed_client_decodes_gzip_json_itself() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let json = format!(r#"{{"results":[],"padding":"{}"}}"#, "a".repeat(256));
+        encoder.write_all(json.as_bytes()).unwrap();
+        let gzipped = encoder.finish().unwrap();
+        let srv = start_server(move |_hit, _path| {
+            let headers = vec![("content-encoding".to_string(), "gzip".to_string())];
+            (200, headers, gzipped.clone())
+        })
+        .await;
+
+        let client = test_client(&srv.base_url).with_timing(true).unwrap();
+        let before = transfer_stats();
+        let (json, headers) = client.get_json(srv.url_string("/x")).await.unwrap();
+        assert_eq!(json["padding"].as_str().unwrap().len(), 256);
+        assert_eq!(headers.get(CONTENT_ENCODING).unwrap(), "gzip");
+        let after = transfer_stats();
+        assert!(after.requests > before.requests);
+        assert!(after.body_bytes - before.body_bytes > after.wire_bytes - before.wire_bytes);
+
+        let _ = srv.shutdown.send(());
+    }
+
+    #[test]
+    fn backoff_warning_threshold_from_env() {
+        assert_eq!(
+            backoff_warning_from_lookup(|_| None).unwrap(),
+            Some(DEFAULT_BACKOFF_WARNING)
+        );
+        let lookup = |value: &'static str| move |_: &str| Some(value.to_string());
+        assert_eq!(
+            backoff_warning_from_lookup(lookup("15")).unwrap(),
+            Some(Duration::from_secs(15))
+        );
+        assert_eq!(backoff_warning_from_lookup(lookup("0")).unwrap(), None);
+        assert!(backoff_warning_from_lookup(lookup("soon")).is_err());
+    }
+
+    #[test]
+    fn pool_settings_read_overrides_and_reject_garbage() {
+        let settings = PoolSettings::from_lookup(|key| match key {
+            "CONFCLI_POOL_MAX_IDLE_PER_HOST" => Some("4".to_string()),
+            "CONFCLI_KEEPALIVE" => Some("0".to_string()),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!(settings.max_idle_per_host, 4);
+        assert_eq!(settings.idle_timeout, PoolSettings::default().idle_timeout);
+        assert_eq!(settings.keep_alive, None);
+
+        let err = PoolSettings::from_lookup(|key| {
+            (key == "CONFCLI_POOL_IDLE_TIMEOUT").then(|| "soon".to_string())
+        })
+        .unwrap_err();
+        assert!(err.to_string().contains("CONFCLI_POOL_IDLE_TIMEOUT"));
+    }
+
+    #[test]
+    fn decode_body_passes_identity_through() {
+        let body = decode_body(&HeaderMap::new(), b"{}").unwrap();
+        assert_eq!(body, b"{}");
+    }
+
     #[tokio::test]
     async fn pagination_loop_is_detected_before_second_request() {
         let srv = start_server(|_hit, path| {
@@ -551,7 +1461,9 @@ mod tests {
         let client = test_client(&srv.base_url);
         let url = srv.url_string("/loop");
 
-        let res = client.get_paginated_results_with_limit(url, true, 10).await;
+        let res = client
+            .get_paginated_results_with_limit(url, true, 10, None)
+            .await;
         assert!(res.is_err());
         let msg = format!("{:#}", res.unwrap_err());
         assert!(msg.contains("Pagination loop detected"));
@@ -578,7 +1490,9 @@ mod tests {
         let client = test_client(&srv.base_url);
         let url = srv.url_string("/pages/1");
 
-        let res = client.get_paginated_results_with_limit(url, true, 3).await;
+        let res = client
+            .get_paginated_results_with_limit(url, true, 3, None)
+            .await;
         assert!(res.is_err());
         let msg = format!("{:#}", res.unwrap_err());
         assert!(msg.contains("Pagination aborted after 3 pages"));
@@ -587,6 +1501,29 @@ mod tests {
         let _ = srv.shutdown.send(());
     }
 
+    #[tokio::test]
+    async fn pagination_up_to_stops_once_enough_results() {
+        let srv = start_server(|_hit, path| {
+            let n: usize = path.trim_start_matches("/pages/").parse().unwrap_or(0);
+            let next = format!("</pages/{}>; rel=next", n + 1);
+            let body = format!("{{\"results\":[{{\"n\":{n}}},{{\"n\":{n}}}]}}")
+                .as_bytes()
+                .to_vec();
+            (200, vec![("link".to_string(), next)], body)
+        })
+        .await;
+
+        let client = test_client(&srv.base_url);
+        let res = client
+            .get_paginated_results_up_to(srv.url_string("/pages/1"), 3)
+            .await
+            .unwrap();
+        assert_eq!(res.len(), 3);
+        assert_eq!(srv.hits.load(Ordering::SeqCst), 2);
+
+        let _ = srv.shutdown.send(());
+    }
+
     #[tokio::test]
     async fn pagination_resolves_query_relative_next_against_current_url() {
         let srv = start_server(|hit, path| match hit {
@@ -610,7 +1547,7 @@ mod tests {
         let url = srv.url_string("/wiki/api/v2/pages?limit=1");
 
         let res = client
-            .get_paginated_results_with_limit(url, true, 10)
+            .get_paginated_results_with_limit(url, true, 10, None)
             .await
             .unwrap();
 
@@ -643,9 +1580,12 @@ mod tests {
         let client = test_client(&srv.base_url);
         let url = srv.url_string("/flaky");
 
+        let before = transfer_stats();
         let (json, _headers) = client.get_json(url).await.unwrap();
         assert_eq!(json.get("ok").and_then(|v| v.as_bool()), Some(true));
         assert_eq!(srv.hits.load(Ordering::SeqCst), 3);
+        // Other tests share the process-wide counters, so only lower bounds hold.
+        assert!(transfer_stats().retries >= before.retries + 2);
 
         let _ = srv.shutdown.send(());
     }
@@ -670,4 +1610,118 @@ mod tests {
 
         let _ = srv.shutdown.send(());
     }
+
+    #[test]
+    fn gateway_clients_resolve_site_links_through_the_gateway() {
+        let (v1, v2) = crate::config::gateway_api_bases("abc");
+        let client = ApiClient::new(
+            "https://example.atlassian.net/wiki".to_string(),
+            v1,
+            v2,
+            AuthMethod::Bearer {
+                token: "test".to_string(),
+            },
+            0,
+        )
+        .unwrap();
+        assert_eq!(client.base_url(), "https://example.atlassian.net/wiki");
+        assert_eq!(
+            client.api_site_url(),
+            "https://api.atlassian.com/ex/confluence/abc/wiki"
+        );
+        assert_eq!(
+            client.jira_api_url("/issue/OPS-1"),
+            "https://api.atlassian.com/ex/jira/abc/rest/api/3/issue/OPS-1"
+        );
+        let current = "https://api.atlassian.com/ex/confluence/abc/wiki/api/v2/pages";
+        assert_eq!(
+            client
+                .resolve_next_page(current, "/wiki/api/v2/pages?cursor=x")
+                .unwrap(),
+            "https://api.atlassian.com/ex/confluence/abc/wiki/api/v2/pages?cursor=x"
+        );
+
+        let direct = test_client("https://example.atlassian.net/wiki");
+        assert_eq!(direct.api_site_url(), "https://example.atlassian.net/wiki");
+    }
+
+    #[test]
+    fn client_certs_must_hold_a_certificate_and_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let cert = dir.path().join("client.pem");
+        let err = ClientCert::from_files(&cert, None).unwrap_err();
+        assert!(format!("{err:#}").contains("Failed to read client certificate"));
+
+        std::fs::write(&cert, "not a certificate").unwrap();
+        let err = ClientCert::from_files(&cert, None).unwrap_err();
+        assert!(format!("{err:#}").contains("does not hold a PEM certificate and private key"));
+    }
+
+    #[tokio::test]
+    async fn cookie_auth_logs_in_again_when_the_session_expires() {
+        let srv = start_server(|hit, path| match (hit, path) {
+            (1 | 3, "/rest/auth/1/session") => {
+                let body = format!(r#"{{"session":{{"name":"JSESSIONID","value":"s{hit}"}}}}"#);
+                (200, Vec::new(), body.into_bytes())
+            }
+            (2, "/x") => (401, Vec::new(), Vec::new()),
+            (4, "/x") => (200, Vec::new(), b"{}".to_vec()),
+            _ => panic!("unexpected request {hit} {path}"),
+        })
+        .await;
+
+        let client = ApiClient::new(
+            srv.base_url.clone(),
+            srv.base_url.clone(),
+            srv.base_url.clone(),
+            AuthMethod::Cookie {
+                username: "ada".to_string(),
+                password: "secret".to_string(),
+            },
+            0,
+        )
+        .unwrap();
+        client.get_json(srv.url_string("/x")).await.unwrap();
+        assert_eq!(client.session_cookie().as_deref(), Some("JSESSIONID=s3"));
+        assert_eq!(srv.hits.load(Ordering::SeqCst), 4);
+
+        let _ = srv.shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn cookie_auth_reuses_and_saves_the_session_file() {
+        let srv = start_server(|hit, path| match (hit, path) {
+            (1, "/x") => (401, Vec::new(), Vec::new()),
+            (2, "/rest/auth/1/session") => (
+                200,
+                Vec::new(),
+                br#"{"session":{"name":"JSESSIONID","value":"new"}}"#.to_vec(),
+            ),
+            (3, "/x") => (200, Vec::new(), b"{}".to_vec()),
+            _ => panic!("unexpected request {hit} {path}"),
+        })
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("sessions").join("default");
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::write(&file, "JSESSIONID=old\n").unwrap();
+
+        let client = ApiClient::new(
+            srv.base_url.clone(),
+            srv.base_url.clone(),
+            srv.base_url.clone(),
+            AuthMethod::Cookie {
+                username: "ada".to_string(),
+                password: "secret".to_string(),
+            },
+            0,
+        )
+        .unwrap()
+        .with_session_file(file.clone());
+        // The saved session is tried first; the login only follows its 401.
+        client.get_json(srv.url_string("/x")).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "JSESSIONID=new");
+
+        let _ = srv.shutdown.send(());
+    }
 }
@@ -0,0 +1,103 @@
+//! System clipboard access for the `--clipboard` flags, behind the `clipboard` feature.
+//!
+//! On Linux the copied text lives in confcli's process, so `copy` keeps serving it and
+//! only returns once something else is copied; run the command in the background if the
+//! terminal is needed meanwhile.
+
+#[cfg(feature = "clipboard")]
+use anyhow::Context;
+use anyhow::Result;
+
+#[cfg(not(feature = "clipboard"))]
+const NOT_BUILT: &str = "Clipboard support needs a build with the `clipboard` feature";
+
+/// Replace the clipboard contents with `text` instead of printing it.
+pub fn copy(text: &str) -> Result<()> {
+    let lines = line_count(text);
+    if cfg!(target_os = "linux") {
+        tracing::info!("Copying {lines} to the clipboard; waiting until something else is copied");
+    }
+    set_text(text)?;
+    tracing::info!("Copied {lines} to the clipboard");
+    Ok(())
+}
+
+fn line_count(text: &str) -> String {
+    match text.lines().count() {
+        1 => "1 line".to_string(),
+        n => format!("{n} lines"),
+    }
+}
+
+#[cfg(all(feature = "clipboard", target_os = "linux"))]
+fn set_text(text: &str) -> Result<()> {
+    use arboard::SetExtLinux;
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set().wait().text(text))
+        .context("Failed to copy to the clipboard")
+}
+
+#[cfg(all(feature = "clipboard", not(target_os = "linux")))]
+fn set_text(text: &str) -> Result<()> {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text))
+        .context("Failed to copy to the clipboard")
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn set_text(_text: &str) -> Result<()> {
+    anyhow::bail!(NOT_BUILT)
+}
+
+/// The clipboard's text; an error when it is empty or holds something else.
+#[cfg(all(feature = "clipboard", feature = "write"))]
+pub fn paste() -> Result<String> {
+    let text = arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .context("Failed to read text from the clipboard")?;
+    pasted(text)
+}
+
+#[cfg(all(not(feature = "clipboard"), feature = "write"))]
+pub fn paste() -> Result<String> {
+    anyhow::bail!(NOT_BUILT)
+}
+
+#[cfg(any(test, all(feature = "clipboard", feature = "write")))]
+fn pasted(text: String) -> Result<String> {
+    if text.trim().is_empty() {
+        anyhow::bail!("The clipboard is empty");
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_count_uses_singular_for_one_line() {
+        assert_eq!(line_count("# Title"), "1 line");
+        assert_eq!(line_count("# Title\n\nBody\n"), "3 lines");
+        assert_eq!(line_count(""), "0 lines");
+    }
+
+    #[test]
+    fn pasted_rejects_blank_text() {
+        let err = pasted(" \n\t".to_string()).unwrap_err();
+        assert_eq!(err.to_string(), "The clipboard is empty");
+        assert_eq!(pasted("body".to_string()).unwrap(), "body");
+    }
+
+    #[cfg(not(feature = "clipboard"))]
+    #[test]
+    fn copy_without_the_feature_explains_how_to_build_it() {
+        assert_eq!(copy("body").unwrap_err().to_string(), NOT_BUILT);
+    }
+
+    #[cfg(all(not(feature = "clipboard"), feature = "write"))]
+    #[test]
+    fn paste_without_the_feature_explains_how_to_build_it() {
+        assert_eq!(paste().unwrap_err().to_string(), NOT_BUILT);
+    }
+}
@@ -0,0 +1,472 @@
+//! `confcli apply`: one page declared in YAML and converged to on every run.
+//!
+//! ```yaml
+//! space: OPS
+//! title: On-call roster
+//! parent: OPS:Runbooks          # page id, URL, or SPACE:Title
+//! body_file: roster.xml         # or body:; storage format, relative to the spec
+//! labels: [oncall, critical]
+//! properties:                   # content properties, any YAML value
+//!   owner: team-sre
+//! ```
+//!
+//! The page is found by title in the space and created if missing. Its body is rewritten when
+//! the spec's body changed or the page was edited since the last apply (both tracked in the
+//! `confcli-apply` property), and it is moved when its parent differs. Labels are only added,
+//! never removed, so labels other tools rely on survive; properties are set to the declared
+//! values.
+
+use anyhow::{Context, Result, anyhow};
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::output::OutputFormat;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::cli::ApplyArgs;
+use crate::commands::generate::publish::{read_property, write_property};
+use crate::commands::label::page_labels;
+use crate::context::AppContext;
+use crate::helpers::{maybe_print_json, maybe_print_rows, url_with_query};
+use crate::plan::{self, Operation};
+use crate::resolve::{resolve_page_id, resolve_space_id};
+
+/// Content property with the body hash and page version of the last apply.
+const APPLY_PROPERTY: &str = "confcli-apply";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PageSpec {
+    space: String,
+    title: String,
+    parent: Option<String>,
+    body: Option<String>,
+    body_file: Option<PathBuf>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    properties: BTreeMap<String, Value>,
+}
+
+/// The page as it is on the site.
+struct LivePage {
+    id: String,
+    title: String,
+    status: String,
+    version: i64,
+    parent_id: String,
+    body: String,
+}
+
+/// One line of the apply report.
+struct Step {
+    kind: &'static str,
+    target: String,
+    status: &'static str,
+    id: String,
+}
+
+impl Step {
+    fn new(kind: &'static str, target: impl Into<String>, status: &'static str, id: &str) -> Self {
+        Self {
+            kind,
+            target: target.into(),
+            status,
+            id: id.to_string(),
+        }
+    }
+}
+
+pub async fn handle(ctx: &AppContext, args: ApplyArgs) -> Result<()> {
+    let client = crate::context::load_client(ctx)?;
+    let spec = load_spec(&args.spec)?;
+    let base_dir = args
+        .spec
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let body = spec_body(&base_dir, &spec)?;
+    let space_id = resolve_space_id(&client, &spec.space).await?;
+    let parent_id = match &spec.parent {
+        Some(parent) => Some(resolve_page_id(&client, parent).await?),
+        None => None,
+    };
+    let title = spec.title.trim();
+    let target = format!("{}:{title}", spec.space.trim());
+    let mut steps = Vec::new();
+
+    let page_id = match find_page(&client, &space_id, title).await? {
+        Some(live) => {
+            converge_page(
+                &client,
+                ctx,
+                &live,
+                body.as_deref(),
+                parent_id.as_deref(),
+                &target,
+                &mut steps,
+            )
+            .await?;
+            Some(live.id)
+        }
+        None if ctx.dry_run => {
+            plan::record(
+                Operation::new("create", "page", &target).with_payload(json!({
+                    "parentId": parent_id,
+                    "bodyBytes": body.as_ref().map(String::len),
+                })),
+            );
+            steps.push(Step::new("page", &target, "would create", ""));
+            None
+        }
+        None => {
+            let body = body.as_deref().unwrap_or("");
+            let mut payload = json!({
+                "spaceId": space_id,
+                "title": title,
+                "status": "current",
+                "body": { "representation": "storage", "value": body },
+            });
+            if let Some(parent_id) = &parent_id {
+                payload["parentId"] = json!(parent_id);
+            }
+            let result = client
+                .post_json(client.v2_url("/pages"), payload)
+                .await
+                .with_context(|| format!("Failed to create page '{title}'"))?;
+            let id = json_str(&result, "id");
+            write_marker(
+                &client,
+                &id,
+                None,
+                body,
+                version_number(&result).unwrap_or(1),
+            )
+            .await?;
+            steps.push(Step::new("page", &target, "created", &id));
+            Some(id)
+        }
+    };
+
+    if !spec.labels.is_empty() {
+        apply_labels(
+            &client,
+            ctx,
+            page_id.as_deref(),
+            &target,
+            &spec.labels,
+            &mut steps,
+        )
+        .await?;
+    }
+    for (key, value) in &spec.properties {
+        let existing = match &page_id {
+            Some(id) => read_property(&client, id, key).await?,
+            None => None,
+        };
+        let id = page_id.as_deref().unwrap_or("");
+        if existing.as_ref().and_then(|p| p.get("value")) == Some(value) {
+            steps.push(Step::new("property", key, "unchanged", id));
+        } else if ctx.dry_run {
+            plan::record(Operation::new("set", "property", key).with_payload(
+                json!({ "page": page_id.as_deref().unwrap_or(&target), "value": value }),
+            ));
+            steps.push(Step::new("property", key, "would set", id));
+        } else {
+            write_property(&client, id, key, existing.as_ref(), value.clone()).await?;
+            steps.push(Step::new("property", key, "set", id));
+        }
+    }
+
+    match args.output {
+        OutputFormat::Json => {
+            let items: Vec<Value> = steps
+                .iter()
+                .map(|step| {
+                    json!({
+                        "kind": step.kind,
+                        "target": step.target,
+                        "status": step.status,
+                        "id": step.id,
+                    })
+                })
+                .collect();
+            maybe_print_json(ctx, &items)
+        }
+        fmt => {
+            let rows = steps
+                .into_iter()
+                .map(|step| {
+                    vec![
+                        step.kind.to_string(),
+                        step.target,
+                        step.status.to_string(),
+                        step.id,
+                    ]
+                })
+                .collect();
+            maybe_print_rows(ctx, fmt, &["Kind", "Target", "Status", "ID"], rows);
+            Ok(())
+        }
+    }
+}
+
+/// Rewrite the body if it drifted and move the page if its parent differs, in one update.
+async fn converge_page(
+    client: &ApiClient,
+    ctx: &AppContext,
+    live: &LivePage,
+    body: Option<&str>,
+    parent_id: Option<&str>,
+    target: &str,
+    steps: &mut Vec<Step>,
+) -> Result<()> {
+    let marker = read_property(client, &live.id, APPLY_PROPERTY).await?;
+    let drifted = body.is_some_and(|body| {
+        !marker_matches(
+            marker.as_ref().and_then(|m| m.get("value")),
+            body,
+            live.version,
+        )
+    });
+    let moved = parent_id.is_some_and(|parent| parent != live.parent_id);
+    if !drifted && !moved {
+        steps.push(Step::new("page", target, "unchanged", &live.id));
+        return Ok(());
+    }
+    if ctx.dry_run {
+        plan::record(
+            Operation::new("update", "page", &live.id).with_payload(json!({
+                "title": live.title,
+                "parentId": if moved { parent_id } else { None },
+                "bodyBytes": if drifted { body.map(str::len) } else { None },
+            })),
+        );
+        steps.push(Step::new("page", target, "would update", &live.id));
+        return Ok(());
+    }
+
+    let new_body = match body {
+        Some(body) if drifted => body,
+        _ => live.body.as_str(),
+    };
+    let mut payload = json!({
+        "id": live.id,
+        "title": live.title,
+        "status": live.status,
+        "body": { "representation": "storage", "value": new_body },
+        "version": { "number": live.version + 1, "message": "Updated by confcli apply" },
+    });
+    if moved {
+        payload["parentId"] = json!(parent_id);
+    }
+    let result = client
+        .put_json(client.v2_url(&format!("/pages/{}", live.id)), payload)
+        .await
+        .with_context(|| format!("Failed to update page {}", live.id))?;
+    // A move alone still adds a version, which the marker has to follow.
+    if let Some(body) = body {
+        let version = version_number(&result).unwrap_or(live.version + 1);
+        write_marker(client, &live.id, marker.as_ref(), body, version).await?;
+    }
+    steps.push(Step::new("page", target, "updated", &live.id));
+    Ok(())
+}
+
+async fn apply_labels(
+    client: &ApiClient,
+    ctx: &AppContext,
+    page_id: Option<&str>,
+    target: &str,
+    labels: &[String],
+    steps: &mut Vec<Step>,
+) -> Result<()> {
+    let existing = match page_id {
+        Some(id) => page_labels(client, id).await?,
+        None => Vec::new(),
+    };
+    let mut wanted: Vec<String> = labels
+        .iter()
+        .map(|label| label.trim().to_lowercase())
+        .collect();
+    wanted.sort();
+    wanted.dedup();
+    let missing: Vec<String> = wanted
+        .iter()
+        .filter(|label| !existing.contains(label))
+        .cloned()
+        .collect();
+    let id = page_id.unwrap_or("");
+    if missing.is_empty() {
+        steps.push(Step::new("labels", wanted.join(", "), "unchanged", id));
+    } else if ctx.dry_run {
+        plan::record(
+            Operation::new("add", "label", page_id.unwrap_or(target))
+                .with_payload(json!({ "labels": missing })),
+        );
+        steps.push(Step::new("labels", missing.join(", "), "would add", id));
+    } else {
+        let body: Value = missing
+            .iter()
+            .map(|name| json!({ "prefix": "global", "name": name }))
+            .collect::<Vec<_>>()
+            .into();
+        client
+            .post_json(client.v1_url(&format!("/content/{id}/label")), body)
+            .await
+            .with_context(|| format!("Failed to label page {id}"))?;
+        steps.push(Step::new("labels", missing.join(", "), "added", id));
+    }
+    Ok(())
+}
+
+fn load_spec(path: &Path) -> Result<PageSpec> {
+    let data = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read spec: {}", path.display()))?;
+    parse_spec(&data).with_context(|| format!("Invalid spec: {}", path.display()))
+}
+
+fn parse_spec(data: &str) -> Result<PageSpec> {
+    let spec: PageSpec = serde_yaml_ng::from_str(data)?;
+    if spec.space.trim().is_empty() {
+        return Err(anyhow!("space cannot be empty"));
+    }
+    if spec.title.trim().is_empty() {
+        return Err(anyhow!("title cannot be empty"));
+    }
+    if spec.body.is_some() && spec.body_file.is_some() {
+        return Err(anyhow!("Use either body or body_file, not both"));
+    }
+    if spec.labels.iter().any(|label| label.trim().is_empty()) {
+        return Err(anyhow!("Labels cannot be empty"));
+    }
+    if spec.properties.contains_key(APPLY_PROPERTY) {
+        return Err(anyhow!(
+            "Property '{APPLY_PROPERTY}' is reserved for tracking applies"
+        ));
+    }
+    Ok(spec)
+}
+
+fn spec_body(base_dir: &Path, spec: &PageSpec) -> Result<Option<String>> {
+    match (&spec.body, &spec.body_file) {
+        (Some(body), _) => Ok(Some(body.clone())),
+        (None, Some(file)) => {
+            let path = base_dir.join(file);
+            std::fs::read_to_string(&path)
+                .map(Some)
+                .with_context(|| format!("Failed to read body_file: {}", path.display()))
+        }
+        (None, None) => Ok(None),
+    }
+}
+
+async fn find_page(client: &ApiClient, space_id: &str, title: &str) -> Result<Option<LivePage>> {
+    let url = url_with_query(
+        &client.v2_url("/pages"),
+        &[
+            ("space-id", space_id.to_string()),
+            ("title", title.to_string()),
+            ("body-format", "storage".to_string()),
+            ("limit", "1".to_string()),
+        ],
+    )?;
+    let items = client.get_paginated_results(url, false).await?;
+    let Some(item) = items.first() else {
+        return Ok(None);
+    };
+    Ok(Some(LivePage {
+        id: json_str(item, "id"),
+        title: json_str(item, "title"),
+        status: item
+            .get("status")
+            .and_then(|v| v.as_str())
+            .unwrap_or("current")
+            .to_string(),
+        version: version_number(item).context("Missing current version number")?,
+        parent_id: json_str(item, "parentId"),
+        body: item
+            .pointer("/body/storage/value")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+    }))
+}
+
+fn version_number(page: &Value) -> Option<i64> {
+    page.pointer("/version/number").and_then(|v| v.as_i64())
+}
+
+fn body_hash(body: &str) -> String {
+    format!("{:x}", Sha256::digest(body.as_bytes()))
+}
+
+/// Whether the last apply wrote this body and nobody has edited the page since.
+fn marker_matches(marker: Option<&Value>, body: &str, version: i64) -> bool {
+    marker.is_some_and(|marker| {
+        json_str(marker, "hash") == body_hash(body)
+            && marker.get("version").and_then(|v| v.as_i64()) == Some(version)
+    })
+}
+
+async fn write_marker(
+    client: &ApiClient,
+    page_id: &str,
+    existing: Option<&Value>,
+    body: &str,
+    version: i64,
+) -> Result<()> {
+    let value = json!({ "hash": body_hash(body), "version": version });
+    write_property(client, page_id, APPLY_PROPERTY, existing, value).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_page_spec() {
+        let spec = parse_spec(
+            r#"
+space: OPS
+title: On-call roster
+parent: OPS:Runbooks
+body_file: roster.xml
+labels: [oncall]
+properties:
+  owner: team-sre
+  review: { every: 90 }
+"#,
+        )
+        .unwrap();
+        assert_eq!(spec.parent.as_deref(), Some("OPS:Runbooks"));
+        assert_eq!(spec.properties["review"], json!({ "every": 90 }));
+    }
+
+    #[test]
+    fn rejects_invalid_specs() {
+        let both = parse_spec("space: OPS\ntitle: A\nbody: x\nbody_file: a.xml").unwrap_err();
+        assert!(both.to_string().contains("not both"));
+
+        let unknown = parse_spec("space: OPS\ntitle: A\nchildren: []").unwrap_err();
+        assert!(format!("{unknown:#}").contains("unknown field"));
+
+        let reserved =
+            parse_spec("space: OPS\ntitle: A\nproperties: { confcli-apply: 1 }").unwrap_err();
+        assert!(reserved.to_string().contains("reserved"));
+    }
+
+    #[test]
+    fn marker_tracks_body_and_version() {
+        let marker = json!({ "hash": body_hash("<p>Hi</p>"), "version": 4 });
+        assert!(marker_matches(Some(&marker), "<p>Hi</p>", 4));
+        // Edited on the site since the last apply.
+        assert!(!marker_matches(Some(&marker), "<p>Hi</p>", 5));
+        // The spec changed.
+        assert!(!marker_matches(Some(&marker), "<p>Bye</p>", 4));
+        assert!(!marker_matches(None, "<p>Hi</p>", 4));
+    }
+}
@@ -0,0 +1,481 @@
+//! `attachment mirror`: incremental copies of a space's attachments to a local directory or
+//! (with the `s3` feature) an S3-compatible bucket. A manifest next to the copies records
+//! each attachment's version and SHA-256, so re-runs only download what changed.
+
+use anyhow::{Context, Result, bail};
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::output::OutputFormat;
+use futures_util::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+#[cfg(feature = "s3")]
+use super::s3::S3Dest;
+use super::space_attachments;
+use crate::cli::AttachmentMirrorArgs;
+use crate::context::AppContext;
+use crate::download::sanitize_filename;
+use crate::helpers::{maybe_print_json, maybe_print_rows, print_line};
+use crate::resolve::{resolve_space_id, resolve_space_key};
+
+/// Manifest file at the root of the destination.
+const MANIFEST: &str = ".confcli-mirror.json";
+/// Page lookups and downloads at once without `--concurrency`.
+const CONCURRENCY: usize = 4;
+
+/// What has been mirrored, keyed by attachment id.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    attachments: BTreeMap<String, MirroredAttachment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MirroredAttachment {
+    /// Object key relative to the destination root.
+    key: String,
+    version: i64,
+    size: i64,
+    sha256: String,
+    page_id: String,
+    mirrored_at: String,
+}
+
+/// Where copies go.
+#[derive(Debug)]
+enum MirrorDest {
+    Local(PathBuf),
+    #[cfg(feature = "s3")]
+    S3(Box<S3Dest>),
+}
+
+impl MirrorDest {
+    fn parse(args: &AttachmentMirrorArgs) -> Result<Self> {
+        if args.dest.starts_with("s3://") {
+            #[cfg(feature = "s3")]
+            return Ok(Self::S3(Box::new(S3Dest::parse(
+                &args.dest,
+                args.region.as_deref(),
+                args.endpoint.as_deref(),
+            )?)));
+            #[cfg(not(feature = "s3"))]
+            bail!("S3 destinations need a build with the `s3` feature");
+        }
+        if args.dest.contains("://") && !args.dest.starts_with("file://") {
+            bail!(
+                "Unsupported destination '{}': use a directory or s3://bucket/prefix",
+                args.dest
+            );
+        }
+        let path = match Url::parse(&args.dest) {
+            Ok(url) if url.scheme() == "file" => url
+                .to_file_path()
+                .map_err(|_| anyhow::anyhow!("Invalid file URL '{}'", args.dest))?,
+            _ => PathBuf::from(&args.dest),
+        };
+        Ok(Self::Local(path))
+    }
+
+    fn describe(&self, key: &str) -> String {
+        match self {
+            Self::Local(root) => root.join(key).display().to_string(),
+            #[cfg(feature = "s3")]
+            Self::S3(s3) => s3.describe(key),
+        }
+    }
+
+    async fn read(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match self {
+            Self::Local(root) => match tokio::fs::read(root.join(key)).await {
+                Ok(bytes) => Ok(Some(bytes)),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(err) => {
+                    Err(err).with_context(|| format!("Failed to read {}", self.describe(key)))
+                }
+            },
+            #[cfg(feature = "s3")]
+            Self::S3(s3) => s3.get(key).await,
+        }
+    }
+
+    /// Copy the downloaded `file` to `key`.
+    #[cfg_attr(not(feature = "s3"), allow(unused_variables))]
+    async fn store(&self, key: &str, file: &Path, sha256: &str, media_type: &str) -> Result<()> {
+        match self {
+            Self::Local(root) => {
+                let dest = root.join(key);
+                if let Some(parent) = dest.parent() {
+                    tokio::fs::create_dir_all(parent)
+                        .await
+                        .with_context(|| format!("Failed to create {}", parent.display()))?;
+                }
+                tokio::fs::copy(file, &dest)
+                    .await
+                    .with_context(|| format!("Failed to write {}", dest.display()))?;
+                Ok(())
+            }
+            #[cfg(feature = "s3")]
+            Self::S3(s3) => {
+                let body = tokio::fs::File::open(file)
+                    .await
+                    .with_context(|| format!("Failed to open {}", file.display()))?;
+                let length = body.metadata().await?.len();
+                s3.put(key, body.into(), length, sha256, Some(media_type))
+                    .await
+            }
+        }
+    }
+
+    async fn write(&self, key: &str, bytes: Vec<u8>) -> Result<()> {
+        match self {
+            Self::Local(root) => {
+                tokio::fs::create_dir_all(root)
+                    .await
+                    .with_context(|| format!("Failed to create {}", root.display()))?;
+                let dest = root.join(key);
+                tokio::fs::write(&dest, bytes)
+                    .await
+                    .with_context(|| format!("Failed to write {}", dest.display()))
+            }
+            #[cfg(feature = "s3")]
+            Self::S3(s3) => {
+                let sha256 = format!("{:x}", Sha256::digest(&bytes));
+                let length = bytes.len() as u64;
+                s3.put(key, bytes.into(), length, &sha256, Some("application/json"))
+                    .await
+            }
+        }
+    }
+
+    /// Whether the copy at `key` still has the recorded checksum.
+    async fn verify(&self, key: &str, sha256: &str) -> Result<bool> {
+        match self {
+            Self::Local(root) => match tokio::fs::metadata(root.join(key)).await {
+                Ok(_) => Ok(file_sha256(&root.join(key)).await? == sha256),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+                Err(err) => Err(err.into()),
+            },
+            #[cfg(feature = "s3")]
+            Self::S3(s3) => Ok(s3.head_sha256(key).await?.as_deref() == Some(sha256)),
+        }
+    }
+}
+
+/// What happened to one attachment.
+#[derive(Debug)]
+struct MirrorResult {
+    id: String,
+    title: String,
+    key: String,
+    outcome: &'static str,
+    entry: Option<MirroredAttachment>,
+}
+
+pub(super) async fn attachment_mirror(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: AttachmentMirrorArgs,
+) -> Result<()> {
+    let dest = MirrorDest::parse(&args)?;
+    let space_id = resolve_space_id(client, &args.space).await?;
+    let space_key = resolve_space_key(client, &space_id).await?;
+    let mut manifest: Manifest = match dest.read(MANIFEST).await? {
+        Some(bytes) => serde_json::from_slice(&bytes)
+            .with_context(|| format!("Invalid mirror manifest {}", dest.describe(MANIFEST)))?,
+        None => Manifest::default(),
+    };
+
+    let items = space_attachments(client, ctx, &args.space, None, ctx.workers(CONCURRENCY)).await?;
+    let staging = tempfile::tempdir().context("Failed to create a staging directory")?;
+    let base = Url::parse(client.api_site_url())?;
+    let manifest_ref = &manifest;
+    let (dest_ref, staging_ref, base_ref, space_ref) = (&dest, staging.path(), &base, &space_key);
+    let results: Vec<Result<MirrorResult>> = stream::iter(items.iter())
+        .map(|item| async move {
+            mirror_one(
+                client,
+                ctx,
+                MirrorJob {
+                    dest: dest_ref,
+                    staging: staging_ref,
+                    base: base_ref,
+                    space_key: space_ref,
+                    previous: manifest_ref.attachments.get(&json_str(item, "id")),
+                    verify: args.verify,
+                },
+                item,
+            )
+            .await
+        })
+        .buffer_unordered(ctx.workers(CONCURRENCY))
+        .collect()
+        .await;
+
+    let mut rows = Vec::new();
+    let mut failures = Vec::new();
+    for result in results {
+        match result {
+            Ok(result) => {
+                if let Some(entry) = &result.entry {
+                    manifest
+                        .attachments
+                        .insert(result.id.clone(), entry.clone());
+                }
+                rows.push(result);
+            }
+            Err(err) => failures.push(format!("{err:#}")),
+        }
+    }
+    rows.sort_by(|a, b| a.key.cmp(&b.key));
+    let changed = rows.iter().any(|row| row.entry.is_some());
+    if changed && !ctx.dry_run {
+        let bytes = serde_json::to_vec_pretty(&manifest)?;
+        dest.write(MANIFEST, bytes).await?;
+    }
+
+    let count = |outcome: &str| rows.iter().filter(|row| row.outcome == outcome).count();
+    let summary = json!({
+        "dest": args.dest,
+        "space": space_key,
+        "copied": count("copied") + count("would copy"),
+        "updated": count("updated") + count("would update"),
+        "unchanged": count("unchanged"),
+        "failed": failures.len(),
+        "dryRun": ctx.dry_run,
+    });
+    match args.output {
+        OutputFormat::Json => {
+            let items: Vec<Value> = rows
+                .iter()
+                .map(|row| {
+                    json!({
+                        "id": row.id,
+                        "title": row.title,
+                        "path": dest.describe(&row.key),
+                        "outcome": row.outcome,
+                        "sha256": row.entry.as_ref().map(|entry| entry.sha256.clone()),
+                    })
+                })
+                .collect();
+            let mut out = summary.clone();
+            out["items"] = json!(items);
+            out["errors"] = json!(failures);
+            maybe_print_json(ctx, &out)?;
+        }
+        fmt => {
+            let table = rows
+                .iter()
+                .filter(|row| row.outcome != "unchanged")
+                .map(|row| {
+                    vec![
+                        row.id.clone(),
+                        row.title.clone(),
+                        row.outcome.to_string(),
+                        dest.describe(&row.key),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            if !table.is_empty() {
+                maybe_print_rows(ctx, fmt, &["ID", "Title", "Outcome", "Path"], table);
+            }
+            for failure in &failures {
+                tracing::warn!("{failure}");
+            }
+            print_line(
+                ctx,
+                &format!(
+                    "{}{} copied, {} updated, {} unchanged, {} failed",
+                    if ctx.dry_run { "Dry run: " } else { "" },
+                    summary["copied"],
+                    summary["updated"],
+                    summary["unchanged"],
+                    failures.len()
+                ),
+            );
+        }
+    }
+    if !failures.is_empty() {
+        bail!("{} attachment(s) could not be mirrored", failures.len());
+    }
+    Ok(())
+}
+
+/// Shared inputs of [`mirror_one`].
+struct MirrorJob<'a> {
+    dest: &'a MirrorDest,
+    staging: &'a Path,
+    base: &'a Url,
+    space_key: &'a str,
+    previous: Option<&'a MirroredAttachment>,
+    verify: bool,
+}
+
+/// Copy one attachment unless the mirror already has its current version. A new version
+/// with unchanged content only updates the manifest.
+async fn mirror_one(
+    client: &ApiClient,
+    ctx: &AppContext,
+    job: MirrorJob<'_>,
+    item: &Value,
+) -> Result<MirrorResult> {
+    let id = json_str(item, "id");
+    let title = json_str(item, "title");
+    let key = object_key(job.space_key, item);
+    let version = item
+        .pointer("/version/number")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(0);
+    let size = item.get("fileSize").and_then(|v| v.as_i64()).unwrap_or(0);
+    let result = |outcome, entry| MirrorResult {
+        id: id.clone(),
+        title: title.clone(),
+        key: key.clone(),
+        outcome,
+        entry,
+    };
+
+    let current = job
+        .previous
+        .filter(|previous| previous.key == key && previous.version == version);
+    if let Some(previous) = current {
+        let intact = !job.verify || job.dest.verify(&key, &previous.sha256).await?;
+        if intact {
+            return Ok(result("unchanged", None));
+        }
+    }
+    if ctx.dry_run {
+        let outcome = if job.previous.is_some() {
+            "would update"
+        } else {
+            "would copy"
+        };
+        return Ok(result(outcome, None));
+    }
+
+    let download = item
+        .get("downloadLink")
+        .and_then(|v| v.as_str())
+        .or_else(|| item.pointer("/_links/download").and_then(|v| v.as_str()))
+        .with_context(|| format!("Attachment {id} has no download link"))?;
+    let url = crate::download::attachment_download_url(job.base, download)?;
+    let file = job.staging.join(&id);
+    crate::download::download_to_file_with_retry(
+        client,
+        url,
+        &file,
+        &format!("attachment {id}"),
+        crate::download::DownloadToFileOptions {
+            retry: crate::download::DownloadRetry::default(),
+            progress: None,
+            verbose: ctx.verbose,
+        },
+    )
+    .await?;
+    let sha256 = file_sha256(&file).await?;
+    let outcome = match job.previous {
+        Some(previous) if previous.key == key && previous.sha256 == sha256 && current.is_none() => {
+            "unchanged"
+        }
+        Some(_) => {
+            job.dest
+                .store(&key, &file, &sha256, &json_str(item, "mediaType"))
+                .await
+                .with_context(|| format!("Failed to mirror attachment {id}"))?;
+            "updated"
+        }
+        None => {
+            job.dest
+                .store(&key, &file, &sha256, &json_str(item, "mediaType"))
+                .await
+                .with_context(|| format!("Failed to mirror attachment {id}"))?;
+            "copied"
+        }
+    };
+    tokio::fs::remove_file(&file).await.ok();
+    let entry = MirroredAttachment {
+        key: key.clone(),
+        version,
+        size,
+        sha256,
+        page_id: json_str(item, "pageId"),
+        mirrored_at: crate::lifecycle::utc_timestamp(),
+    };
+    Ok(result(outcome, Some(entry)))
+}
+
+/// `SPACE/<page id>/<file name>`: attachment names are unique within a page.
+fn object_key(space_key: &str, item: &Value) -> String {
+    let name = sanitize_filename(&json_str(item, "title"));
+    let name = if name.is_empty() || name == "." || name == ".." {
+        json_str(item, "id")
+    } else {
+        name
+    };
+    format!(
+        "{}/{}/{name}",
+        sanitize_filename(space_key),
+        json_str(item, "pageId")
+    )
+}
+
+async fn file_sha256(path: &Path) -> Result<String> {
+    use tokio::io::AsyncReadExt;
+
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn object_keys_group_by_space_and_page() {
+        let item = json!({ "id": "att9", "title": "design/v2.pdf", "pageId": "42" });
+        assert_eq!(object_key("ENG", &item), "ENG/42/designv2.pdf");
+        let unnamed = json!({ "id": "att9", "title": "..", "pageId": "42" });
+        assert_eq!(object_key("ENG", &unnamed), "ENG/42/att9");
+    }
+
+    #[tokio::test]
+    async fn local_manifest_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = MirrorDest::Local(dir.path().join("mirror"));
+        assert!(dest.read(MANIFEST).await.unwrap().is_none());
+
+        let mut manifest = Manifest::default();
+        manifest.attachments.insert(
+            "att1".to_string(),
+            MirroredAttachment {
+                key: "ENG/1/a.txt".to_string(),
+                version: 2,
+                size: 3,
+                sha256: "abc".to_string(),
+                page_id: "1".to_string(),
+                mirrored_at: "2025-01-01T00:00:00Z".to_string(),
+            },
+        );
+        dest.write(MANIFEST, serde_json::to_vec(&manifest).unwrap())
+            .await
+            .unwrap();
+        let bytes = dest.read(MANIFEST).await.unwrap().unwrap();
+        let read: Manifest = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(read.attachments, manifest.attachments);
+    }
+}
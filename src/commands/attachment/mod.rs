@@ -0,0 +1,686 @@
+#[cfg(feature = "write")]
+use anyhow::anyhow;
+use anyhow::{Context, Result};
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::output::OutputFormat;
+use futures_util::stream::{self, StreamExt};
+use serde_json::Value;
+#[cfg(feature = "write")]
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "write")]
+use std::path::PathBuf;
+#[cfg(feature = "write")]
+use std::sync::Arc;
+#[cfg(feature = "write")]
+use tokio::sync::Semaphore;
+#[cfg(feature = "write")]
+use tokio::task::JoinSet;
+use url::Url;
+
+use crate::cli::*;
+use crate::context::AppContext;
+#[cfg(feature = "write")]
+use crate::context::confirm_settings;
+use crate::helpers::*;
+#[cfg(feature = "write")]
+use crate::plan::Operation;
+use crate::progress::Progress;
+use crate::resolve::{resolve_page_id, resolve_space_id};
+
+mod mirror;
+#[cfg(feature = "s3")]
+mod s3;
+
+/// Page lookups at once for `list --space`, without `--concurrency`.
+const LIST_CONCURRENCY: usize = 8;
+/// Uploads at once without `--concurrency`.
+#[cfg(feature = "write")]
+const UPLOAD_CONCURRENCY: usize = 4;
+
+pub async fn handle(ctx: &AppContext, cmd: AttachmentCommand) -> Result<()> {
+    let client = crate::context::load_client(ctx)?;
+    match cmd {
+        AttachmentCommand::List(args) => attachment_list(&client, ctx, args).await,
+        AttachmentCommand::Get(args) => attachment_get(&client, ctx, args).await,
+        AttachmentCommand::Download(args) => attachment_download(&client, ctx, args).await,
+        AttachmentCommand::Mirror(args) => mirror::attachment_mirror(&client, ctx, args).await,
+        #[cfg(feature = "write")]
+        AttachmentCommand::Upload(args) => attachment_upload(&client, ctx, args).await,
+        #[cfg(feature = "write")]
+        AttachmentCommand::Delete(args) => attachment_delete(&client, ctx, args).await,
+    }
+}
+
+async fn attachment_list(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: AttachmentListArgs,
+) -> Result<()> {
+    if let Some(space) = &args.space {
+        let max = (!args.all).then_some(args.limit);
+        let items =
+            space_attachments(client, ctx, space, max, ctx.workers(LIST_CONCURRENCY)).await?;
+        return match args.output {
+            OutputFormat::Json => maybe_print_json(ctx, &items),
+            fmt => {
+                let rows = items
+                    .iter()
+                    .map(|item| {
+                        let mut row = attachment_row(item);
+                        row.push(json_str(item, "pageTitle"));
+                        row.push(json_str(item, "pagePath"));
+                        row
+                    })
+                    .collect();
+                maybe_print_rows(
+                    ctx,
+                    fmt,
+                    &["ID", "Title", "Type", "Size", "Page", "Path"],
+                    rows,
+                );
+                Ok(())
+            }
+        };
+    }
+
+    let url = if let Some(page) = args.page {
+        let page_id = resolve_page_id(client, &page).await?;
+        client.v2_url(&format!(
+            "/pages/{page_id}/attachments?limit={}",
+            args.limit
+        ))
+    } else {
+        client.v2_url(&format!("/attachments?limit={}", args.limit))
+    };
+    let items = client.get_paginated_results(url, args.all).await?;
+    match args.output {
+        OutputFormat::Json => maybe_print_json(ctx, &items),
+        fmt => {
+            let rows = items.iter().map(attachment_row).collect();
+            maybe_print_rows(ctx, fmt, &["ID", "Title", "Type", "Size"], rows);
+            Ok(())
+        }
+    }
+}
+
+fn attachment_row(item: &Value) -> Vec<String> {
+    vec![
+        json_str(item, "id"),
+        json_str(item, "title"),
+        json_str(item, "mediaType"),
+        human_size(item.get("fileSize").and_then(|v| v.as_i64()).unwrap_or(0)),
+    ]
+}
+
+/// Attachments of every page in a space, in page-listing order, each with `pageTitle` and
+/// `pagePath` (ancestor titles down to the page, joined with " / "). Pages are walked
+/// `concurrency` at a time and the walk stops once `max` attachments are found.
+async fn space_attachments(
+    client: &ApiClient,
+    ctx: &AppContext,
+    space: &str,
+    max: Option<usize>,
+    concurrency: usize,
+) -> Result<Vec<Value>> {
+    let space_id = resolve_space_id(client, space).await?;
+    let url = client.v2_url(&format!("/spaces/{space_id}/pages?limit=250"));
+    let pages = client.get_paginated_results(url, true).await?;
+    let tree: HashMap<String, (String, String)> = pages
+        .iter()
+        .map(|page| {
+            (
+                json_str(page, "id"),
+                (json_str(page, "title"), json_str(page, "parentId")),
+            )
+        })
+        .collect();
+
+    let bar = Progress::items(ctx, "pages", pages.len() as u64);
+    let mut per_page = stream::iter(pages.iter().map(|page| json_str(page, "id")))
+        .map(|page_id| {
+            let bar = bar.clone();
+            async move {
+                let url = client.v2_url(&format!("/pages/{page_id}/attachments?limit=250"));
+                let items = client
+                    .get_paginated_results(url, true)
+                    .await
+                    .with_context(|| format!("Failed to list attachments of page {page_id}"));
+                if let Some(bar) = &bar {
+                    bar.inc(1);
+                }
+                items.map(|items| (page_id, items))
+            }
+        })
+        .buffered(concurrency.max(1));
+
+    let mut out = Vec::new();
+    while let Some(res) = per_page.next().await {
+        let (page_id, items) = res?;
+        let title = tree.get(&page_id).map(|(title, _)| title.clone());
+        let path = page_path(&tree, &page_id);
+        for mut item in items {
+            if let Value::Object(obj) = &mut item {
+                obj.insert("pageId".to_string(), Value::String(page_id.clone()));
+                obj.insert(
+                    "pageTitle".to_string(),
+                    title.clone().unwrap_or_default().into(),
+                );
+                obj.insert("pagePath".to_string(), Value::String(path.clone()));
+            }
+            out.push(item);
+        }
+        if max.is_some_and(|max| out.len() >= max) {
+            break;
+        }
+    }
+    drop(per_page);
+    if let Some(bar) = bar {
+        bar.finish();
+    }
+    if let Some(max) = max {
+        out.truncate(max);
+    }
+    Ok(out)
+}
+
+/// Titles from the top of the space down to `page_id`, joined with " / ". `tree` maps page
+/// id to `(title, parent id)`; parents outside it (or a cycle) end the walk.
+fn page_path(tree: &HashMap<String, (String, String)>, page_id: &str) -> String {
+    let mut titles = Vec::new();
+    let mut seen = HashSet::new();
+    let mut current = page_id;
+    while let Some((title, parent)) = tree.get(current) {
+        if !seen.insert(current) {
+            break;
+        }
+        titles.push(title.as_str());
+        current = parent;
+    }
+    titles.reverse();
+    titles.join(" / ")
+}
+
+async fn attachment_get(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: AttachmentGetArgs,
+) -> Result<()> {
+    let url = client.v2_url(&format!("/attachments/{}", args.attachment));
+    let (json, _) = client.get_json(url).await?;
+    match args.output {
+        OutputFormat::Json => maybe_print_json(ctx, &json),
+        fmt => {
+            let rows = vec![
+                vec!["ID".to_string(), json_str(&json, "id")],
+                vec!["Title".to_string(), json_str(&json, "title")],
+                vec!["Type".to_string(), json_str(&json, "mediaType")],
+                vec![
+                    "Size".to_string(),
+                    human_size(json.get("fileSize").and_then(|v| v.as_i64()).unwrap_or(0)),
+                ],
+            ];
+            maybe_print_kv_fmt(ctx, fmt, rows);
+            Ok(())
+        }
+    }
+}
+
+async fn attachment_download(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: AttachmentDownloadArgs,
+) -> Result<()> {
+    let url = client.v2_url(&format!("/attachments/{}", args.attachment));
+    let (json, _) = client.get_json(url).await?;
+    let download = json
+        .get("downloadLink")
+        .and_then(|v| v.as_str())
+        .or_else(|| {
+            json.get("_links")
+                .and_then(|v| v.get("download"))
+                .and_then(|v| v.as_str())
+        })
+        .context("Missing download link")?;
+    let base = Url::parse(client.api_site_url())?;
+    let full_url = crate::download::attachment_download_url(&base, download)?;
+    let file_name = resolve_download_path(&args.dest, &json)?;
+
+    let progress = Progress::bytes(ctx, "download");
+
+    crate::download::download_to_file_with_retry(
+        client,
+        full_url,
+        &file_name,
+        &format!("attachment {}", args.attachment),
+        crate::download::DownloadToFileOptions {
+            retry: crate::download::DownloadRetry::default(),
+            progress: progress.as_ref(),
+            verbose: ctx.verbose,
+        },
+    )
+    .await?;
+
+    if let Some(progress) = progress {
+        progress.finish();
+    }
+
+    print_line(ctx, &format!("Downloaded to {}", file_name.display()));
+    Ok(())
+}
+
+#[cfg(feature = "write")]
+async fn attachment_upload(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: AttachmentUploadArgs,
+) -> Result<()> {
+    let page_id = resolve_page_id(client, &args.page).await?;
+    let existing = match args.if_exists {
+        Some(_) => existing_attachments(client, &page_id).await?,
+        None => HashMap::new(),
+    };
+    let plans = plan_uploads(&args.files, &existing, args.if_exists)?;
+
+    if ctx.dry_run {
+        for plan in &plans {
+            let bytes = std::fs::metadata(&plan.file).ok().map(|m| m.len());
+            let payload = |name: Option<&str>| json!({ "page": page_id, "file": plan.file, "name": name, "bytes": bytes });
+            let operation = match &plan.action {
+                UploadAction::Create { name } => {
+                    Operation::new("upload", "attachment", name).with_payload(payload(Some(name)))
+                }
+                UploadAction::Replace { id } => {
+                    Operation::new("update", "attachment", id).with_payload(payload(None))
+                }
+                UploadAction::Skip { .. } => continue,
+            };
+            crate::plan::record(operation);
+        }
+        if args.if_exists.is_none() {
+            let names: Vec<_> = args.files.iter().map(|f| f.display().to_string()).collect();
+            print_status(
+                ctx,
+                args.print_id,
+                &format!("Would upload {} to page {page_id}", names.join(", ")),
+            );
+            return Ok(());
+        }
+        for plan in &plans {
+            let file = plan.file.display();
+            let line = match &plan.action {
+                UploadAction::Create { name } if plan.renamed() => {
+                    format!("Would upload {file} to page {page_id} as {name}")
+                }
+                UploadAction::Create { .. } => format!("Would upload {file} to page {page_id}"),
+                UploadAction::Replace { id } => {
+                    format!("Would replace attachment {id} on page {page_id} with {file}")
+                }
+                UploadAction::Skip { id } => {
+                    format!("Would skip {file}: page {page_id} already has it as attachment {id}")
+                }
+            };
+            print_status(ctx, args.print_id, &line);
+        }
+        return Ok(());
+    }
+
+    let mut approved = Vec::new();
+    for plan in plans {
+        if matches!(plan.action, UploadAction::Skip { .. }) {
+            approved.push(plan);
+            continue;
+        }
+        let metadata = tokio::fs::metadata(&plan.file).await?;
+        let size = metadata.len();
+        if !args.yes
+            && confirm_settings().upload_needs_confirm(size)
+            && !confirm(&format!(
+                "Upload {} ({:.2} MB)?",
+                plan.file.display(),
+                size as f64 / 1_048_576.0
+            ))?
+        {
+            print_status(
+                ctx,
+                args.print_id,
+                &format!("Skipped {}.", plan.file.display()),
+            );
+            continue;
+        }
+        approved.push(plan);
+    }
+
+    if approved.is_empty() {
+        return Ok(());
+    }
+
+    let comment = args.comment.clone();
+    let sem = Arc::new(Semaphore::new(ctx.workers(UPLOAD_CONCURRENCY)));
+    let client = Arc::new(client.clone());
+    let mut tasks = JoinSet::new();
+    let mut ordered_results = Vec::new();
+
+    for (idx, plan) in approved.into_iter().enumerate() {
+        if matches!(plan.action, UploadAction::Skip { .. }) {
+            let attachment = existing.get(&plan.name()).cloned().unwrap_or_default();
+            ordered_results.push((idx, plan.outcome(), attachment));
+            continue;
+        }
+        let permit = sem.clone().acquire_owned().await?;
+        let client = client.clone();
+        let page_id = page_id.clone();
+        let comment = comment.clone();
+
+        tasks.spawn(async move {
+            let _permit = permit;
+            let result = match &plan.action {
+                UploadAction::Replace { id } => {
+                    client
+                        .update_attachment_data(&page_id, id, &plan.file, comment)
+                        .await?
+                }
+                UploadAction::Create { name } => {
+                    client
+                        .upload_attachment_as(&page_id, &plan.file, name, comment)
+                        .await?
+                }
+                UploadAction::Skip { .. } => unreachable!("skipped files are not uploaded"),
+            };
+            let attachment = result
+                .get("results")
+                .and_then(|v| v.as_array())
+                .and_then(|items| items.first())
+                .cloned()
+                .unwrap_or(result);
+            Ok::<_, anyhow::Error>((idx, plan.outcome(), attachment))
+        });
+    }
+
+    while let Some(res) = tasks.join_next().await {
+        match res {
+            Ok(Ok(result)) => ordered_results.push(result),
+            Ok(Err(err)) => {
+                tasks.abort_all();
+                while tasks.join_next().await.is_some() {}
+                return Err(err.context("Attachment upload failed"));
+            }
+            Err(join_err) => {
+                tasks.abort_all();
+                while tasks.join_next().await.is_some() {}
+                return Err(anyhow!("Attachment upload task failed: {join_err}"));
+            }
+        }
+    }
+
+    ordered_results.sort_by_key(|(idx, _, _)| *idx);
+
+    if args.print_id {
+        for (_, _, attachment) in &ordered_results {
+            print_created_id(ctx, &json_str(attachment, "id"))?;
+        }
+        return Ok(());
+    }
+
+    match args.output {
+        OutputFormat::Json => {
+            let all_attachments: Vec<_> = ordered_results.into_iter().map(|(_, _, a)| a).collect();
+            maybe_print_json(ctx, &all_attachments)?
+        }
+        _ => {
+            for (_, action, attachment) in &ordered_results {
+                let mut rows = vec![
+                    vec!["ID".to_string(), json_str(attachment, "id")],
+                    vec!["Title".to_string(), json_str(attachment, "title")],
+                ];
+                if args.if_exists.is_some() {
+                    rows.push(vec!["Action".to_string(), action.to_string()]);
+                }
+                maybe_print_kv(ctx, rows);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// What `attachment upload` does with one file.
+#[cfg(feature = "write")]
+#[derive(Debug, PartialEq, Eq)]
+enum UploadAction {
+    /// Attach the file as a new attachment called `name`.
+    Create { name: String },
+    /// Upload the file as a new version of attachment `id`.
+    Replace { id: String },
+    /// Leave attachment `id` as it is.
+    Skip { id: String },
+}
+
+#[cfg(feature = "write")]
+#[derive(Debug, PartialEq, Eq)]
+struct UploadPlan {
+    file: PathBuf,
+    action: UploadAction,
+}
+
+#[cfg(feature = "write")]
+impl UploadPlan {
+    /// The file's own name.
+    fn name(&self) -> String {
+        self.file
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    fn renamed(&self) -> bool {
+        matches!(&self.action, UploadAction::Create { name } if *name != self.name())
+    }
+
+    /// The `Action` reported for the file.
+    fn outcome(&self) -> &'static str {
+        match self.action {
+            UploadAction::Create { .. } if self.renamed() => "renamed",
+            UploadAction::Create { .. } => "uploaded",
+            UploadAction::Replace { .. } => "replaced",
+            UploadAction::Skip { .. } => "skipped",
+        }
+    }
+}
+
+/// The page's current attachments, keyed by file name.
+#[cfg(feature = "write")]
+async fn existing_attachments(client: &ApiClient, page_id: &str) -> Result<HashMap<String, Value>> {
+    let url = client.v2_url(&format!("/pages/{page_id}/attachments?limit=250"));
+    let items = client
+        .get_paginated_results(url, true)
+        .await
+        .with_context(|| format!("Failed to list the attachments of page {page_id}"))?;
+    Ok(items
+        .into_iter()
+        .map(|item| (json_str(&item, "title"), item))
+        .collect())
+}
+
+/// Decide what to do with each file given the page's `existing` attachments. Without
+/// `--if-exists` every file is uploaded as is. Renamed files avoid both the existing names
+/// and the names given to earlier files of the same upload.
+#[cfg(feature = "write")]
+fn plan_uploads(
+    files: &[PathBuf],
+    existing: &HashMap<String, Value>,
+    if_exists: Option<IfExists>,
+) -> Result<Vec<UploadPlan>> {
+    let mut taken: HashSet<String> = existing.keys().cloned().collect();
+    let mut plans = Vec::with_capacity(files.len());
+    for file in files {
+        let name = file
+            .file_name()
+            .and_then(|name| name.to_str())
+            .with_context(|| format!("Invalid file name: {}", file.display()))?
+            .to_string();
+        let existing_id = existing.get(&name).map(|item| json_str(item, "id"));
+        let action = match (if_exists, existing_id) {
+            (Some(IfExists::Skip), Some(id)) => UploadAction::Skip { id },
+            (Some(IfExists::Replace), Some(id)) => UploadAction::Replace { id },
+            (Some(IfExists::Rename), _) if taken.contains(&name) => UploadAction::Create {
+                name: free_name(&name, &taken),
+            },
+            _ => UploadAction::Create { name },
+        };
+        if let UploadAction::Create { name } = &action {
+            taken.insert(name.clone());
+        }
+        plans.push(UploadPlan {
+            file: file.clone(),
+            action,
+        });
+    }
+    Ok(plans)
+}
+
+/// `name` with the lowest ` (n)` suffix, before the extension, that isn't `taken`.
+#[cfg(feature = "write")]
+fn free_name(name: &str, taken: &HashSet<String>) -> String {
+    let (stem, ext) = match name.rfind('.') {
+        Some(dot) if dot > 0 => name.split_at(dot),
+        _ => (name, ""),
+    };
+    (1..)
+        .map(|n| format!("{stem} ({n}){ext}"))
+        .find(|candidate| !taken.contains(candidate))
+        .expect("an unused name")
+}
+
+#[cfg(feature = "write")]
+async fn attachment_delete(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: AttachmentDeleteArgs,
+) -> Result<()> {
+    let action = if args.purge { "purge" } else { "delete" };
+
+    if ctx.dry_run {
+        crate::plan::record(Operation::new(action, "attachment", &args.attachment));
+        return print_write_action_result(
+            ctx,
+            args.output,
+            &format!("Would {action} attachment {}", args.attachment),
+            &json!({
+                "dryRun": true,
+                "action": action,
+                "deleted": false,
+                "id": args.attachment,
+            }),
+            vec![
+                vec!["DryRun".to_string(), "true".to_string()],
+                vec!["Action".to_string(), action.to_string()],
+                vec!["Deleted".to_string(), "false".to_string()],
+                vec!["ID".to_string(), args.attachment.clone()],
+            ],
+        );
+    }
+
+    if !args.yes && !confirm_delete(&format!("Delete attachment {}?", args.attachment))? {
+        print_line(ctx, "Cancelled.");
+        return Ok(());
+    }
+
+    let mut url = client.v2_url(&format!("/attachments/{}", args.attachment));
+    if args.purge {
+        url.push_str("?purge=true");
+    }
+    client.delete(url).await?;
+
+    let past = if args.purge { "Purged" } else { "Deleted" };
+    print_write_action_result(
+        ctx,
+        args.output,
+        &format!("{past} attachment {}", args.attachment),
+        &json!({
+            "action": action,
+            "deleted": true,
+            "id": args.attachment,
+        }),
+        vec![
+            vec!["Action".to_string(), action.to_string()],
+            vec!["Deleted".to_string(), "true".to_string()],
+            vec!["ID".to_string(), args.attachment],
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_path_walks_up_to_the_space_root() {
+        let tree: HashMap<String, (String, String)> =
+            [("1", "Home", ""), ("2", "Team", "1"), ("3", "Runbook", "2")]
+                .into_iter()
+                .map(|(id, title, parent)| {
+                    (id.to_string(), (title.to_string(), parent.to_string()))
+                })
+                .collect();
+        assert_eq!(page_path(&tree, "3"), "Home / Team / Runbook");
+        assert_eq!(page_path(&tree, "1"), "Home");
+        assert_eq!(page_path(&tree, "9"), "");
+    }
+
+    #[test]
+    #[cfg(feature = "write")]
+    fn uploads_are_planned_against_existing_names() {
+        let existing: HashMap<String, Value> =
+            [("a.txt", "10"), ("a (1).txt", "11"), ("notes", "12")]
+                .into_iter()
+                .map(|(name, id)| (name.to_string(), json!({ "id": id, "title": name })))
+                .collect();
+        let files: Vec<PathBuf> = ["dir/a.txt", "a.txt", "notes", "b.txt"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let actions = |mode| {
+            plan_uploads(&files, &existing, mode)
+                .unwrap()
+                .into_iter()
+                .map(|plan| plan.action)
+                .collect::<Vec<_>>()
+        };
+        let create = |name: &str| UploadAction::Create {
+            name: name.to_string(),
+        };
+
+        assert_eq!(
+            actions(None),
+            vec![
+                create("a.txt"),
+                create("a.txt"),
+                create("notes"),
+                create("b.txt")
+            ]
+        );
+        assert_eq!(
+            actions(Some(IfExists::Skip))[..3],
+            [
+                UploadAction::Skip { id: "10".into() },
+                UploadAction::Skip { id: "10".into() },
+                UploadAction::Skip { id: "12".into() },
+            ]
+        );
+        assert_eq!(
+            actions(Some(IfExists::Replace))[2..],
+            [UploadAction::Replace { id: "12".into() }, create("b.txt")]
+        );
+        assert_eq!(
+            actions(Some(IfExists::Rename)),
+            vec![
+                create("a (2).txt"),
+                create("a (3).txt"),
+                create("notes (1)"),
+                create("b.txt"),
+            ]
+        );
+    }
+}
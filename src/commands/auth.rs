@@ -1,22 +1,39 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow};
+use base64::Engine;
 use confcli::auth::AuthMethod;
 use confcli::client::ApiClient;
 use confcli::config::{
-    Config, default_api_path_v1, derive_api_path_v2, ensure_leading_slash,
-    normalize_site_url_and_origin,
+    Config, DEFAULT_PROFILE, PASSPHRASE_ENV, default_api_path_v1, derive_api_path_v2,
+    ensure_leading_slash, gateway_api_bases, gateway_cloud_id, normalize_site_url_and_origin,
 };
+use confcli::json_util::json_str;
+use confcli::output::OutputFormat;
 use dialoguer::{Input, Password};
+use futures_util::future::join_all;
+use serde::Serialize;
+use serde_json::{Value, json};
 
-use crate::cli::{AuthCommand, AuthLoginArgs};
-use crate::context::AppContext;
-use crate::helpers::print_line;
+use crate::cli::{
+    AuthCommand, AuthListArgs, AuthLoginArgs, AuthStatusArgs, AuthSwitchArgs, TokenCmdAuth,
+};
+use crate::context::{AppContext, active_profile, client_cert, client_cert_flags, stored_profile};
+use crate::helpers::{maybe_print_json, maybe_print_rows, print_line};
+use crate::lifecycle::{format_timestamp, now_secs};
 
 pub async fn handle(ctx: &AppContext, cmd: AuthCommand) -> Result<()> {
     match cmd {
-        AuthCommand::Login(args) => auth_login(ctx, args).await,
-        AuthCommand::Status => auth_status(ctx).await,
+        AuthCommand::Login(args) => auth_login(ctx, *args).await,
+        AuthCommand::Status(args) => auth_status(ctx, args).await,
+        AuthCommand::List(args) => auth_list(ctx, args),
+        AuthCommand::Switch(args) => auth_switch(ctx, args),
+        AuthCommand::Doctor(args) => crate::commands::doctor::handle(ctx, args).await,
         AuthCommand::Logout => {
-            Config::clear()?;
+            let profile = stored_profile();
+            Config::clear_profile(&profile)?;
+            // A switched-to profile that is gone would leave every command failing.
+            if Config::current_profile()? == profile {
+                Config::set_current_profile(DEFAULT_PROFILE)?;
+            }
             print_line(ctx, "Logged out.");
             Ok(())
         }
@@ -48,15 +65,38 @@ async fn auth_login(ctx: &AppContext, args: AuthLoginArgs) -> Result<()> {
         .unwrap_or_else(|| derive_api_path_v2(&api_path_v1));
     let api_base_v2 = format!("{}{}", origin, api_path_v2.trim_end_matches('/'));
 
-    let auth = if let Some(token) = args.bearer {
+    // Filled in from `--token-cmd` on use; never stored.
+    let token_cmd_auth = args.token_cmd.as_ref().map(|_| args.auth_type);
+    let auth = if token_cmd_auth == Some(TokenCmdAuth::Pat) {
+        AuthMethod::Pat {
+            token: String::new(),
+        }
+    } else if token_cmd_auth == Some(TokenCmdAuth::Bearer) {
+        AuthMethod::Bearer {
+            token: String::new(),
+        }
+    } else if let Some(token) = args.pat {
+        AuthMethod::Pat { token }
+    } else if let Some(token) = args.bearer {
         AuthMethod::Bearer { token }
+    } else if let Some(username) = args.username {
+        let password = if args.token_cmd.is_some() {
+            String::new()
+        } else if let Some(password) = args.password {
+            password
+        } else {
+            Password::new().with_prompt("Password").interact()?
+        };
+        AuthMethod::Cookie { username, password }
     } else {
         let email = if let Some(email) = args.email {
             email
         } else {
             Input::new().with_prompt("Email").interact_text()?
         };
-        let token = if let Some(token) = args
+        let token = if args.token_cmd.is_some() {
+            String::new()
+        } else if let Some(token) = args
             .token
             .or_else(|| std::env::var("CONFLUENCE_API_TOKEN").ok())
         {
@@ -70,91 +110,800 @@ async fn auth_login(ctx: &AppContext, args: AuthLoginArgs) -> Result<()> {
         AuthMethod::Basic { email, token }
     };
 
-    let config = Config {
+    // Logging in again must not silently drop a read-only guard or the confirmation,
+    // User-Agent, theme, or notification settings.
+    let profile = stored_profile();
+    let existing = Config::load_profile(&profile).ok();
+    let (client_cert_path, client_key_path) = match client_cert_flags() {
+        Some((cert, key)) => (
+            Some(std::path::absolute(cert)?),
+            key.map(std::path::absolute).transpose()?,
+        ),
+        None => existing
+            .as_ref()
+            .map(|existing| (existing.client_cert.clone(), existing.client_key.clone()))
+            .unwrap_or_default(),
+    };
+    let encrypt = if args.encrypt || args.no_encrypt {
+        args.encrypt
+    } else {
+        args.token_cmd.is_none()
+            && !args.keyring
+            && existing
+                .as_ref()
+                .is_some_and(|existing| existing.encrypted_token.is_some())
+    };
+    let mut config = Config {
         site_url,
         api_base_v1,
         api_base_v2,
         auth,
+        read_only: existing.as_ref().is_some_and(|existing| existing.read_only),
+        confirm: existing
+            .as_ref()
+            .map(|existing| existing.confirm)
+            .unwrap_or_default(),
+        user_agent: existing
+            .as_ref()
+            .map(|existing| existing.user_agent.clone())
+            .unwrap_or_default(),
+        theme: existing
+            .as_ref()
+            .map(|existing| existing.theme)
+            .unwrap_or_default(),
+        max_concurrent_requests: existing
+            .as_ref()
+            .and_then(|existing| existing.max_concurrent_requests),
+        keyring: if args.keyring || args.no_keyring {
+            args.keyring
+        } else {
+            existing.as_ref().is_some_and(|existing| existing.keyring)
+        },
+        // Filled in below, once the token is known to work.
+        encrypted_token: None,
+        notify: existing.map(|existing| existing.notify).unwrap_or_default(),
+        token_cmd: args.token_cmd,
+        client_cert: client_cert_path,
+        client_key: client_key_path,
     };
-    let client = ApiClient::new(
+    let tls_identity = client_cert(&config)?;
+    let mut auth = config.clone();
+    auth.resolve_token()?;
+    let mut client = ApiClient::new(
         config.site_url.clone(),
         config.api_base_v1.clone(),
         config.api_base_v2.clone(),
-        config.auth.clone(),
+        auth.auth.clone(),
         ctx.verbose,
-    )?;
+    )?
+    .with_client_cert(tls_identity.clone())?;
 
-    // Validate credentials. Prefer v2; fall back to v1 for Server/DC.
-    let v2 = client.v2_url("/spaces?limit=1");
-    let v1 = client.v1_url("/space?limit=1");
-    if let Err(v2_err) = client.get_json(v2).await {
+    // A gateway URL given as the site is only an API base; web links need the site itself.
+    let gateway_site = gateway_cloud_id(&config.site_url).is_some();
+    if args.cloud_id.is_some() || args.gateway || gateway_site {
+        let cloud_id = match args.cloud_id.filter(|id| !id.trim().is_empty()) {
+            Some(cloud_id) if !gateway_site => cloud_id.trim().to_string(),
+            cloud_id => {
+                let identity =
+                    site_identity(&client, &config.site_url, &config.api_base_v1, &auth.auth).await;
+                if gateway_site {
+                    let Some(url) = identity.url else {
+                        return Err(anyhow!(
+                            "Cannot tell which site {} belongs to; pass the site URL with --domain and the cloud id with --cloud-id",
+                            config.site_url
+                        ));
+                    };
+                    config.site_url = normalize_site_url_and_origin(&url)?.0;
+                }
+                cloud_id
+                    .or(identity.cloud_id)
+                    .context("Could not look up the site's cloud id; pass it with --cloud-id")?
+            }
+        };
+        (config.api_base_v1, config.api_base_v2) = gateway_api_bases(&cloud_id);
+        client = ApiClient::new(
+            config.site_url.clone(),
+            config.api_base_v1.clone(),
+            config.api_base_v2.clone(),
+            auth.auth,
+            ctx.verbose,
+        )?
+        .with_client_cert(tls_identity)?;
+    }
+
+    // Validate credentials. PATs and session logins only exist on Server/DC, which has no v2
+    // API and answers unknown credentials as the anonymous user where anonymous access is on,
+    // so ask whose they are. Other credentials: prefer v2; fall back to v1 for Server/DC.
+    let server_credentials = match config.auth {
+        AuthMethod::Pat { .. } => Some("the personal access token"),
+        AuthMethod::Cookie { .. } => Some("the session login"),
+        _ => None,
+    };
+    if matches!(config.auth, AuthMethod::Cookie { .. }) {
+        // A session of the previous login may belong to another user; start a new one.
+        let session = Config::session_path(&profile)?;
+        if session.exists() {
+            std::fs::remove_file(&session)
+                .with_context(|| format!("Failed to delete session: {}", session.display()))?;
+        }
+        client = client.with_session_file(session);
+    }
+    if let Some(credentials) = server_credentials {
+        let (user, _) = client
+            .get_json(client.v1_url("/user/current"))
+            .await
+            .with_context(|| format!("Failed to validate {credentials}"))?;
+        if json_str(&user, "type") == "anonymous" {
+            return Err(anyhow!(
+                "Failed to validate {credentials}: the site treated it as anonymous"
+            ));
+        }
+    } else if let Err(v2_err) = client.get_json(client.v2_url("/spaces?limit=1")).await {
+        let v1 = client.v1_url("/space?limit=1");
         client
             .get_json(v1)
             .await
             .with_context(|| format!("Failed to validate credentials (v2 error: {v2_err})"))?;
     }
-    config.save()?;
-    print_line(ctx, "Saved credentials.");
+    if encrypt {
+        let passphrase = match std::env::var(PASSPHRASE_ENV) {
+            Ok(passphrase) => passphrase,
+            Err(_) => Password::new()
+                .with_prompt("Passphrase to encrypt the token")
+                .with_confirmation("Confirm passphrase", "Passphrases do not match")
+                .interact()?,
+        };
+        config.encrypt_token(&passphrase)?;
+    } else if matches!(config.auth, AuthMethod::Cookie { .. })
+        && !config.keyring
+        && config.token_cmd.is_none()
+    {
+        tracing::warn!(
+            "The password is stored in plaintext in the profile file; use --keyring or --encrypt to protect it"
+        );
+    }
+    config.save_profile(&profile)?;
+    if profile == DEFAULT_PROFILE {
+        print_line(ctx, "Saved credentials.");
+    } else {
+        print_line(ctx, &format!("Saved credentials to profile '{profile}'."));
+    }
     Ok(())
 }
 
-async fn auth_status(ctx: &AppContext) -> Result<()> {
-    if let Some(config) = Config::from_env()? {
-        let client = ApiClient::new(
+async fn auth_status(ctx: &AppContext, args: AuthStatusArgs) -> Result<()> {
+    if args.all_profiles {
+        return all_profiles_status(ctx, args.output).await;
+    }
+    let profile = stored_profile();
+    let health = if ctx.anonymous {
+        Some(
+            check(
+                ctx,
+                &Config::anonymous(active_profile())?,
+                None,
+                "--anonymous".to_string(),
+            )
+            .await,
+        )
+    } else if active_profile().is_some() {
+        check_profile(ctx, &profile).await?
+    } else if let Some(config) = Config::from_env()? {
+        Some(check(ctx, &config, None, "env".to_string()).await)
+    } else {
+        check_profile(ctx, &profile).await?
+    };
+    let Some(health) = health else {
+        if profile == DEFAULT_PROFILE {
+            print_line(ctx, "Not logged in.");
+        } else {
+            print_line(ctx, &format!("Not logged in to profile '{profile}'."));
+        }
+        return Ok(());
+    };
+
+    match args.output {
+        OutputFormat::Json => maybe_print_json(ctx, &health)?,
+        _ => {
+            let source = match health.source.as_str() {
+                "env" => "from env".to_string(),
+                path => format!("config: {path}"),
+            };
+            let line = if health.source == "--anonymous" {
+                format!("Anonymous access to {}", health.site)
+            } else {
+                format!(
+                    "Logged in to {} using {} auth{}{} ({source})",
+                    health.site,
+                    health.auth,
+                    if health.keyring {
+                        ", token in OS keyring"
+                    } else if health.encrypted {
+                        ", token encrypted"
+                    } else {
+                        ""
+                    },
+                    if health.read_only { ", read-only" } else { "" },
+                )
+            };
+            print_line(ctx, &line);
+            if let Some(account) = &health.account {
+                print_line(ctx, &format!("Account: {}", account.describe()));
+            }
+            if let Some(cloud_id) = &health.cloud_id {
+                print_line(ctx, &format!("Cloud ID: {cloud_id}"));
+            }
+            if !health.scopes.is_empty() {
+                print_line(ctx, &format!("Scopes: {}", health.scopes.join(", ")));
+            }
+            if let Some(expires) = health.expires {
+                print_line(
+                    ctx,
+                    &format!("Expires: {}", describe_expiry(expires, now_secs())),
+                );
+            }
+        }
+    }
+    match health.error {
+        Some(error) => Err(anyhow!("Failed to validate auth: {error}")),
+        None => Ok(()),
+    }
+}
+
+/// One stored profile as `auth list` shows it.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfileEntry {
+    name: String,
+    site: String,
+    auth: &'static str,
+    read_only: bool,
+    keyring: bool,
+    encrypted: bool,
+    /// Used without `--profile` (see `auth switch`).
+    current: bool,
+    error: Option<String>,
+}
+
+fn auth_list(ctx: &AppContext, args: AuthListArgs) -> Result<()> {
+    let current = stored_profile();
+    // Only the files: listing must not unlock the OS keyring.
+    let entries: Vec<ProfileEntry> = Config::profile_names()?
+        .into_iter()
+        .map(|name| {
+            let config = Config::load_profile_file(&name);
+            ProfileEntry {
+                current: name == current,
+                site: config
+                    .as_ref()
+                    .map(|config| config.site_url.clone())
+                    .unwrap_or_default(),
+                auth: config
+                    .as_ref()
+                    .map(|config| config.auth.description())
+                    .unwrap_or(""),
+                read_only: config.as_ref().is_ok_and(|config| config.read_only),
+                keyring: config.as_ref().is_ok_and(|config| config.keyring),
+                encrypted: config
+                    .as_ref()
+                    .is_ok_and(|config| config.encrypted_token.is_some()),
+                error: config.err().map(|err| format!("{err:#}")),
+                name,
+            }
+        })
+        .collect();
+
+    match args.output {
+        OutputFormat::Json => maybe_print_json(ctx, &entries)?,
+        fmt => {
+            let rows = entries
+                .iter()
+                .map(|entry| {
+                    let mut notes = Vec::new();
+                    if entry.keyring {
+                        notes.push("token in OS keyring".to_string());
+                    }
+                    if entry.encrypted {
+                        notes.push("token encrypted".to_string());
+                    }
+                    if entry.read_only {
+                        notes.push("read-only".to_string());
+                    }
+                    if let Some(error) = &entry.error {
+                        notes.push(error.clone());
+                    }
+                    vec![
+                        if entry.current { "*" } else { "" }.to_string(),
+                        entry.name.clone(),
+                        entry.site.clone(),
+                        entry.auth.to_string(),
+                        notes.join(", "),
+                    ]
+                })
+                .collect();
+            maybe_print_rows(
+                ctx,
+                fmt,
+                &["Current", "Profile", "Site", "Auth", "Notes"],
+                rows,
+            );
+        }
+    }
+    Ok(())
+}
+
+fn auth_switch(ctx: &AppContext, args: AuthSwitchArgs) -> Result<()> {
+    Config::set_current_profile(&args.name)?;
+    let site = Config::load_profile_file(&args.name)
+        .map(|config| format!(" ({})", config.site_url))
+        .unwrap_or_default();
+    print_line(ctx, &format!("Switched to profile '{}'{site}.", args.name));
+    if Config::from_env().is_ok_and(|config| config.is_some()) {
+        tracing::warn!(
+            "CONFLUENCE_* credentials in the environment still take precedence; unset them to use the profile"
+        );
+    }
+    Ok(())
+}
+
+/// [`check`] a stored profile; `None` when it has no file.
+async fn check_profile(ctx: &AppContext, name: &str) -> Result<Option<AuthHealth>> {
+    let path = Config::profile_path(name)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let config = Config::load_profile(name)?;
+    let profile = (name != DEFAULT_PROFILE).then(|| name.to_string());
+    Ok(Some(
+        check(ctx, &config, profile, path.display().to_string()).await,
+    ))
+}
+
+/// Check every stored profile at once; fails when any of them is unhealthy.
+async fn all_profiles_status(ctx: &AppContext, output: OutputFormat) -> Result<()> {
+    let names = Config::profile_names()?;
+    if names.is_empty() {
+        return Err(anyhow!("Not logged in. Run confcli auth login"));
+    }
+    let checks = names.iter().map(|name| async move {
+        match Config::load_profile(name) {
+            Ok(config) => {
+                let source = Config::profile_path(name)
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_default();
+                check(ctx, &config, Some(name.clone()), source).await
+            }
+            Err(err) => AuthHealth::failed(name, format!("{err:#}")),
+        }
+    });
+    let results = join_all(checks).await;
+    let unhealthy = results
+        .iter()
+        .filter(|health| health.error.is_some())
+        .count();
+
+    match output {
+        OutputFormat::Json => maybe_print_json(ctx, &results)?,
+        fmt => {
+            let now = now_secs();
+            let rows = results
+                .iter()
+                .map(|health| {
+                    vec![
+                        health.profile.clone().unwrap_or_default(),
+                        health.site.clone(),
+                        health.auth.to_string(),
+                        health
+                            .account
+                            .as_ref()
+                            .map(Account::describe)
+                            .unwrap_or_default(),
+                        health
+                            .expires
+                            .map(|expires| describe_expiry(expires, now))
+                            .unwrap_or_default(),
+                        match &health.error {
+                            Some(error) => format!("{}: {error}", health.status),
+                            None => health.status.to_string(),
+                        },
+                    ]
+                })
+                .collect();
+            maybe_print_rows(
+                ctx,
+                fmt,
+                &["Profile", "Site", "Auth", "Account", "Expires", "Status"],
+                rows,
+            );
+        }
+    }
+    if unhealthy > 0 {
+        return Err(anyhow!(
+            "{unhealthy} of {} profile(s) failed the check",
+            results.len()
+        ));
+    }
+    Ok(())
+}
+
+/// What `auth status` found out about one set of credentials.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthHealth {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    profile: Option<String>,
+    site: String,
+    auth: &'static str,
+    /// `env`, `--anonymous`, or the config file the credentials came from.
+    source: String,
+    read_only: bool,
+    /// The token is kept in the OS keyring.
+    keyring: bool,
+    /// The token is encrypted with a passphrase.
+    encrypted: bool,
+    /// `ok`, `expired`, `anonymous`, or `error`.
+    status: &'static str,
+    account: Option<Account>,
+    /// The Cloud site's id; `None` on Server/Data Center.
+    cloud_id: Option<String>,
+    /// OAuth scopes; empty for API tokens and tokens that don't list them.
+    scopes: Vec<String>,
+    /// OAuth expiry in seconds since 1970-01-01 (UTC).
+    #[serde(serialize_with = "serialize_timestamp")]
+    expires: Option<i64>,
+    error: Option<String>,
+}
+
+impl AuthHealth {
+    fn failed(profile: &str, error: String) -> Self {
+        Self {
+            profile: Some(profile.to_string()),
+            site: String::new(),
+            auth: "",
+            source: String::new(),
+            read_only: false,
+            keyring: false,
+            encrypted: false,
+            status: "error",
+            account: None,
+            cloud_id: None,
+            scopes: Vec::new(),
+            expires: None,
+            error: Some(error),
+        }
+    }
+}
+
+fn serialize_timestamp<S: serde::Serializer>(
+    expires: &Option<i64>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    expires.map(format_timestamp).serialize(serializer)
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Account {
+    id: String,
+    name: String,
+    email: String,
+}
+
+impl Account {
+    /// `Name <email> (id)`, leaving out what the site didn't share.
+    fn describe(&self) -> String {
+        let mut out = self.name.clone();
+        if !self.email.is_empty() {
+            out.push_str(&format!(" <{}>", self.email));
+        }
+        if !self.id.is_empty() {
+            out.push_str(&format!(" ({})", self.id));
+        }
+        out
+    }
+}
+
+/// Look at the token, then ask the site who it belongs to.
+async fn check(
+    ctx: &AppContext,
+    config: &Config,
+    profile: Option<String>,
+    source: String,
+) -> AuthHealth {
+    let mut config = config.clone();
+    let resolved = config.resolve_token();
+    let config = &config;
+    let claims = token_claims(&config.auth);
+    let mut health = AuthHealth {
+        profile,
+        site: config.site_url.clone(),
+        auth: config.auth.description(),
+        source,
+        read_only: config.read_only,
+        keyring: config.keyring,
+        encrypted: config.encrypted_token.is_some(),
+        status: "ok",
+        account: None,
+        cloud_id: None,
+        scopes: claims.as_ref().map(token_scopes).unwrap_or_default(),
+        expires: claims
+            .as_ref()
+            .and_then(|claims| claims.get("exp"))
+            .and_then(Value::as_i64),
+        error: None,
+    };
+    if let Err(err) = resolved {
+        health.status = "error";
+        health.error = Some(format!("{err:#}"));
+        return health;
+    }
+    if let Some(expires) = health.expires.filter(|expires| *expires <= now_secs()) {
+        health.status = "expired";
+        health.error = Some(format!("token expired {}", format_timestamp(expires)));
+        return health;
+    }
+    let client = match client_cert(config).and_then(|client_cert| {
+        ApiClient::new(
             config.site_url.clone(),
             config.api_base_v1.clone(),
             config.api_base_v2.clone(),
             config.auth.clone(),
             ctx.verbose,
-        )?;
-        let v2 = client.v2_url("/spaces?limit=1");
-        let v1 = client.v1_url("/space?limit=1");
-        if let Err(v2_err) = client.get_json(v2).await {
-            client
-                .get_json(v1)
-                .await
-                .with_context(|| format!("Failed to validate auth (v2 error: {v2_err})"))?;
-        }
-        print_line(
-            ctx,
-            &format!(
-                "Logged in to {} using {} auth (from env)",
-                config.site_url,
-                config.auth.description()
-            ),
+        )?
+        .with_client_cert(client_cert)
+    }) {
+        Ok(client) => client,
+        Err(err) => {
+            health.status = "error";
+            health.error = Some(format!("{err:#}"));
+            return health;
+        }
+    };
+    let user = client
+        .get_json(client.v1_url("/user/current"))
+        .await
+        .map(|(user, _)| user);
+    match user {
+        Ok(user)
+            if json_str(&user, "type") == "anonymous"
+                && !matches!(config.auth, AuthMethod::Anonymous) =>
+        {
+            health.status = "anonymous";
+            health.error = Some("requests are anonymous; the credentials were not accepted".into());
+        }
+        Ok(user) => {
+            let id = [json_str(&user, "accountId"), json_str(&user, "username")]
+                .into_iter()
+                .find(|id| !id.is_empty())
+                .unwrap_or_default();
+            health.account = Some(Account {
+                id,
+                name: json_str(&user, "displayName"),
+                email: json_str(&user, "email"),
+            });
+            let identity =
+                site_identity(&client, &config.site_url, &config.api_base_v1, &config.auth).await;
+            health.cloud_id = identity.cloud_id;
+            if health.scopes.is_empty() {
+                health.scopes = identity.scopes;
+            }
+        }
+        Err(err) => {
+            health.status = "error";
+            health.error = Some(format!("{err:#}"));
+        }
+    }
+    health
+}
+
+/// Where an OAuth token lists the sites it was granted, with their cloud ids and scopes.
+const ACCESSIBLE_RESOURCES_URL: &str = "https://api.atlassian.com/oauth/token/accessible-resources";
+
+/// Issuers (`iss`) of Atlassian's OAuth 2.0 (3LO) access tokens.
+const ATLASSIAN_ISSUERS: &[&str] = &[
+    "https://auth.atlassian.com",
+    "https://atlassian-account-prod.pus2.auth0.com",
+];
+
+/// Whether `iss` is one of [`ATLASSIAN_ISSUERS`], trailing slash or not.
+fn atlassian_issuer(iss: &str) -> bool {
+    ATLASSIAN_ISSUERS.contains(&iss.trim_end_matches('/'))
+}
+
+/// What Atlassian Cloud knows a site as; empty on Server/Data Center.
+#[derive(Debug, Default)]
+struct SiteIdentity {
+    cloud_id: Option<String>,
+    /// The site's URL as the OAuth accessible resources list it.
+    url: Option<String>,
+    /// Scopes granted to an OAuth token on the site.
+    scopes: Vec<String>,
+}
+
+/// Look up the site's cloud id (already known when the API goes through the gateway) and,
+/// for OAuth tokens, the scopes granted on it. Best effort.
+async fn site_identity(
+    client: &ApiClient,
+    site_url: &str,
+    api_base: &str,
+    auth: &AuthMethod,
+) -> SiteIdentity {
+    let mut identity = SiteIdentity {
+        cloud_id: gateway_cloud_id(api_base)
+            .or_else(|| gateway_cloud_id(site_url))
+            .map(str::to_string),
+        ..SiteIdentity::default()
+    };
+    // Only Atlassian's own tokens go to api.atlassian.com; a Server/Data Center bearer token
+    // must not leave its site.
+    let atlassian_oauth = matches!(auth, AuthMethod::Bearer { .. })
+        && (identity.cloud_id.is_some()
+            || token_claims(auth)
+                .is_some_and(|claims| atlassian_issuer(&json_str(&claims, "iss"))));
+    if atlassian_oauth {
+        match client.get_json(ACCESSIBLE_RESOURCES_URL.to_string()).await {
+            Ok((resources, _)) => {
+                if let Some(resource) =
+                    granted_resource(site_url, identity.cloud_id.as_deref(), &resources)
+                {
+                    identity.cloud_id = Some(json_str(resource, "id"));
+                    identity.url = Some(json_str(resource, "url")).filter(|url| !url.is_empty());
+                    identity.scopes = token_scopes(&json!({ "scp": resource["scopes"] }));
+                    return identity;
+                }
+            }
+            Err(err) => tracing::debug!("No OAuth accessible resources: {err:#}"),
+        }
+    }
+    if identity.cloud_id.is_some() {
+        return identity;
+    }
+    let Ok((_, origin)) = normalize_site_url_and_origin(site_url) else {
+        return identity;
+    };
+    match client.get_json(format!("{origin}/_edge/tenant_info")).await {
+        Ok((info, _)) => {
+            identity.cloud_id = Some(json_str(&info, "cloudId")).filter(|id| !id.is_empty());
+        }
+        Err(err) => tracing::debug!("No Cloud tenant info: {err:#}"),
+    }
+    identity
+}
+
+/// The accessible resource for a site: the one with its cloud id, or at its origin.
+fn granted_resource<'a>(
+    site_url: &str,
+    cloud_id: Option<&str>,
+    resources: &'a Value,
+) -> Option<&'a Value> {
+    let origin = normalize_site_url_and_origin(site_url)
+        .map(|(_, origin)| origin)
+        .unwrap_or_default();
+    resources.as_array()?.iter().find(|resource| {
+        let url = json_str(resource, "url");
+        cloud_id.is_some_and(|id| json_str(resource, "id") == id)
+            || (!url.is_empty() && url.trim_end_matches('/') == origin)
+    })
+}
+
+/// The claims of an OAuth access token, when it is a JWT (Atlassian's are); API tokens and
+/// opaque bearer tokens have none.
+pub(crate) fn token_claims(auth: &AuthMethod) -> Option<Value> {
+    let AuthMethod::Bearer { token } = auth else {
+        return None;
+    };
+    let payload = token.split('.').nth(1)?;
+    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// Scopes from a space-separated `scope` claim or a `scp` array.
+pub(crate) fn token_scopes(claims: &Value) -> Vec<String> {
+    if let Some(scope) = claims.get("scope").and_then(Value::as_str) {
+        return scope.split_whitespace().map(str::to_string).collect();
+    }
+    claims
+        .get("scp")
+        .and_then(Value::as_array)
+        .map(|scopes| {
+            scopes
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// `2026-10-18T12:00:00Z (in 3h 20m)`, or `(expired)`.
+fn describe_expiry(expires: i64, now: i64) -> String {
+    let left = expires - now;
+    let relative = match left {
+        ..=0 => "expired".to_string(),
+        1..3600 => format!("in {}m", (left + 59) / 60),
+        3600..86_400 => format!("in {}h {:02}m", left / 3600, left % 3600 / 60),
+        _ => format!("in {}d", left / 86_400),
+    };
+    format!("{} ({relative})", format_timestamp(expires))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jwt(claims: Value) -> String {
+        let engine = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+        format!(
+            "{}.{}.sig",
+            engine.encode(r#"{"alg":"RS256"}"#),
+            engine.encode(claims.to_string())
+        )
+    }
+
+    #[test]
+    fn only_atlassian_issuers_count_as_atlassian_oauth() {
+        assert!(atlassian_issuer("https://auth.atlassian.com"));
+        assert!(atlassian_issuer("https://auth.atlassian.com/"));
+        assert!(!atlassian_issuer("https://atlassian.evil.example"));
+        assert!(!atlassian_issuer("https://sso.example.com/not-atlassian"));
+        assert!(!atlassian_issuer(""));
+    }
+
+    #[test]
+    fn reads_scopes_and_expiry_from_oauth_tokens() {
+        let auth = AuthMethod::Bearer {
+            token: jwt(serde_json::json!({
+                "scope": "read:confluence-content.all write:confluence-content",
+                "exp": 1_800_000_000,
+            })),
+        };
+        let claims = token_claims(&auth).unwrap();
+        assert_eq!(
+            token_scopes(&claims),
+            ["read:confluence-content.all", "write:confluence-content"]
         );
-        return Ok(());
+        assert_eq!(claims["exp"], 1_800_000_000);
+
+        let opaque = AuthMethod::Bearer {
+            token: "not-a-jwt".to_string(),
+        };
+        assert!(token_claims(&opaque).is_none());
+        let basic = AuthMethod::Basic {
+            email: "a@b.c".to_string(),
+            token: jwt(serde_json::json!({ "exp": 1 })),
+        };
+        assert!(token_claims(&basic).is_none());
     }
 
-    if !Config::exists()? {
-        print_line(ctx, "Not logged in.");
-        return Ok(());
+    #[test]
+    fn matches_the_granted_resource_of_the_site() {
+        let resources = json!([
+            { "id": "c1", "url": "https://one.atlassian.net", "scopes": ["read:confluence-content.all"] },
+            { "id": "c2", "url": "https://two.atlassian.net" },
+        ]);
+        let id = |site: &str, cloud_id| {
+            granted_resource(site, cloud_id, &resources).map(|r| json_str(r, "id"))
+        };
+        assert_eq!(
+            id("https://two.atlassian.net/wiki", None).as_deref(),
+            Some("c2")
+        );
+        assert_eq!(
+            id("https://three.atlassian.net/wiki", Some("c1")).as_deref(),
+            Some("c1")
+        );
+        assert_eq!(id("https://three.atlassian.net/wiki", None), None);
+    }
+
+    #[test]
+    fn describes_expiry_relative_to_now() {
+        assert_eq!(
+            describe_expiry(1_800_000_000, 1_800_000_000 - 3 * 3600 - 20 * 60),
+            "2027-01-15T08:00:00Z (in 3h 20m)"
+        );
+        assert_eq!(describe_expiry(90, 30), "1970-01-01T00:01:30Z (in 1m)");
+        assert_eq!(describe_expiry(30, 90), "1970-01-01T00:00:30Z (expired)");
     }
-    let config = Config::load()?;
-    let client = ApiClient::new(
-        config.site_url.clone(),
-        config.api_base_v1.clone(),
-        config.api_base_v2.clone(),
-        config.auth.clone(),
-        ctx.verbose,
-    )?;
-    let v2 = client.v2_url("/spaces?limit=1");
-    let v1 = client.v1_url("/space?limit=1");
-    if let Err(v2_err) = client.get_json(v2).await {
-        client
-            .get_json(v1)
-            .await
-            .with_context(|| format!("Failed to validate auth (v2 error: {v2_err})"))?;
-    }
-    let path = Config::path()?;
-    print_line(
-        ctx,
-        &format!(
-            "Logged in to {} using {} auth (config: {})",
-            config.site_url,
-            config.auth.description(),
-            path.display()
-        ),
-    );
-    Ok(())
 }
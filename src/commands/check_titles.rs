@@ -0,0 +1,156 @@
+//! `check-titles`: page titles that trip up automation. Duplicates make `SPACE:Title`
+//! ambiguous; titles that differ only in case or in characters file names can't hold end up
+//! with the same name in an export (`--site obsidian` then falls back to `Title (id)`).
+
+use anyhow::{Context, Result, anyhow};
+use confcli::json_util::json_str;
+use confcli::output::OutputFormat;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+use crate::cli::CheckTitlesArgs;
+use crate::commands::export::note_stem;
+use crate::context::AppContext;
+use crate::helpers::{maybe_print_json, maybe_print_rows, url_with_query};
+use crate::resolve::resolve_space_id;
+
+/// Pages sharing a title (`duplicate`) or, with different titles, an export name (`slug`).
+#[derive(Debug)]
+struct Collision<'a> {
+    kind: &'static str,
+    /// The shared title or export name.
+    key: String,
+    pages: Vec<&'a (String, String)>,
+}
+
+pub async fn handle(ctx: &AppContext, args: CheckTitlesArgs) -> Result<()> {
+    let client = crate::context::load_client(ctx)?;
+    let space_id = resolve_space_id(&client, &args.space).await?;
+    let url = url_with_query(
+        &client.v2_url(&format!("/spaces/{space_id}/pages")),
+        &[("limit", "250".to_string())],
+    )?;
+    let pages: Vec<(String, String)> = client
+        .get_paginated_results(url, true)
+        .await
+        .with_context(|| format!("Failed to list pages of space {}", args.space))?
+        .iter()
+        .map(|page| (json_str(page, "id"), json_str(page, "title")))
+        .collect();
+    let collisions = find_collisions(&pages);
+
+    match args.output {
+        OutputFormat::Json => {
+            let items: Vec<Value> = collisions
+                .iter()
+                .map(|collision| {
+                    let pages: Vec<Value> = collision
+                        .pages
+                        .iter()
+                        .map(|(id, title)| json!({ "id": id, "title": title }))
+                        .collect();
+                    json!({ "kind": collision.kind, "key": collision.key, "pages": pages })
+                })
+                .collect();
+            maybe_print_json(ctx, &items)?;
+        }
+        fmt => {
+            let rows = collisions
+                .iter()
+                .flat_map(|collision| {
+                    collision.pages.iter().map(|(id, title)| {
+                        vec![
+                            collision.kind.to_string(),
+                            collision.key.clone(),
+                            id.clone(),
+                            title.clone(),
+                        ]
+                    })
+                })
+                .collect();
+            maybe_print_rows(ctx, fmt, &["Kind", "Key", "ID", "Title"], rows);
+        }
+    }
+
+    if !collisions.is_empty() {
+        return Err(anyhow!(
+            "{} title collision(s) among {} pages in {}",
+            collisions.len(),
+            pages.len(),
+            args.space
+        ));
+    }
+    Ok(())
+}
+
+/// Exact duplicates first, then export-name collisions between different titles, each
+/// sorted by key.
+fn find_collisions(pages: &[(String, String)]) -> Vec<Collision<'_>> {
+    let mut by_title: BTreeMap<&str, Vec<&(String, String)>> = BTreeMap::new();
+    let mut by_slug: BTreeMap<String, Vec<&(String, String)>> = BTreeMap::new();
+    for page in pages {
+        by_title.entry(page.1.as_str()).or_default().push(page);
+        let slug = note_stem(&page.1).to_lowercase();
+        if !slug.is_empty() {
+            by_slug.entry(slug).or_default().push(page);
+        }
+    }
+    let duplicates = by_title
+        .into_iter()
+        .filter(|(_, group)| group.len() > 1)
+        .map(|(title, pages)| Collision {
+            kind: "duplicate",
+            key: title.to_string(),
+            pages,
+        });
+    // A slug only adds something when the titles behind it differ; plain duplicates are
+    // reported above.
+    let slugs = by_slug
+        .into_iter()
+        .filter(|(_, group)| group.iter().any(|page| page.1 != group[0].1))
+        .map(|(slug, pages)| Collision {
+            kind: "slug",
+            key: slug,
+            pages,
+        });
+    duplicates.chain(slugs).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(id: &str, title: &str) -> (String, String) {
+        (id.to_string(), title.to_string())
+    }
+
+    #[test]
+    fn reports_duplicates_and_export_name_collisions() {
+        let pages = [
+            page("1", "Runbook"),
+            page("2", "Runbook"),
+            page("3", "runbook"),
+            page("4", "Q1: Plan"),
+            page("5", "Q1 Plan"),
+            page("6", "Overview"),
+            page("7", "???"),
+        ];
+        let collisions = find_collisions(&pages);
+        let summary: Vec<(&str, &str, Vec<&str>)> = collisions
+            .iter()
+            .map(|c| {
+                let ids = c.pages.iter().map(|(id, _)| id.as_str()).collect();
+                (c.kind, c.key.as_str(), ids)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("duplicate", "Runbook", vec!["1", "2"]),
+                ("slug", "q1 plan", vec!["4", "5"]),
+                ("slug", "runbook", vec!["1", "2", "3"]),
+            ]
+        );
+        assert!(find_collisions(&[page("1", "A"), page("2", "B")]).is_empty());
+    }
+}
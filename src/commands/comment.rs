@@ -7,13 +7,13 @@ use confcli::json_util::json_str;
 use confcli::markdown::markdown_to_storage;
 use confcli::output::OutputFormat;
 #[cfg(feature = "write")]
-use dialoguer::Confirm;
-#[cfg(feature = "write")]
 use serde_json::{Value, json};
 
 use crate::cli::*;
 use crate::context::AppContext;
 use crate::helpers::*;
+#[cfg(feature = "write")]
+use crate::plan::{self, Operation};
 use crate::resolve::resolve_page_id;
 
 pub async fn handle(ctx: &AppContext, cmd: CommentCommand) -> Result<()> {
@@ -96,7 +96,15 @@ async fn comment_add(client: &ApiClient, ctx: &AppContext, args: CommentAddArgs)
     let page_id = resolve_page_id(client, &args.page).await?;
 
     if ctx.dry_run {
-        print_line(ctx, &format!("Would add comment on page {page_id}"));
+        plan::record(
+            Operation::new("add", "comment", &page_id)
+                .with_payload(json!({ "parent": args.parent, "location": args.location })),
+        );
+        print_status(
+            ctx,
+            args.print_id,
+            &format!("Would add comment on page {page_id}"),
+        );
         return Ok(());
     }
 
@@ -146,6 +154,9 @@ async fn comment_add(client: &ApiClient, ctx: &AppContext, args: CommentAddArgs)
 
     let url = client.v1_url("/content");
     let result = client.post_json(url, payload).await?;
+    if args.print_id {
+        return print_created_id(ctx, &json_str(&result, "id"));
+    }
     match args.output {
         OutputFormat::Json => maybe_print_json(ctx, &result),
         fmt => {
@@ -166,6 +177,7 @@ async fn comment_delete(
     args: CommentDeleteArgs,
 ) -> Result<()> {
     if ctx.dry_run {
+        plan::record(Operation::new("delete", "comment", &args.comment));
         return print_write_action_result(
             ctx,
             args.output,
@@ -183,18 +195,9 @@ async fn comment_delete(
         );
     }
 
-    if !args.yes {
-        let confirm = Confirm::new()
-            .with_prompt(format!("Delete comment {}?", args.comment))
-            .default(false)
-            .interact()
-            .map_err(|err| {
-                anyhow::anyhow!("{err}. Use --yes to skip confirmation in non-interactive shells.")
-            })?;
-        if !confirm {
-            print_line(ctx, "Cancelled.");
-            return Ok(());
-        }
+    if !args.yes && !confirm_delete(&format!("Delete comment {}?", args.comment))? {
+        print_line(ctx, "Cancelled.");
+        return Ok(());
     }
 
     let url = client.v1_url(&format!("/content/{}", args.comment));
@@ -2,23 +2,44 @@ use anyhow::{Context, Result, anyhow};
 use confcli::client::ApiClient;
 use confcli::json_util::json_str;
 use confcli::output::OutputFormat;
+use futures_util::stream::{self, StreamExt};
 use serde_json::{Value, json};
-use std::collections::{HashMap, HashSet, VecDeque};
-use std::future::Future;
-use std::pin::Pin;
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
 use tokio::sync::Semaphore;
 use tokio::task::JoinSet;
 
 use crate::cli::CopyTreeArgs;
+use crate::commands::rollback::{Journal, trash_pages};
 use crate::context::AppContext;
 use crate::download::fetch_page_with_body_format;
 use crate::helpers::*;
-use crate::resolve::resolve_page_id;
+use crate::plan::{self, Operation};
+use crate::preflight::{self, PageOperation};
+use crate::progress::Progress;
+use crate::resolve::{resolve_page_id, resolve_space_key};
+
+/// Source body fetches, and sibling groups created in parallel per tree level, without
+/// `--concurrency`.
+const CONCURRENCY: usize = 8;
 
 pub async fn handle(ctx: &AppContext, args: CopyTreeArgs) -> Result<()> {
-    let client = crate::context::load_client(ctx)?;
-    copy_tree(&client, ctx, args).await
+    let client = crate::context::load_client(ctx)?.with_schedule(args.pace, args.run_window);
+    // Pages are read through the active profile and created through the target one, each
+    // with its own connection pool, rate limiter, and pace.
+    let target = match &args.target_profile {
+        Some(name) => {
+            let target = crate::context::load_named_client(ctx, name)?
+                .with_schedule(args.pace, args.run_window);
+            if !ctx.dry_run {
+                target.ensure_writable()?;
+            }
+            target
+        }
+        None => client.clone(),
+    };
+    copy_tree(&client, &target, ctx, args).await
 }
 
 #[derive(Debug, Clone)]
@@ -27,12 +48,135 @@ struct Node {
     parent_id: Option<String>,
     title: String,
     child_position: i64,
+    /// Place in the source listing; orders siblings whose `child_position` ties.
+    listed: usize,
     body_storage: Option<String>,
 }
 
-async fn copy_tree(client: &ApiClient, ctx: &AppContext, args: CopyTreeArgs) -> Result<()> {
+struct CopyState {
+    /// Source page id -> id of its copy.
+    mapping: BTreeMap<String, String>,
+    /// Create responses, parents before their children.
+    created: Vec<Value>,
+    journal: Option<Journal>,
+}
+
+/// Creates the copies of one tree level; shared by that level's concurrent sibling groups.
+struct LevelCopier<'a> {
+    client: &'a ApiClient,
+    ctx: &'a AppContext,
+    nodes: &'a HashMap<String, Node>,
+    target_space_id: &'a str,
+    args: &'a CopyTreeArgs,
+    state: Mutex<CopyState>,
+    /// Set by the first failing group so the others stop before their next create.
+    failed: AtomicBool,
+}
+
+impl LevelCopier<'_> {
+    fn lock_state(&self) -> MutexGuard<'_, CopyState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Copy `ids` (siblings in position order) under `new_parent`, one after another.
+    async fn create_siblings(&self, new_parent: &str, ids: &[String], depth: usize) -> Result<()> {
+        for id in ids {
+            if self.failed.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+            let res = self.create(new_parent, id, depth).await;
+            if res.is_err() {
+                self.failed.store(true, Ordering::Relaxed);
+                return res;
+            }
+        }
+        Ok(())
+    }
+
+    async fn create(&self, new_parent: &str, id: &str, depth: usize) -> Result<()> {
+        let node = self.nodes.get(id).context("Missing node")?;
+        let title = match &self.args.new_title {
+            Some(title) if depth == 0 => title.clone(),
+            _ => copy_title(&node.title, self.args),
+        };
+
+        if self.ctx.dry_run {
+            let new_parent_display = if depth == 0 {
+                new_parent.to_string()
+            } else {
+                // In dry-run mode we don't have real IDs for newly-created pages.
+                // Show the source parent id to make the plan easier to read.
+                let parent_old = node.parent_id.as_ref().context("Missing parentId")?;
+                format!("(copy of {parent_old})")
+            };
+            plan::record(
+                Operation::new("create", "page", &title).with_payload(json!({
+                    "sourceId": node.id,
+                    "parent": new_parent_display,
+                    "bodyBytes": node.body_storage.as_ref().map(String::len),
+                })),
+            );
+            print_line(
+                self.ctx,
+                &format!("Would create '{title}' under {new_parent_display}"),
+            );
+            self.lock_state()
+                .mapping
+                .insert(node.id.clone(), format!("<dry-run:{}>", node.id));
+            return Ok(());
+        }
+
+        let body = node.body_storage.as_ref().cloned().unwrap_or_default();
+        let payload = json!({
+            "spaceId": self.target_space_id,
+            "title": title,
+            "parentId": new_parent,
+            "status": "current",
+            "body": { "representation": "storage", "value": body }
+        });
+        let url = self.client.v2_url("/pages");
+        let result = self.client.post_json(url, payload).await?;
+        let new_id = result
+            .get("id")
+            .and_then(|v| v.as_str())
+            .context("Missing created page id")?
+            .to_string();
+        {
+            let mut state = self.lock_state();
+            state.mapping.insert(node.id.clone(), new_id);
+            let recorded = match state.journal.as_mut() {
+                Some(journal) => journal.record(&result),
+                None => Ok(()),
+            };
+            // Keep the page even if the journal write failed, so a rollback still sees it.
+            state.created.push(result);
+            recorded?;
+        }
+
+        if self.args.delay_ms > 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(self.args.delay_ms)).await;
+        }
+        Ok(())
+    }
+}
+
+/// Title of a copied page: `--rename` rules in order, then `--prefix` and `--copy-suffix`.
+fn copy_title(title: &str, args: &CopyTreeArgs) -> String {
+    let renamed = args
+        .renames
+        .iter()
+        .fold(title.to_string(), |title, rename| rename.apply(&title));
+    format!("{}{renamed}{}", args.prefix, args.copy_suffix)
+}
+
+async fn copy_tree(
+    client: &ApiClient,
+    target: &ApiClient,
+    ctx: &AppContext,
+    args: CopyTreeArgs,
+) -> Result<()> {
     let source_id = resolve_page_id(client, &args.source).await?;
-    let target_parent_id = resolve_page_id(client, &args.target_parent).await?;
+    let target_parent_id = resolve_page_id(target, &args.target_parent).await?;
 
     let exclude = args
         .exclude
@@ -41,13 +185,24 @@ async fn copy_tree(client: &ApiClient, ctx: &AppContext, args: CopyTreeArgs) ->
         .transpose()?;
 
     // SpaceId: inferred from target parent.
-    let target_parent_url = client.v2_url(&format!("/pages/{target_parent_id}"));
-    let (target_parent_json, _) = client.get_json(target_parent_url).await?;
+    let target_parent_url = target.v2_url(&format!("/pages/{target_parent_id}"));
+    let (target_parent_json, _) = target.get_json(target_parent_url).await?;
     let target_space_id = target_parent_json
         .get("spaceId")
         .and_then(|v| v.as_str())
         .context("Target parent missing spaceId")?
         .to_string();
+    if args.preflight {
+        let space_key = resolve_space_key(target, &target_space_id).await?;
+        preflight::check_space(
+            target,
+            ctx,
+            &target_space_id,
+            &space_key,
+            &[PageOperation::Create],
+        )
+        .await?;
+    }
 
     // Descendants (no root).
     // NOTE: Confluence's `/pages/{id}/descendants` endpoint appears to only include a limited
@@ -74,11 +229,12 @@ async fn copy_tree(client: &ApiClient, ctx: &AppContext, args: CopyTreeArgs) ->
             parent_id: None,
             title: root_title,
             child_position: 0,
+            listed: 0,
             body_storage: Some(root_body),
         },
     );
 
-    for item in descendants {
+    for (listed, item) in descendants.into_iter().enumerate() {
         let id = item
             .get("id")
             .and_then(|v| v.as_str())
@@ -107,6 +263,7 @@ async fn copy_tree(client: &ApiClient, ctx: &AppContext, args: CopyTreeArgs) ->
                 parent_id,
                 title,
                 child_position,
+                listed: listed + 1,
                 body_storage: None,
             },
         );
@@ -161,29 +318,24 @@ async fn copy_tree(client: &ApiClient, ctx: &AppContext, args: CopyTreeArgs) ->
         }
     }
     for ids in children.values_mut() {
-        ids.sort_by_key(|id| nodes.get(id).map(|n| n.child_position).unwrap_or(0));
+        ids.sort_by_key(|id| {
+            nodes
+                .get(id)
+                .map(|n| (n.child_position, n.listed))
+                .unwrap_or_default()
+        });
     }
 
     // Fetch bodies for descendants (storage) concurrently.
     let client_arc = Arc::new(client.clone());
-    let sem = Arc::new(Semaphore::new(args.concurrency.max(1)));
+    let sem = Arc::new(Semaphore::new(ctx.workers(CONCURRENCY)));
     let total_to_fetch = nodes
         .iter()
         .filter(|(id, node)| {
             *id != &source_id && !blocked.contains(*id) && node.body_storage.is_none()
         })
         .count();
-    let fetch_bar = if ctx.quiet {
-        None
-    } else {
-        let bar = indicatif::ProgressBar::new(total_to_fetch as u64);
-        bar.set_style(
-            indicatif::ProgressStyle::with_template("{spinner:.green} {pos}/{len} {wide_msg}")
-                .unwrap(),
-        );
-        bar.set_message("page bodies");
-        Some(bar)
-    };
+    let fetch_bar = Progress::items(ctx, "page bodies", total_to_fetch as u64);
     let mut tasks = JoinSet::new();
     for (id, node) in nodes.iter() {
         if id == &source_id {
@@ -224,7 +376,7 @@ async fn copy_tree(client: &ApiClient, ctx: &AppContext, args: CopyTreeArgs) ->
                 tasks.abort_all();
                 while tasks.join_next().await.is_some() {}
                 if let Some(bar) = &fetch_bar {
-                    bar.finish_and_clear();
+                    bar.finish();
                 }
                 return Err(err.context("Fetch task failed"));
             }
@@ -232,146 +384,156 @@ async fn copy_tree(client: &ApiClient, ctx: &AppContext, args: CopyTreeArgs) ->
                 tasks.abort_all();
                 while tasks.join_next().await.is_some() {}
                 if let Some(bar) = &fetch_bar {
-                    bar.finish_and_clear();
+                    bar.finish();
                 }
                 return Err(anyhow!("Fetch task failed: {join_err}"));
             }
         }
     }
     if let Some(bar) = fetch_bar {
-        bar.finish_and_clear();
+        bar.finish();
     }
 
-    // Traversal + create.
-    let mut mapping: HashMap<String, String> = HashMap::new();
-    let mut created: Vec<Value> = Vec::new();
-
-    #[allow(clippy::too_many_arguments)]
-    fn walk<'a>(
-        client: &'a ApiClient,
-        ctx: &'a AppContext,
-        nodes: &'a HashMap<String, Node>,
-        children: &'a HashMap<String, Vec<String>>,
-        mapping: &'a mut HashMap<String, String>,
-        created: &'a mut Vec<Value>,
-        source_id: &'a str,
-        target_parent_id: &'a str,
-        target_space_id: &'a str,
-        args: &'a CopyTreeArgs,
-        depth: usize,
-    ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
-        Box::pin(async move {
-            if args.max_depth > 0 && depth > args.max_depth {
-                return Ok(());
-            }
-
-            let node = nodes.get(source_id).context("Missing node")?;
-            let new_parent = if depth == 0 {
-                target_parent_id.to_string()
-            } else {
-                let parent_old = node.parent_id.as_ref().context("Missing parentId")?;
-                mapping
-                    .get(parent_old)
-                    .cloned()
-                    .context("Missing parent mapping")?
-            };
-
-            let title = if depth == 0 {
-                args.new_title
-                    .clone()
-                    .unwrap_or_else(|| format!("{}{}", node.title, args.copy_suffix))
-            } else {
-                format!("{}{}", node.title, args.copy_suffix)
-            };
-
-            if ctx.dry_run {
-                let new_parent_display = if depth == 0 {
-                    new_parent.clone()
-                } else {
-                    // In dry-run mode we don't have real IDs for newly-created pages.
-                    // Show the source parent id to make the plan easier to read.
-                    let parent_old = node.parent_id.as_ref().context("Missing parentId")?;
-                    format!("(copy of {parent_old})")
-                };
-
-                print_line(
-                    ctx,
-                    &format!("Would create '{title}' under {new_parent_display}"),
-                );
-                mapping.insert(node.id.clone(), format!("<dry-run:{}>", node.id));
-            } else {
-                let body = node.body_storage.as_ref().cloned().unwrap_or_default();
-                let payload = json!({
-                    "spaceId": target_space_id,
-                    "title": title,
-                    "parentId": new_parent,
-                    "status": "current",
-                    "body": { "representation": "storage", "value": body }
-                });
-                let url = client.v2_url("/pages");
-                let result = client.post_json(url, payload).await?;
-                let new_id = result
-                    .get("id")
-                    .and_then(|v| v.as_str())
-                    .context("Missing created page id")?
-                    .to_string();
-                mapping.insert(node.id.clone(), new_id);
-                created.push(result);
-
-                if args.delay_ms > 0 {
-                    tokio::time::sleep(std::time::Duration::from_millis(args.delay_ms)).await;
-                }
-            }
-
-            if let Some(kids) = children.get(source_id) {
-                for kid in kids {
-                    walk(
-                        client,
-                        ctx,
-                        nodes,
-                        children,
-                        mapping,
-                        created,
-                        kid,
-                        target_parent_id,
-                        target_space_id,
-                        args,
-                        depth + 1,
-                    )
-                    .await?;
-                }
-            }
-            Ok(())
-        })
+    // Create one tree level at a time. Every parent of a level already exists, so the level's
+    // sibling groups run concurrently; siblings of one parent are still created in order
+    // because Confluence appends each new child last.
+    let journal = match &args.journal {
+        Some(path) if !ctx.dry_run => Some(Journal::create(path, target.base_url())?),
+        _ => None,
+    };
+    let copier = LevelCopier {
+        client: target,
+        ctx,
+        nodes: &nodes,
+        target_space_id: &target_space_id,
+        args: &args,
+        state: Mutex::new(CopyState {
+            mapping: BTreeMap::new(),
+            created: Vec::new(),
+            journal,
+        }),
+        failed: AtomicBool::new(false),
+    };
+    let mut level: Vec<(String, Vec<String>)> =
+        vec![(target_parent_id.clone(), vec![source_id.clone()])];
+    let mut depth = 0;
+    let mut copied = Ok(());
+    while !level.is_empty() && (args.max_depth == 0 || depth <= args.max_depth) {
+        // A dry run sends nothing, so one group at a time keeps the plan in tree order.
+        let groups = if ctx.dry_run {
+            1
+        } else {
+            ctx.workers(CONCURRENCY)
+        };
+        let results: Vec<Result<()>> = stream::iter(&level)
+            .map(|(new_parent, ids)| copier.create_siblings(new_parent, ids, depth))
+            .buffer_unordered(groups)
+            .collect()
+            .await;
+        if let Some(err) = results.into_iter().find_map(Result::err) {
+            copied = Err(err);
+            break;
+        }
+        let state = copier.lock_state();
+        level = level
+            .iter()
+            .flat_map(|(_, ids)| ids)
+            .filter_map(|id| Some((state.mapping.get(id)?.clone(), children.get(id)?.clone())))
+            .collect();
+        depth += 1;
     }
+    let CopyState {
+        mapping, created, ..
+    } = copier.state.into_inner().unwrap_or_else(|e| e.into_inner());
 
-    walk(
-        client,
-        ctx,
-        &nodes,
-        &children,
-        &mut mapping,
-        &mut created,
-        &source_id,
-        &target_parent_id,
-        &target_space_id,
-        &args,
-        0,
-    )
-    .await?;
+    if let Err(err) = copied {
+        if !args.rollback_on_error || created.is_empty() {
+            return Err(err);
+        }
+        let ids: Vec<String> = created.iter().map(|page| json_str(page, "id")).collect();
+        let left: Vec<String> = trash_pages(target, &ids)
+            .await
+            .into_iter()
+            .filter_map(|(id, res)| res.is_err().then_some(id))
+            .collect();
+        let note = if left.is_empty() {
+            format!("rolled back: trashed the {} page(s) created", ids.len())
+        } else {
+            format!(
+                "rollback incomplete: could not trash {} of {} created page(s): {}",
+                left.len(),
+                ids.len(),
+                left.join(", ")
+            )
+        };
+        return Err(err.context(format!("copy-tree failed ({note})")));
+    }
 
     match args.output {
-        OutputFormat::Json => {
-            maybe_print_json(ctx, &json!({ "mapping": mapping, "created": created }))
-        }
+        OutputFormat::Json => maybe_print_json(
+            ctx,
+            &json!({
+                "sourceSite": client.base_url(),
+                "targetSite": target.base_url(),
+                "mapping": mapping,
+                "created": created,
+            }),
+        ),
         fmt => {
-            let rows = vec![
+            let mut rows = vec![
                 vec!["Source".to_string(), source_id.clone()],
                 vec!["TargetParent".to_string(), target_parent_id.clone()],
                 vec!["Created".to_string(), created.len().to_string()],
             ];
+            if args.target_profile.is_some() {
+                rows.push(vec![
+                    "SourceSite".to_string(),
+                    client.base_url().to_string(),
+                ]);
+                rows.push(vec![
+                    "TargetSite".to_string(),
+                    target.base_url().to_string(),
+                ]);
+            }
             maybe_print_kv_fmt(ctx, fmt, rows);
             Ok(())
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{Cli, Commands};
+    use clap::Parser;
+
+    fn parse(extra: &[&str]) -> CopyTreeArgs {
+        let argv = [&["confcli", "copy-tree", "1", "2"], extra].concat();
+        match Cli::try_parse_from(argv).unwrap().command {
+            Commands::CopyTree(args) => args,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn copy_title_applies_renames_prefix_and_suffix() {
+        let args = parse(&[
+            "--rename",
+            "s/2024/2025/g",
+            "--rename",
+            r"s#q(\d)#Quarter \1#i",
+            "--prefix",
+            "[Plan] ",
+            "--copy-suffix",
+            "",
+        ]);
+        assert_eq!(
+            copy_title("Q3 2024 goals (2024)", &args),
+            "[Plan] Quarter 3 2025 goals (2025)"
+        );
+        assert_eq!(
+            copy_title("Roadmap", &parse(&[])),
+            "Roadmap (Copy)".to_string()
+        );
+    }
+}
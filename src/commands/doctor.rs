@@ -0,0 +1,689 @@
+//! `doctor` (also `auth doctor`): probe the configured site and explain what is wrong with the
+//! setup, most often an API base with or without `/wiki` that doesn't match the site, or a
+//! corporate proxy in the way.
+
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Result, anyhow};
+use confcli::auth::AuthMethod;
+use confcli::client::ApiClient;
+use confcli::config::Config;
+use confcli::json_util::json_str;
+use confcli::output::OutputFormat;
+use reqwest::StatusCode;
+use reqwest::header::{CONTENT_TYPE, DATE, HeaderMap, USER_AGENT};
+use serde_json::{Value, json};
+use url::Url;
+
+use crate::cli::DoctorArgs;
+use crate::commands::auth::{token_claims, token_scopes};
+use crate::context::{AppContext, active_profile, stored_profile};
+use crate::helpers::*;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(15);
+/// Clock skew beyond this is reported.
+const MAX_SKEW_SECS: i64 = 60;
+const PROXY_VARS: &[&str] = &[
+    "HTTPS_PROXY",
+    "https_proxy",
+    "ALL_PROXY",
+    "all_proxy",
+    "HTTP_PROXY",
+    "http_proxy",
+];
+const RATE_LIMIT_HEADERS: &[&str] = &[
+    "x-ratelimit-limit",
+    "x-ratelimit-remaining",
+    "x-ratelimit-nearlimit",
+    "retry-after",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Level {
+    Ok,
+    Warn,
+    Fail,
+    Skip,
+}
+
+impl Level {
+    fn as_str(self) -> &'static str {
+        match self {
+            Level::Ok => "ok",
+            Level::Warn => "warn",
+            Level::Fail => "fail",
+            Level::Skip => "skip",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Check {
+    name: &'static str,
+    level: Level,
+    detail: String,
+    hint: Option<String>,
+}
+
+impl Check {
+    fn new(name: &'static str, level: Level, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            level,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+}
+
+/// One request's outcome, without retries or error mapping.
+struct Probe {
+    result: std::result::Result<(StatusCode, HeaderMap, String), String>,
+    elapsed: Duration,
+}
+
+impl Probe {
+    fn status(&self) -> Option<StatusCode> {
+        self.result.as_ref().ok().map(|(status, _, _)| *status)
+    }
+
+    fn headers(&self) -> Option<&HeaderMap> {
+        self.result.as_ref().ok().map(|(_, headers, _)| headers)
+    }
+
+    fn is_json(&self) -> bool {
+        self.headers()
+            .and_then(|headers| headers.get(CONTENT_TYPE))
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.contains("json"))
+    }
+
+    fn summary(&self) -> String {
+        match &self.result {
+            Ok((status, _, _)) => format!(
+                "HTTP {} in {} ms",
+                status.as_u16(),
+                self.elapsed.as_millis()
+            ),
+            Err(err) => err.clone(),
+        }
+    }
+}
+
+async fn probe(client: &ApiClient, url: &str, authenticated: bool) -> Probe {
+    let started = Instant::now();
+    let mut request = client
+        .http()
+        .get(url)
+        .header(USER_AGENT, client.user_agent())
+        .timeout(PROBE_TIMEOUT);
+    if authenticated {
+        request = match client
+            .ensure_session()
+            .await
+            .and_then(|()| client.apply_auth(request))
+        {
+            Ok(request) => request,
+            Err(err) => {
+                return Probe {
+                    result: Err(format!("{err:#}")),
+                    elapsed: started.elapsed(),
+                };
+            }
+        };
+    }
+    let result = match request.send().await {
+        Ok(response) => {
+            let status = response.status();
+            let headers = response.headers().clone();
+            let body = response.text().await.unwrap_or_default();
+            Ok((status, headers, body))
+        }
+        Err(err) => Err(format!("{:#}", anyhow::Error::from(err))),
+    };
+    Probe {
+        result,
+        elapsed: started.elapsed(),
+    }
+}
+
+pub async fn handle(ctx: &AppContext, args: DoctorArgs) -> Result<()> {
+    let checks = run_checks(ctx).await;
+    let failed = checks
+        .iter()
+        .filter(|check| check.level == Level::Fail)
+        .count();
+
+    match args.output {
+        OutputFormat::Json => {
+            let items: Vec<Value> = checks
+                .iter()
+                .map(|check| {
+                    json!({
+                        "check": check.name,
+                        "status": check.level.as_str(),
+                        "detail": check.detail,
+                        "hint": check.hint,
+                    })
+                })
+                .collect();
+            maybe_print_json(ctx, &json!({ "ok": failed == 0, "checks": items }))?;
+        }
+        fmt => {
+            let rows = checks
+                .iter()
+                .map(|check| {
+                    vec![
+                        check.name.to_string(),
+                        check.level.as_str().to_string(),
+                        check.detail.clone(),
+                    ]
+                })
+                .collect();
+            maybe_print_rows(ctx, fmt, &["Check", "Status", "Detail"], rows);
+            let hints: Vec<_> = checks
+                .iter()
+                .filter_map(|check| check.hint.as_ref().map(|hint| (check.name, hint)))
+                .collect();
+            if !hints.is_empty() {
+                print_line(ctx, "");
+                for (name, hint) in hints {
+                    print_line(ctx, &format!("{name}: {hint}"));
+                }
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(anyhow!("{failed} check(s) failed"));
+    }
+    Ok(())
+}
+
+async fn run_checks(ctx: &AppContext) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let (config, source) = match load_config() {
+        Ok(Some(found)) => found,
+        Ok(None) => {
+            checks.push(
+                Check::new("config", Level::Fail, "not logged in")
+                    .hint("run `confcli auth login` or set CONFLUENCE_DOMAIN with CONFLUENCE_EMAIL and CONFLUENCE_TOKEN"),
+            );
+            return checks;
+        }
+        Err(err) => {
+            checks.push(Check::new("config", Level::Fail, format!("{err:#}")));
+            return checks;
+        }
+    };
+    checks.push(Check::new(
+        "config",
+        Level::Ok,
+        format!(
+            "{source}: {}, {} auth{}",
+            config.site_url,
+            config.auth.description(),
+            if config.read_only { ", read-only" } else { "" }
+        ),
+    ));
+    let client = match crate::context::load_client(ctx) {
+        Ok(client) => client,
+        Err(err) => {
+            checks.push(Check::new("client", Level::Fail, format!("{err:#}")));
+            return checks;
+        }
+    };
+
+    let proxy = configured_proxy();
+    checks.push(dns_check(client.base_url(), proxy.is_some()).await);
+    let site = probe(&client, client.base_url(), false).await;
+    let reachable = site.result.is_ok();
+    checks.push(match (&proxy, reachable) {
+        (None, _) => Check::new("proxy", Level::Ok, "none (direct connection)"),
+        (Some(proxy), true) => Check::new("proxy", Level::Ok, format!("{proxy}, reachable")),
+        (Some(proxy), false) => Check::new(
+            "proxy",
+            Level::Fail,
+            format!("{proxy}, site unreachable through it"),
+        )
+        .hint("check the proxy URL and credentials, or add the site's host to NO_PROXY"),
+    });
+    checks.push(if reachable {
+        Check::new(
+            "site",
+            Level::Ok,
+            format!("{} ({})", client.base_url(), site.summary()),
+        )
+    } else {
+        Check::new("site", Level::Fail, site.summary())
+            .hint("check the site URL and the network path to it (DNS, VPN, firewall)")
+    });
+    checks.push(tls_check(client.base_url(), &site));
+    checks.push(clock_check(site.headers()));
+    if !reachable {
+        return checks;
+    }
+
+    let v1 = probe(&client, &client.v1_url("/space?limit=1"), true).await;
+    checks.push(api_check(&client, "api v1", &client.v1_url(""), &v1, &config.auth).await);
+    let v2 = probe(&client, &client.v2_url("/spaces?limit=1"), true).await;
+    checks.push(match v2.status() {
+        Some(status) if status.is_success() && v2.is_json() => Check::new(
+            "api v2",
+            Level::Ok,
+            format!("{} ({})", client.v2_url(""), v2.summary()),
+        ),
+        Some(StatusCode::NOT_FOUND) if v1.status().is_some_and(|s| s.is_success()) => Check::new(
+            "api v2",
+            Level::Warn,
+            format!("not found at {}", client.v2_url("")),
+        )
+        .hint("Server/Data Center has no v2 API; commands that need it will fail there"),
+        _ => api_check(&client, "api v2", &client.v2_url(""), &v2, &config.auth).await,
+    });
+
+    if v1.status().is_some_and(|status| status.is_success()) {
+        checks.push(user_check(&client).await);
+        checks.push(read_check(&client).await);
+    } else {
+        checks.push(Check::new("user", Level::Skip, "needs a working v1 API"));
+        checks.push(Check::new("read", Level::Skip, "needs a working v1 API"));
+    }
+    checks.push(write_check(&client, &config, &v2).await);
+    checks.push(rate_limit_check([&v1, &v2]));
+    checks
+}
+
+fn load_config() -> Result<Option<(Config, String)>> {
+    if active_profile().is_none()
+        && let Some(config) = Config::from_env()?
+    {
+        return Ok(Some((config, "environment".to_string())));
+    }
+    let profile = stored_profile();
+    let path = Config::profile_path(&profile)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    // Only described here; `load_client` resolves the token (and runs `token_cmd`) itself.
+    let config = Config::load_profile(&profile)?;
+    Ok(Some((config, path.display().to_string())))
+}
+
+/// The proxy reqwest will use from the environment, without credentials.
+fn configured_proxy() -> Option<String> {
+    PROXY_VARS.iter().find_map(|name| {
+        let value = std::env::var(name)
+            .ok()
+            .filter(|value| !value.trim().is_empty())?;
+        let shown = match Url::parse(&value) {
+            Ok(mut url) => {
+                let _ = url.set_username("");
+                let _ = url.set_password(None);
+                url.to_string()
+            }
+            Err(_) => value,
+        };
+        Some(format!("{name}={shown}"))
+    })
+}
+
+async fn api_check(
+    client: &ApiClient,
+    name: &'static str,
+    base: &str,
+    response: &Probe,
+    auth: &AuthMethod,
+) -> Check {
+    let base = base.trim_end_matches('/');
+    let Some(status) = response.status() else {
+        return Check::new(name, Level::Fail, response.summary());
+    };
+    match status {
+        status if status.is_success() && response.is_json() => {
+            Check::new(name, Level::Ok, format!("{base} ({})", response.summary()))
+        }
+        status if status.is_success() => Check::new(name, Level::Fail, format!("{base} answered with HTML, not JSON"))
+            .hint("the base probably points at a web page (login or proxy page) instead of the REST API; check the /wiki part of the site URL"),
+        StatusCode::UNAUTHORIZED => {
+            let hint = match auth {
+                AuthMethod::Basic { .. } => "Cloud expects your Atlassian account email and an API token (not your password); run `confcli auth login` again",
+                AuthMethod::Bearer { .. } => "the token is invalid or expired; for a Server/Data Center personal access token use `confcli auth login --pat`",
+                AuthMethod::Pat { .. } => "the personal access token is invalid, expired, or revoked; create a new one under Profile > Personal Access Tokens",
+                AuthMethod::Cookie { .. } => "the session login worked but the site rejects the session; check that it allows cookie sessions for REST calls",
+                AuthMethod::Anonymous => "the site does not allow anonymous access; drop --anonymous and run `confcli auth login`",
+            };
+            Check::new(name, Level::Fail, format!("{base}: credentials rejected (HTTP 401)")).hint(hint)
+        }
+        StatusCode::FORBIDDEN => Check::new(name, Level::Fail, format!("{base}: forbidden (HTTP 403)"))
+            .hint("the account lacks Confluence access, or an IP allowlist blocks this machine"),
+        StatusCode::NOT_FOUND => {
+            let check = Check::new(name, Level::Fail, format!("not found at {base}"));
+            match discover_v1_base(client, base).await {
+                Some(found) if name == "api v1" => check.hint(format!(
+                    "the REST API answers at {found}: run `confcli auth login --api-path {}` or set CONFLUENCE_API_PATH",
+                    Url::parse(&found).map(|url| url.path().to_string()).unwrap_or(found.clone())
+                )),
+                _ => check.hint("check whether the site URL needs /wiki (Cloud) or not (Server/Data Center)"),
+            }
+        }
+        status => Check::new(name, Level::Fail, format!("{base}: HTTP {}", status.as_u16())),
+    }
+}
+
+/// The first of the usual v1 bases on the site's origin, other than `current`, that answers
+/// with JSON.
+async fn discover_v1_base(client: &ApiClient, current: &str) -> Option<String> {
+    let origin = client.origin_url().trim_end_matches('/');
+    for path in ["/wiki/rest/api", "/rest/api", "/confluence/rest/api"] {
+        let candidate = format!("{origin}{path}");
+        if candidate == current {
+            continue;
+        }
+        let response = probe(client, &format!("{candidate}/space?limit=1"), true).await;
+        if response.is_json() && response.status() != Some(StatusCode::NOT_FOUND) {
+            return Some(candidate);
+        }
+    }
+    None
+}
+
+/// Resolve the site's host the way the connection will (through the system resolver).
+async fn dns_check(site: &str, proxied: bool) -> Check {
+    let Some((host, port)) = Url::parse(site)
+        .ok()
+        .and_then(|url| Some((url.host_str()?.to_string(), url.port_or_known_default()?)))
+    else {
+        return Check::new("dns", Level::Skip, "no host in the site URL");
+    };
+    let started = Instant::now();
+    let lookup = tokio::task::spawn_blocking({
+        let host = host.clone();
+        move || std::net::ToSocketAddrs::to_socket_addrs(&(host.as_str(), port))
+    })
+    .await;
+    match lookup {
+        Ok(Ok(mut addrs)) => match addrs.next() {
+            Some(addr) => Check::new(
+                "dns",
+                Level::Ok,
+                format!(
+                    "{host} -> {} in {} ms",
+                    addr.ip(),
+                    started.elapsed().as_millis()
+                ),
+            ),
+            None => Check::new("dns", Level::Fail, format!("{host}: no addresses")),
+        },
+        // Behind a proxy only the proxy has to resolve the site.
+        Ok(Err(err)) if proxied => Check::new(
+            "dns",
+            Level::Warn,
+            format!("{host}: {err} (the proxy resolves it)"),
+        ),
+        Ok(Err(err)) => Check::new("dns", Level::Fail, format!("{host}: {err}"))
+            .hint("check the site's host name, or connect to the VPN that serves the internal DNS"),
+        Err(err) => Check::new("dns", Level::Fail, err.to_string()),
+    }
+}
+
+/// Whether the TLS handshake with the site worked, judged from the site probe.
+fn tls_check(site: &str, response: &Probe) -> Check {
+    if !site.starts_with("https://") {
+        return Check::new(
+            "tls",
+            Level::Warn,
+            "plain HTTP, credentials travel unencrypted",
+        )
+        .hint("use the https:// URL of the site");
+    }
+    match &response.result {
+        Ok(_) => Check::new("tls", Level::Ok, "certificate verified"),
+        Err(err) if is_tls_error(err) => Check::new("tls", Level::Fail, err.clone()).hint(
+            "a corporate proxy or firewall that re-signs TLS needs its CA certificate in the system trust store",
+        ),
+        Err(_) => Check::new("tls", Level::Skip, "site unreachable"),
+    }
+}
+
+fn is_tls_error(error: &str) -> bool {
+    let error = error.to_ascii_lowercase();
+    ["certificate", "tls", "ssl", "handshake"]
+        .iter()
+        .any(|needle| error.contains(needle))
+}
+
+/// Reading content, not just the space list, works with these credentials.
+async fn read_check(client: &ApiClient) -> Check {
+    let response = probe(client, &client.v1_url("/content?limit=1"), true).await;
+    match response.status() {
+        Some(status) if status.is_success() => {
+            Check::new("read", Level::Ok, format!("content readable ({})", response.summary()))
+        }
+        Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+            Check::new("read", Level::Fail, format!("content not readable ({})", response.summary()))
+                .hint("the token lacks read access; OAuth apps need read:confluence-content.all (or read:page:confluence)")
+        }
+        _ => Check::new("read", Level::Warn, response.summary()),
+    }
+}
+
+/// Whether writes can work, without writing anything: OAuth scopes, then the operations the
+/// account may perform in the first listed space.
+async fn write_check(client: &ApiClient, config: &Config, spaces: &Probe) -> Check {
+    if !cfg!(feature = "write") {
+        return Check::new("write", Level::Skip, "built without write commands");
+    }
+    if config.read_only {
+        return Check::new("write", Level::Skip, "read-only profile");
+    }
+    if matches!(config.auth, AuthMethod::Anonymous) {
+        return Check::new("write", Level::Skip, "anonymous access is read-only");
+    }
+    let scopes = token_claims(&config.auth)
+        .map(|claims| token_scopes(&claims))
+        .unwrap_or_default();
+    if !scopes.is_empty() && !scopes.iter().any(|scope| scope.starts_with("write:")) {
+        return Check::new("write", Level::Warn, "the OAuth token has no write scopes").hint(
+            "grant write:confluence-content (or write:page:confluence) to the app and log in again",
+        );
+    }
+    let first_space = match &spaces.result {
+        Ok((status, _, body)) if status.is_success() => serde_json::from_str::<Value>(body)
+            .ok()
+            .and_then(|body| body.pointer("/results/0").cloned()),
+        _ => None,
+    };
+    let Some(space) = first_space else {
+        return Check::new("write", Level::Skip, "needs a space from the v2 API");
+    };
+    let key = json_str(&space, "key");
+    let url = client.v2_url(&format!("/spaces/{}/operations", json_str(&space, "id")));
+    let response = probe(client, &url, true).await;
+    let operations = match &response.result {
+        Ok((status, _, body)) if status.is_success() => serde_json::from_str::<Value>(body).ok(),
+        _ => None,
+    };
+    let Some(operations) = operations else {
+        return Check::new(
+            "write",
+            Level::Warn,
+            format!("space operations unavailable ({})", response.summary()),
+        );
+    };
+    let can_create_pages = operations["results"].as_array().is_some_and(|operations| {
+        operations.iter().any(|operation| {
+            json_str(operation, "operation") == "create"
+                && json_str(operation, "targetType") == "page"
+        })
+    });
+    if can_create_pages {
+        Check::new("write", Level::Ok, format!("can create pages in {key}"))
+    } else {
+        Check::new(
+            "write",
+            Level::Warn,
+            format!("cannot create pages in {key}"),
+        )
+        .hint("the account may only have read access; ask a space admin for add-page permission")
+    }
+}
+
+async fn user_check(client: &ApiClient) -> Check {
+    let response = probe(client, &client.v1_url("/user/current"), true).await;
+    let user = match &response.result {
+        Ok((status, _, body)) if status.is_success() => serde_json::from_str::<Value>(body).ok(),
+        _ => None,
+    };
+    match user {
+        Some(user) if json_str(&user, "type") == "anonymous" => {
+            Check::new("user", Level::Fail, "requests are anonymous")
+                .hint("the credentials are not being accepted; check the email and token")
+        }
+        Some(user) => {
+            let id = [json_str(&user, "accountId"), json_str(&user, "username")]
+                .into_iter()
+                .find(|id| !id.is_empty())
+                .unwrap_or_default();
+            Check::new(
+                "user",
+                Level::Ok,
+                format!("{} ({id})", json_str(&user, "displayName")),
+            )
+        }
+        None => Check::new(
+            "user",
+            Level::Warn,
+            format!("current user unavailable ({})", response.summary()),
+        ),
+    }
+}
+
+fn clock_check(headers: Option<&HeaderMap>) -> Check {
+    let server = headers
+        .and_then(|headers| headers.get(DATE))
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_http_date);
+    let Some(server) = server else {
+        return Check::new("clock", Level::Skip, "the site sent no Date header");
+    };
+    let local = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let skew = local - server;
+    if skew.abs() > MAX_SKEW_SECS {
+        Check::new(
+            "clock",
+            Level::Warn,
+            format!("local clock is {skew:+} s off the server's"),
+        )
+        .hint("sync the system clock (NTP); OAuth and signed requests fail with a skewed clock")
+    } else {
+        Check::new("clock", Level::Ok, format!("{skew:+} s from the server"))
+    }
+}
+
+fn rate_limit_check(responses: [&Probe; 2]) -> Check {
+    let mut seen = Vec::new();
+    for headers in responses.iter().filter_map(|probe| probe.headers()) {
+        for name in RATE_LIMIT_HEADERS {
+            if let Some(value) = headers.get(*name).and_then(|value| value.to_str().ok())
+                && !seen.iter().any(|(seen, _)| seen == name)
+            {
+                seen.push((*name, value.to_string()));
+            }
+        }
+    }
+    if seen.is_empty() {
+        return Check::new("rate limits", Level::Ok, "no rate-limit headers reported");
+    }
+    let detail = seen
+        .iter()
+        .map(|(name, value)| format!("{name}: {value}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let near = seen
+        .iter()
+        .any(|(name, value)| *name == "x-ratelimit-nearlimit" && value == "true");
+    if near {
+        Check::new("rate limits", Level::Warn, detail)
+            .hint("close to the rate limit; lower --concurrency or spread out automation")
+    } else {
+        Check::new("rate limits", Level::Ok, detail)
+    }
+}
+
+/// Seconds since the epoch of an HTTP date such as `Sun, 18 Oct 2026 09:30:00 GMT`.
+fn parse_http_date(value: &str) -> Option<i64> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let mut parts = value.split_whitespace().skip(1);
+    let (day, month, year, time) = (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+    let day: u32 = day.parse().ok()?;
+    let month = MONTHS.iter().position(|name| *name == month)? + 1;
+    let year: i64 = year.parse().ok()?;
+    let mut time = time.split(':').map(|part| part.parse::<i64>().ok());
+    let (h, m, s) = (time.next()??, time.next()??, time.next()??);
+    let days = crate::lifecycle::parse_date(&format!("{year:04}-{month:02}-{day:02}")).ok()?;
+    Some(days * 86_400 + h * 3600 + m * 60 + s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_http_dates() {
+        assert_eq!(parse_http_date("Thu, 01 Jan 1970 00:01:05 GMT"), Some(65));
+        assert_eq!(
+            parse_http_date("Sun, 18 Oct 2026 09:30:00 GMT"),
+            Some(1_792_315_800)
+        );
+        assert_eq!(parse_http_date("yesterday"), None);
+    }
+
+    #[test]
+    fn tls_failures_are_told_apart_from_unreachable_sites() {
+        let failed = |err: &str| Probe {
+            result: Err(err.to_string()),
+            elapsed: Duration::ZERO,
+        };
+        let site = "https://example.atlassian.net/wiki";
+        let untrusted = failed("error sending request: invalid peer certificate: UnknownIssuer");
+        assert_eq!(tls_check(site, &untrusted).level, Level::Fail);
+        assert_eq!(
+            tls_check(site, &failed("connection refused")).level,
+            Level::Skip
+        );
+        let plain = failed("connection refused");
+        assert_eq!(tls_check("http://wiki.internal", &plain).level, Level::Warn);
+    }
+
+    #[test]
+    fn near_limit_headers_warn() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", "3".parse().unwrap());
+        headers.insert("x-ratelimit-nearlimit", "true".parse().unwrap());
+        let probe = Probe {
+            result: Ok((StatusCode::OK, headers, String::new())),
+            elapsed: Duration::ZERO,
+        };
+        let missing = Probe {
+            result: Err("down".to_string()),
+            elapsed: Duration::ZERO,
+        };
+        let check = rate_limit_check([&probe, &missing]);
+        assert_eq!(check.level, Level::Warn);
+        assert_eq!(
+            check.detail,
+            "x-ratelimit-remaining: 3, x-ratelimit-nearlimit: true"
+        );
+    }
+}
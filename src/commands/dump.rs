@@ -0,0 +1,251 @@
+//! `dump`: every page of a space as one JSON document each, with the body converted to
+//! markdown, as input for embedding and RAG pipelines. Documents are streamed as NDJSON while
+//! bodies are fetched; `--state` makes re-runs emit only pages modified since the last one.
+
+use anyhow::{Context, Result, anyhow};
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::markdown::html_to_markdown;
+use futures_util::{StreamExt, stream};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::io::Write;
+use std::path::Path;
+
+use crate::cli::{DumpArgs, DumpField, DumpFormat};
+use crate::context::AppContext;
+use crate::download::fetch_page_with_body_format;
+use crate::helpers::{maybe_print_json, print_line, url_with_query, web_url};
+use crate::lifecycle::{format_date, parse_since, today};
+use crate::resolve::resolve_space_id;
+
+/// Page bodies fetched at once without `--concurrency`.
+const CONCURRENCY: usize = 4;
+
+const ALL_FIELDS: [DumpField; 8] = [
+    DumpField::Id,
+    DumpField::Title,
+    DumpField::Url,
+    DumpField::Space,
+    DumpField::ParentId,
+    DumpField::Version,
+    DumpField::LastModified,
+    DumpField::MarkdownBody,
+];
+
+/// What `--state` remembers between runs.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DumpState {
+    space: String,
+    /// Newest page modification seen, as the API reports it (`version.createdAt`).
+    last_modified: String,
+}
+
+pub async fn handle(ctx: &AppContext, args: DumpArgs) -> Result<()> {
+    let fields = if args.fields.is_empty() {
+        ALL_FIELDS.to_vec()
+    } else {
+        args.fields.clone()
+    };
+    let state = match &args.state {
+        Some(path) => read_state(path, &args.space)?,
+        None => None,
+    };
+    let since = args
+        .since
+        .as_deref()
+        .map(|since| parse_since(since, today()).map(format_date))
+        .transpose()?;
+
+    let client = crate::context::load_client(ctx)?;
+    let space_id = resolve_space_id(&client, &args.space).await?;
+    let url = url_with_query(
+        &client.v2_url(&format!("/spaces/{space_id}/pages")),
+        &[("limit", "250".to_string())],
+    )?;
+    let pages = client
+        .get_paginated_results(url, true)
+        .await
+        .with_context(|| format!("Failed to list pages of space {}", args.space))?;
+    let newest = pages.iter().map(last_modified).max().map(str::to_string);
+    let selected: Vec<&Value> = pages
+        .iter()
+        .filter(|page| {
+            let modified = last_modified(page);
+            since.as_deref().is_none_or(|since| modified >= since)
+                && state
+                    .as_ref()
+                    .is_none_or(|state| modified > state.last_modified.as_str())
+        })
+        .collect();
+    tracing::info!(
+        "Dumping {} of {} pages in {}",
+        selected.len(),
+        pages.len(),
+        args.space
+    );
+
+    let with_body = fields.contains(&DumpField::MarkdownBody);
+    let mut documents = stream::iter(selected)
+        .map(|page| {
+            let client = &client;
+            let fields = &fields;
+            let space = args.space.as_str();
+            async move {
+                let body = if with_body {
+                    Some(markdown_body(client, &json_str(page, "id")).await?)
+                } else {
+                    None
+                };
+                Ok::<_, anyhow::Error>(document(client.base_url(), space, page, body, fields))
+            }
+        })
+        .buffered(ctx.workers(CONCURRENCY));
+
+    let mut collected = Vec::new();
+    while let Some(document) = documents.next().await {
+        let document = document?;
+        match args.output {
+            DumpFormat::Ndjson => print_line(ctx, &serde_json::to_string(&document)?),
+            DumpFormat::Json => collected.push(document),
+        }
+    }
+    if args.output == DumpFormat::Json {
+        maybe_print_json(ctx, &collected)?;
+    }
+
+    // Only after every document is out, so a failed run is repeated in full next time.
+    if let (Some(path), Some(newest)) = (&args.state, newest) {
+        let state = DumpState {
+            space: args.space.clone(),
+            last_modified: newest,
+        };
+        write_state(path, &state)?;
+    }
+    Ok(())
+}
+
+fn last_modified(page: &Value) -> &str {
+    page.pointer("/version/createdAt")
+        .and_then(Value::as_str)
+        .unwrap_or("")
+}
+
+async fn markdown_body(client: &ApiClient, page_id: &str) -> Result<String> {
+    let (_, view) = fetch_page_with_body_format(client, page_id, "view").await?;
+    html_to_markdown(&view, client.base_url())
+        .with_context(|| format!("Failed to convert page {page_id} to markdown"))
+}
+
+/// The requested fields of `page`.
+fn document(
+    base_url: &str,
+    space: &str,
+    page: &Value,
+    body: Option<String>,
+    fields: &[DumpField],
+) -> Value {
+    let mut doc = Map::new();
+    for field in fields {
+        let (key, value) = match field {
+            DumpField::Id => ("id", json!(json_str(page, "id"))),
+            DumpField::Title => ("title", json!(json_str(page, "title"))),
+            DumpField::Url => ("url", json!(web_url(base_url, page))),
+            DumpField::Space => ("space", json!(space)),
+            DumpField::ParentId => (
+                "parentId",
+                page.get("parentId").cloned().unwrap_or(Value::Null),
+            ),
+            DumpField::Version => (
+                "version",
+                page.pointer("/version/number")
+                    .cloned()
+                    .unwrap_or(Value::Null),
+            ),
+            DumpField::LastModified => ("lastModified", json!(last_modified(page))),
+            DumpField::MarkdownBody => ("markdownBody", json!(body.as_deref().unwrap_or(""))),
+        };
+        doc.insert(key.to_string(), value);
+    }
+    Value::Object(doc)
+}
+
+fn read_state(path: &Path, space: &str) -> Result<Option<DumpState>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read state file: {}", path.display()))?;
+    let state: DumpState = serde_json::from_str(&data)
+        .with_context(|| format!("Invalid state file: {}", path.display()))?;
+    if !state.space.eq_ignore_ascii_case(space) {
+        return Err(anyhow!(
+            "State file {} belongs to space {}, not {space}",
+            path.display(),
+            state.space
+        ));
+    }
+    Ok(Some(state))
+}
+
+/// Replace the state file atomically, so an interrupted write never loses the last one.
+fn write_state(path: &Path, state: &DumpState) -> Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut tmp = tempfile::NamedTempFile::new_in(dir)
+        .with_context(|| format!("Failed to create temp file in {}", dir.display()))?;
+    tmp.write_all(serde_json::to_string_pretty(state)?.as_bytes())
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    tmp.persist(path)
+        .map_err(|e| e.error)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn documents_hold_only_the_requested_fields() {
+        let page = json!({
+            "id": "42",
+            "title": "Runbook",
+            "parentId": "7",
+            "version": { "number": 3, "createdAt": "2024-05-01T10:00:00.000Z" },
+            "_links": { "webui": "/spaces/OPS/pages/42/Runbook" },
+        });
+        let doc = document(
+            "https://example.atlassian.net/wiki",
+            "OPS",
+            &page,
+            Some("# Runbook".to_string()),
+            &[DumpField::Url, DumpField::Id, DumpField::MarkdownBody],
+        );
+        assert_eq!(
+            serde_json::to_string(&doc).unwrap(),
+            r##"{"id":"42","markdownBody":"# Runbook","url":"https://example.atlassian.net/wiki/spaces/OPS/pages/42/Runbook"}"##
+        );
+        let all = document("", "OPS", &page, None, &ALL_FIELDS);
+        assert_eq!(all["lastModified"], "2024-05-01T10:00:00.000Z");
+        assert_eq!(all["version"], 3);
+    }
+
+    #[test]
+    fn state_files_belong_to_one_space() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        assert!(read_state(&path, "OPS").unwrap().is_none());
+        let state = DumpState {
+            space: "OPS".to_string(),
+            last_modified: "2024-05-01T10:00:00.000Z".to_string(),
+        };
+        write_state(&path, &state).unwrap();
+        let read = read_state(&path, "ops").unwrap().unwrap();
+        assert_eq!(read.last_modified, state.last_modified);
+        assert!(read_state(&path, "ENG").is_err());
+    }
+}
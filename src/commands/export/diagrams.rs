@@ -0,0 +1,227 @@
+//! draw.io and Gliffy diagrams. Both macros render client-side, so the exported body has
+//! nothing to show for them; instead we find the macros in the storage body and copy each
+//! diagram's source attachment and PNG preview into `diagrams/`.
+
+use anyhow::Result;
+use confcli::client::ApiClient;
+use confcli::markdown::decode_html_entities;
+use regex::Regex;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use tokio::sync::Semaphore;
+use url::Url;
+
+use super::download_attachment_item;
+use crate::download::sanitize_filename;
+
+static DIAGRAM_MACRO_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?s)<ac:structured-macro\b[^>]*ac:name="(drawio|inc-drawio|gliffy)"[^>]*>(.*?)</ac:structured-macro>"#,
+    )
+    .expect("DIAGRAM_MACRO_RE")
+});
+static MACRO_PARAM_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?s)<ac:parameter ac:name="([^"]+)">(.*?)</ac:parameter>"#)
+        .expect("MACRO_PARAM_RE")
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum DiagramKind {
+    Drawio,
+    Gliffy,
+}
+
+impl DiagramKind {
+    /// Extension for the exported source file.
+    pub(super) fn source_extension(self) -> &'static str {
+        match self {
+            Self::Drawio => "drawio",
+            Self::Gliffy => "gliffy",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Diagram {
+    pub kind: DiagramKind,
+    pub name: String,
+}
+
+/// Whether a rendered (view) body may contain diagrams, to avoid fetching the storage body
+/// for pages that have none.
+pub(super) fn may_contain_diagrams(body: &str) -> bool {
+    let lower = body.to_ascii_lowercase();
+    lower.contains("drawio") || lower.contains("gliffy")
+}
+
+/// Diagram macros in a storage-format body, de-duplicated in page order.
+pub(super) fn find_diagrams(storage: &str) -> Vec<Diagram> {
+    let mut diagrams: Vec<Diagram> = Vec::new();
+    for caps in DIAGRAM_MACRO_RE.captures_iter(storage) {
+        let kind = if &caps[1] == "gliffy" {
+            DiagramKind::Gliffy
+        } else {
+            DiagramKind::Drawio
+        };
+        let wanted = match kind {
+            DiagramKind::Drawio => "diagramName",
+            DiagramKind::Gliffy => "name",
+        };
+        let name = MACRO_PARAM_RE
+            .captures_iter(&caps[2])
+            .find(|param| &param[1] == wanted)
+            .map(|param| decode_html_entities(param[2].trim()))
+            .unwrap_or_default();
+        let diagram = Diagram { kind, name };
+        if !diagram.name.is_empty() && !diagrams.contains(&diagram) {
+            diagrams.push(diagram);
+        }
+    }
+    diagrams
+}
+
+/// The source attachment and PNG preview backing `diagram`, if present.
+///
+/// draw.io stores the XML under the diagram name (newer versions add `.drawio`) with a
+/// `.png` preview next to it; Gliffy uses the name and `name.png`.
+pub(super) fn diagram_attachments<'a>(
+    diagram: &Diagram,
+    attachments: &'a [Value],
+) -> (Option<&'a Value>, Option<&'a Value>) {
+    let find = |candidates: &[String]| {
+        candidates.iter().find_map(|name| {
+            attachments
+                .iter()
+                .find(|item| item.get("title").and_then(|v| v.as_str()) == Some(name.as_str()))
+        })
+    };
+    let name = &diagram.name;
+    let (sources, previews) = match diagram.kind {
+        DiagramKind::Drawio => (
+            vec![name.clone(), format!("{name}.drawio")],
+            vec![format!("{name}.png"), format!("{name}.drawio.png")],
+        ),
+        DiagramKind::Gliffy => (vec![name.clone()], vec![format!("{name}.png")]),
+    };
+    (find(&sources), find(&previews))
+}
+
+/// Files written for one diagram, relative to the page's export folder.
+pub(super) struct ExportedDiagram {
+    pub name: String,
+    pub kind: DiagramKind,
+    pub source: Option<PathBuf>,
+    pub preview: Option<PathBuf>,
+}
+
+/// Download each diagram's source and preview into `<out_dir>/diagrams/`.
+pub(super) async fn export_diagrams(
+    client: &ApiClient,
+    sem: &Semaphore,
+    origin: &Url,
+    out_dir: &Path,
+    diagrams: &[Diagram],
+    attachments: &[Value],
+    verbose: u8,
+) -> Result<Vec<ExportedDiagram>> {
+    let dir = out_dir.join("diagrams");
+    let mut exported = Vec::new();
+    for diagram in diagrams {
+        let (source, preview) = diagram_attachments(diagram, attachments);
+        if source.is_none() && preview.is_none() {
+            tracing::debug!("No attachments found for diagram '{}'", diagram.name);
+            continue;
+        }
+        tokio::fs::create_dir_all(&dir).await?;
+        let stem = sanitize_filename(&diagram.name);
+        let mut files = [None, None];
+        let wanted = [(source, diagram.kind.source_extension()), (preview, "png")];
+        for (slot, (item, ext)) in files.iter_mut().zip(wanted) {
+            let Some(item) = item else { continue };
+            let title = item.get("title").and_then(|v| v.as_str()).unwrap_or("");
+            let file_name = format!("{stem}.{ext}");
+            let _permit = sem.acquire().await?;
+            download_attachment_item(client, origin, item, title, &dir.join(&file_name), verbose)
+                .await?;
+            *slot = Some(Path::new("diagrams").join(file_name));
+        }
+        let [source, preview] = files;
+        exported.push(ExportedDiagram {
+            name: diagram.name.clone(),
+            kind: diagram.kind,
+            source,
+            preview,
+        });
+    }
+    Ok(exported)
+}
+
+/// Markdown section referencing exported diagrams: the PNG preview inline and a link to
+/// the editable source. `prefix` is the link path of the page's folder relative to the
+/// markdown file (empty when they sit side by side).
+pub(super) fn diagrams_markdown(diagrams: &[ExportedDiagram], prefix: &str) -> String {
+    let prefix = if prefix.is_empty() {
+        String::new()
+    } else {
+        format!("{prefix}/")
+    };
+    let mut out = String::from("## Diagrams");
+    for diagram in diagrams {
+        let label = match diagram.kind {
+            DiagramKind::Drawio => "draw.io source",
+            DiagramKind::Gliffy => "Gliffy source",
+        };
+        let name = diagram.name.replace(['[', ']'], "");
+        if let Some(preview) = &diagram.preview {
+            out.push_str(&format!("\n\n![{name}](<{prefix}{}>)", preview.display()));
+        }
+        if let Some(source) = &diagram.source {
+            out.push_str(&format!(
+                "\n\n[{name} ({label})](<{prefix}{}>)",
+                source.display()
+            ));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn finds_drawio_and_gliffy_macros() {
+        let storage = r#"<p>Intro</p><ac:structured-macro ac:name="drawio" ac:schema-version="1"><ac:parameter ac:name="border">true</ac:parameter><ac:parameter ac:name="diagramName">Flow &amp; states</ac:parameter></ac:structured-macro><ac:structured-macro ac:name="gliffy"><ac:parameter ac:name="name">Network</ac:parameter></ac:structured-macro><ac:structured-macro ac:name="drawio"><ac:parameter ac:name="diagramName">Flow &amp; states</ac:parameter></ac:structured-macro>"#;
+        assert_eq!(
+            find_diagrams(storage),
+            vec![
+                Diagram {
+                    kind: DiagramKind::Drawio,
+                    name: "Flow & states".to_string()
+                },
+                Diagram {
+                    kind: DiagramKind::Gliffy,
+                    name: "Network".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn matches_source_and_preview_attachments() {
+        let attachments = vec![
+            json!({ "title": "Flow.drawio" }),
+            json!({ "title": "Flow.png" }),
+            json!({ "title": "Other.png" }),
+        ];
+        let diagram = Diagram {
+            kind: DiagramKind::Drawio,
+            name: "Flow".to_string(),
+        };
+        let (source, preview) = diagram_attachments(&diagram, &attachments);
+        assert_eq!(source.unwrap()["title"], "Flow.drawio");
+        assert_eq!(preview.unwrap()["title"], "Flow.png");
+    }
+}
@@ -0,0 +1,208 @@
+//! `export --estimate`: size up an export from metadata alone (page versions and attachment
+//! sizes) before committing to a long download.
+
+use anyhow::Result;
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::output::OutputFormat;
+use futures_util::stream::{self, StreamExt};
+use serde_json::{Value, json};
+
+use crate::cli::ExportArgs;
+use crate::context::AppContext;
+use crate::helpers::*;
+use crate::progress::Progress;
+
+/// Page size of the attachment listing `export` itself uses.
+const EXPORT_ATTACHMENT_PAGE: usize = 50;
+
+#[derive(Debug, Default, PartialEq)]
+struct PageEstimate {
+    id: String,
+    title: String,
+    version: u64,
+    /// Every attachment of the page: `export` lists them all, then applies `--pattern`.
+    listed: usize,
+    /// The attachments `--pattern` selects, which are the ones downloaded.
+    attachments: usize,
+    attachment_bytes: u64,
+}
+
+impl PageEstimate {
+    /// Requests `export` makes for this page: the body, the attachment listing, and one
+    /// download per attachment. Space lookups, includes, and diagrams are not counted.
+    fn requests(&self, skip_attachments: bool) -> usize {
+        if skip_attachments {
+            return 1;
+        }
+        1 + self.listed.div_ceil(EXPORT_ATTACHMENT_PAGE).max(1) + self.attachments
+    }
+}
+
+pub(super) async fn estimate(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: &ExportArgs,
+    pages: Vec<(String, String)>,
+    listing_requests: u64,
+) -> Result<()> {
+    let matcher = args
+        .pattern
+        .as_deref()
+        .map(confcli::pattern::glob_to_regex_ci)
+        .transpose()?;
+    let bar = Progress::items(ctx, "pages", pages.len() as u64);
+    let mut estimates: Vec<(usize, Result<PageEstimate>)> =
+        stream::iter(pages.into_iter().enumerate())
+            .map(|(idx, (id, title))| {
+                let matcher = matcher.as_ref();
+                let bar = bar.clone();
+                async move {
+                    let estimate =
+                        estimate_page(client, id, title, args.skip_attachments, matcher).await;
+                    if let Some(bar) = &bar {
+                        bar.inc(1);
+                    }
+                    (idx, estimate)
+                }
+            })
+            .buffer_unordered(ctx.workers(super::CONCURRENCY))
+            .collect()
+            .await;
+    if let Some(bar) = bar {
+        bar.finish();
+    }
+    estimates.sort_by_key(|(idx, _)| *idx);
+    let estimates = estimates
+        .into_iter()
+        .map(|(_, estimate)| estimate)
+        .collect::<Result<Vec<_>>>()?;
+
+    let versions: u64 = estimates.iter().map(|page| page.version).sum();
+    let attachments: usize = estimates.iter().map(|page| page.attachments).sum();
+    let attachment_bytes: u64 = estimates.iter().map(|page| page.attachment_bytes).sum();
+    let requests = listing_requests as usize
+        + estimates
+            .iter()
+            .map(|page| page.requests(args.skip_attachments))
+            .sum::<usize>();
+    // Under --pace, the rate rather than the site bounds how long the export takes.
+    let paced_minutes = args
+        .pace
+        .map(|pace| (requests as u64).div_ceil(u64::from(pace)));
+
+    match args.output {
+        OutputFormat::Json => {
+            let pages: Vec<Value> = estimates
+                .iter()
+                .map(|page| {
+                    json!({
+                        "id": page.id,
+                        "title": page.title,
+                        "version": page.version,
+                        "attachments": page.attachments,
+                        "attachmentBytes": page.attachment_bytes,
+                    })
+                })
+                .collect();
+            maybe_print_json(
+                ctx,
+                &json!({
+                    "pages": estimates.len(),
+                    "versions": versions,
+                    "attachments": attachments,
+                    "attachmentBytes": attachment_bytes,
+                    "requests": requests,
+                    "pacedMinutes": paced_minutes,
+                    "items": pages,
+                }),
+            )
+        }
+        fmt => {
+            let mut rows = vec![
+                vec!["Pages".to_string(), estimates.len().to_string()],
+                vec!["Versions".to_string(), versions.to_string()],
+                vec!["Attachments".to_string(), attachments.to_string()],
+                vec![
+                    "Download".to_string(),
+                    format!(
+                        "{} of attachments, plus page bodies",
+                        human_size(attachment_bytes as i64)
+                    ),
+                ],
+                vec!["Requests".to_string(), format!("about {requests}")],
+            ];
+            if let Some(minutes) = paced_minutes {
+                rows.push(vec![
+                    "At --pace".to_string(),
+                    format!("about {minutes} min of requests"),
+                ]);
+            }
+            maybe_print_kv_fmt(ctx, fmt, rows);
+            Ok(())
+        }
+    }
+}
+
+async fn estimate_page(
+    client: &ApiClient,
+    id: String,
+    title: String,
+    skip_attachments: bool,
+    matcher: Option<&regex::Regex>,
+) -> Result<PageEstimate> {
+    let (page, _) = client
+        .get_json(client.v2_url(&format!("/pages/{id}")))
+        .await?;
+    let mut estimate = PageEstimate {
+        title: if title.is_empty() {
+            json_str(&page, "title")
+        } else {
+            title
+        },
+        version: page
+            .pointer("/version/number")
+            .and_then(Value::as_u64)
+            .unwrap_or(0),
+        id,
+        ..Default::default()
+    };
+    if skip_attachments {
+        return Ok(estimate);
+    }
+    let url = client.v2_url(&format!("/pages/{}/attachments?limit=250", estimate.id));
+    for item in client.get_paginated_results(url, true).await? {
+        estimate.listed += 1;
+        let selected = matcher.is_none_or(|re| re.is_match(&json_str(&item, "title")));
+        if selected {
+            estimate.attachments += 1;
+            estimate.attachment_bytes += item.get("fileSize").and_then(Value::as_u64).unwrap_or(0);
+        }
+    }
+    Ok(estimate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_export_requests_per_page() {
+        let page = PageEstimate {
+            listed: 120,
+            attachments: 120,
+            ..Default::default()
+        };
+        // Body, three listing pages of 50, and 120 downloads.
+        assert_eq!(page.requests(false), 124);
+        assert_eq!(page.requests(true), 1);
+        // --pattern picks what is downloaded; the listing still pages through everything.
+        let filtered = PageEstimate {
+            listed: 120,
+            attachments: 4,
+            ..Default::default()
+        };
+        assert_eq!(filtered.requests(false), 8);
+        assert_eq!(PageEstimate::default().requests(false), 2);
+    }
+}
@@ -0,0 +1,105 @@
+//! `export --git-commit`: snapshot the export destination in its git repository, so repeated
+//! exports build a diffable history of the documentation.
+
+use anyhow::{Context, Result, anyhow};
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn git(dir: &Path, args: &[&str]) -> Result<Output> {
+    Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .context("Failed to run git (is it installed and on PATH?)")
+}
+
+fn git_ok(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = git(dir, args)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!("git {} failed: {}", args.join(" "), stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Fail unless `dir` is inside a git work tree. Checked before exporting, so a typo in
+/// `--dest` doesn't cost a full export.
+pub(super) fn ensure_repo(dir: &Path) -> Result<()> {
+    let inside = git(dir, &["rev-parse", "--is-inside-work-tree"])?;
+    if !inside.status.success() || String::from_utf8_lossy(&inside.stdout).trim() != "true" {
+        return Err(anyhow!(
+            "--git-commit: {} is not inside a git repository",
+            dir.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Stage everything under `dir` and commit it with `message`. Only paths under `dir` are
+/// committed, whatever else is staged in the repository. Returns the new commit's short
+/// hash, or `None` when the export changed nothing.
+pub(super) fn commit_snapshot(dir: &Path, message: &str) -> Result<Option<String>> {
+    git_ok(dir, &["add", "--all", "--", "."])?;
+    let unchanged = git(dir, &["diff", "--cached", "--quiet", "--", "."])?;
+    if unchanged.status.success() {
+        return Ok(None);
+    }
+    git_ok(dir, &["commit", "--quiet", "--message", message, "--", "."])?;
+    git_ok(dir, &["rev-parse", "--short", "HEAD"]).map(Some)
+}
+
+/// Commit message for a snapshot: a one-line summary plus the details.
+pub(super) fn commit_message(
+    site: &str,
+    spaces: &[String],
+    pages: usize,
+    timestamp: &str,
+) -> String {
+    let spaces = if spaces.is_empty() {
+        "unknown space".to_string()
+    } else {
+        spaces.join(", ")
+    };
+    format!(
+        "Confluence export of {spaces} at {timestamp}\n\nSite: {site}\nSpaces: {spaces}\nPages: {pages}\nExported: {timestamp}\n"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_repo() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        git_ok(dir.path(), &["init", "--quiet"]).unwrap();
+        git_ok(dir.path(), &["config", "user.name", "confcli test"]).unwrap();
+        git_ok(dir.path(), &["config", "user.email", "test@example.com"]).unwrap();
+        dir
+    }
+
+    #[test]
+    fn commits_only_when_something_changed() {
+        let repo = init_repo();
+        let dest = repo.path().join("docs");
+        std::fs::create_dir_all(&dest).unwrap();
+        std::fs::write(repo.path().join("other.txt"), "staged elsewhere").unwrap();
+        git_ok(repo.path(), &["add", "other.txt"]).unwrap();
+        std::fs::write(dest.join("page.md"), "v1").unwrap();
+
+        ensure_repo(&dest).unwrap();
+        let message = commit_message("https://x.test/wiki", &["OPS".to_string()], 1, "now");
+        assert!(commit_snapshot(&dest, &message).unwrap().is_some());
+        assert_eq!(commit_snapshot(&dest, &message).unwrap(), None);
+
+        let files = git_ok(repo.path(), &["show", "--name-only", "--format=%s", "HEAD"]).unwrap();
+        assert_eq!(files, "Confluence export of OPS at now\n\ndocs/page.md");
+    }
+
+    #[test]
+    fn rejects_directories_outside_a_repo() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = ensure_repo(dir.path()).unwrap_err();
+        assert!(err.to_string().contains("not inside a git repository"));
+    }
+}
@@ -0,0 +1,273 @@
+//! `export --resolve-includes`: inline `include` and `excerpt-include` macros so an exported
+//! page no longer depends on the pages it pulls content from.
+
+use anyhow::Result;
+use confcli::client::ApiClient;
+use confcli::markdown::decode_html_entities;
+use regex::Regex;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::LazyLock;
+
+use crate::download::fetch_page_with_body_format;
+use crate::resolve::resolve_page_id;
+
+static INCLUDE_START_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<ac:structured-macro\b[^>]*ac:name="(include|excerpt-include)"[^>]*>"#)
+        .expect("INCLUDE_START_RE")
+});
+static EXCERPT_START_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<ac:structured-macro\b[^>]*ac:name="excerpt"[^>]*>"#).expect("EXCERPT_START_RE")
+});
+static MACRO_TAG_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<(/?)ac:structured-macro\b[^>]*?(/?)>").expect("MACRO_TAG_RE"));
+static RI_PAGE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<ri:page\b([^>]*?)/?>").expect("RI_PAGE_RE"));
+static RI_ATTR_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"ri:(content-title|space-key)="([^"]*)""#).expect("RI_ATTR_RE"));
+
+/// Inlines includes for one exported page. Fetched bodies are cached, so a page included
+/// several times is only downloaded once.
+pub(super) struct IncludeResolver<'a> {
+    client: &'a ApiClient,
+    max_depth: usize,
+    /// Page id -> (space key, storage body).
+    bodies: HashMap<String, (String, String)>,
+}
+
+impl<'a> IncludeResolver<'a> {
+    pub(super) fn new(client: &'a ApiClient, max_depth: usize) -> Self {
+        Self {
+            client,
+            max_depth,
+            bodies: HashMap::new(),
+        }
+    }
+
+    /// Return `storage` (the body of `page_id` in `space_key`) with every include macro
+    /// replaced by the referenced content. Cycles and includes nested deeper than the
+    /// limit are replaced by a short note instead.
+    pub(super) async fn resolve(
+        &mut self,
+        page_id: &str,
+        space_key: &str,
+        storage: &str,
+    ) -> Result<String> {
+        let mut stack = vec![page_id.to_string()];
+        self.resolve_inner(space_key, storage.to_string(), &mut stack)
+            .await
+    }
+
+    fn resolve_inner<'s>(
+        &'s mut self,
+        space_key: &'s str,
+        storage: String,
+        stack: &'s mut Vec<String>,
+    ) -> Pin<Box<dyn Future<Output = Result<String>> + Send + 's>> {
+        Box::pin(async move {
+            let mut out = String::with_capacity(storage.len());
+            let mut rest = storage.as_str();
+            while let Some(found) = INCLUDE_START_RE.captures(rest) {
+                let start = found.get(0).map(|m| m.start()).unwrap_or(0);
+                let Some(end) = macro_end(rest, start) else {
+                    break;
+                };
+                let excerpt_only = &found[1] == "excerpt-include";
+                let segment = &rest[start..end];
+                out.push_str(&rest[..start]);
+                rest = &rest[end..];
+
+                let Some((space, title)) = include_target(segment, space_key) else {
+                    out.push_str(segment);
+                    continue;
+                };
+                let target = format!("{space}:{title}");
+                let page_id = match resolve_page_id(self.client, &target).await {
+                    Ok(id) => id,
+                    Err(_) => {
+                        out.push_str(&note(&format!("include of \"{target}\" not found")));
+                        continue;
+                    }
+                };
+                if stack.contains(&page_id) {
+                    out.push_str(&note(&format!("include of \"{target}\" skipped: cycle")));
+                    continue;
+                }
+                if stack.len() > self.max_depth {
+                    out.push_str(&note(&format!(
+                        "include of \"{target}\" skipped: deeper than {}",
+                        self.max_depth
+                    )));
+                    continue;
+                }
+
+                let (target_space, body) = self.body(&page_id, &space).await?;
+                let body = if excerpt_only {
+                    excerpt_body(&body).unwrap_or_default()
+                } else {
+                    body
+                };
+                stack.push(page_id);
+                let inlined = self.resolve_inner(&target_space, body, stack).await?;
+                stack.pop();
+                out.push_str(&inlined);
+            }
+            out.push_str(rest);
+            Ok(out)
+        })
+    }
+
+    async fn body(&mut self, page_id: &str, space_key: &str) -> Result<(String, String)> {
+        if let Some(cached) = self.bodies.get(page_id) {
+            return Ok(cached.clone());
+        }
+        let (_, storage) = fetch_page_with_body_format(self.client, page_id, "storage").await?;
+        let entry = (space_key.to_string(), storage);
+        self.bodies.insert(page_id.to_string(), entry.clone());
+        Ok(entry)
+    }
+}
+
+/// Render a resolved storage body to view HTML, so it can go through the usual markdown
+/// conversion.
+pub(super) async fn storage_to_view(
+    client: &ApiClient,
+    page_id: &str,
+    storage: &str,
+) -> Result<String> {
+    client.convert_storage_to_view(page_id, storage).await
+}
+
+fn note(text: &str) -> String {
+    format!("<p><em>[{text}]</em></p>")
+}
+
+/// `(space key, title)` of the page an include macro points at. The space defaults to the
+/// including page's space.
+fn include_target(segment: &str, space_key: &str) -> Option<(String, String)> {
+    let page = RI_PAGE_RE.captures(segment)?;
+    let mut title = None;
+    let mut space = space_key.to_string();
+    for attr in RI_ATTR_RE.captures_iter(&page[1]) {
+        let value = decode_html_entities(&attr[2]);
+        match &attr[1] {
+            "content-title" => title = Some(value),
+            _ => space = value,
+        }
+    }
+    Some((space, title.filter(|t| !t.is_empty())?))
+}
+
+/// Body of the first `excerpt` macro in a page.
+fn excerpt_body(storage: &str) -> Option<String> {
+    let found = EXCERPT_START_RE.find(storage)?;
+    let end = macro_end(storage, found.start())?;
+    let segment = &storage[found.start()..end];
+    let start = segment.find("<ac:rich-text-body>")? + "<ac:rich-text-body>".len();
+    let stop = segment.rfind("</ac:rich-text-body>")?;
+    segment.get(start..stop).map(str::to_string)
+}
+
+/// Byte offset just past the `</ac:structured-macro>` closing the macro opened at `start`.
+fn macro_end(storage: &str, start: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for tag in MACRO_TAG_RE.captures_iter(&storage[start..]) {
+        let whole = tag.get(0)?;
+        if !tag[2].is_empty() {
+            if depth == 0 {
+                return Some(start + whole.end());
+            }
+            continue;
+        }
+        if tag[1].is_empty() {
+            depth += 1;
+        } else {
+            depth = depth.checked_sub(1)?;
+            if depth == 0 {
+                return Some(start + whole.end());
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::http_server::start_server;
+
+    fn include(title: &str) -> String {
+        format!(
+            r#"<ac:structured-macro ac:name="include"><ac:parameter ac:name=""><ac:link><ri:page ri:content-title="{title}" /></ac:link></ac:parameter></ac:structured-macro>"#
+        )
+    }
+
+    fn page(id: &str, storage: &str) -> Vec<u8> {
+        serde_json::json!({ "id": id, "body": { "storage": { "value": storage } } })
+            .to_string()
+            .into_bytes()
+    }
+
+    #[test]
+    fn parses_include_targets() {
+        let segment = r#"<ri:page ri:space-key="OPS" ri:content-title="A &amp; B" />"#;
+        assert_eq!(
+            include_target(segment, "ENG"),
+            Some(("OPS".to_string(), "A & B".to_string()))
+        );
+        let segment = r#"<ri:page ri:content-title="Local" />"#;
+        assert_eq!(
+            include_target(segment, "ENG"),
+            Some(("ENG".to_string(), "Local".to_string()))
+        );
+    }
+
+    #[test]
+    fn extracts_excerpt_body() {
+        let storage = r#"<p>x</p><ac:structured-macro ac:name="excerpt"><ac:rich-text-body><p>Short</p></ac:rich-text-body></ac:structured-macro>"#;
+        assert_eq!(excerpt_body(storage).as_deref(), Some("<p>Short</p>"));
+    }
+
+    #[tokio::test]
+    async fn inlines_includes_and_stops_cycles() {
+        // Space key and titles are unique to this test because the resolve caches are
+        // process-wide.
+        let a_body = format!("<p>A</p>{}", include("IncRoot"));
+        let srv = start_server(move |_, path| {
+            let body = if path.starts_with("/pages/9200002?") {
+                page("9200002", &a_body)
+            } else if path.starts_with("/pages?") && path.contains("title=IncA") {
+                br#"{"results":[{"id":"9200002"}]}"#.to_vec()
+            } else if path.starts_with("/pages?") && path.contains("title=IncRoot") {
+                br#"{"results":[{"id":"9200001"}]}"#.to_vec()
+            } else if path.starts_with("/spaces?") {
+                br#"{"results":[{"id":"9200010","key":"INCL"}]}"#.to_vec()
+            } else {
+                return (404, vec![], b"{}".to_vec());
+            };
+            (200, vec![], body)
+        })
+        .await;
+        let client = ApiClient::new(
+            srv.base_url.clone(),
+            srv.base_url.clone(),
+            srv.base_url.clone(),
+            confcli::auth::AuthMethod::Bearer {
+                token: "test".to_string(),
+            },
+            0,
+        )
+        .unwrap();
+
+        let mut resolver = IncludeResolver::new(&client, 5);
+        let root = format!("<p>Root</p>{}", include("IncA"));
+        let out = resolver.resolve("9200001", "INCL", &root).await.unwrap();
+        assert_eq!(
+            out,
+            "<p>Root</p><p>A</p><p><em>[include of \"INCL:IncRoot\" skipped: cycle]</em></p>"
+        );
+
+        let _ = srv.shutdown.send(());
+    }
+}
@@ -0,0 +1,218 @@
+//! The export manifest: `.confcli-export.json` at the root of the destination, recording the
+//! pages (and their versions) and files that exports into it produced. `export status` and
+//! `export verify` read it back.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+pub(super) const MANIFEST_FILE: &str = ".confcli-export.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct Manifest {
+    pub site_url: String,
+    /// UTC time of the latest export into the destination.
+    pub exported_at: String,
+    /// Pages exported with `--recursive`; their descendants missing from `pages` are new.
+    #[serde(default)]
+    pub trees: Vec<String>,
+    pub pages: Vec<ManifestPage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct ManifestPage {
+    pub id: String,
+    pub title: String,
+    pub version: u64,
+    pub files: Vec<ManifestFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct ManifestFile {
+    /// Relative to the destination, with `/` separators.
+    pub path: String,
+    pub size: u64,
+    /// Hex SHA-256 of the contents; absent in manifests written before hashes were recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Set for downloaded attachments.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachment_id: Option<String>,
+}
+
+impl ManifestFile {
+    /// Entry for a file written by the export, sized and hashed as it is on disk now.
+    pub fn read(dest: &Path, path: &Path, attachment_id: Option<String>) -> Result<Self> {
+        let (size, sha256) = sha256_file(path)?;
+        Ok(Self {
+            path: relative_path(dest, path),
+            size,
+            sha256: Some(sha256),
+            attachment_id,
+        })
+    }
+}
+
+impl ManifestPage {
+    pub fn size(&self) -> u64 {
+        self.files.iter().map(|file| file.size).sum()
+    }
+}
+
+/// Size and hex SHA-256 of a file, streamed so large attachments aren't held in memory.
+pub(super) fn sha256_file(path: &Path) -> Result<(u64, String)> {
+    let mut file =
+        std::fs::File::open(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let size = std::io::copy(&mut file, &mut hasher)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok((size, format!("{:x}", hasher.finalize())))
+}
+
+fn relative_path(dest: &Path, path: &Path) -> String {
+    let rel = path.strip_prefix(dest).unwrap_or(path);
+    rel.components()
+        .map(|part| part.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+impl Manifest {
+    /// The manifest in `dest`, or `None` if nothing has been exported there yet.
+    pub fn load(dest: &Path) -> Result<Option<Self>> {
+        let path = dest.join(MANIFEST_FILE);
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(err).with_context(|| format!("Failed to read {}", path.display()));
+            }
+        };
+        serde_json::from_slice(&data)
+            .with_context(|| format!("Invalid export manifest {}", path.display()))
+            .map(Some)
+    }
+
+    /// Like [`Manifest::load`], failing when `dest` has no manifest.
+    pub fn require(dest: &Path) -> Result<Self> {
+        Self::load(dest)?.ok_or_else(|| {
+            anyhow::anyhow!(
+                "No {MANIFEST_FILE} in {}; export into it first (confcli export PAGE --dest {})",
+                dest.display(),
+                dest.display()
+            )
+        })
+    }
+
+    pub fn save(&self, dest: &Path) -> Result<()> {
+        let path = dest.join(MANIFEST_FILE);
+        std::fs::write(&path, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Fold one export run into the manifest: its pages replace earlier entries with the same
+    /// id and the rest are kept. A manifest written for another site is started over.
+    pub fn merge(
+        &mut self,
+        site_url: &str,
+        exported_at: &str,
+        trees: Vec<String>,
+        pages: Vec<ManifestPage>,
+    ) {
+        if self.site_url != site_url {
+            *self = Self::default();
+            self.site_url = site_url.to_string();
+        }
+        self.exported_at = exported_at.to_string();
+        for tree in trees {
+            if !self.trees.contains(&tree) {
+                self.trees.push(tree);
+            }
+        }
+        for page in pages {
+            match self.pages.iter_mut().find(|known| known.id == page.id) {
+                Some(known) => *known = page,
+                None => self.pages.push(page),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(id: &str, version: u64) -> ManifestPage {
+        ManifestPage {
+            id: id.to_string(),
+            title: format!("Page {id}"),
+            version,
+            files: vec![ManifestFile {
+                path: format!("Page-{id}--{id}/page.md"),
+                size: 10,
+                sha256: None,
+                attachment_id: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn merge_replaces_reexported_pages() {
+        let mut manifest = Manifest::default();
+        manifest.merge(
+            "https://x.test/wiki",
+            "2024-05-01T10:00:00Z",
+            vec!["1".to_string()],
+            vec![page("1", 3), page("2", 1)],
+        );
+        manifest.merge(
+            "https://x.test/wiki",
+            "2024-05-02T10:00:00Z",
+            Vec::new(),
+            vec![page("2", 2), page("3", 1)],
+        );
+        assert_eq!(manifest.exported_at, "2024-05-02T10:00:00Z");
+        assert_eq!(manifest.trees, vec!["1"]);
+        assert_eq!(
+            manifest.pages,
+            vec![page("1", 3), page("2", 2), page("3", 1)]
+        );
+
+        manifest.merge(
+            "https://y.test/wiki",
+            "2024-05-03T10:00:00Z",
+            Vec::new(),
+            vec![page("9", 1)],
+        );
+        assert!(manifest.trees.is_empty());
+        assert_eq!(manifest.pages, vec![page("9", 1)]);
+    }
+
+    #[test]
+    fn files_are_sized_and_hashed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Home--1").join("page.md");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "abc").unwrap();
+        let file = ManifestFile::read(dir.path(), &path, None).unwrap();
+        assert_eq!(file.path, "Home--1/page.md");
+        assert_eq!(file.size, 3);
+        assert_eq!(
+            file.sha256.as_deref(),
+            Some("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+    }
+
+    #[test]
+    fn file_paths_are_relative_to_dest() {
+        let dest = Path::new("/tmp/export");
+        assert_eq!(
+            relative_path(dest, &dest.join("Home--1").join("page.md")),
+            "Home--1/page.md"
+        );
+    }
+}
@@ -0,0 +1,790 @@
+use anyhow::{Context, Result, anyhow};
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::markdown::{
+    CaptionMode, MarkdownFlavor, MarkdownOptions, TableMode, html_to_markdown_with_page_names,
+};
+use confcli::output::OutputFormat;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use url::Url;
+
+use crate::cli::{ExportArgs, ExportCommand, ExportSite, ProgressMode};
+use crate::context::AppContext;
+use crate::download::{
+    DownloadRetry, DownloadToFileOptions, attachment_download_url, download_to_file_with_retry,
+    fetch_page_with_body_format, sanitize_filename,
+};
+use crate::helpers::*;
+use crate::progress::Progress;
+use crate::resolve::{resolve_page_id, resolve_space_key};
+use crate::smart_links::unfurl_smart_links;
+
+mod diagrams;
+mod estimate;
+mod git;
+mod includes;
+mod manifest;
+mod obsidian;
+mod status;
+mod verify;
+
+/// Requests at once (page bodies and attachment downloads) without `--concurrency`.
+const CONCURRENCY: usize = 4;
+
+pub(crate) use obsidian::note_stem;
+
+pub async fn handle(ctx: &AppContext, mut args: ExportArgs) -> Result<()> {
+    if let Some(command) = args.command.take() {
+        return match command {
+            ExportCommand::Status(args) => status::handle(ctx, args).await,
+            ExportCommand::Verify(args) => verify::handle(ctx, args).await,
+        };
+    }
+    let client = crate::context::load_client(ctx)?.with_schedule(args.pace, args.run_window);
+    export_pages(&client, ctx, args).await
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ContentFormat {
+    Markdown,
+    Storage,
+    Adf,
+}
+
+impl ContentFormat {
+    fn parse(input: &str) -> Result<Self> {
+        match input.to_lowercase().as_str() {
+            "md" | "markdown" => Ok(Self::Markdown),
+            "storage" => Ok(Self::Storage),
+            "adf" | "atlas_doc_format" => Ok(Self::Adf),
+            _ => Err(anyhow!(
+                "Invalid --format: {input}. Use md, storage, or adf."
+            )),
+        }
+    }
+
+    fn body_format(self) -> &'static str {
+        match self {
+            Self::Markdown => "view",
+            Self::Storage => "storage",
+            Self::Adf => "atlas_doc_format",
+        }
+    }
+
+    fn file_name(self) -> &'static str {
+        match self {
+            Self::Markdown => "page.md",
+            Self::Storage => "page.storage.html",
+            Self::Adf => "page.adf.json",
+        }
+    }
+}
+
+/// Settings shared by every page of one export run.
+struct ExportOptions {
+    dest: PathBuf,
+    format: ContentFormat,
+    matcher: Option<regex::Regex>,
+    skip_attachments: bool,
+    strip_emoji: bool,
+    table_mode: TableMode,
+    captions: CaptionMode,
+    expand_details: bool,
+    flavor: MarkdownFlavor,
+    /// Note name per exported page id with `--site obsidian`.
+    note_names: Option<HashMap<String, String>>,
+    /// Include nesting limit when `--resolve-includes` is set.
+    resolve_includes: Option<usize>,
+    verbose: u8,
+    quiet: bool,
+    progress: ProgressMode,
+}
+
+#[derive(Debug)]
+struct ExportedPage {
+    id: String,
+    title: String,
+    version: u64,
+    dir: PathBuf,
+    /// `None` for Obsidian notes, which carry their metadata as front matter.
+    meta: Option<PathBuf>,
+    content: PathBuf,
+    attachments: Vec<PathBuf>,
+    /// Attachment id of each downloaded file, for the manifest.
+    attachment_ids: HashMap<PathBuf, String>,
+    diagrams: Vec<PathBuf>,
+    space_key: String,
+}
+
+impl ExportedPage {
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "dir": self.dir,
+            "meta": self.meta,
+            "content": self.content,
+            "attachments": self.attachments,
+            "diagrams": self.diagrams,
+        })
+    }
+
+    fn manifest_entry(&self, dest: &Path) -> Result<manifest::ManifestPage> {
+        let files = self
+            .meta
+            .iter()
+            .chain([&self.content])
+            .chain(&self.attachments)
+            .chain(&self.diagrams)
+            .map(|path| {
+                let attachment_id = self.attachment_ids.get(path).cloned();
+                manifest::ManifestFile::read(dest, path, attachment_id)
+            })
+            .collect::<Result<_>>()?;
+        Ok(manifest::ManifestPage {
+            id: self.id.clone(),
+            title: self.title.clone(),
+            version: self.version,
+            files,
+        })
+    }
+}
+
+async fn export_pages(client: &ApiClient, ctx: &AppContext, args: ExportArgs) -> Result<()> {
+    let format = ContentFormat::parse(&args.format)?;
+    let obsidian = args.site == ExportSite::Obsidian;
+    if obsidian && !matches!(format, ContentFormat::Markdown) {
+        return Err(anyhow!("--site obsidian exports markdown; drop --format"));
+    }
+    if args.resolve_includes && matches!(format, ContentFormat::Adf) {
+        return Err(anyhow!(
+            "--resolve-includes works with --format md or storage, not adf"
+        ));
+    }
+    if args.estimate {
+        let before = confcli::client::transfer_stats().requests;
+        let pages = collect_pages(client, &args.pages, args.recursive).await?;
+        let listing = confcli::client::transfer_stats().requests - before;
+        return estimate::estimate(client, ctx, &args, pages, listing).await;
+    }
+    if args.git_commit {
+        std::fs::create_dir_all(&args.dest)
+            .with_context(|| format!("Failed to create {}", args.dest.display()))?;
+        git::ensure_repo(&args.dest)?;
+    }
+    let matcher = args
+        .pattern
+        .as_deref()
+        .map(confcli::pattern::glob_to_regex_ci)
+        .transpose()?;
+
+    let pages = collect_pages(client, &args.pages, args.recursive).await?;
+    let note_names = if obsidian {
+        Some(obsidian::note_names(
+            &with_titles(client, pages.clone()).await?,
+        ))
+    } else {
+        None
+    };
+    let page_ids: Vec<String> = pages.into_iter().map(|(id, _)| id).collect();
+
+    // One semaphore bounds every request of the run: page bodies and attachment downloads
+    // share the same budget, so `--concurrency` means the same thing for one page or many.
+    let sem = Arc::new(Semaphore::new(ctx.workers(CONCURRENCY)));
+    let opts = Arc::new(ExportOptions {
+        dest: args.dest.clone(),
+        format,
+        matcher,
+        skip_attachments: args.skip_attachments,
+        strip_emoji: args.strip_emoji,
+        table_mode: args.table_mode,
+        captions: args.captions,
+        expand_details: args.expand_as_details,
+        flavor: if obsidian {
+            MarkdownFlavor::Obsidian
+        } else {
+            args.flavor
+        },
+        note_names,
+        resolve_includes: args.resolve_includes.then_some(args.include_depth),
+        verbose: ctx.verbose,
+        quiet: ctx.quiet,
+        progress: ctx.progress,
+    });
+
+    let exported = if page_ids.len() == 1 {
+        vec![export_one(client, &sem, &page_ids[0], &opts, true).await?]
+    } else {
+        export_many(client, ctx, &sem, page_ids, &opts).await?
+    };
+
+    let exported_at = crate::lifecycle::utc_timestamp();
+    let mut trees = Vec::new();
+    if args.recursive {
+        for page in &args.pages {
+            trees.push(resolve_page_id(client, page).await?);
+        }
+    }
+    let entries = exported
+        .iter()
+        .map(|page| page.manifest_entry(&args.dest))
+        .collect::<Result<Vec<_>>>()?;
+    let mut manifest = manifest::Manifest::load(&args.dest)?.unwrap_or_default();
+    manifest.merge(client.base_url(), &exported_at, trees, entries);
+    manifest.save(&args.dest)?;
+
+    if args.git_commit {
+        let mut spaces: Vec<String> = Vec::new();
+        for page in &exported {
+            if !page.space_key.is_empty() && !spaces.contains(&page.space_key) {
+                spaces.push(page.space_key.clone());
+            }
+        }
+        let message = git::commit_message(client.base_url(), &spaces, exported.len(), &exported_at);
+        let commit = git::commit_snapshot(&args.dest, &message)?;
+        if !ctx.quiet {
+            match commit {
+                Some(hash) => eprintln!("Committed export as {hash}"),
+                None => eprintln!("No changes to commit"),
+            }
+        }
+    }
+
+    match args.output {
+        OutputFormat::Json => {
+            if let [single] = exported.as_slice() {
+                maybe_print_json(ctx, &single.to_json())
+            } else {
+                let out: Vec<_> = exported.iter().map(ExportedPage::to_json).collect();
+                maybe_print_json(ctx, &out)
+            }
+        }
+        fmt => {
+            if let [single] = exported.as_slice() {
+                let rows = vec![
+                    vec!["Dir".to_string(), single.dir.display().to_string()],
+                    vec!["Content".to_string(), single.content.display().to_string()],
+                    vec![
+                        "Attachments".to_string(),
+                        single.attachments.len().to_string(),
+                    ],
+                    vec!["Diagrams".to_string(), single.diagrams.len().to_string()],
+                ];
+                maybe_print_kv_fmt(ctx, fmt, rows);
+            } else {
+                // Obsidian notes share the vault root, so list the notes instead.
+                let (heading, path): (&str, fn(&ExportedPage) -> &PathBuf) = if obsidian {
+                    ("Note", |page| &page.content)
+                } else {
+                    ("Dir", |page| &page.dir)
+                };
+                let rows = exported
+                    .iter()
+                    .map(|page| {
+                        vec![
+                            path(page).display().to_string(),
+                            page.attachments.len().to_string(),
+                            page.diagrams.len().to_string(),
+                        ]
+                    })
+                    .collect();
+                maybe_print_rows(ctx, fmt, &[heading, "Attachments", "Diagrams"], rows);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Resolve the requested pages (plus descendants with `--recursive`), de-duplicated in order,
+/// as `(id, title)`. Titles are only known for descendants; they are empty for the pages
+/// named on the command line.
+async fn collect_pages(
+    client: &ApiClient,
+    pages: &[String],
+    recursive: bool,
+) -> Result<Vec<(String, String)>> {
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut out = Vec::new();
+    for page in pages {
+        let page_id = resolve_page_id(client, page).await?;
+        if seen.insert(page_id.clone()) {
+            out.push((page_id.clone(), String::new()));
+        }
+        if recursive {
+            let descendants = confcli::tree::fetch_descendants_via_direct_children(
+                client, &page_id, 250, true, None,
+            )
+            .await?;
+            for item in descendants {
+                let id = json_str(&item, "id");
+                if !id.is_empty() && seen.insert(id.clone()) {
+                    out.push((id, json_str(&item, "title")));
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Fill in the titles `collect_pages` couldn't know.
+async fn with_titles(
+    client: &ApiClient,
+    mut pages: Vec<(String, String)>,
+) -> Result<Vec<(String, String)>> {
+    for (id, title) in pages.iter_mut().filter(|(_, title)| title.is_empty()) {
+        let (json, _) = client
+            .get_json(client.v2_url(&format!("/pages/{id}")))
+            .await?;
+        *title = json_str(&json, "title");
+    }
+    Ok(pages)
+}
+
+async fn export_many(
+    client: &ApiClient,
+    ctx: &AppContext,
+    sem: &Arc<Semaphore>,
+    page_ids: Vec<String>,
+    opts: &Arc<ExportOptions>,
+) -> Result<Vec<ExportedPage>> {
+    let bar = Progress::items(ctx, "pages", page_ids.len() as u64);
+
+    let client = Arc::new(client.clone());
+    let mut tasks = JoinSet::new();
+    for (idx, page_id) in page_ids.into_iter().enumerate() {
+        let client = client.clone();
+        let sem = sem.clone();
+        let opts = opts.clone();
+        let bar = bar.clone();
+        tasks.spawn(async move {
+            let page = export_one(&client, &sem, &page_id, &opts, false).await?;
+            if let Some(bar) = &bar {
+                bar.inc(1);
+            }
+            Ok::<_, anyhow::Error>((idx, page))
+        });
+    }
+
+    let mut exported = Vec::new();
+    while let Some(res) = tasks.join_next().await {
+        let err = match res {
+            Ok(Ok(page)) => {
+                exported.push(page);
+                continue;
+            }
+            Ok(Err(err)) => err.context("Page export task failed"),
+            Err(join_err) => anyhow!("Page export task failed: {join_err}"),
+        };
+        tasks.abort_all();
+        while tasks.join_next().await.is_some() {}
+        if let Some(bar) = &bar {
+            bar.finish();
+        }
+        return Err(err);
+    }
+    if let Some(bar) = bar {
+        bar.finish();
+    }
+
+    exported.sort_by_key(|(idx, _)| *idx);
+    Ok(exported.into_iter().map(|(_, page)| page).collect())
+}
+
+async fn export_one(
+    client: &ApiClient,
+    sem: &Arc<Semaphore>,
+    page_id: &str,
+    opts: &ExportOptions,
+    show_progress: bool,
+) -> Result<ExportedPage> {
+    let (page_json, body) = {
+        let _permit = sem.acquire().await?;
+        fetch_page_with_body_format(client, page_id, opts.format.body_format()).await?
+    };
+
+    let body = match opts.resolve_includes {
+        Some(depth) => {
+            let _permit = sem.acquire().await?;
+            resolve_includes(client, &page_json, page_id, body, opts.format, depth).await?
+        }
+        None => body,
+    };
+
+    // Diagram macros only survive in the storage body; fetch it when the page may have any.
+    let diagram_macros = if opts.skip_attachments {
+        Vec::new()
+    } else if let ContentFormat::Storage = opts.format {
+        diagrams::find_diagrams(&body)
+    } else if diagrams::may_contain_diagrams(&body) {
+        let _permit = sem.acquire().await?;
+        let (_, storage) = fetch_page_with_body_format(client, page_id, "storage").await?;
+        diagrams::find_diagrams(&storage)
+    } else {
+        Vec::new()
+    };
+
+    let mut body_bytes = match opts.format {
+        ContentFormat::Markdown => html_to_markdown_with_page_names(
+            &unfurl_smart_links(client, &body).await,
+            client.base_url(),
+            MarkdownOptions {
+                strip_emoji: opts.strip_emoji,
+                table_mode: opts.table_mode,
+                captions: opts.captions,
+                expand_details: opts.expand_details,
+                flavor: opts.flavor,
+                ..Default::default()
+            },
+            opts.note_names.as_ref(),
+        )?
+        .into_bytes(),
+        ContentFormat::Storage => body.into_bytes(),
+        ContentFormat::Adf => match serde_json::from_str::<serde_json::Value>(&body) {
+            Ok(value) => serde_json::to_vec_pretty(&value)?,
+            Err(_) => body.into_bytes(),
+        },
+    };
+
+    let title = json_str(&page_json, "title");
+    let space_id = json_str(&page_json, "spaceId");
+    let space_key = if !space_id.is_empty() {
+        resolve_space_key(client, &space_id)
+            .await
+            .unwrap_or_default()
+    } else {
+        String::new()
+    };
+
+    // Folders: `<Title>--<id>/` with everything inside. Obsidian: the note at the vault
+    // root, and the page's files under `assets/<id>/`.
+    let note_name = opts.note_names.as_ref().map(|names| {
+        names
+            .get(page_id)
+            .cloned()
+            .unwrap_or_else(|| page_id.to_string())
+    });
+    let (dir, out_dir, content_path, attachments_dir) = match &note_name {
+        Some(name) => {
+            let assets = opts.dest.join("assets").join(page_id);
+            let content = opts.dest.join(format!("{name}.md"));
+            (opts.dest.clone(), assets.clone(), content, assets)
+        }
+        None => {
+            let folder_name = format!("{}--{}", sanitize_filename(&title), page_id);
+            let out_dir = opts.dest.join(folder_name);
+            let content = out_dir.join(opts.format.file_name());
+            let attachments = out_dir.join("attachments");
+            (out_dir.clone(), out_dir, content, attachments)
+        }
+    };
+    tokio::fs::create_dir_all(&dir).await?;
+
+    let meta_path = if note_name.is_none() {
+        let meta_path = out_dir.join("meta.json");
+        let meta = json!({
+            "id": page_id,
+            "title": title,
+            "spaceId": space_id,
+            "spaceKey": space_key,
+            "siteUrl": client.base_url(),
+        });
+        tokio::fs::write(&meta_path, serde_json::to_vec_pretty(&meta)?).await?;
+        Some(meta_path)
+    } else {
+        None
+    };
+
+    let mut attachments_written = Vec::<PathBuf>::new();
+    let mut attachment_ids = HashMap::new();
+    let mut diagrams_written = Vec::<PathBuf>::new();
+    // Attachment title -> vault link, for pointing Obsidian notes at the local copies.
+    let mut local_files: HashMap<String, String> = HashMap::new();
+    if !opts.skip_attachments {
+        if note_name.is_none() {
+            tokio::fs::create_dir_all(&attachments_dir).await?;
+        }
+
+        let url = client.v2_url(&format!("/pages/{page_id}/attachments?limit=50"));
+        let items = {
+            let _permit = sem.acquire().await?;
+            client.get_paginated_results(url, true).await?
+        };
+
+        // Diagrams are exported regardless of --pattern: they are page content.
+        if !diagram_macros.is_empty() {
+            let origin = Url::parse(client.api_site_url())?;
+            let exported = diagrams::export_diagrams(
+                client,
+                sem,
+                &origin,
+                &out_dir,
+                &diagram_macros,
+                &items,
+                opts.verbose,
+            )
+            .await?;
+            if !exported.is_empty() && matches!(opts.format, ContentFormat::Markdown) {
+                let prefix = match note_name {
+                    Some(_) => obsidian::vault_link(&opts.dest, &out_dir),
+                    None => String::new(),
+                };
+                let section = diagrams::diagrams_markdown(&exported, &prefix);
+                body_bytes.extend_from_slice(format!("\n\n{section}\n").as_bytes());
+            }
+            for diagram in exported {
+                let files = diagram.source.into_iter().chain(diagram.preview);
+                diagrams_written.extend(files.map(|file| out_dir.join(file)));
+            }
+        }
+
+        let selected: Vec<serde_json::Value> = items
+            .into_iter()
+            .filter(|item| {
+                if let Some(re) = &opts.matcher {
+                    let title = item.get("title").and_then(|v| v.as_str()).unwrap_or("");
+                    re.is_match(title)
+                } else {
+                    true
+                }
+            })
+            .collect();
+
+        let mut reserved_paths: HashSet<PathBuf> = HashSet::new();
+        let mut planned_downloads = Vec::with_capacity(selected.len());
+        for item in selected {
+            let title = item
+                .get("title")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string();
+            let target_name = sanitize_filename(&title);
+            if target_name.is_empty() {
+                return Err(anyhow!("Unsafe attachment title: {title}"));
+            }
+
+            let target_path =
+                reserve_unique_path(attachments_dir.join(target_name), &reserved_paths);
+            reserved_paths.insert(target_path.clone());
+            planned_downloads.push((item, title, target_path));
+        }
+
+        if note_name.is_some() && !planned_downloads.is_empty() {
+            tokio::fs::create_dir_all(&attachments_dir).await?;
+        }
+        for (_, title, target_path) in &planned_downloads {
+            local_files.insert(title.clone(), obsidian::vault_link(&opts.dest, target_path));
+        }
+
+        let client = Arc::new(client.clone());
+        let origin = Url::parse(client.api_site_url())?;
+
+        let total_bar = if show_progress {
+            Progress::items_with(
+                opts.progress,
+                opts.quiet,
+                "attachments",
+                planned_downloads.len() as u64,
+            )
+        } else {
+            None
+        };
+
+        let verbose = opts.verbose;
+        let mut tasks = JoinSet::new();
+
+        for (item, title, target_path) in planned_downloads {
+            attachment_ids.insert(target_path.clone(), json_str(&item, "id"));
+            let permit = sem.clone().acquire_owned().await?;
+            let client = client.clone();
+            let origin = origin.clone();
+            let bar = total_bar.clone();
+
+            tasks.spawn(async move {
+                let _permit = permit;
+                let path = download_attachment_item(
+                    &client,
+                    &origin,
+                    &item,
+                    &title,
+                    &target_path,
+                    verbose,
+                )
+                .await?;
+                if let Some(bar) = &bar {
+                    bar.inc(1);
+                }
+                Ok::<_, anyhow::Error>(path)
+            });
+        }
+
+        while let Some(res) = tasks.join_next().await {
+            match res {
+                Ok(Ok(path)) => attachments_written.push(path),
+                Ok(Err(err)) => {
+                    tasks.abort_all();
+                    while tasks.join_next().await.is_some() {}
+                    if let Some(bar) = &total_bar {
+                        bar.finish();
+                    }
+                    return Err(err.context("Attachment download task failed"));
+                }
+                Err(join_err) => {
+                    tasks.abort_all();
+                    while tasks.join_next().await.is_some() {}
+                    if let Some(bar) = &total_bar {
+                        bar.finish();
+                    }
+                    return Err(anyhow!("Attachment download task failed: {join_err}"));
+                }
+            }
+        }
+
+        if let Some(bar) = total_bar {
+            bar.finish();
+        }
+    }
+
+    if let Some(name) = &note_name {
+        let labels = {
+            let _permit = sem.acquire().await?;
+            obsidian::page_labels(client, page_id).await?
+        };
+        let markdown = String::from_utf8_lossy(&body_bytes);
+        let markdown = obsidian::localize_attachment_links(&markdown, page_id, &local_files);
+        let front_matter =
+            obsidian::front_matter(&page_json, name, &space_key, client.base_url(), &labels);
+        body_bytes = format!("{front_matter}{markdown}\n").into_bytes();
+    }
+
+    tokio::fs::write(&content_path, body_bytes).await?;
+
+    let version = page_json
+        .get("version")
+        .and_then(|v| v.get("number"))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    Ok(ExportedPage {
+        id: page_id.to_string(),
+        title,
+        version,
+        dir,
+        meta: meta_path,
+        content: content_path,
+        attachments: attachments_written,
+        attachment_ids,
+        diagrams: diagrams_written,
+        space_key,
+    })
+}
+
+/// Inline include macros. Includes are resolved in the storage body, which is then
+/// rendered back to view HTML for markdown output.
+async fn resolve_includes(
+    client: &ApiClient,
+    page_json: &serde_json::Value,
+    page_id: &str,
+    body: String,
+    format: ContentFormat,
+    depth: usize,
+) -> Result<String> {
+    let storage = match format {
+        ContentFormat::Storage => body,
+        _ => {
+            fetch_page_with_body_format(client, page_id, "storage")
+                .await?
+                .1
+        }
+    };
+    let space_id = json_str(page_json, "spaceId");
+    let space_key = resolve_space_key(client, &space_id).await?;
+    let resolved = includes::IncludeResolver::new(client, depth)
+        .resolve(page_id, &space_key, &storage)
+        .await?;
+    match format {
+        ContentFormat::Storage => Ok(resolved),
+        _ => includes::storage_to_view(client, page_id, &resolved).await,
+    }
+}
+
+async fn download_attachment_item(
+    client: &ApiClient,
+    origin: &Url,
+    item: &serde_json::Value,
+    title: &str,
+    target_path: &Path,
+    verbose: u8,
+) -> Result<PathBuf> {
+    let download = item
+        .get("downloadLink")
+        .and_then(|v| v.as_str())
+        .or_else(|| {
+            item.get("_links")
+                .and_then(|v| v.get("download"))
+                .and_then(|v| v.as_str())
+        })
+        .context("Missing attachment download link")?;
+
+    let url = attachment_download_url(origin, download)?;
+    let opts = DownloadToFileOptions {
+        retry: DownloadRetry::default(),
+        progress: None,
+        verbose,
+    };
+    download_to_file_with_retry(client, url, target_path, title, opts).await?;
+
+    Ok(target_path.to_path_buf())
+}
+
+fn reserve_unique_path(path: PathBuf, reserved: &HashSet<PathBuf>) -> PathBuf {
+    if !path.exists() && !reserved.contains(&path) {
+        return path;
+    }
+
+    let parent = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("file")
+        .to_string();
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_string();
+
+    for i in 1..10_000 {
+        let name = if ext.is_empty() {
+            format!("{stem} ({i})")
+        } else {
+            format!("{stem} ({i}).{ext}")
+        };
+        let candidate = parent.join(name);
+        if !candidate.exists() && !reserved.contains(&candidate) {
+            return candidate;
+        }
+    }
+
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserve_unique_path_avoids_pre_reserved_collisions() {
+        let base = std::env::temp_dir().join("confcli-export-tests");
+        let mut reserved = HashSet::new();
+
+        let first = reserve_unique_path(base.join("artifact.txt"), &reserved);
+        reserved.insert(first.clone());
+
+        let second = reserve_unique_path(base.join("artifact.txt"), &reserved);
+
+        assert_ne!(first, second);
+        assert!(second.ends_with("artifact (1).txt"));
+    }
+}
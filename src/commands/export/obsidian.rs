@@ -0,0 +1,213 @@
+//! `export --site obsidian`: a flat vault with one note per page named after its title,
+//! attachments under `assets/<page id>/`, and YAML front matter instead of `meta.json`.
+
+use anyhow::Result;
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::LazyLock;
+
+use crate::download::sanitize_filename;
+
+static ATTACHMENT_URL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"https?://[^\s()<>"]*/download/(?:attachments|thumbnails)/([0-9]+)/([^\s?()<>"]+)[^\s()<>"]*"#)
+        .expect("ATTACHMENT_URL_RE")
+});
+
+/// `title` without the characters Obsidian doesn't allow in file names or links.
+pub(crate) fn note_stem(title: &str) -> String {
+    let name: String = sanitize_filename(title)
+        .chars()
+        .filter(|c| {
+            !matches!(
+                c,
+                '[' | ']' | '|' | '#' | '^' | ':' | '*' | '?' | '"' | '<' | '>'
+            )
+        })
+        .collect();
+    name.trim().trim_start_matches('.').to_string()
+}
+
+/// Note name (file stem and wiki-link target) for each page, keyed by page id. Titles are
+/// stripped of characters Obsidian doesn't allow in file names or links; a title used by
+/// several pages gets the page id appended.
+pub(super) fn note_names(pages: &[(String, String)]) -> HashMap<String, String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for (_, title) in pages {
+        *counts.entry(note_stem(title).to_lowercase()).or_default() += 1;
+    }
+    let mut used = HashSet::new();
+    let mut names = HashMap::new();
+    for (id, title) in pages {
+        let base = note_stem(title);
+        let mut name = if base.is_empty() {
+            id.clone()
+        } else if counts[&base.to_lowercase()] > 1 {
+            format!("{base} ({id})")
+        } else {
+            base
+        };
+        if !used.insert(name.to_lowercase()) {
+            name = format!("{name} ({id})");
+            used.insert(name.to_lowercase());
+        }
+        names.insert(id.clone(), name);
+    }
+    names
+}
+
+/// Page labels, used as note tags.
+pub(super) async fn page_labels(client: &ApiClient, page_id: &str) -> Result<Vec<String>> {
+    let url = client.v2_url(&format!("/pages/{page_id}/labels?limit=250"));
+    let items = client.get_paginated_results(url, true).await?;
+    Ok(items
+        .iter()
+        .map(|item| json_str(item, "name"))
+        .filter(|name| !name.is_empty())
+        .collect())
+}
+
+/// YAML front matter for a note: title (and an alias when the note name differs), labels
+/// as tags, and where the page came from.
+pub(super) fn front_matter(
+    page_json: &Value,
+    note_name: &str,
+    space_key: &str,
+    base_url: &str,
+    labels: &[String],
+) -> String {
+    // A JSON string is a valid YAML double-quoted scalar.
+    let quote = |value: &str| serde_json::to_string(value).unwrap_or_default();
+    let title = json_str(page_json, "title");
+    let mut out = format!("---\ntitle: {}\n", quote(&title));
+    if title != note_name {
+        out.push_str(&format!("aliases:\n  - {}\n", quote(&title)));
+    }
+    if !labels.is_empty() {
+        out.push_str("tags:\n");
+        for label in labels {
+            out.push_str(&format!("  - {}\n", quote(label)));
+        }
+    }
+    out.push_str(&format!(
+        "confluence-id: {}\n",
+        quote(&json_str(page_json, "id"))
+    ));
+    if !space_key.is_empty() {
+        out.push_str(&format!("confluence-space: {}\n", quote(space_key)));
+    }
+    let updated = page_json
+        .get("version")
+        .and_then(|v| v.get("createdAt"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    if !updated.is_empty() {
+        out.push_str(&format!("updated: {}\n", quote(updated)));
+    }
+    if let Some(webui) = page_json
+        .get("_links")
+        .and_then(|v| v.get("webui"))
+        .and_then(|v| v.as_str())
+    {
+        out.push_str(&format!(
+            "source: {}\n",
+            quote(&format!("{base_url}{webui}"))
+        ));
+    }
+    out.push_str("---\n\n");
+    out
+}
+
+/// Point links to this page's attachments at the downloaded copies. `files` maps
+/// attachment titles to paths relative to the vault root.
+pub(super) fn localize_attachment_links(
+    markdown: &str,
+    page_id: &str,
+    files: &HashMap<String, String>,
+) -> String {
+    ATTACHMENT_URL_RE
+        .replace_all(markdown, |caps: &regex::Captures| {
+            let name = urlencoding::decode(&caps[2])
+                .map(|name| name.into_owned())
+                .unwrap_or_else(|_| caps[2].to_string());
+            match files.get(&name) {
+                Some(path) if &caps[1] == page_id => path.clone(),
+                _ => caps[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Vault-relative link target for a file, with the characters markdown link destinations
+/// can't hold percent-encoded.
+pub(super) fn vault_link(vault: &Path, file: &Path) -> String {
+    let relative = file.strip_prefix(vault).unwrap_or(file);
+    relative
+        .components()
+        .map(|part| {
+            part.as_os_str()
+                .to_string_lossy()
+                .replace('%', "%25")
+                .replace(' ', "%20")
+                .replace('(', "%28")
+                .replace(')', "%29")
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn note_names_are_unique_and_link_safe() {
+        let pages = vec![
+            ("1".to_string(), "Design: v2 [draft]".to_string()),
+            ("2".to_string(), "Notes".to_string()),
+            ("3".to_string(), "notes".to_string()),
+        ];
+        let names = note_names(&pages);
+        assert_eq!(names["1"], "Design v2 draft");
+        assert_eq!(names["2"], "Notes (2)");
+        assert_eq!(names["3"], "notes (3)");
+    }
+
+    #[test]
+    fn writes_front_matter() {
+        let page = json!({
+            "id": "42",
+            "title": "Runbook: DB",
+            "version": { "createdAt": "2024-05-01T10:00:00.000Z" },
+            "_links": { "webui": "/spaces/OPS/pages/42" },
+        });
+        let out = front_matter(
+            &page,
+            "Runbook DB",
+            "OPS",
+            "https://x.test/wiki",
+            &["ops".to_string()],
+        );
+        assert_eq!(
+            out,
+            "---\ntitle: \"Runbook: DB\"\naliases:\n  - \"Runbook: DB\"\ntags:\n  - \"ops\"\nconfluence-id: \"42\"\nconfluence-space: \"OPS\"\nupdated: \"2024-05-01T10:00:00.000Z\"\nsource: \"https://x.test/wiki/spaces/OPS/pages/42\"\n---\n\n"
+        );
+    }
+
+    #[test]
+    fn localizes_attachment_links() {
+        let files = HashMap::from([(
+            "my image.png".to_string(),
+            "assets/42/my%20image.png".to_string(),
+        )]);
+        let md = "![my image.png](https://x.test/wiki/download/attachments/42/my%20image.png?version=1&api=v2) [other](https://x.test/wiki/download/attachments/7/my%20image.png)";
+        assert_eq!(
+            localize_attachment_links(md, "42", &files),
+            "![my image.png](assets/42/my%20image.png) [other](https://x.test/wiki/download/attachments/7/my%20image.png)"
+        );
+    }
+}
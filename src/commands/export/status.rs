@@ -0,0 +1,263 @@
+//! `export status`: what an export destination holds according to its manifest, and what
+//! exporting the same pages again would change.
+
+use anyhow::Result;
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::output::OutputFormat;
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+
+use super::manifest::Manifest;
+use crate::cli::ExportStatusArgs;
+use crate::context::AppContext;
+use crate::helpers::*;
+
+/// Page ids per bulk `GET /pages` request (the API's maximum).
+const IDS_PER_REQUEST: usize = 250;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Change {
+    Unchanged,
+    Changed,
+    New,
+    Deleted,
+}
+
+impl Change {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Unchanged => "unchanged",
+            Self::Changed => "changed",
+            Self::New => "new",
+            Self::Deleted => "deleted",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PageStatus {
+    id: String,
+    title: String,
+    /// Version in the snapshot; `None` for pages not exported yet.
+    version: Option<u64>,
+    live_version: Option<u64>,
+    bytes: Option<u64>,
+    /// `None` with `--offline`.
+    change: Option<Change>,
+}
+
+pub(super) async fn handle(ctx: &AppContext, args: ExportStatusArgs) -> Result<()> {
+    let manifest = Manifest::require(&args.dest)?;
+
+    let mut pages: Vec<PageStatus> = manifest
+        .pages
+        .iter()
+        .map(|page| PageStatus {
+            id: page.id.clone(),
+            title: page.title.clone(),
+            version: Some(page.version),
+            live_version: None,
+            bytes: Some(page.size()),
+            change: None,
+        })
+        .collect();
+    if !args.offline {
+        let client = crate::context::load_client_for_site(ctx, &manifest.site_url)?;
+        diff_live(&client, &manifest, &mut pages).await?;
+    }
+
+    let file_count: usize = manifest.pages.iter().map(|page| page.files.len()).sum();
+    let total_bytes: u64 = manifest.pages.iter().map(|page| page.size()).sum();
+
+    match args.output {
+        OutputFormat::Json => {
+            let items: Vec<Value> = pages
+                .iter()
+                .map(|page| {
+                    json!({
+                        "id": page.id,
+                        "title": page.title,
+                        "version": page.version,
+                        "liveVersion": page.live_version,
+                        "bytes": page.bytes,
+                        "change": page.change.map(Change::as_str),
+                    })
+                })
+                .collect();
+            maybe_print_json(
+                ctx,
+                &json!({
+                    "site": manifest.site_url,
+                    "exportedAt": manifest.exported_at,
+                    "pageCount": manifest.pages.len(),
+                    "fileCount": file_count,
+                    "totalBytes": total_bytes,
+                    "pages": items,
+                }),
+            )
+        }
+        fmt => {
+            let optional = |value: Option<u64>| value.map(|v| v.to_string()).unwrap_or_default();
+            let rows = pages
+                .iter()
+                .map(|page| {
+                    let mut row = vec![
+                        page.id.clone(),
+                        page.title.clone(),
+                        optional(page.version),
+                        page.bytes
+                            .map(|bytes| human_size(bytes as i64))
+                            .unwrap_or_default(),
+                    ];
+                    if !args.offline {
+                        row.push(optional(page.live_version));
+                        row.push(page.change.map(Change::as_str).unwrap_or("").to_string());
+                    }
+                    row
+                })
+                .collect();
+            let headers: &[&str] = if args.offline {
+                &["ID", "Title", "Version", "Size"]
+            } else {
+                &["ID", "Title", "Version", "Size", "Live", "Change"]
+            };
+            maybe_print_rows(ctx, fmt, headers, rows);
+
+            print_line(
+                ctx,
+                &format!(
+                    "{} page(s), {file_count} file(s), {} from {}, last exported {}",
+                    manifest.pages.len(),
+                    human_size(total_bytes as i64),
+                    manifest.site_url,
+                    format_timestamp(&manifest.exported_at),
+                ),
+            );
+            if !args.offline {
+                let count = |change: Change| {
+                    pages
+                        .iter()
+                        .filter(|page| page.change == Some(change))
+                        .count()
+                };
+                print_line(
+                    ctx,
+                    &format!(
+                        "Next export: {} changed, {} new, {} deleted, {} unchanged",
+                        count(Change::Changed),
+                        count(Change::New),
+                        count(Change::Deleted),
+                        count(Change::Unchanged),
+                    ),
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Compare the snapshot with the site: pages with a newer live version are changed, pages
+/// the site no longer returns are deleted, and current descendants of `--recursive` exports
+/// that the snapshot lacks are appended as new.
+async fn diff_live(
+    client: &ApiClient,
+    manifest: &Manifest,
+    pages: &mut Vec<PageStatus>,
+) -> Result<()> {
+    let ids: Vec<String> = pages.iter().map(|page| page.id.clone()).collect();
+    let live = live_versions(client, &ids).await?;
+    for page in pages.iter_mut() {
+        page.change = Some(match live.get(&page.id) {
+            None => Change::Deleted,
+            Some(&version) => {
+                page.live_version = Some(version);
+                if page.version.is_some_and(|known| version > known) {
+                    Change::Changed
+                } else {
+                    Change::Unchanged
+                }
+            }
+        });
+    }
+
+    let mut known: HashSet<String> = ids.into_iter().collect();
+    for tree in manifest.trees.iter().filter(|id| live.contains_key(*id)) {
+        let descendants =
+            confcli::tree::fetch_descendants_via_direct_children(client, tree, 250, true, None)
+                .await?;
+        for item in descendants {
+            let id = json_str(&item, "id");
+            if id.is_empty() || !known.insert(id.clone()) {
+                continue;
+            }
+            pages.push(PageStatus {
+                id,
+                title: json_str(&item, "title"),
+                version: None,
+                live_version: None,
+                bytes: None,
+                change: Some(Change::New),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Current version of each page the site still returns, keyed by id.
+async fn live_versions(client: &ApiClient, ids: &[String]) -> Result<HashMap<String, u64>> {
+    let mut versions = HashMap::new();
+    for chunk in ids.chunks(IDS_PER_REQUEST) {
+        let url = url_with_query(
+            &client.v2_url("/pages"),
+            &[
+                ("id", chunk.join(",")),
+                ("limit", IDS_PER_REQUEST.to_string()),
+            ],
+        )?;
+        for page in client.get_paginated_results(url, true).await? {
+            let version = page
+                .get("version")
+                .and_then(|v| v.get("number"))
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0);
+            versions.insert(json_str(&page, "id"), version);
+        }
+    }
+    Ok(versions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::http_server::start_server;
+
+    #[tokio::test]
+    async fn live_versions_batches_ids() {
+        let srv = start_server(|_, path| {
+            if !path.starts_with("/pages?id=1%2C2&") {
+                return (404, vec![], b"{}".to_vec());
+            }
+            let body = br#"{"results":[{"id":"1","version":{"number":4}}],"_links":{}}"#;
+            (200, vec![], body.to_vec())
+        })
+        .await;
+        let client = ApiClient::new(
+            srv.base_url.clone(),
+            srv.base_url.clone(),
+            srv.base_url.clone(),
+            confcli::auth::AuthMethod::Bearer {
+                token: "test".to_string(),
+            },
+            0,
+        )
+        .unwrap();
+        // Page 2 isn't returned: deleted, trashed, or no longer visible.
+        let versions = live_versions(&client, &["1".to_string(), "2".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(versions, HashMap::from([("1".to_string(), 4)]));
+
+        let _ = srv.shutdown.send(());
+    }
+}
@@ -0,0 +1,266 @@
+//! `export verify`: re-check an export destination's files against the sizes and hashes in
+//! its manifest and, with `--live`, downloaded attachments against the site, so a backup can
+//! be trusted before it is needed.
+
+use anyhow::{Result, anyhow};
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::output::OutputFormat;
+use futures_util::stream::{self, StreamExt};
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use super::manifest::{Manifest, ManifestFile, ManifestPage, sha256_file};
+use crate::cli::ExportVerifyArgs;
+use crate::context::AppContext;
+use crate::helpers::*;
+
+/// Pages whose attachment lists are fetched at once with `--live`.
+const LIVE_CONCURRENCY: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Problem {
+    Missing,
+    Unreadable,
+    Corrupted,
+    /// The attachment's size on the site differs from the exported file's.
+    SizeDiffers,
+    NotOnSite,
+    /// The site couldn't be asked about the attachment.
+    Unchecked,
+}
+
+impl Problem {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Missing => "missing",
+            Self::Unreadable => "unreadable",
+            Self::Corrupted => "corrupted",
+            Self::SizeDiffers => "size-differs",
+            Self::NotOnSite => "not-on-site",
+            Self::Unchecked => "unchecked",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Finding {
+    page_id: String,
+    path: String,
+    problem: Problem,
+    detail: String,
+}
+
+pub(super) async fn handle(ctx: &AppContext, args: ExportVerifyArgs) -> Result<()> {
+    let manifest = Manifest::require(&args.dest)?;
+    let checked: usize = manifest.pages.iter().map(|page| page.files.len()).sum();
+
+    let mut findings = Vec::new();
+    for page in &manifest.pages {
+        for file in &page.files {
+            if let Some((problem, detail)) = check_local(&args.dest, file) {
+                findings.push(Finding {
+                    page_id: page.id.clone(),
+                    path: file.path.clone(),
+                    problem,
+                    detail,
+                });
+            }
+        }
+    }
+    if args.live {
+        let client = crate::context::load_client_for_site(ctx, &manifest.site_url)?;
+        let flagged: HashSet<String> = findings.iter().map(|f| f.path.clone()).collect();
+        findings.extend(check_live(&client, &manifest, &flagged).await);
+    }
+
+    match args.output {
+        OutputFormat::Json => {
+            let problems: Vec<Value> = findings
+                .iter()
+                .map(|finding| {
+                    json!({
+                        "pageId": finding.page_id,
+                        "path": finding.path,
+                        "problem": finding.problem.as_str(),
+                        "detail": finding.detail,
+                    })
+                })
+                .collect();
+            maybe_print_json(
+                ctx,
+                &json!({
+                    "pages": manifest.pages.len(),
+                    "files": checked,
+                    "live": args.live,
+                    "problems": problems,
+                }),
+            )?;
+        }
+        fmt if !findings.is_empty() => {
+            let rows = findings
+                .iter()
+                .map(|finding| {
+                    vec![
+                        finding.page_id.clone(),
+                        finding.path.clone(),
+                        finding.problem.as_str().to_string(),
+                        finding.detail.clone(),
+                    ]
+                })
+                .collect();
+            maybe_print_rows(ctx, fmt, &["Page", "Path", "Problem", "Detail"], rows);
+        }
+        _ => {
+            let against = if args.live {
+                "the manifest and the site"
+            } else {
+                "the manifest"
+            };
+            print_line(
+                ctx,
+                &format!(
+                    "All {checked} file(s) of {} page(s) match {against}.",
+                    manifest.pages.len()
+                ),
+            );
+        }
+    }
+
+    if !findings.is_empty() {
+        return Err(anyhow!(
+            "{} of {checked} file(s) failed verification in {}",
+            findings.len(),
+            args.dest.display()
+        ));
+    }
+    Ok(())
+}
+
+/// The problem with a file on disk, if any, and a detail for the report.
+fn check_local(dest: &Path, file: &ManifestFile) -> Option<(Problem, String)> {
+    let path = dest.join(&file.path);
+    let size = match std::fs::metadata(&path) {
+        Ok(meta) => meta.len(),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Some((Problem::Missing, "not on disk".to_string()));
+        }
+        Err(err) => return Some((Problem::Unreadable, err.to_string())),
+    };
+    if size != file.size {
+        return Some((
+            Problem::Corrupted,
+            format!("{size} bytes, expected {}", file.size),
+        ));
+    }
+    let expected = file.sha256.as_deref()?;
+    match sha256_file(&path) {
+        Ok((_, actual)) if actual == expected => None,
+        Ok(_) => Some((Problem::Corrupted, "SHA-256 differs".to_string())),
+        Err(err) => Some((Problem::Unreadable, format!("{err:#}"))),
+    }
+}
+
+/// Compare every downloaded attachment not already in `flagged` with the page's attachments
+/// on the site.
+async fn check_live(
+    client: &ApiClient,
+    manifest: &Manifest,
+    flagged: &HashSet<String>,
+) -> Vec<Finding> {
+    let pages: Vec<&ManifestPage> = manifest
+        .pages
+        .iter()
+        .filter(|page| page.files.iter().any(|file| file.attachment_id.is_some()))
+        .collect();
+    let findings: Vec<Vec<Finding>> = stream::iter(pages)
+        .map(|page| async move {
+            let sizes = live_attachment_sizes(client, &page.id).await;
+            let mut findings = Vec::new();
+            for file in &page.files {
+                let Some(id) = &file.attachment_id else {
+                    continue;
+                };
+                if flagged.contains(&file.path) {
+                    continue;
+                }
+                let finding = match &sizes {
+                    Err(err) => Some((Problem::Unchecked, format!("{err:#}"))),
+                    Ok(sizes) => match sizes.get(id) {
+                        None => Some((
+                            Problem::NotOnSite,
+                            format!("attachment {id} is gone from page {}", page.id),
+                        )),
+                        Some(&size) if size != file.size => Some((
+                            Problem::SizeDiffers,
+                            format!("{} bytes, site has {size}", file.size),
+                        )),
+                        Some(_) => None,
+                    },
+                };
+                if let Some((problem, detail)) = finding {
+                    findings.push(Finding {
+                        page_id: page.id.clone(),
+                        path: file.path.clone(),
+                        problem,
+                        detail,
+                    });
+                }
+            }
+            findings
+        })
+        .buffered(LIVE_CONCURRENCY)
+        .collect()
+        .await;
+    findings.into_iter().flatten().collect()
+}
+
+/// `fileSize` of each attachment currently on a page, keyed by attachment id.
+async fn live_attachment_sizes(client: &ApiClient, page_id: &str) -> Result<HashMap<String, u64>> {
+    let url = client.v2_url(&format!("/pages/{page_id}/attachments?limit=250"));
+    let items = client.get_paginated_results(url, true).await?;
+    Ok(items
+        .iter()
+        .map(|item| {
+            let size = item.get("fileSize").and_then(|v| v.as_u64()).unwrap_or(0);
+            (json_str(item, "id"), size)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_files_are_checked_against_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("page.md");
+        std::fs::write(&path, "abc").unwrap();
+        let mut file = ManifestFile::read(dir.path(), &path, None).unwrap();
+        assert_eq!(check_local(dir.path(), &file), None);
+
+        std::fs::write(&path, "abd").unwrap();
+        assert_eq!(
+            check_local(dir.path(), &file),
+            Some((Problem::Corrupted, "SHA-256 differs".to_string()))
+        );
+        std::fs::write(&path, "abcd").unwrap();
+        assert_eq!(
+            check_local(dir.path(), &file).map(|(problem, _)| problem),
+            Some(Problem::Corrupted)
+        );
+
+        // Manifests without hashes still get the size check.
+        file.sha256 = None;
+        file.size = 4;
+        assert_eq!(check_local(dir.path(), &file), None);
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            check_local(dir.path(), &file).map(|(problem, _)| problem),
+            Some(Problem::Missing)
+        );
+    }
+}
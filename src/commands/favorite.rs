@@ -0,0 +1,114 @@
+use anyhow::Result;
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::output::OutputFormat;
+use serde_json::Value;
+#[cfg(feature = "write")]
+use serde_json::json;
+
+use crate::cli::*;
+use crate::commands::search::search_result_space;
+use crate::context::AppContext;
+#[cfg(feature = "write")]
+use crate::helpers::print_line;
+use crate::helpers::{escape_cql_text, maybe_print_json, maybe_print_rows, url_with_query};
+#[cfg(feature = "write")]
+use crate::plan::{self, Operation};
+#[cfg(feature = "write")]
+use crate::resolve::resolve_page_id;
+
+pub async fn handle(ctx: &AppContext, cmd: FavoriteCommand) -> Result<()> {
+    let client = crate::context::load_client(ctx)?;
+    match cmd {
+        FavoriteCommand::List(args) => favorite_list(&client, ctx, args).await,
+        #[cfg(feature = "write")]
+        FavoriteCommand::Add(args) => favorite_set(&client, ctx, args, true).await,
+        #[cfg(feature = "write")]
+        FavoriteCommand::Remove(args) => favorite_set(&client, ctx, args, false).await,
+    }
+}
+
+async fn favorite_list(client: &ApiClient, ctx: &AppContext, args: FavoriteListArgs) -> Result<()> {
+    let cql = favorite_cql(args.space.as_deref());
+    let url = url_with_query(
+        &client.v1_url("/search"),
+        &[("cql", cql), ("limit", args.limit.to_string())],
+    )?;
+    let results = client.get_paginated_results(url, args.all).await?;
+    match args.output {
+        OutputFormat::Json => maybe_print_json(ctx, &results),
+        fmt => {
+            let rows = results.iter().map(favorite_row).collect();
+            maybe_print_rows(ctx, fmt, &["ID", "Space", "Title"], rows);
+            Ok(())
+        }
+    }
+}
+
+fn favorite_cql(space: Option<&str>) -> String {
+    let mut cql = "type = page AND favourite = currentUser()".to_string();
+    if let Some(space) = space {
+        cql.push_str(&format!(" AND space = \"{}\"", escape_cql_text(space)));
+    }
+    cql
+}
+
+fn favorite_row(item: &Value) -> Vec<String> {
+    let content = item.get("content").cloned().unwrap_or(Value::Null);
+    vec![
+        json_str(&content, "id"),
+        search_result_space(item),
+        json_str(&content, "title"),
+    ]
+}
+
+/// Save or unsave a page via the v1 relation API ("favourite" from the current user).
+#[cfg(feature = "write")]
+async fn favorite_set(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: FavoriteArgs,
+    add: bool,
+) -> Result<()> {
+    let page_id = resolve_page_id(client, &args.page).await?;
+
+    if ctx.dry_run {
+        let action = if add { "add" } else { "remove" };
+        plan::record(Operation::new(action, "favorite", &page_id));
+        let message = if add {
+            format!("Would save page {page_id} for later")
+        } else {
+            format!("Would remove page {page_id} from saved for later")
+        };
+        print_line(ctx, &message);
+        return Ok(());
+    }
+
+    let url = client.v1_url(&format!(
+        "/relation/favourite/from/user/current/to/content/{page_id}"
+    ));
+    if add {
+        client.put_json_no_content(url, json!({})).await?;
+        print_line(ctx, &format!("Saved page {page_id} for later."));
+    } else {
+        client.delete(url).await?;
+        print_line(
+            ctx,
+            &format!("Removed page {page_id} from saved for later."),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn favorite_cql_scopes_to_space() {
+        assert_eq!(
+            favorite_cql(Some("MFS")),
+            "type = page AND favourite = currentUser() AND space = \"MFS\""
+        );
+    }
+}
@@ -0,0 +1,427 @@
+use anyhow::{Context, Result};
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::markdown::html_to_markdown;
+use regex::Regex;
+use serde_json::{Value, json};
+use std::sync::LazyLock;
+
+use crate::cli::{ChangelogDate, GenerateChangelogArgs};
+use crate::context::AppContext;
+use crate::helpers::{
+    escape_cql_text, maybe_print_json, maybe_print_kv_fmt, url_with_query, web_url,
+};
+use crate::lifecycle::{format_date, parse_since, today};
+use crate::resolve::{resolve_space_id, resolve_space_key};
+use confcli::output::OutputFormat;
+
+static HEADING_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<h([1-6])\b[^>]*>(.*?)</h[1-6]\s*>").expect("HEADING_RE"));
+static HEADING_TAG_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)<(/?)h([1-6])\b").expect("HEADING_TAG_RE"));
+static TAG_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]*>").expect("TAG_RE"));
+
+/// One labeled page or blog post.
+#[derive(Debug)]
+struct Entry {
+    id: String,
+    title: String,
+    #[cfg(feature = "write")]
+    kind: String,
+    #[cfg(feature = "write")]
+    space_key: String,
+    /// `YYYY-MM-DD` of the date chosen with `--date`.
+    date: String,
+    url: String,
+    /// View HTML for markdown output, storage for a page.
+    body: String,
+}
+
+pub(super) async fn generate_changelog(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: GenerateChangelogArgs,
+) -> Result<()> {
+    let to_page = args.out_is_page();
+    #[cfg(not(feature = "write"))]
+    if to_page {
+        anyhow::bail!("Writing release notes to a page needs a build with the `write` feature");
+    }
+
+    let space_key = match &args.space {
+        Some(space) => {
+            Some(resolve_space_key(client, &resolve_space_id(client, space).await?).await?)
+        }
+        None => None,
+    };
+    let since = args
+        .since
+        .as_deref()
+        .map(|since| parse_since(since, today()).map(format_date))
+        .transpose()?;
+    let representation = if to_page { "storage" } else { "view" };
+    let entries = fetch_entries(
+        client,
+        &args,
+        space_key.as_deref(),
+        since.as_deref(),
+        representation,
+    )
+    .await?;
+
+    let mut kept = Vec::with_capacity(entries.len());
+    for mut entry in entries {
+        if let Some(heading) = &args.section {
+            match section(&entry.body, heading) {
+                Some(body) => entry.body = body.to_string(),
+                None => {
+                    tracing::debug!(page = %entry.id, "no '{heading}' section; skipped");
+                    continue;
+                }
+            }
+        }
+        kept.push(entry);
+    }
+
+    #[cfg(feature = "write")]
+    if to_page {
+        return publish_changelog(client, ctx, &args, &kept).await;
+    }
+
+    let markdown = changelog_markdown(client.base_url(), args.title.as_deref(), &kept)?;
+    let Some(path) = &args.out else {
+        if !ctx.quiet {
+            print!("{markdown}");
+        }
+        return Ok(());
+    };
+    tokio::fs::write(path, &markdown)
+        .await
+        .with_context(|| format!("Failed to write {path}"))?;
+    let result = json!({ "file": path, "entries": kept.len() });
+    match args.output {
+        Some(OutputFormat::Json) => maybe_print_json(ctx, &result),
+        Some(fmt) => {
+            maybe_print_kv_fmt(
+                ctx,
+                fmt,
+                vec![
+                    vec!["File".to_string(), path.clone()],
+                    vec!["Entries".to_string(), kept.len().to_string()],
+                ],
+            );
+            Ok(())
+        }
+        None => {
+            crate::helpers::print_line(ctx, &format!("Wrote {} entries to {path}", kept.len()));
+            Ok(())
+        }
+    }
+}
+
+/// Labeled pages and blog posts, newest first by the `--date` field.
+async fn fetch_entries(
+    client: &ApiClient,
+    args: &GenerateChangelogArgs,
+    space_key: Option<&str>,
+    since: Option<&str>,
+    representation: &str,
+) -> Result<Vec<Entry>> {
+    let date_field = match args.date {
+        ChangelogDate::Created => "created",
+        ChangelogDate::Modified => "lastmodified",
+    };
+    let mut cql = format!(
+        "label = \"{}\" AND type in (page, blogpost)",
+        escape_cql_text(&args.label)
+    );
+    if let Some(key) = space_key {
+        cql.push_str(&format!(" AND space = \"{}\"", escape_cql_text(key)));
+    }
+    if let Some(since) = since {
+        cql.push_str(&format!(" AND {date_field} >= \"{since}\""));
+    }
+    cql.push_str(&format!(" ORDER BY {date_field} DESC"));
+    let url = url_with_query(
+        &client.v1_url("/content/search"),
+        &[
+            ("cql", cql),
+            (
+                "expand",
+                format!("body.{representation},history,version,space"),
+            ),
+            ("limit", "25".to_string()),
+        ],
+    )?;
+    let items = client.get_paginated_results(url, true).await?;
+    let mut entries: Vec<Entry> = items
+        .iter()
+        .map(|item| entry(client.base_url(), item, args.date, representation))
+        .collect();
+    // CQL orders by the full timestamp already; this keeps the order stable by day too.
+    entries.sort_by(|a, b| b.date.cmp(&a.date));
+    Ok(entries)
+}
+
+fn entry(base_url: &str, item: &Value, date: ChangelogDate, representation: &str) -> Entry {
+    let timestamp = match date {
+        ChangelogDate::Created => item.pointer("/history/createdDate"),
+        ChangelogDate::Modified => item.pointer("/version/when"),
+    }
+    .and_then(|v| v.as_str())
+    .unwrap_or("");
+    Entry {
+        id: json_str(item, "id"),
+        title: json_str(item, "title"),
+        #[cfg(feature = "write")]
+        kind: json_str(item, "type"),
+        #[cfg(feature = "write")]
+        space_key: item
+            .pointer("/space/key")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        date: timestamp.chars().take(10).collect(),
+        url: web_url(base_url, item),
+        body: item
+            .pointer(&format!("/body/{representation}/value"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+    }
+}
+
+/// The content under the first heading whose text is `heading` (case-insensitive), up to
+/// the next heading of the same or a higher level.
+fn section<'a>(html: &'a str, heading: &str) -> Option<&'a str> {
+    let wanted = heading.trim().to_lowercase();
+    let mut headings = HEADING_RE.captures_iter(html);
+    let (level, start) = headings.by_ref().find_map(|caps| {
+        let text = TAG_RE.replace_all(&caps[2], "");
+        (text.trim().to_lowercase() == wanted).then(|| {
+            let level: u8 = caps[1].parse().unwrap_or(1);
+            (level, caps.get(0).expect("match").end())
+        })
+    })?;
+    let end = headings
+        .find(|caps| caps[1].parse::<u8>().unwrap_or(1) <= level)
+        .map_or(html.len(), |caps| caps.get(0).expect("match").start());
+    Some(&html[start..end])
+}
+
+/// Shift headings down `by` levels (capped at `h6`) so entry content nests under the entry
+/// heading.
+fn demote_headings(html: &str, by: u8) -> String {
+    HEADING_TAG_RE
+        .replace_all(html, |caps: &regex::Captures| {
+            let level: u8 = caps[2].parse().unwrap_or(1);
+            format!("<{}h{}", &caps[1], (level + by).min(6))
+        })
+        .into_owned()
+}
+
+fn changelog_markdown(base_url: &str, title: Option<&str>, entries: &[Entry]) -> Result<String> {
+    let mut out = String::new();
+    if let Some(title) = title {
+        out.push_str(&format!("# {title}\n\n"));
+    }
+    for entry in entries {
+        out.push_str(&format!(
+            "## [{}]({}) ({})\n\n",
+            entry.title, entry.url, entry.date
+        ));
+        let body = html_to_markdown(&demote_headings(&entry.body, 2), base_url)?;
+        let body = body.trim();
+        if !body.is_empty() {
+            out.push_str(body);
+            out.push_str("\n\n");
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(feature = "write")]
+static ATTACHMENT_REF_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<ri:attachment\s+ri:filename="([^"]*)"\s*/>"#).expect("ATTACHMENT_REF_RE")
+});
+
+#[cfg(feature = "write")]
+async fn publish_changelog(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: &GenerateChangelogArgs,
+    entries: &[Entry],
+) -> Result<()> {
+    use super::publish::{Target, find_out_page, publish};
+    use crate::helpers::print_write_action_result;
+
+    let out = args.out.as_deref().context("Missing --out")?;
+    let existing = find_out_page(client, out).await?;
+    let entries: Vec<&Entry> = entries
+        .iter()
+        .filter(|entry| Some(&entry.id) != existing.as_ref().map(|page| &page.id))
+        .collect();
+    let body = changelog_storage(args, &entries);
+    let target = Target {
+        reference: out,
+        existing,
+        parent: args.parent.as_deref(),
+        force: args.force,
+        property: "confcli-changelog",
+        meta: json!({ "label": args.label }),
+        message: "Regenerated by confcli generate changelog",
+    };
+    let published = publish(client, ctx, target, &body).await?;
+    let (action, page_id, title) = (published.action, &published.page_id, &published.title);
+    let count = entries.len();
+    let message = match action {
+        "unchanged" => {
+            format!("Release notes page '{title}' ({page_id}) is up to date: {count} entries")
+        }
+        "created" => format!("Created release notes page '{title}' ({page_id}): {count} entries"),
+        "updated" => format!("Updated release notes page '{title}' ({page_id}): {count} entries"),
+        "would update" => {
+            format!("Would update release notes page '{title}' ({page_id}): {count} entries")
+        }
+        _ => format!("Would create release notes page '{title}': {count} entries"),
+    };
+    let mut result = json!({
+        "pageId": page_id,
+        "title": title,
+        "action": action,
+        "entries": count,
+    });
+    if ctx.dry_run {
+        result["dryRun"] = json!(true);
+    }
+    print_write_action_result(
+        ctx,
+        args.output,
+        &message,
+        &result,
+        vec![
+            vec!["Page".to_string(), page_id.clone()],
+            vec!["Title".to_string(), title.clone()],
+            vec!["Action".to_string(), action.to_string()],
+            vec!["Entries".to_string(), count.to_string()],
+        ],
+    )
+}
+
+/// Storage body of a release notes page: the generated note, then per entry a dated
+/// heading, its content, and a link back. Attachment references are qualified with the
+/// source page so images and files still resolve.
+#[cfg(feature = "write")]
+fn changelog_storage(args: &GenerateChangelogArgs, entries: &[&Entry]) -> String {
+    use super::publish::{generated_note, shell_word};
+    use confcli::markdown::escape_xml;
+
+    let mut command = format!(
+        "confcli generate changelog --label {}",
+        shell_word(&args.label)
+    );
+    for (flag, value) in [
+        ("--space", &args.space),
+        ("--since", &args.since),
+        ("--section", &args.section),
+        ("--out", &args.out),
+    ] {
+        if let Some(value) = value {
+            command.push_str(&format!(" {flag} {}", shell_word(value)));
+        }
+    }
+    if args.date == ChangelogDate::Modified {
+        command.push_str(" --date modified");
+    }
+    let mut out = generated_note(&format!("content labeled {}", args.label), &command);
+    if entries.is_empty() {
+        out.push_str("<p>No matching content.</p>");
+    }
+    for entry in entries {
+        let container = if entry.kind == "blogpost" {
+            format!(
+                "<ri:blog-post ri:space-key=\"{}\" ri:content-title=\"{}\" ri:posting-day=\"{}\" />",
+                escape_xml(&entry.space_key),
+                escape_xml(&entry.title),
+                entry.date.replace('-', "/")
+            )
+        } else {
+            format!(
+                "<ri:page ri:space-key=\"{}\" ri:content-title=\"{}\" />",
+                escape_xml(&entry.space_key),
+                escape_xml(&entry.title)
+            )
+        };
+        let body = ATTACHMENT_REF_RE.replace_all(&entry.body, |caps: &regex::Captures| {
+            format!(
+                "<ri:attachment ri:filename=\"{}\">{container}</ri:attachment>",
+                &caps[1]
+            )
+        });
+        out.push_str(&format!(
+            "<h2>{} ({})</h2>{}<p><a href=\"{}\">Source</a></p>",
+            escape_xml(&entry.title),
+            entry.date,
+            demote_headings(&body, 2),
+            escape_xml(&entry.url)
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_sections_by_heading() {
+        let html = "<h1>Intro</h1><p>a</p><h2>What&#39;s <em>new</em></h2><p>b</p><h3>Detail</h3><p>c</p><h2>Other</h2><p>d</p>";
+        assert_eq!(
+            section(html, "what&#39;s new"),
+            Some("<p>b</p><h3>Detail</h3><p>c</p>")
+        );
+        assert_eq!(section(html, "Other"), Some("<p>d</p>"));
+        assert_eq!(section(html, "Missing"), None);
+    }
+
+    #[test]
+    fn renders_markdown_newest_first() {
+        let item = |id: &str, title: &str, created: &str, body: &str| {
+            json!({
+                "id": id,
+                "title": title,
+                "type": "page",
+                "space": { "key": "ENG" },
+                "history": { "createdDate": created },
+                "_links": { "webui": format!("/spaces/ENG/pages/{id}") },
+                "body": { "view": { "value": body } },
+            })
+        };
+        let base = "https://example.atlassian.net/wiki";
+        let mut entries = vec![
+            entry(
+                base,
+                &item(
+                    "1",
+                    "1.0",
+                    "2025-01-05T10:00:00.000Z",
+                    "<h1>Fixes</h1><p>one</p>",
+                ),
+                ChangelogDate::Created,
+                "view",
+            ),
+            entry(
+                base,
+                &item("2", "1.1", "2025-02-01T09:00:00.000Z", "<p>two</p>"),
+                ChangelogDate::Created,
+                "view",
+            ),
+        ];
+        entries.sort_by(|a, b| b.date.cmp(&a.date));
+        let markdown = changelog_markdown(base, Some("Release notes"), &entries).unwrap();
+        assert_eq!(
+            markdown,
+            "# Release notes\n\n## [1.1](https://example.atlassian.net/wiki/spaces/ENG/pages/2) (2025-02-01)\n\ntwo\n\n## [1.0](https://example.atlassian.net/wiki/spaces/ENG/pages/1) (2025-01-05)\n\n### Fixes\n\none\n\n"
+        );
+    }
+}
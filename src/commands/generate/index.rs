@@ -0,0 +1,258 @@
+use anyhow::Result;
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::markdown::escape_xml;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+use super::publish::{Target, find_out_page, generated_note, publish, shell_word};
+use crate::cli::{GenerateIndexArgs, IndexGroupBy};
+use crate::commands::label::global_label_names;
+use crate::context::AppContext;
+use crate::helpers::{print_write_action_result, url_with_query};
+use crate::resolve::{resolve_space_id, resolve_space_key};
+
+/// Content property that marks a page as generated by `generate index`.
+const INDEX_PROPERTY: &str = "confcli-index";
+
+/// A page listed on the index.
+#[derive(Debug, Clone, PartialEq)]
+struct IndexEntry {
+    id: String,
+    title: String,
+    labels: Vec<String>,
+}
+
+pub(super) async fn generate_index(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: GenerateIndexArgs,
+) -> Result<()> {
+    let space_id = resolve_space_id(client, &args.space).await?;
+    let space_key = resolve_space_key(client, &space_id).await?;
+    let out = find_out_page(client, &args.out).await?;
+
+    let entries: Vec<IndexEntry> = fetch_labeled_pages(client, &space_key)
+        .await?
+        .into_iter()
+        .filter(|entry| Some(&entry.id) != out.as_ref().map(|page| &page.id))
+        .collect();
+    let groups = match args.group_by {
+        IndexGroupBy::Label => group_by_label(&entries, &args.labels, args.include_unlabeled),
+    };
+    let body = index_storage(&space_key, &groups, &args);
+    let listed: usize = groups.iter().map(|(_, pages)| pages.len()).sum();
+
+    let target = Target {
+        reference: &args.out,
+        existing: out,
+        parent: args.parent.as_deref(),
+        force: args.force,
+        property: INDEX_PROPERTY,
+        meta: json!({ "space": space_key, "groupBy": "label" }),
+        message: "Regenerated by confcli generate index",
+    };
+    let published = publish(client, ctx, target, &body).await?;
+    let (action, page_id, title) = (published.action, &published.page_id, &published.title);
+
+    let summary = format!("{} groups, {listed} entries", groups.len());
+    let message = match action {
+        "unchanged" => format!("Index page '{title}' ({page_id}) is up to date: {summary}"),
+        "created" => format!("Created index page '{title}' ({page_id}): {summary}"),
+        "updated" => format!("Updated index page '{title}' ({page_id}): {summary}"),
+        "would update" => format!("Would update index page '{title}' ({page_id}): {summary}"),
+        _ => format!("Would create index page '{title}': {summary}"),
+    };
+    let mut result = json!({
+        "space": space_key,
+        "pageId": page_id,
+        "title": title,
+        "action": action,
+        "groups": groups.len(),
+        "entries": listed,
+    });
+    if ctx.dry_run {
+        result["dryRun"] = json!(true);
+    }
+    print_write_action_result(
+        ctx,
+        args.output,
+        &message,
+        &result,
+        vec![
+            vec!["Page".to_string(), page_id.clone()],
+            vec!["Title".to_string(), title.clone()],
+            vec!["Action".to_string(), action.to_string()],
+            vec!["Groups".to_string(), groups.len().to_string()],
+            vec!["Entries".to_string(), listed.to_string()],
+        ],
+    )
+}
+
+/// Every current page of the space with its global labels, via one paginated content search.
+async fn fetch_labeled_pages(client: &ApiClient, space_key: &str) -> Result<Vec<IndexEntry>> {
+    let cql = format!("space = \"{space_key}\" AND type = page ORDER BY title");
+    let url = url_with_query(
+        &client.v1_url("/content/search"),
+        &[
+            ("cql", cql),
+            ("expand", "metadata.labels".to_string()),
+            ("limit", "100".to_string()),
+        ],
+    )?;
+    let items = client.get_paginated_results(url, true).await?;
+    Ok(items.iter().map(index_entry).collect())
+}
+
+fn index_entry(item: &Value) -> IndexEntry {
+    IndexEntry {
+        id: json_str(item, "id"),
+        title: json_str(item, "title"),
+        labels: global_label_names(item),
+    }
+}
+
+/// Pages grouped by label: the `only` labels in the given order, or every label
+/// alphabetically, then an "Unlabeled" group if asked for. Pages are sorted by title.
+fn group_by_label<'a>(
+    entries: &'a [IndexEntry],
+    only: &[String],
+    include_unlabeled: bool,
+) -> Vec<(String, Vec<&'a IndexEntry>)> {
+    let mut by_label: BTreeMap<&str, Vec<&IndexEntry>> = BTreeMap::new();
+    for entry in entries {
+        for label in &entry.labels {
+            by_label.entry(label.as_str()).or_default().push(entry);
+        }
+    }
+    let mut groups: Vec<(String, Vec<&IndexEntry>)> = if only.is_empty() {
+        by_label
+            .into_iter()
+            .map(|(label, pages)| (label.to_string(), pages))
+            .collect()
+    } else {
+        only.iter()
+            .filter_map(|label| {
+                by_label
+                    .get(label.as_str())
+                    .map(|pages| (label.clone(), pages.clone()))
+            })
+            .collect()
+    };
+    if include_unlabeled {
+        let unlabeled: Vec<&IndexEntry> = entries
+            .iter()
+            .filter(|entry| entry.labels.is_empty())
+            .collect();
+        if !unlabeled.is_empty() {
+            groups.push(("Unlabeled".to_string(), unlabeled));
+        }
+    }
+    for (_, pages) in &mut groups {
+        pages.sort_by_cached_key(|entry| (entry.title.to_lowercase(), entry.id.clone()));
+        pages.dedup_by(|a, b| a.id == b.id);
+    }
+    groups
+}
+
+/// Storage body of the index: a note on how the page is maintained, then one heading and
+/// link list per group. Deterministic, so an unchanged listing hashes the same.
+fn index_storage(
+    space_key: &str,
+    groups: &[(String, Vec<&IndexEntry>)],
+    args: &GenerateIndexArgs,
+) -> String {
+    let mut command = format!(
+        "confcli generate index --space {} --out {}",
+        shell_word(&args.space),
+        shell_word(&args.out)
+    );
+    for label in &args.labels {
+        command.push_str(&format!(" --label {}", shell_word(label)));
+    }
+    if args.include_unlabeled {
+        command.push_str(" --include-unlabeled");
+    }
+    let mut out = generated_note(&format!("the labels of space {space_key}"), &command);
+    if groups.is_empty() {
+        out.push_str("<p>No labeled pages.</p>");
+    }
+    for (label, pages) in groups {
+        out.push_str(&format!("<h2>{}</h2><ul>", escape_xml(label)));
+        for page in pages {
+            out.push_str(&format!(
+                "<li><ac:link><ri:page ri:space-key=\"{}\" ri:content-title=\"{}\" /></ac:link></li>",
+                escape_xml(space_key),
+                escape_xml(&page.title)
+            ));
+        }
+        out.push_str("</ul>");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, title: &str, labels: &[&str]) -> IndexEntry {
+        IndexEntry {
+            id: id.to_string(),
+            title: title.to_string(),
+            labels: labels.iter().map(|l| l.to_string()).collect(),
+        }
+    }
+
+    fn titles(groups: &[(String, Vec<&IndexEntry>)]) -> Vec<(String, Vec<String>)> {
+        groups
+            .iter()
+            .map(|(label, pages)| {
+                (
+                    label.clone(),
+                    pages.iter().map(|page| page.title.clone()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn groups_pages_by_label() {
+        let entries = vec![
+            entry("1", "zeta", &["runbook"]),
+            entry("2", "Alpha", &["runbook", "api"]),
+            entry("3", "Loose", &[]),
+        ];
+        assert_eq!(
+            titles(&group_by_label(&entries, &[], true)),
+            vec![
+                ("api".to_string(), vec!["Alpha".to_string()]),
+                (
+                    "runbook".to_string(),
+                    vec!["Alpha".to_string(), "zeta".to_string()]
+                ),
+                ("Unlabeled".to_string(), vec!["Loose".to_string()]),
+            ]
+        );
+        let only = vec!["runbook".to_string(), "missing".to_string()];
+        assert_eq!(
+            titles(&group_by_label(&entries, &only, false)),
+            vec![(
+                "runbook".to_string(),
+                vec!["Alpha".to_string(), "zeta".to_string()]
+            )]
+        );
+    }
+
+    #[test]
+    fn reads_global_labels_from_search_results() {
+        let item = json!({
+            "id": "7",
+            "title": "Deploys",
+            "metadata": { "labels": { "results": [
+                { "prefix": "global", "name": "runbook" },
+                { "prefix": "my", "name": "favourite" },
+            ] } },
+        });
+        assert_eq!(index_entry(&item), entry("7", "Deploys", &["runbook"]));
+    }
+}
@@ -0,0 +1,21 @@
+use anyhow::Result;
+
+use crate::cli::GenerateCommand;
+use crate::context::AppContext;
+
+mod changelog;
+#[cfg(feature = "write")]
+mod index;
+#[cfg(feature = "write")]
+pub(crate) mod publish;
+mod report;
+
+pub async fn handle(ctx: &AppContext, cmd: GenerateCommand) -> Result<()> {
+    let client = crate::context::load_client(ctx)?;
+    match cmd {
+        #[cfg(feature = "write")]
+        GenerateCommand::Index(args) => index::generate_index(&client, ctx, args).await,
+        GenerateCommand::Changelog(args) => changelog::generate_changelog(&client, ctx, args).await,
+        GenerateCommand::Report(args) => report::generate_report(&client, ctx, args).await,
+    }
+}
@@ -0,0 +1,265 @@
+//! Writing generated storage to a Confluence page. A generated page carries a content
+//! property with the hash of what was written, so re-runs only add a version when the
+//! content changed, and pages confcli did not generate are not overwritten by accident.
+
+use anyhow::{Context, Result};
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::markdown::escape_xml;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::context::AppContext;
+use crate::helpers::url_with_query;
+use crate::plan::{self, Operation};
+use crate::resolve::{resolve_page_id, resolve_space_id};
+
+/// An existing target page.
+pub(super) struct OutPage {
+    pub id: String,
+    pub title: String,
+    status: String,
+    version: i64,
+}
+
+/// Where to publish: the `--out` reference, the page it names if that exists, and what the
+/// marker property records.
+pub(super) struct Target<'a> {
+    pub reference: &'a str,
+    pub existing: Option<OutPage>,
+    pub parent: Option<&'a str>,
+    pub force: bool,
+    /// Content property key marking pages this generator wrote.
+    pub property: &'static str,
+    /// Extra fields stored in the property next to the content hash.
+    pub meta: Value,
+    /// Version message for updates.
+    pub message: &'static str,
+}
+
+/// What [`publish`] did: `created`, `updated`, `unchanged`, `would create`, or
+/// `would update`.
+pub(super) struct Published {
+    pub action: &'static str,
+    pub page_id: String,
+    pub title: String,
+}
+
+/// The `--out` page if it exists. A `SPACE:Title` that matches no page is `None` (to be
+/// created); other references must resolve.
+pub(super) async fn find_out_page(client: &ApiClient, reference: &str) -> Result<Option<OutPage>> {
+    let reference = reference.trim();
+    let page_id = match title_reference(reference) {
+        Some((space, title)) => {
+            let space_id = resolve_space_id(client, space).await?;
+            let url = url_with_query(
+                &client.v2_url("/pages"),
+                &[
+                    ("space-id", space_id),
+                    ("title", title.to_string()),
+                    ("limit", "1".to_string()),
+                ],
+            )?;
+            let items = client.get_paginated_results(url, false).await?;
+            match items.first() {
+                Some(item) => json_str(item, "id"),
+                None => return Ok(None),
+            }
+        }
+        None => resolve_page_id(client, reference).await?,
+    };
+    let (page, _) = client
+        .get_json(client.v2_url(&format!("/pages/{page_id}")))
+        .await?;
+    Ok(Some(OutPage {
+        id: page_id,
+        title: json_str(&page, "title"),
+        status: page
+            .get("status")
+            .and_then(|v| v.as_str())
+            .unwrap_or("current")
+            .to_string(),
+        version: page
+            .get("version")
+            .and_then(|v| v.get("number"))
+            .and_then(|v| v.as_i64())
+            .context("Missing current version number")?,
+    }))
+}
+
+/// `(space, title)` of a `SPACE:Title` reference; `None` for ids and URLs.
+fn title_reference(reference: &str) -> Option<(&str, &str)> {
+    if reference.chars().all(|c| c.is_ascii_digit()) || Url::parse(reference).is_ok() {
+        return None;
+    }
+    let (space, title) = reference.split_once(':')?;
+    Some((space.trim(), title.trim()))
+}
+
+/// Write `body` to the target page, creating it if needed. Honors `--dry-run`.
+pub(super) async fn publish(
+    client: &ApiClient,
+    ctx: &AppContext,
+    target: Target<'_>,
+    body: &str,
+) -> Result<Published> {
+    let hash = format!("{:x}", Sha256::digest(body.as_bytes()));
+    let mut value = target.meta.clone();
+    value["hash"] = json!(hash);
+
+    let Some(page) = &target.existing else {
+        let (space, title) = title_reference(target.reference)
+            .context("--out must be an existing page or SPACE:Title")?;
+        if ctx.dry_run {
+            plan::record(
+                Operation::new("create", "page", format!("{space}:{title}")).with_payload(json!({
+                    "parent": target.parent,
+                    "bodyBytes": body.len(),
+                    "hash": hash,
+                })),
+            );
+            return Ok(Published {
+                action: "would create",
+                page_id: String::new(),
+                title: title.to_string(),
+            });
+        }
+        let space_id = resolve_space_id(client, space).await?;
+        let mut payload = json!({
+            "spaceId": space_id,
+            "title": title,
+            "status": "current",
+            "body": { "representation": "storage", "value": body },
+        });
+        if let Some(parent) = target.parent {
+            payload["parentId"] = json!(resolve_page_id(client, parent).await?);
+        }
+        let result = client
+            .post_json(client.v2_url("/pages"), payload)
+            .await
+            .with_context(|| format!("Failed to create page '{title}'"))?;
+        let page_id = json_str(&result, "id");
+        write_property(client, &page_id, target.property, None, value).await?;
+        return Ok(Published {
+            action: "created",
+            page_id,
+            title: title.to_string(),
+        });
+    };
+
+    let property = read_property(client, &page.id, target.property).await?;
+    let written_hash = property
+        .as_ref()
+        .map(|property| json_str(&property["value"], "hash"));
+    if written_hash.is_none() && !target.force {
+        anyhow::bail!(
+            "Page {} ('{}') was not generated by confcli; pass --force to replace its content",
+            page.id,
+            page.title
+        );
+    }
+    let published = |action| Published {
+        action,
+        page_id: page.id.clone(),
+        title: page.title.clone(),
+    };
+    if written_hash.as_deref() == Some(hash.as_str()) {
+        return Ok(published("unchanged"));
+    }
+    if ctx.dry_run {
+        plan::record(
+            Operation::new("update", "page", &page.id).with_payload(json!({
+                "title": page.title,
+                "bodyBytes": body.len(),
+                "hash": hash,
+            })),
+        );
+        return Ok(published("would update"));
+    }
+    let payload = json!({
+        "id": page.id,
+        "title": page.title,
+        "status": page.status,
+        "body": { "representation": "storage", "value": body },
+        "version": { "number": page.version + 1, "message": target.message },
+    });
+    client
+        .put_json(client.v2_url(&format!("/pages/{}", page.id)), payload)
+        .await
+        .with_context(|| format!("Failed to update page {}", page.id))?;
+    write_property(client, &page.id, target.property, property.as_ref(), value).await?;
+    Ok(published("updated"))
+}
+
+/// The content property `key` of a page, if set.
+pub(crate) async fn read_property(
+    client: &ApiClient,
+    page_id: &str,
+    key: &str,
+) -> Result<Option<Value>> {
+    let url = url_with_query(
+        &client.v2_url(&format!("/pages/{page_id}/properties")),
+        &[("key", key.to_string())],
+    )?;
+    let items = client.get_paginated_results(url, false).await?;
+    Ok(items.into_iter().next())
+}
+
+/// Set a content property, updating `existing` (as returned by [`read_property`]) if given.
+pub(crate) async fn write_property(
+    client: &ApiClient,
+    page_id: &str,
+    key: &str,
+    existing: Option<&Value>,
+    value: Value,
+) -> Result<()> {
+    match existing {
+        Some(property) => {
+            let version = property
+                .pointer("/version/number")
+                .and_then(|v| v.as_i64())
+                .unwrap_or(1);
+            let url = client.v2_url(&format!(
+                "/pages/{page_id}/properties/{}",
+                json_str(property, "id")
+            ));
+            client
+                .put_json(
+                    url,
+                    json!({ "key": key, "value": value, "version": { "number": version + 1 } }),
+                )
+                .await?;
+        }
+        None => {
+            let url = client.v2_url(&format!("/pages/{page_id}/properties"));
+            client
+                .post_json(url, json!({ "key": key, "value": value }))
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Info panel opening a generated page: what it was built from and the command that
+/// refreshes it.
+pub(super) fn generated_note(source: &str, command: &str) -> String {
+    format!(
+        "<ac:structured-macro ac:name=\"info\"><ac:rich-text-body><p>Generated from {} by <code>{}</code>. Manual edits are replaced on the next run.</p></ac:rich-text-body></ac:structured-macro>",
+        escape_xml(source),
+        escape_xml(command)
+    )
+}
+
+/// `value` quoted for a POSIX shell when it needs it.
+pub(super) fn shell_word(value: &str) -> String {
+    if !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.:/".contains(c))
+    {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
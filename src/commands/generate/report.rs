@@ -0,0 +1,419 @@
+use anyhow::{Context, Result};
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::output::OutputFormat;
+use serde_json::{Value, json};
+
+use crate::cli::GenerateReportArgs;
+use crate::context::AppContext;
+use crate::helpers::{
+    csv_record, escape_cql_text, maybe_print_json, maybe_print_kv_fmt, maybe_print_rows,
+    print_line, url_with_query, web_url,
+};
+use crate::resolve::{resolve_space_id, resolve_space_key};
+
+/// A page and its values of the reported properties, in `--property` order.
+#[derive(Debug)]
+struct ReportRow {
+    id: String,
+    title: String,
+    url: String,
+    values: Vec<Option<Value>>,
+}
+
+/// A report column: a property, or one field of a property whose values are objects.
+#[derive(Debug, PartialEq)]
+struct Column {
+    property: usize,
+    field: Option<String>,
+    header: String,
+}
+
+pub(super) async fn generate_report(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: GenerateReportArgs,
+) -> Result<()> {
+    let to_page = args.out_is_page();
+    #[cfg(not(feature = "write"))]
+    if to_page {
+        anyhow::bail!("Writing a report to a page needs a build with the `write` feature");
+    }
+
+    let space_id = resolve_space_id(client, &args.space).await?;
+    let space_key = resolve_space_key(client, &space_id).await?;
+    let mut rows = fetch_rows(client, &args, &space_key).await?;
+    if !args.include_missing {
+        rows.retain(|row| row.values.iter().any(Option::is_some));
+    }
+
+    #[cfg(feature = "write")]
+    if to_page {
+        return publish_report(client, ctx, &args, &space_key, rows).await;
+    }
+
+    let columns = columns(&args.properties, &rows);
+    match args.out.as_deref() {
+        Some(path) => {
+            let mut headers = vec!["ID".to_string(), "Title".to_string()];
+            headers.extend(columns.iter().map(|column| column.header.clone()));
+            headers.push("URL".to_string());
+            let mut csv = csv_record(&headers);
+            csv.push('\n');
+            for row in &rows {
+                let mut fields = vec![row.id.clone(), row.title.clone()];
+                fields.extend(cells(&columns, row));
+                fields.push(row.url.clone());
+                csv.push_str(&csv_record(&fields));
+                csv.push('\n');
+            }
+            if path == "-" {
+                if !ctx.quiet {
+                    print!("{csv}");
+                }
+                return Ok(());
+            }
+            tokio::fs::write(path, csv)
+                .await
+                .with_context(|| format!("Failed to write {path}"))?;
+            let result = json!({ "file": path, "pages": rows.len(), "columns": columns.len() });
+            match args.output {
+                Some(OutputFormat::Json) => maybe_print_json(ctx, &result),
+                Some(fmt) => {
+                    maybe_print_kv_fmt(
+                        ctx,
+                        fmt,
+                        vec![
+                            vec!["File".to_string(), path.to_string()],
+                            vec!["Pages".to_string(), rows.len().to_string()],
+                        ],
+                    );
+                    Ok(())
+                }
+                None => {
+                    print_line(ctx, &format!("Wrote {} pages to {path}", rows.len()));
+                    Ok(())
+                }
+            }
+        }
+        None => match args.output.unwrap_or(OutputFormat::Table) {
+            OutputFormat::Json => {
+                let items: Vec<Value> = rows
+                    .iter()
+                    .map(|row| {
+                        let properties: serde_json::Map<String, Value> = args
+                            .properties
+                            .iter()
+                            .zip(&row.values)
+                            .filter_map(|(key, value)| Some((key.clone(), value.clone()?)))
+                            .collect();
+                        json!({
+                            "id": row.id,
+                            "title": row.title,
+                            "url": row.url,
+                            "properties": properties,
+                        })
+                    })
+                    .collect();
+                maybe_print_json(ctx, &items)
+            }
+            fmt => {
+                let mut headers = vec!["ID", "Title"];
+                headers.extend(columns.iter().map(|column| column.header.as_str()));
+                let table = rows
+                    .iter()
+                    .map(|row| {
+                        let mut fields = vec![row.id.clone(), row.title.clone()];
+                        fields.extend(cells(&columns, row));
+                        fields
+                    })
+                    .collect();
+                maybe_print_rows(ctx, fmt, &headers, table);
+                Ok(())
+            }
+        },
+    }
+}
+
+/// Current pages of the space (optionally with a label) and the reported properties, via
+/// one paginated content search that expands each property.
+async fn fetch_rows(
+    client: &ApiClient,
+    args: &GenerateReportArgs,
+    space_key: &str,
+) -> Result<Vec<ReportRow>> {
+    let mut cql = format!("space = \"{}\" AND type = page", escape_cql_text(space_key));
+    if let Some(label) = &args.label {
+        cql.push_str(&format!(" AND label = \"{}\"", escape_cql_text(label)));
+    }
+    cql.push_str(" ORDER BY title");
+    let expand = args
+        .properties
+        .iter()
+        .map(|key| format!("metadata.properties.{key}"))
+        .collect::<Vec<_>>()
+        .join(",");
+    let url = url_with_query(
+        &client.v1_url("/content/search"),
+        &[
+            ("cql", cql),
+            ("expand", expand),
+            ("limit", "100".to_string()),
+        ],
+    )?;
+    let items = client.get_paginated_results(url, true).await?;
+    Ok(items
+        .iter()
+        .map(|item| report_row(client.base_url(), item, &args.properties))
+        .collect())
+}
+
+fn report_row(base_url: &str, item: &Value, properties: &[String]) -> ReportRow {
+    let values = properties
+        .iter()
+        .map(|key| {
+            item.pointer("/metadata/properties")
+                .and_then(|props| props.get(key))
+                .and_then(|property| property.get("value"))
+                .cloned()
+        })
+        .collect();
+    ReportRow {
+        id: json_str(item, "id"),
+        title: json_str(item, "title"),
+        url: web_url(base_url, item),
+        values,
+    }
+}
+
+/// One column per property, or, when every value of a property is an object, one column
+/// per field seen (`team.name`).
+fn columns(properties: &[String], rows: &[ReportRow]) -> Vec<Column> {
+    let mut columns = Vec::new();
+    for (index, key) in properties.iter().enumerate() {
+        let values: Vec<&Value> = rows
+            .iter()
+            .filter_map(|row| row.values[index].as_ref())
+            .collect();
+        if values.is_empty() || !values.iter().all(|value| value.is_object()) {
+            columns.push(Column {
+                property: index,
+                field: None,
+                header: key.clone(),
+            });
+            continue;
+        }
+        let mut fields: Vec<&String> = values
+            .iter()
+            .filter_map(|value| value.as_object())
+            .flat_map(|object| object.keys())
+            .collect();
+        fields.sort();
+        fields.dedup();
+        columns.extend(fields.into_iter().map(|field| Column {
+            property: index,
+            field: Some(field.clone()),
+            header: format!("{key}.{field}"),
+        }));
+    }
+    columns
+}
+
+fn cells(columns: &[Column], row: &ReportRow) -> Vec<String> {
+    columns
+        .iter()
+        .map(|column| {
+            let value = row.values[column.property].as_ref();
+            let value = match &column.field {
+                Some(field) => value.and_then(|value| value.get(field)),
+                None => value,
+            };
+            value.map(cell_text).unwrap_or_default()
+        })
+        .collect()
+}
+
+/// A property value as cell text: scalars as is, arrays joined with commas, objects as JSON.
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items.iter().map(cell_text).collect::<Vec<_>>().join(", "),
+        Value::Object(_) => value.to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(feature = "write")]
+async fn publish_report(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: &GenerateReportArgs,
+    space_key: &str,
+    rows: Vec<ReportRow>,
+) -> Result<()> {
+    use super::publish::{Target, find_out_page, publish};
+    use crate::helpers::print_write_action_result;
+
+    let out = args.out.as_deref().context("Missing --out")?;
+    let existing = find_out_page(client, out).await?;
+    let rows: Vec<ReportRow> = rows
+        .into_iter()
+        .filter(|row| Some(&row.id) != existing.as_ref().map(|page| &page.id))
+        .collect();
+    let columns = columns(&args.properties, &rows);
+    let body = report_storage(args, space_key, &columns, &rows);
+    let target = Target {
+        reference: out,
+        existing,
+        parent: args.parent.as_deref(),
+        force: args.force,
+        property: "confcli-report",
+        meta: json!({ "space": space_key, "properties": args.properties }),
+        message: "Regenerated by confcli generate report",
+    };
+    let published = publish(client, ctx, target, &body).await?;
+    let (action, page_id, title) = (published.action, &published.page_id, &published.title);
+    let count = rows.len();
+    let message = match action {
+        "unchanged" => format!("Report page '{title}' ({page_id}) is up to date: {count} pages"),
+        "created" => format!("Created report page '{title}' ({page_id}): {count} pages"),
+        "updated" => format!("Updated report page '{title}' ({page_id}): {count} pages"),
+        "would update" => format!("Would update report page '{title}' ({page_id}): {count} pages"),
+        _ => format!("Would create report page '{title}': {count} pages"),
+    };
+    let mut result = json!({
+        "space": space_key,
+        "pageId": page_id,
+        "title": title,
+        "action": action,
+        "pages": count,
+        "columns": columns.len(),
+    });
+    if ctx.dry_run {
+        result["dryRun"] = json!(true);
+    }
+    print_write_action_result(
+        ctx,
+        args.output,
+        &message,
+        &result,
+        vec![
+            vec!["Page".to_string(), page_id.clone()],
+            vec!["Title".to_string(), title.clone()],
+            vec!["Action".to_string(), action.to_string()],
+            vec!["Pages".to_string(), count.to_string()],
+        ],
+    )
+}
+
+/// Storage body of a report page: the generated note, then one table row per page with a
+/// link to it. Deterministic, so an unchanged report hashes the same.
+#[cfg(feature = "write")]
+fn report_storage(
+    args: &GenerateReportArgs,
+    space_key: &str,
+    columns: &[Column],
+    rows: &[ReportRow],
+) -> String {
+    use super::publish::{generated_note, shell_word};
+    use confcli::markdown::escape_xml;
+
+    let mut command = String::from("confcli generate report");
+    for key in &args.properties {
+        command.push_str(&format!(" --property {}", shell_word(key)));
+    }
+    command.push_str(&format!(" --space {}", shell_word(&args.space)));
+    if let Some(label) = &args.label {
+        command.push_str(&format!(" --label {}", shell_word(label)));
+    }
+    if args.include_missing {
+        command.push_str(" --include-missing");
+    }
+    if let Some(out) = &args.out {
+        command.push_str(&format!(" --out {}", shell_word(out)));
+    }
+    let source = format!(
+        "the {} properties of space {space_key}",
+        args.properties.join(", ")
+    );
+    let mut out = generated_note(&source, &command);
+    if rows.is_empty() {
+        out.push_str("<p>No pages with these properties.</p>");
+        return out;
+    }
+    out.push_str("<table><tbody><tr><th>Page</th>");
+    for column in columns {
+        out.push_str(&format!("<th>{}</th>", escape_xml(&column.header)));
+    }
+    out.push_str("</tr>");
+    for row in rows {
+        out.push_str(&format!(
+            "<tr><td><ac:link><ri:page ri:space-key=\"{}\" ri:content-title=\"{}\" /></ac:link></td>",
+            escape_xml(space_key),
+            escape_xml(&row.title)
+        ));
+        for cell in cells(columns, row) {
+            out.push_str(&format!("<td>{}</td>", escape_xml(&cell)));
+        }
+        out.push_str("</tr>");
+    }
+    out.push_str("</tbody></table>");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(title: &str, values: Vec<Option<Value>>) -> ReportRow {
+        ReportRow {
+            id: title.to_lowercase(),
+            title: title.to_string(),
+            url: String::new(),
+            values,
+        }
+    }
+
+    #[test]
+    fn reads_expanded_properties() {
+        let item = json!({
+            "id": "1",
+            "title": "Billing",
+            "_links": { "webui": "/spaces/ENG/pages/1" },
+            "metadata": { "properties": { "team": { "key": "team", "value": "payments" } } },
+        });
+        let properties = ["team".to_string(), "owner".to_string()];
+        let row = report_row("https://example.atlassian.net/wiki", &item, &properties);
+        assert_eq!(row.values, vec![Some(json!("payments")), None]);
+        assert_eq!(
+            row.url,
+            "https://example.atlassian.net/wiki/spaces/ENG/pages/1"
+        );
+    }
+
+    #[test]
+    fn object_properties_get_a_column_per_field() {
+        let properties = ["team".to_string(), "tier".to_string()];
+        let rows = vec![
+            row(
+                "A",
+                vec![
+                    Some(json!({ "name": "core", "slack": "#core" })),
+                    Some(json!(1)),
+                ],
+            ),
+            row(
+                "B",
+                vec![Some(json!({ "name": "web" })), Some(json!(["a", "b"]))],
+            ),
+            row("C", vec![None, Some(json!({ "x": true }))]),
+        ];
+        let columns = columns(&properties, &rows);
+        let headers: Vec<&str> = columns.iter().map(|c| c.header.as_str()).collect();
+        assert_eq!(headers, ["team.name", "team.slack", "tier"]);
+        assert_eq!(cells(&columns, &rows[0]), ["core", "#core", "1"]);
+        assert_eq!(cells(&columns, &rows[1]), ["web", "", "a, b"]);
+        assert_eq!(cells(&columns, &rows[2]), ["", "", "{\"x\":true}"]);
+    }
+}
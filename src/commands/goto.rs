@@ -0,0 +1,126 @@
+use std::io::IsTerminal;
+
+use anyhow::{Context, Result, bail};
+use confcli::json_util::json_str;
+use serde_json::Value;
+
+use crate::cli::GotoArgs;
+use crate::commands::search::{search_page, search_result_space, to_cql_query};
+use crate::context::AppContext;
+use crate::helpers::{escape_cql_text, open_url, print_line, web_url};
+
+pub async fn handle(ctx: &AppContext, args: GotoArgs) -> Result<()> {
+    if args.query.trim().is_empty() {
+        bail!("Search query cannot be empty");
+    }
+    let mut cql = to_cql_query(&args.query);
+    if let Some(space) = &args.space {
+        cql = format!("space = \"{}\" AND ({cql})", escape_cql_text(space));
+    }
+    let client = crate::context::load_client(ctx)?;
+    let results = search_page(&client, &cql, args.limit).await?;
+    if results.is_empty() {
+        bail!("No results for '{}'", args.query);
+    }
+
+    let choice = match confident_match(&args.query, &results) {
+        Some(index) => index,
+        None if std::io::stdin().is_terminal() && std::io::stderr().is_terminal() => {
+            let labels: Vec<String> = results
+                .iter()
+                .enumerate()
+                .map(|(i, item)| format!("{}. {}", i + 1, describe(item)))
+                .collect();
+            dialoguer::Select::new()
+                .with_prompt(format!(
+                    "{} matches for '{}'. Open",
+                    results.len(),
+                    args.query
+                ))
+                .items(&labels)
+                .default(0)
+                .interact_opt()?
+                .context("Cancelled")?
+        }
+        None => {
+            let list: Vec<String> = results
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    let id = json_str(item.get("content").unwrap_or(&Value::Null), "id");
+                    format!("  {}. {}  (id {id})", i + 1, describe(item))
+                })
+                .collect();
+            bail!(
+                "No clear best match for '{}'. Top hits:\n{}",
+                args.query,
+                list.join("\n")
+            );
+        }
+    };
+
+    let url = web_url(client.base_url(), &results[choice]);
+    if url.is_empty() {
+        bail!("Missing web link for '{}'", describe(&results[choice]));
+    }
+    if ctx.dry_run {
+        print_line(ctx, &format!("Would open {url}"));
+        return Ok(());
+    }
+    print_line(ctx, &format!("Opening {url}"));
+    open_url(&url)
+}
+
+/// The hit to open without asking: the only result, or the only one whose title is the
+/// query (ignoring case and surrounding whitespace).
+fn confident_match(query: &str, results: &[Value]) -> Option<usize> {
+    if results.len() == 1 {
+        return Some(0);
+    }
+    let query = query.trim().to_lowercase();
+    let mut exact = results.iter().enumerate().filter(|(_, item)| {
+        let content = item.get("content").unwrap_or(&Value::Null);
+        json_str(content, "title").trim().to_lowercase() == query
+    });
+    match (exact.next(), exact.next()) {
+        (Some((index, _)), None) => Some(index),
+        _ => None,
+    }
+}
+
+/// `Title (SPACE)`, for the picker.
+fn describe(item: &Value) -> String {
+    let title = json_str(item.get("content").unwrap_or(&Value::Null), "title");
+    match search_result_space(item) {
+        space if space.is_empty() => title,
+        space => format!("{title} ({space})"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn hit(title: &str) -> Value {
+        json!({ "content": { "id": "1", "title": title, "space": { "key": "MFS" } } })
+    }
+
+    #[test]
+    fn confident_match_needs_a_single_hit_or_a_unique_exact_title() {
+        assert_eq!(confident_match("anything", &[hit("Runbook")]), Some(0));
+        assert_eq!(
+            confident_match(" runbook ", &[hit("Runbook notes"), hit("Runbook")]),
+            Some(1)
+        );
+        assert_eq!(
+            confident_match("runbook", &[hit("Runbook"), hit("runbook")]),
+            None
+        );
+        assert_eq!(
+            confident_match("runbook", &[hit("Runbook notes"), hit("Old runbook")]),
+            None
+        );
+        assert_eq!(describe(&hit("Runbook")), "Runbook (MFS)");
+    }
+}
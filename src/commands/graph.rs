@@ -0,0 +1,232 @@
+use anyhow::Result;
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::markdown::decode_html_entities;
+use regex::Regex;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use url::Url;
+
+use crate::cli::{GraphArgs, GraphFormat};
+use crate::context::AppContext;
+use crate::download::fetch_space_pages_with_body;
+use crate::helpers::{maybe_print_json, print_line};
+use crate::resolve::{extract_page_id_from_url, resolve_space_id, resolve_space_key};
+
+static RI_PAGE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<ri:page\b([^>]*?)/?>").expect("RI_PAGE_RE"));
+static RI_ATTR_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"ri:(content-title|space-key)="([^"]*)""#).expect("RI_ATTR_RE"));
+static HREF_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"<a\b[^>]*\bhref="([^"]+)""#).expect("HREF_RE"));
+
+pub async fn handle(ctx: &AppContext, args: GraphArgs) -> Result<()> {
+    let client = crate::context::load_client(ctx)?;
+    graph(&client, ctx, args).await
+}
+
+/// The link graph of one space: pages in listing order, each with the in-space pages it
+/// links to.
+struct LinkGraph {
+    pages: Vec<(String, String)>,
+    links: Vec<Vec<usize>>,
+}
+
+impl LinkGraph {
+    /// Build the graph from pages with storage bodies. Links are `ri:page` references
+    /// (links, includes) and absolute or `/wiki` URLs pointing at a page of the space.
+    fn build(items: &[Value], space_key: &str, site: &Url) -> Self {
+        let pages: Vec<(String, String)> = items
+            .iter()
+            .map(|item| (json_str(item, "id"), json_str(item, "title")))
+            .collect();
+        let by_id: HashMap<&str, usize> = pages
+            .iter()
+            .enumerate()
+            .map(|(idx, (id, _))| (id.as_str(), idx))
+            .collect();
+        let by_title: HashMap<&str, usize> = pages
+            .iter()
+            .enumerate()
+            .map(|(idx, (_, title))| (title.as_str(), idx))
+            .collect();
+
+        let mut links = Vec::with_capacity(items.len());
+        for (idx, item) in items.iter().enumerate() {
+            let storage = item
+                .get("body")
+                .and_then(|b| b.get("storage"))
+                .and_then(|s| s.get("value"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            let mut targets = Vec::new();
+            for page in RI_PAGE_RE.captures_iter(storage) {
+                let mut title = None;
+                let mut key = space_key.to_string();
+                for attr in RI_ATTR_RE.captures_iter(&page[1]) {
+                    let value = decode_html_entities(&attr[2]);
+                    match &attr[1] {
+                        "content-title" => title = Some(value),
+                        _ => key = value,
+                    }
+                }
+                if key == space_key
+                    && let Some(&target) = title.as_deref().and_then(|t| by_title.get(t))
+                {
+                    targets.push(target);
+                }
+            }
+            for href in HREF_RE.captures_iter(storage) {
+                let href = decode_html_entities(&href[1]);
+                let Ok(url) = site.join(&href) else { continue };
+                if url.host_str() != site.host_str() {
+                    continue;
+                }
+                if let Some(&target) =
+                    extract_page_id_from_url(&url).and_then(|id| by_id.get(id.as_str()))
+                {
+                    targets.push(target);
+                }
+            }
+            targets.retain(|&target| target != idx);
+            targets.sort_unstable();
+            targets.dedup();
+            links.push(targets);
+        }
+        Self { pages, links }
+    }
+
+    /// Weakly connected components, largest first; pages keep their listing order.
+    fn clusters(&self) -> Vec<Vec<usize>> {
+        let mut parent: Vec<usize> = (0..self.pages.len()).collect();
+        fn root(parent: &mut [usize], mut idx: usize) -> usize {
+            while parent[idx] != idx {
+                parent[idx] = parent[parent[idx]];
+                idx = parent[idx];
+            }
+            idx
+        }
+        for (from, targets) in self.links.iter().enumerate() {
+            for &to in targets {
+                let (a, b) = (root(&mut parent, from), root(&mut parent, to));
+                if a != b {
+                    parent[a.max(b)] = a.min(b);
+                }
+            }
+        }
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        let mut group_of: HashMap<usize, usize> = HashMap::new();
+        for idx in 0..self.pages.len() {
+            let r = root(&mut parent, idx);
+            let group = *group_of.entry(r).or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[group].push(idx);
+        }
+        groups.sort_by_key(|group| std::cmp::Reverse(group.len()));
+        groups
+    }
+
+    fn to_dot(&self, space_key: &str) -> String {
+        let quote =
+            |value: &str| format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut out = format!("digraph {} {{\n", quote(space_key));
+        for (id, title) in &self.pages {
+            out.push_str(&format!("  {} [label={}];\n", quote(id), quote(title)));
+        }
+        for (from, targets) in self.links.iter().enumerate() {
+            for &to in targets {
+                out.push_str(&format!(
+                    "  {} -> {};\n",
+                    quote(&self.pages[from].0),
+                    quote(&self.pages[to].0)
+                ));
+            }
+        }
+        out.push('}');
+        out
+    }
+
+    fn to_json(&self, space_key: &str) -> Value {
+        let id = |idx: &usize| self.pages[*idx].0.clone();
+        let nodes: Vec<Value> = self
+            .pages
+            .iter()
+            .zip(&self.links)
+            .map(|((page_id, title), targets)| {
+                json!({
+                    "id": page_id,
+                    "title": title,
+                    "links": targets.iter().map(id).collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        let clusters: Vec<Vec<String>> = self
+            .clusters()
+            .iter()
+            .map(|group| group.iter().map(id).collect())
+            .collect();
+        json!({ "space": space_key, "nodes": nodes, "clusters": clusters })
+    }
+}
+
+async fn graph(client: &ApiClient, ctx: &AppContext, args: GraphArgs) -> Result<()> {
+    let space_id = resolve_space_id(client, &args.space).await?;
+    let space_key = resolve_space_key(client, &space_id).await?;
+    let items = fetch_space_pages_with_body(client, &space_id, "storage").await?;
+    let site = Url::parse(client.base_url())?;
+    let graph = LinkGraph::build(&items, &space_key, &site);
+
+    match args.output {
+        GraphFormat::Json => maybe_print_json(ctx, &graph.to_json(&space_key)),
+        GraphFormat::Dot => {
+            print_line(ctx, &graph.to_dot(&space_key));
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(id: &str, title: &str, storage: &str) -> Value {
+        json!({ "id": id, "title": title, "body": { "storage": { "value": storage } } })
+    }
+
+    #[test]
+    fn builds_links_and_clusters() {
+        let items = vec![
+            page(
+                "1",
+                "Home",
+                r#"<ac:link><ri:page ri:content-title="Guide &amp; FAQ" /></ac:link><ac:link><ri:page ri:space-key="OTHER" ri:content-title="Setup" /></ac:link>"#,
+            ),
+            page(
+                "2",
+                "Guide & FAQ",
+                r#"<a href="https://x.test/wiki/spaces/OPS/pages/1/Home">home</a><a href="/wiki/spaces/OPS/pages/2">self</a>"#,
+            ),
+            page("3", "Setup", ""),
+            page(
+                "4",
+                "Orphan",
+                r#"<a href="https://elsewhere.test/pages/3">x</a>"#,
+            ),
+        ];
+        let site = Url::parse("https://x.test/wiki").unwrap();
+        let graph = LinkGraph::build(&items, "OPS", &site);
+        assert_eq!(graph.links, vec![vec![1], vec![0], vec![], vec![]]);
+
+        let out = graph.to_json("OPS");
+        assert_eq!(out["nodes"][0]["links"], json!(["2"]));
+        assert_eq!(out["clusters"], json!([["1", "2"], ["3"], ["4"]]));
+        assert!(
+            graph
+                .to_dot("OPS")
+                .contains("  \"2\" [label=\"Guide & FAQ\"];\n  \"3\"")
+        );
+    }
+}
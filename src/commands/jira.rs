@@ -0,0 +1,235 @@
+//! Remote issue links between pages and Jira issues, through the Jira REST API of the same
+//! Atlassian site with the same credentials.
+//!
+//! Links carry a global id derived from the page id, which makes `jira link` idempotent and
+//! lets `jira links` find the issues again with `issuesWithRemoteLinksByGlobalId`.
+
+use anyhow::{Context, Result};
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::output::OutputFormat;
+#[cfg(feature = "write")]
+use regex::Regex;
+use serde_json::Value;
+#[cfg(feature = "write")]
+use serde_json::json;
+#[cfg(feature = "write")]
+use std::sync::LazyLock;
+
+#[cfg(feature = "write")]
+use crate::cli::JiraLinkArgs;
+use crate::cli::{JiraCommand, JiraLinksArgs};
+use crate::context::AppContext;
+use crate::helpers::*;
+#[cfg(feature = "write")]
+use crate::plan::{self, Operation};
+use crate::resolve::resolve_page_id;
+
+#[cfg(feature = "write")]
+static ISSUE_KEY_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Z][A-Z0-9_]*-[1-9][0-9]*$").expect("ISSUE_KEY_RE"));
+
+pub async fn handle(ctx: &AppContext, cmd: JiraCommand) -> Result<()> {
+    let client = crate::context::load_client(ctx)?;
+    match cmd {
+        #[cfg(feature = "write")]
+        JiraCommand::Link(args) => jira_link(&client, ctx, args).await,
+        JiraCommand::Links(args) => jira_links(&client, ctx, args).await,
+    }
+}
+
+/// The remote link's global id, which is also the link target: the page's id-based URL, so
+/// it survives renames and moves.
+fn page_global_id(client: &ApiClient, page_id: &str) -> String {
+    format!(
+        "{}/pages/viewpage.action?pageId={page_id}",
+        client.base_url().trim_end_matches('/')
+    )
+}
+
+#[cfg(feature = "write")]
+fn normalize_issue_key(key: &str) -> Result<String> {
+    let key = key.trim().to_ascii_uppercase();
+    if !ISSUE_KEY_RE.is_match(&key) {
+        anyhow::bail!("'{key}' is not a Jira issue key like PROJ-123");
+    }
+    Ok(key)
+}
+
+#[cfg(feature = "write")]
+async fn jira_link(client: &ApiClient, ctx: &AppContext, args: JiraLinkArgs) -> Result<()> {
+    let keys = args
+        .issues
+        .iter()
+        .map(|key| normalize_issue_key(key))
+        .collect::<Result<Vec<_>>>()?;
+    let page_id = resolve_page_id(client, &args.page).await?;
+    let (page, _) = client
+        .get_json(client.v2_url(&format!("/pages/{page_id}")))
+        .await?;
+    let title = json_str(&page, "title");
+    let global_id = page_global_id(client, &page_id);
+
+    let mut results = Vec::with_capacity(keys.len());
+    for key in keys {
+        if ctx.dry_run {
+            plan::record(
+                Operation::new("link", "issue", &key)
+                    .with_payload(json!({ "pageId": page_id, "globalId": global_id })),
+            );
+            results.push(json!({ "issue": key, "pageId": page_id, "dryRun": true }));
+            continue;
+        }
+        let payload = json!({
+            "globalId": global_id,
+            "application": { "type": "com.atlassian.confluence", "name": "Confluence" },
+            "relationship": "Wiki Page",
+            "object": { "url": global_id, "title": title },
+        });
+        let link = client
+            .post_json(
+                client.jira_api_url(&format!("/issue/{key}/remotelink")),
+                payload,
+            )
+            .await
+            .with_context(|| format!("Failed to link {key} to page {page_id}"))?;
+        results.push(json!({ "issue": key, "pageId": page_id, "linkId": link.get("id") }));
+    }
+
+    match args.output {
+        OutputFormat::Json => maybe_print_json(ctx, &results),
+        fmt => {
+            let status = if ctx.dry_run { "would link" } else { "linked" };
+            let rows = results
+                .iter()
+                .map(|result| vec![json_str(result, "issue"), title.clone(), status.to_string()])
+                .collect();
+            maybe_print_rows(ctx, fmt, &["Issue", "Page", "Status"], rows);
+            Ok(())
+        }
+    }
+}
+
+fn links_jql(global_id: &str) -> String {
+    format!(
+        "issue in issuesWithRemoteLinksByGlobalId(\"{}\") ORDER BY key",
+        global_id.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+/// One page of the Jira issue search for `jql`.
+fn links_search_url(
+    client: &ApiClient,
+    jql: &str,
+    next_page_token: Option<&str>,
+) -> Result<String> {
+    let mut query = vec![
+        ("jql", jql.to_string()),
+        ("fields", "summary,status".to_string()),
+        ("maxResults", "100".to_string()),
+    ];
+    if let Some(token) = next_page_token {
+        query.push(("nextPageToken", token.to_string()));
+    }
+    url_with_query(&client.jira_api_url("/search/jql"), &query)
+}
+
+async fn jira_links(client: &ApiClient, ctx: &AppContext, args: JiraLinksArgs) -> Result<()> {
+    let page_id = resolve_page_id(client, &args.page).await?;
+    let jql = links_jql(&page_global_id(client, &page_id));
+
+    let mut issues: Vec<Value> = Vec::new();
+    let mut next_page_token: Option<String> = None;
+    loop {
+        let url = links_search_url(client, &jql, next_page_token.as_deref())?;
+        let (page, _) = client
+            .get_json(url)
+            .await
+            .context("Jira search for linked issues failed")?;
+        if let Some(batch) = page.get("issues").and_then(Value::as_array) {
+            issues.extend(batch.iter().cloned());
+        }
+        next_page_token = page
+            .get("nextPageToken")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        if next_page_token.is_none() {
+            break;
+        }
+    }
+
+    match args.output {
+        OutputFormat::Json => maybe_print_json(ctx, &issues),
+        fmt => {
+            let origin = client.origin_url().trim_end_matches('/');
+            let rows = issues
+                .iter()
+                .map(|issue| {
+                    let key = json_str(issue, "key");
+                    let fields = issue.get("fields").cloned().unwrap_or(Value::Null);
+                    let status = fields
+                        .get("status")
+                        .map(|status| json_str(status, "name"))
+                        .unwrap_or_default();
+                    vec![
+                        key.clone(),
+                        json_str(&fields, "summary"),
+                        status,
+                        format!("{origin}/browse/{key}"),
+                    ]
+                })
+                .collect();
+            maybe_print_rows(ctx, fmt, &["Key", "Summary", "Status", "URL"], rows);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "write")]
+    #[test]
+    fn issue_keys_are_normalized_and_validated() {
+        assert_eq!(normalize_issue_key(" ops-123 ").unwrap(), "OPS-123");
+        assert_eq!(normalize_issue_key("A2_B-7").unwrap(), "A2_B-7");
+        assert!(normalize_issue_key("OPS").is_err());
+        assert!(normalize_issue_key("OPS-0").is_err());
+        assert!(normalize_issue_key("https://x.atlassian.net/browse/OPS-1").is_err());
+    }
+
+    #[test]
+    fn links_search_goes_through_the_gateway() {
+        let (v1, v2) = confcli::config::gateway_api_bases("abc");
+        let client = ApiClient::new(
+            "https://example.atlassian.net/wiki".to_string(),
+            v1,
+            v2,
+            confcli::auth::AuthMethod::Bearer {
+                token: "test".to_string(),
+            },
+            0,
+        )
+        .unwrap();
+        let url = links_search_url(&client, "issue = OPS-1", Some("next")).unwrap();
+        assert!(
+            url.starts_with("https://api.atlassian.com/ex/jira/abc/rest/api/3/search/jql?"),
+            "{url}"
+        );
+        assert!(url.contains("nextPageToken=next"), "{url}");
+        // The remote link still points at the page on the site itself.
+        assert_eq!(
+            page_global_id(&client, "42"),
+            "https://example.atlassian.net/wiki/pages/viewpage.action?pageId=42"
+        );
+    }
+
+    #[test]
+    fn links_jql_quotes_the_global_id() {
+        assert_eq!(
+            links_jql("https://x.test/wiki/pages/viewpage.action?pageId=42"),
+            "issue in issuesWithRemoteLinksByGlobalId(\"https://x.test/wiki/pages/viewpage.action?pageId=42\") ORDER BY key"
+        );
+    }
+}
@@ -9,12 +9,24 @@ use serde_json::Value;
 use serde_json::json;
 
 use crate::cli::*;
+use crate::commands::question::display_type;
 use crate::context::AppContext;
 #[cfg(feature = "write")]
 use crate::helpers::print_line;
-use crate::helpers::{maybe_print_json, maybe_print_rows, url_with_query};
+use crate::helpers::{
+    add_web_urls, escape_cql_text, maybe_print_json, maybe_print_rows, url_with_query,
+    with_url_column,
+};
+#[cfg(feature = "write")]
+use crate::plan::{self, Operation};
+#[cfg(feature = "write")]
+use crate::progress::Progress;
 use crate::resolve::resolve_page_id;
 
+/// Pages changed at once by `remove` and `propagate`, without `--concurrency`.
+#[cfg(feature = "write")]
+const CONCURRENCY: usize = 4;
+
 pub async fn handle(ctx: &AppContext, cmd: LabelCommand) -> Result<()> {
     let client = crate::context::load_client(ctx)?;
     match cmd {
@@ -23,6 +35,8 @@ pub async fn handle(ctx: &AppContext, cmd: LabelCommand) -> Result<()> {
         LabelCommand::Add(args) => label_add(&client, ctx, args).await,
         #[cfg(feature = "write")]
         LabelCommand::Remove(args) => label_remove(&client, ctx, args).await,
+        #[cfg(feature = "write")]
+        LabelCommand::Propagate(args) => label_propagate(&client, ctx, args).await,
         LabelCommand::Pages(args) => label_pages(&client, ctx, args).await,
     }
 }
@@ -85,6 +99,9 @@ async fn label_add(client: &ApiClient, ctx: &AppContext, args: LabelAddArgs) ->
     let page_id = resolve_page_id(client, &args.page).await?;
 
     if ctx.dry_run {
+        plan::record(
+            Operation::new("add", "label", &page_id).with_payload(json!({ "labels": args.labels })),
+        );
         let names = args.labels.join(", ");
         print_line(
             ctx,
@@ -112,11 +129,13 @@ async fn label_add(client: &ApiClient, ctx: &AppContext, args: LabelAddArgs) ->
 
 #[cfg(feature = "write")]
 async fn label_remove(client: &ApiClient, ctx: &AppContext, args: LabelRemoveArgs) -> Result<()> {
-    const REMOVE_CONCURRENCY: usize = 4;
-
     let page_id = resolve_page_id(client, &args.page).await?;
 
     if ctx.dry_run {
+        plan::record(
+            Operation::new("remove", "label", &page_id)
+                .with_payload(json!({ "labels": args.labels })),
+        );
         let names = args.labels.join(", ");
         print_line(
             ctx,
@@ -140,7 +159,7 @@ async fn label_remove(client: &ApiClient, ctx: &AppContext, args: LabelRemoveArg
                 (label, res)
             }
         })
-        .buffer_unordered(REMOVE_CONCURRENCY);
+        .buffer_unordered(ctx.workers(CONCURRENCY));
 
     let mut failures: Vec<String> = Vec::new();
     while let Some((label, result)) = stream.next().await {
@@ -166,30 +185,242 @@ async fn label_remove(client: &ApiClient, ctx: &AppContext, args: LabelRemoveArg
     Ok(())
 }
 
+/// A page below the propagation root and the labels it lacks.
+#[cfg(feature = "write")]
+struct PropagateTarget {
+    id: String,
+    title: String,
+    missing: Vec<String>,
+}
+
+#[cfg(feature = "write")]
+async fn label_propagate(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: LabelPropagateArgs,
+) -> Result<()> {
+    let page_id = resolve_page_id(client, &args.page).await?;
+    let mut labels: Vec<String> = if args.labels.is_empty() {
+        page_labels(client, &page_id).await?
+    } else {
+        args.labels
+            .iter()
+            .map(|label| label.trim().to_lowercase())
+            .filter(|label| !label.is_empty())
+            .collect()
+    };
+    labels.sort();
+    labels.dedup();
+    if labels.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Page {page_id} has no labels to propagate; pass --labels"
+        ));
+    }
+
+    let descendants = labeled_descendants(client, &page_id).await?;
+    let total = descendants.len();
+    let targets: Vec<PropagateTarget> = descendants
+        .iter()
+        .filter_map(|item| {
+            let present: Vec<String> = global_label_names(item)
+                .iter()
+                .map(|name| name.to_lowercase())
+                .collect();
+            let missing: Vec<String> = labels
+                .iter()
+                .filter(|label| !present.contains(label))
+                .cloned()
+                .collect();
+            (!missing.is_empty()).then(|| PropagateTarget {
+                id: json_str(item, "id"),
+                title: json_str(item, "title"),
+                missing,
+            })
+        })
+        .collect();
+    let skipped = total - targets.len();
+
+    let outcomes: Vec<Result<()>> = if ctx.dry_run {
+        for target in &targets {
+            plan::record(
+                Operation::new("add", "label", &target.id)
+                    .with_payload(json!({ "title": target.title, "labels": target.missing })),
+            );
+        }
+        targets.iter().map(|_| Ok(())).collect()
+    } else {
+        let progress = Progress::items(ctx, "pages", targets.len() as u64);
+        let outcomes = stream::iter(&targets)
+            .map(|target| async {
+                let body: Value = target
+                    .missing
+                    .iter()
+                    .map(|name| json!({ "prefix": "global", "name": name }))
+                    .collect::<Vec<_>>()
+                    .into();
+                let url = client.v1_url(&format!("/content/{}/label", target.id));
+                let result = client.post_json(url, body).await.map(|_| ());
+                if let Some(progress) = &progress {
+                    progress.inc(1);
+                }
+                result
+            })
+            .buffered(ctx.workers(CONCURRENCY))
+            .collect()
+            .await;
+        if let Some(progress) = progress {
+            progress.finish();
+        }
+        outcomes
+    };
+
+    let status = |outcome: &Result<()>| match outcome {
+        Ok(()) if ctx.dry_run => "would add".to_string(),
+        Ok(()) => "added".to_string(),
+        Err(err) => format!("failed: {err:#}"),
+    };
+    let failed = outcomes.iter().filter(|outcome| outcome.is_err()).count();
+    let labeled = targets.len() - failed;
+    match args.output {
+        OutputFormat::Json => {
+            let pages: Vec<Value> = targets
+                .iter()
+                .zip(&outcomes)
+                .map(|(target, outcome)| {
+                    json!({
+                        "id": target.id,
+                        "title": target.title,
+                        "added": target.missing,
+                        "status": status(outcome),
+                    })
+                })
+                .collect();
+            maybe_print_json(
+                ctx,
+                &json!({
+                    "page": page_id,
+                    "labels": labels,
+                    "descendants": total,
+                    "labeled": labeled,
+                    "skipped": skipped,
+                    "failed": failed,
+                    "pages": pages,
+                }),
+            )?;
+        }
+        fmt => {
+            let rows: Vec<Vec<String>> = targets
+                .iter()
+                .zip(&outcomes)
+                .map(|(target, outcome)| {
+                    vec![
+                        target.id.clone(),
+                        target.title.clone(),
+                        target.missing.join(", "),
+                        status(outcome),
+                    ]
+                })
+                .collect();
+            if !rows.is_empty() {
+                maybe_print_rows(ctx, fmt, &["ID", "Title", "Labels", "Status"], rows);
+            }
+            let verb = if ctx.dry_run {
+                "Would label"
+            } else {
+                "Labeled"
+            };
+            print_line(
+                ctx,
+                &format!(
+                    "{verb} {labeled} of {total} page(s) below {page_id}; {skipped} already had the labels; {failed} failed"
+                ),
+            );
+        }
+    }
+    if failed > 0 {
+        return Err(anyhow::anyhow!("Failed to label {failed} page(s)"));
+    }
+    Ok(())
+}
+
+/// The global labels on `page_id`, lowercased.
+#[cfg(feature = "write")]
+pub(crate) async fn page_labels(client: &ApiClient, page_id: &str) -> Result<Vec<String>> {
+    let url = url_with_query(
+        &client.v1_url(&format!("/content/{page_id}/label")),
+        &[
+            ("prefix", "global".to_string()),
+            ("limit", "200".to_string()),
+        ],
+    )?;
+    let items = client.get_paginated_results(url, true).await?;
+    Ok(items
+        .iter()
+        .map(|item| json_str(item, "name").to_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect())
+}
+
+/// Every page below `page_id` with its labels, via one paginated content search.
+#[cfg(feature = "write")]
+async fn labeled_descendants(client: &ApiClient, page_id: &str) -> Result<Vec<Value>> {
+    let url = url_with_query(
+        &client.v1_url("/content/search"),
+        &[
+            ("cql", format!("ancestor = {page_id} AND type = page")),
+            ("expand", "metadata.labels".to_string()),
+            ("limit", "100".to_string()),
+        ],
+    )?;
+    client.get_paginated_results(url, true).await
+}
+
+/// The global (unprefixed) label names of a content item expanded with `metadata.labels`.
+#[cfg(feature = "write")]
+pub(crate) fn global_label_names(item: &Value) -> Vec<String> {
+    item.pointer("/metadata/labels/results")
+        .and_then(|v| v.as_array())
+        .map(|labels| {
+            labels
+                .iter()
+                .filter(|label| {
+                    matches!(
+                        label.get("prefix").and_then(|v| v.as_str()),
+                        None | Some("global")
+                    )
+                })
+                .map(|label| json_str(label, "name"))
+                .filter(|name| !name.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 async fn label_pages(client: &ApiClient, ctx: &AppContext, args: LabelPagesArgs) -> Result<()> {
     let cql = label_cql(&args.label);
     let url = url_with_query(
         &client.v1_url("/search"),
         &[("cql", cql), ("limit", args.limit.to_string())],
     )?;
-    let results = client.get_paginated_results(url, args.all).await?;
+    let mut results = client.get_paginated_results(url, args.all).await?;
+    add_web_urls(client.base_url(), &mut results);
     match args.output {
         OutputFormat::Json => maybe_print_json(ctx, &results),
         fmt => {
             let rows = results.iter().map(label_result_row).collect();
-            maybe_print_rows(ctx, fmt, &["ID", "Type", "Title"], rows);
+            let (headers, rows) = with_url_column(
+                args.show_url,
+                client.base_url(),
+                &["ID", "Type", "Title"],
+                &results,
+                rows,
+            );
+            maybe_print_rows(ctx, fmt, &headers, rows);
             Ok(())
         }
     }
 }
 
-fn escape_cql_text(value: &str) -> String {
-    value
-        .replace('\\', "\\\\")
-        .replace('"', "\\\"")
-        .replace(['\n', '\r', '\t'], " ")
-}
-
 fn label_cql(label: &str) -> String {
     let label = escape_cql_text(label);
     if label.contains(':') {
@@ -202,7 +433,7 @@ fn label_cql(label: &str) -> String {
 fn label_result_row(item: &Value) -> Vec<String> {
     if let Some(content) = item.get("content") {
         let id = json_str(content, "id");
-        let typ = json_str(content, "type");
+        let typ = display_type(&json_str(content, "type")).to_string();
         let title = json_str(content, "title");
         return vec![id, typ, title];
     }
@@ -231,3 +462,22 @@ fn label_result_row(item: &Value) -> Vec<String> {
     let title = json_str(item, "title");
     vec![id, typ, title]
 }
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn global_label_names_skip_personal_and_team_labels() {
+        let item = json!({
+            "metadata": { "labels": { "results": [
+                { "prefix": "global", "name": "pci" },
+                { "prefix": "my", "name": "todo" },
+                { "name": "payments" },
+                { "prefix": "team", "name": "ops" },
+            ] } }
+        });
+        assert_eq!(global_label_names(&item), ["pci", "payments"]);
+        assert!(global_label_names(&json!({ "id": "1" })).is_empty());
+    }
+}
@@ -1,11 +1,31 @@
+#[cfg(feature = "write")]
+pub mod apply;
 pub mod attachment;
 pub mod auth;
+pub mod check_titles;
 pub mod comment;
+pub mod doctor;
+pub mod dump;
 pub mod export;
+pub mod favorite;
+pub mod generate;
+pub mod goto;
+pub mod graph;
+pub mod jira;
 pub mod label;
 pub mod page;
+pub mod question;
+pub mod recent;
+pub mod report;
+pub mod resolve;
+pub mod schema;
 pub mod search;
+pub mod sitemap;
 pub mod space;
+pub mod template;
 
 #[cfg(feature = "write")]
 pub mod copy_tree;
+
+#[cfg(feature = "write")]
+pub mod rollback;
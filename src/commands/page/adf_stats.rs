@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use confcli::client::ApiClient;
+use confcli::output::OutputFormat;
+use serde_json::{Value, json};
+
+use crate::cli::PageAdfStatsArgs;
+use crate::context::AppContext;
+use crate::helpers::*;
+use crate::resolve::*;
+
+/// Node types defined by the ADF schema; anything else is reported as unknown.
+const KNOWN_NODES: &[&str] = &[
+    "blockCard",
+    "blockquote",
+    "bodiedExtension",
+    "bulletList",
+    "caption",
+    "codeBlock",
+    "date",
+    "decisionItem",
+    "decisionList",
+    "doc",
+    "embedCard",
+    "emoji",
+    "expand",
+    "extension",
+    "extensionFrame",
+    "hardBreak",
+    "heading",
+    "inlineCard",
+    "inlineExtension",
+    "layoutColumn",
+    "layoutSection",
+    "listItem",
+    "media",
+    "mediaGroup",
+    "mediaInline",
+    "mediaSingle",
+    "mention",
+    "multiBodiedExtension",
+    "nestedExpand",
+    "orderedList",
+    "panel",
+    "paragraph",
+    "placeholder",
+    "rule",
+    "status",
+    "table",
+    "tableCell",
+    "tableHeader",
+    "tableRow",
+    "taskItem",
+    "taskList",
+    "text",
+];
+
+const EXTENSION_NODES: &[&str] = &[
+    "extension",
+    "inlineExtension",
+    "bodiedExtension",
+    "multiBodiedExtension",
+];
+
+/// Node counts and shape of an ADF document.
+#[derive(Debug, Default, PartialEq)]
+struct AdfStats {
+    nodes: usize,
+    /// Deepest nesting below `doc`, which sits at depth 0.
+    max_depth: usize,
+    types: BTreeMap<String, usize>,
+    /// Extension nodes by `extensionKey`.
+    extensions: BTreeMap<String, usize>,
+}
+
+impl AdfStats {
+    fn unknown(&self) -> Vec<&str> {
+        self.types
+            .keys()
+            .map(String::as_str)
+            .filter(|name| !KNOWN_NODES.contains(name))
+            .collect()
+    }
+}
+
+fn adf_stats(doc: &Value) -> AdfStats {
+    let mut stats = AdfStats::default();
+    // Walk with an explicit stack so pathological nesting cannot overflow ours.
+    let mut stack = vec![(doc, 0usize)];
+    while let Some((node, depth)) = stack.pop() {
+        let name = node
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or("(missing type)");
+        stats.nodes += 1;
+        stats.max_depth = stats.max_depth.max(depth);
+        *stats.types.entry(name.to_string()).or_default() += 1;
+        if EXTENSION_NODES.contains(&name) {
+            let key = node
+                .pointer("/attrs/extensionKey")
+                .and_then(Value::as_str)
+                .unwrap_or("(no key)");
+            *stats.extensions.entry(key.to_string()).or_default() += 1;
+        }
+        if let Some(children) = node.get("content").and_then(Value::as_array) {
+            stack.extend(children.iter().map(|child| (child, depth + 1)));
+        }
+    }
+    stats
+}
+
+pub(super) async fn page_adf_stats(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: PageAdfStatsArgs,
+) -> Result<()> {
+    let page_id = resolve_page_id(client, &args.page).await?;
+    let url = client.v2_url(&format!("/pages/{page_id}?body-format=atlas_doc_format"));
+    let (json, _) = client.get_json(url).await?;
+    let body = json
+        .pointer("/body/atlas_doc_format/value")
+        .and_then(Value::as_str)
+        .context("Missing ADF body content")?;
+    let doc: Value = serde_json::from_str(body).context("Page body is not valid ADF JSON")?;
+    let stats = adf_stats(&doc);
+
+    match args.output {
+        OutputFormat::Json => maybe_print_json(
+            ctx,
+            &json!({
+                "pageId": page_id,
+                "nodes": stats.nodes,
+                "maxDepth": stats.max_depth,
+                "types": stats.types,
+                "unknown": stats.unknown(),
+                "extensions": stats.extensions,
+            }),
+        ),
+        fmt => {
+            let unknown = stats.unknown();
+            let summary = vec![
+                vec!["Page".to_string(), page_id],
+                vec!["Nodes".to_string(), stats.nodes.to_string()],
+                vec!["Max depth".to_string(), stats.max_depth.to_string()],
+                vec![
+                    "Unknown".to_string(),
+                    if unknown.is_empty() {
+                        "none".to_string()
+                    } else {
+                        unknown.join(", ")
+                    },
+                ],
+            ];
+            maybe_print_kv_fmt(ctx, fmt, summary);
+
+            let mut types: Vec<_> = stats.types.iter().collect();
+            types.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+            let rows = types
+                .into_iter()
+                .map(|(name, count)| vec![name.clone(), count.to_string()])
+                .collect();
+            print_line(ctx, "");
+            maybe_print_rows(ctx, fmt, &["Node", "Count"], rows);
+
+            if !stats.extensions.is_empty() {
+                let rows = stats
+                    .extensions
+                    .iter()
+                    .map(|(key, count)| vec![key.clone(), count.to_string()])
+                    .collect();
+                print_line(ctx, "");
+                maybe_print_rows(ctx, fmt, &["Extension", "Count"], rows);
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_nodes_depth_extensions_and_unknown_types() {
+        let doc = json!({
+            "type": "doc",
+            "content": [
+                { "type": "paragraph", "content": [{ "type": "text", "text": "hi" }] },
+                {
+                    "type": "bulletList",
+                    "content": [{
+                        "type": "listItem",
+                        "content": [{ "type": "paragraph", "content": [{ "type": "text", "text": "x" }] }]
+                    }]
+                },
+                { "type": "extension", "attrs": { "extensionKey": "toc" } },
+                { "type": "fancyWidget" }
+            ]
+        });
+        let stats = adf_stats(&doc);
+        assert_eq!(stats.nodes, 9);
+        assert_eq!(stats.max_depth, 4);
+        assert_eq!(stats.types["paragraph"], 2);
+        assert_eq!(stats.types["text"], 2);
+        assert_eq!(stats.extensions["toc"], 1);
+        assert_eq!(stats.unknown(), vec!["fancyWidget"]);
+    }
+}
@@ -0,0 +1,147 @@
+use anyhow::{Result, anyhow};
+use confcli::client::ApiClient;
+use serde_json::json;
+
+use crate::cli::PageExpireArgs;
+use crate::context::AppContext;
+use crate::helpers::*;
+use crate::lifecycle::*;
+use crate::plan::{self, Operation};
+use crate::resolve::*;
+
+pub(super) async fn page_expire(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: PageExpireArgs,
+) -> Result<()> {
+    let page_id = resolve_page_id(client, &args.page).await?;
+    let existing = read_expiry(client, &page_id).await?;
+
+    if args.clear {
+        return clear_expiry(client, ctx, &args, &page_id, existing).await;
+    }
+
+    let on = args
+        .on
+        .as_deref()
+        .ok_or_else(|| anyhow!("Provide --on YYYY-MM-DD or --clear"))?;
+    let expires_on = format_date(parse_date(on)?);
+    let action = ExpiryAction::parse(&args.action)?;
+    let action_text = action.to_string();
+    let value = expiry_value(&expires_on, &action);
+
+    let verb = if existing.is_some() { "Update" } else { "Set" };
+    let kv = |prefix: Vec<Vec<String>>| {
+        let mut rows = prefix;
+        rows.push(vec!["ID".to_string(), page_id.clone()]);
+        rows.push(vec!["ExpiresOn".to_string(), expires_on.clone()]);
+        rows.push(vec!["Action".to_string(), action_text.clone()]);
+        rows
+    };
+
+    if ctx.dry_run {
+        plan::record(
+            Operation::new("set", "expiry", &page_id)
+                .with_payload(json!({ "expiresOn": expires_on, "action": action_text })),
+        );
+        return print_write_action_result(
+            ctx,
+            args.output,
+            &format!(
+                "Would {} expiry of page {page_id} to {expires_on} ({action_text})",
+                verb.to_lowercase()
+            ),
+            &json!({
+                "dryRun": true,
+                "id": page_id,
+                "expiresOn": expires_on,
+                "action": action_text,
+            }),
+            kv(vec![vec!["DryRun".to_string(), "true".to_string()]]),
+        );
+    }
+
+    match &existing {
+        Some(expiry) => {
+            let url = client.v2_url(&format!(
+                "/pages/{page_id}/properties/{}",
+                expiry.property_id
+            ));
+            client
+                .put_json(
+                    url,
+                    json!({
+                        "key": EXPIRY_PROPERTY,
+                        "value": value,
+                        "version": { "number": expiry.version + 1 },
+                    }),
+                )
+                .await?;
+        }
+        None => {
+            let url = client.v2_url(&format!("/pages/{page_id}/properties"));
+            client
+                .post_json(url, json!({ "key": EXPIRY_PROPERTY, "value": value }))
+                .await?;
+        }
+    }
+    add_label(client, &page_id, EXPIRY_LABEL).await?;
+
+    print_write_action_result(
+        ctx,
+        args.output,
+        &format!("{verb} expiry of page {page_id}: {expires_on} ({action_text})"),
+        &json!({
+            "id": page_id,
+            "expiresOn": expires_on,
+            "action": action_text,
+        }),
+        kv(Vec::new()),
+    )
+}
+
+async fn clear_expiry(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: &PageExpireArgs,
+    page_id: &str,
+    existing: Option<Expiry>,
+) -> Result<()> {
+    let Some(expiry) = existing else {
+        print_line(ctx, &format!("Page {page_id} has no expiry."));
+        return Ok(());
+    };
+
+    if ctx.dry_run {
+        plan::record(Operation::new("clear", "expiry", page_id));
+        return print_write_action_result(
+            ctx,
+            args.output,
+            &format!("Would clear expiry of page {page_id}"),
+            &json!({ "dryRun": true, "id": page_id, "cleared": true }),
+            vec![
+                vec!["DryRun".to_string(), "true".to_string()],
+                vec!["ID".to_string(), page_id.to_string()],
+            ],
+        );
+    }
+
+    let url = client.v2_url(&format!(
+        "/pages/{page_id}/properties/{}",
+        expiry.property_id
+    ));
+    client.delete(url).await?;
+    let url = client.v1_url(&format!(
+        "/content/{page_id}/label?name={}&prefix=global",
+        urlencoding::encode(EXPIRY_LABEL)
+    ));
+    client.delete(url).await?;
+
+    print_write_action_result(
+        ctx,
+        args.output,
+        &format!("Cleared expiry of page {page_id}"),
+        &json!({ "id": page_id, "cleared": true }),
+        vec![vec!["ID".to_string(), page_id.to_string()]],
+    )
+}
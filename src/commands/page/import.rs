@@ -0,0 +1,214 @@
+//! `page create --from-url`: fetch an external article, keep its main content, and turn it
+//! into a storage body that cites the source.
+//!
+//! The readability pass is regex-based like the rest of the HTML handling here: it keeps the
+//! first `<article>` (or `<main>`, or `<body>`) and drops page chrome inside it. Good enough
+//! for blog posts and docs pages; not a full reader-mode implementation.
+
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use confcli::client::ApiClient;
+use confcli::markdown::{decode_html_entities, escape_xml, html_to_markdown, markdown_to_storage};
+use confcli::sanitize::{HtmlSanitize, sanitize_html};
+use regex::{Captures, Regex};
+use url::Url;
+
+/// Time allowed for fetching the whole article.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+/// Larger pages are not articles worth importing.
+const MAX_ARTICLE_BYTES: usize = 10 * 1024 * 1024;
+/// Link schemes kept in imported content; anything else (`javascript:`, `data:`, ...) goes.
+const LINK_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// An external page reduced to its title and a Confluence storage body.
+pub(super) struct ImportedArticle {
+    pub(super) title: Option<String>,
+    pub(super) storage: String,
+}
+
+/// Elements that are page chrome or not content at all.
+const CHROME_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "nav", "header", "footer", "aside", "form",
+    "button", "iframe", "svg",
+];
+
+static CHROME_RES: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    CHROME_TAGS
+        .iter()
+        .map(|tag| Regex::new(&format!(r"(?is)<{tag}\b[^>]*>.*?</{tag}\s*>")).expect("CHROME_RE"))
+        .collect()
+});
+static COMMENT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<!--.*?-->").expect("COMMENT_RE"));
+static ARTICLE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<article\b[^>]*>(.*)</article\s*>").expect("ARTICLE_RE"));
+static MAIN_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<main\b[^>]*>(.*)</main\s*>").expect("MAIN_RE"));
+static BODY_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<body\b[^>]*>(.*)</body\s*>").expect("BODY_RE"));
+static OG_TITLE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?is)<meta\b[^>]*property\s*=\s*["']og:title["'][^>]*content\s*=\s*"([^"]*)""#)
+        .expect("OG_TITLE_RE")
+});
+static TITLE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<title\b[^>]*>(.*?)</title\s*>").expect("TITLE_RE"));
+static LINK_ATTR_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)\b(href|src)\s*=\s*"([^"]*)""#).expect("LINK_ATTR_RE"));
+
+/// Fetch `url` without Confluence credentials and convert its main content to storage.
+///
+/// The fetch uses its own HTTP client: the API client's carries the site's credentials and
+/// TLS client certificate, which must not be offered to an arbitrary host.
+pub(super) async fn fetch_article(client: &ApiClient, url: &str) -> Result<ImportedArticle> {
+    let source = Url::parse(url).with_context(|| format!("Invalid --from-url '{url}'"))?;
+    if !matches!(source.scheme(), "http" | "https") {
+        bail!("--from-url needs an http or https URL, not '{url}'");
+    }
+    let http = reqwest::Client::builder()
+        .user_agent(client.user_agent())
+        .timeout(FETCH_TIMEOUT)
+        .build()?;
+    let mut response = http
+        .get(source.clone())
+        .header(reqwest::header::ACCEPT, "text/html,application/xhtml+xml")
+        .send()
+        .await
+        .with_context(|| format!("Failed to fetch {url}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!("Fetching {url} failed ({status})");
+    }
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("")
+        .to_string();
+    if !content_type.is_empty() && !content_type.contains("html") {
+        bail!("{url} is {content_type}, not an HTML page");
+    }
+    let too_large = || anyhow::anyhow!("{url} is larger than {MAX_ARTICLE_BYTES} bytes");
+    if response
+        .content_length()
+        .is_some_and(|len| len > MAX_ARTICLE_BYTES as u64)
+    {
+        return Err(too_large());
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .with_context(|| format!("Failed to read {url}"))?
+    {
+        if body.len() + chunk.len() > MAX_ARTICLE_BYTES {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    let html = String::from_utf8_lossy(&body);
+
+    let (title, content) = readable_html(&html, &source);
+    let markdown = html_to_markdown(&content, "")?;
+    let storage = format!(
+        "{}{}",
+        source_note(source.as_str()),
+        markdown_to_storage(&markdown)
+    );
+    Ok(ImportedArticle { title, storage })
+}
+
+/// The page title and the main content of `html`, with chrome and active content removed and
+/// links made absolute against `source`; links to other schemes than [`LINK_SCHEMES`] are
+/// dropped.
+fn readable_html(html: &str, source: &Url) -> (Option<String>, String) {
+    let title = OG_TITLE_RE
+        .captures(html)
+        .or_else(|| TITLE_RE.captures(html))
+        .map(|caps| decode_html_entities(caps[1].trim()))
+        .filter(|title| !title.is_empty());
+
+    let html = COMMENT_RE.replace_all(html, "");
+    let mut content = [&*ARTICLE_RE, &*MAIN_RE, &*BODY_RE]
+        .iter()
+        .find_map(|re| re.captures(&html).map(|caps| caps[1].to_string()))
+        .unwrap_or_else(|| html.to_string());
+    for re in CHROME_RES.iter() {
+        content = re.replace_all(&content, "").into_owned();
+    }
+    // Rebuilt HTML quotes every attribute, so the link rewrite below sees all of them.
+    let content = sanitize_html(&content, HtmlSanitize::Strict);
+    let content = LINK_ATTR_RE
+        .replace_all(&content, |caps: &Captures| {
+            let target = decode_html_entities(&caps[2]);
+            if target.starts_with('#') {
+                return caps[0].to_string();
+            }
+            match source.join(&target) {
+                Ok(absolute) if LINK_SCHEMES.contains(&absolute.scheme()) => {
+                    format!("{}=\"{}\"", &caps[1], absolute.as_str().replace('"', "%22"))
+                }
+                _ => String::new(),
+            }
+        })
+        .into_owned();
+    (title, content)
+}
+
+/// The citation paragraph that opens an imported page.
+fn source_note(url: &str) -> String {
+    let url = escape_xml(url);
+    let date = &crate::lifecycle::utc_timestamp()[..10];
+    format!("<p><em>Imported from <a href=\"{url}\">{url}</a> on {date}.</em></p>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_article_and_drops_chrome() {
+        let html = r##"<html><head><title>Fallback</title>
+            <meta property="og:title" content="Tuning Kafka &amp; friends"></head>
+            <body><nav><a href="/">Home</a></nav>
+            <article><header>By someone</header><h1>Tuning</h1>
+            <p>See <a href="../guide?x=1&amp;y=2">the guide</a> and <a href="#part">below</a>.</p>
+            <script>track()</script><img src="img/chart.png"></article>
+            <footer>(c) 2024</footer></body></html>"##;
+        let source = Url::parse("https://example.com/blog/posts/kafka").unwrap();
+        let (title, content) = readable_html(html, &source);
+        assert_eq!(title.as_deref(), Some("Tuning Kafka & friends"));
+        assert!(content.contains("<h1>Tuning</h1>"));
+        assert!(content.contains(r#"href="https://example.com/blog/guide?x=1&y=2""#));
+        assert!(content.contains(r##"href="#part""##));
+        assert!(content.contains(r#"src="https://example.com/blog/posts/img/chart.png""#));
+        for chrome in ["Home", "By someone", "track()", "(c) 2024"] {
+            assert!(!content.contains(chrome), "{chrome} should be dropped");
+        }
+    }
+
+    #[test]
+    fn drops_links_to_other_schemes() {
+        let source = Url::parse("https://example.com/").unwrap();
+        let (_, content) = readable_html(
+            concat!(
+                "<body><a href='javascript:alert(1)'>a</a> <a href=\"data:text/html,x\">b</a> ",
+                "<a href=\"mailto:x@example.com\">c</a> <a href=\"&#106;avascript:x\">d</a></body>",
+            ),
+            &source,
+        );
+        assert!(!content.contains("javascript"), "{content}");
+        assert!(!content.contains("data:"), "{content}");
+        assert!(content.contains(r#"href="mailto:x@example.com""#));
+    }
+
+    #[test]
+    fn falls_back_to_title_and_body() {
+        let source = Url::parse("https://example.com/").unwrap();
+        let (title, content) =
+            readable_html("<title> Notes </title><body><p>Hi</p></body>", &source);
+        assert_eq!(title.as_deref(), Some("Notes"));
+        assert_eq!(content, "<p>Hi</p>");
+    }
+}
@@ -0,0 +1,96 @@
+use anyhow::Result;
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::output::OutputFormat;
+#[cfg(feature = "write")]
+use serde_json::json;
+
+#[cfg(feature = "write")]
+use crate::cli::PageLikeArgs;
+use crate::cli::PageLikesArgs;
+use crate::context::AppContext;
+use crate::helpers::*;
+#[cfg(feature = "write")]
+use crate::plan::{self, Operation};
+use crate::resolve::*;
+
+pub(super) async fn page_likes(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: PageLikesArgs,
+) -> Result<()> {
+    let page_id = resolve_page_id(client, &args.page).await?;
+    let url = url_with_query(
+        &client.v2_url(&format!("/pages/{page_id}/likes/users")),
+        &[("limit", args.limit.to_string())],
+    )?;
+    let items = client.get_paginated_results(url, args.all).await?;
+
+    match args.output {
+        OutputFormat::Json => maybe_print_json(ctx, &items),
+        fmt => {
+            let mut rows = Vec::with_capacity(items.len());
+            for item in &items {
+                let account_id = json_str(item, "accountId");
+                let name = resolve_user_display_name(client, &account_id)
+                    .await
+                    .unwrap_or_else(|_| account_id.clone());
+                rows.push(vec![account_id, name]);
+            }
+            maybe_print_rows(ctx, fmt, &["AccountId", "Name"], rows);
+            Ok(())
+        }
+    }
+}
+
+/// Like or unlike a page as the authenticated user.
+///
+/// Neither REST v1 nor v2 exposes a write endpoint for likes, so this goes through the
+/// `likes/1.0` API that the Confluence web UI uses.
+#[cfg(feature = "write")]
+pub(super) async fn page_like(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: PageLikeArgs,
+    like: bool,
+) -> Result<()> {
+    let page_id = resolve_page_id(client, &args.page).await?;
+    let action = if like { "like" } else { "unlike" };
+
+    if ctx.dry_run {
+        plan::record(Operation::new(action, "page", &page_id));
+        return print_write_action_result(
+            ctx,
+            args.output,
+            &format!("Would {action} page {page_id}"),
+            &json!({ "dryRun": true, "action": action, "id": page_id }),
+            vec![
+                vec!["DryRun".to_string(), "true".to_string()],
+                vec!["Action".to_string(), action.to_string()],
+                vec!["ID".to_string(), page_id.clone()],
+            ],
+        );
+    }
+
+    let url = format!(
+        "{}/rest/likes/1.0/content/{page_id}/likes",
+        client.api_site_url()
+    );
+    if like {
+        client.post_json_no_content(url, json!({})).await?;
+    } else {
+        client.delete(url).await?;
+    }
+
+    let past = if like { "Liked" } else { "Unliked" };
+    print_write_action_result(
+        ctx,
+        args.output,
+        &format!("{past} page {page_id}"),
+        &json!({ "action": action, "id": page_id }),
+        vec![
+            vec!["Action".to_string(), action.to_string()],
+            vec!["ID".to_string(), page_id],
+        ],
+    )
+}
@@ -4,12 +4,15 @@ use confcli::json_util::json_str;
 use confcli::markdown::{
     MarkdownOptions, decode_unicode_escapes_str, html_to_markdown_with_options,
 };
-use confcli::output::OutputFormat;
+use confcli::output::{OutputFormat, emit_line};
+use confcli::sanitize::{HtmlSanitize, sanitize_html};
 
+use crate::activity::{add_comment_activity, with_activity_columns};
 use crate::cli::{PageBodyArgs, PageGetArgs, PageListArgs};
 use crate::context::AppContext;
 use crate::helpers::*;
 use crate::resolve::*;
+use crate::smart_links::unfurl_smart_links;
 
 pub(super) async fn page_list(
     client: &ApiClient,
@@ -28,7 +31,14 @@ pub(super) async fn page_list(
         pairs.push(("title", title));
     }
     let url = url_with_query(&client.v2_url("/pages"), &pairs)?;
-    let items = client.get_paginated_results(url, args.all).await?;
+    let mut items = match &args.under {
+        Some(page) => list_pages_under(client, page, url).await?,
+        None => client.get_paginated_results(url, args.all).await?,
+    };
+    add_web_urls(client.base_url(), &mut items);
+    if args.show_activity {
+        add_comment_activity(client, &mut items).await?;
+    }
     match args.output {
         OutputFormat::Json => maybe_print_json(ctx, &items),
         fmt => {
@@ -57,7 +67,15 @@ pub(super) async fn page_list(
                     ]
                 })
                 .collect();
-            maybe_print_rows(ctx, fmt, &["ID", "Title", "Space", "Status"], rows);
+            let (headers, rows) = with_activity_columns(
+                args.show_activity,
+                &["ID", "Title", "Space", "Status"],
+                &items,
+                rows,
+            );
+            let (headers, rows) =
+                with_url_column(args.show_url, client.base_url(), &headers, &items, rows);
+            maybe_print_rows(ctx, fmt, &headers, rows);
             Ok(())
         }
     }
@@ -69,6 +87,21 @@ pub(super) async fn page_get(
     args: PageGetArgs,
 ) -> Result<()> {
     let page_id = resolve_page_id(client, &args.page).await?;
+    let markdown_options = MarkdownOptions {
+        keep_empty_list_items: args.keep_empty_list_items,
+        strip_emoji: args.strip_emoji,
+        source_link: args.source_link,
+        table_mode: args.table_mode,
+        captions: args.captions,
+        expand_details: args.expand_as_details,
+        flavor: args.flavor,
+    };
+    if args.include_headers && args.output != OutputFormat::Json {
+        return Err(anyhow::anyhow!("--include-headers requires -o json"));
+    }
+    if args.clipboard && args.output != OutputFormat::Markdown {
+        return Err(anyhow::anyhow!("--clipboard requires -o markdown"));
+    }
 
     match args.output {
         OutputFormat::Json => {
@@ -79,7 +112,10 @@ pub(super) async fn page_get(
             if let Some(version) = args.version {
                 url.push_str(&format!("&version={version}"));
             }
-            let (json, _) = client.get_json(url).await?;
+            let (mut json, headers) = client.get_json(url).await?;
+            if args.include_headers {
+                add_response_meta(&mut json, &headers);
+            }
             maybe_print_json(ctx, &json)
         }
         OutputFormat::Table => {
@@ -146,20 +182,19 @@ pub(super) async fn page_get(
                 .and_then(|view| view.get("value"))
                 .and_then(|value| value.as_str())
                 .context("Missing view body content")?;
-            let markdown = html_to_markdown_with_options(
-                html,
-                client.base_url(),
-                MarkdownOptions {
-                    keep_empty_list_items: args.keep_empty_list_items,
-                },
-            )?;
+            let html = unfurl_smart_links(client, html).await;
+            let markdown =
+                html_to_markdown_with_options(&html, client.base_url(), markdown_options)?;
             let output = if ctx.quiet {
                 markdown
             } else {
-                add_markdown_header(client.base_url(), &view_json, &markdown)
+                add_markdown_header(client.base_url(), &view_json, &markdown, markdown_options)
             };
+            if args.clipboard {
+                return crate::clipboard::copy(&output);
+            }
             if !ctx.quiet {
-                println!("{output}");
+                emit_line(&output);
             }
             Ok(())
         }
@@ -172,44 +207,57 @@ pub(super) async fn page_body(
     args: PageBodyArgs,
 ) -> Result<()> {
     let page_id = resolve_page_id(client, &args.page).await?;
+    let markdown_options = MarkdownOptions {
+        keep_empty_list_items: args.keep_empty_list_items,
+        strip_emoji: args.strip_emoji,
+        source_link: args.source_link,
+        table_mode: args.table_mode,
+        captions: args.captions,
+        expand_details: args.expand_as_details,
+        flavor: args.flavor,
+    };
     let format = args.format.to_lowercase();
+    if args.sanitize != HtmlSanitize::None && format != "view" {
+        return Err(anyhow::anyhow!("--sanitize only applies to --format view"));
+    }
+    if args.clipboard && args.output == OutputFormat::Json {
+        return Err(anyhow::anyhow!(
+            "--clipboard cannot be combined with -o json"
+        ));
+    }
     let body_value: String = match format.as_str() {
         "markdown" | "md" => {
-            let url = client.v2_url(&format!("/pages/{page_id}?body-format=view"));
-            let (json, _) = client.get_json(url).await?;
+            let json =
+                crate::body_cache::page_with_body(client, &page_id, "view", args.cache).await?;
             let html = json
                 .get("body")
                 .and_then(|body| body.get("view"))
                 .and_then(|view| view.get("value"))
                 .and_then(|value| value.as_str())
                 .context("Missing view body content")?;
-            let markdown = html_to_markdown_with_options(
-                html,
-                client.base_url(),
-                MarkdownOptions {
-                    keep_empty_list_items: args.keep_empty_list_items,
-                },
-            )?;
+            let html = unfurl_smart_links(client, html).await;
+            let markdown =
+                html_to_markdown_with_options(&html, client.base_url(), markdown_options)?;
             if ctx.quiet {
                 markdown
             } else {
-                add_markdown_header(client.base_url(), &json, &markdown)
+                add_markdown_header(client.base_url(), &json, &markdown, markdown_options)
             }
         }
         "view" => {
-            let url = client.v2_url(&format!("/pages/{page_id}?body-format=view"));
-            let (json, _) = client.get_json(url).await?;
+            let json =
+                crate::body_cache::page_with_body(client, &page_id, "view", args.cache).await?;
             let html = json
                 .get("body")
                 .and_then(|body| body.get("view"))
                 .and_then(|view| view.get("value"))
                 .and_then(|value| value.as_str())
                 .context("Missing view body content")?;
-            decode_unicode_escapes_str(html)
+            sanitize_html(&decode_unicode_escapes_str(html), args.sanitize)
         }
         "storage" => {
-            let url = client.v2_url(&format!("/pages/{page_id}?body-format=storage"));
-            let (json, _) = client.get_json(url).await?;
+            let json =
+                crate::body_cache::page_with_body(client, &page_id, "storage", args.cache).await?;
             json.get("body")
                 .and_then(|body| body.get("storage"))
                 .and_then(|storage| storage.get("value"))
@@ -218,8 +266,9 @@ pub(super) async fn page_body(
                 .to_string()
         }
         "atlas_doc_format" | "adf" => {
-            let url = client.v2_url(&format!("/pages/{page_id}?body-format=atlas_doc_format"));
-            let (json, _) = client.get_json(url).await?;
+            let json =
+                crate::body_cache::page_with_body(client, &page_id, "atlas_doc_format", args.cache)
+                    .await?;
             let body = json
                 .get("body")
                 .and_then(|body| body.get("atlas_doc_format"))
@@ -241,6 +290,9 @@ pub(super) async fn page_body(
         }
     };
 
+    if args.clipboard {
+        return crate::clipboard::copy(&body_value);
+    }
     match args.output {
         OutputFormat::Json => {
             let obj = serde_json::json!({
@@ -252,7 +304,7 @@ pub(super) async fn page_body(
         }
         _ => {
             if !ctx.quiet {
-                println!("{body_value}");
+                emit_line(&body_value);
             }
             Ok(())
         }
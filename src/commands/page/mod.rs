@@ -3,8 +3,22 @@ use anyhow::Result;
 use crate::cli::*;
 use crate::context::AppContext;
 
+mod adf_stats;
+#[cfg(feature = "write")]
+mod expire;
+#[cfg(feature = "write")]
+mod import;
+mod likes;
 mod listing;
 mod navigation;
+mod owner;
+#[cfg(feature = "write")]
+mod reorder;
+#[cfg(feature = "write")]
+mod status;
+mod table;
+#[cfg(feature = "write")]
+mod template;
 #[cfg(feature = "write")]
 mod write_ops;
 
@@ -15,6 +29,13 @@ pub async fn handle(ctx: &AppContext, cmd: PageCommand) -> Result<()> {
         PageCommand::Get(args) => listing::page_get(&client, ctx, args).await,
         PageCommand::Body(args) => listing::page_body(&client, ctx, args).await,
         #[cfg(feature = "write")]
+        PageCommand::Table(PageTableArgs {
+            command: Some(PageTableCommand::Update(args)),
+            ..
+        }) => table::page_table_update(&client, ctx, args).await,
+        PageCommand::Table(args) => table::page_table(&client, ctx, args).await,
+        PageCommand::AdfStats(args) => adf_stats::page_adf_stats(&client, ctx, args).await,
+        #[cfg(feature = "write")]
         PageCommand::Edit(args) => write_ops::page_edit(&client, ctx, args).await,
         #[cfg(feature = "write")]
         PageCommand::Create(args) => write_ops::page_create(&client, ctx, args).await,
@@ -23,7 +44,30 @@ pub async fn handle(ctx: &AppContext, cmd: PageCommand) -> Result<()> {
         #[cfg(feature = "write")]
         PageCommand::Delete(args) => write_ops::page_delete(&client, ctx, args).await,
         PageCommand::Children(args) => navigation::page_children(&client, ctx, args).await,
+        #[cfg(feature = "write")]
+        PageCommand::Reorder(args) => reorder::page_reorder(&client, ctx, args).await,
         PageCommand::History(args) => navigation::page_history(&client, ctx, args).await,
         PageCommand::Open(args) => navigation::page_open(&client, ctx, args).await,
+        PageCommand::Permalink(args) => navigation::page_permalink(&client, ctx, args).await,
+        #[cfg(feature = "write")]
+        PageCommand::Like(args) => likes::page_like(&client, ctx, args, true).await,
+        #[cfg(feature = "write")]
+        PageCommand::Unlike(args) => likes::page_like(&client, ctx, args, false).await,
+        PageCommand::Likes(args) => likes::page_likes(&client, ctx, args).await,
+        #[cfg(feature = "write")]
+        PageCommand::SaveAsTemplate(args) => {
+            template::page_save_as_template(&client, ctx, args).await
+        }
+        #[cfg(feature = "write")]
+        PageCommand::Expire(args) => expire::page_expire(&client, ctx, args).await,
+        #[cfg(feature = "write")]
+        PageCommand::SetStatus(args) => status::page_set_status(&client, ctx, args).await,
+        PageCommand::Owner(PageOwnerCommand::Get(args)) => {
+            owner::page_owner_get(&client, ctx, args).await
+        }
+        #[cfg(feature = "write")]
+        PageCommand::Owner(PageOwnerCommand::Set(args)) => {
+            owner::page_owner_set(&client, ctx, args).await
+        }
     }
 }
@@ -2,8 +2,11 @@ use anyhow::{Context, Result};
 use confcli::client::ApiClient;
 use confcli::json_util::json_str;
 use confcli::output::OutputFormat;
+use futures_util::stream::{self, StreamExt};
+use serde_json::{Value, json};
+use std::collections::HashMap;
 
-use crate::cli::{PageChildrenArgs, PageHistoryArgs, PageOpenArgs};
+use crate::cli::{ContentType, PageChildrenArgs, PageHistoryArgs, PageOpenArgs, PagePermalinkArgs};
 use crate::context::AppContext;
 use crate::helpers::*;
 use crate::resolve::*;
@@ -15,9 +18,11 @@ pub(super) async fn page_children(
 ) -> Result<()> {
     let page_id = resolve_page_id(client, &args.page).await?;
 
-    let items = if args.recursive {
+    // A type filter can drop any share of the results, so `-n` is applied after filtering.
+    let fetch_all = args.all || args.r#type != ContentType::All;
+    let mut items = if args.recursive {
         confcli::tree::fetch_descendants_via_direct_children(
-            client, &page_id, args.limit, args.all, None,
+            client, &page_id, args.limit, fetch_all, None,
         )
         .await?
     } else {
@@ -25,8 +30,12 @@ pub(super) async fn page_children(
             &client.v2_url(&format!("/pages/{page_id}/direct-children")),
             &[("limit", args.limit.to_string())],
         )?;
-        client.get_paginated_results(url, args.all).await?
+        client.get_paginated_results(url, fetch_all).await?
     };
+    retain_content_type(&mut items, "page", args.r#type);
+    if !args.all {
+        items.truncate(args.limit);
+    }
 
     match args.output {
         OutputFormat::Json => maybe_print_json(ctx, &items),
@@ -38,17 +47,31 @@ pub(super) async fn page_children(
                         vec![
                             json_str(item, "id"),
                             json_str(item, "title"),
+                            json_str(item, "type"),
                             json_str(item, "parentId"),
+                            json_str(item, "childPosition"),
                         ]
                     })
                     .collect();
-                maybe_print_rows(ctx, fmt, &["ID", "Title", "Parent"], rows);
+                maybe_print_rows(
+                    ctx,
+                    fmt,
+                    &["ID", "Title", "Type", "Parent", "Position"],
+                    rows,
+                );
             } else {
                 let rows = items
                     .iter()
-                    .map(|item| vec![json_str(item, "id"), json_str(item, "title")])
+                    .map(|item| {
+                        vec![
+                            json_str(item, "id"),
+                            json_str(item, "title"),
+                            json_str(item, "type"),
+                            json_str(item, "childPosition"),
+                        ]
+                    })
                     .collect();
-                maybe_print_rows(ctx, fmt, &["ID", "Title"], rows);
+                maybe_print_rows(ctx, fmt, &["ID", "Title", "Type", "Position"], rows);
             }
             Ok(())
         }
@@ -65,7 +88,17 @@ pub(super) async fn page_history(
         &client.v2_url(&format!("/pages/{page_id}/versions")),
         &[("limit", args.limit.to_string())],
     )?;
-    let items = client.get_paginated_results(url, false).await?;
+    let mut items = match args.max_results {
+        Some(max) => client.get_paginated_results_up_to(url, max).await?,
+        None => client.get_paginated_results(url, args.all).await?,
+    };
+    let names = author_names(client, &items).await;
+    for item in &mut items {
+        let name = names.get(&json_str(item, "authorId")).cloned();
+        if let (Some(name), Value::Object(map)) = (name, item) {
+            map.insert("authorName".to_string(), Value::String(name));
+        }
+    }
     match args.output {
         OutputFormat::Json => maybe_print_json(ctx, &items),
         fmt => {
@@ -84,15 +117,43 @@ pub(super) async fn page_history(
                         .map(|b| if b { "yes" } else { "no" })
                         .unwrap_or("")
                         .to_string();
-                    vec![number, message, created_at, minor_edit]
+                    let author = json_str(item, "authorName");
+                    vec![number, author, message, created_at, minor_edit]
                 })
                 .collect();
-            maybe_print_rows(ctx, fmt, &["Version", "Message", "Created", "Minor"], rows);
+            maybe_print_rows(
+                ctx,
+                fmt,
+                &["Version", "Author", "Message", "Created", "Minor"],
+                rows,
+            );
             Ok(())
         }
     }
 }
 
+/// Display names of the versions' authors, keyed by account id (the id itself when the
+/// user can't be looked up). Lookups are cached, so repeat authors cost nothing.
+async fn author_names(client: &ApiClient, items: &[Value]) -> HashMap<String, String> {
+    let mut ids: Vec<String> = items
+        .iter()
+        .map(|item| json_str(item, "authorId"))
+        .filter(|id| !id.is_empty())
+        .collect();
+    ids.sort();
+    ids.dedup();
+    stream::iter(ids)
+        .map(|id| async move {
+            let name = resolve_user_display_name(client, &id)
+                .await
+                .unwrap_or_else(|_| id.clone());
+            (id, name)
+        })
+        .buffer_unordered(4)
+        .collect()
+        .await
+}
+
 pub(super) async fn page_open(
     client: &ApiClient,
     ctx: &AppContext,
@@ -117,3 +178,84 @@ pub(super) async fn page_open(
     open_url(&full_url)?;
     Ok(())
 }
+
+pub(super) async fn page_permalink(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: PagePermalinkArgs,
+) -> Result<()> {
+    let page_id = resolve_page_id(client, &args.page).await?;
+    let mut url = client.v2_url(&format!("/pages/{page_id}"));
+    if let Some(version) = args.version {
+        url = url_with_query(&url, &[("version", version.to_string())])?;
+    }
+    // Fetching the version (rather than trusting `--version`) rejects ones that don't exist.
+    let (json, _) = client
+        .get_json(url)
+        .await
+        .with_context(|| match args.version {
+            Some(version) => format!("Failed to fetch version {version} of page {page_id}"),
+            None => format!("Failed to fetch page {page_id}"),
+        })?;
+    let version = json
+        .get("version")
+        .and_then(|v| v.get("number"))
+        .and_then(|v| v.as_i64())
+        .context("Missing version number for page")?;
+    let title = json_str(&json, "title");
+    let link = permalink(client.base_url(), &page_id, version);
+    let markdown = markdown_link(&title, version, &link);
+
+    match args.output {
+        Some(OutputFormat::Json) => maybe_print_json(
+            ctx,
+            &json!({
+                "id": page_id,
+                "title": title,
+                "version": version,
+                "url": link,
+                "markdown": markdown,
+            }),
+        ),
+        _ if args.markdown => {
+            print_line(ctx, &markdown);
+            Ok(())
+        }
+        _ => {
+            print_line(ctx, &link);
+            Ok(())
+        }
+    }
+}
+
+/// The id-based view URL pinned to `version`; it survives renames and moves.
+fn permalink(base_url: &str, page_id: &str, version: i64) -> String {
+    format!(
+        "{}/pages/viewpage.action?pageId={page_id}&pageVersion={version}",
+        base_url.trim_end_matches('/')
+    )
+}
+
+/// `[Title (v7)](url)`, with brackets in the title escaped.
+fn markdown_link(title: &str, version: i64, url: &str) -> String {
+    let title = title.replace('[', "\\[").replace(']', "\\]");
+    format!("[{title} (v{version})]({url})")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permalink_pins_the_version() {
+        let link = permalink("https://x.test/wiki/", "42", 7);
+        assert_eq!(
+            link,
+            "https://x.test/wiki/pages/viewpage.action?pageId=42&pageVersion=7"
+        );
+        assert_eq!(
+            markdown_link("Runbook [draft]", 7, &link),
+            "[Runbook \\[draft\\] (v7)](https://x.test/wiki/pages/viewpage.action?pageId=42&pageVersion=7)"
+        );
+    }
+}
@@ -0,0 +1,204 @@
+use anyhow::Result;
+#[cfg(feature = "write")]
+use anyhow::anyhow;
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::output::OutputFormat;
+#[cfg(feature = "write")]
+use futures_util::stream::{self, StreamExt};
+#[cfg(feature = "write")]
+use serde_json::json;
+
+use crate::cli::PageOwnerGetArgs;
+#[cfg(feature = "write")]
+use crate::cli::PageOwnerSetArgs;
+use crate::context::AppContext;
+use crate::helpers::*;
+#[cfg(feature = "write")]
+use crate::plan::{self, Operation};
+use crate::resolve::*;
+
+/// Ownership updates at once with `--cql`, without `--concurrency`.
+#[cfg(feature = "write")]
+const CONCURRENCY: usize = 4;
+
+pub(super) async fn page_owner_get(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: PageOwnerGetArgs,
+) -> Result<()> {
+    let page_id = resolve_page_id(client, &args.page).await?;
+    let url = client.v2_url(&format!("/pages/{page_id}"));
+    let (json, _) = client.get_json(url).await?;
+    let owner_id = json_str(&json, "ownerId");
+    let author_id = json_str(&json, "authorId");
+
+    match args.output {
+        OutputFormat::Json => maybe_print_json(
+            ctx,
+            &serde_json::json!({
+                "id": page_id,
+                "title": json_str(&json, "title"),
+                "ownerId": owner_id,
+                "authorId": author_id,
+            }),
+        ),
+        fmt => {
+            let owner_name = display_name_or_id(client, &owner_id).await;
+            let author_name = display_name_or_id(client, &author_id).await;
+            let rows = vec![
+                vec!["ID".to_string(), page_id],
+                vec!["Title".to_string(), json_str(&json, "title")],
+                vec!["Owner".to_string(), owner_name],
+                vec!["OwnerId".to_string(), owner_id],
+                vec!["Author".to_string(), author_name],
+            ];
+            maybe_print_kv_fmt(ctx, fmt, rows);
+            Ok(())
+        }
+    }
+}
+
+async fn display_name_or_id(client: &ApiClient, account_id: &str) -> String {
+    if account_id.is_empty() {
+        return String::new();
+    }
+    resolve_user_display_name(client, account_id)
+        .await
+        .unwrap_or_else(|_| account_id.to_string())
+}
+
+#[cfg(feature = "write")]
+pub(super) async fn page_owner_set(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: PageOwnerSetArgs,
+) -> Result<()> {
+    // With --cql the only positional is the new owner, which clap assigns to `page`.
+    let (page, user) = match (&args.cql, args.page, args.user) {
+        (Some(_), Some(user), None) => (None, user),
+        (Some(_), _, _) => {
+            return Err(anyhow!(
+                "With --cql, pass only the new owner: confcli page owner set --cql <query> <user>"
+            ));
+        }
+        (None, Some(page), Some(user)) => (Some(page), user),
+        (None, _, _) => {
+            return Err(anyhow!(
+                "Provide a page and a new owner: confcli page owner set <page> <user>"
+            ));
+        }
+    };
+
+    let owner_id = resolve_account_id(client, &user).await?;
+
+    let page_ids = match (&page, &args.cql) {
+        (Some(page), _) => vec![resolve_page_id(client, page).await?],
+        (None, Some(cql)) => pages_from_cql(client, cql).await?,
+        (None, None) => unreachable!("validated above"),
+    };
+
+    if page_ids.is_empty() {
+        print_line(ctx, "No pages matched.");
+        return Ok(());
+    }
+
+    if ctx.dry_run {
+        for page_id in &page_ids {
+            plan::record(
+                Operation::new("set", "owner", page_id)
+                    .with_payload(json!({ "ownerId": owner_id })),
+            );
+        }
+        let count = page_ids.len();
+        return print_write_action_result(
+            ctx,
+            args.output,
+            &format!("Would set owner of {count} page(s) to {owner_id}"),
+            &json!({
+                "dryRun": true,
+                "ownerId": owner_id,
+                "pages": page_ids,
+            }),
+            vec![
+                vec!["DryRun".to_string(), "true".to_string()],
+                vec!["Owner".to_string(), owner_id.clone()],
+                vec!["Pages".to_string(), count.to_string()],
+            ],
+        );
+    }
+
+    if args.cql.is_some()
+        && !args.yes
+        && !confirm(&format!(
+            "Set owner of {} page(s) to {owner_id}?",
+            page_ids.len()
+        ))?
+    {
+        print_line(ctx, "Cancelled.");
+        return Ok(());
+    }
+
+    let client = client.clone();
+    let mut stream = stream::iter(page_ids.iter().cloned())
+        .map(|page_id| {
+            let client = client.clone();
+            let owner_id = owner_id.clone();
+            async move {
+                let url = client.v2_url(&format!("/pages/{page_id}/owner"));
+                let res = client
+                    .put_json_no_content(url, json!({ "ownerId": owner_id }))
+                    .await;
+                (page_id, res)
+            }
+        })
+        .buffer_unordered(ctx.workers(CONCURRENCY));
+
+    let mut updated: Vec<String> = Vec::new();
+    let mut failures: Vec<String> = Vec::new();
+    while let Some((page_id, result)) = stream.next().await {
+        match result {
+            Ok(()) => updated.push(page_id),
+            Err(err) => failures.push(format!("{page_id}: {err:#}")),
+        }
+    }
+
+    if !failures.is_empty() {
+        return Err(anyhow!(
+            "Failed to change owner of {} page(s): {}",
+            failures.len(),
+            failures.join("; ")
+        ));
+    }
+
+    let count = updated.len();
+    print_write_action_result(
+        ctx,
+        args.output,
+        &format!("Set owner of {count} page(s) to {owner_id}"),
+        &json!({
+            "ownerId": owner_id,
+            "pages": updated,
+        }),
+        vec![
+            vec!["Owner".to_string(), owner_id.clone()],
+            vec!["Pages".to_string(), count.to_string()],
+        ],
+    )
+}
+
+/// Ids of the pages (not blog posts or other content) matched by a CQL query.
+#[cfg(feature = "write")]
+pub(super) async fn pages_from_cql(client: &ApiClient, cql: &str) -> Result<Vec<String>> {
+    let results = crate::commands::search::search_all(client, cql, 100).await?;
+    let mut ids: Vec<String> = results
+        .iter()
+        .filter_map(|item| item.get("content"))
+        .filter(|content| content.get("type").and_then(|v| v.as_str()) == Some("page"))
+        .map(|content| json_str(content, "id"))
+        .filter(|id| !id.is_empty())
+        .collect();
+    ids.sort();
+    ids.dedup();
+    Ok(ids)
+}
@@ -0,0 +1,161 @@
+use anyhow::{Result, anyhow};
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use serde_json::{Value, json};
+
+use crate::cli::PageReorderArgs;
+use crate::context::AppContext;
+use crate::helpers::*;
+use crate::plan::{self, Operation};
+use crate::resolve::*;
+
+/// Where to move a page relative to a sibling, as understood by the v1 move endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Placement {
+    Before,
+    After,
+}
+
+impl Placement {
+    fn as_str(self) -> &'static str {
+        match self {
+            Placement::Before => "before",
+            Placement::After => "after",
+        }
+    }
+}
+
+pub(super) async fn page_reorder(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: PageReorderArgs,
+) -> Result<()> {
+    let page_id = resolve_page_id(client, &args.page).await?;
+
+    let target = match (&args.before, &args.after, args.position) {
+        (Some(before), _, _) => Some((Placement::Before, resolve_page_id(client, before).await?)),
+        (_, Some(after), _) => Some((Placement::After, resolve_page_id(client, after).await?)),
+        (_, _, Some(position)) => {
+            let siblings = sibling_ids(client, &page_id).await?;
+            placement_for_position(&siblings, position)
+        }
+        (None, None, None) => unreachable!("clap requires --position, --before, or --after"),
+    };
+
+    let Some((placement, target_id)) = target else {
+        print_line(
+            ctx,
+            &format!("Page {page_id} has no siblings; nothing to do."),
+        );
+        return Ok(());
+    };
+    if target_id == page_id {
+        return Err(anyhow!("Cannot place a page relative to itself"));
+    }
+
+    let placement = placement.as_str();
+    if ctx.dry_run {
+        plan::record(
+            Operation::new("move", "page", &page_id)
+                .with_payload(json!({ "placement": placement, "targetId": target_id })),
+        );
+        return print_write_action_result(
+            ctx,
+            args.output,
+            &format!("Would move page {page_id} {placement} {target_id}"),
+            &json!({
+                "dryRun": true,
+                "id": page_id,
+                "placement": placement,
+                "targetId": target_id,
+            }),
+            vec![
+                vec!["DryRun".to_string(), "true".to_string()],
+                vec!["ID".to_string(), page_id.clone()],
+                vec!["Placement".to_string(), placement.to_string()],
+                vec!["Target".to_string(), target_id.clone()],
+            ],
+        );
+    }
+
+    let url = client.v1_url(&format!("/content/{page_id}/move/{placement}/{target_id}"));
+    client.put_json_no_content(url, json!({})).await?;
+
+    print_write_action_result(
+        ctx,
+        args.output,
+        &format!("Moved page {page_id} {placement} {target_id}"),
+        &json!({
+            "id": page_id,
+            "placement": placement,
+            "targetId": target_id,
+        }),
+        vec![
+            vec!["ID".to_string(), page_id],
+            vec!["Placement".to_string(), placement.to_string()],
+            vec!["Target".to_string(), target_id],
+        ],
+    )
+}
+
+/// Ids of the page's siblings (excluding the page itself), in sidebar order.
+async fn sibling_ids(client: &ApiClient, page_id: &str) -> Result<Vec<String>> {
+    let (page, _) = client
+        .get_json(client.v2_url(&format!("/pages/{page_id}")))
+        .await?;
+    let parent_id = json_str(&page, "parentId");
+    if parent_id.is_empty() {
+        return Err(anyhow!(
+            "Page {page_id} has no parent page; use --before or --after instead of --position"
+        ));
+    }
+
+    let url = url_with_query(
+        &client.v2_url(&format!("/pages/{parent_id}/direct-children")),
+        &[("limit", "250".to_string())],
+    )?;
+    let mut children = client.get_paginated_results(url, true).await?;
+    children.sort_by_key(child_position);
+    Ok(children
+        .iter()
+        .map(|child| json_str(child, "id"))
+        .filter(|id| !id.is_empty() && id != page_id)
+        .collect())
+}
+
+fn child_position(item: &Value) -> i64 {
+    item.get("childPosition")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(i64::MAX)
+}
+
+/// Translate a 1-based position into "before sibling N" (or "after the last sibling").
+fn placement_for_position(siblings: &[String], position: usize) -> Option<(Placement, String)> {
+    match siblings.get(position.saturating_sub(1)) {
+        Some(id) => Some((Placement::Before, id.clone())),
+        None => siblings.last().map(|id| (Placement::After, id.clone())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position_maps_to_sibling_placement() {
+        let siblings = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        assert_eq!(
+            placement_for_position(&siblings, 1),
+            Some((Placement::Before, "a".to_string()))
+        );
+        assert_eq!(
+            placement_for_position(&siblings, 3),
+            Some((Placement::Before, "c".to_string()))
+        );
+        assert_eq!(
+            placement_for_position(&siblings, 9),
+            Some((Placement::After, "c".to_string()))
+        );
+        assert_eq!(placement_for_position(&[], 1), None);
+    }
+}
@@ -0,0 +1,403 @@
+//! `page set-status`: archive pages, or make archived pages current again, in bulk.
+
+use anyhow::{Context, Result, anyhow};
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::output::OutputFormat;
+use futures_util::stream::{self, StreamExt};
+use serde_json::{Value, json};
+use std::time::Duration;
+
+use super::owner::pages_from_cql;
+use crate::cli::{PageSetStatusArgs, PageStatus};
+use crate::context::AppContext;
+use crate::helpers::*;
+use crate::plan::{self, Operation};
+use crate::resolve::resolve_page_id;
+
+/// Pages per archive request (the API's limit).
+const ARCHIVE_BATCH: usize = 300;
+/// Wait between polls of an archive task.
+const ARCHIVE_POLL: Duration = Duration::from_secs(1);
+/// Polls before giving up on an archive task (10 minutes).
+const ARCHIVE_POLLS: u32 = 600;
+/// Pages looked up and updated at once without `--concurrency`.
+const CONCURRENCY: usize = 4;
+
+impl PageStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Current => "current",
+            Self::Archived => "archived",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PageState {
+    id: String,
+    title: String,
+    status: String,
+}
+
+#[derive(Debug)]
+enum Outcome {
+    Unchanged,
+    WouldChange,
+    Changed,
+    Failed(String),
+}
+
+impl Outcome {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unchanged => "unchanged",
+            Self::WouldChange => "would-change",
+            Self::Changed => "changed",
+            Self::Failed(_) => "failed",
+        }
+    }
+}
+
+pub(super) async fn page_set_status(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: PageSetStatusArgs,
+) -> Result<()> {
+    let ids = match &args.cql {
+        Some(cql) => pages_from_cql(client, cql).await?,
+        None => {
+            let mut ids: Vec<String> = Vec::new();
+            for page in &args.pages {
+                let id = resolve_page_id(client, page).await?;
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+            ids
+        }
+    };
+    if ids.is_empty() {
+        print_line(ctx, "No pages matched.");
+        return Ok(());
+    }
+
+    let target = args.status.as_str();
+    let states: Vec<Result<PageState>> = stream::iter(ids)
+        .map(|id| async move {
+            let (json, _) = client
+                .get_json(client.v2_url(&format!("/pages/{id}")))
+                .await
+                .with_context(|| format!("Failed to read page {id}"))?;
+            Ok(PageState {
+                title: json_str(&json, "title"),
+                status: json_str(&json, "status"),
+                id,
+            })
+        })
+        .buffered(ctx.workers(CONCURRENCY))
+        .collect()
+        .await;
+    let states = states.into_iter().collect::<Result<Vec<_>>>()?;
+    let to_change: Vec<&PageState> = states.iter().filter(|p| p.status != target).collect();
+
+    let mut outcomes: Vec<Outcome> = states
+        .iter()
+        .map(|page| {
+            if page.status == target {
+                Outcome::Unchanged
+            } else {
+                Outcome::WouldChange
+            }
+        })
+        .collect();
+
+    if ctx.dry_run {
+        for page in &to_change {
+            plan::record(
+                Operation::new("update", "page", &page.id).with_payload(json!({
+                    "title": page.title,
+                    "status": target,
+                    "previousStatus": page.status,
+                })),
+            );
+        }
+    }
+    if !ctx.dry_run && !to_change.is_empty() {
+        if to_change.len() > 1
+            && !args.yes
+            && !confirm(&format!(
+                "Set status of {} page(s) to {target}?",
+                to_change.len()
+            ))?
+        {
+            print_line(ctx, "Cancelled.");
+            return Ok(());
+        }
+
+        let ids: Vec<String> = to_change.iter().map(|page| page.id.clone()).collect();
+        let results = match args.status {
+            PageStatus::Archived => archive_pages(client, &ids).await,
+            PageStatus::Current => {
+                stream::iter(ids)
+                    .map(|id| async move {
+                        let res = restore_page(client, &id).await;
+                        (id, res)
+                    })
+                    .buffered(ctx.workers(CONCURRENCY))
+                    .collect()
+                    .await
+            }
+        };
+        for (id, res) in results {
+            if let Some(idx) = states.iter().position(|page| page.id == id) {
+                outcomes[idx] = match res {
+                    Ok(()) => Outcome::Changed,
+                    Err(err) => Outcome::Failed(format!("{err:#}")),
+                };
+            }
+        }
+    }
+
+    match args.output {
+        OutputFormat::Json => {
+            let items: Vec<Value> = states
+                .iter()
+                .zip(&outcomes)
+                .map(|(page, outcome)| {
+                    json!({
+                        "id": page.id,
+                        "title": page.title,
+                        "from": page.status,
+                        "to": target,
+                        "result": outcome.as_str(),
+                        "error": match outcome {
+                            Outcome::Failed(err) => Some(err.as_str()),
+                            _ => None,
+                        },
+                    })
+                })
+                .collect();
+            maybe_print_json(ctx, &items)?;
+        }
+        fmt => {
+            let rows = states
+                .iter()
+                .zip(&outcomes)
+                .map(|(page, outcome)| {
+                    let result = match outcome {
+                        Outcome::Unchanged => format!("already {target}"),
+                        Outcome::WouldChange => format!("would be {target}"),
+                        Outcome::Changed => target.to_string(),
+                        Outcome::Failed(err) => err.clone(),
+                    };
+                    vec![
+                        page.id.clone(),
+                        page.title.clone(),
+                        page.status.clone(),
+                        result,
+                    ]
+                })
+                .collect();
+            maybe_print_rows(ctx, fmt, &["ID", "Title", "From", "Result"], rows);
+        }
+    }
+
+    let count = |kind: fn(&Outcome) -> bool| outcomes.iter().filter(|o| kind(o)).count();
+    let unchanged = count(|o| matches!(o, Outcome::Unchanged));
+    let failed = count(|o| matches!(o, Outcome::Failed(_)));
+    if args.output != OutputFormat::Json {
+        let summary = if ctx.dry_run {
+            format!(
+                "Would set {} page(s) to {target}; {unchanged} already {target}",
+                to_change.len()
+            )
+        } else {
+            format!(
+                "Set {} page(s) to {target}; {unchanged} already {target}; {failed} failed",
+                count(|o| matches!(o, Outcome::Changed))
+            )
+        };
+        print_line(ctx, &summary);
+    }
+    if failed > 0 {
+        return Err(anyhow!(
+            "{failed} of {} page(s) could not be set to {target}",
+            to_change.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Archive pages through the v1 archive endpoint, one batch at a time: each request starts
+/// a background task, and Confluence runs only one archive task per user. Every page of a
+/// batch gets that batch's result.
+async fn archive_pages(client: &ApiClient, ids: &[String]) -> Vec<(String, Result<()>)> {
+    let mut results = Vec::with_capacity(ids.len());
+    for batch in ids.chunks(ARCHIVE_BATCH) {
+        let res = archive_batch(client, batch).await;
+        let err = res.err().map(|err| format!("{err:#}"));
+        for id in batch {
+            let res = match &err {
+                Some(err) => Err(anyhow!("{err}")),
+                None => Ok(()),
+            };
+            results.push((id.clone(), res));
+        }
+    }
+    results
+}
+
+async fn archive_batch(client: &ApiClient, ids: &[String]) -> Result<()> {
+    let pages: Vec<Value> = ids
+        .iter()
+        .map(|id| match id.parse::<u64>() {
+            Ok(num) => json!({ "id": num }),
+            Err(_) => json!({ "id": id }),
+        })
+        .collect();
+    let task = client
+        .post_json(client.v1_url("/content/archive"), json!({ "pages": pages }))
+        .await
+        .context("Failed to start archiving")?;
+    let task_id = json_str(&task, "id");
+    if task_id.is_empty() {
+        return Err(anyhow!("Archive request returned no task id"));
+    }
+    wait_for_archive(client, &task_id, ARCHIVE_POLL, ARCHIVE_POLLS).await
+}
+
+/// Poll the archive task until it finishes, at most `polls` times `interval` apart.
+async fn wait_for_archive(
+    client: &ApiClient,
+    task_id: &str,
+    interval: Duration,
+    polls: u32,
+) -> Result<()> {
+    for _ in 0..polls {
+        let (task, _) = client
+            .get_json(client.v1_url(&format!("/longtask/{task_id}")))
+            .await
+            .with_context(|| format!("Failed to check archive task {task_id}"))?;
+        if task.get("finished").and_then(|v| v.as_bool()) == Some(true) {
+            if task.get("successful").and_then(|v| v.as_bool()) == Some(false) {
+                let messages: Vec<String> = task
+                    .get("messages")
+                    .and_then(|v| v.as_array())
+                    .map(|items| {
+                        items
+                            .iter()
+                            .map(|item| json_str(item, "translation"))
+                            .filter(|msg| !msg.is_empty())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                return Err(anyhow!("Archive task failed: {}", messages.join("; ")));
+            }
+            return Ok(());
+        }
+        tokio::time::sleep(interval).await;
+    }
+    Err(anyhow!(
+        "Archive task {task_id} did not finish within {}s; it may still complete later",
+        (interval * polls).as_secs()
+    ))
+}
+
+/// Make a page current again: an update with the same title and body, `status: current`.
+async fn restore_page(client: &ApiClient, page_id: &str) -> Result<()> {
+    let url = client.v2_url(&format!("/pages/{page_id}?body-format=storage"));
+    let (current, _) = client.get_json(url).await?;
+    let version = current
+        .get("version")
+        .and_then(|v| v.get("number"))
+        .and_then(|v| v.as_i64())
+        .context("Missing current version number")?;
+    let body = current
+        .get("body")
+        .and_then(|body| body.get("storage"))
+        .and_then(|body| body.get("value"))
+        .and_then(|value| value.as_str())
+        .unwrap_or("");
+    let payload = json!({
+        "id": page_id,
+        "title": json_str(&current, "title"),
+        "status": "current",
+        "body": { "representation": "storage", "value": body },
+        "version": { "number": version + 1 },
+    });
+    client
+        .put_json(client.v2_url(&format!("/pages/{page_id}")), payload)
+        .await
+        .map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::http_server::start_server;
+
+    #[tokio::test]
+    async fn archive_waits_for_the_task() {
+        let srv = start_server(|_, path| {
+            let body: &[u8] = if path == "/content/archive" {
+                br#"{"id":"task-1"}"#
+            } else if path == "/longtask/task-1" {
+                br#"{"finished":true,"successful":false,"messages":[{"translation":"Page 2 is a draft"}]}"#
+            } else {
+                return (404, vec![], b"{}".to_vec());
+            };
+            (200, vec![], body.to_vec())
+        })
+        .await;
+        let client = ApiClient::new(
+            srv.base_url.clone(),
+            srv.base_url.clone(),
+            srv.base_url.clone(),
+            confcli::auth::AuthMethod::Bearer {
+                token: "test".to_string(),
+            },
+            0,
+        )
+        .unwrap();
+
+        let results = archive_pages(&client, &["1".to_string(), "2".to_string()]).await;
+        assert_eq!(results.len(), 2);
+        for (_, res) in results {
+            let err = res.unwrap_err().to_string();
+            assert!(err.contains("Page 2 is a draft"), "{err}");
+        }
+
+        let _ = srv.shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn archive_gives_up_on_a_task_that_never_finishes() {
+        let srv = start_server(|_, path| {
+            if path == "/longtask/task-7" {
+                (200, vec![], br#"{"finished":false}"#.to_vec())
+            } else {
+                (404, vec![], b"{}".to_vec())
+            }
+        })
+        .await;
+        let client = ApiClient::new(
+            srv.base_url.clone(),
+            srv.base_url.clone(),
+            srv.base_url.clone(),
+            confcli::auth::AuthMethod::Bearer {
+                token: "test".to_string(),
+            },
+            0,
+        )
+        .unwrap();
+
+        let err = wait_for_archive(&client, "task-7", Duration::ZERO, 3)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Archive task task-7 did not finish"), "{err}");
+
+        let _ = srv.shutdown.send(());
+    }
+}
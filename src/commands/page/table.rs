@@ -0,0 +1,880 @@
+use anyhow::{Context, Result};
+use confcli::client::ApiClient;
+use confcli::markdown::decode_html_entities;
+use confcli::output::OutputFormat;
+use regex::Regex;
+use serde_json::{Map, Value, json};
+use std::sync::LazyLock;
+
+use crate::cli::{PageTableArgs, TableOutput, TableSource};
+#[cfg(feature = "write")]
+use crate::cli::{PageTableUpdateArgs, TableDataFormat};
+use crate::context::AppContext;
+use crate::helpers::{csv_record, maybe_print_json, maybe_print_rows, print_line};
+#[cfg(feature = "write")]
+use crate::helpers::{print_write_action_result, read_body};
+#[cfg(feature = "write")]
+use crate::plan::{self, Operation};
+use crate::resolve::resolve_page_id;
+#[cfg(feature = "write")]
+use confcli::json_util::json_str;
+#[cfg(feature = "write")]
+use std::collections::HashMap;
+
+static TABLE_PART_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)<(/?)(table|tr|td|th)\b([^>]*?)/?>").expect("TABLE_PART_RE"));
+static SPAN_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\b(colspan|rowspan)\s*=\s*"?\s*([0-9]+)"#).expect("SPAN_RE")
+});
+static SPAN_ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\s+(?:colspan|rowspan)\s*=\s*(?:"[^"]*"|'[^']*'|[0-9]+)"#)
+        .expect("SPAN_ATTR_RE")
+});
+static CDATA_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<!\[CDATA\[(.*?)\]\]>").expect("CDATA_RE"));
+static STATUS_MACRO_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?s)<ac:structured-macro\b[^>]*ac:name="status"[^>]*>(.*?)</ac:structured-macro>"#,
+    )
+    .expect("STATUS_MACRO_RE")
+});
+static STATUS_TITLE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?s)<ac:parameter\b[^>]*ac:name="title"[^>]*>(.*?)</ac:parameter>"#)
+        .expect("STATUS_TITLE_RE")
+});
+static PARAMETER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)<ac:parameter\b[^>]*>.*?</ac:parameter>").expect("PARAMETER_RE")
+});
+static AC_LINK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<ac:link\b[^>]*>(.*?)</ac:link>").expect("AC_LINK_RE"));
+static LINK_TARGET_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"ri:(content-title|filename|value|account-id)="([^"]*)""#).expect("LINK_TARGET_RE")
+});
+static LINE_BREAK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)<br\b[^>]*>|</(?:p|li|div|h[1-6]|pre|blockquote)>").expect("LINE_BREAK_RE")
+});
+#[cfg(feature = "write")]
+static COLGROUP_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<colgroup\b.*?</colgroup>").expect("COLGROUP_RE"));
+static TAG_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]*>").expect("TAG_RE"));
+static SPACES_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[ \t\r\u{a0}]+").expect("SPACES_RE"));
+
+pub(super) async fn page_table(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: PageTableArgs,
+) -> Result<()> {
+    let page = args.page.context("Missing page")?;
+    let page_id = resolve_page_id(client, &page).await?;
+    let body_format = match args.body_format {
+        TableSource::View => "view",
+        TableSource::Storage => "storage",
+    };
+    let url = client.v2_url(&format!("/pages/{page_id}?body-format={body_format}"));
+    let (json, _) = client.get_json(url).await?;
+    let html = json
+        .get("body")
+        .and_then(|body| body.get(body_format))
+        .and_then(|body| body.get("value"))
+        .and_then(|value| value.as_str())
+        .with_context(|| format!("Missing {body_format} body content"))?;
+
+    let mut tables = parse_tables(html);
+    if tables.is_empty() {
+        anyhow::bail!("Page {page_id} has no tables");
+    }
+    let count = tables.len();
+    let tables: Vec<(usize, PageTable)> = match args.index {
+        Some(index) => {
+            let index = index as usize;
+            if index > count {
+                anyhow::bail!(
+                    "Page {page_id} has {count} table{}; --index must be between 1 and {count}",
+                    if count == 1 { "" } else { "s" }
+                );
+            }
+            vec![(index, tables.swap_remove(index - 1))]
+        }
+        None => tables
+            .into_iter()
+            .enumerate()
+            .map(|(idx, table)| (idx + 1, table))
+            .collect(),
+    };
+
+    if args.output == TableOutput::Json {
+        let out: Vec<Value> = tables
+            .iter()
+            .map(|(index, table)| table.to_json(*index))
+            .collect();
+        return maybe_print_json(ctx, &out);
+    }
+    let [(_, table)] = tables.as_slice() else {
+        anyhow::bail!(
+            "Page {page_id} has {count} tables; pick one with --index (1-{count}) or use -o json"
+        );
+    };
+    match args.output {
+        TableOutput::Csv => {
+            print_line(ctx, &csv_record(&table.headers));
+            for row in &table.rows {
+                print_line(ctx, &csv_record(row));
+            }
+            Ok(())
+        }
+        _ => {
+            let headers: Vec<&str> = table.headers.iter().map(String::as_str).collect();
+            maybe_print_rows(ctx, OutputFormat::Table, &headers, table.rows.clone());
+            Ok(())
+        }
+    }
+}
+
+/// A table read from a page body. Every row has one value per header.
+#[derive(Debug)]
+struct PageTable {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    /// Where the table came from, for writing it back.
+    #[cfg(feature = "write")]
+    origin: TableOrigin,
+}
+
+/// The body markup behind a [`PageTable`].
+#[cfg(feature = "write")]
+#[derive(Debug, Default)]
+struct TableOrigin {
+    /// Byte range of the whole `<table>` element in the body.
+    span: std::ops::Range<usize>,
+    /// Whether the first row was a header row (otherwise the headers are `Column N`).
+    header_row: bool,
+    /// Every row's cells as `<td>`/`<th>` elements without span attributes, header row
+    /// first when there is one.
+    cells: Vec<Vec<String>>,
+}
+
+impl PageTable {
+    /// `{index, headers, rows}` with each row an object keyed by header.
+    fn to_json(&self, index: usize) -> Value {
+        let rows: Vec<Value> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let object: Map<String, Value> = self
+                    .headers
+                    .iter()
+                    .cloned()
+                    .zip(row.iter().cloned().map(Value::String))
+                    .collect();
+                Value::Object(object)
+            })
+            .collect();
+        json!({ "index": index, "headers": self.headers, "rows": rows })
+    }
+}
+
+/// One `<td>`/`<th>` before spans are laid out.
+struct Cell {
+    text: String,
+    /// The element with its span attributes dropped.
+    markup: String,
+    header: bool,
+    colspan: usize,
+    rowspan: usize,
+}
+
+/// A laid-out grid position: text, markup, and whether it came from a header cell.
+type GridCell = (String, String, bool);
+
+/// The top-level tables of a storage or view body, in document order. Tables nested in a
+/// cell are flattened into that cell's text.
+fn parse_tables(html: &str) -> Vec<PageTable> {
+    let mut tables = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0usize;
+    let mut rows: Vec<Vec<Cell>> = Vec::new();
+    let mut row: Option<Vec<Cell>> = None;
+    // The open cell's start tag: its byte range, name, and attributes.
+    let mut cell: Option<(std::ops::Range<usize>, bool, String)> = None;
+
+    for part in TABLE_PART_RE.captures_iter(html) {
+        let whole = part.get(0).expect("match");
+        let closing = !part[1].is_empty();
+        let name = part[2].to_ascii_lowercase();
+        if name == "table" {
+            if !closing {
+                depth += 1;
+                if depth == 1 {
+                    start = whole.start();
+                    rows.clear();
+                    row = None;
+                    cell = None;
+                }
+            } else if depth > 0 {
+                if depth == 1 {
+                    close_cell(html, whole.start(), &mut cell, &mut row);
+                    rows.extend(row.take());
+                    let table = layout(std::mem::take(&mut rows), start..whole.end());
+                    tables.push(table);
+                }
+                depth -= 1;
+            }
+            continue;
+        }
+        if depth != 1 {
+            continue;
+        }
+        // Missing end tags are implied by the next row or cell, as in HTML.
+        close_cell(html, whole.start(), &mut cell, &mut row);
+        match (name.as_str(), closing) {
+            ("tr", false) => {
+                rows.extend(row.take());
+                row = Some(Vec::new());
+            }
+            ("tr", true) => rows.extend(row.take()),
+            (_, false) => cell = Some((whole.range(), name == "th", part[3].to_string())),
+            (_, true) => {}
+        }
+    }
+    tables
+}
+
+fn close_cell(
+    html: &str,
+    end: usize,
+    cell: &mut Option<(std::ops::Range<usize>, bool, String)>,
+    row: &mut Option<Vec<Cell>>,
+) {
+    let Some((tag, header, attrs)) = cell.take() else {
+        return;
+    };
+    let mut colspan = 1;
+    let mut rowspan = 1;
+    for span in SPAN_RE.captures_iter(&attrs) {
+        let value = span[2].parse::<usize>().unwrap_or(1).clamp(1, 1000);
+        if span[1].eq_ignore_ascii_case("colspan") {
+            colspan = value;
+        } else {
+            rowspan = value;
+        }
+    }
+    let content = &html[tag.end..end];
+    let name = if header { "th" } else { "td" };
+    let attrs = SPAN_ATTR_RE.replace_all(&attrs, "");
+    row.get_or_insert_with(Vec::new).push(Cell {
+        text: cell_text(content),
+        markup: format!("<{name}{attrs}>{content}</{name}>"),
+        header,
+        colspan,
+        rowspan,
+    });
+}
+
+/// Lay cells out on a grid. A row-spanning cell repeats its text in every row it covers, so
+/// each row stands on its own; a column-spanning cell keeps its text in the first column and
+/// leaves the others empty. A first row made only of header cells names the columns.
+#[cfg_attr(not(feature = "write"), allow(unused_variables))]
+fn layout(rows: Vec<Vec<Cell>>, span: std::ops::Range<usize>) -> PageTable {
+    // Cells still owed to later rows by a rowspan, per column.
+    let mut carried: Vec<Option<(usize, GridCell)>> = Vec::new();
+    let mut grid: Vec<Vec<GridCell>> = Vec::new();
+    for row in rows {
+        let mut out: Vec<GridCell> = Vec::new();
+        let mut cells = row.into_iter();
+        loop {
+            let col = out.len();
+            if let Some(Some((left, cell))) = carried.get_mut(col) {
+                out.push(cell.clone());
+                *left -= 1;
+                if *left == 0 {
+                    carried[col] = None;
+                }
+                continue;
+            }
+            let Some(cell) = cells.next() else {
+                if carried.iter().skip(col).any(Option::is_some) {
+                    out.push((String::new(), "<td></td>".to_string(), false));
+                    continue;
+                }
+                break;
+            };
+            for span in 0..cell.colspan {
+                let placed = if span == 0 {
+                    (cell.text.clone(), cell.markup.clone(), cell.header)
+                } else {
+                    let name = if cell.header { "th" } else { "td" };
+                    (String::new(), format!("<{name}></{name}>"), cell.header)
+                };
+                if cell.rowspan > 1 {
+                    let col = out.len();
+                    if carried.len() <= col {
+                        carried.resize(col + 1, None);
+                    }
+                    carried[col] = Some((cell.rowspan - 1, placed.clone()));
+                }
+                out.push(placed);
+            }
+        }
+        grid.push(out);
+    }
+
+    let width = grid.iter().map(Vec::len).max().unwrap_or(0);
+    let header_row = grid
+        .first()
+        .is_some_and(|row| !row.is_empty() && row.iter().all(|(_, _, header)| *header));
+    let names: Vec<String> = if header_row {
+        grid[0].iter().map(|(text, _, _)| text.clone()).collect()
+    } else {
+        Vec::new()
+    };
+    let mut headers: Vec<String> = Vec::with_capacity(width);
+    for idx in 0..width {
+        let name = names
+            .get(idx)
+            .map(|name| name.replace('\n', " "))
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| format!("Column {}", idx + 1));
+        let mut unique = name.clone();
+        let mut n = 2;
+        while headers.contains(&unique) {
+            unique = format!("{name} ({n})");
+            n += 1;
+        }
+        headers.push(unique);
+    }
+    let rows = grid
+        .iter()
+        .skip(usize::from(header_row))
+        .map(|row| {
+            let mut row: Vec<String> = row.iter().map(|(text, _, _)| text.clone()).collect();
+            row.resize(width, String::new());
+            row
+        })
+        .collect();
+    PageTable {
+        headers,
+        rows,
+        #[cfg(feature = "write")]
+        origin: TableOrigin {
+            span,
+            header_row,
+            cells: grid
+                .into_iter()
+                .map(|row| row.into_iter().map(|(_, markup, _)| markup).collect())
+                .collect(),
+        },
+    }
+}
+
+/// The visible text of a cell: tags dropped, entities decoded, one line per paragraph or
+/// line break. Storage-format links without a body show their target (`@<account id>` for
+/// mentions), and status macros their label.
+fn cell_text(html: &str) -> String {
+    let html = CDATA_RE.replace_all(html, |caps: &regex::Captures| {
+        caps[1]
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    });
+    let html = STATUS_MACRO_RE.replace_all(&html, |caps: &regex::Captures| {
+        STATUS_TITLE_RE
+            .captures(&caps[1])
+            .map(|title| title[1].to_string())
+            .unwrap_or_default()
+    });
+    let html = PARAMETER_RE.replace_all(&html, "");
+    let html = AC_LINK_RE.replace_all(&html, |caps: &regex::Captures| {
+        let inner = &caps[1];
+        if inner.contains("link-body") {
+            inner.to_string()
+        } else {
+            LINK_TARGET_RE
+                .captures(inner)
+                .map(|target| match &target[1] {
+                    "account-id" => format!("@{}", &target[2]),
+                    _ => target[2].to_string(),
+                })
+                .unwrap_or_default()
+        }
+    });
+    let html = LINE_BREAK_RE.replace_all(&html, "\n");
+    let text = decode_html_entities(&TAG_RE.replace_all(&html, ""));
+    text.lines()
+        .map(|line| SPACES_RE.replace_all(line, " ").trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(feature = "write")]
+pub(super) async fn page_table_update(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: PageTableUpdateArgs,
+) -> Result<()> {
+    let format =
+        args.format.unwrap_or_else(
+            || match args.from.extension().and_then(|ext| ext.to_str()) {
+                Some(ext) if ext.eq_ignore_ascii_case("json") => TableDataFormat::Json,
+                _ => TableDataFormat::Csv,
+            },
+        );
+    let input = read_body(None, Some(&args.from)).await?;
+    let (headers, rows) = match format {
+        TableDataFormat::Csv => table_from_csv(&input),
+        TableDataFormat::Json => table_from_json(&input),
+    }
+    .with_context(|| format!("Failed to read table data from {}", args.from.display()))?;
+
+    let page_id = resolve_page_id(client, &args.page).await?;
+    let url = client.v2_url(&format!("/pages/{page_id}?body-format=storage"));
+    let (page, _) = client.get_json(url).await?;
+    let version = page
+        .get("version")
+        .and_then(|v| v.get("number"))
+        .and_then(|v| v.as_i64())
+        .context("Missing current version number")?;
+    let storage = page
+        .get("body")
+        .and_then(|body| body.get("storage"))
+        .and_then(|body| body.get("value"))
+        .and_then(|value| value.as_str())
+        .context("Missing storage body content")?;
+
+    let mut tables = parse_tables(storage);
+    let count = tables.len();
+    let index = match args.index {
+        Some(index) if index as usize > count => anyhow::bail!(
+            "Page {page_id} has {count} table{} in its storage body; --index must be between 1 and {count}",
+            if count == 1 { "" } else { "s" }
+        ),
+        Some(index) => index as usize,
+        None if count == 1 => 1,
+        None if count == 0 => anyhow::bail!("Page {page_id} has no tables"),
+        None => anyhow::bail!(
+            "Page {page_id} has {count} tables; pick one with --index (see `confcli page table {page_id} --body-format storage`)"
+        ),
+    };
+    let old = tables.swap_remove(index - 1);
+    let markup = table_storage(storage, &old, &headers, &rows);
+    let span = old.origin.span.clone();
+    let body = format!("{}{markup}{}", &storage[..span.start], &storage[span.end..]);
+
+    let summary = json!({
+        "pageId": page_id,
+        "index": index,
+        "headers": headers,
+        "previousRows": old.rows.len(),
+        "rows": rows.len(),
+    });
+    if body == storage {
+        let mut result = summary;
+        result["updated"] = json!(false);
+        return print_write_action_result(
+            ctx,
+            args.output,
+            &format!("Table {index} on page {page_id} already has this content"),
+            &result,
+            vec![
+                vec!["Page".to_string(), page_id.clone()],
+                vec!["Table".to_string(), index.to_string()],
+                vec!["Updated".to_string(), "false".to_string()],
+            ],
+        );
+    }
+    if ctx.dry_run {
+        plan::record(
+            Operation::new("update", "table", &page_id).with_payload(json!({
+                "index": index,
+                "headers": headers,
+                "rows": rows.len(),
+                "previousRows": old.rows.len(),
+            })),
+        );
+        let mut result = summary;
+        result["dryRun"] = json!(true);
+        result["previousHeaders"] = json!(old.headers);
+        return print_write_action_result(
+            ctx,
+            args.output,
+            &format!(
+                "Would replace table {index} on page {page_id} ({} rows: {}) with {} rows: {}",
+                old.rows.len(),
+                old.headers.join(", "),
+                rows.len(),
+                headers.join(", ")
+            ),
+            &result,
+            vec![
+                vec!["DryRun".to_string(), "true".to_string()],
+                vec!["Page".to_string(), page_id.clone()],
+                vec!["Table".to_string(), index.to_string()],
+                vec![
+                    "Rows".to_string(),
+                    format!("{} -> {}", old.rows.len(), rows.len()),
+                ],
+            ],
+        );
+    }
+
+    let mut payload = json!({
+        "id": page_id,
+        "title": json_str(&page, "title"),
+        "status": page.get("status").and_then(|v| v.as_str()).unwrap_or("current"),
+        "body": { "representation": "storage", "value": body },
+        "version": { "number": version + 1 }
+    });
+    if let Some(message) = args.message {
+        payload["version"]["message"] = Value::String(message);
+    }
+    client
+        .put_json(client.v2_url(&format!("/pages/{page_id}")), payload)
+        .await
+        .with_context(|| format!("Failed to update page {page_id}"))?;
+    let mut result = summary;
+    result["updated"] = json!(true);
+    result["version"] = json!(version + 1);
+    print_write_action_result(
+        ctx,
+        args.output,
+        &format!(
+            "Replaced table {index} on page {page_id} with {} rows (version {})",
+            rows.len(),
+            version + 1
+        ),
+        &result,
+        vec![
+            vec!["Page".to_string(), page_id.clone()],
+            vec!["Table".to_string(), index.to_string()],
+            vec!["Rows".to_string(), rows.len().to_string()],
+            vec!["Version".to_string(), (version + 1).to_string()],
+        ],
+    )
+}
+
+/// Storage markup for `old` rewritten to hold `headers` and `rows`. The original `<table>`
+/// tag is kept, and so is `<colgroup>` while the column count is unchanged. A cell whose
+/// text matches a cell of the same column in `old` reuses that cell's markup, so formatting
+/// survives a round trip; other cells are written as plain paragraphs.
+#[cfg(feature = "write")]
+fn table_storage(
+    storage: &str,
+    old: &PageTable,
+    headers: &[String],
+    rows: &[Vec<String>],
+) -> String {
+    let original = &storage[old.origin.span.clone()];
+    let open_tag = TABLE_PART_RE
+        .find(original)
+        .map_or("<table>", |tag| tag.as_str());
+    let colgroup = COLGROUP_RE
+        .find(original)
+        .filter(|_| old.headers.len() == headers.len())
+        .map_or("", |colgroup| colgroup.as_str());
+
+    let old_rows = &old.origin.cells[usize::from(old.origin.header_row)..];
+    let mut known: HashMap<(usize, &str), &str> = HashMap::new();
+    for (texts, cells) in old.rows.iter().zip(old_rows) {
+        for (col, (text, cell)) in texts.iter().zip(cells).enumerate() {
+            known.entry((col, text.as_str())).or_insert(cell.as_str());
+        }
+    }
+    let old_columns: Vec<Option<usize>> = headers
+        .iter()
+        .map(|header| old.headers.iter().position(|old| old == header))
+        .collect();
+
+    let mut out = format!("{open_tag}{colgroup}<tbody>");
+    let synthetic = headers
+        .iter()
+        .enumerate()
+        .all(|(idx, header)| *header == format!("Column {}", idx + 1));
+    if old.origin.header_row || !synthetic {
+        out.push_str("<tr>");
+        for (header, old_col) in headers.iter().zip(&old_columns) {
+            match old_col.filter(|_| old.origin.header_row) {
+                Some(col) => out.push_str(&old.origin.cells[0][col]),
+                None => out.push_str(&format!("<th>{}</th>", paragraphs(header))),
+            }
+        }
+        out.push_str("</tr>");
+    }
+    for row in rows {
+        out.push_str("<tr>");
+        for (value, old_col) in row.iter().zip(&old_columns) {
+            match old_col.and_then(|col| known.get(&(col, value.as_str()))) {
+                Some(cell) => out.push_str(cell),
+                None => out.push_str(&format!("<td>{}</td>", paragraphs(value))),
+            }
+        }
+        out.push_str("</tr>");
+    }
+    out.push_str("</tbody></table>");
+    out
+}
+
+/// `text` as storage paragraphs, one per line.
+#[cfg(feature = "write")]
+fn paragraphs(text: &str) -> String {
+    if text.is_empty() {
+        return "<p />".to_string();
+    }
+    text.lines()
+        .map(|line| {
+            let escaped = line
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;");
+            format!("<p>{escaped}</p>")
+        })
+        .collect()
+}
+
+/// Headers and rows from CSV whose first record is the header row. Short rows are padded;
+/// rows longer than the header row are an error.
+#[cfg(feature = "write")]
+fn table_from_csv(input: &str) -> Result<(Vec<String>, Vec<Vec<String>>)> {
+    let mut records = parse_csv(input)?.into_iter();
+    let headers = records.next().context("The CSV has no header row")?;
+    let mut rows = Vec::new();
+    for (idx, mut record) in records.enumerate() {
+        if record.len() > headers.len() {
+            anyhow::bail!(
+                "CSV row {} has {} fields but the header row has {}",
+                idx + 2,
+                record.len(),
+                headers.len()
+            );
+        }
+        record.resize(headers.len(), String::new());
+        rows.push(record);
+    }
+    Ok((headers, rows))
+}
+
+/// Headers and rows from JSON: one table as printed by `page table -o json` (`{headers,
+/// rows}`, or an array holding exactly one), or an array of row objects. Without `headers`,
+/// columns appear in the order their keys are first seen.
+#[cfg(feature = "write")]
+fn table_from_json(input: &str) -> Result<(Vec<String>, Vec<Vec<String>>)> {
+    let value: Value = serde_json::from_str(input).context("Invalid JSON")?;
+    let (headers, rows) = match &value {
+        Value::Array(items) if items.len() == 1 && items[0].get("rows").is_some() => {
+            (items[0].get("headers"), &items[0]["rows"])
+        }
+        Value::Array(_) => (None, &value),
+        Value::Object(_) if value.get("rows").is_some() => (value.get("headers"), &value["rows"]),
+        _ => anyhow::bail!("Expected an array of row objects or an object with `rows`"),
+    };
+    let rows = rows.as_array().context("`rows` must be an array")?;
+    let mut headers: Vec<String> = match headers {
+        Some(headers) => headers
+            .as_array()
+            .context("`headers` must be an array of strings")?
+            .iter()
+            .map(|header| header.as_str().map(str::to_string))
+            .collect::<Option<_>>()
+            .context("`headers` must be an array of strings")?,
+        None => Vec::new(),
+    };
+    let mut objects = Vec::with_capacity(rows.len());
+    for (idx, row) in rows.iter().enumerate() {
+        let object = row
+            .as_object()
+            .with_context(|| format!("Row {} is not an object", idx + 1))?;
+        for key in object.keys() {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+        objects.push(object);
+    }
+    let rows = objects
+        .into_iter()
+        .map(|object| {
+            headers
+                .iter()
+                .map(|header| match object.get(header) {
+                    None | Some(Value::Null) => String::new(),
+                    Some(Value::String(text)) => text.clone(),
+                    Some(other) => other.to_string(),
+                })
+                .collect()
+        })
+        .collect();
+    Ok((headers, rows))
+}
+
+/// RFC 4180 records. Quoted fields may hold commas, quotes (doubled), and line breaks;
+/// blank lines are skipped.
+#[cfg(feature = "write")]
+fn parse_csv(input: &str) -> Result<Vec<Vec<String>>> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut was_quoted = false;
+    let mut chars = input.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if quoted {
+            match ch {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => quoted = false,
+                _ => field.push(ch),
+            }
+            continue;
+        }
+        match ch {
+            '"' if field.is_empty() => {
+                quoted = true;
+                was_quoted = true;
+            }
+            ',' => {
+                record.push(std::mem::take(&mut field));
+                was_quoted = false;
+            }
+            '\r' => {}
+            '\n' => {
+                if !record.is_empty() || !field.is_empty() || was_quoted {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+                was_quoted = false;
+            }
+            _ => field.push(ch),
+        }
+    }
+    if quoted {
+        anyhow::bail!("Unterminated quoted field in CSV");
+    }
+    if !record.is_empty() || !field.is_empty() || was_quoted {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_storage_tables_with_spans_and_markup() {
+        let storage = r#"<p>Intro</p>
+<table data-layout="default"><tbody>
+<tr><th><p>Team</p></th><th><p>Service</p></th><th><p>Status</p></th></tr>
+<tr><td rowspan="2"><p>Payments</p></td><td><p>ledger &amp; api</p></td>
+<td><ac:structured-macro ac:name="status"><ac:parameter ac:name="colour">Green</ac:parameter><ac:parameter ac:name="title">DONE</ac:parameter></ac:structured-macro></td></tr>
+<tr><td><ac:link><ri:page ri:content-title="Billing" /></ac:link></td><td><p>one</p><p>two</p></td></tr>
+<tr><td colspan="2">Total</td><td><table><tr><td>nested</td></tr></table></td></tr>
+</tbody></table>
+<table><tr><td>a</td><td>b</td></tr></table>"#;
+        let tables = parse_tables(storage);
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[0].headers, strings(&["Team", "Service", "Status"]));
+        assert_eq!(
+            tables[0].rows,
+            vec![
+                strings(&["Payments", "ledger & api", "DONE"]),
+                strings(&["Payments", "Billing", "one\ntwo"]),
+                strings(&["Total", "", "nested"]),
+            ]
+        );
+        assert_eq!(tables[1].headers, strings(&["Column 1", "Column 2"]));
+        assert_eq!(tables[1].rows, vec![strings(&["a", "b"])]);
+    }
+
+    #[test]
+    fn headers_are_unique_and_rows_keyed_by_them() {
+        let table = parse_tables(
+            "<table><tr><th>Name</th><th>Name</th><th></th></tr><tr><td>x</td><td>y&#8217;s</td></tr></table>",
+        )
+        .remove(0);
+        assert_eq!(table.headers, strings(&["Name", "Name (2)", "Column 3"]));
+        assert_eq!(
+            table.to_json(1),
+            json!({
+                "index": 1,
+                "headers": ["Name", "Name (2)", "Column 3"],
+                "rows": [{ "Name": "x", "Name (2)": "y\u{2019}s", "Column 3": "" }],
+            })
+        );
+    }
+
+    #[test]
+    fn csv_quotes_only_when_needed() {
+        assert_eq!(
+            csv_record(&strings(&["plain", "a,b", "say \"hi\"", "two\nlines"])),
+            "plain,\"a,b\",\"say \"\"hi\"\"\",\"two\nlines\""
+        );
+    }
+
+    #[cfg(feature = "write")]
+    #[test]
+    fn rewritten_tables_keep_markup_of_unchanged_cells() {
+        let storage = concat!(
+            "<p>Before</p>",
+            r#"<table data-layout="wide"><colgroup><col /><col /></colgroup><tbody>"#,
+            "<tr><th><p><strong>Name</strong></p></th><th><p>Owner</p></th></tr>",
+            r#"<tr><td class="highlight-green"><p>api</p></td><td><ac:link><ri:user ri:account-id="42" /></ac:link></td></tr>"#,
+            "</tbody></table><p>After</p>",
+        );
+        let old = parse_tables(storage).remove(0);
+        assert_eq!(old.rows, vec![strings(&["api", "@42"])]);
+        let rows = vec![
+            strings(&["api", "@42"]),
+            strings(&["web & <ui>", "ops\nteam"]),
+        ];
+        let markup = table_storage(storage, &old, &strings(&["Name", "Owner"]), &rows);
+        assert_eq!(
+            markup,
+            concat!(
+                r#"<table data-layout="wide"><colgroup><col /><col /></colgroup><tbody>"#,
+                "<tr><th><p><strong>Name</strong></p></th><th><p>Owner</p></th></tr>",
+                r#"<tr><td class="highlight-green"><p>api</p></td><td><ac:link><ri:user ri:account-id="42" /></ac:link></td></tr>"#,
+                "<tr><td><p>web &amp; &lt;ui&gt;</p></td><td><p>ops</p><p>team</p></td></tr>",
+                "</tbody></table>",
+            )
+        );
+        let markup = table_storage(storage, &old, &strings(&["Name", "Owner", "Tier"]), &[]);
+        assert_eq!(
+            markup,
+            r#"<table data-layout="wide"><tbody><tr><th><p><strong>Name</strong></p></th><th><p>Owner</p></th><th><p>Tier</p></th></tr></tbody></table>"#
+        );
+    }
+
+    #[cfg(feature = "write")]
+    #[test]
+    fn reads_csv_and_json_table_data() {
+        let csv = "\u{feff}Name,Notes\r\napi,\"multi\nline, \"\"quoted\"\"\"\r\n\r\nweb\n";
+        let (headers, rows) = table_from_csv(csv).unwrap();
+        assert_eq!(headers, strings(&["Name", "Notes"]));
+        assert_eq!(
+            rows,
+            vec![
+                strings(&["api", "multi\nline, \"quoted\""]),
+                strings(&["web", ""])
+            ]
+        );
+        assert!(table_from_csv("a,b\n1,2,3\n").is_err());
+        assert!(parse_csv("a,\"b\n").is_err());
+
+        let printed =
+            r#"[{"index": 2, "headers": ["Zeta", "Alpha"], "rows": [{"Alpha": 1, "Zeta": null}]}]"#;
+        let (headers, rows) = table_from_json(printed).unwrap();
+        assert_eq!(headers, strings(&["Zeta", "Alpha"]));
+        assert_eq!(rows, vec![strings(&["", "1"])]);
+        let (headers, rows) = table_from_json(r#"[{"b": "x"}, {"a": true, "b": "y"}]"#).unwrap();
+        assert_eq!(headers, strings(&["b", "a"]));
+        assert_eq!(rows, vec![strings(&["x", ""]), strings(&["y", "true"])]);
+    }
+}
@@ -0,0 +1,97 @@
+use anyhow::{Context, Result};
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use serde_json::json;
+
+use crate::cli::PageSaveAsTemplateArgs;
+use crate::context::AppContext;
+use crate::helpers::*;
+use crate::plan::{self, Operation};
+use crate::resolve::*;
+
+pub(super) async fn page_save_as_template(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: PageSaveAsTemplateArgs,
+) -> Result<()> {
+    let page_id = resolve_page_id(client, &args.page).await?;
+    let url = client.v2_url(&format!("/pages/{page_id}?body-format=storage"));
+    let (page, _) = client.get_json(url).await?;
+    let body = page
+        .get("body")
+        .and_then(|body| body.get("storage"))
+        .and_then(|storage| storage.get("value"))
+        .and_then(|value| value.as_str())
+        .context("Missing storage body content")?
+        .to_string();
+    let space_key = if args.global {
+        None
+    } else {
+        Some(resolve_space_key(client, &json_str(&page, "spaceId")).await?)
+    };
+    let scope = space_key
+        .as_deref()
+        .map(|key| format!("space {key}"))
+        .unwrap_or_else(|| "global".to_string());
+
+    if ctx.dry_run {
+        plan::record(
+            Operation::new("create", "template", &args.name)
+                .with_payload(json!({ "pageId": page_id, "space": space_key })),
+        );
+        return print_write_action_result(
+            ctx,
+            args.output,
+            &format!(
+                "Would create {scope} template '{}' from page {page_id}",
+                args.name
+            ),
+            &json!({
+                "dryRun": true,
+                "name": args.name,
+                "pageId": page_id,
+                "space": space_key,
+            }),
+            vec![
+                vec!["DryRun".to_string(), "true".to_string()],
+                vec!["Name".to_string(), args.name.clone()],
+                vec!["Page".to_string(), page_id.clone()],
+                vec!["Scope".to_string(), scope],
+            ],
+        );
+    }
+
+    let mut payload = json!({
+        "name": args.name,
+        "templateType": "page",
+        "body": { "storage": { "value": body, "representation": "storage" } },
+    });
+    if let Some(description) = &args.description {
+        payload["description"] = json!(description);
+    }
+    if let Some(key) = &space_key {
+        payload["space"] = json!({ "key": key });
+    }
+
+    let result = client
+        .post_json(client.v1_url("/template"), payload)
+        .await?;
+    let template_id = json_str(&result, "templateId");
+    print_write_action_result(
+        ctx,
+        args.output,
+        &format!("Created {scope} template '{}' ({template_id})", args.name),
+        &json!({
+            "templateId": template_id,
+            "name": args.name,
+            "pageId": page_id,
+            "space": space_key,
+        }),
+        vec![
+            vec!["TemplateId".to_string(), template_id.clone()],
+            vec!["Name".to_string(), args.name.clone()],
+            vec!["Page".to_string(), page_id],
+            vec!["Scope".to_string(), scope],
+        ],
+    )
+}
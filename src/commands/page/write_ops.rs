@@ -2,14 +2,13 @@ use anyhow::{Context, Result};
 use confcli::client::ApiClient;
 use confcli::json_util::json_str;
 use confcli::output::OutputFormat;
-use dialoguer::Confirm;
 use serde_json::{Value, json};
-use similar::TextDiff;
 use tempfile::TempDir;
 
 use crate::cli::{PageCreateArgs, PageDeleteArgs, PageEditArgs, PageUpdateArgs};
 use crate::context::AppContext;
 use crate::helpers::*;
+use crate::plan::{self, Operation};
 use crate::resolve::*;
 
 pub(super) async fn page_edit(
@@ -57,11 +56,7 @@ pub(super) async fn page_edit(
         .to_string();
 
     let (orig_for_file, ext) = if body_format == "atlas_doc_format" {
-        let pretty = match serde_json::from_str::<serde_json::Value>(&original_body) {
-            Ok(v) => serde_json::to_string_pretty(&v).unwrap_or(original_body.clone()),
-            Err(_) => original_body.clone(),
-        };
-        (pretty, "json")
+        (pretty_adf(&original_body), "json")
     } else {
         (original_body.clone(), "html")
     };
@@ -105,29 +100,12 @@ pub(super) async fn page_edit(
     }
 
     if args.diff {
-        let diff = TextDiff::from_lines(&orig_for_file, &edited);
-        let unified = diff
-            .unified_diff()
-            .context_radius(3)
-            .header("original", "edited")
-            .to_string();
-        if !ctx.quiet {
-            print!("{unified}");
-        }
+        print_diff(ctx, &orig_for_file, &edited, "original", "edited");
     }
 
-    if !args.yes {
-        let confirm = Confirm::new()
-            .with_prompt("Save changes?")
-            .default(false)
-            .interact()
-            .map_err(|err| {
-                anyhow::anyhow!("{err}. Use --yes to skip confirmation in non-interactive shells.")
-            })?;
-        if !confirm {
-            print_line(ctx, "Cancelled.");
-            return Ok(());
-        }
+    if !args.yes && !confirm("Save changes?")? {
+        print_line(ctx, "Cancelled.");
+        return Ok(());
     }
 
     let check_url = client.v2_url(&format!("/pages/{page_id}"));
@@ -176,32 +154,70 @@ pub(super) async fn page_edit(
     Ok(())
 }
 
+/// ADF pretty-printed, so edits and diffs get one line per node field; other text as-is.
+fn pretty_adf(body: &str) -> String {
+    match serde_json::from_str::<Value>(body) {
+        Ok(v) => serde_json::to_string_pretty(&v).unwrap_or_else(|_| body.to_string()),
+        Err(_) => body.to_string(),
+    }
+}
+
 pub(super) async fn page_create(
     client: &ApiClient,
     ctx: &AppContext,
     args: PageCreateArgs,
 ) -> Result<()> {
-    let title = match &args.title {
-        Some(title) => title.clone(),
-        None => derive_title_from_file(args.body_file.as_ref())
+    let imported = match &args.from_url {
+        Some(url) => Some(super::import::fetch_article(client, url).await?),
+        None => None,
+    };
+    let title = match (&args.title, &imported) {
+        (Some(title), _) => title.clone(),
+        (None, Some(article)) => article
+            .title
+            .clone()
+            .context("The imported page has no <title>; pass --title")?,
+        (None, None) if args.body_clipboard => {
+            anyhow::bail!("--title is required with --body-clipboard")
+        }
+        (None, None) => derive_title_from_file(args.body_file.as_ref())
             .context("Title is required when reading from stdin")?,
     };
 
     if ctx.dry_run {
-        print_line(
+        plan::record(
+            Operation::new("create", "page", format!("{}:{title}", args.space)).with_payload(
+                json!({
+                    "space": args.space,
+                    "title": title,
+                    "parent": args.parent,
+                    "status": args.status,
+                    "sourceUrl": args.from_url,
+                }),
+            ),
+        );
+        print_status(
             ctx,
+            args.print_id,
             &format!("Would create page '{title}' in space {}", args.space),
         );
         return Ok(());
     }
 
     let space_id = resolve_space_id(client, &args.space).await?;
-    let body = read_body(args.body, args.body_file.as_ref()).await?;
+    let (body, body_format) = match imported {
+        Some(article) => (article.storage, "storage".to_string()),
+        None if args.body_clipboard => (crate::clipboard::paste()?, args.body_format),
+        None => (
+            read_body(args.body, args.body_file.as_ref()).await?,
+            args.body_format,
+        ),
+    };
 
     let mut payload = json!({
         "spaceId": space_id,
         "title": title,
-        "body": { "representation": args.body_format, "value": body },
+        "body": { "representation": body_format, "value": body },
         "status": args.status.unwrap_or_else(|| "current".to_string()),
     });
     if let Some(parent) = args.parent {
@@ -210,6 +226,9 @@ pub(super) async fn page_create(
     }
     let url = client.v2_url("/pages");
     let result = client.post_json(url, payload).await?;
+    if args.print_id {
+        return print_created_id(ctx, &json_str(&result, "id"));
+    }
     match args.output {
         OutputFormat::Json => maybe_print_json(ctx, &result),
         fmt => {
@@ -253,6 +272,12 @@ pub(super) async fn page_update(
         ));
     }
 
+    if args.diff && args.body.is_none() && args.body_file.is_none() {
+        return Err(anyhow::anyhow!(
+            "--diff compares a new body with the page's; pass --body or --body-file"
+        ));
+    }
+
     let page_id = resolve_page_id(client, &args.page).await?;
 
     let get_url = client.v2_url(&format!(
@@ -286,7 +311,43 @@ pub(super) async fn page_update(
         })
         .unwrap_or_else(|| "current".to_string());
 
+    // Read before the dry-run check, so a wrong --body-file path fails the preview too.
+    let new_body = if args.body.is_some() || args.body_file.is_some() {
+        Some(read_body(args.body, args.body_file.as_ref()).await?)
+    } else {
+        None
+    };
+    let current_body = current
+        .get("body")
+        .and_then(|body| body.get(&args.body_format))
+        .and_then(|body| body.get("value"))
+        .and_then(|value| value.as_str());
+
+    if args.diff
+        && let Some(new_body) = new_body.as_deref()
+    {
+        let (old, new) = if args.body_format == "atlas_doc_format" {
+            (pretty_adf(current_body.unwrap_or("")), pretty_adf(new_body))
+        } else {
+            (current_body.unwrap_or("").to_string(), new_body.to_string())
+        };
+        if old == new {
+            print_line(ctx, "Body unchanged.");
+        } else {
+            print_diff(ctx, &old, &new, "remote", "new");
+        }
+    }
+
     if ctx.dry_run {
+        plan::record(
+            Operation::new("update", "page", &page_id).with_payload(json!({
+                "title": title,
+                "status": status,
+                "parent": args.parent,
+                "version": current_version + 1,
+                "message": args.message,
+            })),
+        );
         print_line(
             ctx,
             &format!(
@@ -297,16 +358,16 @@ pub(super) async fn page_update(
         return Ok(());
     }
 
-    let body = if args.body.is_none() && args.body_file.is_none() {
-        current
-            .get("body")
-            .and_then(|body| body.get(&args.body_format))
-            .and_then(|body| body.get("value"))
-            .and_then(|value| value.as_str())
+    if args.diff && !args.yes && !confirm("Save changes?")? {
+        print_line(ctx, "Cancelled.");
+        return Ok(());
+    }
+
+    let body = match new_body {
+        Some(body) => body,
+        None => current_body
             .context("Missing body content for update")?
-            .to_string()
-    } else {
-        read_body(args.body, args.body_file.as_ref()).await?
+            .to_string(),
     };
 
     let mut payload = json!({
@@ -354,6 +415,7 @@ pub(super) async fn page_delete(
     let action = if args.purge { "purge" } else { "delete" };
 
     if ctx.dry_run {
+        plan::record(Operation::new(action, "page", &page_id));
         return print_write_action_result(
             ctx,
             args.output,
@@ -373,18 +435,9 @@ pub(super) async fn page_delete(
         );
     }
 
-    if !args.yes {
-        let confirm = Confirm::new()
-            .with_prompt(format!("Delete page {page_id}?"))
-            .default(false)
-            .interact()
-            .map_err(|err| {
-                anyhow::anyhow!("{err}. Use --yes to skip confirmation in non-interactive shells.")
-            })?;
-        if !confirm {
-            print_line(ctx, "Cancelled.");
-            return Ok(());
-        }
+    if !args.yes && !confirm_delete(&format!("Delete page {page_id}?"))? {
+        print_line(ctx, "Cancelled.");
+        return Ok(());
     }
 
     if args.purge {
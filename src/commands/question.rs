@@ -0,0 +1,199 @@
+//! Read-only access to Confluence Questions, which the add-on stores as custom content
+//! (questions, with their answers as child content).
+
+use anyhow::{Context, Result, bail};
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::markdown::{MarkdownOptions, html_to_markdown_with_options};
+use confcli::output::OutputFormat;
+use serde_json::Value;
+
+use crate::cli::{QuestionCommand, QuestionGetArgs, QuestionListArgs};
+use crate::commands::search::search_result_space;
+use crate::context::AppContext;
+use crate::helpers::{
+    escape_cql_text, maybe_print_json, maybe_print_kv_fmt, maybe_print_rows, print_line,
+    url_with_query, web_url,
+};
+
+/// Content type of a question created with the Confluence Questions add-on.
+pub(crate) const QUESTION_TYPE: &str =
+    "ac:com.atlassian.confluence.plugins.confluence-questions:question";
+/// Content type of an answer to a question.
+pub(crate) const ANSWER_TYPE: &str =
+    "ac:com.atlassian.confluence.plugins.confluence-questions:answer";
+
+/// The name shown for a content `type` in tables: `question` and `answer` for Confluence
+/// Questions content, the type itself otherwise.
+pub(crate) fn display_type(content_type: &str) -> &str {
+    match content_type {
+        QUESTION_TYPE => "question",
+        ANSWER_TYPE => "answer",
+        other => other,
+    }
+}
+
+pub async fn handle(ctx: &AppContext, cmd: QuestionCommand) -> Result<()> {
+    let client = crate::context::load_client(ctx)?;
+    match cmd {
+        QuestionCommand::List(args) => question_list(&client, ctx, args).await,
+        QuestionCommand::Get(args) => question_get(&client, ctx, args).await,
+    }
+}
+
+async fn question_list(client: &ApiClient, ctx: &AppContext, args: QuestionListArgs) -> Result<()> {
+    let cql = question_cql(args.space.as_deref(), args.topic.as_deref());
+    let url = url_with_query(
+        &client.v1_url("/search"),
+        &[
+            ("cql", cql),
+            ("limit", args.limit.to_string()),
+            ("expand", "content.space,content.history".to_string()),
+        ],
+    )?;
+    let results = client
+        .get_paginated_results(url, args.all)
+        .await
+        .context("Failed to list questions (is the Confluence Questions add-on installed?)")?;
+    match args.output {
+        OutputFormat::Json => maybe_print_json(ctx, &results),
+        fmt => {
+            let rows = results.iter().map(question_row).collect();
+            maybe_print_rows(
+                ctx,
+                fmt,
+                &["ID", "Space", "Title", "Asked by", "Asked"],
+                rows,
+            );
+            Ok(())
+        }
+    }
+}
+
+fn question_cql(space: Option<&str>, topic: Option<&str>) -> String {
+    let mut cql = format!("type = \"{QUESTION_TYPE}\"");
+    if let Some(space) = space {
+        cql.push_str(&format!(" AND space = \"{}\"", escape_cql_text(space)));
+    }
+    if let Some(topic) = topic {
+        cql.push_str(&format!(" AND label = \"{}\"", escape_cql_text(topic)));
+    }
+    cql.push_str(" ORDER BY created DESC");
+    cql
+}
+
+fn question_row(item: &Value) -> Vec<String> {
+    let content = item.get("content").cloned().unwrap_or(Value::Null);
+    let (author, created) = history(&content);
+    vec![
+        json_str(&content, "id"),
+        search_result_space(item),
+        json_str(&content, "title"),
+        author,
+        created,
+    ]
+}
+
+/// Author display name and creation date from a v1 `history` expansion.
+fn history(content: &Value) -> (String, String) {
+    let history = content.get("history").cloned().unwrap_or(Value::Null);
+    let author = history
+        .get("createdBy")
+        .map(|user| json_str(user, "displayName"))
+        .unwrap_or_default();
+    (author, json_str(&history, "createdDate"))
+}
+
+fn storage_body(content: &Value) -> &str {
+    content
+        .pointer("/body/storage/value")
+        .and_then(Value::as_str)
+        .unwrap_or("")
+}
+
+async fn question_get(client: &ApiClient, ctx: &AppContext, args: QuestionGetArgs) -> Result<()> {
+    let url = url_with_query(
+        &client.v1_url(&format!("/content/{}", args.id)),
+        &[("expand", "body.storage,space,history,version".to_string())],
+    )?;
+    let (mut question, _) = client.get_json(url).await?;
+    let content_type = json_str(&question, "type");
+    if content_type != QUESTION_TYPE {
+        bail!(
+            "Content {} is a {}, not a Confluence Questions question",
+            args.id,
+            display_type(&content_type)
+        );
+    }
+
+    let url = url_with_query(
+        &client.v1_url(&format!("/content/{}/child/{ANSWER_TYPE}", args.id)),
+        &[
+            ("expand", "body.storage,history".to_string()),
+            ("limit", "50".to_string()),
+        ],
+    )?;
+    let mut answers = client.get_paginated_results(url, true).await?;
+    answers.sort_by_key(|answer| history(answer).1);
+
+    if args.output == OutputFormat::Json {
+        question["answers"] = Value::Array(answers);
+        return maybe_print_json(ctx, &question);
+    }
+
+    let (author, created) = history(&question);
+    let space = question
+        .get("space")
+        .map(|space| json_str(space, "key"))
+        .unwrap_or_default();
+    let rows = vec![
+        vec!["ID".to_string(), json_str(&question, "id")],
+        vec!["Title".to_string(), json_str(&question, "title")],
+        vec!["Space".to_string(), space],
+        vec!["Asked by".to_string(), author],
+        vec!["Asked".to_string(), created],
+        vec!["Answers".to_string(), answers.len().to_string()],
+        vec!["URL".to_string(), web_url(client.base_url(), &question)],
+    ];
+    maybe_print_kv_fmt(ctx, args.output, rows);
+
+    let to_markdown = |content: &Value| {
+        html_to_markdown_with_options(
+            storage_body(content),
+            client.base_url(),
+            MarkdownOptions::default(),
+        )
+    };
+    print_line(ctx, "");
+    print_line(ctx, to_markdown(&question)?.trim_end());
+    for answer in &answers {
+        let (author, created) = history(answer);
+        print_line(ctx, "");
+        print_line(ctx, &format!("## Answer by {author} ({created})"));
+        print_line(ctx, "");
+        print_line(ctx, to_markdown(answer)?.trim_end());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn question_cql_scopes_to_space_and_topic() {
+        assert_eq!(
+            question_cql(Some("ENG"), Some("kafka")),
+            format!(
+                "type = \"{QUESTION_TYPE}\" AND space = \"ENG\" AND label = \"kafka\" ORDER BY created DESC"
+            )
+        );
+    }
+
+    #[test]
+    fn questions_content_gets_short_type_names() {
+        assert_eq!(display_type(QUESTION_TYPE), "question");
+        assert_eq!(display_type(ANSWER_TYPE), "answer");
+        assert_eq!(display_type("page"), "page");
+    }
+}
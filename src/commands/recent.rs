@@ -0,0 +1,76 @@
+use anyhow::Result;
+use confcli::json_util::json_str;
+use confcli::output::OutputFormat;
+use serde_json::Value;
+
+use crate::cli::RecentArgs;
+use crate::commands::search::search_result_space;
+use crate::context::AppContext;
+use crate::helpers::{escape_cql_text, maybe_print_json, maybe_print_rows, url_with_query};
+
+/// How far back `recentlyViewedContent()` looks; the result limit is applied by the search.
+const RECENTLY_VIEWED_WINDOW: usize = 200;
+
+pub async fn handle(ctx: &AppContext, args: RecentArgs) -> Result<()> {
+    let client = crate::context::load_client(ctx)?;
+    let cql = recent_cql(args.mine, args.space.as_deref());
+    let url = url_with_query(
+        &client.v1_url("/search"),
+        &[("cql", cql), ("limit", args.limit.to_string())],
+    )?;
+    let (json, _) = client.get_json(url).await?;
+    let results = json
+        .get("results")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    match args.output {
+        OutputFormat::Json => maybe_print_json(ctx, &results),
+        fmt => {
+            let rows = results.iter().map(recent_row).collect();
+            maybe_print_rows(ctx, fmt, &["ID", "Space", "Title", "Modified"], rows);
+            Ok(())
+        }
+    }
+}
+
+fn recent_cql(mine: bool, space: Option<&str>) -> String {
+    let mut cql = if mine {
+        "type = page AND contributor = currentUser()".to_string()
+    } else {
+        format!("type = page AND id in recentlyViewedContent({RECENTLY_VIEWED_WINDOW})")
+    };
+    if let Some(space) = space {
+        cql.push_str(&format!(" AND space = \"{}\"", escape_cql_text(space)));
+    }
+    cql.push_str(" ORDER BY lastmodified DESC");
+    cql
+}
+
+fn recent_row(item: &Value) -> Vec<String> {
+    let content = item.get("content").cloned().unwrap_or(Value::Null);
+    vec![
+        json_str(&content, "id"),
+        search_result_space(item),
+        json_str(&content, "title"),
+        json_str(item, "friendlyLastModified"),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recent_cql_switches_between_viewed_and_edited() {
+        assert_eq!(
+            recent_cql(false, None),
+            "type = page AND id in recentlyViewedContent(200) ORDER BY lastmodified DESC"
+        );
+        assert_eq!(
+            recent_cql(true, Some("M\"FS")),
+            "type = page AND contributor = currentUser() AND space = \"M\\\"FS\" ORDER BY lastmodified DESC"
+        );
+    }
+}
@@ -0,0 +1,265 @@
+//! `report duplicates`: pairs of pages in a space whose bodies (compared as MinHash
+//! signatures of word shingles) or titles (character bigrams) are nearly the same.
+
+use anyhow::Result;
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::output::OutputFormat;
+use regex::Regex;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::LazyLock;
+
+use crate::cli::ReportDuplicatesArgs;
+use crate::context::AppContext;
+use crate::download::fetch_space_pages_with_body;
+use crate::helpers::{maybe_print_json, maybe_print_rows};
+use crate::resolve::resolve_space_id;
+
+/// Words per shingle.
+const SHINGLE_WORDS: usize = 5;
+/// MinHash signature length; the similarity estimate is within ~0.1 of the true Jaccard
+/// index for most pairs.
+const SIGNATURE_LEN: usize = 64;
+
+static TAG_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]*>").expect("TAG_RE"));
+static ENTITY_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"&[a-zA-Z0-9#]+;").expect("ENTITY_RE"));
+
+struct PageFingerprint {
+    id: String,
+    title: String,
+    /// `None` for pages without text.
+    signature: Option<[u64; SIGNATURE_LEN]>,
+    bigrams: HashSet<(char, char)>,
+}
+
+struct DuplicatePair {
+    a: usize,
+    b: usize,
+    score: f64,
+    body: f64,
+    title: f64,
+}
+
+pub(super) async fn report_duplicates(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: ReportDuplicatesArgs,
+) -> Result<()> {
+    let space_id = resolve_space_id(client, &args.space).await?;
+    let items = fetch_space_pages_with_body(client, &space_id, "storage").await?;
+    let pages: Vec<PageFingerprint> = items.iter().map(fingerprint).collect();
+    let pairs = find_duplicates(&pages, args.threshold);
+
+    match args.output {
+        OutputFormat::Json => {
+            let items: Vec<Value> = pairs
+                .iter()
+                .map(|pair| {
+                    let page =
+                        |idx: usize| json!({ "id": pages[idx].id, "title": pages[idx].title });
+                    json!({
+                        "pages": [page(pair.a), page(pair.b)],
+                        "score": round2(pair.score),
+                        "bodySimilarity": round2(pair.body),
+                        "titleSimilarity": round2(pair.title),
+                    })
+                })
+                .collect();
+            maybe_print_json(ctx, &items)
+        }
+        fmt => {
+            let percent = |value: f64| format!("{:.0}%", value * 100.0);
+            let rows = pairs
+                .iter()
+                .map(|pair| {
+                    vec![
+                        percent(pair.score),
+                        percent(pair.body),
+                        percent(pair.title),
+                        pages[pair.a].id.clone(),
+                        pages[pair.a].title.clone(),
+                        pages[pair.b].id.clone(),
+                        pages[pair.b].title.clone(),
+                    ]
+                })
+                .collect();
+            maybe_print_rows(
+                ctx,
+                fmt,
+                &["Score", "Body", "Title", "ID A", "Page A", "ID B", "Page B"],
+                rows,
+            );
+            Ok(())
+        }
+    }
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+fn fingerprint(item: &Value) -> PageFingerprint {
+    let storage = item
+        .get("body")
+        .and_then(|b| b.get("storage"))
+        .and_then(|s| s.get("value"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    let title = json_str(item, "title");
+    PageFingerprint {
+        id: json_str(item, "id"),
+        signature: signature(&shingles(&words(storage))),
+        bigrams: bigrams(&title),
+        title,
+    }
+}
+
+/// Every pair scoring at least `threshold`, best first. The score is the body similarity,
+/// pulled up halfway toward the title similarity when that is higher, so a page copied
+/// under a new title still scores 1.0 and a same-titled page needs a related body.
+fn find_duplicates(pages: &[PageFingerprint], threshold: f64) -> Vec<DuplicatePair> {
+    let mut pairs = Vec::new();
+    for a in 0..pages.len() {
+        for b in a + 1..pages.len() {
+            let body = match (&pages[a].signature, &pages[b].signature) {
+                (Some(x), Some(y)) => signature_similarity(x, y),
+                _ => 0.0,
+            };
+            let title = dice(&pages[a].bigrams, &pages[b].bigrams);
+            let score = body.max((body + title) / 2.0);
+            if score >= threshold {
+                pairs.push(DuplicatePair {
+                    a,
+                    b,
+                    score,
+                    body,
+                    title,
+                });
+            }
+        }
+    }
+    pairs.sort_by(|x, y| y.score.total_cmp(&x.score));
+    pairs
+}
+
+/// Lowercased words of a storage body's text.
+fn words(storage: &str) -> Vec<String> {
+    let text = TAG_RE.replace_all(storage, " ");
+    let text = ENTITY_RE.replace_all(&text, " ");
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Hashes of all runs of [`SHINGLE_WORDS`] consecutive words (the whole text for shorter
+/// bodies).
+fn shingles(words: &[String]) -> HashSet<u64> {
+    let hash = |shingle: &[String]| {
+        let mut hasher = DefaultHasher::new();
+        shingle.hash(&mut hasher);
+        hasher.finish()
+    };
+    if words.len() < SHINGLE_WORDS {
+        return if words.is_empty() {
+            HashSet::new()
+        } else {
+            HashSet::from([hash(words)])
+        };
+    }
+    words.windows(SHINGLE_WORDS).map(hash).collect()
+}
+
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// MinHash signature: for each of [`SIGNATURE_LEN`] hash functions, the smallest hash of
+/// any shingle.
+fn signature(shingles: &HashSet<u64>) -> Option<[u64; SIGNATURE_LEN]> {
+    if shingles.is_empty() {
+        return None;
+    }
+    let mut sig = [u64::MAX; SIGNATURE_LEN];
+    for &shingle in shingles {
+        for (i, slot) in sig.iter_mut().enumerate() {
+            let h = splitmix64(shingle ^ splitmix64(i as u64));
+            *slot = (*slot).min(h);
+        }
+    }
+    Some(sig)
+}
+
+/// Estimated Jaccard similarity of the shingle sets behind two signatures.
+fn signature_similarity(a: &[u64; SIGNATURE_LEN], b: &[u64; SIGNATURE_LEN]) -> f64 {
+    let same = a.iter().zip(b).filter(|(x, y)| x == y).count();
+    same as f64 / SIGNATURE_LEN as f64
+}
+
+fn bigrams(title: &str) -> HashSet<(char, char)> {
+    let chars: Vec<char> = title
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .collect();
+    chars.windows(2).map(|w| (w[0], w[1])).collect()
+}
+
+/// Sørensen–Dice coefficient of two bigram sets.
+fn dice(a: &HashSet<(char, char)>, b: &HashSet<(char, char)>) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    2.0 * a.intersection(b).count() as f64 / (a.len() + b.len()) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(id: &str, title: &str, body: &str) -> PageFingerprint {
+        fingerprint(&json!({ "id": id, "title": title, "body": { "storage": { "value": body } } }))
+    }
+
+    #[test]
+    fn finds_copies_and_near_copies() {
+        let text = "<p>Deploys run from the release branch every Tuesday after the change advisory board has approved the list of tickets.</p>";
+        let edited = "<p>Deploys run from the release branch every Tuesday after the change advisory board has approved the list of tickets and the on-call engineer signed off.</p>";
+        let pages = vec![
+            page("1", "Release process", text),
+            page("2", "Copy of Release process", text),
+            page("3", "Release process (old)", edited),
+            page(
+                "4",
+                "Holiday calendar",
+                "<p>Office closed on public holidays.</p>",
+            ),
+        ];
+        let pairs = find_duplicates(&pages, 0.6);
+        let found: Vec<(&str, &str)> = pairs
+            .iter()
+            .map(|p| (pages[p.a].id.as_str(), pages[p.b].id.as_str()))
+            .collect();
+        assert_eq!(found[0], ("1", "2"));
+        assert_eq!(pairs[0].body, 1.0);
+        assert!(found.contains(&("1", "3")));
+        assert!(found.iter().all(|(a, b)| *a != "4" && *b != "4"));
+    }
+
+    #[test]
+    fn empty_pages_only_match_on_title() {
+        let pages = vec![page("1", "Notes", ""), page("2", "Notes", "")];
+        let pairs = find_duplicates(&pages, 0.5);
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(pairs[0].body, 0.0);
+        assert_eq!(pairs[0].score, 0.5);
+    }
+}
@@ -0,0 +1,224 @@
+//! `report missing-attachments`: `ri:attachment` references (images, file links, view-file
+//! macros) in page bodies that point at files the owning page no longer has.
+
+use anyhow::Result;
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::markdown::decode_html_entities;
+use confcli::output::OutputFormat;
+use futures_util::stream::{self, StreamExt};
+use regex::Regex;
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+
+use crate::cli::ReportMissingAttachmentsArgs;
+use crate::context::AppContext;
+use crate::download::fetch_space_pages_with_body;
+use crate::helpers::{maybe_print_json, maybe_print_rows};
+use crate::resolve::{resolve_page_id, resolve_space_id, resolve_space_key};
+
+/// Attachment lists fetched in parallel without `--concurrency`.
+const CONCURRENCY: usize = 8;
+
+static ATTACHMENT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)<ri:attachment\b([^>]*?)(?:/>|>(.*?)</ri:attachment>)").expect("ATTACHMENT_RE")
+});
+static FILENAME_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"ri:filename="([^"]*)""#).expect("FILENAME_RE"));
+static RI_PAGE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<ri:page\b([^>]*?)/?>").expect("RI_PAGE_RE"));
+static RI_ATTR_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"ri:(content-title|space-key)="([^"]*)""#).expect("RI_ATTR_RE"));
+
+/// One attachment reference found in a storage body.
+#[derive(Debug, PartialEq, Eq)]
+struct AttachmentRef {
+    file: String,
+    /// `(space key, title)` when the file belongs to another page.
+    owner: Option<(String, String)>,
+}
+
+struct MissingAttachment {
+    id: String,
+    title: String,
+    file: String,
+    /// Id of the page the file should be attached to, when it isn't the referencing page.
+    attached_to: Option<String>,
+    problem: String,
+}
+
+pub(super) async fn report_missing_attachments(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: ReportMissingAttachmentsArgs,
+) -> Result<()> {
+    let space_id = resolve_space_id(client, &args.space).await?;
+    let space_key = resolve_space_key(client, &space_id).await?;
+    let items = fetch_space_pages_with_body(client, &space_id, "storage").await?;
+    let by_title: HashMap<String, String> = items
+        .iter()
+        .map(|item| (json_str(item, "title"), json_str(item, "id")))
+        .collect();
+
+    // (page, file, owner page id, or why the owner couldn't be found)
+    let mut checks: Vec<(&Value, String, Result<String, String>)> = Vec::new();
+    for item in &items {
+        let storage = item
+            .get("body")
+            .and_then(|b| b.get("storage"))
+            .and_then(|s| s.get("value"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        for reference in attachment_refs(storage) {
+            let owner = match reference.owner {
+                None => Ok(json_str(item, "id")),
+                Some((key, title)) if key.is_empty() || key == space_key => by_title
+                    .get(&title)
+                    .cloned()
+                    .ok_or_else(|| format!("page \"{title}\" not found")),
+                Some((key, title)) => resolve_page_id(client, &format!("{key}:{title}"))
+                    .await
+                    .map_err(|_| format!("page \"{key}:{title}\" not found")),
+            };
+            checks.push((item, reference.file, owner));
+        }
+    }
+
+    let owners: HashSet<&String> = checks
+        .iter()
+        .filter_map(|(_, _, owner)| owner.as_ref().ok())
+        .collect();
+    let attachment_lists: HashMap<String, HashSet<String>> = stream::iter(owners)
+        .map(|owner| async move {
+            let url = client.v2_url(&format!("/pages/{owner}/attachments?limit=250"));
+            let items = client.get_paginated_results(url, true).await?;
+            let titles = items.iter().map(|item| json_str(item, "title")).collect();
+            Ok::<_, anyhow::Error>((owner.clone(), titles))
+        })
+        .buffer_unordered(ctx.workers(CONCURRENCY))
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<_>>()?;
+
+    let mut missing = Vec::new();
+    for (item, file, owner) in checks {
+        let id = json_str(item, "id");
+        let (attached_to, problem) = match owner {
+            Err(problem) => (None, problem),
+            Ok(owner) => {
+                let files = &attachment_lists[&owner];
+                if files.contains(&file) {
+                    continue;
+                }
+                let problem = match files.iter().find(|f| f.eq_ignore_ascii_case(&file)) {
+                    Some(similar) => format!("not attached (found \"{similar}\")"),
+                    None => "not attached".to_string(),
+                };
+                ((owner != id).then_some(owner), problem)
+            }
+        };
+        missing.push(MissingAttachment {
+            title: json_str(item, "title"),
+            id,
+            file,
+            attached_to,
+            problem,
+        });
+    }
+
+    match args.output {
+        OutputFormat::Json => {
+            let items: Vec<Value> = missing
+                .iter()
+                .map(|m| {
+                    json!({
+                        "id": m.id,
+                        "title": m.title,
+                        "file": m.file,
+                        "attachedTo": m.attached_to,
+                        "problem": m.problem,
+                    })
+                })
+                .collect();
+            maybe_print_json(ctx, &items)
+        }
+        fmt => {
+            let rows = missing
+                .into_iter()
+                .map(|m| {
+                    vec![
+                        m.id,
+                        m.title,
+                        m.file,
+                        m.attached_to.unwrap_or_default(),
+                        m.problem,
+                    ]
+                })
+                .collect();
+            maybe_print_rows(
+                ctx,
+                fmt,
+                &["ID", "Title", "File", "Attached To", "Problem"],
+                rows,
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Attachment references in a storage body, de-duplicated in order.
+fn attachment_refs(storage: &str) -> Vec<AttachmentRef> {
+    let mut refs: Vec<AttachmentRef> = Vec::new();
+    for caps in ATTACHMENT_RE.captures_iter(storage) {
+        let Some(file) = FILENAME_RE.captures(&caps[1]) else {
+            continue;
+        };
+        let file = decode_html_entities(&file[1]);
+        let inner = caps.get(2).map(|m| m.as_str()).unwrap_or("");
+        if inner.contains("<ri:blog-post") {
+            // Blog posts can't be looked up by title here; leave them alone.
+            continue;
+        }
+        let owner = RI_PAGE_RE.captures(inner).map(|page| {
+            let mut key = String::new();
+            let mut title = String::new();
+            for attr in RI_ATTR_RE.captures_iter(&page[1]) {
+                match &attr[1] {
+                    "content-title" => title = decode_html_entities(&attr[2]),
+                    _ => key = decode_html_entities(&attr[2]),
+                }
+            }
+            (key, title)
+        });
+        let reference = AttachmentRef { file, owner };
+        if !reference.file.trim().is_empty() && !refs.contains(&reference) {
+            refs.push(reference);
+        }
+    }
+    refs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_attachment_references() {
+        let storage = r#"<ac:image><ri:attachment ri:filename="a &amp; b.png" /></ac:image><ac:link><ri:attachment ri:filename="spec.pdf"><ri:page ri:space-key="OPS" ri:content-title="Specs" /></ri:attachment></ac:link><ac:image><ri:attachment ri:filename="a &amp; b.png" /></ac:image><ri:attachment ri:filename="post.png"><ri:blog-post ri:content-title="News" ri:posting-day="2024/01/01" /></ri:attachment>"#;
+        assert_eq!(
+            attachment_refs(storage),
+            vec![
+                AttachmentRef {
+                    file: "a & b.png".to_string(),
+                    owner: None,
+                },
+                AttachmentRef {
+                    file: "spec.pdf".to_string(),
+                    owner: Some(("OPS".to_string(), "Specs".to_string())),
+                },
+            ]
+        );
+    }
+}
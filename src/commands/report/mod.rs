@@ -0,0 +1,194 @@
+use anyhow::Result;
+#[cfg(feature = "write")]
+use anyhow::anyhow;
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::output::OutputFormat;
+use futures_util::stream::{self, StreamExt};
+use serde_json::{Value, json};
+
+use crate::cli::*;
+use crate::commands::search::{search_all, search_result_space};
+use crate::context::AppContext;
+use crate::helpers::{escape_cql_text, maybe_print_json, maybe_print_rows};
+use crate::lifecycle::*;
+#[cfg(feature = "write")]
+use crate::plan::{self, Operation};
+
+mod duplicates;
+mod missing_attachments;
+
+/// Pages whose expiry is read in parallel without `--concurrency`.
+const CONCURRENCY: usize = 8;
+
+pub async fn handle(ctx: &AppContext, cmd: ReportCommand) -> Result<()> {
+    let client = crate::context::load_client(ctx)?;
+    match cmd {
+        ReportCommand::Expiring(args) => report_expiring(&client, ctx, args).await,
+        ReportCommand::Duplicates(args) => duplicates::report_duplicates(&client, ctx, args).await,
+        ReportCommand::MissingAttachments(args) => {
+            missing_attachments::report_missing_attachments(&client, ctx, args).await
+        }
+    }
+}
+
+/// One annotated page that is expired or due within the requested window.
+struct ExpiringPage {
+    id: String,
+    title: String,
+    space: String,
+    expires_on: String,
+    days_left: i64,
+    action: String,
+    applied: Option<String>,
+}
+
+async fn report_expiring(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: ReportExpiringArgs,
+) -> Result<()> {
+    let mut cql = format!("type = page AND label = \"{}\"", EXPIRY_LABEL);
+    if let Some(space) = &args.space {
+        cql.push_str(&format!(" AND space = \"{}\"", escape_cql_text(space)));
+    }
+    let results = search_all(client, &cql, 100).await?;
+
+    let today = today();
+    let cutoff = today + i64::from(args.within);
+    let mut reads = stream::iter(results)
+        .map(|item| async move {
+            let content = item.get("content").cloned().unwrap_or(Value::Null);
+            let id = json_str(&content, "id");
+            let expiry = read_expiry(client, &id).await;
+            (item, content, id, expiry)
+        })
+        .buffer_unordered(ctx.workers(CONCURRENCY));
+
+    let mut pages = Vec::new();
+    while let Some((item, content, id, expiry)) = reads.next().await {
+        let Some(expiry) = expiry? else {
+            continue;
+        };
+        let Ok(expires) = parse_date(&expiry.expires_on) else {
+            tracing::debug!(
+                "Skipping page {id}: invalid expiry date '{}'",
+                expiry.expires_on
+            );
+            continue;
+        };
+        if expires > cutoff {
+            continue;
+        }
+        pages.push(ExpiringPage {
+            title: json_str(&content, "title"),
+            space: search_result_space(&item),
+            id,
+            expires_on: expiry.expires_on,
+            days_left: expires - today,
+            action: expiry.action,
+            applied: None,
+        });
+    }
+    pages.sort_by(|a, b| a.days_left.cmp(&b.days_left).then(a.id.cmp(&b.id)));
+
+    #[cfg(feature = "write")]
+    if args.apply {
+        apply_actions(client, ctx, &mut pages).await?;
+    }
+
+    match args.output {
+        OutputFormat::Json => {
+            let items: Vec<Value> = pages
+                .iter()
+                .map(|page| {
+                    let mut item = json!({
+                        "id": page.id,
+                        "title": page.title,
+                        "space": page.space,
+                        "expiresOn": page.expires_on,
+                        "daysLeft": page.days_left,
+                        "action": page.action,
+                    });
+                    if let Some(applied) = &page.applied {
+                        item["applied"] = json!(applied);
+                    }
+                    item
+                })
+                .collect();
+            maybe_print_json(ctx, &items)
+        }
+        fmt => {
+            let rows = pages
+                .into_iter()
+                .map(|page| {
+                    let status = match page.days_left {
+                        d if d < 0 => format!("expired {} day(s) ago", -d),
+                        0 => "expires today".to_string(),
+                        d => format!("in {d} day(s)"),
+                    };
+                    vec![
+                        page.id,
+                        page.space,
+                        page.title,
+                        page.expires_on,
+                        status,
+                        page.action,
+                        page.applied.unwrap_or_default(),
+                    ]
+                })
+                .collect();
+            maybe_print_rows(
+                ctx,
+                fmt,
+                &[
+                    "ID", "Space", "Title", "Expires", "Status", "Action", "Applied",
+                ],
+                rows,
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Run the action of every page whose expiry date has arrived. Pages that are only
+/// upcoming (`--within`) are reported but left alone.
+#[cfg(feature = "write")]
+async fn apply_actions(
+    client: &ApiClient,
+    ctx: &AppContext,
+    pages: &mut [ExpiringPage],
+) -> Result<()> {
+    let mut failures = Vec::new();
+    for page in pages.iter_mut().filter(|page| page.days_left <= 0) {
+        let action = match ExpiryAction::parse(&page.action) {
+            Ok(action) => action,
+            Err(err) => {
+                failures.push(format!("{}: {err}", page.id));
+                continue;
+            }
+        };
+        let ExpiryAction::Label(name) = action else {
+            continue;
+        };
+        if ctx.dry_run {
+            plan::record(
+                Operation::new("add", "label", &page.id).with_payload(json!({ "labels": [name] })),
+            );
+            page.applied = Some(format!("would add label {name}"));
+            continue;
+        }
+        match add_label(client, &page.id, &name).await {
+            Ok(()) => page.applied = Some(format!("added label {name}")),
+            Err(err) => failures.push(format!("{}: {err:#}", page.id)),
+        }
+    }
+    if !failures.is_empty() {
+        return Err(anyhow!(
+            "Failed to apply expiry action to {} page(s): {}",
+            failures.len(),
+            failures.join("; ")
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use anyhow::Result;
+use confcli::output::OutputFormat;
+use serde_json::json;
+use url::Url;
+
+use crate::cli::{ResolveArgs, ResolveKind};
+use crate::context::AppContext;
+use crate::helpers::{maybe_print_json, print_line};
+use crate::resolve::{resolve_page_id, resolve_space_id};
+
+pub async fn handle(ctx: &AppContext, args: ResolveArgs) -> Result<()> {
+    let client = crate::context::load_client(ctx)?;
+    let kind = match args.kind {
+        ResolveKind::Auto => guess_kind(&args.reference),
+        other => other,
+    };
+
+    let (id, kind_name) = match kind {
+        ResolveKind::Space => (resolve_space_id(&client, &args.reference).await?, "space"),
+        _ => (resolve_page_id(&client, &args.reference).await?, "page"),
+    };
+
+    match args.output {
+        OutputFormat::Json => maybe_print_json(ctx, &json!({ "id": id, "type": kind_name })),
+        _ => {
+            print_line(ctx, &id);
+            Ok(())
+        }
+    }
+}
+
+/// Bare keys (`MFS`, `~jdoe`) are spaces; ids, URLs, and `SPACE:Title` are pages.
+fn guess_kind(reference: &str) -> ResolveKind {
+    let reference = reference.trim();
+    if reference.is_empty()
+        || reference.chars().all(|c| c.is_ascii_digit())
+        || reference.contains(':')
+        || Url::parse(reference).is_ok()
+    {
+        ResolveKind::Page
+    } else {
+        ResolveKind::Space
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guess_kind_treats_bare_keys_as_spaces() {
+        assert_eq!(guess_kind("MFS"), ResolveKind::Space);
+        assert_eq!(guess_kind("~jdoe"), ResolveKind::Space);
+        assert_eq!(guess_kind("12345"), ResolveKind::Page);
+        assert_eq!(guess_kind("MFS:Overview"), ResolveKind::Page);
+        assert_eq!(
+            guess_kind("https://example.atlassian.net/wiki/spaces/MFS/pages/1/T"),
+            ResolveKind::Page
+        );
+    }
+}
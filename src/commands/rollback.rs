@@ -0,0 +1,227 @@
+//! Journals of pages created by `copy-tree` (`--journal`, `--rollback-on-error`) and
+//! `confcli rollback`, which trashes the pages recorded in one.
+
+use anyhow::{Context, Result};
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::output::OutputFormat;
+use serde_json::{Value, json};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::cli::RollbackArgs;
+use crate::context::AppContext;
+use crate::helpers::{confirm_delete, maybe_print_json, maybe_print_rows, print_line};
+use crate::plan::{self, Operation};
+
+/// Append-only record of created pages, one JSON object per line. Each line is written as
+/// soon as its page exists, so the journal stays usable when the process dies mid-run.
+pub struct Journal {
+    file: File,
+    path: PathBuf,
+    /// Site the pages were created on, so `rollback` can pick the matching profile.
+    site: String,
+}
+
+impl Journal {
+    pub fn create(path: &Path, site: &str) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create journal {}", path.display()))?;
+        Ok(Self {
+            file,
+            path: path.to_path_buf(),
+            site: site.to_string(),
+        })
+    }
+
+    /// Record a page from a create response.
+    pub fn record(&mut self, page: &Value) -> Result<()> {
+        let entry = json!({
+            "id": json_str(page, "id"),
+            "title": json_str(page, "title"),
+            "spaceId": json_str(page, "spaceId"),
+            "parentId": json_str(page, "parentId"),
+            "site": self.site,
+        });
+        writeln!(self.file, "{entry}")
+            .with_context(|| format!("Failed to write journal {}", self.path.display()))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct JournalContents {
+    /// Site recorded by the run, if any (journals are always written with one).
+    site: Option<String>,
+    /// `(id, title)` of every page, in creation order.
+    pages: Vec<(String, String)>,
+}
+
+fn read_journal(path: &Path) -> Result<JournalContents> {
+    let data = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read journal {}", path.display()))?;
+    let mut site = None;
+    let mut pages = Vec::new();
+    for (idx, line) in data.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: Value = serde_json::from_str(line)
+            .with_context(|| format!("Invalid journal line {}: {}", idx + 1, path.display()))?;
+        let id = json_str(&entry, "id");
+        if id.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Journal line {} has no page id: {}",
+                idx + 1,
+                path.display()
+            ));
+        }
+        let entry_site = json_str(&entry, "site");
+        if !entry_site.is_empty() {
+            site.get_or_insert(entry_site);
+        }
+        pages.push((id, json_str(&entry, "title")));
+    }
+    Ok(JournalContents { site, pages })
+}
+
+/// Move pages to the trash, newest first so children go before their parents. Returns the
+/// outcome for each page in that order.
+pub async fn trash_pages(client: &ApiClient, ids: &[String]) -> Vec<(String, Result<()>)> {
+    let mut results = Vec::with_capacity(ids.len());
+    for id in ids.iter().rev() {
+        let url = client.v2_url(&format!("/pages/{id}"));
+        let res = client
+            .delete(url)
+            .await
+            .with_context(|| format!("Failed to trash page {id}"));
+        results.push((id.clone(), res));
+    }
+    results
+}
+
+pub async fn handle(ctx: &AppContext, args: RollbackArgs) -> Result<()> {
+    let JournalContents { site, pages } = read_journal(&args.journal)?;
+    // The pages may live on another profile's site (`copy-tree --target-profile`).
+    let client = match &site {
+        Some(site) => crate::context::load_client_for_site(ctx, site)?,
+        None => crate::context::load_client(ctx)?,
+    };
+    if !ctx.dry_run {
+        client.ensure_writable()?;
+    }
+    rollback(&client, ctx, args, pages).await
+}
+
+async fn rollback(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: RollbackArgs,
+    pages: Vec<(String, String)>,
+) -> Result<()> {
+    if pages.is_empty() {
+        print_line(ctx, "Journal is empty; nothing to roll back.");
+        return Ok(());
+    }
+
+    if ctx.dry_run {
+        for (id, title) in pages.iter().rev() {
+            plan::record(
+                Operation::new("trash", "page", id).with_payload(json!({ "title": title })),
+            );
+            print_line(ctx, &format!("Would trash page {id} '{title}'"));
+        }
+        return Ok(());
+    }
+
+    if !args.yes
+        && !confirm_delete(&format!(
+            "Trash {} page(s) recorded in {}?",
+            pages.len(),
+            args.journal.display()
+        ))?
+    {
+        print_line(ctx, "Cancelled.");
+        return Ok(());
+    }
+
+    let ids: Vec<String> = pages.iter().map(|(id, _)| id.clone()).collect();
+    let results = trash_pages(client, &ids).await;
+    let title_of = |id: &str| {
+        pages
+            .iter()
+            .find(|(page_id, _)| page_id == id)
+            .map(|(_, title)| title.clone())
+            .unwrap_or_default()
+    };
+    let failed = results.iter().filter(|(_, res)| res.is_err()).count();
+
+    match args.output {
+        OutputFormat::Json => {
+            let items: Vec<Value> = results
+                .iter()
+                .map(|(id, res)| {
+                    json!({
+                        "id": id,
+                        "title": title_of(id),
+                        "trashed": res.is_ok(),
+                        "error": res.as_ref().err().map(|err| format!("{err:#}")),
+                    })
+                })
+                .collect();
+            maybe_print_json(ctx, &items)?;
+        }
+        fmt => {
+            let rows = results
+                .iter()
+                .map(|(id, res)| {
+                    let outcome = match res {
+                        Ok(()) => "trashed".to_string(),
+                        Err(err) => format!("{err:#}"),
+                    };
+                    vec![id.clone(), title_of(id), outcome]
+                })
+                .collect();
+            maybe_print_rows(ctx, fmt, &["ID", "Title", "Result"], rows);
+        }
+    }
+
+    if failed > 0 {
+        return Err(anyhow::anyhow!(
+            "{failed} of {} page(s) could not be trashed",
+            results.len()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn journal_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("copy.jsonl");
+        let mut journal = Journal::create(&path, "https://x.test/wiki").unwrap();
+        journal
+            .record(&json!({ "id": "10", "title": "Root (Copy)", "spaceId": "1", "parentId": "5" }))
+            .unwrap();
+        journal
+            .record(
+                &json!({ "id": "11", "title": "Child (Copy)", "spaceId": "1", "parentId": "10" }),
+            )
+            .unwrap();
+        drop(journal);
+        assert_eq!(
+            read_journal(&path).unwrap(),
+            JournalContents {
+                site: Some("https://x.test/wiki".to_string()),
+                pages: vec![
+                    ("10".to_string(), "Root (Copy)".to_string()),
+                    ("11".to_string(), "Child (Copy)".to_string()),
+                ],
+            }
+        );
+    }
+}
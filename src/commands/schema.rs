@@ -0,0 +1,23 @@
+use anyhow::Result;
+use confcli::schema::{SCHEMA_COMMANDS, schema_for};
+
+use crate::cli::SchemaArgs;
+use crate::context::AppContext;
+use crate::helpers::{maybe_print_json, print_line};
+
+pub fn handle(ctx: &AppContext, args: SchemaArgs) -> Result<()> {
+    if args.command.is_empty() {
+        for command in SCHEMA_COMMANDS {
+            print_line(ctx, command);
+        }
+        return Ok(());
+    }
+
+    let command = args.command.join(" ");
+    let schema = schema_for(&command).ok_or_else(|| {
+        anyhow::anyhow!(
+            "No schema for '{command}'. Run `confcli schema` to list supported commands."
+        )
+    })?;
+    maybe_print_json(ctx, &schema)
+}
@@ -2,32 +2,50 @@ use anyhow::Result;
 use confcli::client::ApiClient;
 use confcli::json_util::json_str;
 use confcli::output::OutputFormat;
+use futures_util::future::join_all;
 use regex::Regex;
 use serde_json::Value;
 use std::sync::LazyLock;
+use url::Url;
 
 use crate::cli::SearchCommand;
+use crate::commands::question::display_type;
 use crate::context::AppContext;
-use crate::helpers::{maybe_print_json, maybe_print_rows, url_with_query};
+use crate::helpers::{
+    add_web_urls, escape_cql_text, maybe_print_json, maybe_print_rows, url_with_query,
+    with_url_column,
+};
 
 pub async fn handle(ctx: &AppContext, cmd: SearchCommand) -> Result<()> {
     if cmd.query.trim().is_empty() {
         return Err(anyhow::anyhow!("Search query cannot be empty"));
     }
-    let client = crate::context::load_client(ctx)?;
     let mut cql = to_cql_query(&cmd.query);
-    if let Some(space) = cmd.space {
+    if let Some(space) = &cmd.space {
         // Always quote + escape the space key to avoid CQL injection and to support keys like "~user".
-        let space = escape_cql_text(&space);
+        let space = escape_cql_text(space);
         cql = format!("space = \"{space}\" AND ({cql})");
     }
+    if cmd.all_profiles {
+        return search_all_profiles(ctx, &cmd, &cql).await;
+    }
+    let client = crate::context::load_client(ctx)?;
+    let base_url = client.base_url();
     if cmd.all {
-        let results = search_all(&client, &cql, cmd.limit).await?;
+        let mut results = search_all(&client, &cql, cmd.limit).await?;
+        add_web_urls(base_url, &mut results);
         match cmd.output {
             OutputFormat::Json => maybe_print_json(ctx, &results),
             fmt => {
                 let rows = results.iter().map(search_result_row).collect();
-                maybe_print_rows(ctx, fmt, &["ID", "Type", "Space", "Title"], rows);
+                let (headers, rows) = with_url_column(
+                    cmd.show_url,
+                    base_url,
+                    &["ID", "Type", "Space", "Title"],
+                    &results,
+                    rows,
+                );
+                maybe_print_rows(ctx, fmt, &headers, rows);
                 Ok(())
             }
         }
@@ -36,7 +54,10 @@ pub async fn handle(ctx: &AppContext, cmd: SearchCommand) -> Result<()> {
             &client.v1_url("/search"),
             &[("cql", cql), ("limit", cmd.limit.to_string())],
         )?;
-        let (json, _) = client.get_json(url).await?;
+        let (mut json, _) = client.get_json(url).await?;
+        if let Some(results) = json.get_mut("results").and_then(|v| v.as_array_mut()) {
+            add_web_urls(base_url, results);
+        }
         match cmd.output {
             OutputFormat::Json => maybe_print_json(ctx, &json),
             fmt => {
@@ -46,16 +67,119 @@ pub async fn handle(ctx: &AppContext, cmd: SearchCommand) -> Result<()> {
                     .cloned()
                     .unwrap_or_default();
                 let rows = results.iter().map(search_result_row).collect();
-                maybe_print_rows(ctx, fmt, &["ID", "Type", "Space", "Title"], rows);
+                let (headers, rows) = with_url_column(
+                    cmd.show_url,
+                    base_url,
+                    &["ID", "Type", "Space", "Title"],
+                    &results,
+                    rows,
+                );
+                maybe_print_rows(ctx, fmt, &headers, rows);
                 Ok(())
             }
         }
     }
 }
 
+/// Run the same query against every stored profile concurrently. Profiles that fail are
+/// reported on stderr and skipped, unless all of them fail.
+async fn search_all_profiles(ctx: &AppContext, cmd: &SearchCommand, cql: &str) -> Result<()> {
+    let clients = crate::context::load_profile_clients(ctx)?;
+    let searches = clients.iter().map(|(profile, client)| async move {
+        let results = if cmd.all {
+            search_all(client, cql, cmd.limit).await
+        } else {
+            search_page(client, cql, cmd.limit).await
+        };
+        (profile, client, results)
+    });
+
+    let mut merged: Vec<Value> = Vec::new();
+    let mut errors = Vec::new();
+    for (profile, client, results) in join_all(searches).await {
+        match results {
+            Ok(mut results) => {
+                add_web_urls(client.base_url(), &mut results);
+                let site = site_host(client.base_url());
+                for mut item in results {
+                    if let Some(obj) = item.as_object_mut() {
+                        obj.insert("profile".to_string(), Value::String(profile.clone()));
+                        obj.insert("site".to_string(), Value::String(site.clone()));
+                    }
+                    merged.push(item);
+                }
+            }
+            Err(err) => {
+                tracing::warn!("Search failed for profile '{profile}': {err:#}");
+                errors.push(err);
+            }
+        }
+    }
+    if errors.len() == clients.len()
+        && let Some(err) = errors.into_iter().next()
+    {
+        return Err(err.context("Search failed for every profile"));
+    }
+
+    match cmd.output {
+        OutputFormat::Json => maybe_print_json(ctx, &merged),
+        fmt => {
+            let rows = merged
+                .iter()
+                .map(|item| {
+                    let mut row = vec![json_str(item, "site")];
+                    row.extend(search_result_row(item));
+                    row
+                })
+                .collect();
+            // URLs are already absolute, so the base URL is unused here.
+            let (headers, rows) = with_url_column(
+                cmd.show_url,
+                "",
+                &["Site", "ID", "Type", "Space", "Title"],
+                &merged,
+                rows,
+            );
+            maybe_print_rows(ctx, fmt, &headers, rows);
+            Ok(())
+        }
+    }
+}
+
+pub(crate) async fn search_page(client: &ApiClient, cql: &str, limit: usize) -> Result<Vec<Value>> {
+    let url = url_with_query(
+        &client.v1_url("/search"),
+        &[("cql", cql.to_string()), ("limit", limit.to_string())],
+    )?;
+    let (json, _) = client.get_json(url).await?;
+    Ok(json
+        .get("results")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default())
+}
+
+fn site_host(base_url: &str) -> String {
+    Url::parse(base_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| base_url.to_string())
+}
+
 fn search_result_row(item: &Value) -> Vec<String> {
     let content = item.get("content").cloned().unwrap_or(Value::Null);
-    let space = content
+    vec![
+        json_str(&content, "id"),
+        display_type(&json_str(&content, "type")).to_string(),
+        search_result_space(item),
+        json_str(&content, "title"),
+    ]
+}
+
+/// Space key of a v1 search result, falling back to the container title for personal spaces.
+pub(crate) fn search_result_space(item: &Value) -> String {
+    let content = item.get("content").cloned().unwrap_or(Value::Null);
+    content
         .get("space")
         .and_then(|s| s.get("key"))
         .and_then(|v| v.as_str())
@@ -81,20 +205,7 @@ fn search_result_row(item: &Value) -> Vec<String> {
                     .map(|s| s.to_string())
             }
         })
-        .unwrap_or_default();
-    vec![
-        json_str(&content, "id"),
-        json_str(&content, "type"),
-        space,
-        json_str(&content, "title"),
-    ]
-}
-
-fn escape_cql_text(value: &str) -> String {
-    value
-        .replace('\\', "\\\\")
-        .replace('"', "\\\"")
-        .replace(['\n', '\r', '\t'], " ")
+        .unwrap_or_default()
 }
 
 static CQL_KEYWORD_RE: LazyLock<Regex> =
@@ -102,7 +213,7 @@ static CQL_KEYWORD_RE: LazyLock<Regex> =
 
 static CQL_FIELD_OP_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\w+\s*[=~!<>]").unwrap());
 
-fn to_cql_query(query: &str) -> String {
+pub(crate) fn to_cql_query(query: &str) -> String {
     let has_keyword = CQL_KEYWORD_RE.is_match(query);
     let has_field_op = CQL_FIELD_OP_RE.is_match(query);
     let has_parens = query.contains('(') && query.contains(')');
@@ -119,7 +230,7 @@ fn to_cql_query(query: &str) -> String {
 /// Note: The v1 search API uses offset-based pagination (`start` parameter).
 /// Under concurrent modifications, results may be duplicated or skipped as
 /// content shifts between pages. There is no cursor-based alternative in v1.
-async fn search_all(client: &ApiClient, cql: &str, limit: usize) -> Result<Vec<Value>> {
+pub(crate) async fn search_all(client: &ApiClient, cql: &str, limit: usize) -> Result<Vec<Value>> {
     if limit == 0 {
         return Err(anyhow::anyhow!("--limit must be at least 1"));
     }
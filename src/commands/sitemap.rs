@@ -0,0 +1,132 @@
+use anyhow::Result;
+use confcli::json_util::json_str;
+use confcli::markdown::escape_xml;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::cli::{SitemapArgs, SitemapFormat};
+use crate::commands::search::{search_all, search_result_space};
+use crate::context::AppContext;
+use crate::helpers::{escape_cql_text, maybe_print_json, print_line, web_url};
+
+/// URLs a single sitemap file may hold, per the sitemaps.org protocol.
+const SITEMAP_URL_LIMIT: usize = 50_000;
+
+/// One content item of the inventory.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    id: String,
+    #[serde(rename = "type")]
+    kind: String,
+    title: String,
+    space: String,
+    url: String,
+    last_modified: String,
+}
+
+pub async fn handle(ctx: &AppContext, args: SitemapArgs) -> Result<()> {
+    let client = crate::context::load_client(ctx)?;
+    let mut cql = "type in (page, blogpost, attachment)".to_string();
+    if let Some(space) = &args.space {
+        cql.push_str(&format!(" AND space = \"{}\"", escape_cql_text(space)));
+    }
+    // Creation order doesn't shift while people edit, which keeps offset pagination stable.
+    cql.push_str(" ORDER BY created ASC");
+    let results = search_all(&client, &cql, 100).await?;
+
+    let entries: Vec<Entry> = results
+        .iter()
+        .map(|item| entry(client.base_url(), item))
+        .filter(|entry| !entry.url.is_empty())
+        .collect();
+    match args.output {
+        SitemapFormat::Json => maybe_print_json(ctx, &entries),
+        SitemapFormat::Xml => {
+            if entries.len() > SITEMAP_URL_LIMIT {
+                tracing::warn!(
+                    "{} URLs exceed the {SITEMAP_URL_LIMIT} a sitemap file may hold; split it with --space",
+                    entries.len()
+                );
+            }
+            print_line(ctx, &to_xml(&entries));
+            Ok(())
+        }
+    }
+}
+
+fn entry(base_url: &str, item: &Value) -> Entry {
+    let content = item.get("content").unwrap_or(&Value::Null);
+    Entry {
+        id: json_str(content, "id"),
+        kind: json_str(content, "type"),
+        title: json_str(content, "title"),
+        space: search_result_space(item),
+        url: web_url(base_url, item),
+        last_modified: json_str(item, "lastModified"),
+    }
+}
+
+/// A sitemaps.org `urlset`; `lastmod` is left out where the search result had no timestamp.
+fn to_xml(entries: &[Entry]) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    for entry in entries {
+        out.push_str(&format!(
+            "  <url>\n    <loc>{}</loc>\n",
+            escape_xml(&entry.url)
+        ));
+        if !entry.last_modified.is_empty() {
+            out.push_str(&format!(
+                "    <lastmod>{}</lastmod>\n",
+                escape_xml(&entry.last_modified)
+            ));
+        }
+        out.push_str("  </url>\n");
+    }
+    out.push_str("</urlset>");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn builds_a_urlset_with_escaped_locations() {
+        let base = "https://example.atlassian.net/wiki";
+        let items = [
+            json!({
+                "content": {
+                    "id": "1",
+                    "type": "page",
+                    "title": "Home",
+                    "space": { "key": "MFS" },
+                    "_links": { "webui": "/spaces/MFS/pages/1/Home" },
+                },
+                "lastModified": "2024-05-01T10:00:00.000Z",
+            }),
+            json!({
+                "content": {
+                    "id": "att2",
+                    "type": "attachment",
+                    "title": "a.png",
+                    "_links": { "webui": "/pages/viewpageattachments.action?pageId=1&preview=a.png" },
+                },
+            }),
+        ];
+        let entries: Vec<Entry> = items.iter().map(|item| entry(base, item)).collect();
+        assert_eq!(entries[0].space, "MFS");
+        assert_eq!(
+            to_xml(&entries),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n  \
+             <url>\n    <loc>https://example.atlassian.net/wiki/spaces/MFS/pages/1/Home</loc>\n    \
+             <lastmod>2024-05-01T10:00:00.000Z</lastmod>\n  </url>\n  \
+             <url>\n    <loc>https://example.atlassian.net/wiki/pages/viewpageattachments.action?pageId=1&amp;preview=a.png</loc>\n  </url>\n\
+             </urlset>"
+        );
+    }
+}
@@ -0,0 +1,517 @@
+//! `confcli space bootstrap`: "space as code" from a declarative YAML manifest.
+//!
+//! ```yaml
+//! space:
+//!   key: ENG
+//!   name: Engineering
+//!   description: Team space
+//! pages:
+//!   - title: Runbooks
+//!     body: "<p>Start here.</p>"      # storage format
+//!     labels: [ops]
+//!     children:
+//!       - title: On-call
+//!         body_file: oncall.xml       # relative to the manifest
+//! permissions:
+//!   - group: eng
+//!     operations: [read:space, create:page]
+//! ```
+//!
+//! Everything is looked up before it is created, so re-running a manifest is a no-op apart
+//! from whatever is still missing. Existing pages are never modified.
+
+use anyhow::{Context, Result, anyhow};
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::output::OutputFormat;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::cli::{SpaceBootstrapArgs, parse_space_key};
+use crate::context::AppContext;
+use crate::helpers::{maybe_print_json, maybe_print_rows, url_with_query};
+use crate::plan::{self, Operation};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    space: SpaceSpec,
+    #[serde(default)]
+    pages: Vec<PageSpec>,
+    #[serde(default)]
+    permissions: Vec<PermissionSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SpaceSpec {
+    key: String,
+    name: String,
+    description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PageSpec {
+    title: String,
+    body: Option<String>,
+    body_file: Option<PathBuf>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    children: Vec<PageSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PermissionSpec {
+    group: Option<String>,
+    user: Option<String>,
+    /// `operation:target`, e.g. `read:space`, `create:page`, `delete:comment`.
+    operations: Vec<String>,
+}
+
+/// A single permission grant, keyed the same way the v1 API reports existing ones.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Grant {
+    subject_type: &'static str,
+    identifier: String,
+    operation: String,
+    target: String,
+}
+
+/// One line of the bootstrap report.
+struct Step {
+    kind: &'static str,
+    target: String,
+    status: &'static str,
+    id: String,
+}
+
+impl Step {
+    fn new(kind: &'static str, target: impl Into<String>, status: &'static str, id: &str) -> Self {
+        Self {
+            kind,
+            target: target.into(),
+            status,
+            id: id.to_string(),
+        }
+    }
+}
+
+pub(super) async fn space_bootstrap(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: SpaceBootstrapArgs,
+) -> Result<()> {
+    let manifest = load_manifest(&args.manifest)?;
+    let base_dir = args
+        .manifest
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let key = manifest.space.key.clone();
+    let mut steps = Vec::new();
+
+    let space_id = match find_space_id(client, &key).await? {
+        Some(id) => {
+            steps.push(Step::new("space", &key, "exists", &id));
+            Some(id)
+        }
+        None if ctx.dry_run => {
+            plan::record(Operation::new("create", "space", &key).with_payload(json!({
+                "name": manifest.space.name,
+                "description": manifest.space.description,
+            })));
+            steps.push(Step::new("space", &key, "would create", ""));
+            None
+        }
+        None => {
+            let id = create_space(client, &manifest.space).await?;
+            steps.push(Step::new("space", &key, "created", &id));
+            Some(id)
+        }
+    };
+
+    for page in &manifest.pages {
+        bootstrap_page(
+            client,
+            ctx,
+            &base_dir,
+            space_id.as_deref(),
+            None,
+            page,
+            &mut steps,
+        )
+        .await?;
+    }
+
+    let grants = manifest_grants(&manifest.permissions)?;
+    if !grants.is_empty() {
+        let existing = if space_id.is_some() {
+            existing_grants(client, &key).await?
+        } else {
+            HashSet::new()
+        };
+        for grant in grants {
+            let label = format!(
+                "{} {} {}:{}",
+                grant.subject_type, grant.identifier, grant.operation, grant.target
+            );
+            if existing.contains(&grant) {
+                steps.push(Step::new("permission", label, "exists", ""));
+            } else if ctx.dry_run {
+                plan::record(
+                    Operation::new("add", "permission", &key).with_payload(json!({
+                        "subjectType": grant.subject_type,
+                        "identifier": grant.identifier,
+                        "operation": grant.operation,
+                        "target": grant.target,
+                    })),
+                );
+                steps.push(Step::new("permission", label, "would create", ""));
+            } else {
+                add_grant(client, &key, &grant).await?;
+                steps.push(Step::new("permission", label, "created", ""));
+            }
+        }
+    }
+
+    match args.output {
+        OutputFormat::Json => {
+            let items: Vec<Value> = steps
+                .iter()
+                .map(|step| {
+                    json!({
+                        "kind": step.kind,
+                        "target": step.target,
+                        "status": step.status,
+                        "id": step.id,
+                    })
+                })
+                .collect();
+            maybe_print_json(ctx, &items)
+        }
+        fmt => {
+            let rows = steps
+                .into_iter()
+                .map(|step| {
+                    vec![
+                        step.kind.to_string(),
+                        step.target,
+                        step.status.to_string(),
+                        step.id,
+                    ]
+                })
+                .collect();
+            maybe_print_rows(ctx, fmt, &["Kind", "Target", "Status", "ID"], rows);
+            Ok(())
+        }
+    }
+}
+
+fn load_manifest(path: &Path) -> Result<Manifest> {
+    let data = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read manifest: {}", path.display()))?;
+    let manifest =
+        parse_manifest(&data).with_context(|| format!("Invalid manifest: {}", path.display()))?;
+    Ok(manifest)
+}
+
+fn parse_manifest(data: &str) -> Result<Manifest> {
+    let manifest: Manifest = serde_yaml_ng::from_str(data)?;
+    parse_space_key(&manifest.space.key).map_err(|err| anyhow!("space.key: {err}"))?;
+    validate_pages(&manifest.pages, &mut HashSet::new())?;
+    manifest_grants(&manifest.permissions)?;
+    Ok(manifest)
+}
+
+fn validate_pages<'a>(pages: &'a [PageSpec], seen: &mut HashSet<&'a str>) -> Result<()> {
+    for page in pages {
+        if page.title.trim().is_empty() {
+            return Err(anyhow!("Page titles cannot be empty"));
+        }
+        if page.body.is_some() && page.body_file.is_some() {
+            return Err(anyhow!(
+                "Page '{}': use either body or body_file, not both",
+                page.title
+            ));
+        }
+        // Titles are unique per space, so a duplicate would silently collapse into one page.
+        if !seen.insert(page.title.trim()) {
+            return Err(anyhow!("Duplicate page title '{}'", page.title));
+        }
+        validate_pages(&page.children, seen)?;
+    }
+    Ok(())
+}
+
+fn manifest_grants(permissions: &[PermissionSpec]) -> Result<Vec<Grant>> {
+    let mut grants = Vec::new();
+    for permission in permissions {
+        let (subject_type, identifier) = match (&permission.group, &permission.user) {
+            (Some(group), None) => ("group", group.clone()),
+            (None, Some(user)) => ("user", user.clone()),
+            _ => {
+                return Err(anyhow!(
+                    "Each permission needs exactly one of group or user"
+                ));
+            }
+        };
+        for op in &permission.operations {
+            let (operation, target) = op.split_once(':').ok_or_else(|| {
+                anyhow!("Invalid operation '{op}': use operation:target, e.g. read:space")
+            })?;
+            grants.push(Grant {
+                subject_type,
+                identifier: identifier.clone(),
+                operation: operation.trim().to_string(),
+                target: target.trim().to_string(),
+            });
+        }
+    }
+    Ok(grants)
+}
+
+async fn find_space_id(client: &ApiClient, key: &str) -> Result<Option<String>> {
+    let url = url_with_query(
+        &client.v2_url("/spaces"),
+        &[("keys", key.to_string()), ("limit", "1".to_string())],
+    )?;
+    let items = client.get_paginated_results(url, false).await?;
+    Ok(items.first().map(|item| json_str(item, "id")))
+}
+
+async fn create_space(client: &ApiClient, space: &SpaceSpec) -> Result<String> {
+    let mut payload = json!({ "key": space.key, "name": space.name });
+    if let Some(desc) = &space.description {
+        payload["description"] = json!({
+            "plain": { "value": desc, "representation": "plain" }
+        });
+    }
+    // v1, as in `space create`: the v2 endpoint ignores the description.
+    let result = client.post_json(client.v1_url("/space"), payload).await?;
+    Ok(json_str(&result, "id"))
+}
+
+fn bootstrap_page<'a>(
+    client: &'a ApiClient,
+    ctx: &'a AppContext,
+    base_dir: &'a Path,
+    space_id: Option<&'a str>,
+    parent_id: Option<&'a str>,
+    page: &'a PageSpec,
+    steps: &'a mut Vec<Step>,
+) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + 'a>> {
+    Box::pin(async move {
+        let title = page.title.trim();
+        let existing = match space_id {
+            Some(space_id) => find_page_id(client, space_id, title).await?,
+            None => None,
+        };
+
+        let page_id = match existing {
+            Some(id) => {
+                steps.push(Step::new("page", title, "exists", &id));
+                Some(id)
+            }
+            None if ctx.dry_run => {
+                plan::record(Operation::new("create", "page", title).with_payload(json!({
+                    "parentId": parent_id,
+                    "bodyFile": page.body_file,
+                })));
+                steps.push(Step::new("page", title, "would create", ""));
+                None
+            }
+            None => {
+                let space_id = space_id.context("Space id missing after creation")?;
+                let body = page_body(base_dir, page)?;
+                let mut payload = json!({
+                    "spaceId": space_id,
+                    "title": title,
+                    "status": "current",
+                    "body": { "representation": "storage", "value": body },
+                });
+                if let Some(parent_id) = parent_id {
+                    payload["parentId"] = json!(parent_id);
+                }
+                let result = client.post_json(client.v2_url("/pages"), payload).await?;
+                let id = json_str(&result, "id");
+                steps.push(Step::new("page", title, "created", &id));
+                Some(id)
+            }
+        };
+
+        if !page.labels.is_empty() {
+            let target = format!("{title}: {}", page.labels.join(", "));
+            match &page_id {
+                Some(id) if !ctx.dry_run => {
+                    // Adding a label that is already present is a no-op on the server.
+                    let body: Value = page
+                        .labels
+                        .iter()
+                        .map(|name| json!({ "prefix": "global", "name": name }))
+                        .collect::<Vec<_>>()
+                        .into();
+                    client
+                        .post_json(client.v1_url(&format!("/content/{id}/label")), body)
+                        .await?;
+                    steps.push(Step::new("labels", target, "applied", id));
+                }
+                _ => {
+                    let labeled = page_id.as_deref().unwrap_or(title);
+                    plan::record(
+                        Operation::new("add", "label", labeled)
+                            .with_payload(json!({ "labels": page.labels })),
+                    );
+                    steps.push(Step::new("labels", target, "would apply", ""));
+                }
+            }
+        }
+
+        for child in &page.children {
+            bootstrap_page(
+                client,
+                ctx,
+                base_dir,
+                space_id,
+                page_id.as_deref(),
+                child,
+                steps,
+            )
+            .await?;
+        }
+        Ok(())
+    })
+}
+
+async fn find_page_id(client: &ApiClient, space_id: &str, title: &str) -> Result<Option<String>> {
+    let url = url_with_query(
+        &client.v2_url("/pages"),
+        &[
+            ("space-id", space_id.to_string()),
+            ("title", title.to_string()),
+            ("limit", "1".to_string()),
+        ],
+    )?;
+    let items = client.get_paginated_results(url, false).await?;
+    Ok(items.first().map(|item| json_str(item, "id")))
+}
+
+fn page_body(base_dir: &Path, page: &PageSpec) -> Result<String> {
+    match (&page.body, &page.body_file) {
+        (Some(body), _) => Ok(body.clone()),
+        (None, Some(file)) => {
+            let path = base_dir.join(file);
+            std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read body_file: {}", path.display()))
+        }
+        (None, None) => Ok(String::new()),
+    }
+}
+
+async fn existing_grants(client: &ApiClient, key: &str) -> Result<HashSet<Grant>> {
+    let url = url_with_query(
+        &client.v1_url(&format!("/space/{}", urlencoding::encode(key))),
+        &[("expand", "permissions".to_string())],
+    )?;
+    let (json, _) = client.get_json(url).await?;
+    let mut grants = HashSet::new();
+    let permissions = json
+        .get("permissions")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+    for permission in &permissions {
+        let Some(op) = permission.get("operation") else {
+            continue;
+        };
+        let operation = json_str(op, "operation");
+        let target = json_str(op, "targetType");
+        let subjects = permission.get("subjects");
+        for (subject_type, field) in [("group", "name"), ("user", "accountId")] {
+            let results = subjects
+                .and_then(|s| s.get(subject_type))
+                .and_then(|s| s.get("results"))
+                .and_then(|r| r.as_array());
+            for subject in results.into_iter().flatten() {
+                grants.insert(Grant {
+                    subject_type,
+                    identifier: json_str(subject, field),
+                    operation: operation.clone(),
+                    target: target.clone(),
+                });
+            }
+        }
+    }
+    Ok(grants)
+}
+
+async fn add_grant(client: &ApiClient, key: &str, grant: &Grant) -> Result<()> {
+    let url = client.v1_url(&format!("/space/{}/permission", urlencoding::encode(key)));
+    let payload = json!({
+        "subject": { "type": grant.subject_type, "identifier": grant.identifier },
+        "operation": { "key": grant.operation, "target": grant.target },
+    });
+    client.post_json(url, payload).await.with_context(|| {
+        format!(
+            "Failed to grant {}:{} to {} {}",
+            grant.operation, grant.target, grant.subject_type, grant.identifier
+        )
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nested_manifest() {
+        let manifest = parse_manifest(
+            r#"
+space:
+  key: ENG
+  name: Engineering
+pages:
+  - title: Runbooks
+    labels: [ops]
+    children:
+      - title: On-call
+        body: "<p>Page me</p>"
+permissions:
+  - group: eng
+    operations: [read:space, create:page]
+"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.space.key, "ENG");
+        assert_eq!(manifest.pages[0].children[0].title, "On-call");
+        let grants = manifest_grants(&manifest.permissions).unwrap();
+        assert_eq!(grants.len(), 2);
+        assert_eq!(grants[1].operation, "create");
+        assert_eq!(grants[1].target, "page");
+    }
+
+    #[test]
+    fn rejects_invalid_manifests() {
+        let bad_key = parse_manifest("space: { key: eng, name: E }").unwrap_err();
+        assert!(format!("{bad_key:#}").contains("space.key"));
+
+        let unknown = parse_manifest("space: { key: ENG, name: E }\nextra: 1").unwrap_err();
+        assert!(format!("{unknown:#}").contains("unknown field"));
+
+        let duplicate =
+            parse_manifest("space: { key: ENG, name: E }\npages: [{ title: A }, { title: A }]")
+                .unwrap_err();
+        assert!(format!("{duplicate:#}").contains("Duplicate page title"));
+
+        let subject = parse_manifest(
+            "space: { key: ENG, name: E }\npermissions: [{ operations: [read:space] }]",
+        )
+        .unwrap_err();
+        assert!(format!("{subject:#}").contains("exactly one of group or user"));
+    }
+}
@@ -2,22 +2,33 @@ use anyhow::Result;
 use confcli::client::ApiClient;
 use confcli::json_util::json_str;
 use confcli::output::OutputFormat;
-#[cfg(feature = "write")]
-use dialoguer::Confirm;
+use serde_json::Value;
 #[cfg(feature = "write")]
 use serde_json::json;
 
-use crate::cli::{SpaceCommand, SpaceGetArgs, SpaceListArgs, SpacePagesArgs};
+use crate::activity::{add_comment_activity, with_activity_columns};
+use crate::cli::{ContentType, SpaceCommand, SpaceGetArgs, SpaceListArgs, SpacePagesArgs};
 #[cfg(feature = "write")]
 use crate::cli::{SpaceCreateArgs, SpaceDeleteArgs};
 use crate::context::AppContext;
 use crate::helpers::print_line;
+use crate::helpers::{
+    add_web_urls, maybe_print_json, maybe_print_kv_fmt, maybe_print_rows, retain_content_type,
+    url_with_query, with_url_column,
+};
+#[cfg(feature = "write")]
+use crate::helpers::{confirm_delete, print_created_id, print_status, print_write_action_result};
 #[cfg(feature = "write")]
-use crate::helpers::print_write_action_result;
-use crate::helpers::{maybe_print_json, maybe_print_kv_fmt, maybe_print_rows, url_with_query};
+use crate::plan::{self, Operation};
 #[cfg(feature = "write")]
 use crate::resolve::resolve_space_key;
-use crate::resolve::{build_page_tree, resolve_space_id};
+use crate::resolve::{
+    build_page_tree, list_pages_under, resolve_page_id, resolve_space_id, subtree_page_ids,
+};
+
+#[cfg(feature = "write")]
+mod bootstrap;
+mod shortcuts;
 
 pub async fn handle(ctx: &AppContext, cmd: SpaceCommand) -> Result<()> {
     let client = crate::context::load_client(ctx)?;
@@ -25,10 +36,13 @@ pub async fn handle(ctx: &AppContext, cmd: SpaceCommand) -> Result<()> {
         SpaceCommand::List(args) => space_list(&client, ctx, args).await,
         SpaceCommand::Get(args) => space_get(&client, ctx, args).await,
         SpaceCommand::Pages(args) => space_pages(&client, ctx, args).await,
+        SpaceCommand::Shortcuts(cmd) => shortcuts::handle(&client, ctx, cmd).await,
         #[cfg(feature = "write")]
         SpaceCommand::Create(args) => space_create(&client, ctx, args).await,
         #[cfg(feature = "write")]
         SpaceCommand::Delete(args) => space_delete(&client, ctx, args).await,
+        #[cfg(feature = "write")]
+        SpaceCommand::Bootstrap(args) => bootstrap::space_bootstrap(&client, ctx, args).await,
     }
 }
 
@@ -91,15 +105,35 @@ async fn space_get(client: &ApiClient, ctx: &AppContext, args: SpaceGetArgs) ->
 
 async fn space_pages(client: &ApiClient, ctx: &AppContext, args: SpacePagesArgs) -> Result<()> {
     let space_id = resolve_space_id(client, &args.space).await?;
-    let mut pairs = vec![("limit", args.limit.to_string()), ("depth", args.depth)];
-    if let Some(status) = args.status {
-        pairs.push(("status", status));
+    let mut items = if args.r#type == ContentType::Page {
+        let mut pairs = vec![
+            ("limit", args.limit.to_string()),
+            ("depth", args.depth.clone()),
+        ];
+        if let Some(status) = &args.status {
+            pairs.push(("status", status.clone()));
+        }
+        if let Some(title) = &args.title {
+            pairs.push(("title", title.clone()));
+        }
+        let url = url_with_query(&client.v2_url(&format!("/spaces/{space_id}/pages")), &pairs)?;
+        match &args.under {
+            Some(page) => list_pages_under(client, page, url).await?,
+            None => client.get_paginated_results(url, args.all).await?,
+        }
+    } else {
+        space_tree_items(client, &space_id, &args).await?
+    };
+    retain_content_type(&mut items, "page", args.r#type);
+    if !args.all {
+        items.truncate(args.limit);
     }
-    if let Some(title) = args.title {
-        pairs.push(("title", title));
+    add_web_urls(client.base_url(), &mut items);
+    if args.show_activity {
+        add_comment_activity(client, &mut items).await?;
     }
-    let url = url_with_query(&client.v2_url(&format!("/spaces/{space_id}/pages")), &pairs)?;
-    let items = client.get_paginated_results(url, args.all).await?;
+
+    let show_type = args.r#type != ContentType::Page;
 
     if args.tree {
         match args.output {
@@ -119,26 +153,87 @@ async fn space_pages(client: &ApiClient, ctx: &AppContext, args: SpacePagesArgs)
                 let rows = items
                     .iter()
                     .map(|item| {
-                        vec![
-                            json_str(item, "id"),
-                            json_str(item, "title"),
-                            json_str(item, "status"),
-                            json_str(item, "parentId"),
-                        ]
+                        let mut row = vec![json_str(item, "id"), json_str(item, "title")];
+                        if show_type {
+                            row.push(json_str(item, "type"));
+                        }
+                        row.push(json_str(item, "status"));
+                        row.push(json_str(item, "parentId"));
+                        row
                     })
                     .collect();
-                maybe_print_rows(ctx, fmt, &["ID", "Title", "Status", "Parent"], rows);
+                let headers: &[&str] = if show_type {
+                    &["ID", "Title", "Type", "Status", "Parent"]
+                } else {
+                    &["ID", "Title", "Status", "Parent"]
+                };
+                let (headers, rows) =
+                    with_activity_columns(args.show_activity, headers, &items, rows);
+                let (headers, rows) =
+                    with_url_column(args.show_url, client.base_url(), &headers, &items, rows);
+                maybe_print_rows(ctx, fmt, &headers, rows);
                 Ok(())
             }
         }
     }
 }
 
+/// Everything in a space's page tree, walked from the root pages, since only pages have a
+/// space-wide listing. `--depth root` stops at the roots; `--status`, `--title`, and `--under`
+/// are applied to the walked items.
+async fn space_tree_items(
+    client: &ApiClient,
+    space_id: &str,
+    args: &SpacePagesArgs,
+) -> Result<Vec<Value>> {
+    let mut pairs = vec![("limit", "250".to_string()), ("depth", "root".to_string())];
+    if let Some(status) = &args.status {
+        pairs.push(("status", status.clone()));
+    }
+    let url = url_with_query(&client.v2_url(&format!("/spaces/{space_id}/pages")), &pairs)?;
+    let roots = client.get_paginated_results(url, true).await?;
+
+    let mut items = Vec::new();
+    for root in roots {
+        let root_id = json_str(&root, "id");
+        items.push(root);
+        if args.depth != "root" && !root_id.is_empty() {
+            items.extend(
+                confcli::tree::fetch_descendants_via_direct_children(
+                    client, &root_id, 250, true, None,
+                )
+                .await?,
+            );
+        }
+    }
+
+    if let Some(status) = &args.status {
+        let wanted: Vec<&str> = status.split(',').map(str::trim).collect();
+        items.retain(|item| wanted.contains(&json_str(item, "status").as_str()));
+    }
+    if let Some(title) = &args.title {
+        items.retain(|item| json_str(item, "title") == *title);
+    }
+    if let Some(page) = &args.under {
+        let page_id = resolve_page_id(client, page).await?;
+        let subtree = subtree_page_ids(client, &page_id).await?;
+        items.retain(|item| subtree.contains(&json_str(item, "id")));
+    }
+    Ok(items)
+}
+
 #[cfg(feature = "write")]
 async fn space_create(client: &ApiClient, ctx: &AppContext, args: SpaceCreateArgs) -> Result<()> {
     if ctx.dry_run {
-        print_line(
+        plan::record(
+            Operation::new("create", "space", &args.key).with_payload(json!({
+                "name": args.name,
+                "description": args.description,
+            })),
+        );
+        print_status(
             ctx,
+            args.print_id,
             &format!("Would create space '{}' ({})", args.name, args.key),
         );
         return Ok(());
@@ -157,6 +252,9 @@ async fn space_create(client: &ApiClient, ctx: &AppContext, args: SpaceCreateArg
     // Use v1 API because the v2 endpoint ignores the description field.
     let url = client.v1_url("/space");
     let result = client.post_json(url, payload).await?;
+    if args.print_id {
+        return print_created_id(ctx, &json_str(&result, "id"));
+    }
 
     match args.output {
         OutputFormat::Json => {
@@ -224,6 +322,7 @@ async fn space_delete(client: &ApiClient, ctx: &AppContext, args: SpaceDeleteArg
     };
 
     if ctx.dry_run {
+        plan::record(Operation::new("delete", "space", &space_key));
         return print_write_action_result(
             ctx,
             args.output,
@@ -243,20 +342,13 @@ async fn space_delete(client: &ApiClient, ctx: &AppContext, args: SpaceDeleteArg
         );
     }
 
-    if !args.yes {
-        let confirm = Confirm::new()
-            .with_prompt(format!(
-                "Delete space {space_key}? This will trash all content in the space."
-            ))
-            .default(false)
-            .interact()
-            .map_err(|err| {
-                anyhow::anyhow!("{err}. Use --yes to skip confirmation in non-interactive shells.")
-            })?;
-        if !confirm {
-            print_line(ctx, "Cancelled.");
-            return Ok(());
-        }
+    if !args.yes
+        && !confirm_delete(&format!(
+            "Delete space {space_key}? This will trash all content in the space."
+        ))?
+    {
+        print_line(ctx, "Cancelled.");
+        return Ok(());
     }
 
     // Use v1 API — the v2 DELETE /spaces/{id} endpoint does not support space deletion.
@@ -0,0 +1,356 @@
+//! `space shortcuts`: the quick links in a space's sidebar.
+//!
+//! Confluence has no public REST API for these, so this uses the sidebar endpoints the web UI
+//! calls (`/rest/ia/1.0`, relative to the site URL): `GET space?spaceKey=` lists them,
+//! `POST link` adds one, and `DELETE link/{id}` removes one.
+
+use anyhow::{Context, Result};
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use confcli::output::OutputFormat;
+#[cfg(feature = "write")]
+use serde_json::json;
+use serde_json::{Map, Value};
+
+#[cfg(feature = "write")]
+use crate::cli::{SpaceShortcutsAddArgs, SpaceShortcutsRemoveArgs};
+use crate::cli::{SpaceShortcutsCommand, SpaceShortcutsListArgs};
+use crate::context::AppContext;
+use crate::helpers::*;
+#[cfg(feature = "write")]
+use crate::plan::{self, Operation};
+#[cfg(feature = "write")]
+use crate::resolve::resolve_page_id;
+use crate::resolve::{resolve_space_id, resolve_space_key};
+
+pub(super) async fn handle(
+    client: &ApiClient,
+    ctx: &AppContext,
+    cmd: SpaceShortcutsCommand,
+) -> Result<()> {
+    match cmd {
+        SpaceShortcutsCommand::List(args) => shortcuts_list(client, ctx, args).await,
+        #[cfg(feature = "write")]
+        SpaceShortcutsCommand::Add(args) => shortcuts_add(client, ctx, args).await,
+        #[cfg(feature = "write")]
+        SpaceShortcutsCommand::Remove(args) => shortcuts_remove(client, ctx, args).await,
+    }
+}
+
+fn ia_url(client: &ApiClient, path: &str) -> String {
+    format!("{}/rest/ia/1.0{path}", client.api_site_url())
+}
+
+/// The key of a space given by key or id.
+async fn space_key(client: &ApiClient, space: &str) -> Result<String> {
+    let space_id = resolve_space_id(client, space).await?;
+    resolve_space_key(client, &space_id).await
+}
+
+/// The space's sidebar shortcuts, in sidebar order.
+async fn fetch_shortcuts(client: &ApiClient, space_key: &str) -> Result<Vec<Value>> {
+    let url = url_with_query(
+        &ia_url(client, "/space"),
+        &[("spaceKey", space_key.to_string())],
+    )?;
+    let (json, _) = client
+        .get_json(url)
+        .await
+        .with_context(|| format!("Failed to read the sidebar of space {space_key}"))?;
+    Ok(json
+        .get("quickLinks")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default())
+}
+
+/// A shortcut as confcli prints it: `id`, `title`, `url`, and `pageId` for page links.
+fn shortcut_json(link: &Value) -> Value {
+    let mut out = Map::new();
+    out.insert("id".into(), Value::String(json_str(link, "id")));
+    let title = match json_str(link, "customTitle") {
+        custom if !custom.is_empty() => custom,
+        _ => json_str(link, "title"),
+    };
+    out.insert("title".into(), Value::String(title));
+    out.insert("url".into(), Value::String(json_str(link, "url")));
+    let page_id = json_str(link, "resourceId");
+    out.insert(
+        "pageId".into(),
+        if page_id.is_empty() || page_id == "0" {
+            Value::Null
+        } else {
+            Value::String(page_id)
+        },
+    );
+    out.insert(
+        "hidden".into(),
+        Value::Bool(link.get("hidden").and_then(|v| v.as_bool()) == Some(true)),
+    );
+    Value::Object(out)
+}
+
+async fn shortcuts_list(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: SpaceShortcutsListArgs,
+) -> Result<()> {
+    let key = space_key(client, &args.space).await?;
+    let shortcuts: Vec<Value> = fetch_shortcuts(client, &key)
+        .await?
+        .iter()
+        .map(shortcut_json)
+        .collect();
+    match args.output {
+        OutputFormat::Json => maybe_print_json(ctx, &shortcuts),
+        fmt => {
+            let rows = shortcuts
+                .iter()
+                .map(|link| {
+                    vec![
+                        json_str(link, "id"),
+                        json_str(link, "title"),
+                        json_str(link, "url"),
+                    ]
+                })
+                .collect();
+            maybe_print_rows(ctx, fmt, &["ID", "Title", "URL"], rows);
+            Ok(())
+        }
+    }
+}
+
+/// Whether shortcut `link` (as from [`shortcut_json`]) already points at `url` or `page_id`.
+#[cfg(feature = "write")]
+fn links_to(link: &Value, url: Option<&str>, page_id: Option<&str>) -> bool {
+    let same_url = url.is_some_and(|url| {
+        json_str(link, "url").trim_end_matches('/') == url.trim().trim_end_matches('/')
+    });
+    let same_page = page_id.is_some_and(|id| json_str(link, "pageId") == id);
+    same_url || same_page
+}
+
+#[cfg(feature = "write")]
+async fn shortcuts_add(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: SpaceShortcutsAddArgs,
+) -> Result<()> {
+    let key = space_key(client, &args.space).await?;
+    let page_id = match &args.page {
+        Some(page) => Some(resolve_page_id(client, page).await?),
+        None => None,
+    };
+    let target = page_id
+        .as_deref()
+        .map(|id| format!("page {id}"))
+        .or_else(|| args.url.clone())
+        .unwrap_or_default();
+
+    let existing = fetch_shortcuts(client, &key)
+        .await?
+        .iter()
+        .map(shortcut_json)
+        .find(|link| links_to(link, args.url.as_deref(), page_id.as_deref()));
+    if let Some(link) = existing {
+        return print_write_action_result(
+            ctx,
+            args.output,
+            &format!(
+                "Space {key} already links {target} (shortcut {})",
+                json_str(&link, "id")
+            ),
+            &json!({ "space": key, "added": false, "shortcut": link }),
+            vec![
+                vec!["Space".to_string(), key.clone()],
+                vec!["Added".to_string(), "false".to_string()],
+                vec!["ID".to_string(), json_str(&link, "id")],
+            ],
+        );
+    }
+
+    if ctx.dry_run {
+        plan::record(
+            Operation::new("add", "shortcut", &key).with_payload(json!({ "target": target })),
+        );
+        return print_write_action_result(
+            ctx,
+            args.output,
+            &format!("Would add a shortcut to {target} in space {key}"),
+            &json!({ "dryRun": true, "space": key, "added": false, "target": target }),
+            vec![
+                vec!["DryRun".to_string(), "true".to_string()],
+                vec!["Space".to_string(), key.clone()],
+                vec!["Target".to_string(), target.clone()],
+            ],
+        );
+    }
+
+    let mut body = json!({ "spaceKey": key });
+    if let Some(id) = &page_id {
+        body["pageId"] = json!(id);
+    }
+    if let Some(url) = &args.url {
+        body["url"] = json!(url);
+    }
+    if let Some(title) = &args.title {
+        body["customTitle"] = json!(title);
+    }
+    let created = client
+        .post_json(ia_url(client, "/link"), body)
+        .await
+        .with_context(|| format!("Failed to add a shortcut to space {key}"))?;
+    let link = shortcut_json(&created);
+    print_write_action_result(
+        ctx,
+        args.output,
+        &format!(
+            "Added shortcut {} to {target} in space {key}",
+            json_str(&link, "id")
+        ),
+        &json!({ "space": key, "added": true, "shortcut": link }),
+        vec![
+            vec!["Space".to_string(), key.clone()],
+            vec!["Added".to_string(), "true".to_string()],
+            vec!["ID".to_string(), json_str(&link, "id")],
+            vec!["Title".to_string(), json_str(&link, "title")],
+        ],
+    )
+}
+
+/// The one shortcut `wanted` names: by id, else by exact title or URL.
+#[cfg(feature = "write")]
+fn find_shortcut<'a>(shortcuts: &'a [Value], wanted: &str, space_key: &str) -> Result<&'a Value> {
+    if let Some(link) = shortcuts.iter().find(|link| json_str(link, "id") == wanted) {
+        return Ok(link);
+    }
+    let matches: Vec<&Value> = shortcuts
+        .iter()
+        .filter(|link| json_str(link, "title") == wanted || links_to(link, Some(wanted), None))
+        .collect();
+    match matches.as_slice() {
+        [link] => Ok(link),
+        [] => Err(anyhow::anyhow!(
+            "Space {space_key} has no shortcut '{wanted}'; see confcli space shortcuts list {space_key}"
+        )),
+        _ => Err(anyhow::anyhow!(
+            "{} shortcuts in space {space_key} match '{wanted}'; remove one by id",
+            matches.len()
+        )),
+    }
+}
+
+#[cfg(feature = "write")]
+async fn shortcuts_remove(
+    client: &ApiClient,
+    ctx: &AppContext,
+    args: SpaceShortcutsRemoveArgs,
+) -> Result<()> {
+    let key = space_key(client, &args.space).await?;
+    let shortcuts: Vec<Value> = fetch_shortcuts(client, &key)
+        .await?
+        .iter()
+        .map(shortcut_json)
+        .collect();
+    let link = find_shortcut(&shortcuts, &args.shortcut, &key)?;
+    let id = json_str(link, "id");
+
+    if ctx.dry_run {
+        plan::record(
+            Operation::new("remove", "shortcut", &id).with_payload(json!({
+                "space": key,
+                "title": json_str(link, "title"),
+            })),
+        );
+        return print_write_action_result(
+            ctx,
+            args.output,
+            &format!(
+                "Would remove shortcut {id} ('{}') from space {key}",
+                json_str(link, "title")
+            ),
+            &json!({ "dryRun": true, "space": key, "removed": false, "shortcut": link }),
+            vec![
+                vec!["DryRun".to_string(), "true".to_string()],
+                vec!["Space".to_string(), key.clone()],
+                vec!["ID".to_string(), id.clone()],
+            ],
+        );
+    }
+
+    client
+        .delete(ia_url(client, &format!("/link/{id}")))
+        .await
+        .with_context(|| format!("Failed to remove shortcut {id} from space {key}"))?;
+    print_write_action_result(
+        ctx,
+        args.output,
+        &format!(
+            "Removed shortcut {id} ('{}') from space {key}",
+            json_str(link, "title")
+        ),
+        &json!({ "space": key, "removed": true, "shortcut": link }),
+        vec![
+            vec!["Space".to_string(), key.clone()],
+            vec!["Removed".to_string(), "true".to_string()],
+            vec!["ID".to_string(), id.clone()],
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortcuts_are_normalized() {
+        let link = shortcut_json(&serde_json::json!({
+            "id": 42,
+            "title": "Onboarding",
+            "customTitle": "",
+            "url": "/spaces/ENG/pages/7/Onboarding",
+            "resourceId": 7,
+            "hidden": false,
+        }));
+        assert_eq!(
+            link,
+            serde_json::json!({
+                "id": "42",
+                "title": "Onboarding",
+                "url": "/spaces/ENG/pages/7/Onboarding",
+                "pageId": "7",
+                "hidden": false,
+            })
+        );
+        let external = shortcut_json(&serde_json::json!({
+            "id": 43,
+            "title": "https://status.example.com",
+            "customTitle": "Status",
+            "url": "https://status.example.com",
+        }));
+        assert_eq!(external["title"], "Status");
+        assert_eq!(external["pageId"], Value::Null);
+    }
+
+    #[cfg(feature = "write")]
+    #[test]
+    fn shortcuts_are_found_by_id_title_or_url() {
+        let shortcuts = vec![
+            serde_json::json!({ "id": "1", "title": "Status", "url": "https://status.example.com/", "pageId": null }),
+            serde_json::json!({ "id": "2", "title": "Docs", "url": "https://docs.example.com", "pageId": null }),
+            serde_json::json!({ "id": "3", "title": "Docs", "url": "/spaces/ENG/pages/9", "pageId": "9" }),
+        ];
+        let id = |wanted| find_shortcut(&shortcuts, wanted, "ENG").map(|l| json_str(l, "id"));
+        assert_eq!(id("2").unwrap(), "2");
+        assert_eq!(id("Status").unwrap(), "1");
+        assert_eq!(id("https://status.example.com").unwrap(), "1");
+        assert!(
+            id("Docs")
+                .unwrap_err()
+                .to_string()
+                .contains("remove one by id")
+        );
+        assert!(id("Wiki").is_err());
+        assert!(links_to(&shortcuts[2], None, Some("9")));
+    }
+}
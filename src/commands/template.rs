@@ -0,0 +1,64 @@
+use anyhow::{Context, Result};
+use confcli::client::ApiClient;
+use confcli::markdown::{MarkdownOptions, html_to_markdown_with_options};
+use confcli::output::{OutputFormat, emit_line};
+use serde_json::json;
+
+use crate::cli::{TemplateCommand, TemplateGetArgs};
+use crate::context::AppContext;
+use crate::helpers::maybe_print_json;
+
+pub async fn handle(ctx: &AppContext, cmd: TemplateCommand) -> Result<()> {
+    let client = crate::context::load_client(ctx)?;
+    match cmd {
+        TemplateCommand::Get(args) => template_get(&client, ctx, args).await,
+    }
+}
+
+async fn template_get(client: &ApiClient, ctx: &AppContext, args: TemplateGetArgs) -> Result<()> {
+    let format = args.format.to_lowercase();
+    if !matches!(format.as_str(), "markdown" | "md" | "storage") {
+        return Err(anyhow::anyhow!(
+            "Invalid body format: {}. Use markdown or storage.",
+            args.format
+        ));
+    }
+
+    let url = client.v1_url(&format!(
+        "/template/{}?expand=body.storage",
+        urlencoding::encode(&args.id)
+    ));
+    let (json, _) = client.get_json(url).await?;
+    let storage = json
+        .get("body")
+        .and_then(|body| body.get("storage"))
+        .and_then(|storage| storage.get("value"))
+        .and_then(|value| value.as_str())
+        .context("Missing storage body content")?;
+
+    // Templates have no rendered (view) body, so markdown is converted from storage XHTML;
+    // macros and template variables are dropped.
+    let body = if format == "storage" {
+        storage.to_string()
+    } else {
+        html_to_markdown_with_options(storage, client.base_url(), MarkdownOptions::default())?
+    };
+
+    match args.output {
+        OutputFormat::Json => maybe_print_json(
+            ctx,
+            &json!({
+                "templateId": args.id,
+                "name": json.get("name").and_then(|v| v.as_str()).unwrap_or(""),
+                "format": args.format,
+                "body": body,
+            }),
+        ),
+        _ => {
+            if !ctx.quiet {
+                emit_line(&body);
+            }
+            Ok(())
+        }
+    }
+}
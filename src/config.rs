@@ -6,6 +6,7 @@ use std::env;
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use tempfile::NamedTempFile;
 use url::Url;
 
@@ -29,17 +30,415 @@ pub struct Config {
     #[serde(default)]
     pub api_base_v2: String,
     pub auth: AuthMethod,
+    /// Refuse every write command at runtime, even in builds with the `write` feature.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
+    /// When write commands ask for confirmation.
+    #[serde(default, skip_serializing_if = "ConfirmSettings::is_default")]
+    pub confirm: ConfirmSettings,
+    /// How requests identify themselves.
+    #[serde(default, skip_serializing_if = "UserAgentSettings::is_default")]
+    pub user_agent: UserAgentSettings,
+    /// Table and diff styling.
+    #[serde(default, skip_serializing_if = "ThemeSettings::is_default")]
+    pub theme: ThemeSettings,
+    /// What `--notify` does when a command finishes.
+    #[serde(default, skip_serializing_if = "NotifySettings::is_default")]
+    pub notify: NotifySettings,
+    /// Ceiling on API requests in flight at once, across all workers of a command
+    /// (`--concurrency` overrides it); unlimited when absent or `0`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_requests: Option<usize>,
+    /// The token is kept in the OS keyring (`auth login --keyring`), not in this file.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub keyring: bool,
+    /// Shell command that prints the token (`auth login --token-cmd`), run whenever a client is
+    /// built; the token itself is then never stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_cmd: Option<String>,
+    /// The token, encrypted with a passphrase (`auth login --encrypt`); `auth` then holds an
+    /// empty token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encrypted_token: Option<EncryptedToken>,
+    /// PEM client certificate for sites behind a mutual-TLS gateway (`--client-cert`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_cert: Option<PathBuf>,
+    /// PEM private key for `client_cert`, unless the certificate file holds it (`--client-key`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key: Option<PathBuf>,
 }
 
+/// A token encrypted with ChaCha20-Poly1305 under a key derived from a passphrase with
+/// Argon2id; every field is base64.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptedToken {
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+/// Holds the passphrase of encrypted tokens, for scripts and CI.
+pub const PASSPHRASE_ENV: &str = "CONFCLI_PASSPHRASE";
+
+/// Asks for the passphrase when [`PASSPHRASE_ENV`] is unset (see [`set_passphrase_prompt`]).
+static PASSPHRASE_PROMPT: OnceLock<fn() -> Result<String>> = OnceLock::new();
+
+/// Let [`Config::resolve_token`] ask for the passphrase of an encrypted token; without a
+/// prompt it requires [`PASSPHRASE_ENV`].
+pub fn set_passphrase_prompt(prompt: fn() -> Result<String>) {
+    let _ = PASSPHRASE_PROMPT.set(prompt);
+}
+
+fn passphrase() -> Result<String> {
+    if let Ok(passphrase) = env::var(PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+    match PASSPHRASE_PROMPT.get() {
+        Some(prompt) => prompt(),
+        None => Err(anyhow::anyhow!(
+            "The stored token is encrypted; set {PASSPHRASE_ENV} to its passphrase"
+        )),
+    }
+}
+
+/// The `confirm` section of a config file, e.g. `{"upload_size": 20971520, "delete": "never"}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfirmSettings {
+    /// Uploads larger than this many bytes are confirmed first; `0` never asks.
+    #[serde(default = "ConfirmSettings::default_upload_size")]
+    pub upload_size: u64,
+    /// Whether deleting (or trashing) content asks first.
+    #[serde(default)]
+    pub delete: ConfirmWhen,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfirmWhen {
+    #[default]
+    Always,
+    Never,
+}
+
+impl ConfirmSettings {
+    fn default_upload_size() -> u64 {
+        5 * 1024 * 1024
+    }
+
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether uploading a file of `size` bytes needs confirmation.
+    pub fn upload_needs_confirm(&self, size: u64) -> bool {
+        self.upload_size > 0 && size > self.upload_size
+    }
+}
+
+impl Default for ConfirmSettings {
+    fn default() -> Self {
+        Self {
+            upload_size: Self::default_upload_size(),
+            delete: ConfirmWhen::Always,
+        }
+    }
+}
+
+/// The `user_agent` section of a config file, e.g. `{"suffix": "docs-bot (docs@example.com)"}`.
+///
+/// Atlassian support asks automation accounts to identify themselves when diagnosing rate
+/// limits; the suffix keeps confcli's own product token and adds the team's.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserAgentSettings {
+    /// Replaces `confcli/<version>` entirely (`CONFCLI_USER_AGENT`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// Appended after the product token, separated by a space (`CONFCLI_USER_AGENT_SUFFIX`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suffix: Option<String>,
+}
+
+impl UserAgentSettings {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// These settings with `CONFCLI_USER_AGENT` and `CONFCLI_USER_AGENT_SUFFIX` applied on top.
+    pub fn with_env(self) -> Self {
+        self.with_lookup(|key| env::var(key).ok())
+    }
+
+    fn with_lookup(self, lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let lookup = |key| lookup(key).filter(|value: &String| !value.trim().is_empty());
+        Self {
+            value: lookup("CONFCLI_USER_AGENT").or(self.value),
+            suffix: lookup("CONFCLI_USER_AGENT_SUFFIX").or(self.suffix),
+        }
+    }
+
+    /// The header value: `value` (or `default`), then `suffix`.
+    pub fn header(&self, default: &str) -> String {
+        let base = self
+            .value
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .unwrap_or(default);
+        match self.suffix.as_deref().map(str::trim) {
+            Some(suffix) if !suffix.is_empty() => format!("{base} {suffix}"),
+            _ => base.to_string(),
+        }
+    }
+}
+
+/// The `theme` section of a config file, e.g. `{"borders": "ascii", "header": "underline"}`.
+///
+/// Colors and emphasis only apply when color is on (`--color`, `NO_COLOR`); borders always do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThemeSettings {
+    #[serde(default)]
+    pub borders: TableBorders,
+    /// Emphasis of table headers and key columns.
+    #[serde(default)]
+    pub header: HeaderStyle,
+    #[serde(default = "ThemeSettings::default_diff_added")]
+    pub diff_added: ThemeColor,
+    #[serde(default = "ThemeSettings::default_diff_removed")]
+    pub diff_removed: ThemeColor,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TableBorders {
+    #[default]
+    None,
+    Ascii,
+    Utf8,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeaderStyle {
+    #[default]
+    Bold,
+    Underline,
+    Plain,
+}
+
+/// One of the eight basic terminal colors, which light and dark palettes both adapt, or
+/// `none` for the terminal's default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeColor {
+    None,
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl ThemeColor {
+    /// The SGR foreground code, or `None` for the default color.
+    pub fn ansi_code(self) -> Option<u8> {
+        match self {
+            ThemeColor::None => None,
+            ThemeColor::Black => Some(30),
+            ThemeColor::Red => Some(31),
+            ThemeColor::Green => Some(32),
+            ThemeColor::Yellow => Some(33),
+            ThemeColor::Blue => Some(34),
+            ThemeColor::Magenta => Some(35),
+            ThemeColor::Cyan => Some(36),
+            ThemeColor::White => Some(37),
+        }
+    }
+}
+
+impl ThemeSettings {
+    fn default_diff_added() -> ThemeColor {
+        ThemeColor::Green
+    }
+
+    fn default_diff_removed() -> ThemeColor {
+        ThemeColor::Red
+    }
+
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Default for ThemeSettings {
+    fn default() -> Self {
+        Self {
+            borders: TableBorders::default(),
+            header: HeaderStyle::default(),
+            diff_added: Self::default_diff_added(),
+            diff_removed: Self::default_diff_removed(),
+        }
+    }
+}
+
+/// The `notify` section of a config file, e.g. `{"webhook": "https://hooks.example.com/T1"}`.
+///
+/// With neither a command nor a webhook, `--notify` shows a desktop notification.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotifySettings {
+    /// Shell command to run instead; the outcome is in `CONFCLI_NOTIFY_*` variables.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// URL that receives the outcome as a JSON POST.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<String>,
+}
+
+impl NotifySettings {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Name under which `config.json` is listed alongside the named profiles.
+pub const DEFAULT_PROFILE: &str = "default";
+
 impl Config {
     pub fn path() -> Result<PathBuf> {
         let base = config_dir().context("Unable to resolve config directory")?;
         Ok(base.join("confcli").join("config.json"))
     }
 
+    /// Directory holding named profiles, one `<name>.json` per site (same format as `config.json`).
+    pub fn profiles_dir() -> Result<PathBuf> {
+        let base = config_dir().context("Unable to resolve config directory")?;
+        Ok(base.join("confcli").join("profiles"))
+    }
+
     pub fn load() -> Result<Self> {
-        let path = Self::path()?;
+        Self::load_profile(DEFAULT_PROFILE)
+    }
+
+    /// File of a named profile: `profiles/<name>.json`, or `config.json` for `default`.
+    pub fn profile_path(name: &str) -> Result<PathBuf> {
+        if name == DEFAULT_PROFILE {
+            return Self::path();
+        }
+        validate_profile_name(name)?;
+        Ok(Self::profiles_dir()?.join(format!("{name}.json")))
+    }
+
+    /// File keeping a profile's cookie-auth session between runs: `sessions/<name>`.
+    pub fn session_path(name: &str) -> Result<PathBuf> {
+        validate_profile_name(name)?;
+        let base = config_dir().context("Unable to resolve config directory")?;
+        Ok(base.join("confcli").join("sessions").join(name))
+    }
+
+    /// Load a named profile's file as is: a token kept in the OS keyring is not fetched.
+    pub fn load_profile_file(name: &str) -> Result<Self> {
+        Self::load_from(&Self::profile_path(name)?)
+    }
+
+    /// Load a named profile; `default` is `config.json`.
+    ///
+    /// With `keyring` set, the token comes from the OS keyring; a token still in the file
+    /// (saved while the keyring was unavailable) is used when the keyring can't be read.
+    pub fn load_profile(name: &str) -> Result<Self> {
+        let mut config = Self::load_profile_file(name)?;
+        if config.keyring
+            && config.token_cmd.is_none()
+            && config.encrypted_token.is_none()
+            && let Some(token) = config.auth.token_mut()
+        {
+            match keyring_get(name) {
+                Ok(secret) => *token = secret,
+                Err(err) if !token.is_empty() => {
+                    tracing::debug!("Using the token in the config file: {err:#}");
+                }
+                Err(err) => {
+                    return Err(err.context(format!(
+                        "Failed to read the token of profile '{name}' from the OS keyring; run confcli auth login again"
+                    )));
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    /// File naming the profile `auth switch` made current.
+    pub fn current_profile_path() -> Result<PathBuf> {
+        let base = config_dir().context("Unable to resolve config directory")?;
+        Ok(base.join("confcli").join("current-profile"))
+    }
+
+    /// The profile used without `--profile`, as chosen with `auth switch`; `default` unless
+    /// another one was.
+    pub fn current_profile() -> Result<String> {
+        let path = Self::current_profile_path()?;
+        if !path.exists() {
+            return Ok(DEFAULT_PROFILE.to_string());
+        }
         let data = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let name = data.trim();
+        if name.is_empty() {
+            return Ok(DEFAULT_PROFILE.to_string());
+        }
+        validate_profile_name(name).with_context(|| format!("Invalid {}", path.display()))?;
+        Ok(name.to_string())
+    }
+
+    /// Make a stored profile the one used without `--profile`.
+    pub fn set_current_profile(name: &str) -> Result<()> {
+        if name != DEFAULT_PROFILE && !Self::profile_path(name)?.exists() {
+            return Err(anyhow::anyhow!(
+                "No profile named '{name}'; run confcli --profile {name} auth login"
+            ));
+        }
+        let path = Self::current_profile_path()?;
+        if name == DEFAULT_PROFILE {
+            if path.exists() {
+                fs::remove_file(&path)
+                    .with_context(|| format!("Failed to delete {}", path.display()))?;
+            }
+            return Ok(());
+        }
+        fs::write(&path, format!("{name}\n"))
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Stored profile names, `default` first (if `config.json` exists), then alphabetically.
+    pub fn profile_names() -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        if Self::exists()? {
+            names.push(DEFAULT_PROFILE.to_string());
+        }
+        let dir = Self::profiles_dir()?;
+        if dir.is_dir() {
+            let mut named: Vec<String> = fs::read_dir(&dir)
+                .with_context(|| format!("Failed to read profiles dir: {}", dir.display()))?
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    let path = entry.path();
+                    if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                        return None;
+                    }
+                    let stem = path.file_stem()?.to_str()?.to_string();
+                    (validate_profile_name(&stem).is_ok() && stem != DEFAULT_PROFILE)
+                        .then_some(stem)
+                })
+                .collect();
+            named.sort();
+            names.extend(named);
+        }
+        Ok(names)
+    }
+
+    fn load_from(path: &Path) -> Result<Self> {
+        let data = fs::read_to_string(path)
             .with_context(|| format!("Failed to read config: {}", path.display()))?;
         let mut config: Config = serde_json::from_str(&data)
             .with_context(|| format!("Invalid config format: {}", path.display()))?;
@@ -61,27 +460,51 @@ impl Config {
         let site_url = normalize_site_url(&base_input)?;
 
         // Competitor migration: allow `CONFLUENCE_API_TOKEN` as a synonym for `CONFLUENCE_TOKEN`.
-        let bearer = env::var("CONFLUENCE_BEARER_TOKEN")
-            .ok()
-            .filter(|s| !s.trim().is_empty());
-        if let Some(token) = bearer {
+        let token_var = |name| {
+            env::var(name)
+                .ok()
+                .filter(|s: &String| !s.trim().is_empty())
+        };
+        let email = token_var("CONFLUENCE_EMAIL");
+        let token = token_var("CONFLUENCE_TOKEN").or_else(|| token_var("CONFLUENCE_API_TOKEN"));
+        let cookie = match (
+            token_var("CONFLUENCE_USERNAME"),
+            token_var("CONFLUENCE_PASSWORD"),
+        ) {
+            (Some(username), Some(password)) => Some(AuthMethod::Cookie { username, password }),
+            _ => None,
+        };
+        let token_auth = token_var("CONFLUENCE_PAT")
+            .map(|token| AuthMethod::Pat { token })
+            .or_else(|| {
+                token_var("CONFLUENCE_BEARER_TOKEN").map(|token| AuthMethod::Bearer { token })
+            });
+        if token_auth.is_none() && cookie.is_some() && email.is_some() && token.is_some() {
+            return Err(anyhow::anyhow!(
+                "Ambiguous env-based auth: both CONFLUENCE_USERNAME + CONFLUENCE_PASSWORD and CONFLUENCE_EMAIL + CONFLUENCE_TOKEN are set; unset one pair."
+            ));
+        }
+        if let Some(auth) = token_auth.or(cookie) {
             let (api_base_v1, api_base_v2) = api_bases_from_env_or_defaults(&site_url)?;
             return Ok(Some(Config {
                 site_url,
                 api_base_v1,
                 api_base_v2,
-                auth: AuthMethod::Bearer { token },
+                auth,
+                read_only: false,
+                confirm: ConfirmSettings::default(),
+                user_agent: UserAgentSettings::default(),
+                theme: ThemeSettings::default(),
+                notify: NotifySettings::default(),
+                max_concurrent_requests: None,
+                keyring: false,
+                token_cmd: None,
+                encrypted_token: None,
+                client_cert: None,
+                client_key: None,
             }));
         }
 
-        let email = env::var("CONFLUENCE_EMAIL")
-            .ok()
-            .filter(|s| !s.trim().is_empty());
-        let token = env::var("CONFLUENCE_TOKEN")
-            .ok()
-            .or_else(|| env::var("CONFLUENCE_API_TOKEN").ok())
-            .filter(|s| !s.trim().is_empty());
-
         match (email, token) {
             (Some(email), Some(token)) => {
                 let (api_base_v1, api_base_v2) = api_bases_from_env_or_defaults(&site_url)?;
@@ -90,19 +513,113 @@ impl Config {
                     api_base_v1,
                     api_base_v2,
                     auth: AuthMethod::Basic { email, token },
+                    read_only: false,
+                    confirm: ConfirmSettings::default(),
+                    user_agent: UserAgentSettings::default(),
+                    theme: ThemeSettings::default(),
+                    notify: NotifySettings::default(),
+                    max_concurrent_requests: None,
+                    keyring: false,
+                    token_cmd: None,
+                    encrypted_token: None,
+                    client_cert: None,
+                    client_key: None,
                 }))
             }
             (None, None) => Err(anyhow::anyhow!(
-                "CONFLUENCE_BASE_URL/CONFLUENCE_URL/CONFLUENCE_DOMAIN is set, but no auth env vars were provided. Set either CONFLUENCE_BEARER_TOKEN, or both CONFLUENCE_EMAIL + CONFLUENCE_TOKEN."
+                "CONFLUENCE_BASE_URL/CONFLUENCE_URL/CONFLUENCE_DOMAIN is set, but no auth env vars were provided. Set either CONFLUENCE_PAT (Server/Data Center), CONFLUENCE_BEARER_TOKEN, both CONFLUENCE_USERNAME + CONFLUENCE_PASSWORD (Server/Data Center session login), or both CONFLUENCE_EMAIL + CONFLUENCE_TOKEN, or pass --anonymous for a site with public access."
             )),
             _ => Err(anyhow::anyhow!(
-                "Incomplete env-based auth: set both CONFLUENCE_EMAIL and CONFLUENCE_TOKEN (or use CONFLUENCE_BEARER_TOKEN)."
+                "Incomplete env-based auth: set both CONFLUENCE_EMAIL and CONFLUENCE_TOKEN (or use CONFLUENCE_PAT or CONFLUENCE_BEARER_TOKEN)."
             )),
         }
     }
 
+    /// Credential-free config for `--anonymous`, with writes refused: the site and settings
+    /// of `profile` (from `--profile`) if given, else the site from `CONFLUENCE_BASE_URL` (or
+    /// `CONFLUENCE_URL`/`CONFLUENCE_DOMAIN`), else those of the profile `auth switch` chose.
+    /// Profiles are read as files only; their tokens are never needed.
+    pub fn anonymous(profile: Option<&str>) -> Result<Self> {
+        let base_input = env::var("CONFLUENCE_BASE_URL")
+            .ok()
+            .or_else(|| env::var("CONFLUENCE_URL").ok())
+            .or_else(|| env::var("CONFLUENCE_DOMAIN").ok());
+        let mut config = match base_input.filter(|_| profile.is_none()) {
+            None => {
+                let name = match profile {
+                    Some(name) => name.to_string(),
+                    None => Self::current_profile()?,
+                };
+                if profile.is_none() && !Self::profile_path(&name)?.exists() {
+                    return Err(anyhow::anyhow!(
+                        "--anonymous needs a site: set CONFLUENCE_BASE_URL (or CONFLUENCE_DOMAIN)"
+                    ));
+                }
+                Self::load_profile_file(&name)
+                    .with_context(|| format!("Failed to load profile '{name}'"))?
+            }
+            Some(input) => {
+                let site_url = normalize_site_url(&input)?;
+                let (api_base_v1, api_base_v2) = api_bases_from_env_or_defaults(&site_url)?;
+                Config {
+                    site_url,
+                    api_base_v1,
+                    api_base_v2,
+                    auth: AuthMethod::Anonymous,
+                    read_only: true,
+                    confirm: ConfirmSettings::default(),
+                    user_agent: UserAgentSettings::default(),
+                    theme: ThemeSettings::default(),
+                    notify: NotifySettings::default(),
+                    max_concurrent_requests: None,
+                    keyring: false,
+                    token_cmd: None,
+                    encrypted_token: None,
+                    client_cert: None,
+                    client_key: None,
+                }
+            }
+        };
+        config.auth = AuthMethod::Anonymous;
+        config.read_only = true;
+        Ok(config)
+    }
+
+    /// Fill in the token by running `token_cmd`, if there is one.
+    pub fn resolve_token(&mut self) -> Result<()> {
+        let Some(token) = self.auth.token_mut() else {
+            return Ok(());
+        };
+        if let Some(command) = self.token_cmd.as_deref() {
+            *token = run_token_cmd(command)?;
+        } else if let Some(encrypted) = &self.encrypted_token
+            && token.is_empty()
+        {
+            *token = decrypt_token(encrypted, &passphrase()?)?;
+        }
+        Ok(())
+    }
+
+    /// Replace the token with its encryption under `passphrase` (see [`EncryptedToken`]).
+    pub fn encrypt_token(&mut self, passphrase: &str) -> Result<()> {
+        let Some(token) = self.auth.token_mut() else {
+            return Err(anyhow::anyhow!(
+                "{} auth has no token to encrypt",
+                self.auth.description()
+            ));
+        };
+        self.encrypted_token = Some(encrypt_token(token, passphrase)?);
+        token.clear();
+        Ok(())
+    }
+
     pub fn save(&self) -> Result<()> {
-        let path = Self::path()?;
+        self.save_profile(DEFAULT_PROFILE)
+    }
+
+    /// Save as a named profile (see [`Config::profile_path`]).
+    pub fn save_profile(&self, name: &str) -> Result<()> {
+        let path = Self::profile_path(name)?;
         let parent = path
             .parent()
             .context("Config path had no parent directory")?;
@@ -112,6 +629,29 @@ impl Config {
         // Always write normalized config to disk.
         let mut normalized = self.clone();
         normalized.normalize_and_backfill()?;
+        let had_keyring = Self::load_from(&path).is_ok_and(|stored| stored.keyring);
+        if normalized.token_cmd.is_some() || normalized.encrypted_token.is_some() {
+            if let Some(token) = normalized.auth.token_mut() {
+                token.clear();
+            }
+            normalized.keyring = false;
+        } else if normalized.keyring
+            && let Some(token) = normalized.auth.token_mut()
+        {
+            match keyring_set(name, token) {
+                Ok(()) => token.clear(),
+                Err(err) => {
+                    tracing::warn!(
+                        "OS keyring unavailable, storing the token in {} instead: {err:#}",
+                        path.display()
+                    );
+                    normalized.keyring = false;
+                }
+            }
+        }
+        if had_keyring && !normalized.keyring {
+            let _ = keyring_delete(name);
+        }
         let data = serde_json::to_string_pretty(&normalized)?;
 
         // Write atomically:
@@ -164,7 +704,22 @@ impl Config {
     }
 
     pub fn clear() -> Result<()> {
-        let path = Self::path()?;
+        Self::clear_profile(DEFAULT_PROFILE)
+    }
+
+    /// Delete a named profile's file, and its keyring entry and saved session, if there are.
+    pub fn clear_profile(name: &str) -> Result<()> {
+        let path = Self::profile_path(name)?;
+        let session = Self::session_path(name)?;
+        if session.exists() {
+            fs::remove_file(&session)
+                .with_context(|| format!("Failed to delete session: {}", session.display()))?;
+        }
+        if Self::load_from(&path).is_ok_and(|stored| stored.keyring)
+            && let Err(err) = keyring_delete(name)
+        {
+            tracing::warn!("{err:#}");
+        }
         if Path::new(&path).exists() {
             fs::remove_file(&path)
                 .with_context(|| format!("Failed to delete config: {}", path.display()))?;
@@ -193,6 +748,180 @@ impl Config {
     }
 }
 
+/// `command` run by the shell: `sh -c` on Unix, `cmd /C` on Windows.
+pub fn shell_command(command: &str) -> std::process::Command {
+    // `cmd` does its own parsing of the command line, so pass the command through unquoted.
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        let mut shell = std::process::Command::new("cmd");
+        shell.arg("/C").raw_arg(command);
+        shell
+    }
+    #[cfg(not(windows))]
+    {
+        let mut shell = std::process::Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+}
+
+/// Run a `token_cmd` through the shell; its stdout, minus surrounding whitespace, is the token.
+/// Stdin and stderr stay attached so password managers can prompt.
+fn run_token_cmd(command: &str) -> Result<String> {
+    let output = shell_command(command)
+        .stdin(std::process::Stdio::inherit())
+        .stderr(std::process::Stdio::inherit())
+        .output()
+        .with_context(|| format!("Failed to run token command: {command}"))?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "Token command failed ({}): {command}",
+            output.status
+        ));
+    }
+    let token = String::from_utf8(output.stdout)
+        .with_context(|| format!("Token command printed invalid UTF-8: {command}"))?
+        .trim()
+        .to_string();
+    if token.is_empty() {
+        return Err(anyhow::anyhow!("Token command printed nothing: {command}"));
+    }
+    Ok(token)
+}
+
+/// Service of confcli's OS keyring entries; the account is the profile name.
+#[cfg(feature = "keyring")]
+const KEYRING_SERVICE: &str = "confcli";
+
+#[cfg(feature = "keyring")]
+fn keyring_entry(profile: &str) -> Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, profile).context("Failed to open the OS keyring")
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_get(profile: &str) -> Result<String> {
+    keyring_entry(profile)?
+        .get_password()
+        .context("Failed to read from the OS keyring")
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_set(profile: &str, token: &str) -> Result<()> {
+    keyring_entry(profile)?
+        .set_password(token)
+        .context("Failed to write to the OS keyring")
+}
+
+/// Remove a profile's entry; one that is already gone is fine.
+#[cfg(feature = "keyring")]
+fn keyring_delete(profile: &str) -> Result<()> {
+    match keyring_entry(profile)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(err) => Err(err).context("Failed to delete from the OS keyring"),
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+fn keyring_get(_profile: &str) -> Result<String> {
+    Err(no_keyring())
+}
+
+#[cfg(not(feature = "keyring"))]
+fn keyring_set(_profile: &str, _token: &str) -> Result<()> {
+    Err(no_keyring())
+}
+
+#[cfg(not(feature = "keyring"))]
+fn keyring_delete(_profile: &str) -> Result<()> {
+    Err(no_keyring())
+}
+
+#[cfg(not(feature = "keyring"))]
+fn no_keyring() -> anyhow::Error {
+    anyhow::anyhow!("confcli was built without OS keyring support (the `keyring` feature)")
+}
+
+#[cfg(feature = "encrypt")]
+fn token_key(passphrase: &str, salt: &[u8]) -> Result<chacha20poly1305::Key> {
+    let mut key = chacha20poly1305::Key::default();
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| anyhow::anyhow!("Failed to derive the encryption key: {err}"))?;
+    Ok(key)
+}
+
+#[cfg(feature = "encrypt")]
+fn encrypt_token(token: &str, passphrase: &str) -> Result<EncryptedToken> {
+    use base64::Engine;
+    use chacha20poly1305::aead::{Aead, Generate, KeyInit};
+    use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+
+    let b64 = base64::engine::general_purpose::STANDARD;
+    let salt = <[u8; 16]>::try_generate().context("Failed to generate a salt")?;
+    let nonce = Nonce::try_generate().context("Failed to generate a nonce")?;
+    let ciphertext = ChaCha20Poly1305::new(&token_key(passphrase, &salt)?)
+        .encrypt(&nonce, token.as_bytes())
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt the token"))?;
+    Ok(EncryptedToken {
+        salt: b64.encode(salt),
+        nonce: b64.encode(nonce),
+        ciphertext: b64.encode(ciphertext),
+    })
+}
+
+#[cfg(feature = "encrypt")]
+fn decrypt_token(encrypted: &EncryptedToken, passphrase: &str) -> Result<String> {
+    use base64::Engine;
+    use chacha20poly1305::aead::{Aead, KeyInit};
+    use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+
+    let b64 = base64::engine::general_purpose::STANDARD;
+    let invalid = || anyhow::anyhow!("The encrypted token in the config file is malformed");
+    let salt = b64.decode(&encrypted.salt).map_err(|_| invalid())?;
+    let nonce = Nonce::try_from(
+        b64.decode(&encrypted.nonce)
+            .map_err(|_| invalid())?
+            .as_slice(),
+    )
+    .map_err(|_| invalid())?;
+    let ciphertext = b64.decode(&encrypted.ciphertext).map_err(|_| invalid())?;
+    let token = ChaCha20Poly1305::new(&token_key(passphrase, &salt)?)
+        .decrypt(&nonce, ciphertext.as_slice())
+        .map_err(|_| anyhow::anyhow!("Failed to decrypt the stored token; wrong passphrase?"))?;
+    String::from_utf8(token).map_err(|_| invalid())
+}
+
+#[cfg(not(feature = "encrypt"))]
+fn encrypt_token(_token: &str, _passphrase: &str) -> Result<EncryptedToken> {
+    Err(no_encryption())
+}
+
+#[cfg(not(feature = "encrypt"))]
+fn decrypt_token(_encrypted: &EncryptedToken, _passphrase: &str) -> Result<String> {
+    Err(no_encryption())
+}
+
+#[cfg(not(feature = "encrypt"))]
+fn no_encryption() -> anyhow::Error {
+    anyhow::anyhow!("confcli was built without token encryption (the `encrypt` feature)")
+}
+
+/// Profile names become file names, so keep them to `[A-Za-z0-9_-]`.
+pub fn validate_profile_name(name: &str) -> Result<()> {
+    if !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "Invalid profile name '{name}': use letters, digits, '-' or '_'"
+        ))
+    }
+}
+
 pub fn normalize_site_url(input: &str) -> Result<String> {
     // Accept bare domains; default to https.
     let mut s = input.trim().to_string();
@@ -241,6 +970,27 @@ pub fn normalize_site_url_and_origin(input: &str) -> Result<(String, String)> {
     Ok((site_url, origin))
 }
 
+/// Atlassian's API gateway: OAuth apps and scoped API tokens reach a Cloud site's API at
+/// `<gateway><cloudId>/wiki/...` instead of on the site itself.
+pub const ATLASSIAN_GATEWAY: &str = "https://api.atlassian.com/ex/confluence/";
+
+/// Prefix of the same site's Jira API on the gateway; followed by the cloud id.
+pub const ATLASSIAN_JIRA_GATEWAY: &str = "https://api.atlassian.com/ex/jira/";
+
+/// The v1 and v2 API bases of a Cloud site, routed through the gateway.
+pub fn gateway_api_bases(cloud_id: &str) -> (String, String) {
+    let base = format!("{ATLASSIAN_GATEWAY}{cloud_id}/wiki");
+    (format!("{base}/rest/api"), format!("{base}/api/v2"))
+}
+
+/// The cloud id in a gateway URL, e.g. `abc` in `https://api.atlassian.com/ex/confluence/abc/wiki`.
+pub fn gateway_cloud_id(url: &str) -> Option<&str> {
+    url.strip_prefix(ATLASSIAN_GATEWAY)?
+        .split('/')
+        .next()
+        .filter(|id| !id.is_empty())
+}
+
 /// Default v1 REST API path for a given site URL.
 pub fn default_api_path_v1(site_url: &str) -> String {
     if site_url.trim_end_matches('/').ends_with("/wiki") {
@@ -277,6 +1027,12 @@ fn normalize_full_url(input: &str) -> Result<String> {
 }
 
 fn api_bases_from_env_or_defaults(site_url: &str) -> Result<(String, String)> {
+    if let Some(cloud_id) = env::var("CONFLUENCE_CLOUD_ID")
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+    {
+        return Ok(gateway_api_bases(cloud_id.trim()));
+    }
     let site = Url::parse(site_url).context("Invalid Confluence URL")?;
     let origin = format!(
         "{}://{}{}",
@@ -350,6 +1106,17 @@ mod tests {
                 email: "a@b.c".to_string(),
                 token: "x".to_string(),
             },
+            read_only: false,
+            confirm: ConfirmSettings::default(),
+            user_agent: UserAgentSettings::default(),
+            theme: ThemeSettings::default(),
+            notify: NotifySettings::default(),
+            max_concurrent_requests: None,
+            keyring: false,
+            token_cmd: None,
+            encrypted_token: None,
+            client_cert: None,
+            client_key: None,
         };
         cfg.normalize_and_backfill().unwrap();
         assert_eq!(
@@ -371,6 +1138,136 @@ mod tests {
         }
     }
 
+    #[test]
+    fn confirm_settings_default_and_round_trip() {
+        let json = r#"{"site_url":"https://x.test/wiki","auth":{"type":"bearer","token":"t"}}"#;
+        let cfg: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(cfg.confirm, ConfirmSettings::default());
+        assert!(cfg.confirm.upload_needs_confirm(6 * 1024 * 1024));
+        assert!(!serde_json::to_string(&cfg).unwrap().contains("confirm"));
+
+        let json = r#"{"site_url":"https://x.test/wiki","auth":{"type":"bearer","token":"t"},"confirm":{"upload_size":0,"delete":"never"}}"#;
+        let cfg: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(cfg.confirm.delete, ConfirmWhen::Never);
+        assert!(!cfg.confirm.upload_needs_confirm(u64::MAX));
+        let saved = serde_json::to_value(&cfg).unwrap();
+        assert_eq!(saved["confirm"]["delete"], "never");
+    }
+
+    #[test]
+    fn user_agent_suffix_extends_the_default() {
+        let json = r#"{"site_url":"https://x.test/wiki","auth":{"type":"bearer","token":"t"},"user_agent":{"suffix":"docs-bot (docs@example.com)"}}"#;
+        let cfg: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            cfg.user_agent.header("confcli/1.0"),
+            "confcli/1.0 docs-bot (docs@example.com)"
+        );
+        assert_eq!(
+            UserAgentSettings::default().header("confcli/1.0"),
+            "confcli/1.0"
+        );
+
+        let env = cfg
+            .user_agent
+            .with_lookup(|key| (key == "CONFCLI_USER_AGENT").then(|| "acme-sync/2".to_string()));
+        assert_eq!(
+            env.header("confcli/1.0"),
+            "acme-sync/2 docs-bot (docs@example.com)"
+        );
+    }
+
+    #[test]
+    fn theme_fills_in_defaults_and_is_omitted_when_default() {
+        let json = r#"{"site_url":"https://x.test/wiki","auth":{"type":"bearer","token":"t"},"theme":{"borders":"ascii","diff_added":"blue"}}"#;
+        let cfg: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(cfg.theme.borders, TableBorders::Ascii);
+        assert_eq!(cfg.theme.header, HeaderStyle::Bold);
+        assert_eq!(cfg.theme.diff_added, ThemeColor::Blue);
+        assert_eq!(cfg.theme.diff_removed, ThemeColor::Red);
+
+        let plain: Config = serde_json::from_str(
+            r#"{"site_url":"https://x.test/wiki","auth":{"type":"bearer","token":"t"}}"#,
+        )
+        .unwrap();
+        assert!(!serde_json::to_string(&plain).unwrap().contains("theme"));
+    }
+
+    #[test]
+    fn notify_settings_round_trip_and_are_omitted_when_empty() {
+        let json = r#"{"site_url":"https://x.test/wiki","auth":{"type":"bearer","token":"t"},"notify":{"webhook":"https://hooks.test/1"}}"#;
+        let cfg: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(cfg.notify.webhook.as_deref(), Some("https://hooks.test/1"));
+        assert_eq!(cfg.notify.command, None);
+        let saved = serde_json::to_value(&cfg).unwrap();
+        assert_eq!(
+            saved["notify"],
+            serde_json::json!({ "webhook": "https://hooks.test/1" })
+        );
+
+        let plain: Config = serde_json::from_str(
+            r#"{"site_url":"https://x.test/wiki","auth":{"type":"bearer","token":"t"}}"#,
+        )
+        .unwrap();
+        assert!(!serde_json::to_string(&plain).unwrap().contains("notify"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn token_cmd_supplies_the_token() {
+        let config = |command: &str| -> Config {
+            serde_json::from_value(serde_json::json!({
+                "site_url": "https://x.test/wiki",
+                "auth": { "type": "pat", "token": "" },
+                "token_cmd": command,
+            }))
+            .unwrap()
+        };
+        let mut cfg = config("printf ' secret\\n'");
+        cfg.resolve_token().unwrap();
+        assert!(matches!(cfg.auth, AuthMethod::Pat { token } if token == "secret"));
+        assert!(config("exit 3").resolve_token().is_err());
+        assert!(config("true").resolve_token().is_err());
+    }
+
+    #[cfg(feature = "encrypt")]
+    #[test]
+    fn encrypted_tokens_need_the_passphrase() {
+        let mut cfg: Config = serde_json::from_value(serde_json::json!({
+            "site_url": "https://x.test/wiki",
+            "auth": { "type": "bearer", "token": "secret" },
+        }))
+        .unwrap();
+        cfg.encrypt_token("correct horse").unwrap();
+        assert!(matches!(&cfg.auth, AuthMethod::Bearer { token } if token.is_empty()));
+        let encrypted = cfg.encrypted_token.as_ref().unwrap();
+        assert!(!encrypted.ciphertext.contains("secret"));
+        assert_eq!(decrypt_token(encrypted, "correct horse").unwrap(), "secret");
+        assert!(decrypt_token(encrypted, "wrong").is_err());
+    }
+
+    #[test]
+    fn gateway_bases_carry_the_cloud_id() {
+        let (v1, v2) = gateway_api_bases("abc");
+        assert_eq!(
+            v1,
+            "https://api.atlassian.com/ex/confluence/abc/wiki/rest/api"
+        );
+        assert_eq!(
+            v2,
+            "https://api.atlassian.com/ex/confluence/abc/wiki/api/v2"
+        );
+        assert_eq!(gateway_cloud_id(&v2), Some("abc"));
+        assert_eq!(gateway_cloud_id("https://example.atlassian.net/wiki"), None);
+    }
+
+    #[test]
+    fn profile_names_must_be_safe_file_names() {
+        assert!(validate_profile_name("work-eu_2").is_ok());
+        assert!(validate_profile_name("").is_err());
+        assert!(validate_profile_name("../etc").is_err());
+        assert!(validate_profile_name("a b").is_err());
+    }
+
     #[test]
     fn from_env_requires_auth_when_base_present() {
         let _lock = ENV_LOCK.lock().unwrap();
@@ -382,11 +1279,46 @@ mod tests {
             std::env::remove_var("CONFLUENCE_TOKEN");
             std::env::remove_var("CONFLUENCE_API_TOKEN");
             std::env::remove_var("CONFLUENCE_BEARER_TOKEN");
+            std::env::remove_var("CONFLUENCE_PAT");
         }
 
         let err = Config::from_env().unwrap_err();
         assert!(err.to_string().contains("no auth env vars"));
 
+        // A personal access token wins over a bearer token.
+        unsafe {
+            std::env::set_var("CONFLUENCE_BEARER_TOKEN", "oauth");
+            std::env::set_var("CONFLUENCE_PAT", "pat");
+        }
+        let config = Config::from_env().unwrap().unwrap();
+        assert!(matches!(config.auth, AuthMethod::Pat { token } if token == "pat"));
+        unsafe {
+            std::env::remove_var("CONFLUENCE_BEARER_TOKEN");
+            std::env::remove_var("CONFLUENCE_PAT");
+        }
+
+        // A username and password log in with a session cookie.
+        unsafe {
+            std::env::set_var("CONFLUENCE_USERNAME", "ada");
+            std::env::set_var("CONFLUENCE_PASSWORD", "secret");
+        }
+        let config = Config::from_env().unwrap().unwrap();
+        assert!(matches!(config.auth, AuthMethod::Cookie { username, .. } if username == "ada"));
+
+        // ...but not when an email and API token are set as well.
+        unsafe {
+            std::env::set_var("CONFLUENCE_EMAIL", "ada@example.com");
+            std::env::set_var("CONFLUENCE_TOKEN", "token");
+        }
+        let err = Config::from_env().unwrap_err();
+        assert!(err.to_string().contains("Ambiguous env-based auth"));
+        unsafe {
+            std::env::remove_var("CONFLUENCE_EMAIL");
+            std::env::remove_var("CONFLUENCE_TOKEN");
+            std::env::remove_var("CONFLUENCE_USERNAME");
+            std::env::remove_var("CONFLUENCE_PASSWORD");
+        }
+
         // Cleanup.
         unsafe {
             std::env::remove_var("CONFLUENCE_BASE_URL");
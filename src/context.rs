@@ -1,33 +1,260 @@
 use anyhow::{Context, Result};
-use confcli::client::ApiClient;
-use confcli::config::Config;
+use confcli::auth::AuthMethod;
+use confcli::client::{
+    ApiClient, ClientCert, DEFAULT_USER_AGENT, PoolSettings, backoff_warning_from_env,
+};
+#[cfg(feature = "write")]
+use confcli::config::ConfirmSettings;
+use confcli::config::{Config, DEFAULT_PROFILE, NotifySettings, PASSPHRASE_ENV, ThemeSettings};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::cli::ProgressMode;
 
 #[derive(Debug, Clone, Copy)]
 pub struct AppContext {
     pub quiet: bool,
     pub verbose: u8,
     pub dry_run: bool,
+    pub timing: bool,
+    pub progress: ProgressMode,
+    /// `--concurrency` given on the command line; overrides `max_concurrent_requests`.
+    pub concurrency: Option<usize>,
+    /// `--anonymous`: send no credentials, and refuse writes.
+    pub anonymous: bool,
+}
+
+impl AppContext {
+    /// Workers a bulk command runs at once: `--concurrency`, else the command's `default`.
+    pub fn workers(&self, default: usize) -> usize {
+        self.concurrency.unwrap_or(default)
+    }
 }
 
+/// Shared by every `load_client` call so repeated commands in one process reuse a single
+/// connection pool instead of paying for new TLS handshakes.
+static CLIENT: OnceLock<ApiClient> = OnceLock::new();
+
+/// Set from the global `--profile` flag; the profile is read wherever a config is, so this is
+/// process-wide rather than part of [`AppContext`].
+static PROFILE: OnceLock<String> = OnceLock::new();
+
+/// Set from the global `--client-cert` and `--client-key` flags, which win over a profile's.
+static CLIENT_CERT: OnceLock<(PathBuf, Option<PathBuf>)> = OnceLock::new();
+
+/// The `confirm` section of `config.json`, read once.
+#[cfg(feature = "write")]
+static CONFIRM: OnceLock<ConfirmSettings> = OnceLock::new();
+
 pub fn load_client(ctx: &AppContext) -> Result<ApiClient> {
-    if let Some(config) = Config::from_env()? {
-        return ApiClient::new(
-            config.site_url,
-            config.api_base_v1,
-            config.api_base_v2,
-            config.auth,
-            ctx.verbose,
-        );
-    }
-    if !Config::exists()? {
+    if let Some(client) = CLIENT.get() {
+        return Ok(client.clone());
+    }
+    let client = build_client(ctx)?;
+    Ok(CLIENT.get_or_init(|| client).clone())
+}
+
+/// The `User-Agent` of the shared client, if one was built.
+pub fn active_user_agent() -> Option<String> {
+    CLIENT.get().map(|client| client.user_agent().to_string())
+}
+
+pub fn set_profile(name: Option<String>) {
+    if let Some(name) = name {
+        let _ = PROFILE.set(name);
+    }
+}
+
+/// The `--profile` in use, if any.
+pub fn active_profile() -> Option<&'static str> {
+    PROFILE.get().map(String::as_str)
+}
+
+pub fn set_client_cert(cert: Option<PathBuf>, key: Option<PathBuf>) {
+    if let Some(cert) = cert {
+        let _ = CLIENT_CERT.set((cert, key));
+    }
+}
+
+/// The `--client-cert` and `--client-key` paths, if given.
+pub fn client_cert_flags() -> Option<(PathBuf, Option<PathBuf>)> {
+    CLIENT_CERT.get().cloned()
+}
+
+/// The TLS client certificate to present: `--client-cert`, else the config's.
+pub fn client_cert(config: &Config) -> Result<Option<ClientCert>> {
+    let (cert, key) = match CLIENT_CERT.get() {
+        Some((cert, key)) => (cert, key.as_ref()),
+        None => match &config.client_cert {
+            Some(cert) => (cert, config.client_key.as_ref()),
+            None => return Ok(None),
+        },
+    };
+    ClientCert::from_files(cert, key.map(PathBuf::as_path)).map(Some)
+}
+
+/// Ask on the terminal for the passphrase of an encrypted token (see `auth login --encrypt`).
+pub fn prompt_passphrase() -> Result<String> {
+    dialoguer::Password::new()
+        .with_prompt("Passphrase for the stored token")
+        .interact()
+        .with_context(|| format!("Failed to read the passphrase; set {PASSPHRASE_ENV}"))
+}
+
+/// The profile whose file commands read: `--profile`, else the one `auth switch` selected
+/// (`default`, i.e. `config.json`, unless changed).
+pub fn stored_profile() -> String {
+    match active_profile() {
+        Some(name) => name.to_string(),
+        None => Config::current_profile().unwrap_or_else(|err| {
+            tracing::warn!("{err:#}; using the default profile");
+            DEFAULT_PROFILE.to_string()
+        }),
+    }
+}
+
+/// The stored profile's config, if there is one that loads; settings in it also apply with
+/// env-based auth. Read as a file only: settings never need the token, and reading it could
+/// unlock the OS keyring.
+fn stored_config() -> Option<Config> {
+    Config::load_profile_file(&stored_profile()).ok()
+}
+
+/// When write commands ask first, from `config.json` (also with env-based auth); defaults
+/// when there is no config file.
+#[cfg(feature = "write")]
+pub fn confirm_settings() -> ConfirmSettings {
+    *CONFIRM.get_or_init(|| {
+        stored_config()
+            .map(|config| config.confirm)
+            .unwrap_or_default()
+    })
+}
+
+/// Table and diff styling from `config.json`; defaults when there is no config file.
+pub fn theme_settings() -> ThemeSettings {
+    stored_config()
+        .map(|config| config.theme)
+        .unwrap_or_default()
+}
+
+/// What `--notify` does, from `config.json`; a desktop notification when there is none.
+pub fn notify_settings() -> NotifySettings {
+    stored_config()
+        .map(|config| config.notify)
+        .unwrap_or_default()
+}
+
+/// Fail fast, before a write command reads or changes anything, when the active profile
+/// is marked `read_only`.
+pub fn ensure_writable(ctx: &AppContext) -> Result<()> {
+    if ctx.anonymous {
+        return Err(anyhow::anyhow!(
+            "--anonymous is read-only; write commands need credentials (run confcli auth login)"
+        ));
+    }
+    load_client(ctx)?.ensure_writable()
+}
+
+/// One client per stored profile (`config.json` as `default`, then `profiles/*.json`).
+pub fn load_profile_clients(ctx: &AppContext) -> Result<Vec<(String, ApiClient)>> {
+    let names = Config::profile_names()?;
+    if names.is_empty() {
+        return Err(anyhow::anyhow!("Not logged in. Run confcli auth login"));
+    }
+    names
+        .into_iter()
+        .map(|name| Ok((name.clone(), load_named_client(ctx, &name)?)))
+        .collect()
+}
+
+/// A separate client (own connection pool and rate limiter) for a stored profile.
+pub fn load_named_client(ctx: &AppContext, name: &str) -> Result<ApiClient> {
+    let config =
+        Config::load_profile(name).with_context(|| format!("Failed to load profile '{name}'"))?;
+    client_from_config(ctx, config, Some(name))
+}
+
+/// The client whose site is `site`: the active one if it matches, else the first stored
+/// profile for that site.
+pub fn load_client_for_site(ctx: &AppContext, site: &str) -> Result<ApiClient> {
+    let site = site.trim_end_matches('/');
+    let client = load_client(ctx)?;
+    if client.base_url() == site {
+        return Ok(client);
+    }
+    for name in Config::profile_names()? {
+        let client = load_named_client(ctx, &name)?;
+        if client.base_url() == site {
+            return Ok(client);
+        }
+    }
+    Err(anyhow::anyhow!("No stored profile for site {site}"))
+}
+
+fn build_client(ctx: &AppContext) -> Result<ApiClient> {
+    if ctx.anonymous {
+        return client_from_config(ctx, Config::anonymous(active_profile())?, None);
+    }
+    if let Some(name) = active_profile() {
+        let config = Config::load_profile(name).with_context(|| {
+            format!("Failed to load profile '{name}'; run confcli --profile {name} auth login")
+        })?;
+        return client_from_config(ctx, config, Some(name));
+    }
+    if let Some(mut config) = Config::from_env()? {
+        // Env-based auth still identifies the client the way `config.json` says.
+        if let Some(stored) = stored_config() {
+            config.user_agent = stored.user_agent;
+            config.max_concurrent_requests = stored.max_concurrent_requests;
+        }
+        return client_from_config(ctx, config, None);
+    }
+    let name = stored_profile();
+    if !Config::profile_path(&name)?.exists() {
+        if name != DEFAULT_PROFILE {
+            return Err(anyhow::anyhow!(
+                "Profile '{name}' selected with auth switch no longer exists; run confcli auth switch default"
+            ));
+        }
         return Err(anyhow::anyhow!("Not logged in. Run confcli auth login"));
     }
-    let config = Config::load().context("Failed to load config")?;
-    ApiClient::new(
+    let config = Config::load_profile(&name).context("Failed to load config")?;
+    client_from_config(ctx, config, Some(&name))
+}
+
+/// The client for `config`; cookie-auth sessions of a stored `profile` outlive the run.
+fn client_from_config(
+    ctx: &AppContext,
+    mut config: Config,
+    profile: Option<&str>,
+) -> Result<ApiClient> {
+    config.resolve_token()?;
+    let client_cert = client_cert(&config)?;
+    let read_only = config.read_only;
+    let max_concurrent_requests = ctx.concurrency.or(config.max_concurrent_requests);
+    let user_agent = config.user_agent.with_env().header(DEFAULT_USER_AGENT);
+    tracing::trace!(user_agent = %user_agent, "client identification");
+    let session_file = match (&config.auth, profile) {
+        (AuthMethod::Cookie { .. }, Some(profile)) => Some(Config::session_path(profile)?),
+        _ => None,
+    };
+    let client = ApiClient::new(
         config.site_url,
         config.api_base_v1,
         config.api_base_v2,
         config.auth,
         ctx.verbose,
-    )
+    )?
+    .with_pool_settings(PoolSettings::from_env()?)?
+    .with_timing(ctx.timing)?
+    .with_user_agent(user_agent)?
+    .with_client_cert(client_cert)?
+    .with_backoff_warning(backoff_warning_from_env()?)
+    .with_read_only(read_only)
+    .with_max_concurrent_requests(max_concurrent_requests);
+    Ok(match session_file {
+        Some(path) => client.with_session_file(path),
+        None => client,
+    })
 }
@@ -1,13 +1,14 @@
 use anyhow::{Context, Result};
 use confcli::client::ApiClient;
 use futures_util::StreamExt;
-use indicatif::ProgressBar;
 use reqwest::header::HeaderMap;
 use serde_json::Value;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
 use url::Url;
 
+use crate::progress::Progress;
+
 #[derive(Debug, Clone, Copy)]
 pub struct DownloadRetry {
     pub max_attempts: u32,
@@ -19,6 +20,25 @@ impl Default for DownloadRetry {
     }
 }
 
+/// Every current page of a space with its body in `body_format` (`body.<format>.value`).
+pub async fn fetch_space_pages_with_body(
+    client: &ApiClient,
+    space_id: &str,
+    body_format: &str,
+) -> Result<Vec<Value>> {
+    let url = crate::helpers::url_with_query(
+        &client.v2_url(&format!("/spaces/{space_id}/pages")),
+        &[
+            ("body-format", body_format.to_string()),
+            ("limit", "250".to_string()),
+        ],
+    )?;
+    client
+        .get_paginated_results(url, true)
+        .await
+        .with_context(|| format!("Failed to list pages of space {space_id}"))
+}
+
 pub async fn fetch_page_with_body_format(
     client: &ApiClient,
     page_id: &str,
@@ -74,9 +94,8 @@ pub fn attachment_download_url(base: &Url, download: &str) -> Result<Url> {
 
 pub struct DownloadToFileOptions<'a> {
     pub retry: DownloadRetry,
-    pub progress: Option<&'a ProgressBar>,
+    pub progress: Option<&'a Progress>,
     pub verbose: u8,
-    pub quiet: bool,
 }
 
 pub async fn download_to_file_with_retry(
@@ -87,6 +106,7 @@ pub async fn download_to_file_with_retry(
     opts: DownloadToFileOptions<'_>,
 ) -> Result<()> {
     let mut attempt = 0u32;
+    let mut renewed = false;
     loop {
         attempt += 1;
 
@@ -94,6 +114,11 @@ pub async fn download_to_file_with_retry(
         // Ensure we don't append to previous failed attempts.
         let _ = tokio::fs::remove_file(&tmp).await;
 
+        client.ensure_session().await?;
+        client.wait_for_rate_limit().await;
+        // Held until the body is on disk, so downloads count against the request ceiling.
+        let slot = client.request_slot().await;
+        let session = client.session_cookie();
         let response = match client
             .apply_auth(client.http().get(url.clone()))?
             .send()
@@ -111,18 +136,27 @@ pub async fn download_to_file_with_retry(
                     });
                 }
                 let wait = ApiClient::retry_wait_from_headers(&HeaderMap::new(), attempt);
-                if !opts.quiet {
-                    eprintln!(
-                        "Retrying download ({attempt}/{}) in {:?}: {label} (request error: {err})",
-                        opts.retry.max_attempts, wait
-                    );
-                }
-                tokio::time::sleep(wait).await;
+                tracing::warn!(
+                    "Retrying download ({attempt}/{}) in {wait:?}: {label} (request error: {err})",
+                    opts.retry.max_attempts
+                );
+                drop(slot);
+                client.back_off(None, wait).await;
                 continue;
             }
         };
 
         let status = response.status();
+        if status == reqwest::StatusCode::UNAUTHORIZED
+            && !renewed
+            && client.renew_session(session.as_deref()).await?
+        {
+            // Logging in again is not a failed attempt.
+            renewed = true;
+            attempt -= 1;
+            drop(slot);
+            continue;
+        }
         if !status.is_success() {
             let headers = response.headers().clone();
             let body = response.text().await.unwrap_or_default();
@@ -136,13 +170,13 @@ pub async fn download_to_file_with_retry(
             let _ = tokio::fs::remove_file(&tmp).await;
             if attempt < opts.retry.max_attempts && (status == 429 || status.is_server_error()) {
                 let wait = ApiClient::retry_wait_from_headers(&headers, attempt);
-                if !opts.quiet {
-                    eprintln!(
-                        "Retrying download ({attempt}/{}) in {:?}: {label} (status {status})",
-                        opts.retry.max_attempts, wait
-                    );
-                }
-                tokio::time::sleep(wait).await;
+                tracing::warn!(
+                    "Retrying download ({attempt}/{}) in {wait:?}: {label} (status {status})",
+                    opts.retry.max_attempts
+                );
+                // A 429 pauses every concurrent download/request, not just this one.
+                drop(slot);
+                client.back_off(Some(status), wait).await;
                 continue;
             }
 
@@ -154,11 +188,8 @@ pub async fn download_to_file_with_retry(
             });
         }
 
-        let total = response.content_length();
-        if let (Some(bar), Some(total)) = (opts.progress, total)
-            && bar.length().is_none()
-        {
-            bar.set_length(total);
+        if let (Some(progress), Some(total)) = (opts.progress, response.content_length()) {
+            progress.set_total_if_unknown(total);
         }
 
         let mut file = tokio::fs::File::create(&tmp)
@@ -168,8 +199,8 @@ pub async fn download_to_file_with_retry(
         while let Some(chunk) = stream.next().await {
             let chunk = chunk.context("Download stream error")?;
             tokio::io::AsyncWriteExt::write_all(&mut file, &chunk).await?;
-            if let Some(bar) = opts.progress {
-                bar.inc(chunk.len() as u64);
+            if let Some(progress) = opts.progress {
+                progress.inc(chunk.len() as u64);
             }
         }
 
@@ -321,7 +352,6 @@ mod tests {
                 retry: DownloadRetry { max_attempts: 3 },
                 progress: None,
                 verbose: 0,
-                quiet: true,
             },
         )
         .await
@@ -359,7 +389,6 @@ mod tests {
                 retry: DownloadRetry { max_attempts: 3 },
                 progress: None,
                 verbose: 0,
-                quiet: true,
             },
         )
         .await;
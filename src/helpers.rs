@@ -1,6 +1,7 @@
 #[cfg(feature = "write")]
 use anyhow::Context;
 use anyhow::Result;
+use confcli::markdown::{MarkdownOptions, add_source_header};
 use confcli::output::{
     OutputFormat, print_json, print_kv, print_markdown_kv, print_markdown_table_with_count,
     print_table_with_count,
@@ -12,8 +13,13 @@ use url::Url;
 
 use crate::context::AppContext;
 
+/// `--quiet`, or a dry-run plan that replaces the command's own output (see [`crate::plan`]).
+fn silenced(ctx: &AppContext) -> bool {
+    ctx.quiet || crate::plan::captures_output()
+}
+
 pub fn maybe_print_json<T: serde::Serialize>(ctx: &AppContext, value: &T) -> Result<()> {
-    if ctx.quiet {
+    if silenced(ctx) {
         return Ok(());
     }
     print_json(value)
@@ -21,7 +27,7 @@ pub fn maybe_print_json<T: serde::Serialize>(ctx: &AppContext, value: &T) -> Res
 
 #[cfg(feature = "write")]
 pub fn maybe_print_kv(ctx: &AppContext, rows: Vec<Vec<String>>) {
-    if ctx.quiet {
+    if silenced(ctx) {
         return;
     }
     print_kv(rows);
@@ -49,11 +55,91 @@ pub fn print_write_action_result(
     }
 }
 
+/// Ask a yes/no question (default no). Without a terminal to ask on, fails pointing at `--yes`.
+#[cfg(feature = "write")]
+pub fn confirm(prompt: &str) -> Result<bool> {
+    dialoguer::Confirm::new()
+        .with_prompt(prompt)
+        .default(false)
+        .interact()
+        .map_err(|err| {
+            anyhow::anyhow!("{err}. Use --yes to skip confirmation in non-interactive shells.")
+        })
+}
+
+/// Print a unified diff of `old` and `new` on stdout or into `--out` (colored on a terminal).
+#[cfg(feature = "write")]
+pub fn print_diff(ctx: &AppContext, old: &str, new: &str, old_header: &str, new_header: &str) {
+    if silenced(ctx) {
+        return;
+    }
+    let unified = similar::TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(3)
+        .header(old_header, new_header)
+        .to_string();
+    confcli::output::emit(&confcli::output::colorize_diff(&unified));
+}
+
+/// [`confirm`] for deleting or trashing content, unless the config says
+/// `"confirm": {"delete": "never"}`.
+#[cfg(feature = "write")]
+pub fn confirm_delete(prompt: &str) -> Result<bool> {
+    if crate::context::confirm_settings().delete == confcli::config::ConfirmWhen::Never {
+        return Ok(true);
+    }
+    confirm(prompt)
+}
+
+/// A status message from a command that may run with `--print-id`: stdout then carries only
+/// the created id, so the message goes to stderr.
+#[cfg(feature = "write")]
+pub fn print_status(ctx: &AppContext, print_id: bool, message: &str) {
+    if !print_id {
+        print_line(ctx, message);
+    } else if !ctx.quiet {
+        eprintln!("{message}");
+    }
+}
+
+/// `--print-id`: the created entity's id alone on stdout, for `$(confcli ... --print-id)`.
+#[cfg(feature = "write")]
+pub fn print_created_id(ctx: &AppContext, id: &str) -> Result<()> {
+    if id.is_empty() {
+        return Err(anyhow::anyhow!("The response did not include an id"));
+    }
+    print_line(ctx, id);
+    Ok(())
+}
+
 pub fn print_line(ctx: &AppContext, message: &str) {
-    if ctx.quiet {
+    if silenced(ctx) {
         return;
     }
-    println!("{message}");
+    confcli::output::emit_line(message);
+}
+
+/// Escape a value for use inside a double-quoted CQL string.
+pub fn escape_cql_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(['\n', '\r', '\t'], " ")
+}
+
+/// One RFC 4180 record: fields with commas, quotes, or line breaks are quoted.
+pub fn csv_record(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
 }
 
 pub fn human_size(bytes: i64) -> String {
@@ -80,14 +166,80 @@ pub fn resolve_download_path(output: &Option<PathBuf>, json: &Value) -> Result<P
     Ok(PathBuf::from(file_name))
 }
 
-pub fn add_markdown_header(base_url: &str, json: &Value, markdown: &str) -> String {
+/// Absolute web URL of a content item: `_links.webui` (v2 items, v1 content), the `content` of a
+/// v1 search result, or a search result's relative `url`. Empty if the item has no web link.
+pub fn web_url(base_url: &str, item: &Value) -> String {
+    let link = item
+        .get("_links")
+        .and_then(|v| v.get("webui"))
+        .or_else(|| item.get("content")?.get("_links")?.get("webui"))
+        .or_else(|| item.get("url"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    if link.is_empty() || link.starts_with("http://") || link.starts_with("https://") {
+        link.to_string()
+    } else {
+        format!("{}{link}", base_url.trim_end_matches('/'))
+    }
+}
+
+/// Set an absolute `url` on every object item so JSON output can be clicked or pasted as-is.
+pub fn add_web_urls(base_url: &str, items: &mut [Value]) {
+    for item in items.iter_mut() {
+        let url = web_url(base_url, item);
+        if let Some(obj) = item.as_object_mut()
+            && !url.is_empty()
+        {
+            obj.insert("url".to_string(), Value::String(url));
+        }
+    }
+}
+
+/// Give every item a `type` (page listings omit it, so `default` fills the gap) and keep only
+/// the items of `kind`.
+pub fn retain_content_type(items: &mut Vec<Value>, default: &str, kind: crate::cli::ContentType) {
+    for item in items.iter_mut() {
+        if let Some(obj) = item.as_object_mut()
+            && !obj.get("type").is_some_and(|v| v.is_string())
+        {
+            obj.insert("type".to_string(), Value::String(default.to_string()));
+        }
+    }
+    items.retain(|item| kind.matches(item.get("type").and_then(|v| v.as_str()).unwrap_or("")));
+}
+
+/// Append a `URL` column (header and one cell per row) when `show_url` is set.
+pub fn with_url_column<'a>(
+    show_url: bool,
+    base_url: &str,
+    headers: &[&'a str],
+    items: &[Value],
+    mut rows: Vec<Vec<String>>,
+) -> (Vec<&'a str>, Vec<Vec<String>>) {
+    let mut headers = headers.to_vec();
+    if show_url {
+        headers.push("URL");
+        for (row, item) in rows.iter_mut().zip(items) {
+            row.push(web_url(base_url, item));
+        }
+    }
+    (headers, rows)
+}
+
+pub fn add_markdown_header(
+    base_url: &str,
+    json: &Value,
+    markdown: &str,
+    options: MarkdownOptions,
+) -> String {
     let webui = json
         .get("_links")
         .and_then(|v| v.get("webui"))
         .and_then(|v| v.as_str());
     if let Some(webui) = webui {
         let source = format!("{base_url}{webui}");
-        format!("<!-- Source: {source} -->\n\n{markdown}")
+        let title = json.get("title").and_then(|v| v.as_str()).unwrap_or("");
+        add_source_header(markdown, title, &source, options)
     } else {
         markdown.to_string()
     }
@@ -100,7 +252,7 @@ pub fn maybe_print_rows(
     headers: &[&str],
     rows: Vec<Vec<String>>,
 ) {
-    if ctx.quiet {
+    if silenced(ctx) {
         return;
     }
     match format {
@@ -111,7 +263,7 @@ pub fn maybe_print_rows(
 
 /// Print key-value data respecting the output format (Table or Markdown).
 pub fn maybe_print_kv_fmt(ctx: &AppContext, format: OutputFormat, rows: Vec<Vec<String>>) {
-    if ctx.quiet {
+    if silenced(ctx) {
         return;
     }
     match format {
@@ -177,6 +329,45 @@ pub fn format_timestamp(s: &str) -> String {
     }
 }
 
+/// Response headers kept by `--include-headers`: request ids, cache validators, and (by
+/// prefix) rate-limit state.
+const META_HEADERS: &[&str] = &[
+    "x-request-id",
+    "x-arequestid",
+    "x-trace-id",
+    "traceparent",
+    "etag",
+    "last-modified",
+    "cache-control",
+    "retry-after",
+];
+const META_HEADER_PREFIXES: &[&str] = &["x-ratelimit-", "x-beta-ratelimit-", "ratelimit-"];
+
+/// Nest the selected response headers under `_meta.headers` (lower-case names) in an
+/// object response.
+pub fn add_response_meta(json: &mut Value, headers: &reqwest::header::HeaderMap) {
+    let selected: serde_json::Map<String, Value> = headers
+        .iter()
+        .filter(|(name, _)| {
+            let name = name.as_str();
+            META_HEADERS.contains(&name)
+                || META_HEADER_PREFIXES
+                    .iter()
+                    .any(|prefix| name.starts_with(prefix))
+        })
+        .filter_map(|(name, value)| {
+            let value = value.to_str().ok()?;
+            Some((name.as_str().to_string(), Value::String(value.to_string())))
+        })
+        .collect();
+    if let Some(obj) = json.as_object_mut() {
+        obj.insert(
+            "_meta".to_string(),
+            serde_json::json!({ "headers": selected }),
+        );
+    }
+}
+
 pub fn open_url(url: &str) -> Result<()> {
     #[cfg(target_os = "macos")]
     {
@@ -200,6 +391,69 @@ mod tests {
     use super::*;
     use std::collections::HashMap;
 
+    #[test]
+    fn web_url_prefers_webui_and_keeps_absolute_links() {
+        let base = "https://example.atlassian.net/wiki";
+        let page = serde_json::json!({ "_links": { "webui": "/spaces/MFS/pages/1/A" } });
+        assert_eq!(
+            web_url(base, &page),
+            "https://example.atlassian.net/wiki/spaces/MFS/pages/1/A"
+        );
+        let search = serde_json::json!({ "url": "/spaces/MFS/pages/2/B", "content": {} });
+        assert_eq!(
+            web_url(base, &search),
+            "https://example.atlassian.net/wiki/spaces/MFS/pages/2/B"
+        );
+        let absolute = serde_json::json!({ "url": "https://other.example/x" });
+        assert_eq!(web_url(base, &absolute), "https://other.example/x");
+        assert_eq!(web_url(base, &serde_json::json!({})), "");
+    }
+
+    #[test]
+    fn content_type_filter_fills_in_missing_types() {
+        use crate::cli::ContentType;
+        let listing = || {
+            vec![
+                serde_json::json!({ "id": "1" }),
+                serde_json::json!({ "id": "2", "type": "folder" }),
+                serde_json::json!({ "id": "3", "type": "whiteboard" }),
+            ]
+        };
+        let mut items = listing();
+        retain_content_type(&mut items, "page", ContentType::All);
+        let types: Vec<&str> = items.iter().map(|i| i["type"].as_str().unwrap()).collect();
+        assert_eq!(types, ["page", "folder", "whiteboard"]);
+
+        let mut items = listing();
+        retain_content_type(&mut items, "page", ContentType::Folder);
+        assert_eq!(
+            items,
+            vec![serde_json::json!({ "id": "2", "type": "folder" })]
+        );
+    }
+
+    #[test]
+    fn response_meta_keeps_selected_headers() {
+        use reqwest::header::{HeaderMap, HeaderValue};
+        let mut headers = HeaderMap::new();
+        headers.insert("x-arequestid", HeaderValue::from_static("abc123"));
+        headers.insert("X-RateLimit-Remaining", HeaderValue::from_static("98"));
+        headers.insert("etag", HeaderValue::from_static("\"7\""));
+        headers.insert("content-type", HeaderValue::from_static("application/json"));
+        let mut json = serde_json::json!({ "id": "1" });
+        add_response_meta(&mut json, &headers);
+        assert_eq!(
+            json["_meta"],
+            serde_json::json!({
+                "headers": {
+                    "x-arequestid": "abc123",
+                    "x-ratelimit-remaining": "98",
+                    "etag": "\"7\"",
+                }
+            })
+        );
+    }
+
     #[test]
     fn url_with_query_round_trips_query_pairs() {
         let out = url_with_query(
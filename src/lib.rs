@@ -1,11 +1,24 @@
+//! Confluence Cloud client library behind the `confcli` binary.
+//!
+//! Without default features the library is just [`client::ApiClient`], its configuration,
+//! and the JSON helpers. `markdown` adds HTML/Markdown conversion and sanitizing,
+//! `cli-helpers` the table and JSON output layer, and `export` the export dependencies;
+//! `cli` enables all three, which the binary requires.
+
 pub mod auth;
 pub mod client;
 pub mod config;
 pub mod json_util;
+#[cfg(feature = "markdown")]
 pub mod markdown;
+#[cfg(feature = "cli-helpers")]
 pub mod output;
 pub mod pagination;
 pub mod pattern;
+#[cfg(feature = "markdown")]
+pub mod sanitize;
+pub mod schedule;
+pub mod schema;
 pub mod tree;
 
 #[cfg(test)]
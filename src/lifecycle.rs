@@ -0,0 +1,255 @@
+//! Content lifecycle annotations: an expiry date and follow-up action stored in a page
+//! content property, so `confcli report expiring` can drive review workflows from cron.
+
+use anyhow::{Result, anyhow};
+use confcli::client::ApiClient;
+use confcli::json_util::json_str;
+use serde_json::Value;
+#[cfg(feature = "write")]
+use serde_json::json;
+
+use crate::helpers::url_with_query;
+
+/// Content property holding `{"expiresOn": "YYYY-MM-DD", "action": "..."}`.
+pub const EXPIRY_PROPERTY: &str = "confcli-expiry";
+/// Marker label on annotated pages. Content properties are not searchable with CQL, so the
+/// report finds candidates by this label and then reads their property.
+pub const EXPIRY_LABEL: &str = "confcli-expiry";
+
+/// What `report expiring --apply` does once a page's expiry date has passed.
+#[cfg(feature = "write")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpiryAction {
+    /// Only report the page.
+    None,
+    /// Add this label, e.g. `label:stale` to route the page into a review queue.
+    Label(String),
+}
+
+#[cfg(feature = "write")]
+impl ExpiryAction {
+    pub fn parse(value: &str) -> Result<Self> {
+        let value = value.trim();
+        if value.is_empty() || value == "none" {
+            return Ok(Self::None);
+        }
+        match value.split_once(':') {
+            Some(("label", name)) if !name.trim().is_empty() && !name.contains(' ') => {
+                Ok(Self::Label(name.trim().to_string()))
+            }
+            _ => Err(anyhow!(
+                "Invalid action '{value}': use label:<name> or none"
+            )),
+        }
+    }
+}
+
+#[cfg(feature = "write")]
+impl std::fmt::Display for ExpiryAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => f.write_str("none"),
+            Self::Label(name) => write!(f, "label:{name}"),
+        }
+    }
+}
+
+/// The expiry annotation on one page, with the property id/version needed to update it.
+#[derive(Debug, Clone)]
+pub struct Expiry {
+    #[cfg(feature = "write")]
+    pub property_id: String,
+    #[cfg(feature = "write")]
+    pub version: u64,
+    pub expires_on: String,
+    pub action: String,
+}
+
+/// Parse a `YYYY-MM-DD` date into days since 1970-01-01.
+pub fn parse_date(value: &str) -> Result<i64> {
+    let invalid = || anyhow!("Invalid date '{value}': expected YYYY-MM-DD");
+    let mut parts = value.trim().splitn(3, '-');
+    let (Some(y), Some(m), Some(d)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+    if y.len() != 4 || m.len() != 2 || d.len() != 2 {
+        return Err(invalid());
+    }
+    let y: i64 = y.parse().map_err(|_| invalid())?;
+    let m: u32 = m.parse().map_err(|_| invalid())?;
+    let d: u32 = d.parse().map_err(|_| invalid())?;
+    if !(1..=12).contains(&m) || d == 0 || d > days_in_month(y, m) {
+        return Err(invalid());
+    }
+    Ok(days_from_civil(y, m, d))
+}
+
+/// Parse a start date given as `Nd` or `Nw` (that many days or weeks before today) or as
+/// `YYYY-MM-DD`, into days since 1970-01-01.
+pub fn parse_since(value: &str, today: i64) -> Result<i64> {
+    let value = value.trim();
+    let relative = |suffix: char, days: i64| {
+        value
+            .strip_suffix(suffix)
+            .and_then(|count| count.parse::<i64>().ok())
+            .map(|count| today - count * days)
+    };
+    match relative('d', 1).or_else(|| relative('w', 7)) {
+        Some(days) => Ok(days),
+        None => parse_date(value)
+            .map_err(|_| anyhow!("Invalid date '{value}': expected Nd, Nw, or YYYY-MM-DD")),
+    }
+}
+
+/// Format days since 1970-01-01 as `YYYY-MM-DD`.
+pub fn format_date(days: i64) -> String {
+    let (y, m, d) = civil_from_days(days);
+    format!("{y:04}-{m:02}-{d:02}")
+}
+
+/// Today's date (UTC) as days since 1970-01-01.
+pub fn today() -> i64 {
+    now_secs().div_euclid(86_400)
+}
+
+/// The current time as seconds since 1970-01-01 (UTC).
+pub fn now_secs() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// The current time (UTC) as `YYYY-MM-DDTHH:MM:SSZ`.
+pub fn utc_timestamp() -> String {
+    format_timestamp(now_secs())
+}
+
+/// Format seconds since 1970-01-01 as `YYYY-MM-DDTHH:MM:SSZ`.
+pub fn format_timestamp(secs: i64) -> String {
+    let time = secs.rem_euclid(86_400);
+    format!(
+        "{}T{:02}:{:02}:{:02}Z",
+        format_date(secs.div_euclid(86_400)),
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+fn days_in_month(y: i64, m: u32) -> u32 {
+    match m {
+        2 if (y % 4 == 0 && y % 100 != 0) || y % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Proleptic Gregorian conversions (Howard Hinnant's `days_from_civil`/`civil_from_days`).
+fn days_from_civil(y: i64, m: u32, d: u32) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (m as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    (y, m, d)
+}
+
+/// Read the expiry annotation of a page, if it has one.
+pub async fn read_expiry(client: &ApiClient, page_id: &str) -> Result<Option<Expiry>> {
+    let url = url_with_query(
+        &client.v2_url(&format!("/pages/{page_id}/properties")),
+        &[("key", EXPIRY_PROPERTY.to_string())],
+    )?;
+    let items = client.get_paginated_results(url, false).await?;
+    Ok(items.first().map(expiry_from_property))
+}
+
+fn expiry_from_property(property: &Value) -> Expiry {
+    let value = property.get("value").cloned().unwrap_or(Value::Null);
+    Expiry {
+        #[cfg(feature = "write")]
+        property_id: json_str(property, "id"),
+        #[cfg(feature = "write")]
+        version: property
+            .get("version")
+            .and_then(|v| v.get("number"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(1),
+        expires_on: json_str(&value, "expiresOn"),
+        action: json_str(&value, "action"),
+    }
+}
+
+/// Property value written by `page expire`.
+#[cfg(feature = "write")]
+pub fn expiry_value(expires_on: &str, action: &ExpiryAction) -> Value {
+    json!({ "expiresOn": expires_on, "action": action.to_string() })
+}
+
+/// Add a global label to a page (a no-op when it is already present).
+#[cfg(feature = "write")]
+pub async fn add_label(client: &ApiClient, page_id: &str, name: &str) -> Result<()> {
+    let url = client.v1_url(&format!("/content/{page_id}/label"));
+    client
+        .post_json(url, json!([{ "prefix": "global", "name": name }]))
+        .await?;
+    Ok(())
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates_round_trip() {
+        for date in ["1970-01-01", "2000-02-29", "2025-12-31", "2100-03-01"] {
+            assert_eq!(format_date(parse_date(date).unwrap()), date);
+        }
+        assert_eq!(parse_date("1970-01-02").unwrap(), 1);
+        assert!(parse_date("2025-02-29").is_err());
+        assert!(parse_date("2025-13-01").is_err());
+        assert!(parse_date("25-1-1").is_err());
+    }
+
+    #[test]
+    fn parses_since() {
+        let today = parse_date("2025-03-10").unwrap();
+        assert_eq!(
+            format_date(parse_since("30d", today).unwrap()),
+            "2025-02-08"
+        );
+        assert_eq!(format_date(parse_since("2w", today).unwrap()), "2025-02-24");
+        assert_eq!(
+            parse_since("2025-01-01", today).unwrap(),
+            parse_date("2025-01-01").unwrap()
+        );
+        assert!(parse_since("3m", today).is_err());
+    }
+
+    #[test]
+    fn parses_actions() {
+        assert_eq!(
+            ExpiryAction::parse("label:stale").unwrap(),
+            ExpiryAction::Label("stale".to_string())
+        );
+        assert_eq!(ExpiryAction::parse("none").unwrap(), ExpiryAction::None);
+        assert!(ExpiryAction::parse("archive").is_err());
+        assert!(ExpiryAction::parse("label:").is_err());
+    }
+}
@@ -0,0 +1,61 @@
+//! Diagnostics through `tracing`, written to stderr.
+//!
+//! The filter comes from `CONFCLI_LOG`, then `RUST_LOG` (both use `EnvFilter` directives, e.g.
+//! `CONFCLI_LOG=confcli::client=trace,warn`), and otherwise from the verbosity flags: warnings
+//! only by default, requests and retries with `-v`, responses with `-vv`. `--quiet` silences
+//! everything unless a filter is set explicitly.
+
+use std::io::IsTerminal;
+use tracing_subscriber::EnvFilter;
+
+use crate::cli::LogFormat;
+use confcli::output::ColorChoice;
+
+const FILTER_VARS: [&str; 2] = ["CONFCLI_LOG", "RUST_LOG"];
+
+pub fn init(verbose: u8, quiet: bool, format: LogFormat, color: ColorChoice) {
+    let filter = FILTER_VARS
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| default_filter(verbose, quiet).to_string());
+    let filter = EnvFilter::try_new(&filter).unwrap_or_else(|err| {
+        eprintln!("Ignoring invalid log filter '{filter}': {err}");
+        EnvFilter::new(default_filter(verbose, quiet))
+    });
+
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr);
+    // A second subscriber can only come from tests; keeping the first is fine.
+    let _ = match format {
+        LogFormat::Text => builder
+            .without_time()
+            .with_target(false)
+            .with_ansi(color.enabled(std::io::stderr().is_terminal()))
+            .try_init(),
+        LogFormat::Json => builder.json().flatten_event(true).try_init(),
+    };
+}
+
+fn default_filter(verbose: u8, quiet: bool) -> &'static str {
+    match (quiet, verbose) {
+        (true, _) => "off",
+        (false, 0) => "warn",
+        (false, 1) => "warn,confcli=debug",
+        (false, _) => "warn,confcli=trace",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verbosity_picks_the_default_filter() {
+        assert_eq!(default_filter(0, false), "warn");
+        assert_eq!(default_filter(1, false), "warn,confcli=debug");
+        assert_eq!(default_filter(3, false), "warn,confcli=trace");
+        assert_eq!(default_filter(2, true), "off");
+    }
+}
@@ -1,14 +1,26 @@
 use anyhow::Result;
-use clap::{CommandFactory, Parser};
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
+use confcli::output::OutputFormat;
 use std::io;
 use std::io::Write;
 
+mod activity;
+mod body_cache;
 mod cli;
+mod clipboard;
 mod commands;
 mod context;
 mod download;
 mod helpers;
+mod lifecycle;
+mod logging;
+mod notify;
+mod plan;
+#[cfg(feature = "write")]
+mod preflight;
+mod progress;
 mod resolve;
+mod smart_links;
 #[cfg(test)]
 mod test_support;
 
@@ -22,27 +34,73 @@ async fn main() -> Result<()> {
     if cfg!(debug_assertions) || std::env::var_os("CONFCLI_LOAD_DOTENV").is_some() {
         dotenvy::dotenv().ok();
     }
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    logging::init(cli.verbose, cli.quiet, cli.log_format, cli.color);
+    confcli::output::set_style(cli.color, context::theme_settings());
+    confcli::output::set_table_layout(cli.table_layout());
+    confcli::output::set_summary(!cli.no_summary);
     let ctx = AppContext {
         quiet: cli.quiet,
         verbose: cli.verbose,
         dry_run: cli.dry_run,
+        timing: cli.timing,
+        progress: cli.progress,
+        concurrency: cli.concurrency,
+        anonymous: cli.anonymous,
     };
+    resolve::set_fuzzy_titles(cli.fuzzy);
+    context::set_profile(cli.profile.clone());
+    context::set_client_cert(cli.client_cert.clone(), cli.client_key.clone());
+    confcli::config::set_passphrase_prompt(context::prompt_passphrase);
+    let plan = cli.dry_run && cli.command.has_plan() && output_is_json(&matches);
+    plan::set_capture(plan);
+    let started = std::time::Instant::now();
 
-    let result = match cli.command {
-        Commands::Auth(cmd) => commands::auth::handle(&ctx, cmd).await,
-        Commands::Space(cmd) => commands::space::handle(&ctx, cmd).await,
-        Commands::Page(cmd) => commands::page::handle(&ctx, cmd).await,
-        Commands::Search(cmd) => commands::search::handle(&ctx, cmd).await,
-        Commands::Attachment(cmd) => commands::attachment::handle(&ctx, cmd).await,
-        Commands::Label(cmd) => commands::label::handle(&ctx, cmd).await,
-        Commands::Comment(cmd) => commands::comment::handle(&ctx, cmd).await,
-        Commands::Export(args) => commands::export::handle(&ctx, args).await,
-        #[cfg(feature = "write")]
-        Commands::CopyTree(args) => commands::copy_tree::handle(&ctx, args).await,
-        Commands::Completions(args) => generate_completions(&ctx, args),
+    let out_file = cli
+        .out
+        .filter(|_| !cli.command.owns_out())
+        .map(std::path::PathBuf::from);
+    if out_file.is_some() {
+        confcli::output::capture();
+    }
+
+    let result = run(&ctx, cli.command).await;
+    let result = match result {
+        Ok(()) if plan && !ctx.quiet => plan::print(&command_path(&matches)),
+        result => result,
+    };
+    let result = match (result, &out_file) {
+        (Ok(()), Some(path)) => confcli::output::persist_captured(path),
+        (result, _) => result,
     };
 
+    if cli.notify {
+        let command = command_path(&matches);
+        let outcome = notify::Outcome {
+            command: &command,
+            error: result.as_ref().err().map(format_error_chain),
+            elapsed: started.elapsed(),
+        };
+        notify::send(&context::notify_settings(), &outcome).await;
+    }
+
+    let stats = confcli::client::transfer_stats();
+    tracing::debug!(
+        requests = stats.requests,
+        retries = stats.retries,
+        rate_limited = stats.rate_limited,
+        backoff_ms = stats.backoff.as_millis() as u64,
+        "transfer summary"
+    );
+
+    if ctx.timing {
+        eprintln!("{}", format_timing(started.elapsed()));
+        if let Some(user_agent) = context::active_user_agent() {
+            eprintln!("User-Agent: {user_agent}");
+        }
+    }
+
     if let Err(err) = result {
         if !ctx.quiet {
             if ctx.verbose > 0 {
@@ -57,6 +115,67 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+async fn run(ctx: &AppContext, command: Commands) -> Result<()> {
+    if command.is_write() && !ctx.dry_run {
+        context::ensure_writable(ctx)?;
+    }
+    match command {
+        Commands::Auth(cmd) => commands::auth::handle(ctx, cmd).await,
+        Commands::Space(cmd) => commands::space::handle(ctx, cmd).await,
+        Commands::Page(cmd) => commands::page::handle(ctx, cmd).await,
+        Commands::Search(cmd) => commands::search::handle(ctx, cmd).await,
+        Commands::Goto(args) => commands::goto::handle(ctx, args).await,
+        Commands::Attachment(cmd) => commands::attachment::handle(ctx, cmd).await,
+        Commands::Label(cmd) => commands::label::handle(ctx, cmd).await,
+        Commands::Comment(cmd) => commands::comment::handle(ctx, cmd).await,
+        Commands::Favorite(cmd) => commands::favorite::handle(ctx, cmd).await,
+        Commands::Export(args) => commands::export::handle(ctx, args).await,
+        #[cfg(feature = "write")]
+        Commands::CopyTree(args) => commands::copy_tree::handle(ctx, args).await,
+        #[cfg(feature = "write")]
+        Commands::Rollback(args) => commands::rollback::handle(ctx, args).await,
+        #[cfg(feature = "write")]
+        Commands::Apply(args) => commands::apply::handle(ctx, args).await,
+        Commands::Template(cmd) => commands::template::handle(ctx, cmd).await,
+        Commands::Question(cmd) => commands::question::handle(ctx, cmd).await,
+        Commands::Jira(cmd) => commands::jira::handle(ctx, cmd).await,
+        Commands::Recent(args) => commands::recent::handle(ctx, args).await,
+        Commands::Report(cmd) => commands::report::handle(ctx, cmd).await,
+        Commands::Graph(args) => commands::graph::handle(ctx, args).await,
+        Commands::Sitemap(args) => commands::sitemap::handle(ctx, args).await,
+        Commands::CheckTitles(args) => commands::check_titles::handle(ctx, args).await,
+        Commands::Dump(args) => commands::dump::handle(ctx, args).await,
+        Commands::Generate(cmd) => commands::generate::handle(ctx, cmd).await,
+        Commands::Resolve(args) => commands::resolve::handle(ctx, args).await,
+        Commands::Doctor(args) => commands::doctor::handle(ctx, args).await,
+        Commands::Schema(args) => commands::schema::handle(ctx, args),
+        Commands::Completions(args) => generate_completions(ctx, args),
+    }
+}
+
+/// The subcommands as typed, e.g. `page create`, for messages about the whole run.
+fn command_path(matches: &ArgMatches) -> String {
+    let mut names = Vec::new();
+    let mut current = matches;
+    while let Some((name, sub)) = current.subcommand() {
+        names.push(name);
+        current = sub;
+    }
+    names.join(" ")
+}
+
+/// Whether the invoked subcommand was given `-o json`.
+fn output_is_json(matches: &ArgMatches) -> bool {
+    let mut current = matches;
+    while let Some((_, sub)) = current.subcommand() {
+        current = sub;
+    }
+    matches!(
+        current.try_get_one::<OutputFormat>("output"),
+        Ok(Some(OutputFormat::Json))
+    )
+}
+
 fn format_error_chain(err: &anyhow::Error) -> String {
     let mut out = err.to_string();
     for cause in err.chain().skip(1) {
@@ -66,6 +185,29 @@ fn format_error_chain(err: &anyhow::Error) -> String {
     out
 }
 
+fn format_timing(elapsed: std::time::Duration) -> String {
+    let stats = confcli::client::transfer_stats();
+    let mut out = format!("Timing: {} request(s) in {:.2?}", stats.requests, elapsed);
+    if stats.body_bytes > 0 {
+        let saved = 100.0 - (stats.wire_bytes as f64 * 100.0 / stats.body_bytes as f64);
+        out.push_str(&format!(
+            "; received {} compressed, {} decompressed ({saved:.0}% saved)",
+            helpers::human_size(stats.wire_bytes as i64),
+            helpers::human_size(stats.body_bytes as i64),
+        ));
+    }
+    if stats.retries > 0 {
+        out.push_str(&format!(
+            "; {} retr{} ({} rate-limited), {:.1?} backing off",
+            stats.retries,
+            if stats.retries == 1 { "y" } else { "ies" },
+            stats.rate_limited,
+            stats.backoff,
+        ));
+    }
+    out
+}
+
 fn generate_completions(ctx: &AppContext, args: cli::CompletionsArgs) -> Result<()> {
     if ctx.quiet {
         return Ok(());
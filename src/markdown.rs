@@ -1,16 +1,90 @@
 use anyhow::Result;
+#[cfg(feature = "cli-helpers")]
+use clap::ValueEnum;
 use htmd::HtmlToMarkdown;
 use pulldown_cmark::{Options, Parser, html};
 use regex::Regex;
+use std::collections::HashMap;
 use std::sync::LazyLock;
 
 #[derive(Debug, Clone, Copy, Default)]
 pub struct MarkdownOptions {
     pub keep_empty_list_items: bool,
+    /// Drop emoji characters and Confluence emoticon images (also from the header title).
+    pub strip_emoji: bool,
+    /// How [`add_source_header`] records the page's web link.
+    pub source_link: SourceLink,
+    /// Whether tables become pipe tables or stay raw HTML.
+    pub table_mode: TableMode,
+    /// Where image captions (`<figcaption>`, `ac:caption`) go.
+    pub captions: CaptionMode,
+    /// Render expand macros as `<details>` blocks instead of flattening their content.
+    pub expand_details: bool,
+    /// Dialect-specific details: task lists, callouts, wiki-links, and line breaks.
+    pub flavor: MarkdownFlavor,
+}
+
+/// How image captions are rendered in markdown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli-helpers", derive(ValueEnum))]
+pub enum CaptionMode {
+    /// As the image title: `![alt](src "caption")`.
+    #[default]
+    Title,
+    /// As an italic line below the image.
+    Line,
+    /// Drop captions.
+    Drop,
+}
+
+/// How HTML tables are rendered in markdown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli-helpers", derive(ValueEnum))]
+pub enum TableMode {
+    /// Always emit GitHub-flavored pipe tables.
+    #[default]
+    Gfm,
+    /// Always keep tables as raw HTML.
+    Html,
+    /// Keep raw HTML only for tables a pipe table can't express (merged cells or block
+    /// content such as lists, code, or several paragraphs in a cell).
+    Auto,
+}
+
+/// Where the page's source link goes in markdown output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli-helpers", derive(ValueEnum))]
+pub enum SourceLink {
+    /// `<!-- Source: URL -->` (some renderers show HTML comments verbatim).
+    #[default]
+    Comment,
+    /// A YAML front matter block with `title` and `source`.
+    FrontMatter,
+    /// A reference-style link definition, which renders as nothing.
+    Reference,
+    /// No source link.
+    None,
+}
+
+/// Markdown dialect the output is written for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli-helpers", derive(ValueEnum))]
+pub enum MarkdownFlavor {
+    /// GitHub: `[x]` task lists, `> [!NOTE]` alerts, and trailing-space line breaks.
+    #[default]
+    Gfm,
+    /// Plain CommonMark: tasks as literal `[x]` text, callouts as plain blockquotes, and
+    /// backslash line breaks.
+    Commonmark,
+    /// Obsidian: task lists, `> [!info]` callouts, `[[wiki-links]]` for links to Confluence
+    /// pages, and bare newlines as line breaks.
+    Obsidian,
 }
 
 // Regex compilation is fairly expensive and markdown conversion is a hot path.
 // Keep these compiled once for the lifetime of the process.
+static ENTITY_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").expect("ENTITY_RE"));
 static STYLE_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"(?s)<style[^>]*>.*?</style>").expect("STYLE_RE"));
 static PANEL_RE: LazyLock<Regex> = LazyLock::new(|| {
@@ -41,6 +115,92 @@ static TABLE_CELL_SEP_RE: LazyLock<Regex> =
 static IMAGE_ONLY_CELL_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^!\[[^\]]*\]\([^)]*\)$").expect("IMAGE_ONLY_CELL_RE"));
 
+static EMOTICON_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<img[^>]*class="[^"]*\bemoticon\b[^"]*"[^>]*>"#).expect("EMOTICON_RE")
+});
+
+static TABLE_TAG_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)<(/?)table\b[^>]*>").expect("TABLE_TAG_RE"));
+static SPAN_ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\b(?:colspan|rowspan)="?\s*([0-9]+)"#).expect("SPAN_ATTR_RE")
+});
+static BLOCK_IN_CELL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)<(?:ul|ol|pre|blockquote|h[1-6])\b").expect("BLOCK_IN_CELL_RE")
+});
+static CELL_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<t[dh]\b[^>]*>(.*?)</t[dh]>").expect("CELL_RE"));
+static PARAGRAPH_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)<p\b").expect("PARAGRAPH_RE"));
+static HTML_TAG_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"<([a-zA-Z][a-zA-Z0-9]*)((?:\s[^>]*)?)(/?)>").expect("HTML_TAG_RE")
+});
+static HTML_ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"([a-zA-Z-]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).expect("HTML_ATTR_RE")
+});
+static TEXT_ALIGN_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"text-align:\s*(left|center|right)").expect("TEXT_ALIGN_RE"));
+static BLANK_LINES_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\n\s*\n").expect("BLANK_LINES_RE"));
+
+static AC_IMAGE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<ac:image\b([^>]*)>(.*?)</ac:image>").expect("AC_IMAGE_RE"));
+static AC_IMAGE_ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(ac:alt|ac:title|ri:filename|ri:value)="([^"]*)""#).expect("AC_IMAGE_ATTR_RE")
+});
+static AC_CAPTION_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<ac:caption>(.*?)</ac:caption>").expect("AC_CAPTION_RE"));
+static FIGURE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<figure\b[^>]*>(.*?)</figure>").expect("FIGURE_RE"));
+static FIGCAPTION_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)<figcaption\b[^>]*>(.*?)</figcaption>").expect("FIGCAPTION_RE")
+});
+static ANY_TAG_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]+>").expect("ANY_TAG_RE"));
+static TITLE_ATTR_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\stitle="[^"]*""#).expect("TITLE_ATTR_RE"));
+
+static EXPAND_START_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"<div\b[^>]*class="[^"]*\bexpand-container\b[^"]*"[^>]*>|<ac:structured-macro\b[^>]*ac:name="expand"[^>]*>"#,
+    )
+    .expect("EXPAND_START_RE")
+});
+static EXPAND_TITLE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?s)<span[^>]*class="[^"]*\bexpand-control-text\b[^"]*"[^>]*>(.*?)</span>"#)
+        .expect("EXPAND_TITLE_RE")
+});
+static EXPAND_CONTENT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<div\b[^>]*class="([^"]*\bexpand-content\b[^"]*)"[^>]*>"#)
+        .expect("EXPAND_CONTENT_RE")
+});
+static MACRO_TITLE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?s)<ac:parameter ac:name="title">(.*?)</ac:parameter>"#).expect("MACRO_TITLE_RE")
+});
+static DIV_TAG_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<(/?)div\b[^>]*?(/?)>").expect("DIV_TAG_RE"));
+static MACRO_TAG_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<(/?)ac:structured-macro\b[^>]*?(/?)>").expect("MACRO_TAG_RE"));
+
+static TASK_ITEM_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<li\b([^>]*\bdata-inline-task-id="[^"]*"[^>]*)>"#).expect("TASK_ITEM_RE")
+});
+static CHECKED_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"class="[^"]*\bchecked\b"#).expect("CHECKED_RE"));
+static CALLOUT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?s)<div class="confluence-information-macro confluence-information-macro-(information|note|warning|tip)\b[^"]*"[^>]*>.*?<div class="confluence-information-macro-body[^"]*"[^>]*>(.*?)</div>\s*</div>"#,
+    )
+    .expect("CALLOUT_RE")
+});
+static CALLOUT_LINE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^((?:>\s?)+)CONFCLICALLOUT([A-Z]+)X\s*$").expect("CALLOUT_LINE_RE")
+});
+static PAGE_LINK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?s)<a\s([^>]*\bdata-linked-resource-type="page"[^>]*)>(.*?)</a>"#)
+        .expect("PAGE_LINK_RE")
+});
+static LINKED_ID_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\bdata-linked-resource-id="([0-9]+)""#).expect("LINKED_ID_RE"));
+
 static EMPTY_LIST_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^\s*(?:[-*+]|\d+\.)\s*$").expect("EMPTY_LIST_RE"));
 static TABLE_SEP_RE: LazyLock<Regex> = LazyLock::new(|| {
@@ -56,12 +216,531 @@ pub fn html_to_markdown_with_options(
     base_url: &str,
     options: MarkdownOptions,
 ) -> Result<String> {
-    let cleaned = preprocess_html(html, base_url)?;
+    html_to_markdown_with_page_names(html, base_url, options, None)
+}
+
+/// Like [`html_to_markdown_with_options`], but with the Obsidian flavor only links to pages
+/// in `page_names` (page id -> note name) become wiki-links, pointing at that note. Links
+/// to other pages stay regular links. Without a map, the link text is used as note name.
+pub fn html_to_markdown_with_page_names(
+    html: &str,
+    base_url: &str,
+    options: MarkdownOptions,
+    page_names: Option<&HashMap<String, String>>,
+) -> Result<String> {
+    let mut cleaned = preprocess_html(html, base_url)?;
+    cleaned = apply_captions(&cleaned, options.captions);
+    let mut expands = Vec::new();
+    if options.expand_details {
+        cleaned = extract_expands(&cleaned, &mut expands);
+    }
+    if options.strip_emoji {
+        cleaned = EMOTICON_RE.replace_all(&cleaned, "").to_string();
+    }
+    let (cleaned, raw_tables) = extract_html_tables(&cleaned, options.table_mode);
+    let mut wiki_links = Vec::new();
+    let cleaned = mark_flavor_html(&cleaned, options.flavor, page_names, &mut wiki_links);
     let markdown = HtmlToMarkdown::new().convert(&cleaned)?;
-    let markdown = postprocess_markdown(&markdown, options);
+    let mut markdown = postprocess_markdown(&markdown, options);
+    markdown = apply_flavor(&markdown, options.flavor, &wiki_links);
+    for (idx, table) in raw_tables.iter().enumerate() {
+        markdown = markdown.replace(&table_placeholder(idx), table);
+    }
+    for (idx, (title, open)) in expands.iter().enumerate() {
+        let open = if *open { " open" } else { "" };
+        markdown = markdown.replace(
+            &expand_placeholder(idx),
+            &format!("<details{open}><summary>{title}</summary>"),
+        );
+    }
+    markdown = markdown.replace(EXPAND_END_PLACEHOLDER, "</details>");
+    if options.strip_emoji {
+        markdown = strip_emoji(&markdown);
+    }
     Ok(markdown.trim().to_string())
 }
 
+/// Turn storage-format `ac:image` elements into `<figure>`s, then render every figure's
+/// caption according to `mode` so it isn't silently dropped by the converter.
+fn apply_captions(html: &str, mode: CaptionMode) -> String {
+    let html = AC_IMAGE_RE.replace_all(html, |caps: &regex::Captures| {
+        let mut alt = "";
+        let mut title = "";
+        let mut src = "";
+        for attr in AC_IMAGE_ATTR_RE.captures_iter(&caps[0]) {
+            let value = attr.get(2).map(|m| m.as_str()).unwrap_or("");
+            match &attr[1] {
+                "ac:alt" => alt = value,
+                "ac:title" => title = value,
+                _ if src.is_empty() => src = value,
+                _ => {}
+            }
+        }
+        let alt = if alt.is_empty() { src } else { alt };
+        let title = if title.is_empty() {
+            String::new()
+        } else {
+            format!(" title=\"{title}\"")
+        };
+        let caption = AC_CAPTION_RE
+            .captures(&caps[2])
+            .map(|c| format!("<figcaption>{}</figcaption>", &c[1]))
+            .unwrap_or_default();
+        format!("<figure><img src=\"{src}\" alt=\"{alt}\"{title}>{caption}</figure>")
+    });
+
+    FIGURE_RE
+        .replace_all(&html, |caps: &regex::Captures| {
+            let inner = &caps[1];
+            let caption = FIGCAPTION_RE
+                .captures(inner)
+                .map(|c| caption_text(&c[1]))
+                .unwrap_or_default();
+            let body = FIGCAPTION_RE.replace_all(inner, "");
+            if caption.is_empty() || mode == CaptionMode::Drop {
+                return format!("<p>{body}</p>");
+            }
+            match mode {
+                CaptionMode::Title => {
+                    let title = caption.replace('"', "&quot;");
+                    let body = IMG_RE.replace(&body, |img: &regex::Captures| {
+                        let attrs = TITLE_ATTR_RE.replace_all(&img[1], "");
+                        format!("<img{attrs} title=\"{title}\"{}>", &img[2])
+                    });
+                    format!("<p>{body}</p>")
+                }
+                _ => format!("<p>{body}</p><p><em>{caption}</em></p>"),
+            }
+        })
+        .to_string()
+}
+
+/// Caption markup flattened to one line of text.
+fn caption_text(html: &str) -> String {
+    let text = ANY_TAG_RE.replace_all(html, " ");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+const EXPAND_END_PLACEHOLDER: &str = "CONFCLIEXPANDENDX";
+
+fn expand_placeholder(idx: usize) -> String {
+    format!("CONFCLIEXPAND{idx}X")
+}
+
+const DEFAULT_EXPAND_TITLE: &str = "Click here to expand...";
+
+/// Replace expand macros (rendered `expand-container` divs or storage `expand` macros) with
+/// placeholder paragraphs around their content, recording each one's title and whether it
+/// starts expanded. Nested expands are handled recursively.
+fn extract_expands(html: &str, expands: &mut Vec<(String, bool)>) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(found) = EXPAND_START_RE.find(rest) {
+        let storage = found.as_str().starts_with("<ac:");
+        let tags: &Regex = if storage { &MACRO_TAG_RE } else { &DIV_TAG_RE };
+        let Some(end) = matching_close(rest, found.start(), tags) else {
+            break;
+        };
+        let segment = &rest[found.start()..end];
+        let Some((title, open, content)) = expand_parts(segment, storage) else {
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        };
+
+        out.push_str(&rest[..found.start()]);
+        let idx = expands.len();
+        let title = if title.is_empty() {
+            DEFAULT_EXPAND_TITLE.to_string()
+        } else {
+            title
+        };
+        expands.push((title, open));
+        let inner = extract_expands(content, expands);
+        out.push_str(&format!(
+            "<p>{}</p>{inner}<p>{EXPAND_END_PLACEHOLDER}</p>",
+            expand_placeholder(idx)
+        ));
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Title, initially-open flag, and body HTML of one expand macro.
+fn expand_parts(segment: &str, storage: bool) -> Option<(String, bool, &str)> {
+    if storage {
+        let title = MACRO_TITLE_RE
+            .captures(segment)
+            .map(|c| caption_text(&c[1]))
+            .unwrap_or_default();
+        let start = segment.find("<ac:rich-text-body>")? + "<ac:rich-text-body>".len();
+        let end = segment.rfind("</ac:rich-text-body>")?;
+        // Confluence has no "start expanded" setting for the storage macro.
+        return Some((title, false, segment.get(start..end)?));
+    }
+    let title = EXPAND_TITLE_RE
+        .captures(segment)
+        .map(|c| caption_text(&c[1]))
+        .unwrap_or_default();
+    let content = EXPAND_CONTENT_RE.captures(segment)?;
+    let open = !content[1].split_whitespace().any(|c| c == "expand-hidden");
+    let tag = content.get(0)?;
+    let end = matching_close(segment, tag.start(), &DIV_TAG_RE)?;
+    let inner_end = segment[..end].rfind('<')?;
+    Some((title, open, &segment[tag.end()..inner_end]))
+}
+
+/// Byte offset just past the tag closing the element that opens at `start`.
+fn matching_close(html: &str, start: usize, tags: &Regex) -> Option<usize> {
+    let mut depth = 0usize;
+    for tag in tags.captures_iter(&html[start..]) {
+        if !tag[2].is_empty() {
+            continue;
+        }
+        if tag[1].is_empty() {
+            depth += 1;
+        } else {
+            depth = depth.checked_sub(1)?;
+            if depth == 0 {
+                return Some(start + tag.get(0)?.end());
+            }
+        }
+    }
+    None
+}
+
+const TASK_DONE_PLACEHOLDER: &str = "CONFCLITASKDONEX";
+const TASK_OPEN_PLACEHOLDER: &str = "CONFCLITASKOPENX";
+
+fn wiki_link_placeholder(idx: usize) -> String {
+    format!("CONFCLIWIKI{idx}X")
+}
+
+/// Mark the constructs whose markdown differs between flavors (tasks, info/note/warning/tip
+/// callouts, and, for Obsidian, page links) with placeholders that [`apply_flavor`] turns
+/// into the flavor's syntax once the converter can no longer escape it.
+fn mark_flavor_html(
+    html: &str,
+    flavor: MarkdownFlavor,
+    page_names: Option<&HashMap<String, String>>,
+    wiki_links: &mut Vec<String>,
+) -> String {
+    let html = TASK_ITEM_RE.replace_all(html, |caps: &regex::Captures| {
+        let marker = if CHECKED_RE.is_match(&caps[1]) {
+            TASK_DONE_PLACEHOLDER
+        } else {
+            TASK_OPEN_PLACEHOLDER
+        };
+        format!("<li{}>{marker} ", &caps[1])
+    });
+    let html = CALLOUT_RE.replace_all(&html, |caps: &regex::Captures| {
+        if flavor == MarkdownFlavor::Commonmark {
+            return format!("<blockquote>{}</blockquote>", &caps[2]);
+        }
+        let kind = caps[1].to_ascii_uppercase();
+        format!(
+            "<blockquote><p>CONFCLICALLOUT{kind}X</p>{}</blockquote>",
+            &caps[2]
+        )
+    });
+    if flavor != MarkdownFlavor::Obsidian {
+        return html.into_owned();
+    }
+    PAGE_LINK_RE
+        .replace_all(&html, |caps: &regex::Captures| {
+            let text = ANY_TAG_RE.replace_all(&caps[2], "");
+            let text = decode_html_entities(text.trim()).replace(['[', ']', '|'], "");
+            let target = match page_names {
+                None => text.replace(['#', '^'], ""),
+                Some(names) => {
+                    let name = LINKED_ID_RE
+                        .captures(&caps[1])
+                        .and_then(|id| names.get(&id[1]));
+                    match name {
+                        Some(name) => name.clone(),
+                        None => return caps[0].to_string(),
+                    }
+                }
+            };
+            if target.is_empty() {
+                return caps[0].to_string();
+            }
+            let link = if text.is_empty() || text == target {
+                target
+            } else {
+                format!("{target}|{text}")
+            };
+            wiki_links.push(link);
+            wiki_link_placeholder(wiki_links.len() - 1)
+        })
+        .into_owned()
+}
+
+/// Replace the placeholders left by [`mark_flavor_html`] and adjust hard line breaks.
+fn apply_flavor(markdown: &str, flavor: MarkdownFlavor, wiki_links: &[String]) -> String {
+    let (done, open) = match flavor {
+        MarkdownFlavor::Commonmark => ("\\[x\\]", "\\[ \\]"),
+        _ => ("[x]", "[ ]"),
+    };
+    let mut out: Vec<String> = Vec::new();
+    let mut in_fence = false;
+    let mut skip_blank_quote = false;
+    for line in markdown.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if in_fence {
+            out.push(line.to_string());
+            continue;
+        }
+        // The converter leaves an empty quote line between the marker and the body.
+        if std::mem::take(&mut skip_blank_quote) && line.trim().trim_matches('>').is_empty() {
+            continue;
+        }
+        if let Some(caps) = CALLOUT_LINE_RE.captures(line) {
+            let kind = callout_kind(&caps[2], flavor);
+            out.push(format!("{} [!{kind}]", caps[1].trim_end()));
+            skip_blank_quote = true;
+            continue;
+        }
+        let mut line = line
+            .replace(TASK_DONE_PLACEHOLDER, done)
+            .replace(TASK_OPEN_PLACEHOLDER, open);
+        if line.ends_with("  ") && !line.trim().is_empty() {
+            match flavor {
+                MarkdownFlavor::Gfm => {}
+                MarkdownFlavor::Commonmark => line = format!("{}\\", line.trim_end()),
+                MarkdownFlavor::Obsidian => line = line.trim_end().to_string(),
+            }
+        }
+        out.push(line);
+    }
+    let mut markdown = out.join("\n");
+    for (idx, text) in wiki_links.iter().enumerate() {
+        markdown = markdown.replace(&wiki_link_placeholder(idx), &format!("[[{text}]]"));
+    }
+    markdown
+}
+
+/// Alert / callout type for a Confluence info, note, warning, or tip macro.
+fn callout_kind(macro_kind: &str, flavor: MarkdownFlavor) -> &'static str {
+    match (macro_kind, flavor) {
+        ("NOTE", MarkdownFlavor::Obsidian) => "warning",
+        ("WARNING", MarkdownFlavor::Obsidian) => "danger",
+        ("TIP", MarkdownFlavor::Obsidian) => "tip",
+        (_, MarkdownFlavor::Obsidian) => "info",
+        ("NOTE", _) => "WARNING",
+        ("WARNING", _) => "CAUTION",
+        ("TIP", _) => "TIP",
+        _ => "NOTE",
+    }
+}
+
+/// Decode the named entities storage format uses (`&amp;`, `&lt;`, `&gt;`, `&quot;`,
+/// `&apos;`, `&nbsp;` as a plain space) and numeric ones, in one pass; unknown entities stay.
+pub fn decode_html_entities(value: &str) -> String {
+    ENTITY_RE
+        .replace_all(value, |caps: &regex::Captures| {
+            let entity = &caps[1];
+            let decoded = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => {
+                    let code = entity
+                        .strip_prefix("#x")
+                        .or_else(|| entity.strip_prefix("#X"))
+                        .map(|hex| u32::from_str_radix(hex, 16))
+                        .or_else(|| entity.strip_prefix('#').map(str::parse::<u32>));
+                    code.and_then(Result::ok).and_then(char::from_u32)
+                }
+            };
+            decoded.map_or_else(|| caps[0].to_string(), String::from)
+        })
+        .into_owned()
+}
+
+/// Escape text for XML/XHTML content or a double- or single-quoted attribute.
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn table_placeholder(idx: usize) -> String {
+    format!("CONFCLIRAWTABLE{idx}X")
+}
+
+/// Pull out the top-level tables that `mode` keeps as HTML, leaving a placeholder paragraph
+/// behind so the markdown converter never sees them.
+fn extract_html_tables(html: &str, mode: TableMode) -> (String, Vec<String>) {
+    if mode == TableMode::Gfm {
+        return (html.to_string(), Vec::new());
+    }
+    let mut out = String::with_capacity(html.len());
+    let mut tables = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0usize;
+    let mut copied = 0usize;
+    for tag in TABLE_TAG_RE.captures_iter(html) {
+        let whole = tag.get(0).expect("match");
+        let closing = !tag[1].is_empty();
+        if !closing {
+            if depth == 0 {
+                start = whole.start();
+            }
+            depth += 1;
+        } else if depth > 0 {
+            depth -= 1;
+            if depth == 0 {
+                let table = &html[start..whole.end()];
+                if mode == TableMode::Html || !fits_pipe_table(table) {
+                    out.push_str(&html[copied..start]);
+                    out.push_str(&format!("<p>{}</p>", table_placeholder(tables.len())));
+                    tables.push(tidy_table_html(table));
+                    copied = whole.end();
+                }
+            }
+        }
+    }
+    out.push_str(&html[copied..]);
+    (out, tables)
+}
+
+/// Whether a pipe table can represent `table` without losing structure.
+fn fits_pipe_table(table: &str) -> bool {
+    let nested = TABLE_TAG_RE
+        .captures_iter(table)
+        .filter(|tag| tag[1].is_empty())
+        .count()
+        > 1;
+    if nested {
+        return false;
+    }
+    let merged = SPAN_ATTR_RE
+        .captures_iter(table)
+        .any(|cap| cap[1].parse::<u32>().unwrap_or(1) > 1);
+    if merged {
+        return false;
+    }
+    !CELL_RE.captures_iter(table).any(|cell| {
+        let content = &cell[1];
+        BLOCK_IN_CELL_RE.is_match(content) || PARAGRAPH_RE.find_iter(content).count() > 1
+    })
+}
+
+/// Strip Confluence's presentation attributes from a kept table, turning `text-align`
+/// styles into `align` so column alignment survives. Blank lines are removed because they
+/// would end the markdown HTML block early.
+fn tidy_table_html(table: &str) -> String {
+    let tidy = HTML_TAG_RE.replace_all(table, |caps: &regex::Captures| {
+        let name = &caps[1];
+        let mut attrs = String::new();
+        for attr in HTML_ATTR_RE.captures_iter(&caps[2]) {
+            let key = attr[1].to_ascii_lowercase();
+            let value = attr
+                .get(2)
+                .or(attr.get(3))
+                .map(|m| m.as_str())
+                .unwrap_or("");
+            match key.as_str() {
+                "colspan" | "rowspan" | "align" | "href" | "src" | "alt" | "title" => {
+                    attrs.push_str(&format!(" {key}=\"{value}\""));
+                }
+                "style" => {
+                    if let Some(align) = TEXT_ALIGN_RE.captures(value) {
+                        attrs.push_str(&format!(" align=\"{}\"", &align[1]));
+                    }
+                }
+                _ => {}
+            }
+        }
+        format!("<{name}{attrs}{}>", &caps[3])
+    });
+    BLANK_LINES_RE.replace_all(tidy.trim(), "\n").to_string()
+}
+
+/// Prefix `markdown` with a link to the page it came from, in the style chosen by
+/// `options.source_link`.
+pub fn add_source_header(
+    markdown: &str,
+    title: &str,
+    source: &str,
+    options: MarkdownOptions,
+) -> String {
+    let title = if options.strip_emoji {
+        strip_emoji(title).trim().to_string()
+    } else {
+        title.to_string()
+    };
+    match options.source_link {
+        SourceLink::Comment => format!("<!-- Source: {source} -->\n\n{markdown}"),
+        SourceLink::FrontMatter => {
+            // A JSON string is a valid YAML double-quoted scalar.
+            let title = serde_json::to_string(&title).unwrap_or_default();
+            format!("---\ntitle: {title}\nsource: {source}\n---\n\n{markdown}")
+        }
+        SourceLink::Reference => {
+            let title = title.replace('\\', "\\\\").replace('"', "\\\"");
+            format!("[source]: <{source}> \"{title}\"\n\n{markdown}")
+        }
+        SourceLink::None => markdown.to_string(),
+    }
+}
+
+/// Remove emoji (including modifiers, joiners, and variation selectors). A space left
+/// dangling by a removed emoji, as in `🚀 Launch` or `Done ✅`, is removed with it.
+pub fn strip_emoji(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut removed = false;
+    for ch in text.chars() {
+        if is_emoji_component(ch) {
+            removed = true;
+            continue;
+        }
+        if removed {
+            removed = false;
+            let at_boundary = out.is_empty() || out.ends_with(char::is_whitespace);
+            if ch == ' ' && at_boundary {
+                continue;
+            }
+            if (ch == '\n' || ch == '\r') && out.ends_with(' ') {
+                out.pop();
+            }
+        }
+        out.push(ch);
+    }
+    if removed && out.ends_with(' ') {
+        out.pop();
+    }
+    out
+}
+
+fn is_emoji_component(ch: char) -> bool {
+    matches!(
+        ch as u32,
+        0x1F000..=0x1FAFF   // pictographs, emoticons, transport, flags, skin tones
+            | 0x2600..=0x27BF   // misc symbols and dingbats
+            | 0x231A..=0x231B
+            | 0x23E9..=0x23F3
+            | 0x23F8..=0x23FA
+            | 0x2B05..=0x2B07
+            | 0x2B1B..=0x2B1C
+            | 0x2B50
+            | 0x2B55
+            | 0x200D            // zero-width joiner
+            | 0x20E3            // combining keycap
+            | 0xFE0E..=0xFE0F   // variation selectors
+            | 0xE0020..=0xE007F // tag sequences (subdivision flags)
+    )
+}
+
 pub fn decode_unicode_escapes_str(input: &str) -> String {
     decode_unicode_escapes(input)
 }
@@ -289,6 +968,14 @@ fn postprocess_markdown(markdown: &str, options: MarkdownOptions) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn decodes_named_and_numeric_entities_once() {
+        assert_eq!(
+            decode_html_entities("a &lt;b&gt; &quot;c&apos; &#39;d&#x27; &amp;lt; &bogus;"),
+            "a <b> \"c' 'd' &lt; &bogus;"
+        );
+    }
+
     #[test]
     fn converts_panel_to_blockquote() {
         let html = r#"<div class="panel"><div class="panelContent">Hello</div></div>"#;
@@ -334,6 +1021,139 @@ mod tests {
         assert_eq!(md, "![](image.webp)");
     }
 
+    #[test]
+    fn strips_emoji_and_emoticons() {
+        let html = r#"<h1>🚀 Launch plan</h1><p>Done ✅<img class="emoticon emoticon-tick" alt="(tick)" src="/wiki/s/tick.png"></p><p>👍🏽 ok</p>"#;
+        let options = MarkdownOptions {
+            strip_emoji: true,
+            ..Default::default()
+        };
+        let md = html_to_markdown_with_options(html, "https://example.com/wiki", options).unwrap();
+        assert_eq!(md, "# Launch plan\n\nDone\n\nok");
+        assert_eq!(strip_emoji("Family 👨‍👩‍👧 trip"), "Family trip");
+    }
+
+    #[test]
+    fn source_header_styles() {
+        let source = "https://example.com/wiki/spaces/X/pages/1";
+        let header = |source_link, strip_emoji| {
+            let options = MarkdownOptions {
+                source_link,
+                strip_emoji,
+                ..Default::default()
+            };
+            add_source_header("Body", "🚀 \"Launch\"", source, options)
+        };
+        assert_eq!(
+            header(SourceLink::Comment, false),
+            format!("<!-- Source: {source} -->\n\nBody")
+        );
+        assert_eq!(
+            header(SourceLink::FrontMatter, true),
+            format!("---\ntitle: \"\\\"Launch\\\"\"\nsource: {source}\n---\n\nBody")
+        );
+        assert_eq!(
+            header(SourceLink::Reference, true),
+            format!("[source]: <{source}> \"\\\"Launch\\\"\"\n\nBody")
+        );
+        assert_eq!(header(SourceLink::None, false), "Body");
+    }
+
+    #[test]
+    fn auto_table_mode_keeps_merged_cells_as_html() {
+        let html = r#"<table class="confluenceTable"><tbody><tr><th colspan="2" class="confluenceTh">Both</th></tr><tr><td style="text-align: center;">a</td><td>b</td></tr></tbody></table><table><tr><th>H</th></tr><tr><td>x</td></tr></table>"#;
+        let options = MarkdownOptions {
+            table_mode: TableMode::Auto,
+            ..Default::default()
+        };
+        let md = html_to_markdown_with_options(html, "https://example.com", options).unwrap();
+        assert!(md.starts_with(
+            r#"<table><tbody><tr><th colspan="2">Both</th></tr><tr><td align="center">a</td><td>b</td></tr></tbody></table>"#
+        ));
+        assert!(md.contains("| H |"), "{md}");
+    }
+
+    #[test]
+    fn pipe_table_detection() {
+        assert!(fits_pipe_table(
+            "<table><tr><td><p>one</p></td></tr></table>"
+        ));
+        assert!(fits_pipe_table(
+            r#"<table><tr><td colspan="1">x</td></tr></table>"#
+        ));
+        assert!(!fits_pipe_table(
+            "<table><tr><td><p>a</p><p>b</p></td></tr></table>"
+        ));
+        assert!(!fits_pipe_table(
+            "<table><tr><td><ul><li>x</li></ul></td></tr></table>"
+        ));
+        assert!(!fits_pipe_table(
+            "<table><tr><td><table><tr><td>x</td></tr></table></td></tr></table>"
+        ));
+    }
+
+    #[test]
+    fn renders_figure_captions() {
+        let html = r#"<figure><img src="https://example.com/a.png" alt="a.png"><figcaption><p>The <b>setup</b> flow</p></figcaption></figure>"#;
+        let render = |captions| {
+            let options = MarkdownOptions {
+                captions,
+                ..Default::default()
+            };
+            html_to_markdown_with_options(html, "https://example.com", options).unwrap()
+        };
+        assert_eq!(
+            render(CaptionMode::Title),
+            r#"![a.png](https://example.com/a.png "The setup flow")"#
+        );
+        assert_eq!(
+            render(CaptionMode::Line),
+            "![a.png](https://example.com/a.png)\n\n*The setup flow*"
+        );
+        assert_eq!(
+            render(CaptionMode::Drop),
+            "![a.png](https://example.com/a.png)"
+        );
+    }
+
+    #[test]
+    fn converts_storage_images_with_captions() {
+        let html = r#"<ac:image ac:alt="Diagram"><ri:attachment ri:filename="arch.png" /><ac:caption><p>Architecture</p></ac:caption></ac:image>"#;
+        let md = html_to_markdown(html, "https://example.com").unwrap();
+        assert_eq!(md, r#"![Diagram](arch.png "Architecture")"#);
+    }
+
+    #[test]
+    fn renders_expand_macros_as_details() {
+        let html = r#"<div id="expander-1" class="expand-container"><div class="expand-control"><span class="expand-icon"></span><span class="expand-control-text">More info</span></div><div class="expand-content expand-hidden"><p>Hidden <b>text</b></p><div class="expand-container"><div class="expand-control"><span class="expand-control-text">Inner</span></div><div class="expand-content"><p>Deep</p></div></div></div></div><p>After</p>"#;
+        let options = MarkdownOptions {
+            expand_details: true,
+            ..Default::default()
+        };
+        let md = html_to_markdown_with_options(html, "https://example.com", options).unwrap();
+        assert_eq!(
+            md,
+            "<details><summary>More info</summary>\n\nHidden **text**\n\n<details open><summary>Inner</summary>\n\nDeep\n\n</details>\n\n</details>\n\nAfter"
+        );
+
+        let flat = html_to_markdown(html, "https://example.com").unwrap();
+        assert!(!flat.contains("<details"));
+    }
+
+    #[test]
+    fn renders_storage_expand_macro_as_details() {
+        let html = r#"<ac:structured-macro ac:name="expand" ac:schema-version="1"><ac:rich-text-body><p>Body</p></ac:rich-text-body></ac:structured-macro>"#;
+        let options = MarkdownOptions {
+            expand_details: true,
+            ..Default::default()
+        };
+        let md = html_to_markdown_with_options(html, "https://example.com", options).unwrap();
+        assert_eq!(
+            md,
+            "<details><summary>Click here to expand...</summary>\n\nBody\n\n</details>"
+        );
+    }
+
     #[test]
     fn adds_alt_text_from_alias() {
         let html = r#"<img data-linked-resource-default-alias="diagram.png" src="/wiki/download/diagram.png">"#;
@@ -343,4 +1163,60 @@ mod tests {
             "![diagram.png](https://example.com/wiki/download/diagram.png)"
         );
     }
+
+    #[test]
+    fn flavors_render_tasks_callouts_and_line_breaks() {
+        let html = r#"<ul class="inline-task-list"><li data-inline-task-id="1" class="checked">Ship</li><li data-inline-task-id="2">Test</li></ul><div class="confluence-information-macro confluence-information-macro-warning"><span class="confluence-information-macro-icon"> </span><div class="confluence-information-macro-body"><p>Careful</p></div></div><p>a<br/>b</p>"#;
+        let render = |flavor| {
+            let options = MarkdownOptions {
+                flavor,
+                ..Default::default()
+            };
+            html_to_markdown_with_options(html, "https://example.com", options).unwrap()
+        };
+        assert_eq!(
+            render(MarkdownFlavor::Gfm),
+            "*   [x] Ship\n*   [ ] Test\n\n> [!CAUTION]\n> Careful\n\na  \nb"
+        );
+        assert_eq!(
+            render(MarkdownFlavor::Commonmark),
+            "*   \\[x\\] Ship\n*   \\[ \\] Test\n\n> Careful\n\na\\\nb"
+        );
+        assert_eq!(
+            render(MarkdownFlavor::Obsidian),
+            "*   [x] Ship\n*   [ ] Test\n\n> [!danger]\n> Careful\n\na\nb"
+        );
+    }
+
+    #[test]
+    fn obsidian_flavor_uses_wiki_links_for_pages() {
+        let html = r#"<p>See <a href="/wiki/spaces/A/pages/1/Run+book" data-linked-resource-id="1" data-linked-resource-type="page">Run book</a> and <a href="https://example.com">this</a>.</p>"#;
+        let options = MarkdownOptions {
+            flavor: MarkdownFlavor::Obsidian,
+            ..Default::default()
+        };
+        let md = html_to_markdown_with_options(html, "https://example.com/wiki", options).unwrap();
+        assert_eq!(md, "See [[Run book]] and [this](https://example.com).");
+    }
+
+    #[test]
+    fn obsidian_wiki_links_follow_page_names() {
+        let html = r#"<p><a href="/wiki/x" data-linked-resource-id="7" data-linked-resource-type="page">the runbook</a> <a href="/wiki/y" data-linked-resource-id="8" data-linked-resource-type="page">Other</a></p>"#;
+        let names = HashMap::from([("7".to_string(), "Runbook".to_string())]);
+        let options = MarkdownOptions {
+            flavor: MarkdownFlavor::Obsidian,
+            ..Default::default()
+        };
+        let md = html_to_markdown_with_page_names(
+            html,
+            "https://example.com/wiki",
+            options,
+            Some(&names),
+        )
+        .unwrap();
+        assert_eq!(
+            md,
+            "[[Runbook|the runbook]] [Other](https://example.com/wiki/y)"
+        );
+    }
 }
@@ -0,0 +1,176 @@
+//! `--notify`: tell the user a command finished, through a desktop notification or the
+//! command and webhook configured in the `notify` section of `config.json`.
+//!
+//! Notifying is best effort. A failure is logged and never changes the command's exit code.
+
+use std::time::Duration;
+
+use confcli::config::{NotifySettings, shell_command};
+use serde_json::json;
+use tokio::process::Command;
+
+/// How a command ended, for the notification.
+pub struct Outcome<'a> {
+    /// Subcommand path, e.g. `export` or `page create`.
+    pub command: &'a str,
+    pub error: Option<String>,
+    pub elapsed: Duration,
+}
+
+impl Outcome<'_> {
+    fn status(&self) -> &'static str {
+        if self.error.is_some() {
+            "failure"
+        } else {
+            "success"
+        }
+    }
+
+    fn message(&self) -> String {
+        let elapsed = format_elapsed(self.elapsed);
+        match &self.error {
+            None => format!("{} finished in {elapsed}", self.command),
+            Some(error) => format!("{} failed after {elapsed}: {error}", self.command),
+        }
+    }
+}
+
+pub async fn send(settings: &NotifySettings, outcome: &Outcome<'_>) {
+    if let Some(command) = &settings.command {
+        run_command(command, outcome).await;
+    }
+    if let Some(webhook) = &settings.webhook {
+        post_webhook(webhook, outcome).await;
+    }
+    if settings.command.is_none() && settings.webhook.is_none() {
+        desktop_notification(outcome).await;
+    }
+}
+
+async fn run_command(command: &str, outcome: &Outcome<'_>) {
+    let status = Command::from(shell_command(command))
+        .env("CONFCLI_NOTIFY_COMMAND", outcome.command)
+        .env("CONFCLI_NOTIFY_STATUS", outcome.status())
+        .env(
+            "CONFCLI_NOTIFY_DURATION",
+            outcome.elapsed.as_secs().to_string(),
+        )
+        .env("CONFCLI_NOTIFY_MESSAGE", outcome.message())
+        .status()
+        .await;
+    match status {
+        Ok(status) if status.success() => {}
+        Ok(status) => tracing::warn!(%status, "notify command failed"),
+        Err(err) => tracing::warn!(error = %err, "notify command could not start"),
+    }
+}
+
+async fn post_webhook(url: &str, outcome: &Outcome<'_>) {
+    let payload = json!({
+        "command": outcome.command,
+        "status": outcome.status(),
+        "durationSecs": outcome.elapsed.as_secs_f64(),
+        "message": outcome.message(),
+        "error": outcome.error,
+    });
+    let response = match reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+    {
+        Ok(client) => client.post(url).json(&payload).send().await,
+        Err(err) => Err(err),
+    };
+    match response {
+        Ok(response) if response.status().is_success() => {}
+        Ok(response) => tracing::warn!(status = %response.status(), "notify webhook failed"),
+        Err(err) => tracing::warn!(error = %err, "notify webhook failed"),
+    }
+}
+
+/// `notify-send` on Linux, Notification Center on macOS; a terminal bell where neither works.
+async fn desktop_notification(outcome: &Outcome<'_>) {
+    let title = match outcome.error {
+        None => "confcli: done",
+        Some(_) => "confcli: failed",
+    };
+    let message = outcome.message();
+    let shown = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification {} with title {}",
+            applescript_string(&message),
+            applescript_string(title)
+        );
+        Command::new("osascript")
+            .args(["-e", &script])
+            .status()
+            .await
+    } else {
+        Command::new("notify-send")
+            .args([title, &message])
+            .status()
+            .await
+    };
+    if !shown.is_ok_and(|status| status.success()) {
+        tracing::debug!("no desktop notifier; ringing the terminal bell");
+        eprint!("\x07");
+    }
+}
+
+fn applescript_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    match secs {
+        0..60 => format!("{:.1}s", elapsed.as_secs_f64()),
+        60..3600 => format!("{}m {:02}s", secs / 60, secs % 60),
+        _ => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_name_the_command_and_duration() {
+        let done = Outcome {
+            command: "export",
+            error: None,
+            elapsed: Duration::from_secs(192),
+        };
+        assert_eq!(done.message(), "export finished in 3m 12s");
+        assert_eq!(done.status(), "success");
+
+        let failed = Outcome {
+            command: "copy-tree",
+            error: Some("HTTP 403".to_string()),
+            elapsed: Duration::from_millis(2500),
+        };
+        assert_eq!(failed.message(), "copy-tree failed after 2.5s: HTTP 403");
+        assert_eq!(failed.status(), "failure");
+        assert_eq!(format_elapsed(Duration::from_secs(3723)), "1h 02m");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn command_sees_the_outcome_in_its_environment() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out.txt");
+        let command = format!(
+            "echo \"$CONFCLI_NOTIFY_COMMAND $CONFCLI_NOTIFY_STATUS $CONFCLI_NOTIFY_DURATION\" > '{}'",
+            out.display()
+        );
+        let outcome = Outcome {
+            command: "page create",
+            error: None,
+            elapsed: Duration::from_secs(4),
+        };
+        run_command(&command, &outcome).await;
+        assert_eq!(
+            std::fs::read_to_string(&out).unwrap(),
+            "page create success 4\n"
+        );
+    }
+}
@@ -1,7 +1,290 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::ValueEnum;
-use comfy_table::{Attribute, Cell, ContentArrangement, Table, presets::NOTHING};
+use comfy_table::presets::{ASCII_FULL_CONDENSED, NOTHING, UTF8_FULL_CONDENSED};
+use comfy_table::{Attribute, Cell, ContentArrangement, Table};
 use serde::Serialize;
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use crate::config::{HeaderStyle, TableBorders, ThemeSettings};
+
+/// Results held back for `--out` instead of going to stdout; `None` prints directly.
+static CAPTURED: Mutex<Option<Vec<u8>>> = Mutex::new(None);
+
+/// `--color` and the config's `theme`, set once at startup.
+static STYLE: OnceLock<(ColorChoice, ThemeSettings)> = OnceLock::new();
+
+/// Cleared by `--no-summary`.
+static SUMMARY: AtomicBool = AtomicBool::new(true);
+
+/// `--wide`/`--truncate`, set once at startup.
+static LAYOUT: OnceLock<TableLayout> = OnceLock::new();
+
+/// How tables handle rows wider than the terminal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TableLayout {
+    /// Wrap long cells onto extra lines to fit the terminal.
+    #[default]
+    Wrap,
+    /// Never wrap or cut; rows may run past the terminal edge.
+    Wide,
+    /// Cut long cells with `…`: to this many characters, or (`None`) so each row fits on
+    /// one terminal line.
+    Truncate(Option<usize>),
+}
+
+/// Lay out every later table as `layout`.
+pub fn set_table_layout(layout: TableLayout) {
+    let _ = LAYOUT.set(layout);
+}
+
+/// When to use color and text emphasis.
+#[derive(Debug, Clone, Copy, Default, ValueEnum, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// On a terminal, unless `NO_COLOR` is set or `TERM=dumb`.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether a stream that is (or is not) a terminal gets color.
+    pub fn enabled(self, is_terminal: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                is_terminal
+                    && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                    && std::env::var("TERM").map_or(true, |term| term != "dumb")
+            }
+        }
+    }
+}
+
+/// Style every later table and diff with `color` and `theme`.
+pub fn set_style(color: ColorChoice, theme: ThemeSettings) {
+    let _ = STYLE.set((color, theme));
+}
+
+fn style() -> (ColorChoice, ThemeSettings) {
+    STYLE.get().copied().unwrap_or_default()
+}
+
+/// Whether results get color: never when captured for `--out`, unless `--color always`.
+fn color_enabled() -> bool {
+    let (color, _) = style();
+    color.enabled(!capturing() && std::io::stdout().is_terminal())
+}
+
+/// A table with the theme's borders, and the emphasis for its header cells (none when color
+/// is off).
+fn themed_table() -> (Table, Option<Attribute>) {
+    let (_, theme) = style();
+    let mut table = Table::new();
+    table.load_preset(match theme.borders {
+        TableBorders::None => NOTHING,
+        TableBorders::Ascii => ASCII_FULL_CONDENSED,
+        TableBorders::Utf8 => UTF8_FULL_CONDENSED,
+    });
+    if !color_enabled() {
+        return (table, None);
+    }
+    table.enforce_styling();
+    let attribute = match theme.header {
+        HeaderStyle::Bold => Some(Attribute::Bold),
+        HeaderStyle::Underline => Some(Attribute::Underlined),
+        HeaderStyle::Plain => None,
+    };
+    (table, attribute)
+}
+
+fn header_cell(text: impl Into<String>, attribute: Option<Attribute>) -> Cell {
+    let cell = Cell::new(text.into());
+    match attribute {
+        Some(attribute) => cell.add_attribute(attribute),
+        None => cell,
+    }
+}
+
+/// Print (or, with `false`, drop) the decorations around results, such as "N results".
+pub fn set_summary(enabled: bool) {
+    SUMMARY.store(enabled, Ordering::Relaxed);
+}
+
+/// A decoration around results ("N results", "No results found."): on stderr, so piped or
+/// `--out` results never contain it, and dimmed when stderr gets color.
+fn summary(text: &str) {
+    if !SUMMARY.load(Ordering::Relaxed) {
+        return;
+    }
+    if style().0.enabled(std::io::stderr().is_terminal()) {
+        eprintln!("\x1b[2m{text}\x1b[0m");
+    } else {
+        eprintln!("{text}");
+    }
+}
+
+fn count_label(count: usize) -> String {
+    let label = if count == 1 { "result" } else { "results" };
+    format!("{count} {label}")
+}
+
+/// The terminal's width, if results go to one (`COLUMNS` when the size is unknown).
+fn terminal_width() -> Option<usize> {
+    if capturing() || !std::io::stdout().is_terminal() {
+        return None;
+    }
+    Table::new()
+        .width()
+        .map(usize::from)
+        .or_else(|| std::env::var("COLUMNS").ok()?.trim().parse().ok())
+}
+
+/// Apply the [`TableLayout`] to `table`, returning `rows` with long cells cut when
+/// truncating. A column is never cut below `floors[i]` characters (its header's width).
+fn arrange(table: &mut Table, floors: &[usize], rows: Vec<Vec<String>>) -> Vec<Vec<String>> {
+    let width = terminal_width();
+    let cap = match LAYOUT.get().copied().unwrap_or_default() {
+        TableLayout::Wrap => {
+            match width {
+                Some(width) => table
+                    .set_width(width.min(usize::from(u16::MAX)) as u16)
+                    .set_content_arrangement(ContentArrangement::Dynamic),
+                None => table.set_content_arrangement(ContentArrangement::Disabled),
+            };
+            return rows;
+        }
+        TableLayout::Wide => {
+            table.set_content_arrangement(ContentArrangement::Disabled);
+            return rows;
+        }
+        TableLayout::Truncate(cap) => cap,
+    };
+    table.set_content_arrangement(ContentArrangement::Disabled);
+    let rows: Vec<Vec<String>> = rows
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|cell| cell.replace('\n', " "))
+                .collect()
+        })
+        .collect();
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let floors: Vec<usize> = (0..columns)
+        .map(|i| floors.get(i).copied().unwrap_or(0))
+        .collect();
+    let cap = match cap {
+        Some(cap) => cap,
+        None => {
+            let Some(width) = width else { return rows };
+            let widths: Vec<usize> = (0..columns)
+                .map(|i| {
+                    rows.iter()
+                        .filter_map(|row| row.get(i))
+                        .map(|cell| cell.chars().count())
+                        .max()
+                        .unwrap_or(0)
+                })
+                .collect();
+            let separators = if style().1.borders == TableBorders::None {
+                (2 * columns).saturating_sub(1)
+            } else {
+                3 * columns + 1
+            };
+            fitting_cap(&widths, &floors, width.saturating_sub(separators))
+        }
+    };
+    rows.into_iter()
+        .map(|row| {
+            row.into_iter()
+                .enumerate()
+                .map(|(i, cell)| ellipsize(cell, cap.max(floors[i])))
+                .collect()
+        })
+        .collect()
+}
+
+/// The largest cell width at which the columns fit in `available` characters (at least 4,
+/// so a cut cell keeps a few characters).
+fn fitting_cap(widths: &[usize], floors: &[usize], available: usize) -> usize {
+    let total = |cap: usize| -> usize {
+        widths
+            .iter()
+            .zip(floors)
+            .map(|(width, floor)| (*width).min(cap).max(*floor))
+            .sum()
+    };
+    let (mut low, mut high) = (4, widths.iter().copied().max().unwrap_or(0).max(4));
+    if total(high) <= available {
+        return high;
+    }
+    while low < high {
+        let mid = (low + high).div_ceil(2);
+        if total(mid) <= available {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    low
+}
+
+/// `text` cut to `max` characters, the last one replaced by `…`.
+fn ellipsize(text: String, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text;
+    }
+    let mut cut: String = text.chars().take(max.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+/// Flush the first column against the left edge when the table has no borders.
+fn trim_first_column(table: &mut Table) {
+    if style().1.borders == TableBorders::None
+        && let Some(col) = table.column_mut(0)
+    {
+        col.set_padding((0, 1));
+    }
+}
+
+/// Color the `+`/`-` lines of a unified diff with the theme's diff colors, when color is on.
+pub fn colorize_diff(diff: &str) -> String {
+    if !color_enabled() {
+        return diff.to_string();
+    }
+    colorize_diff_with(diff, &style().1)
+}
+
+fn colorize_diff_with(diff: &str, theme: &ThemeSettings) -> String {
+    let mut out = String::with_capacity(diff.len());
+    for line in diff.split_inclusive('\n') {
+        let color = if line.starts_with("+++") || line.starts_with("---") {
+            None
+        } else if line.starts_with('+') {
+            theme.diff_added.ansi_code()
+        } else if line.starts_with('-') {
+            theme.diff_removed.ansi_code()
+        } else {
+            None
+        };
+        match color {
+            Some(code) => {
+                let (text, newline) = match line.strip_suffix('\n') {
+                    Some(text) => (text, "\n"),
+                    None => (line, ""),
+                };
+                out.push_str(&format!("\x1b[{code}m{text}\x1b[0m{newline}"));
+            }
+            None => out.push_str(line),
+        }
+    }
+    out
+}
 
 #[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
 pub enum OutputFormat {
@@ -21,33 +304,83 @@ impl std::fmt::Display for OutputFormat {
     }
 }
 
+/// Hold everything printed through [`emit`] and [`emit_line`] until [`persist_captured`].
+pub fn capture() {
+    *CAPTURED.lock().unwrap_or_else(|e| e.into_inner()) = Some(Vec::new());
+}
+
+fn capturing() -> bool {
+    CAPTURED.lock().unwrap_or_else(|e| e.into_inner()).is_some()
+}
+
+/// Print command results: to stdout, or into the `--out` buffer.
+pub fn emit(text: &str) {
+    let mut captured = CAPTURED.lock().unwrap_or_else(|e| e.into_inner());
+    match captured.as_mut() {
+        Some(buffer) => buffer.extend_from_slice(text.as_bytes()),
+        None => print!("{text}"),
+    }
+}
+
+/// [`emit`] followed by a newline.
+pub fn emit_line(text: &str) {
+    let mut captured = CAPTURED.lock().unwrap_or_else(|e| e.into_inner());
+    match captured.as_mut() {
+        Some(buffer) => {
+            buffer.extend_from_slice(text.as_bytes());
+            buffer.push(b'\n');
+        }
+        None => println!("{text}"),
+    }
+}
+
+/// Atomically replace `path` with the captured output (temp file in the same directory,
+/// then rename), so readers never see a partial file and a failed command leaves it alone.
+pub fn persist_captured(path: &Path) -> Result<()> {
+    let data = CAPTURED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take()
+        .unwrap_or_default();
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut tmp = tempfile::NamedTempFile::new_in(dir)
+        .with_context(|| format!("Failed to create temp file in {}", dir.display()))?;
+    tmp.write_all(&data)
+        .and_then(|_| tmp.as_file().sync_all())
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    tmp.persist(path)
+        .map_err(|e| e.error)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
 pub fn print_json<T: Serialize>(value: &T) -> Result<()> {
     let data = serde_json::to_string_pretty(value)?;
-    println!("{data}");
+    emit_line(&data);
     Ok(())
 }
 
 pub fn print_table(headers: &[&str], rows: Vec<Vec<String>>) {
     if rows.is_empty() {
-        println!("No results found.");
+        summary("No results found.");
         return;
     }
-    let mut table = Table::new();
-    table
-        .load_preset(NOTHING)
-        .set_content_arrangement(ContentArrangement::Dynamic)
-        .set_header(
-            headers
-                .iter()
-                .map(|h| Cell::new(h).add_attribute(Attribute::Bold))
-                .collect::<Vec<_>>(),
-        );
+    let (mut table, attribute) = themed_table();
+    let floors: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    let rows = arrange(&mut table, &floors, rows);
+    table.set_header(
+        headers
+            .iter()
+            .map(|h| header_cell(*h, attribute))
+            .collect::<Vec<_>>(),
+    );
     for row in rows {
         table.add_row(row);
     }
-    if let Some(col) = table.column_mut(0) {
-        col.set_padding((0, 1));
-    }
+    trim_first_column(&mut table);
     print_trimmed(&table);
 }
 
@@ -55,8 +388,7 @@ pub fn print_table_with_count(headers: &[&str], rows: Vec<Vec<String>>) {
     let count = rows.len();
     print_table(headers, rows);
     if count > 0 {
-        let label = if count == 1 { "result" } else { "results" };
-        println!("\x1b[2m{count} {label}\x1b[0m");
+        summary(&count_label(count));
     }
 }
 
@@ -64,30 +396,33 @@ pub fn print_kv(rows: Vec<Vec<String>>) {
     if rows.is_empty() {
         return;
     }
-    let mut table = Table::new();
-    table
-        .load_preset(NOTHING)
-        .set_content_arrangement(ContentArrangement::Dynamic);
+    let (mut table, attribute) = themed_table();
+    // Keys are never cut.
+    let key_width = rows
+        .iter()
+        .filter_map(|row| row.first())
+        .map(|key| key.chars().count())
+        .max()
+        .unwrap_or(0);
+    let rows = arrange(&mut table, &[key_width], rows);
     for row in rows {
         let mut cells = row.into_iter();
         let mut cell_row = Vec::new();
         if let Some(key) = cells.next() {
-            cell_row.push(Cell::new(key).add_attribute(Attribute::Bold));
+            cell_row.push(header_cell(key, attribute));
         }
         for val in cells {
             cell_row.push(Cell::new(val));
         }
         table.add_row(cell_row);
     }
-    if let Some(col) = table.column_mut(0) {
-        col.set_padding((0, 1));
-    }
+    trim_first_column(&mut table);
     print_trimmed(&table);
 }
 
 fn print_trimmed(table: &Table) {
     for line in table.to_string().lines() {
-        println!("{}", line.trim_end());
+        emit_line(line.trim_end());
     }
 }
 
@@ -99,7 +434,7 @@ fn escape_md_cell(s: &str) -> String {
 
 pub fn print_markdown_table(headers: &[&str], rows: Vec<Vec<String>>) {
     if rows.is_empty() {
-        println!("No results found.");
+        summary("No results found.");
         return;
     }
     let header_line = format!("| {} |", headers.join(" | "));
@@ -111,11 +446,11 @@ pub fn print_markdown_table(headers: &[&str], rows: Vec<Vec<String>>) {
             .collect::<Vec<_>>()
             .join(" | ")
     );
-    println!("{header_line}");
-    println!("{sep_line}");
+    emit_line(&header_line);
+    emit_line(&sep_line);
     for row in rows {
         let escaped: Vec<String> = row.iter().map(|c| escape_md_cell(c)).collect();
-        println!("| {} |", escaped.join(" | "));
+        emit_line(&format!("| {} |", escaped.join(" | ")));
     }
 }
 
@@ -123,19 +458,59 @@ pub fn print_markdown_table_with_count(headers: &[&str], rows: Vec<Vec<String>>)
     let count = rows.len();
     print_markdown_table(headers, rows);
     if count > 0 {
-        let label = if count == 1 { "result" } else { "results" };
-        println!("\n*{count} {label}*");
+        summary(&count_label(count));
     }
 }
 
 pub fn print_markdown_kv(rows: Vec<Vec<String>>) {
     for row in rows {
         if row.len() >= 2 {
-            println!(
+            emit_line(&format!(
                 "**{}** {}",
                 escape_md_cell(&row[0]),
                 escape_md_cell(&row[1])
-            );
+            ));
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ThemeColor;
+
+    #[test]
+    fn diff_lines_get_theme_colors() {
+        let theme = ThemeSettings {
+            diff_removed: ThemeColor::Magenta,
+            ..ThemeSettings::default()
+        };
+        let diff = "--- original\n+++ edited\n@@ -1 +1 @@\n-old\n+new\n same";
+        assert_eq!(
+            colorize_diff_with(diff, &theme),
+            "--- original\n+++ edited\n@@ -1 +1 @@\n\x1b[35m-old\x1b[0m\n\x1b[32m+new\x1b[0m\n same"
+        );
+    }
+
+    #[test]
+    fn truncation_shrinks_the_widest_columns_first() {
+        // Title 60 wide, URL 50, ID 6 (header "Title" keeps that column at least 5).
+        let widths = [6, 60, 50];
+        let floors = [2, 5, 3];
+        assert_eq!(fitting_cap(&widths, &floors, 200), 60);
+        assert_eq!(fitting_cap(&widths, &floors, 66), 30);
+        assert_eq!(fitting_cap(&widths, &floors, 5), 4);
+        assert_eq!(
+            ellipsize("Quarterly planning".to_string(), 10),
+            "Quarterly…"
+        );
+        assert_eq!(ellipsize("Short".to_string(), 10), "Short");
+    }
+
+    #[test]
+    fn explicit_color_choice_ignores_the_terminal() {
+        assert!(ColorChoice::Always.enabled(false));
+        assert!(!ColorChoice::Never.enabled(true));
+        assert!(!ColorChoice::Auto.enabled(false));
+    }
+}
@@ -0,0 +1,82 @@
+//! Dry-run plans: the writes a command would make, collected as it runs.
+//!
+//! With `--dry-run -o json`, a write command's own output is replaced by one plan document
+//! (`{"dryRun": true, "command": ..., "operations": [...]}`), so CI can diff what an import or
+//! copy-tree would do before approving the real run. Other formats keep each command's
+//! "Would ..." lines.
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::{Value, json};
+
+/// Set by `main` for `--dry-run -o json` on a write command.
+static CAPTURE: AtomicBool = AtomicBool::new(false);
+static OPERATIONS: Mutex<Vec<Operation>> = Mutex::new(Vec::new());
+
+/// One write a dry run skipped.
+#[derive(Debug, Clone, Serialize)]
+pub struct Operation {
+    /// What would happen: `create`, `update`, `delete`, `add`, `upload`, ...
+    pub action: &'static str,
+    /// What it happens to: `page`, `attachment`, `label`, `space`, ...
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    /// The id, key, title, or file it applies to.
+    pub target: String,
+    /// A summary of the request: titles, parents, sizes; never full bodies.
+    #[serde(skip_serializing_if = "Value::is_null")]
+    pub payload: Value,
+}
+
+#[cfg(feature = "write")]
+impl Operation {
+    pub fn new(action: &'static str, kind: &'static str, target: impl Into<String>) -> Self {
+        Self {
+            action,
+            kind,
+            target: target.into(),
+            payload: Value::Null,
+        }
+    }
+
+    pub fn with_payload(mut self, payload: Value) -> Self {
+        self.payload = payload;
+        self
+    }
+}
+
+/// Add `operation` to the plan. Commands still print their own dry-run message.
+#[cfg(feature = "write")]
+pub fn record(operation: Operation) {
+    OPERATIONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(operation);
+}
+
+pub fn set_capture(enabled: bool) {
+    CAPTURE.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether the plan owns stdout, so command output is held back.
+pub fn captures_output() -> bool {
+    CAPTURE.load(Ordering::Relaxed)
+}
+
+/// The plan document for `command` (e.g. `page create`), in the order operations were recorded.
+pub fn document(command: &str) -> Value {
+    let operations = OPERATIONS.lock().unwrap_or_else(|e| e.into_inner());
+    json!({
+        "dryRun": true,
+        "command": command,
+        "operations": *operations,
+    })
+}
+
+/// Print the plan document on stdout.
+pub fn print(command: &str) -> Result<()> {
+    confcli::output::print_json(&document(command))
+}
@@ -0,0 +1,119 @@
+//! `--preflight` for large writes: one read-only request listing what the current user may
+//! do with pages in the target space, so a missing permission is reported before the first
+//! write instead of halfway through.
+
+use anyhow::{Context, Result};
+use confcli::client::ApiClient;
+use serde_json::Value;
+use std::collections::HashSet;
+
+use crate::context::AppContext;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PageOperation {
+    Create,
+    Update,
+    Delete,
+}
+
+impl PageOperation {
+    const ALL: [PageOperation; 3] = [Self::Create, Self::Update, Self::Delete];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Update => "update",
+            Self::Delete => "delete",
+        }
+    }
+}
+
+/// Probe the page operations permitted in a space, print them to stderr, and fail listing
+/// every `required` operation that is missing.
+pub async fn check_space(
+    client: &ApiClient,
+    ctx: &AppContext,
+    space_id: &str,
+    space_label: &str,
+    required: &[PageOperation],
+) -> Result<()> {
+    let url = client.v2_url(&format!("/spaces/{space_id}/operations"));
+    let (json, _) = client.get_json(url).await.with_context(|| {
+        format!("Preflight could not read your permissions in space {space_label}")
+    })?;
+    let granted = page_operations(&json);
+
+    if !ctx.quiet {
+        let summary: Vec<String> = PageOperation::ALL
+            .iter()
+            .map(|op| {
+                let state = if granted.contains(op) { "yes" } else { "no" };
+                format!("{} {state}", op.as_str())
+            })
+            .collect();
+        eprintln!(
+            "Preflight: pages in space {space_label}: {}",
+            summary.join(", ")
+        );
+    }
+
+    let missing: Vec<&str> = required
+        .iter()
+        .filter(|op| !granted.contains(op))
+        .map(|op| op.as_str())
+        .collect();
+    if !missing.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Preflight failed: missing permission to {} pages in space {space_label}; nothing was changed",
+            missing.join("/")
+        ));
+    }
+    Ok(())
+}
+
+/// Page operations from a `/spaces/{id}/operations` response. Confluence's "Add page" space
+/// permission also covers editing, so `create` implies `update` unless the server lists
+/// `update` separately.
+fn page_operations(json: &Value) -> HashSet<PageOperation> {
+    let ops: Vec<&str> = json
+        .get("operations")
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter(|item| item.get("targetType").and_then(|v| v.as_str()) == Some("page"))
+                .filter_map(|item| item.get("operation").and_then(|v| v.as_str()))
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut granted: HashSet<PageOperation> = PageOperation::ALL
+        .into_iter()
+        .filter(|op| ops.contains(&op.as_str()))
+        .collect();
+    if granted.contains(&PageOperation::Create) {
+        granted.insert(PageOperation::Update);
+    }
+    granted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reads_page_operations() {
+        let json = json!({
+            "operations": [
+                { "operation": "read", "targetType": "space" },
+                { "operation": "create", "targetType": "page" },
+                { "operation": "delete", "targetType": "blogpost" },
+            ]
+        });
+        assert_eq!(
+            page_operations(&json),
+            HashSet::from([PageOperation::Create, PageOperation::Update])
+        );
+        assert!(page_operations(&json!({})).is_empty());
+    }
+}
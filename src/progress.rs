@@ -0,0 +1,200 @@
+//! Progress reporting for long-running commands.
+//!
+//! `--progress bar` (the default) draws indicatif bars; `--progress json` writes one NDJSON
+//! event per line to stderr so wrappers and CI UIs can render their own progress:
+//!
+//! ```text
+//! {"event":"start","task":"pages","current":0,"total":12,"bytes":null}
+//! {"event":"progress","task":"pages","current":1,"total":12,"bytes":null}
+//! {"event":"finish","task":"pages","current":12,"total":12,"bytes":null}
+//! ```
+//!
+//! Byte-oriented tasks (single downloads) count bytes in `current`/`total` and mirror the
+//! count in `bytes`; their `progress` events are throttled.
+
+use indicatif::{ProgressBar, ProgressStyle};
+use serde_json::json;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::cli::ProgressMode;
+use crate::context::AppContext;
+
+const BYTES_EVENT_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Clone)]
+pub enum Progress {
+    Bar(ProgressBar),
+    Json(Arc<JsonProgress>),
+}
+
+pub struct JsonProgress {
+    task: String,
+    bytes: bool,
+    current: AtomicU64,
+    /// 0 means unknown.
+    total: AtomicU64,
+    last_emit: Mutex<Option<Instant>>,
+}
+
+impl Progress {
+    /// Progress over a known number of items (pages, attachments, ...).
+    pub fn items(ctx: &AppContext, task: &str, total: u64) -> Option<Self> {
+        Self::items_with(ctx.progress, ctx.quiet, task, total)
+    }
+
+    /// Like [`Progress::items`], for callers that only carry the mode and quiet flag.
+    pub fn items_with(mode: ProgressMode, quiet: bool, task: &str, total: u64) -> Option<Self> {
+        match mode {
+            ProgressMode::None => None,
+            ProgressMode::Bar if quiet => None,
+            ProgressMode::Bar => {
+                let bar = ProgressBar::new(total);
+                bar.set_style(
+                    ProgressStyle::with_template("{spinner:.green} {pos}/{len} {wide_msg}")
+                        .unwrap(),
+                );
+                bar.set_message(task.to_string());
+                Some(Self::Bar(bar))
+            }
+            ProgressMode::Json => Some(Self::json(task, false, total)),
+        }
+    }
+
+    /// Progress over a byte stream whose length may only be known once the response arrives.
+    pub fn bytes(ctx: &AppContext, task: &str) -> Option<Self> {
+        match ctx.progress {
+            ProgressMode::None => None,
+            ProgressMode::Bar if ctx.quiet => None,
+            ProgressMode::Bar => {
+                let bar = ProgressBar::new_spinner();
+                bar.set_style(
+                    ProgressStyle::with_template(
+                        "{spinner:.green} {bytes}/{total_bytes} {bar:40.cyan/blue} {eta}",
+                    )
+                    .unwrap(),
+                );
+                bar.enable_steady_tick(Duration::from_millis(120));
+                Some(Self::Bar(bar))
+            }
+            ProgressMode::Json => Some(Self::json(task, true, 0)),
+        }
+    }
+
+    fn json(task: &str, bytes: bool, total: u64) -> Self {
+        let state = JsonProgress {
+            task: task.to_string(),
+            bytes,
+            current: AtomicU64::new(0),
+            total: AtomicU64::new(total),
+            last_emit: Mutex::new(None),
+        };
+        state.emit("start");
+        Self::Json(Arc::new(state))
+    }
+
+    /// Set the total if it is not known yet (e.g. from a response's Content-Length).
+    pub fn set_total_if_unknown(&self, total: u64) {
+        match self {
+            Self::Bar(bar) => {
+                if bar.length().is_none() {
+                    bar.set_length(total);
+                }
+            }
+            Self::Json(state) => {
+                let _ =
+                    state
+                        .total
+                        .compare_exchange(0, total, Ordering::Relaxed, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn inc(&self, delta: u64) {
+        match self {
+            Self::Bar(bar) => bar.inc(delta),
+            Self::Json(state) => {
+                state.current.fetch_add(delta, Ordering::Relaxed);
+                if state.should_emit() {
+                    state.emit("progress");
+                }
+            }
+        }
+    }
+
+    pub fn finish(&self) {
+        match self {
+            Self::Bar(bar) => bar.finish_and_clear(),
+            Self::Json(state) => state.emit("finish"),
+        }
+    }
+}
+
+impl JsonProgress {
+    fn should_emit(&self) -> bool {
+        if !self.bytes {
+            return true;
+        }
+        let now = Instant::now();
+        let mut last = self.last_emit.lock().unwrap_or_else(|e| e.into_inner());
+        if last.is_some_and(|prev| now.duration_since(prev) < BYTES_EVENT_INTERVAL) {
+            return false;
+        }
+        *last = Some(now);
+        true
+    }
+
+    fn event(&self, event: &str) -> serde_json::Value {
+        let current = self.current.load(Ordering::Relaxed);
+        let total = match self.total.load(Ordering::Relaxed) {
+            0 => None,
+            n => Some(n),
+        };
+        json!({
+            "event": event,
+            "task": self.task,
+            "current": current,
+            "total": total,
+            "bytes": self.bytes.then_some(current),
+        })
+    }
+
+    fn emit(&self, event: &str) {
+        eprintln!("{}", self.event(event));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_events_carry_task_counts_and_bytes() {
+        let Some(Progress::Json(state)) =
+            Progress::items_with(ProgressMode::Json, true, "pages", 3)
+        else {
+            panic!("expected json progress");
+        };
+        state.current.store(2, Ordering::Relaxed);
+        let event = state.event("progress");
+        assert_eq!(event["task"], "pages");
+        assert_eq!(event["current"], 2);
+        assert_eq!(event["total"], 3);
+        assert!(event["bytes"].is_null());
+
+        let Progress::Json(bytes) = Progress::json("download", true, 0) else {
+            unreachable!()
+        };
+        bytes.current.store(10, Ordering::Relaxed);
+        let event = bytes.event("progress");
+        assert!(event["total"].is_null());
+        assert_eq!(event["bytes"], 10);
+    }
+
+    #[test]
+    fn none_mode_and_quiet_bars_are_disabled() {
+        assert!(Progress::items_with(ProgressMode::None, false, "pages", 1).is_none());
+        assert!(Progress::items_with(ProgressMode::Bar, true, "pages", 1).is_none());
+    }
+}
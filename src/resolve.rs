@@ -1,29 +1,98 @@
 use anyhow::{Context, Result};
 use confcli::client::ApiClient;
+use futures_util::stream::{self, StreamExt};
 use lru::LruCache;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::io::IsTerminal;
 use std::num::NonZeroUsize;
-use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
 use tokio::sync::Mutex;
 use url::Url;
 
-use crate::helpers::url_with_query;
+use crate::helpers::{escape_cql_text, url_with_query};
 
 const SPACE_KEY_CACHE_CAPACITY: usize = 1024;
+const SPACE_ID_CACHE_CAPACITY: usize = 1024;
+const PAGE_ID_CACHE_CAPACITY: usize = 1024;
+const USER_NAME_CACHE_CAPACITY: usize = 1024;
+const LINK_TITLE_CACHE_CAPACITY: usize = 1024;
+const SUBTREE_CACHE_CAPACITY: usize = 64;
+
+/// Max concurrent `/spaces?ids=` requests when resolving many space keys at once.
+const SPACE_LOOKUP_CONCURRENCY: usize = 4;
+/// The v2 `/spaces` endpoint accepts at most 250 ids per request.
+const SPACE_LOOKUP_CHUNK: usize = 250;
+/// Candidates offered when `--fuzzy` falls back to a `title ~` search.
+const FUZZY_CANDIDATE_LIMIT: usize = 10;
+
+/// Set from the global `--fuzzy` flag; page references are resolved deep inside handlers,
+/// so this is process-wide rather than threaded through every call.
+static FUZZY_TITLES: AtomicBool = AtomicBool::new(false);
+
+pub fn set_fuzzy_titles(enabled: bool) {
+    FUZZY_TITLES.store(enabled, Ordering::Relaxed);
+}
+
+type StringCache = Mutex<LruCache<String, String>>;
 
-// Bounded cache to avoid unbounded memory growth in long-running / heavily scripted usage.
+// Bounded caches to avoid unbounded memory growth in long-running / heavily scripted usage.
 // Tokio mutex avoids blocking async runtime worker threads.
-static SPACE_KEY_CACHE: OnceLock<Mutex<LruCache<String, String>>> = OnceLock::new();
+static SPACE_KEY_CACHE: OnceLock<StringCache> = OnceLock::new();
+static SPACE_ID_CACHE: OnceLock<StringCache> = OnceLock::new();
+static PAGE_ID_CACHE: OnceLock<StringCache> = OnceLock::new();
+static USER_NAME_CACHE: OnceLock<StringCache> = OnceLock::new();
+static LINK_TITLE_CACHE: OnceLock<StringCache> = OnceLock::new();
+static SUBTREE_CACHE: OnceLock<SubtreeCache> = OnceLock::new();
+
+type SubtreeCache = Mutex<LruCache<String, Arc<HashSet<String>>>>;
+
+fn new_cache(capacity: usize) -> StringCache {
+    Mutex::new(LruCache::new(
+        NonZeroUsize::new(capacity).expect("non-zero cache capacity"),
+    ))
+}
 
-fn space_key_cache() -> &'static Mutex<LruCache<String, String>> {
-    SPACE_KEY_CACHE.get_or_init(|| {
+/// Space id -> display key.
+fn space_key_cache() -> &'static StringCache {
+    SPACE_KEY_CACHE.get_or_init(|| new_cache(SPACE_KEY_CACHE_CAPACITY))
+}
+
+/// Space key -> space id.
+fn space_id_cache() -> &'static StringCache {
+    SPACE_ID_CACHE.get_or_init(|| new_cache(SPACE_ID_CACHE_CAPACITY))
+}
+
+/// `SPACE:Title` (space part as typed) -> page id.
+fn page_id_cache() -> &'static StringCache {
+    PAGE_ID_CACHE.get_or_init(|| new_cache(PAGE_ID_CACHE_CAPACITY))
+}
+
+fn user_name_cache() -> &'static StringCache {
+    USER_NAME_CACHE.get_or_init(|| new_cache(USER_NAME_CACHE_CAPACITY))
+}
+
+/// Link URL -> display title (empty when the target couldn't be resolved).
+fn link_title_cache() -> &'static StringCache {
+    LINK_TITLE_CACHE.get_or_init(|| new_cache(LINK_TITLE_CACHE_CAPACITY))
+}
+
+/// Page id -> ids of the page and all its descendants.
+fn subtree_cache() -> &'static SubtreeCache {
+    SUBTREE_CACHE.get_or_init(|| {
         Mutex::new(LruCache::new(
-            NonZeroUsize::new(SPACE_KEY_CACHE_CAPACITY).expect("non-zero cache capacity"),
+            NonZeroUsize::new(SUBTREE_CACHE_CAPACITY).expect("non-zero cache capacity"),
         ))
     })
 }
 
+/// Cache key scoped to the client's site, so clients for different profiles in one process
+/// (e.g. a cross-site copy) never see each other's ids.
+fn scoped(client: &ApiClient, key: &str) -> String {
+    format!("{}\u{1f}{key}", client.base_url())
+}
+
 pub async fn resolve_page_id(client: &ApiClient, page: &str) -> Result<String> {
     let page = page.trim();
     if page.is_empty() {
@@ -49,28 +118,129 @@ pub async fn resolve_page_id(client: &ApiClient, page: &str) -> Result<String> {
             ));
         }
 
-        let space_id = resolve_space_id(client, space).await?;
-        let url = url_with_query(
-            &client.v2_url("/pages"),
-            &[
-                ("space-id", space_id),
-                ("title", title.to_string()),
-                ("limit", "1".to_string()),
-            ],
-        )?;
-        let items = client.get_paginated_results(url, false).await?;
-        let id = items
-            .first()
-            .and_then(|item| item.get("id"))
-            .and_then(|v| v.as_str())
-            .with_context(|| format!("Page '{title}' not found in space {space}"))?;
-        return Ok(id.to_string());
+        let cache_key = scoped(client, &format!("{space}:{title}"));
+        {
+            let mut guard = page_id_cache().lock().await;
+            if let Some(id) = guard.get(&cache_key).cloned() {
+                return Ok(id);
+            }
+        }
+
+        let fuzzy = FUZZY_TITLES.load(Ordering::Relaxed);
+        let id = resolve_page_title(client, space, title, fuzzy).await?;
+        page_id_cache().lock().await.put(cache_key, id.clone());
+        return Ok(id);
     }
     Err(anyhow::anyhow!(
         "Unable to resolve page reference '{page}'. Use a page id, URL, or SPACE:Title."
     ))
 }
 
+async fn resolve_page_title(
+    client: &ApiClient,
+    space: &str,
+    title: &str,
+    fuzzy: bool,
+) -> Result<String> {
+    let space_id = resolve_space_id(client, space).await?;
+    let url = url_with_query(
+        &client.v2_url("/pages"),
+        &[
+            ("space-id", space_id.clone()),
+            ("title", title.to_string()),
+            ("limit", "1".to_string()),
+        ],
+    )?;
+    let items = client.get_paginated_results(url, false).await?;
+    if let Some(id) = items
+        .first()
+        .and_then(|item| item.get("id"))
+        .and_then(|v| v.as_str())
+    {
+        return Ok(id.to_string());
+    }
+
+    if !fuzzy {
+        return Err(anyhow::anyhow!(
+            "Page '{title}' not found in space {space} (use --fuzzy to search similar titles)"
+        ));
+    }
+
+    let space_key = resolve_space_key(client, &space_id).await?;
+    let candidates = fuzzy_title_candidates(client, &space_key, title).await?;
+    let interactive = std::io::stdin().is_terminal() && std::io::stderr().is_terminal();
+    choose_title_candidate(space, title, &candidates, interactive)
+}
+
+/// `(id, title)` pairs of pages in `space_key` whose title contains words from `title`.
+async fn fuzzy_title_candidates(
+    client: &ApiClient,
+    space_key: &str,
+    title: &str,
+) -> Result<Vec<(String, String)>> {
+    let cql = format!(
+        "type = page AND space = \"{}\" AND title ~ \"{}\"",
+        escape_cql_text(space_key),
+        escape_cql_text(title)
+    );
+    let url = url_with_query(
+        &client.v1_url("/search"),
+        &[("cql", cql), ("limit", FUZZY_CANDIDATE_LIMIT.to_string())],
+    )?;
+    let (json, _) = client.get_json(url).await?;
+    let candidates = json
+        .get("results")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|item| item.get("content"))
+        .filter_map(|content| {
+            let id = content.get("id").and_then(|v| v.as_str())?;
+            let title = content.get("title").and_then(|v| v.as_str()).unwrap_or("");
+            Some((id.to_string(), title.to_string()))
+        })
+        .collect();
+    Ok(candidates)
+}
+
+fn choose_title_candidate(
+    space: &str,
+    title: &str,
+    candidates: &[(String, String)],
+    interactive: bool,
+) -> Result<String> {
+    if candidates.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Page '{title}' not found in space {space}, and no similar titles matched"
+        ));
+    }
+
+    if interactive {
+        let labels: Vec<String> = candidates
+            .iter()
+            .map(|(id, title)| format!("{title} ({id})"))
+            .collect();
+        let choice = dialoguer::Select::new()
+            .with_prompt(format!("No page titled '{title}' in {space}. Pick one"))
+            .items(&labels)
+            .default(0)
+            .interact_opt()?;
+        return match choice {
+            Some(idx) => Ok(candidates[idx].0.clone()),
+            None => Err(anyhow::anyhow!("Cancelled")),
+        };
+    }
+
+    let list: Vec<String> = candidates
+        .iter()
+        .map(|(id, title)| format!("  {space}:{title}  (id {id})"))
+        .collect();
+    Err(anyhow::anyhow!(
+        "Page '{title}' not found in space {space}. Similar titles:\n{}",
+        list.join("\n")
+    ))
+}
+
 pub async fn resolve_space_id(client: &ApiClient, space: &str) -> Result<String> {
     let space = space.trim();
     if space.is_empty() {
@@ -81,6 +251,13 @@ pub async fn resolve_space_id(client: &ApiClient, space: &str) -> Result<String>
         return Ok(space.to_string());
     }
 
+    {
+        let mut guard = space_id_cache().lock().await;
+        if let Some(id) = guard.get(&scoped(client, space)).cloned() {
+            return Ok(id);
+        }
+    }
+
     // Avoid manual string formatting here: `space` is user input and must be URL-encoded.
     let url = url_with_query(
         &client.v2_url("/spaces"),
@@ -91,15 +268,20 @@ pub async fn resolve_space_id(client: &ApiClient, space: &str) -> Result<String>
         .first()
         .and_then(|item| item.get("id"))
         .and_then(|v| v.as_str())
-        .with_context(|| format!("Space '{space}' not found"))?;
-    Ok(id.to_string())
+        .with_context(|| format!("Space '{space}' not found"))?
+        .to_string();
+    space_id_cache()
+        .lock()
+        .await
+        .put(scoped(client, space), id.clone());
+    Ok(id)
 }
 
 pub async fn resolve_space_key(client: &ApiClient, space_id: &str) -> Result<String> {
     // Fast path: serve from cache.
     {
         let mut guard = space_key_cache().lock().await;
-        if let Some(key) = guard.get(space_id).cloned() {
+        if let Some(key) = guard.get(&scoped(client, space_id)).cloned() {
             return Ok(key);
         }
     }
@@ -114,7 +296,7 @@ pub async fn resolve_space_key(client: &ApiClient, space_id: &str) -> Result<Str
 
     {
         let mut guard = space_key_cache().lock().await;
-        guard.put(space_id.to_string(), key.clone());
+        guard.put(scoped(client, space_id), key.clone());
     }
 
     Ok(key)
@@ -137,7 +319,7 @@ pub async fn resolve_space_keys(
     {
         let mut guard = space_key_cache().lock().await;
         for id in &unique {
-            if let Some(key) = guard.get(id).cloned() {
+            if let Some(key) = guard.get(&scoped(client, id)).cloned() {
                 out.insert(id.clone(), key);
             } else {
                 missing.push(id.clone());
@@ -149,12 +331,25 @@ pub async fn resolve_space_keys(
         return Ok(out);
     }
 
+    // Large listings touch many spaces; fetch the id chunks concurrently instead of serially.
+    let chunks: Vec<Vec<String>> = missing
+        .chunks(SPACE_LOOKUP_CHUNK)
+        .map(|chunk| chunk.to_vec())
+        .collect();
+    let mut stream = stream::iter(chunks)
+        .map(|chunk| async move {
+            let url = url_with_query(
+                &client.v2_url("/spaces"),
+                &[("ids", chunk.join(",")), ("limit", chunk.len().to_string())],
+            )?;
+            client.get_paginated_results(url, false).await
+        })
+        .buffer_unordered(SPACE_LOOKUP_CONCURRENCY);
+
     let mut fetched = HashMap::new();
-    for chunk in missing.chunks(250) {
-        let ids = chunk.join(",");
-        let url = client.v2_url(&format!("/spaces?ids={ids}&limit={}", chunk.len()));
-        let items = client.get_paginated_results(url, false).await?;
-        for item in items {
+    let mut keys_to_ids = Vec::new();
+    while let Some(items) = stream.next().await {
+        for item in items? {
             if let (Some(id), Some(key)) = (
                 item.get("id").and_then(|v| v.as_str()),
                 item.get("key").and_then(|v| v.as_str()),
@@ -168,15 +363,22 @@ pub async fn resolve_space_keys(
                     key.to_string()
                 };
                 fetched.insert(id.to_string(), display);
+                keys_to_ids.push((key.to_string(), id.to_string()));
             }
         }
     }
 
-    // Update cache.
+    // Update caches (both directions, so later `--space KEY` lookups are free too).
     {
         let mut guard = space_key_cache().lock().await;
         for (id, key) in &fetched {
-            guard.put(id.clone(), key.clone());
+            guard.put(scoped(client, id), key.clone());
+        }
+    }
+    {
+        let mut guard = space_id_cache().lock().await;
+        for (key, id) in keys_to_ids {
+            guard.put(scoped(client, &key), id);
         }
     }
 
@@ -184,6 +386,125 @@ pub async fn resolve_space_keys(
     Ok(out)
 }
 
+/// Resolve a user reference to an Atlassian account id.
+///
+/// `me` / `@me` map to the authenticated user; anything else is taken as an account id
+/// (Cloud does not allow looking users up by email).
+#[cfg(feature = "write")]
+pub async fn resolve_account_id(client: &ApiClient, user: &str) -> Result<String> {
+    let user = user.trim();
+    if user.is_empty() {
+        return Err(anyhow::anyhow!(
+            "User reference cannot be empty. Use an account id or 'me'."
+        ));
+    }
+    if !user.eq_ignore_ascii_case("me") && !user.eq_ignore_ascii_case("@me") {
+        return Ok(user.to_string());
+    }
+    let (json, _) = client.get_json(client.v1_url("/user/current")).await?;
+    let id = json
+        .get("accountId")
+        .and_then(|v| v.as_str())
+        .context("Current user response missing accountId")?;
+    Ok(id.to_string())
+}
+
+/// Look up a user's display name by account id (best-effort, cached per process).
+pub async fn resolve_user_display_name(client: &ApiClient, account_id: &str) -> Result<String> {
+    {
+        let mut guard = user_name_cache().lock().await;
+        if let Some(name) = guard.get(&scoped(client, account_id)).cloned() {
+            return Ok(name);
+        }
+    }
+
+    let url = url_with_query(
+        &client.v1_url("/user"),
+        &[("accountId", account_id.to_string())],
+    )?;
+    let (json, _) = client.get_json(url).await?;
+    let name = json
+        .get("displayName")
+        .or_else(|| json.get("publicName"))
+        .and_then(|v| v.as_str())
+        .unwrap_or(account_id)
+        .to_string();
+
+    {
+        let mut guard = user_name_cache().lock().await;
+        guard.put(scoped(client, account_id), name.clone());
+    }
+
+    Ok(name)
+}
+
+/// Human-readable title for a Confluence page or Jira issue link on the client's site:
+/// the page title, or `KEY-1: Summary` for issues. Other links, and targets that can't be
+/// read, yield `None`. Results (including misses) are cached per URL.
+pub async fn resolve_link_title(client: &ApiClient, link: &str) -> Option<String> {
+    {
+        let mut guard = link_title_cache().lock().await;
+        if let Some(title) = guard.get(&scoped(client, link)) {
+            return (!title.is_empty()).then(|| title.clone());
+        }
+    }
+
+    let title = fetch_link_title(client, link).await.unwrap_or_default();
+    link_title_cache()
+        .lock()
+        .await
+        .put(scoped(client, link), title.clone());
+    (!title.is_empty()).then_some(title)
+}
+
+async fn fetch_link_title(client: &ApiClient, link: &str) -> Option<String> {
+    let url = Url::parse(link).ok()?;
+    let site = Url::parse(client.origin_url()).ok()?;
+    if url.host_str() != site.host_str() {
+        return None;
+    }
+
+    if let Some(key) = jira_issue_key(&url) {
+        let api = client.jira_api_url(&format!("/issue/{key}?fields=summary"));
+        let (json, _) = client.get_json(api).await.ok()?;
+        let summary = json
+            .get("fields")
+            .and_then(|f| f.get("summary"))
+            .and_then(|s| s.as_str())
+            .unwrap_or("");
+        return Some(if summary.is_empty() {
+            key
+        } else {
+            format!("{key}: {summary}")
+        });
+    }
+
+    let page_id = extract_page_id_from_url(&url)?;
+    let (json, _) = client
+        .get_json(client.v2_url(&format!("/pages/{page_id}")))
+        .await
+        .ok()?;
+    json.get("title")
+        .and_then(|t| t.as_str())
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+}
+
+/// `PROJ-123` from a Jira `/browse/PROJ-123` URL.
+fn jira_issue_key(url: &Url) -> Option<String> {
+    let mut segments = url.path_segments()?;
+    segments.find(|seg| *seg == "browse")?;
+    let key = segments.next()?;
+    let (project, number) = key.split_once('-')?;
+    let valid = project.starts_with(|c: char| c.is_ascii_uppercase())
+        && project
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+        && !number.is_empty()
+        && number.chars().all(|c| c.is_ascii_digit());
+    valid.then(|| key.to_string())
+}
+
 pub fn extract_page_id_from_url(url: &Url) -> Option<String> {
     if let Some(segments) = url.path_segments() {
         let mut iter = segments;
@@ -211,6 +532,42 @@ pub fn extract_page_id_from_url(url: &Url) -> Option<String> {
     })
 }
 
+/// Ids of a page and all its descendants (`--under`), from the direct-children walk. The
+/// walk costs a request per page with children, so the set is cached per page.
+pub async fn subtree_page_ids(client: &ApiClient, page_id: &str) -> Result<Arc<HashSet<String>>> {
+    let key = scoped(client, page_id);
+    if let Some(ids) = subtree_cache().lock().await.get(&key) {
+        return Ok(ids.clone());
+    }
+    let descendants =
+        confcli::tree::fetch_descendants_via_direct_children(client, page_id, 250, true, None)
+            .await
+            .with_context(|| format!("Failed to list the pages under {page_id}"))?;
+    let mut ids: HashSet<String> = descendants
+        .iter()
+        .filter_map(|item| item.get("id").and_then(|v| v.as_str()))
+        .map(str::to_string)
+        .collect();
+    ids.insert(page_id.to_string());
+    let ids = Arc::new(ids);
+    subtree_cache().lock().await.put(key, ids.clone());
+    Ok(ids)
+}
+
+/// Every result of a page listing `url` that lies in `page`'s subtree. All result pages are
+/// fetched, since a subtree can be spread across the whole listing.
+pub async fn list_pages_under(client: &ApiClient, page: &str, url: String) -> Result<Vec<Value>> {
+    let page_id = resolve_page_id(client, page).await?;
+    let subtree = subtree_page_ids(client, &page_id).await?;
+    let mut items = client.get_paginated_results(url, true).await?;
+    items.retain(|item| {
+        item.get("id")
+            .and_then(|v| v.as_str())
+            .is_some_and(|id| subtree.contains(id))
+    });
+    Ok(items)
+}
+
 #[cfg(feature = "write")]
 pub async fn page_status(client: &ApiClient, page_id: &str) -> Result<String> {
     let url = client.v2_url(&format!("/pages/{page_id}"));
@@ -231,6 +588,12 @@ pub fn build_page_tree(items: &[Value]) -> Vec<String> {
         child_position: i64,
     }
 
+    // Pages whose parent isn't listed (e.g. the top of an `--under` subtree) are roots too.
+    let listed: HashSet<&str> = items
+        .iter()
+        .filter_map(|item| item.get("id").and_then(|v| v.as_str()))
+        .collect();
+
     // Avoid cloning full JSON blobs into the tree structure; we only need a few fields.
     let mut roots: Vec<NodeView> = Vec::new();
     let mut children: HashMap<String, Vec<NodeView>> = HashMap::new();
@@ -262,7 +625,7 @@ pub fn build_page_tree(items: &[Value]) -> Vec<String> {
             child_position,
         };
 
-        if view.parent_id.is_empty() {
+        if view.parent_id.is_empty() || !listed.contains(view.parent_id.as_str()) {
             roots.push(view);
         } else {
             children
@@ -336,6 +699,18 @@ mod tests {
         assert!(format!("{err:#}").contains("SPACE:Title"));
     }
 
+    #[test]
+    fn page_tree_roots_include_pages_with_unlisted_parents() {
+        let items = vec![
+            serde_json::json!({ "id": "2", "parentId": "1", "title": "Team", "childPosition": 0 }),
+            serde_json::json!({ "id": "3", "parentId": "2", "title": "Runbook", "childPosition": 0 }),
+        ];
+        assert_eq!(
+            build_page_tree(&items),
+            vec!["- Team (2)", "  - Runbook (3)"]
+        );
+    }
+
     #[tokio::test]
     async fn resolve_space_id_rejects_empty_input() {
         let client = test_client();
@@ -344,6 +719,104 @@ mod tests {
         assert!(format!("{err:#}").contains("cannot be empty"));
     }
 
+    #[tokio::test]
+    async fn resolve_space_keys_batches_lookups_and_caches_results() {
+        use crate::test_support::http_server::start_server;
+        use std::sync::atomic::Ordering;
+
+        // Ids are unique to this test because the caches are process-wide.
+        let srv = start_server(|_hit, path| {
+            let query = Url::parse(&format!("http://localhost{path}")).unwrap();
+            let ids: String = query
+                .query_pairs()
+                .find(|(k, _)| k == "ids")
+                .map(|(_, v)| v.into_owned())
+                .unwrap_or_default();
+            let results: Vec<Value> = ids
+                .split(',')
+                .map(|id| serde_json::json!({ "id": id, "key": format!("K{id}") }))
+                .collect();
+            let body = serde_json::json!({ "results": results }).to_string();
+            (200, vec![], body.into_bytes())
+        })
+        .await;
+
+        let client = ApiClient::new(
+            srv.base_url.clone(),
+            srv.base_url.clone(),
+            srv.base_url.clone(),
+            AuthMethod::Bearer {
+                token: "test".to_string(),
+            },
+            0,
+        )
+        .unwrap();
+
+        let ids: Vec<String> = (0..300).map(|i| format!("9100{i:03}")).collect();
+        let keys = resolve_space_keys(&client, &ids).await.unwrap();
+        assert_eq!(keys.len(), 300);
+        assert_eq!(keys.get("9100042").map(String::as_str), Some("K9100042"));
+        assert_eq!(srv.hits.load(Ordering::SeqCst), 2);
+
+        // Second pass is served entirely from the cache, including key -> id lookups.
+        resolve_space_keys(&client, &ids).await.unwrap();
+        assert_eq!(
+            resolve_space_id(&client, "K9100007").await.unwrap(),
+            "9100007"
+        );
+        assert_eq!(srv.hits.load(Ordering::SeqCst), 2);
+
+        let _ = srv.shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn fuzzy_title_lookup_lists_candidates_when_not_interactive() {
+        use crate::test_support::http_server::start_server;
+
+        let srv = start_server(|_hit, path| {
+            let body = if path.starts_with("/pages") {
+                serde_json::json!({ "results": [] })
+            } else if path.starts_with("/spaces/") {
+                serde_json::json!({ "id": "9200001", "key": "FUZ" })
+            } else {
+                assert!(path.starts_with("/search"), "unexpected path {path}");
+                assert!(path.contains("title"));
+                serde_json::json!({ "results": [
+                    { "content": { "id": "11", "type": "page", "title": "Release notes 2024" } },
+                    { "content": { "id": "12", "type": "page", "title": "Release process" } },
+                ] })
+            };
+            (200, vec![], body.to_string().into_bytes())
+        })
+        .await;
+        let client = ApiClient::new(
+            srv.base_url.clone(),
+            srv.base_url.clone(),
+            srv.base_url.clone(),
+            AuthMethod::Bearer {
+                token: "test".to_string(),
+            },
+            0,
+        )
+        .unwrap();
+
+        let err = resolve_page_title(&client, "9200001", "Release", false)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("--fuzzy"));
+
+        let candidates = fuzzy_title_candidates(&client, "FUZ", "Release")
+            .await
+            .unwrap();
+        assert_eq!(candidates.len(), 2);
+        let err = choose_title_candidate("FUZ", "Release", &candidates, false).unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("FUZ:Release notes 2024  (id 11)"));
+        assert!(msg.contains("FUZ:Release process  (id 12)"));
+
+        let _ = srv.shutdown.send(());
+    }
+
     #[test]
     fn extract_page_id_from_query_requires_numeric_page_id() {
         let valid =
@@ -0,0 +1,129 @@
+//! Sanitizing rendered (`view`) HTML for `page body --format view --sanitize`.
+//!
+//! The HTML is parsed and rebuilt by `ammonia` from an allow-list of tags, attributes, and
+//! URL schemes, so malformed markup and encoded URLs can't slip active content through.
+
+#[cfg(feature = "cli-helpers")]
+use clap::ValueEnum;
+use std::collections::HashSet;
+
+/// How much of the rendered HTML to remove.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli-helpers", derive(ValueEnum))]
+pub enum HtmlSanitize {
+    /// The HTML exactly as Confluence rendered it.
+    #[default]
+    None,
+    /// Drop `<script>` and `<style>` elements, event handlers, and non-web URLs (such as
+    /// `javascript:`); keep styling and embedded frames.
+    Basic,
+    /// `basic`, plus `style` and `data-*` attributes and `<iframe>`, `<object>`, and
+    /// `<embed>` elements.
+    Strict,
+}
+
+pub fn sanitize_html(html: &str, mode: HtmlSanitize) -> String {
+    if mode == HtmlSanitize::None {
+        return html.to_string();
+    }
+    let mut builder = ammonia::Builder::default();
+    // Confluence's own markup: keep it renderable and leave links as they were.
+    builder
+        .link_rel(None)
+        .add_generic_attributes(["class", "id", "dir"]);
+    match mode {
+        HtmlSanitize::Basic => {
+            builder
+                .add_generic_attributes(["style"])
+                .generic_attribute_prefixes(HashSet::from(["data-"]))
+                .add_tags(["iframe"])
+                .add_tag_attributes(
+                    "iframe",
+                    ["src", "width", "height", "title", "allowfullscreen"],
+                );
+        }
+        _ => {
+            builder.clean_content_tags(HashSet::from(["script", "style", "iframe", "object"]));
+        }
+    }
+    builder.clean(html).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HTML: &str = concat!(
+        r#"<style>p { color: red }</style>"#,
+        r#"<p class="x" style="margin: 0" data-mce-id='7' onclick="steal()">Set onload=1 here</p>"#,
+        r#"<script type="text/javascript">alert(1)</script>"#,
+        r#"<a href="javascript:alert(1)">bad</a> <a href="https://x.test/">good</a>"#,
+        r#"<iframe src="https://x.test/embed"></iframe>"#,
+    );
+
+    #[test]
+    fn none_keeps_html_untouched() {
+        assert_eq!(sanitize_html(HTML, HtmlSanitize::None), HTML);
+    }
+
+    #[test]
+    fn basic_drops_active_content() {
+        assert_eq!(
+            sanitize_html(HTML, HtmlSanitize::Basic),
+            concat!(
+                r#"<p class="x" style="margin: 0" data-mce-id="7">Set onload=1 here</p>"#,
+                r#"<a>bad</a> <a href="https://x.test/">good</a>"#,
+                r#"<iframe src="https://x.test/embed"></iframe>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn strict_also_drops_styling_data_and_embeds() {
+        assert_eq!(
+            sanitize_html(HTML, HtmlSanitize::Strict),
+            concat!(
+                r#"<p class="x">Set onload=1 here</p>"#,
+                r#"<a>bad</a> <a href="https://x.test/">good</a>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn bypass_payloads_are_neutralized() {
+        let payloads = [
+            "<img/onerror=alert(1) src=x>",
+            r#"<a href="&#106;avascript:alert(1)">x</a>"#,
+            r#"<a title=">" onclick="alert(1)">x</a>"#,
+            "<a href=\"java\tscript:alert(1)\">x</a>",
+            r#"<svg><a xlink:href="javascript:alert(1)">x</a></svg>"#,
+            "<scr<script></script>ipt>alert(1)</script>",
+        ];
+        for mode in [HtmlSanitize::Basic, HtmlSanitize::Strict] {
+            for payload in payloads {
+                let out = sanitize_html(payload, mode).to_ascii_lowercase();
+                for active in [
+                    "onerror",
+                    "onclick",
+                    "javascript",
+                    "xlink:href",
+                    "<script",
+                    "<scr",
+                ] {
+                    assert!(!out.contains(active), "{payload:?} -> {out:?}");
+                }
+            }
+        }
+        assert_eq!(
+            sanitize_html("<img/onerror=alert(1) src=x>", HtmlSanitize::Basic),
+            r#"<img src="x">"#
+        );
+        assert_eq!(
+            sanitize_html(
+                r#"<a title=">" onclick="alert(1)">x</a>"#,
+                HtmlSanitize::Basic
+            ),
+            r#"<a title="&gt;">x</a>"#
+        );
+    }
+}
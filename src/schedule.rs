@@ -0,0 +1,177 @@
+//! Request pacing and run windows for long unattended jobs (`--pace`, `--run-window`).
+//!
+//! Both are enforced by [`crate::client::ApiClient`] before every request, so all workers of
+//! a bulk command share one budget: a paced client starts requests evenly spread at the given
+//! rate, and a client with a run window holds requests while the clock is outside it.
+
+use std::str::FromStr;
+use std::sync::Mutex as StdMutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// A daily window of UTC wall-clock time, e.g. `22:00-06:00`; it may wrap past midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunWindow {
+    /// Seconds after midnight the window opens.
+    start: u64,
+    /// Seconds after midnight the window closes.
+    end: u64,
+}
+
+impl RunWindow {
+    /// How long to wait, `seconds` after midnight, until the window is open (`None` when it
+    /// already is).
+    pub fn wait_at(&self, seconds: u64) -> Option<Duration> {
+        let seconds = seconds % SECONDS_PER_DAY;
+        let open = if self.start < self.end {
+            (self.start..self.end).contains(&seconds)
+        } else {
+            seconds >= self.start || seconds < self.end
+        };
+        if open {
+            return None;
+        }
+        let wait = (self.start + SECONDS_PER_DAY - seconds) % SECONDS_PER_DAY;
+        Some(Duration::from_secs(wait))
+    }
+
+    /// [`RunWindow::wait_at`] for the current time.
+    pub fn wait_now(&self) -> Option<Duration> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.wait_at(now)
+    }
+}
+
+impl std::fmt::Display for RunWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let hm = |s: u64| format!("{:02}:{:02}", s / 3600, s % 3600 / 60);
+        write!(f, "{}-{} UTC", hm(self.start), hm(self.end))
+    }
+}
+
+impl FromStr for RunWindow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid run window '{s}': expected HH:MM-HH:MM (UTC)");
+        let (start, end) = s.trim().split_once('-').ok_or_else(invalid)?;
+        let time = |t: &str| -> Option<u64> {
+            let (h, m) = t.trim().split_once(':')?;
+            let (h, m) = (h.parse::<u64>().ok()?, m.parse::<u64>().ok()?);
+            (h < 24 && m < 60).then_some(h * 3600 + m * 60)
+        };
+        let (start, end) = (
+            time(start).ok_or_else(invalid)?,
+            time(end).ok_or_else(invalid)?,
+        );
+        if start == end {
+            return Err(format!(
+                "invalid run window '{s}': start and end are the same"
+            ));
+        }
+        Ok(Self { start, end })
+    }
+}
+
+/// Pacing and run window shared by every clone of one client.
+#[derive(Debug, Default)]
+pub(crate) struct Schedule {
+    /// Gap between request starts; `None` is unpaced.
+    interval: Option<Duration>,
+    next: StdMutex<Option<tokio::time::Instant>>,
+    window: Option<RunWindow>,
+    /// Set while a pause for the window has been logged, so concurrent workers log it once.
+    pausing: AtomicBool,
+}
+
+impl Schedule {
+    /// `pace` is in requests per minute; `None` or `0` is unpaced.
+    pub(crate) fn new(pace: Option<u32>, window: Option<RunWindow>) -> Self {
+        Self {
+            interval: pace
+                .filter(|pace| *pace > 0)
+                .map(|pace| Duration::from_secs(60) / pace),
+            window,
+            ..Self::default()
+        }
+    }
+
+    /// Wait until the run window is open, then for this request's turn.
+    pub(crate) async fn wait(&self) {
+        if let Some(window) = &self.window {
+            while let Some(wait) = window.wait_now() {
+                if !self.pausing.swap(true, Ordering::Relaxed) {
+                    tracing::warn!(
+                        "Outside the run window {window}; pausing {} min until it opens",
+                        wait.as_secs().div_ceil(60)
+                    );
+                }
+                tokio::time::sleep(wait).await;
+            }
+            self.pausing.store(false, Ordering::Relaxed);
+        }
+        if let Some(interval) = self.interval {
+            let turn = {
+                let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+                let now = tokio::time::Instant::now();
+                let turn = next.map_or(now, |next| next.max(now));
+                *next = Some(turn + interval);
+                turn
+            };
+            tokio::time::sleep_until(turn).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_windows() {
+        let night: RunWindow = "22:00-06:00".parse().unwrap();
+        assert_eq!(night.to_string(), "22:00-06:00 UTC");
+        assert!("9:30-17:00".parse::<RunWindow>().is_ok());
+        assert!("22:00".parse::<RunWindow>().is_err());
+        assert!("24:00-06:00".parse::<RunWindow>().is_err());
+        assert!("06:00-06:00".parse::<RunWindow>().is_err());
+    }
+
+    #[test]
+    fn waits_until_the_window_opens() {
+        let hour = |h: u64| h * 3600;
+        let night: RunWindow = "22:00-06:00".parse().unwrap();
+        assert_eq!(night.wait_at(hour(23)), None);
+        assert_eq!(night.wait_at(hour(3)), None);
+        assert_eq!(night.wait_at(hour(6)), Some(Duration::from_secs(hour(16))));
+        assert_eq!(
+            night.wait_at(hour(21) + 1800),
+            Some(Duration::from_secs(1800))
+        );
+
+        let office: RunWindow = "09:00-17:00".parse().unwrap();
+        assert_eq!(office.wait_at(hour(12)), None);
+        assert_eq!(
+            office.wait_at(hour(18)),
+            Some(Duration::from_secs(hour(15)))
+        );
+        // Days roll over: a timestamp is taken modulo one day.
+        assert_eq!(office.wait_at(10 * 86_400 + hour(10)), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn paced_requests_are_spread_evenly() {
+        let schedule = Schedule::new(Some(120), None);
+        let start = tokio::time::Instant::now();
+        for _ in 0..3 {
+            schedule.wait().await;
+        }
+        // 120/min is one every 500ms; the first goes out at once.
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,652 @@
+//! JSON Schemas describing the `-o json` output of each command.
+//!
+//! Most commands pass Confluence API objects through unchanged, so these schemas only pin down
+//! the fields confcli itself relies on (and that downstream pipelines can count on) and leave
+//! `additionalProperties` open. Commands that build their own JSON objects get closed schemas.
+
+use serde_json::{Value, json};
+
+const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Command paths (space-separated) that have a schema, in help order.
+pub const SCHEMA_COMMANDS: &[&str] = &[
+    "space list",
+    "space get",
+    "space pages",
+    "space shortcuts list",
+    "page list",
+    "page get",
+    "page body",
+    "page table",
+    "page children",
+    "page history",
+    "page permalink",
+    "page likes",
+    "page adf-stats",
+    "page owner get",
+    "search",
+    "recent",
+    "favorite list",
+    "attachment list",
+    "attachment get",
+    "attachment mirror",
+    "label list",
+    "label pages",
+    "label propagate",
+    "comment list",
+    "template get",
+    "question list",
+    "question get",
+    "jira links",
+    "doctor",
+    "auth list",
+    "report expiring",
+    "report duplicates",
+    "report missing-attachments",
+    "export",
+    "export status",
+    "export verify",
+    "graph",
+    "sitemap",
+    "check-titles",
+    "resolve",
+];
+
+/// Return the JSON Schema for a command path such as `page list`, or `None` if unknown.
+pub fn schema_for(command: &str) -> Option<Value> {
+    let command = command.split_whitespace().collect::<Vec<_>>().join(" ");
+    let body = match command.as_str() {
+        "space list" => array_of(space()),
+        "space get" => space(),
+        "space pages" | "page children" => array_of(tree_item()),
+        "space shortcuts list" => array_of(closed_object(
+            &["id", "title", "url", "pageId", "hidden"],
+            json!({
+                "id": string(),
+                "title": string(),
+                "url": string(),
+                "pageId": { "type": ["string", "null"] },
+                "hidden": { "type": "boolean" },
+            }),
+        )),
+        "page list" => array_of(page()),
+        "page get" => page(),
+        "page table" => array_of(closed_object(
+            &["index", "headers", "rows"],
+            json!({
+                "index": { "type": "integer" },
+                "headers": array_of(string()),
+                "rows": array_of(json!({
+                    "type": "object",
+                    "additionalProperties": string(),
+                })),
+            }),
+        )),
+        "page body" => closed_object(
+            &["pageId", "format", "body"],
+            json!({
+                "pageId": string(),
+                "format": string(),
+                "body": string(),
+            }),
+        ),
+        "page history" => array_of(open_object(
+            &["number"],
+            json!({
+                "number": { "type": "integer" },
+                "message": string(),
+                "minorEdit": { "type": "boolean" },
+                "createdAt": string(),
+                "authorId": string(),
+                "authorName": string(),
+            }),
+        )),
+        "page permalink" => closed_object(
+            &["id", "title", "version", "url", "markdown"],
+            json!({
+                "id": string(),
+                "title": string(),
+                "version": { "type": "integer" },
+                "url": string(),
+                "markdown": string(),
+            }),
+        ),
+        "page adf-stats" => closed_object(
+            &[
+                "pageId",
+                "nodes",
+                "maxDepth",
+                "types",
+                "unknown",
+                "extensions",
+            ],
+            json!({
+                "pageId": id(),
+                "nodes": { "type": "integer" },
+                "maxDepth": { "type": "integer" },
+                "types": { "type": "object", "additionalProperties": { "type": "integer" } },
+                "unknown": { "type": "array", "items": string() },
+                "extensions": { "type": "object", "additionalProperties": { "type": "integer" } },
+            }),
+        ),
+        "page likes" => array_of(open_object(
+            &["accountId"],
+            json!({ "accountId": string() }),
+        )),
+        "page owner get" => closed_object(
+            &["id", "title", "ownerId", "authorId"],
+            json!({
+                "id": string(),
+                "title": string(),
+                "ownerId": string(),
+                "authorId": string(),
+            }),
+        ),
+        // `search --all` prints the merged results; a single page prints the raw v1 envelope.
+        "search" => json!({
+            "anyOf": [
+                array_of(search_result()),
+                open_object(
+                    &["results"],
+                    json!({
+                        "results": array_of(search_result()),
+                        "start": { "type": "integer" },
+                        "limit": { "type": "integer" },
+                        "size": { "type": "integer" },
+                        "totalSize": { "type": "integer" },
+                    }),
+                ),
+            ],
+        }),
+        "label pages" | "recent" | "favorite list" => array_of(search_result()),
+        "attachment list" => array_of(attachment()),
+        "attachment get" => attachment(),
+        "attachment mirror" => closed_object(
+            &[
+                "dest",
+                "space",
+                "copied",
+                "updated",
+                "unchanged",
+                "failed",
+                "dryRun",
+                "items",
+                "errors",
+            ],
+            json!({
+                "dest": string(),
+                "space": string(),
+                "copied": { "type": "integer" },
+                "updated": { "type": "integer" },
+                "unchanged": { "type": "integer" },
+                "failed": { "type": "integer" },
+                "dryRun": { "type": "boolean" },
+                "items": array_of(closed_object(
+                    &["id", "title", "path", "outcome", "sha256"],
+                    json!({
+                        "id": string(),
+                        "title": string(),
+                        "path": string(),
+                        "outcome": {
+                            "enum": ["copied", "updated", "unchanged", "would copy", "would update"],
+                        },
+                        "sha256": { "type": ["string", "null"] },
+                    }),
+                )),
+                "errors": array_of(string()),
+            }),
+        ),
+        "label propagate" => closed_object(
+            &[
+                "page",
+                "labels",
+                "descendants",
+                "labeled",
+                "skipped",
+                "failed",
+                "pages",
+            ],
+            json!({
+                "page": string(),
+                "labels": array_of(string()),
+                "descendants": { "type": "integer" },
+                "labeled": { "type": "integer" },
+                "skipped": { "type": "integer" },
+                "failed": { "type": "integer" },
+                "pages": array_of(closed_object(
+                    &["id", "title", "added", "status"],
+                    json!({
+                        "id": string(),
+                        "title": string(),
+                        "added": array_of(string()),
+                        "status": string(),
+                    }),
+                )),
+            }),
+        ),
+        "label list" => array_of(open_object(
+            &["id", "name"],
+            json!({
+                "id": string(),
+                "name": string(),
+                "prefix": string(),
+            }),
+        )),
+        "comment list" => array_of(open_object(
+            &["id"],
+            json!({
+                "id": string(),
+                "type": string(),
+                "title": string(),
+                "extensions": { "type": "object" },
+                "ancestors": { "type": "array", "items": { "type": "object" } },
+                "history": { "type": "object" },
+            }),
+        )),
+        "template get" => closed_object(
+            &["templateId", "name", "format", "body"],
+            json!({
+                "templateId": string(),
+                "name": string(),
+                "format": string(),
+                "body": string(),
+            }),
+        ),
+        "question list" => array_of(search_result()),
+        "question get" => open_object(
+            &["id", "type", "title", "answers"],
+            json!({
+                "id": id(),
+                "type": string(),
+                "title": string(),
+                "body": { "type": "object" },
+                "space": { "type": "object" },
+                "history": { "type": "object" },
+                "answers": { "type": "array", "items": { "type": "object" } },
+            }),
+        ),
+        "jira links" => array_of(open_object(
+            &["key", "fields"],
+            json!({
+                "id": string(),
+                "key": string(),
+                "fields": {
+                    "type": "object",
+                    "properties": {
+                        "summary": string(),
+                        "status": { "type": "object" },
+                    },
+                },
+            }),
+        )),
+        "doctor" => closed_object(
+            &["ok", "checks"],
+            json!({
+                "ok": { "type": "boolean" },
+                "checks": array_of(closed_object(
+                    &["check", "status", "detail", "hint"],
+                    json!({
+                        "check": string(),
+                        "status": { "type": "string", "enum": ["ok", "warn", "fail", "skip"] },
+                        "detail": string(),
+                        "hint": { "type": ["string", "null"] },
+                    }),
+                )),
+            }),
+        ),
+        "report expiring" => array_of(closed_object(
+            &["id", "title", "space", "expiresOn", "daysLeft", "action"],
+            json!({
+                "id": string(),
+                "title": string(),
+                "space": string(),
+                "expiresOn": string(),
+                "daysLeft": { "type": "integer" },
+                "action": string(),
+                "applied": string(),
+            }),
+        )),
+        "report duplicates" => array_of(closed_object(
+            &["pages", "score", "bodySimilarity", "titleSimilarity"],
+            json!({
+                "pages": {
+                    "type": "array",
+                    "minItems": 2,
+                    "maxItems": 2,
+                    "items": closed_object(
+                        &["id", "title"],
+                        json!({ "id": string(), "title": string() }),
+                    ),
+                },
+                "score": { "type": "number" },
+                "bodySimilarity": { "type": "number" },
+                "titleSimilarity": { "type": "number" },
+            }),
+        )),
+        "report missing-attachments" => array_of(closed_object(
+            &["id", "title", "file", "attachedTo", "problem"],
+            json!({
+                "id": string(),
+                "title": string(),
+                "file": string(),
+                // Set when the file belongs to another page than the one referencing it.
+                "attachedTo": { "type": ["string", "null"] },
+                "problem": string(),
+            }),
+        )),
+        // One page prints a single object; several pages (or `--recursive`) print an array.
+        "export" => {
+            let page = closed_object(
+                &["dir", "meta", "content", "attachments", "diagrams"],
+                json!({
+                    "dir": string(),
+                    "meta": { "type": ["string", "null"] },
+                    "content": string(),
+                    "attachments": { "type": "array", "items": string() },
+                    "diagrams": { "type": "array", "items": string() },
+                }),
+            );
+            json!({ "anyOf": [page.clone(), array_of(page)] })
+        }
+        "export status" => closed_object(
+            &[
+                "site",
+                "exportedAt",
+                "pageCount",
+                "fileCount",
+                "totalBytes",
+                "pages",
+            ],
+            json!({
+                "site": string(),
+                "exportedAt": string(),
+                "pageCount": { "type": "integer" },
+                "fileCount": { "type": "integer" },
+                "totalBytes": { "type": "integer" },
+                "pages": array_of(closed_object(
+                    &["id", "title", "version", "liveVersion", "bytes", "change"],
+                    json!({
+                        "id": string(),
+                        "title": string(),
+                        // Null for pages only found on the site (`change` is "new").
+                        "version": { "type": ["integer", "null"] },
+                        "bytes": { "type": ["integer", "null"] },
+                        // Null with `--offline` and for deleted pages.
+                        "liveVersion": { "type": ["integer", "null"] },
+                        "change": {
+                            "type": ["string", "null"],
+                            "enum": ["unchanged", "changed", "new", "deleted", null],
+                        },
+                    }),
+                )),
+            }),
+        ),
+        // Printed before the command exits non-zero for a non-empty `problems`.
+        "export verify" => closed_object(
+            &["pages", "files", "live", "problems"],
+            json!({
+                "pages": { "type": "integer" },
+                "files": { "type": "integer" },
+                "live": { "type": "boolean" },
+                "problems": array_of(closed_object(
+                    &["pageId", "path", "problem", "detail"],
+                    json!({
+                        "pageId": string(),
+                        "path": string(),
+                        "problem": {
+                            "type": "string",
+                            "enum": [
+                                "missing",
+                                "unreadable",
+                                "corrupted",
+                                "size-differs",
+                                "not-on-site",
+                                "unchecked",
+                            ],
+                        },
+                        "detail": string(),
+                    }),
+                )),
+            }),
+        ),
+        "sitemap" => array_of(closed_object(
+            &["id", "type", "title", "space", "url", "lastModified"],
+            json!({
+                "id": string(),
+                "type": { "enum": ["page", "blogpost", "attachment"] },
+                "title": string(),
+                "space": string(),
+                "url": string(),
+                "lastModified": string(),
+            }),
+        )),
+        "auth list" => array_of(closed_object(
+            &[
+                "name",
+                "site",
+                "auth",
+                "readOnly",
+                "keyring",
+                "encrypted",
+                "current",
+                "error",
+            ],
+            json!({
+                "name": string(),
+                "site": string(),
+                "auth": string(),
+                "readOnly": { "type": "boolean" },
+                "keyring": { "type": "boolean" },
+                "encrypted": { "type": "boolean" },
+                "current": { "type": "boolean" },
+                "error": { "type": ["string", "null"] },
+            }),
+        )),
+        "check-titles" => array_of(closed_object(
+            &["kind", "key", "pages"],
+            json!({
+                "kind": { "enum": ["duplicate", "slug"] },
+                "key": string(),
+                "pages": array_of(closed_object(
+                    &["id", "title"],
+                    json!({ "id": string(), "title": string() }),
+                )),
+            }),
+        )),
+        "graph" => closed_object(
+            &["space", "nodes", "clusters"],
+            json!({
+                "space": string(),
+                "nodes": array_of(closed_object(
+                    &["id", "title", "links"],
+                    json!({
+                        "id": string(),
+                        "title": string(),
+                        "links": array_of(string()),
+                    }),
+                )),
+                // Weakly connected components, largest first.
+                "clusters": array_of(array_of(string())),
+            }),
+        ),
+        "resolve" => closed_object(
+            &["id", "type"],
+            json!({
+                "id": string(),
+                "type": { "type": "string", "enum": ["page", "space"] },
+            }),
+        ),
+        _ => return None,
+    };
+
+    let mut schema = body;
+    if let Some(obj) = schema.as_object_mut() {
+        obj.insert("$schema".to_string(), Value::String(DRAFT.to_string()));
+        obj.insert(
+            "title".to_string(),
+            Value::String(format!("confcli {command} -o json")),
+        );
+    }
+    Some(schema)
+}
+
+fn string() -> Value {
+    json!({ "type": "string" })
+}
+
+/// Confluence ids are strings in v2 and sometimes numbers in v1.
+fn id() -> Value {
+    json!({ "type": ["string", "integer"] })
+}
+
+fn array_of(items: Value) -> Value {
+    json!({ "type": "array", "items": items })
+}
+
+fn open_object(required: &[&str], properties: Value) -> Value {
+    json!({
+        "type": "object",
+        "required": required,
+        "properties": properties,
+        "additionalProperties": true,
+    })
+}
+
+fn closed_object(required: &[&str], properties: Value) -> Value {
+    json!({
+        "type": "object",
+        "required": required,
+        "properties": properties,
+        "additionalProperties": false,
+    })
+}
+
+fn links() -> Value {
+    open_object(
+        &[],
+        json!({
+            "webui": string(),
+            "download": string(),
+        }),
+    )
+}
+
+fn space() -> Value {
+    open_object(
+        &["id", "key", "name"],
+        json!({
+            "id": id(),
+            "key": string(),
+            "name": string(),
+            "type": string(),
+            "status": string(),
+            "homepageId": id(),
+            "_links": links(),
+        }),
+    )
+}
+
+fn page() -> Value {
+    open_object(
+        &["id", "title"],
+        json!({
+            "id": id(),
+            "title": string(),
+            "status": string(),
+            "spaceId": id(),
+            "parentId": id(),
+            "ownerId": string(),
+            "authorId": string(),
+            "childPosition": { "type": "integer" },
+            "depth": { "type": "integer" },
+            "version": open_object(&[], json!({ "number": { "type": "integer" } })),
+            "body": { "type": "object" },
+            "url": string(),
+            // With `--show-activity` (page list, space pages).
+            "commentCount": { "type": "integer" },
+            "lastCommentAt": { "type": ["string", "null"] },
+            // With `page get --include-headers`: selected response headers, lower-case names.
+            "_meta": open_object(
+                &["headers"],
+                json!({
+                    "headers": {
+                        "type": "object",
+                        "additionalProperties": string(),
+                    },
+                }),
+            ),
+            "_links": links(),
+        }),
+    )
+}
+
+/// A page-tree listing item: a [`page`] that always carries its content `type`.
+fn tree_item() -> Value {
+    let mut item = page();
+    item["required"] = json!(["id", "title", "type"]);
+    item["properties"]["type"] = string();
+    item
+}
+
+fn attachment() -> Value {
+    open_object(
+        &["id", "title"],
+        json!({
+            "id": id(),
+            "title": string(),
+            "mediaType": string(),
+            "fileSize": { "type": "integer" },
+            "pageId": id(),
+            "downloadLink": string(),
+            // With `attachment list --space`.
+            "pageTitle": string(),
+            "pagePath": string(),
+            "_links": links(),
+        }),
+    )
+}
+
+fn search_result() -> Value {
+    open_object(
+        &[],
+        json!({
+            "title": string(),
+            "entityType": string(),
+            "url": string(),
+            "content": open_object(
+                &["id", "type"],
+                json!({
+                    "id": id(),
+                    "type": string(),
+                    "title": string(),
+                    "space": open_object(&[], json!({ "key": string() })),
+                }),
+            ),
+            "resultGlobalContainer": { "type": "object" },
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_listed_command_has_a_schema() {
+        for command in SCHEMA_COMMANDS {
+            let schema = schema_for(command).unwrap_or_else(|| panic!("missing: {command}"));
+            assert_eq!(schema["$schema"], DRAFT);
+        }
+    }
+
+    #[test]
+    fn unknown_command_has_no_schema() {
+        assert!(schema_for("page frobnicate").is_none());
+    }
+
+    #[test]
+    fn command_whitespace_is_normalized() {
+        let schema = schema_for("  page   list ").unwrap();
+        assert_eq!(schema["type"], "array");
+    }
+}
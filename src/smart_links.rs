@@ -0,0 +1,145 @@
+//! Smart links (inline cards). Confluence renders them in view HTML as an anchor whose text
+//! is the bare URL (or nothing at all), so the markdown ends up with raw URLs. Before
+//! conversion we swap that text for the title of the linked page or Jira issue.
+
+use confcli::client::ApiClient;
+use confcli::markdown::{decode_html_entities, escape_xml};
+use futures_util::stream::{self, StreamExt};
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use crate::resolve::resolve_link_title;
+
+const LOOKUP_CONCURRENCY: usize = 4;
+
+static ANCHOR_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<a\s([^>]*)>(.*?)</a>").expect("ANCHOR_RE"));
+static HREF_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\bhref="([^"]*)""#).expect("HREF_RE"));
+static TAG_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]*>").expect("TAG_RE"));
+
+/// Replace the text of smart links in `html` with the resolved title of their target.
+/// Links that can't be resolved are left untouched.
+pub async fn unfurl_smart_links(client: &ApiClient, html: &str) -> String {
+    let mut urls: Vec<String> = Vec::new();
+    for caps in ANCHOR_RE.captures_iter(html) {
+        if let Some(href) = smart_link_href(&caps[1], &caps[2])
+            && !urls.contains(&href)
+        {
+            urls.push(href);
+        }
+    }
+    if urls.is_empty() {
+        return html.to_string();
+    }
+
+    let titles: HashMap<String, String> = stream::iter(urls)
+        .map(|url| async move {
+            let title = resolve_link_title(client, &url).await;
+            title.map(|title| (url, title))
+        })
+        .buffer_unordered(LOOKUP_CONCURRENCY)
+        .filter_map(|entry| async move { entry })
+        .collect()
+        .await;
+
+    ANCHOR_RE
+        .replace_all(html, |caps: &regex::Captures| {
+            let title = smart_link_href(&caps[1], &caps[2]).and_then(|href| titles.get(&href));
+            match title {
+                Some(title) => format!("<a {}>{}</a>", &caps[1], escape_xml(title)),
+                None => caps[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// The decoded href of an anchor that is a smart link: an inline card, or an absolute link
+/// whose text is empty or just the URL itself.
+fn smart_link_href(attrs: &str, inner: &str) -> Option<String> {
+    let href = decode_html_entities(HREF_RE.captures(attrs)?.get(1)?.as_str());
+    if !(href.starts_with("https://") || href.starts_with("http://")) {
+        return None;
+    }
+    let text = decode_html_entities(TAG_RE.replace_all(inner, "").trim());
+    let is_card = attrs.contains("data-card-appearance");
+    let bare = text.is_empty() || text.trim_end_matches('/') == href.trim_end_matches('/');
+    (is_card || bare).then_some(href)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::http_server::start_server;
+
+    #[test]
+    fn detects_smart_links() {
+        assert_eq!(
+            smart_link_href(
+                r#"href="https://x.test/a" data-card-appearance="inline""#,
+                "Custom"
+            ),
+            Some("https://x.test/a".to_string())
+        );
+        assert_eq!(
+            smart_link_href(
+                r#"href="https://x.test/a?b=1&amp;c=2""#,
+                "https://x.test/a?b=1&amp;c=2"
+            ),
+            Some("https://x.test/a?b=1&c=2".to_string())
+        );
+        assert_eq!(
+            smart_link_href(r#"href="https://x.test/a""#, ""),
+            Some("https://x.test/a".to_string())
+        );
+        assert_eq!(smart_link_href(r#"href="https://x.test/a""#, "Docs"), None);
+        assert_eq!(smart_link_href(r#"href="/wiki/x""#, ""), None);
+    }
+
+    #[tokio::test]
+    async fn replaces_link_text_with_titles() {
+        let srv = start_server(|_, path| {
+            if path.starts_with("/pages/9300001") {
+                (
+                    200,
+                    vec![],
+                    br#"{"id":"9300001","title":"Runbook & FAQ"}"#.to_vec(),
+                )
+            } else if path.starts_with("/rest/api/3/issue/OPS-93") {
+                (
+                    200,
+                    vec![],
+                    br#"{"fields":{"summary":"Rotate keys"}}"#.to_vec(),
+                )
+            } else {
+                (404, vec![], b"{}".to_vec())
+            }
+        })
+        .await;
+        let client = ApiClient::new(
+            srv.base_url.clone(),
+            srv.base_url.clone(),
+            srv.base_url.clone(),
+            confcli::auth::AuthMethod::Bearer {
+                token: "test".to_string(),
+            },
+            0,
+        )
+        .unwrap();
+
+        let base = &srv.base_url;
+        let html = format!(
+            r#"<p><a href="{base}/pages/viewpage.action?pageId=9300001" data-card-appearance="inline">{base}/pages/viewpage.action?pageId=9300001</a> <a href="{base}/browse/OPS-93"></a> <a href="{base}/browse/OPS-94">{base}/browse/OPS-94</a> <a href="https://example.com/x">Example</a></p>"#
+        );
+        let out = unfurl_smart_links(&client, &html).await;
+        assert_eq!(
+            out,
+            format!(
+                r#"<p><a href="{base}/pages/viewpage.action?pageId=9300001" data-card-appearance="inline">Runbook &amp; FAQ</a> <a href="{base}/browse/OPS-93">OPS-93: Rotate keys</a> <a href="{base}/browse/OPS-94">{base}/browse/OPS-94</a> <a href="https://example.com/x">Example</a></p>"#
+            )
+        );
+
+        let _ = srv.shutdown.send(());
+    }
+}
@@ -9,8 +9,11 @@ use url::Url;
 /// Why this exists: Confluence's `/pages/{id}/descendants` endpoint appears to only include a
 /// limited depth (at least on Cloud), which breaks deep trees.
 ///
+/// Folders, whiteboards, and other non-page children are walked through their own
+/// `direct-children` endpoint, so mixed trees are followed all the way down.
+///
 /// The returned items are the raw `direct-children` items, augmented with:
-/// - `parentId`: the parent page id
+/// - `parentId`: the parent content id
 /// - `depth`: 1-based depth relative to the root (children of root are depth=1)
 ///
 /// Notes on `limit`/`all`:
@@ -26,15 +29,15 @@ pub async fn fetch_descendants_via_direct_children(
     max_depth: Option<usize>,
 ) -> Result<Vec<Value>> {
     let mut out: Vec<Value> = Vec::new();
-    let mut q: VecDeque<(String, usize)> = VecDeque::new();
+    let mut q: VecDeque<(String, String, usize)> = VecDeque::new();
     let mut seen: HashSet<String> = HashSet::new();
 
-    q.push_back((root_id.to_string(), 0));
+    q.push_back((root_id.to_string(), "page".to_string(), 0));
     seen.insert(root_id.to_string());
 
     let unlimited_depth = max_depth.unwrap_or(0) == 0;
 
-    while let Some((parent_id, depth)) = q.pop_front() {
+    while let Some((parent_id, parent_type, depth)) = q.pop_front() {
         if !unlimited_depth {
             let max_depth = max_depth.unwrap_or(0);
             if depth >= max_depth {
@@ -58,7 +61,7 @@ pub async fn fetch_descendants_via_direct_children(
         };
 
         let url = with_query(
-            &client.v2_url(&format!("/pages/{parent_id}/direct-children")),
+            &client.v2_url(&direct_children_path(&parent_type, &parent_id)),
             &[("limit", page_size.to_string())],
         )?;
 
@@ -72,6 +75,11 @@ pub async fn fetch_descendants_via_direct_children(
             if id.is_empty() {
                 continue;
             }
+            let content_type = child
+                .get("type")
+                .and_then(|v| v.as_str())
+                .unwrap_or("page")
+                .to_string();
 
             if let Some(obj) = child.as_object_mut() {
                 obj.insert("parentId".to_string(), Value::String(parent_id.clone()));
@@ -84,7 +92,7 @@ pub async fn fetch_descendants_via_direct_children(
             out.push(child);
 
             if seen.insert(id.clone()) {
-                q.push_back((id, depth + 1));
+                q.push_back((id, content_type, depth + 1));
             }
 
             if !all && out.len() >= limit {
@@ -96,6 +104,18 @@ pub async fn fetch_descendants_via_direct_children(
     Ok(out)
 }
 
+/// The v2 `direct-children` path of a content item of type `content_type`.
+fn direct_children_path(content_type: &str, id: &str) -> String {
+    let collection = match content_type {
+        "folder" => "folders",
+        "whiteboard" => "whiteboards",
+        "database" => "databases",
+        "embed" => "embeds",
+        _ => "pages",
+    };
+    format!("/{collection}/{id}/direct-children")
+}
+
 fn with_query(base: &str, params: &[(&str, String)]) -> Result<String> {
     let mut url = Url::parse(base)?;
     {
@@ -106,3 +126,28 @@ fn with_query(base: &str, params: &[(&str, String)]) -> Result<String> {
     }
     Ok(url.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn children_are_listed_through_their_own_collection() {
+        assert_eq!(
+            direct_children_path("page", "1"),
+            "/pages/1/direct-children"
+        );
+        assert_eq!(
+            direct_children_path("folder", "2"),
+            "/folders/2/direct-children"
+        );
+        assert_eq!(
+            direct_children_path("whiteboard", "3"),
+            "/whiteboards/3/direct-children"
+        );
+        assert_eq!(
+            direct_children_path("blogpost", "4"),
+            "/pages/4/direct-children"
+        );
+    }
+}
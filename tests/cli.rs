@@ -1,10 +1,125 @@
 use assert_cmd::Command;
 use predicates::prelude::*;
+use serde_json::{Value, json};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
 
 fn confcli() -> Command {
     Command::new(assert_cmd::cargo::cargo_bin!("confcli"))
 }
 
+/// A stand-in Confluence site on a local port. Each request gets the JSON of the first route
+/// `"METHOD /path"` with its path, optionally followed by leading query parameters, or a 404.
+struct MockSite {
+    url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+    home: tempfile::TempDir,
+}
+
+impl MockSite {
+    fn start(routes: Vec<(&'static str, Value)>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/wiki", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap_or_default();
+                let mut length = 0;
+                loop {
+                    let mut header = String::new();
+                    if reader.read_line(&mut header).unwrap_or_default() == 0 || header == "\r\n" {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        length = value.trim().parse().unwrap_or_default();
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap_or_default();
+                let request = request_line
+                    .split_whitespace()
+                    .take(2)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                log.lock()
+                    .unwrap()
+                    .push(format!("{request} {}", String::from_utf8_lossy(&body)));
+                let (status, body) = match routes
+                    .iter()
+                    .find(|(route, _)| route_matches(route, &request))
+                {
+                    Some((_, json)) => ("200 OK", json.to_string()),
+                    None => ("404 Not Found", r#"{"message":"not found"}"#.to_string()),
+                };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+            }
+        });
+        Self {
+            url,
+            requests,
+            home: tempfile::tempdir().unwrap(),
+        }
+    }
+
+    /// `confcli` logged in to this site through the environment, with a config dir of its own
+    /// and no proxy settings inherited.
+    fn confcli(&self) -> Command {
+        let mut cmd = confcli();
+        cmd.env_clear()
+            .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+            .env("HOME", self.home.path())
+            .env("XDG_CONFIG_HOME", self.home.path())
+            .env("CONFLUENCE_DOMAIN", &self.url)
+            .env("CONFLUENCE_EMAIL", "ada@example.com")
+            .env("CONFLUENCE_TOKEN", "not-a-real-token");
+        cmd
+    }
+
+    /// Every request so far, as `METHOD /path?query body`.
+    fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// The requests other than GETs.
+    fn writes(&self) -> Vec<String> {
+        self.requests()
+            .into_iter()
+            .filter(|request| !request.starts_with("GET "))
+            .collect()
+    }
+}
+
+fn route_matches(route: &str, request: &str) -> bool {
+    request
+        .strip_prefix(route)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(['?', '&']))
+}
+
+/// The space `DOC` (id 1), as the v2 API finds it by key.
+fn doc_space() -> Vec<(&'static str, Value)> {
+    vec![
+        (
+            "GET /wiki/api/v2/spaces?keys=DOC",
+            json!({ "results": [{ "id": "1", "key": "DOC", "name": "Docs" }] }),
+        ),
+        (
+            "GET /wiki/api/v2/spaces/1",
+            json!({ "id": "1", "key": "DOC", "name": "Docs" }),
+        ),
+    ]
+}
+
 #[test]
 fn help_flag() {
     confcli()
@@ -42,6 +157,23 @@ fn auth_help() {
         .stdout(predicate::str::contains("login").and(predicate::str::contains("status")));
 }
 
+#[test]
+fn auth_login_auth_type_requires_a_token_command() {
+    // Rejected by arg parsing, before any prompt or network request.
+    confcli()
+        .args([
+            "auth",
+            "login",
+            "--domain",
+            "wiki.example.com",
+            "--auth-type",
+            "pat",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--token-cmd <COMMAND>"));
+}
+
 #[test]
 fn space_help() {
     confcli()
@@ -96,6 +228,64 @@ fn label_pages_supports_all_flag() {
         .stdout(predicate::str::contains("--all"));
 }
 
+#[test]
+fn page_table_index_is_one_based() {
+    confcli()
+        .args(["page", "table", "MFS:Inventory", "--index", "0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--index"));
+}
+
+#[test]
+#[cfg(feature = "write")]
+fn page_table_update_rejects_ragged_csv() {
+    let dir = tempfile::tempdir().unwrap();
+    let data = dir.path().join("data.csv");
+    std::fs::write(&data, "Name,Owner\napi,ops,extra\n").unwrap();
+    confcli()
+        .env("XDG_CONFIG_HOME", dir.path())
+        .env("HOME", dir.path())
+        .env("CONFLUENCE_DOMAIN", "example.atlassian.net")
+        .env("CONFLUENCE_EMAIL", "me@example.com")
+        .env("CONFLUENCE_TOKEN", "token")
+        .args(["page", "table", "update", "12345", "--from"])
+        .arg(&data)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "CSV row 2 has 3 fields but the header row has 2",
+        ));
+}
+
+#[test]
+#[cfg(feature = "write")]
+fn generate_index_requires_out_page() {
+    confcli()
+        .args(["generate", "index", "--space", "ENG"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--out <OUT>"));
+}
+
+#[test]
+fn generate_changelog_requires_label() {
+    confcli()
+        .args(["generate", "changelog", "--since", "30d"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--label <LABEL>"));
+}
+
+#[test]
+fn generate_report_requires_property() {
+    confcli()
+        .args(["generate", "report", "--space", "ENG"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--property <PROPERTIES>"));
+}
+
 #[test]
 fn completions_bash() {
     confcli()
@@ -151,6 +341,45 @@ fn page_create_missing_space() {
         .stderr(predicate::str::contains("--space"));
 }
 
+#[test]
+#[cfg(feature = "write")]
+fn print_id_conflicts_with_output_format() {
+    confcli()
+        .args([
+            "page",
+            "create",
+            "--space",
+            "MFS",
+            "--title",
+            "T",
+            "--print-id",
+            "-o",
+            "json",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+#[cfg(feature = "write")]
+fn page_create_from_url_conflicts_with_body() {
+    confcli()
+        .args([
+            "page",
+            "create",
+            "--space",
+            "MFS",
+            "--from-url",
+            "https://example.com/article",
+            "--body",
+            "<p>Hi</p>",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
 #[test]
 #[cfg(feature = "write")]
 fn page_update_requires_at_least_one_change() {
@@ -165,6 +394,32 @@ fn page_update_requires_at_least_one_change() {
         .stderr(predicate::str::contains("Nothing to update"));
 }
 
+#[test]
+#[cfg(feature = "write")]
+fn page_update_diff_requires_a_new_body() {
+    // This should fail before making any network requests.
+    confcli()
+        .args(["page", "update", "12345", "--title", "New", "--diff"])
+        .env("CONFLUENCE_DOMAIN", "example.atlassian.net")
+        .env("CONFLUENCE_EMAIL", "test@example.com")
+        .env("CONFLUENCE_TOKEN", "not-a-real-token")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("pass --body or --body-file"));
+}
+
+#[test]
+#[cfg(feature = "write")]
+fn anonymous_mode_refuses_writes() {
+    // This should fail before making any network requests.
+    confcli()
+        .args(["--anonymous", "page", "delete", "12345", "--yes"])
+        .env("CONFLUENCE_DOMAIN", "example.atlassian.net")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--anonymous is read-only"));
+}
+
 #[test]
 fn dry_run_flag_accepted() {
     // --dry-run should be accepted as a global flag (not rejected by arg parsing).
@@ -198,6 +453,99 @@ fn auth_status_not_logged_in() {
         .stdout(predicate::str::contains("Not logged in"));
 }
 
+#[test]
+fn auth_status_reports_a_missing_profile() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let run = |profile: &str| {
+        let mut cmd = confcli();
+        cmd.args(["--profile", profile, "auth", "status"])
+            .current_dir(temp_dir.path())
+            .env("XDG_CONFIG_HOME", temp_dir.path())
+            .env("HOME", temp_dir.path())
+            .env("CONFLUENCE_BASE_URL", "https://example.atlassian.net/wiki")
+            .env("CONFLUENCE_EMAIL", "a@example.com")
+            .env("CONFLUENCE_TOKEN", "x");
+        cmd.assert()
+    };
+    // The profile wins over credentials in the environment.
+    run("work")
+        .success()
+        .stdout(predicate::str::contains("Not logged in to profile 'work'"));
+    run("../work").failure();
+}
+
+#[test]
+fn keyring_profile_without_a_stored_token_fails() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let profiles = temp_dir.path().join("confcli").join("profiles");
+    std::fs::create_dir_all(&profiles).unwrap();
+    std::fs::write(
+        profiles.join("missing-token.json"),
+        r#"{"site_url": "https://example.atlassian.net/wiki", "auth": {"type": "basic", "email": "a@example.com", "token": ""}, "keyring": true}"#,
+    )
+    .unwrap();
+    confcli()
+        .args(["--profile", "missing-token", "auth", "status"])
+        .current_dir(temp_dir.path())
+        .env("XDG_CONFIG_HOME", temp_dir.path())
+        .env("HOME", temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Failed to read the token of profile 'missing-token' from the OS keyring",
+        ));
+}
+
+#[test]
+fn auth_switch_changes_the_current_profile() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let dir = temp_dir.path().join("confcli");
+    std::fs::create_dir_all(dir.join("profiles")).unwrap();
+    let profile = |site: &str| {
+        format!(r#"{{"site_url": "{site}", "auth": {{"type": "pat", "token": "t"}}}}"#)
+    };
+    std::fs::write(dir.join("config.json"), profile("https://a.example.com")).unwrap();
+    std::fs::write(
+        dir.join("profiles").join("work.json"),
+        profile("https://b.example.com"),
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = confcli();
+        cmd.args(args)
+            .current_dir(temp_dir.path())
+            .env("XDG_CONFIG_HOME", temp_dir.path())
+            .env("HOME", temp_dir.path())
+            .env_remove("CONFCLI_PROFILE");
+        cmd.assert()
+    };
+    let current = |output: &[u8]| -> Vec<String> {
+        let entries: serde_json::Value = serde_json::from_slice(output).unwrap();
+        entries
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|entry| entry["current"] == true)
+            .map(|entry| entry["name"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let listed = run(&["auth", "list", "-o", "json"]).success();
+    assert_eq!(current(&listed.get_output().stdout), ["default"]);
+    run(&["auth", "switch", "work"])
+        .success()
+        .stdout(predicate::str::contains("Switched to profile 'work'"));
+    let listed = run(&["auth", "list", "-o", "json"]).success();
+    assert_eq!(current(&listed.get_output().stdout), ["work"]);
+    run(&["auth", "switch", "staging"]).failure();
+
+    // Logging out of the current profile falls back to config.json.
+    run(&["auth", "logout"]).success();
+    assert!(!dir.join("profiles").join("work.json").exists());
+    let listed = run(&["auth", "list", "-o", "json"]).success();
+    assert_eq!(current(&listed.get_output().stdout), ["default"]);
+}
+
 #[test]
 fn quiet_suppresses_auth_status_output() {
     let temp_dir = tempfile::tempdir().unwrap();
@@ -304,6 +652,17 @@ fn attachment_upload_supports_concurrency_flag() {
         .stdout(predicate::str::contains("--concurrency"));
 }
 
+#[test]
+#[cfg(feature = "write")]
+fn attachment_upload_if_exists_modes() {
+    confcli()
+        .args(["attachment", "upload", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--if-exists"))
+        .stdout(predicate::str::contains("rename"));
+}
+
 #[test]
 #[cfg(feature = "write")]
 fn space_delete_help() {
@@ -314,6 +673,141 @@ fn space_delete_help() {
         .stdout(predicate::str::contains("Delete a space"));
 }
 
+#[test]
+#[cfg(feature = "write")]
+fn page_expire_requires_date_or_clear() {
+    confcli()
+        .args(["page", "expire", "12345"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--on"));
+
+    confcli()
+        .args(["page", "expire", "12345", "--on", "2025-12-31", "--clear"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+#[cfg(feature = "write")]
+fn space_bootstrap_reports_bad_manifest() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = dir.path().join("space.yaml");
+    std::fs::write(&manifest, "space: { key: ENG, name: Eng }\nbogus: true\n").unwrap();
+    confcli()
+        .env("XDG_CONFIG_HOME", dir.path())
+        .env("HOME", dir.path())
+        .env("CONFLUENCE_DOMAIN", "example.atlassian.net")
+        .env("CONFLUENCE_EMAIL", "me@example.com")
+        .env("CONFLUENCE_TOKEN", "token")
+        .args(["space", "bootstrap"])
+        .arg(&manifest)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid manifest"));
+}
+
+#[test]
+#[cfg(feature = "write")]
+fn space_shortcuts_add_needs_url_or_page() {
+    confcli()
+        .args(["space", "shortcuts", "add", "ENG", "--title", "Status"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--url <URL>|--page <PAGE>"));
+}
+
+#[test]
+fn attachment_mirror_rejects_unknown_destinations() {
+    let dir = tempfile::tempdir().unwrap();
+    confcli()
+        .env("XDG_CONFIG_HOME", dir.path())
+        .env("HOME", dir.path())
+        .env("CONFLUENCE_DOMAIN", "example.atlassian.net")
+        .env("CONFLUENCE_EMAIL", "me@example.com")
+        .env("CONFLUENCE_TOKEN", "token")
+        .args([
+            "attachment",
+            "mirror",
+            "--space",
+            "ENG",
+            "--dest",
+            "gs://bucket/x",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unsupported destination"));
+}
+
+#[test]
+#[cfg(feature = "write")]
+fn copy_tree_rejects_malformed_rename() {
+    confcli()
+        .args(["copy-tree", "1", "2", "--rename", "s/2024/2025"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("expected s/REGEX/REPLACEMENT/"));
+    confcli()
+        .args(["copy-tree", "1", "2", "--rename", "s/(/x/"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid regex"));
+}
+
+#[test]
+#[cfg(feature = "write")]
+fn read_only_profile_refuses_write_commands() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = r#"{"site_url":"http://127.0.0.1:9","auth":{"type":"bearer","token":"t"},"read_only":true}"#;
+    // dirs::config_dir() is XDG_CONFIG_HOME on Linux and ~/Library/Application Support on macOS.
+    for base in [
+        dir.path().to_path_buf(),
+        dir.path().join("Library/Application Support"),
+    ] {
+        std::fs::create_dir_all(base.join("confcli")).unwrap();
+        std::fs::write(base.join("confcli/config.json"), config).unwrap();
+    }
+    confcli()
+        .current_dir(dir.path())
+        .env("XDG_CONFIG_HOME", dir.path())
+        .env("HOME", dir.path())
+        .env_remove("CONFLUENCE_DOMAIN")
+        .env_remove("CONFLUENCE_BASE_URL")
+        .env_remove("CONFLUENCE_URL")
+        .args(["label", "add", "12345", "draft"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("read-only"));
+}
+
+#[test]
+#[cfg(feature = "write")]
+fn upload_confirmation_threshold_comes_from_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = r#"{"site_url":"http://127.0.0.1:9","auth":{"type":"bearer","token":"t"},"confirm":{"upload_size":4}}"#;
+    for base in [
+        dir.path().to_path_buf(),
+        dir.path().join("Library/Application Support"),
+    ] {
+        std::fs::create_dir_all(base.join("confcli")).unwrap();
+        std::fs::write(base.join("confcli/config.json"), config).unwrap();
+    }
+    std::fs::write(dir.path().join("notes.txt"), "more than four bytes").unwrap();
+    // The prompt can't be answered without a terminal, which points at --yes.
+    confcli()
+        .current_dir(dir.path())
+        .env("XDG_CONFIG_HOME", dir.path())
+        .env("HOME", dir.path())
+        .env_remove("CONFLUENCE_DOMAIN")
+        .env_remove("CONFLUENCE_BASE_URL")
+        .env_remove("CONFLUENCE_URL")
+        .args(["attachment", "upload", "12345", "notes.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Use --yes"));
+}
+
 #[test]
 #[cfg(feature = "write")]
 fn delete_commands_accept_output_flag() {
@@ -361,3 +855,1299 @@ fn space_create_rejects_invalid_key() {
             "space key must start with an uppercase letter",
         ));
 }
+
+#[test]
+fn page_owner_help() {
+    confcli()
+        .args(["page", "owner", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("get"));
+}
+
+#[test]
+#[cfg(feature = "write")]
+fn page_owner_set_rejects_page_with_cql() {
+    // Argument validation happens before any network request.
+    confcli()
+        .args(["page", "owner", "set", "123", "abc", "--cql", "space = MFS"])
+        .env("CONFLUENCE_DOMAIN", "example.atlassian.net")
+        .env("CONFLUENCE_EMAIL", "test@example.com")
+        .env("CONFLUENCE_TOKEN", "not-a-real-token")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "With --cql, pass only the new owner",
+        ));
+}
+
+#[test]
+#[cfg(feature = "write")]
+fn dry_run_json_prints_a_plan() {
+    // A numeric page id resolves without a request, so this stays offline.
+    confcli()
+        .args(["--dry-run", "page", "delete", "123", "-o", "json"])
+        .env("CONFLUENCE_DOMAIN", "example.atlassian.net")
+        .env("CONFLUENCE_EMAIL", "test@example.com")
+        .env("CONFLUENCE_TOKEN", "not-a-real-token")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains(r#""command": "page delete""#)
+                .and(predicate::str::contains(r#""action": "delete""#))
+                .and(predicate::str::contains(r#""target": "123""#))
+                .and(predicate::str::contains("Would delete").not()),
+        );
+}
+
+#[test]
+fn page_likes_help() {
+    confcli()
+        .args(["page", "likes", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("liked").and(predicate::str::contains("--all")));
+}
+
+#[test]
+fn schema_prints_json_schema_for_command() {
+    confcli()
+        .args(["schema", "page", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"$schema\"").and(predicate::str::contains("\"array\"")));
+}
+
+#[test]
+fn schema_rejects_unknown_command() {
+    confcli()
+        .args(["schema", "page", "frobnicate"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No schema for 'page frobnicate'"));
+}
+
+#[test]
+fn out_writes_results_to_file_and_keeps_it_on_failure() {
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("schema.json");
+    confcli()
+        .args(["schema", "page", "list", "--out"])
+        .arg(&out)
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+    let written = std::fs::read_to_string(&out).unwrap();
+    assert!(written.contains("\"$schema\""));
+
+    confcli()
+        .args(["schema", "page", "frobnicate", "--out"])
+        .arg(&out)
+        .assert()
+        .failure();
+    assert_eq!(std::fs::read_to_string(&out).unwrap(), written);
+}
+
+#[test]
+fn export_accepts_multiple_pages_and_recursive() {
+    confcli()
+        .args(["export", "--help"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("--recursive").and(predicate::str::contains("--concurrency")),
+        );
+}
+
+#[test]
+fn export_status_offline_reads_manifest() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join(".confcli-export.json"),
+        r#"{
+  "siteUrl": "https://example.atlassian.net/wiki",
+  "exportedAt": "2024-05-01T10:00:00Z",
+  "pages": [
+    { "id": "1", "title": "Home", "version": 7,
+      "files": [{ "path": "Home--1/page.md", "size": 1024 }] }
+  ]
+}"#,
+    )
+    .unwrap();
+    confcli()
+        .args(["export", "status", "--offline"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Home")
+                .and(predicate::str::contains("1 page(s), 1 file(s), 1 KiB"))
+                .and(predicate::str::contains("2024-05-01 10:00")),
+        );
+
+    let empty = tempfile::tempdir().unwrap();
+    confcli()
+        .args(["export", "status", "--offline"])
+        .arg(empty.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No .confcli-export.json"));
+}
+
+#[test]
+fn export_verify_reports_missing_files() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("Home--1")).unwrap();
+    std::fs::write(dir.path().join("Home--1").join("page.md"), "abc").unwrap();
+    std::fs::write(
+        dir.path().join(".confcli-export.json"),
+        r#"{
+  "siteUrl": "https://example.atlassian.net/wiki",
+  "exportedAt": "2024-05-01T10:00:00Z",
+  "pages": [
+    { "id": "1", "title": "Home", "version": 7,
+      "files": [
+        { "path": "Home--1/page.md", "size": 3,
+          "sha256": "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad" },
+        { "path": "Home--1/attachments/a.png", "size": 10, "attachmentId": "att9" }
+      ] }
+  ]
+}"#,
+    )
+    .unwrap();
+    confcli()
+        .args(["export", "verify"])
+        .arg(dir.path())
+        .assert()
+        .failure()
+        .stdout(
+            predicate::str::contains("Home--1/attachments/a.png")
+                .and(predicate::str::contains("missing"))
+                .and(predicate::str::contains("page.md").not()),
+        )
+        .stderr(predicate::str::contains(
+            "1 of 2 file(s) failed verification",
+        ));
+}
+
+#[test]
+fn graph_requires_space_and_known_format() {
+    confcli()
+        .args(["graph"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--space"));
+    confcli()
+        .args(["graph", "--space", "MFS", "-o", "table"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("dot"));
+}
+
+#[test]
+fn report_duplicates_rejects_out_of_range_threshold() {
+    confcli()
+        .args([
+            "report",
+            "duplicates",
+            "--space",
+            "MFS",
+            "--threshold",
+            "1.5",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("between 0 and 1"));
+}
+
+#[test]
+fn export_concurrency_zero_rejected() {
+    confcli()
+        .args(["export", "123", "--concurrency", "0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("limit must be at least 1"));
+}
+
+#[test]
+#[cfg(feature = "write")]
+fn page_set_status_needs_pages_or_cql() {
+    confcli()
+        .args(["page", "set-status", "--status", "archived"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("<PAGE>"));
+    confcli()
+        .args([
+            "page",
+            "set-status",
+            "123",
+            "--cql",
+            "space = MFS",
+            "--status",
+            "archived",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    confcli()
+        .args(["page", "set-status", "123", "--status", "trashed"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "possible values: current, archived",
+        ));
+}
+
+#[test]
+#[cfg(feature = "write")]
+fn page_reorder_requires_exactly_one_target() {
+    confcli()
+        .args(["page", "reorder", "123"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--position"));
+
+    confcli()
+        .args([
+            "page",
+            "reorder",
+            "123",
+            "--position",
+            "1",
+            "--before",
+            "456",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn search_all_profiles_requires_a_stored_profile() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    confcli()
+        .args(["search", "--all-profiles", "onboarding"])
+        .current_dir(temp_dir.path())
+        .env("XDG_CONFIG_HOME", temp_dir.path())
+        .env("HOME", temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Not logged in"));
+}
+
+#[test]
+fn template_get_help() {
+    confcli()
+        .args(["template", "get", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--format"));
+}
+
+#[test]
+fn tree_listings_reject_unknown_content_type() {
+    let commands: [&[&str]; 2] = [
+        &["page", "children", "MFS:Home"],
+        &["space", "pages", "MFS"],
+    ];
+    for command in commands {
+        confcli()
+            .args(command)
+            .args(["--type", "blogpost"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "possible values: page, folder, whiteboard, all",
+            ));
+    }
+}
+
+#[test]
+fn log_format_must_be_text_or_json() {
+    confcli()
+        .args(["--log-format", "xml", "space", "list"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("possible values: text, json"));
+}
+
+#[test]
+fn report_missing_attachments_lists_files_the_page_lacks() {
+    let mut routes = doc_space();
+    routes.extend([
+        (
+            "GET /wiki/api/v2/spaces/1/pages",
+            json!({ "results": [{
+                "id": "10",
+                "title": "Home",
+                "body": { "storage": { "value": "<ac:image><ri:attachment ri:filename=\"gone.png\" /></ac:image><ac:image><ri:attachment ri:filename=\"here.png\" /></ac:image>" } },
+            }] }),
+        ),
+        (
+            "GET /wiki/api/v2/pages/10/attachments",
+            json!({ "results": [{ "id": "att1", "title": "here.png" }] }),
+        ),
+    ]);
+    let site = MockSite::start(routes);
+    let output = site
+        .confcli()
+        .args([
+            "report",
+            "missing-attachments",
+            "--space",
+            "DOC",
+            "-o",
+            "json",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let missing: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(
+        missing,
+        json!([{
+            "id": "10",
+            "title": "Home",
+            "file": "gone.png",
+            "attachedTo": null,
+            "problem": "not attached",
+        }])
+    );
+    assert!(site.writes().is_empty());
+}
+
+#[test]
+fn resolve_finds_pages_by_title_and_spaces_by_key() {
+    let mut routes = doc_space();
+    routes.push((
+        "GET /wiki/api/v2/pages?space-id=1&title=Home",
+        json!({ "results": [{ "id": "10", "title": "Home" }] }),
+    ));
+    let site = MockSite::start(routes);
+    site.confcli()
+        .args(["resolve", "DOC:Home", "-o", "json"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains(r#""id": "10""#)
+                .and(predicate::str::contains(r#""type": "page""#)),
+        );
+    site.confcli()
+        .args(["resolve", "DOC"])
+        .assert()
+        .success()
+        .stdout("1\n");
+}
+
+/// One v1 search hit for page 10, "Home" in `DOC`.
+fn home_search_hit() -> Value {
+    json!({
+        "content": { "id": "10", "title": "Home", "type": "page" },
+        "resultGlobalContainer": { "title": "Docs", "displayUrl": "/spaces/DOC" },
+        "url": "/spaces/DOC/pages/10/Home",
+        "friendlyLastModified": "yesterday",
+    })
+}
+
+#[test]
+fn recent_mine_searches_pages_the_user_edited() {
+    let site = MockSite::start(vec![(
+        "GET /wiki/rest/api/search",
+        json!({ "results": [home_search_hit()] }),
+    )]);
+    site.confcli()
+        .args(["recent", "--mine", "--space", "DOC"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"10\s+DOC\s+Home\s+yesterday").unwrap());
+    let requests = site.requests();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].contains(
+        "cql=type+%3D+page+AND+contributor+%3D+currentUser%28%29+AND+space+%3D+%22DOC%22"
+    ));
+}
+
+#[test]
+fn favorite_list_searches_saved_pages() {
+    let site = MockSite::start(vec![(
+        "GET /wiki/rest/api/search",
+        json!({ "results": [home_search_hit()] }),
+    )]);
+    site.confcli()
+        .args(["favorite", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"10\s+DOC\s+Home").unwrap());
+    assert!(site.requests()[0].contains("favourite+%3D+currentUser%28%29"));
+}
+
+#[test]
+#[cfg(feature = "write")]
+fn favorite_add_and_remove_use_the_relation_api() {
+    let site = MockSite::start(vec![
+        (
+            "PUT /wiki/rest/api/relation/favourite/from/user/current/to/content/10",
+            json!({}),
+        ),
+        (
+            "DELETE /wiki/rest/api/relation/favourite/from/user/current/to/content/10",
+            json!({}),
+        ),
+    ]);
+    site.confcli()
+        .args(["favorite", "add", "10"])
+        .assert()
+        .success()
+        .stdout("Saved page 10 for later.\n");
+    site.confcli()
+        .args(["favorite", "remove", "10"])
+        .assert()
+        .success();
+    site.confcli()
+        .args(["--dry-run", "favorite", "add", "10"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Would save page 10 for later"));
+    assert_eq!(
+        site.writes(),
+        [
+            "PUT /wiki/rest/api/relation/favourite/from/user/current/to/content/10 {}",
+            "DELETE /wiki/rest/api/relation/favourite/from/user/current/to/content/10 ",
+        ]
+    );
+}
+
+#[test]
+fn question_get_shows_the_question_and_its_answers() {
+    let site = MockSite::start(vec![
+        (
+            "GET /wiki/rest/api/content/500",
+            json!({
+                "id": "500",
+                "type": "ac:com.atlassian.confluence.plugins.confluence-questions:question",
+                "title": "How do I export?",
+                "space": { "key": "DOC" },
+                "body": { "storage": { "value": "<p>Which flag?</p>" } },
+                "history": { "createdBy": { "displayName": "Ada" }, "createdDate": "2026-01-01" },
+            }),
+        ),
+        (
+            "GET /wiki/rest/api/content/500/child/ac:com.atlassian.confluence.plugins.confluence-questions:answer",
+            json!({ "results": [{
+                "id": "501",
+                "body": { "storage": { "value": "<p>Use <code>--estimate</code> first.</p>" } },
+                "history": { "createdBy": { "displayName": "Grace" }, "createdDate": "2026-01-02" },
+            }] }),
+        ),
+        (
+            "GET /wiki/rest/api/content/600",
+            json!({ "id": "600", "type": "page", "title": "Home" }),
+        ),
+    ]);
+    site.confcli()
+        .args(["question", "get", "500"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::is_match(r"Answers\s+1")
+                .unwrap()
+                .and(predicate::str::contains("Which flag?"))
+                .and(predicate::str::contains("## Answer by Grace (2026-01-02)"))
+                .and(predicate::str::contains("Use `--estimate` first.")),
+        );
+    site.confcli()
+        .args(["question", "get", "600"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Content 600 is a page, not a Confluence Questions question",
+        ));
+}
+
+#[test]
+fn page_adf_stats_counts_nodes_and_flags_unknown_ones() {
+    let doc = json!({
+        "type": "doc",
+        "content": [
+            { "type": "paragraph", "content": [{ "type": "text", "text": "Hi" }] },
+            { "type": "extension", "attrs": { "extensionKey": "toc" } },
+            { "type": "sparkle" },
+        ],
+    });
+    let site = MockSite::start(vec![(
+        "GET /wiki/api/v2/pages/10?body-format=atlas_doc_format",
+        json!({ "id": "10", "body": { "atlas_doc_format": { "value": doc.to_string() } } }),
+    )]);
+    let output = site
+        .confcli()
+        .args(["page", "adf-stats", "10", "-o", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stats: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(stats["nodes"], 5);
+    assert_eq!(stats["maxDepth"], 2);
+    assert_eq!(stats["unknown"], json!(["sparkle"]));
+    assert_eq!(stats["extensions"], json!({ "toc": 1 }));
+}
+
+#[test]
+fn jira_links_searches_issues_by_the_page_global_id() {
+    let site = MockSite::start(vec![(
+        "GET /rest/api/3/search/jql",
+        json!({ "issues": [{
+            "key": "OPS-1",
+            "fields": { "summary": "Fix the runbook", "status": { "name": "Done" } },
+        }] }),
+    )]);
+    site.confcli()
+        .args(["jira", "links", "10"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::is_match(
+                r"OPS-1\s+Fix the runbook\s+Done\s+http://127\.0\.0\.1:\d+/browse/OPS-1",
+            )
+            .unwrap(),
+        );
+    assert!(site.requests()[0].contains("issuesWithRemoteLinksByGlobalId"));
+}
+
+#[test]
+#[cfg(feature = "write")]
+fn jira_link_adds_a_remote_link_per_issue() {
+    let site = MockSite::start(vec![
+        (
+            "GET /wiki/api/v2/pages/10",
+            json!({ "id": "10", "title": "Runbook" }),
+        ),
+        (
+            "POST /rest/api/3/issue/OPS-1/remotelink",
+            json!({ "id": 7 }),
+        ),
+    ]);
+    site.confcli()
+        .args(["jira", "link", "10", "ops-1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"OPS-1\s+Runbook\s+linked").unwrap());
+    let writes = site.writes();
+    assert_eq!(writes.len(), 1);
+    let body: Value = serde_json::from_str(
+        writes[0]
+            .strip_prefix("POST /rest/api/3/issue/OPS-1/remotelink ")
+            .unwrap(),
+    )
+    .unwrap();
+    let global_id = format!("{}/pages/viewpage.action?pageId=10", site.url);
+    assert_eq!(body["globalId"], global_id);
+    assert_eq!(
+        body["object"],
+        json!({ "url": global_id, "title": "Runbook" })
+    );
+}
+
+#[test]
+fn export_estimate_counts_only_attachments_matching_the_pattern() {
+    let site = MockSite::start(vec![
+        (
+            "GET /wiki/api/v2/pages/10",
+            json!({ "id": "10", "title": "Home", "version": { "number": 4 } }),
+        ),
+        (
+            "GET /wiki/api/v2/pages/10/attachments",
+            json!({ "results": [
+                { "id": "a1", "title": "a.png", "fileSize": 100 },
+                { "id": "a2", "title": "b.PNG", "fileSize": 200 },
+                { "id": "a3", "title": "c.pdf", "fileSize": 50 },
+            ] }),
+        ),
+    ]);
+    let output = site
+        .confcli()
+        .args([
+            "export",
+            "10",
+            "--estimate",
+            "--pattern",
+            "*.png",
+            "-o",
+            "json",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let estimate: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(estimate["pages"], 1);
+    assert_eq!(estimate["versions"], 4);
+    assert_eq!(estimate["attachments"], 2);
+    assert_eq!(estimate["attachmentBytes"], 300);
+    // The body, one attachment listing page, and two downloads.
+    assert_eq!(estimate["requests"], 4);
+    assert!(site.writes().is_empty());
+}
+
+#[test]
+fn doctor_reports_a_wrong_api_path() {
+    // Nothing answers under /wiki: the hint is about the site URL, not the credentials.
+    let site = MockSite::start(Vec::new());
+    site.confcli().arg("doctor").assert().failure().stdout(
+        predicate::str::is_match(r"api v1\s+fail\s+not found")
+            .unwrap()
+            .and(predicate::str::contains(
+                "api v1: check whether the site URL needs /wiki",
+            )),
+    );
+}
+
+#[test]
+fn doctor_passes_against_a_working_site() {
+    let site = MockSite::start(vec![
+        ("GET /wiki", json!({})),
+        (
+            "GET /wiki/rest/api/space",
+            json!({ "results": [{ "id": 1, "key": "DOC" }] }),
+        ),
+        (
+            "GET /wiki/api/v2/spaces",
+            json!({ "results": [{ "id": "1", "key": "DOC" }] }),
+        ),
+        (
+            "GET /wiki/rest/api/user/current",
+            json!({ "type": "known", "displayName": "Ada", "accountId": "a1" }),
+        ),
+        ("GET /wiki/rest/api/content", json!({ "results": [] })),
+    ]);
+    site.confcli().arg("doctor").assert().success().stdout(
+        predicate::str::is_match(r"api v2\s+ok")
+            .unwrap()
+            .and(predicate::str::is_match(r"user\s+ok\s+Ada \(a1\)").unwrap())
+            .and(predicate::str::is_match(r"read\s+ok").unwrap()),
+    );
+    assert!(site.writes().is_empty());
+}
+
+#[test]
+#[cfg(feature = "write")]
+fn auth_doctor_checks_the_write_permission() {
+    let site = MockSite::start(vec![
+        ("GET /wiki", json!({})),
+        (
+            "GET /wiki/rest/api/space",
+            json!({ "results": [{ "id": 1, "key": "DOC" }] }),
+        ),
+        (
+            "GET /wiki/api/v2/spaces",
+            json!({ "results": [{ "id": "1", "key": "DOC" }] }),
+        ),
+        (
+            "GET /wiki/api/v2/spaces/1/operations",
+            json!({ "results": [{ "operation": "create", "targetType": "page" }] }),
+        ),
+        (
+            "GET /wiki/rest/api/user/current",
+            json!({ "type": "known", "displayName": "Ada", "accountId": "a1" }),
+        ),
+        ("GET /wiki/rest/api/content", json!({ "results": [] })),
+    ]);
+    let output = site
+        .confcli()
+        .args(["auth", "doctor", "-o", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let report: Value = serde_json::from_slice(&output).unwrap();
+    let check = |name: &str| {
+        report["checks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|check| check["check"] == name)
+            .map(|check| (check["status"].clone(), check["detail"].clone()))
+            .unwrap()
+    };
+    assert_eq!(check("tls").0, "warn");
+    assert_eq!(check("read").0, "ok");
+    assert_eq!(
+        check("write"),
+        (json!("ok"), json!("can create pages in DOC"))
+    );
+    assert!(site.writes().is_empty());
+}
+
+#[test]
+#[cfg(feature = "write")]
+fn label_propagate_adds_only_missing_labels() {
+    let labels = |names: &[&str]| {
+        let results: Vec<Value> = names
+            .iter()
+            .map(|name| json!({ "prefix": "global", "name": name }))
+            .collect();
+        json!({ "labels": { "results": results } })
+    };
+    let site = MockSite::start(vec![
+        (
+            "GET /wiki/rest/api/content/10/label",
+            json!({ "results": [{ "prefix": "global", "name": "Team-A" }] }),
+        ),
+        (
+            "GET /wiki/rest/api/content/search",
+            json!({ "results": [
+                { "id": "11", "title": "Labeled", "metadata": labels(&["team-a"]) },
+                { "id": "12", "title": "Bare", "metadata": labels(&[]) },
+            ] }),
+        ),
+        (
+            "POST /wiki/rest/api/content/12/label",
+            json!({ "results": [] }),
+        ),
+    ]);
+    site.confcli()
+        .args(["label", "propagate", "10"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"12\s+Bare\s+team-a\s+added").unwrap());
+    assert_eq!(
+        site.writes(),
+        [r#"POST /wiki/rest/api/content/12/label [{"name":"team-a","prefix":"global"}]"#]
+    );
+}
+
+#[test]
+fn page_permalink_pins_the_current_version() {
+    let site = MockSite::start(vec![(
+        "GET /wiki/api/v2/pages/10",
+        json!({ "id": "10", "title": "Runbook", "version": { "number": 7 } }),
+    )]);
+    let link = format!("{}/pages/viewpage.action?pageId=10&pageVersion=7", site.url);
+    site.confcli()
+        .args(["page", "permalink", "10"])
+        .assert()
+        .success()
+        .stdout(format!("{link}\n"));
+    site.confcli()
+        .args(["page", "permalink", "10", "--markdown"])
+        .assert()
+        .success()
+        .stdout(format!("[Runbook (v7)]({link})\n"));
+}
+
+#[test]
+fn goto_opens_the_exact_title_match_or_lists_the_hits() {
+    let hit = |id: &str, title: &str| {
+        json!({
+            "content": { "id": id, "title": title, "type": "page" },
+            "resultGlobalContainer": { "title": "Docs", "displayUrl": "/spaces/DOC" },
+            "url": format!("/spaces/DOC/pages/{id}"),
+        })
+    };
+    let site = MockSite::start(vec![(
+        "GET /wiki/rest/api/search",
+        json!({ "results": [hit("11", "Runbook archive"), hit("10", "Runbook")] }),
+    )]);
+    site.confcli()
+        .args(["--dry-run", "goto", "runbook"])
+        .assert()
+        .success()
+        .stdout(format!("Would open {}/spaces/DOC/pages/10\n", site.url));
+    // Without a terminal to pick from, an ambiguous query lists the candidates.
+    site.confcli()
+        .args(["goto", "runb"])
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains("No clear best match for 'runb'")
+                .and(predicate::str::contains(
+                    "1. Runbook archive (DOC)  (id 11)",
+                ))
+                .and(predicate::str::contains("2. Runbook (DOC)  (id 10)")),
+        );
+}
+
+#[test]
+fn sitemap_lists_every_item_with_escaped_urls() {
+    let site = MockSite::start(vec![(
+        "GET /wiki/rest/api/search",
+        json!({ "results": [
+            {
+                "content": { "id": "10", "title": "Home", "type": "page" },
+                "url": "/spaces/DOC/pages/10/Home",
+                "lastModified": "2026-01-01T00:00:00.000Z",
+            },
+            {
+                "content": { "id": "att1", "title": "a.png", "type": "attachment" },
+                "url": "/download/attachments/10/a.png?version=1&api=v2",
+            },
+        ] }),
+    )]);
+    site.confcli()
+        .args(["sitemap", "--space", "DOC"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n  <url>\n    <loc>{0}/spaces/DOC/pages/10/Home</loc>\n    <lastmod>2026-01-01T00:00:00.000Z</lastmod>\n  </url>\n  <url>\n    <loc>{0}/download/attachments/10/a.png?version=1&amp;api=v2</loc>\n  </url>\n</urlset>\n",
+            site.url
+        ));
+    assert!(site.requests()[0].contains("space+%3D+%22DOC%22+ORDER+BY+created+ASC"));
+}
+
+#[test]
+#[cfg(feature = "write")]
+fn apply_creates_a_missing_page_with_labels_and_properties() {
+    let mut routes = doc_space();
+    routes.extend([
+        (
+            "GET /wiki/api/v2/pages?space-id=1",
+            json!({ "results": [] }),
+        ),
+        (
+            "POST /wiki/api/v2/pages",
+            json!({ "id": "20", "title": "On-call roster", "version": { "number": 1 } }),
+        ),
+        (
+            "POST /wiki/api/v2/pages/20/properties",
+            json!({ "id": "p1" }),
+        ),
+        (
+            "GET /wiki/api/v2/pages/20/properties",
+            json!({ "results": [] }),
+        ),
+        (
+            "GET /wiki/rest/api/content/20/label",
+            json!({ "results": [] }),
+        ),
+        (
+            "POST /wiki/rest/api/content/20/label",
+            json!({ "results": [] }),
+        ),
+    ]);
+    let site = MockSite::start(routes);
+    let spec = site.home.path().join("roster.yaml");
+    std::fs::write(
+        &spec,
+        "space: DOC\ntitle: On-call roster\nbody: <p>Roster</p>\nlabels: [oncall]\nproperties:\n  owner: team-sre\n",
+    )
+    .unwrap();
+
+    site.confcli()
+        .arg("--dry-run")
+        .arg("apply")
+        .arg(&spec)
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"page\s+DOC:On-call roster\s+would create").unwrap());
+    assert!(site.writes().is_empty());
+
+    site.confcli()
+        .arg("apply")
+        .arg(&spec)
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::is_match(r"page\s+DOC:On-call roster\s+created\s+20")
+                .unwrap()
+                .and(predicate::str::is_match(r"labels\s+oncall\s+added").unwrap())
+                .and(predicate::str::is_match(r"property\s+owner\s+set").unwrap()),
+        );
+    let writes = site.writes();
+    assert_eq!(writes.len(), 4);
+    assert!(writes[0].starts_with("POST /wiki/api/v2/pages {"));
+    assert!(writes[0].contains(r#""value":"<p>Roster</p>""#));
+    assert!(writes[1].contains(r#""key":"confcli-apply""#));
+    assert_eq!(
+        writes[2],
+        r#"POST /wiki/rest/api/content/20/label [{"name":"oncall","prefix":"global"}]"#
+    );
+    assert_eq!(
+        writes[3],
+        r#"POST /wiki/api/v2/pages/20/properties {"key":"owner","value":"team-sre"}"#
+    );
+}
+
+#[test]
+fn check_titles_fails_on_duplicate_titles() {
+    let mut routes = doc_space();
+    routes.push((
+        "GET /wiki/api/v2/spaces/1/pages",
+        json!({ "results": [
+            { "id": "1", "title": "Runbook" },
+            { "id": "2", "title": "Runbook" },
+            { "id": "3", "title": "Overview" },
+        ] }),
+    ));
+    let site = MockSite::start(routes);
+    site.confcli()
+        .args(["check-titles", "--space", "DOC"])
+        .assert()
+        .failure()
+        .stdout(
+            predicate::str::is_match(r"duplicate\s+Runbook\s+1\s+Runbook")
+                .unwrap()
+                .and(predicate::str::contains("Overview").not()),
+        )
+        .stderr(predicate::str::contains(
+            "1 title collision(s) among 3 pages in DOC",
+        ));
+}
+
+#[test]
+fn dump_writes_one_document_per_page() {
+    let mut routes = doc_space();
+    routes.extend([
+        (
+            "GET /wiki/api/v2/spaces/1/pages",
+            json!({ "results": [{
+                "id": "10",
+                "title": "Home",
+                "version": { "number": 2, "createdAt": "2026-01-01T00:00:00Z" },
+            }] }),
+        ),
+        (
+            "GET /wiki/api/v2/pages/10?body-format=view",
+            json!({
+                "id": "10",
+                "title": "Home",
+                "body": { "view": { "value": "<p>Hello <b>world</b></p>" } },
+            }),
+        ),
+    ]);
+    let site = MockSite::start(routes);
+    site.confcli()
+        .args([
+            "dump",
+            "--space",
+            "DOC",
+            "--fields",
+            "id,title,version,markdownBody",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            r#"{"id":"10","markdownBody":"Hello **world**","title":"Home","version":2}"#
+                .to_string()
+                + "\n",
+        );
+}
+
+#[cfg(feature = "encrypt")]
+#[test]
+fn encrypted_profile_needs_the_passphrase() {
+    let mut routes = doc_space();
+    routes.push(("GET /wiki/api/v2/spaces?limit=1", json!({ "results": [] })));
+    let site = MockSite::start(routes);
+    site.confcli()
+        .args(["--profile", "vault", "auth", "login", "--encrypt"])
+        .env("CONFCLI_PASSPHRASE", "open sesame")
+        .assert()
+        .success();
+    let stored = std::fs::read_to_string(
+        site.home
+            .path()
+            .join("confcli")
+            .join("profiles")
+            .join("vault.json"),
+    )
+    .unwrap();
+    assert!(stored.contains("encrypted_token"));
+    assert!(!stored.contains("not-a-real-token"));
+
+    // Not a terminal, so there is no prompt to fall back to.
+    site.confcli()
+        .args(["--profile", "vault", "space", "get", "DOC"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("set CONFCLI_PASSPHRASE"));
+    site.confcli()
+        .args(["--profile", "vault", "space", "get", "DOC"])
+        .env("CONFCLI_PASSPHRASE", "open sesame")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Docs"));
+}
+
+#[test]
+fn anonymous_uses_the_selected_profile_site() {
+    let site = MockSite::start(doc_space());
+    let dir = site.home.path().join("confcli");
+    std::fs::create_dir_all(dir.join("profiles")).unwrap();
+    let profile =
+        |url: &str| format!(r#"{{"site_url": "{url}", "auth": {{"type": "pat", "token": "t"}}}}"#);
+    let unreachable = "http://127.0.0.1:9/wiki";
+    std::fs::write(dir.join("config.json"), profile(unreachable)).unwrap();
+    std::fs::write(dir.join("profiles").join("public.json"), profile(&site.url)).unwrap();
+
+    // --profile wins over the env site.
+    site.confcli()
+        .args(["--profile", "public", "--anonymous", "space", "get", "DOC"])
+        .env("CONFLUENCE_DOMAIN", unreachable)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Docs"));
+
+    // Without env or --profile, the profile `auth switch` chose, not config.json.
+    site.confcli()
+        .args(["auth", "switch", "public"])
+        .assert()
+        .success();
+    site.confcli()
+        .args(["--anonymous", "space", "get", "DOC"])
+        .env_remove("CONFLUENCE_DOMAIN")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Docs"));
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn copy_tree_dry_run_lists_pages_in_tree_order() {
+    let page = |id: &str, title: &str, parent: &str| {
+        json!({ "id": id, "title": title, "parentId": parent, "spaceId": "1",
+                "body": { "storage": { "value": "<p>x</p>" } } })
+    };
+    let site = MockSite::start(vec![
+        (
+            "GET /wiki/api/v2/pages/10/direct-children",
+            json!({ "results": [
+                { "id": "13", "title": "Zeta", "parentId": "10" },
+                { "id": "11", "title": "Alpha", "parentId": "10" },
+                { "id": "12", "title": "Mid", "parentId": "10" },
+            ] }),
+        ),
+        (
+            "GET /wiki/api/v2/pages/11/direct-children",
+            json!({ "results": [] }),
+        ),
+        (
+            "GET /wiki/api/v2/pages/12/direct-children",
+            json!({ "results": [] }),
+        ),
+        (
+            "GET /wiki/api/v2/pages/13/direct-children",
+            json!({ "results": [] }),
+        ),
+        ("GET /wiki/api/v2/pages/10", page("10", "Root", "")),
+        ("GET /wiki/api/v2/pages/11", page("11", "Alpha", "10")),
+        ("GET /wiki/api/v2/pages/12", page("12", "Mid", "10")),
+        ("GET /wiki/api/v2/pages/13", page("13", "Zeta", "10")),
+        ("GET /wiki/api/v2/pages/20", page("20", "Target", "")),
+    ]);
+    let output = site
+        .confcli()
+        .args(["--dry-run", "copy-tree", "10", "20"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        output.status.success(),
+        "{stdout}{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let planned: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.starts_with("Would create"))
+        .collect();
+    assert_eq!(
+        planned,
+        [
+            "Would create 'Root (Copy)' under 20",
+            "Would create 'Zeta (Copy)' under (copy of 10)",
+            "Would create 'Alpha (Copy)' under (copy of 10)",
+            "Would create 'Mid (Copy)' under (copy of 10)",
+        ]
+    );
+    assert!(site.writes().is_empty());
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn copy_tree_to_another_profile_and_roll_back() {
+    let source = MockSite::start(vec![
+        (
+            "GET /wiki/api/v2/pages/10/direct-children",
+            json!({ "results": [] }),
+        ),
+        (
+            "GET /wiki/api/v2/pages/10",
+            json!({ "id": "10", "title": "Runbook", "spaceId": "1",
+                    "body": { "storage": { "value": "<p>steps</p>" } } }),
+        ),
+    ]);
+    let target = MockSite::start(vec![
+        (
+            "GET /wiki/api/v2/pages/20",
+            json!({ "id": "20", "title": "Archive", "spaceId": "9" }),
+        ),
+        (
+            "POST /wiki/api/v2/pages",
+            json!({ "id": "501", "title": "Runbook (Copy)", "spaceId": "9", "parentId": "20" }),
+        ),
+        ("DELETE /wiki/api/v2/pages/501", json!({})),
+    ]);
+    let profiles = source.home.path().join("confcli").join("profiles");
+    std::fs::create_dir_all(&profiles).unwrap();
+    std::fs::write(
+        profiles.join("archive.json"),
+        format!(
+            r#"{{"site_url": "{}", "auth": {{"type": "pat", "token": "t"}}}}"#,
+            target.url
+        ),
+    )
+    .unwrap();
+    let journal = source.home.path().join("copy.jsonl");
+
+    let output = source
+        .confcli()
+        .args([
+            "copy-tree",
+            "10",
+            "20",
+            "--target-profile",
+            "archive",
+            "-o",
+            "json",
+        ])
+        .arg("--journal")
+        .arg(&journal)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let report: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["sourceSite"], source.url);
+    assert_eq!(report["targetSite"], target.url);
+    assert_eq!(report["mapping"], json!({ "10": "501" }));
+
+    // Reads stay on the source; the page is created on the target.
+    assert!(source.writes().is_empty());
+    assert!(
+        source
+            .requests()
+            .iter()
+            .any(|r| r.starts_with("GET /wiki/api/v2/pages/10"))
+    );
+    let created = target.writes();
+    assert_eq!(created.len(), 1, "{created:?}");
+    assert!(created[0].starts_with("POST /wiki/api/v2/pages "));
+    assert!(created[0].contains(r#""parentId":"20""#), "{}", created[0]);
+
+    // The journal names the target site, so rollback picks the archive profile by itself.
+    source
+        .confcli()
+        .args(["rollback", "--yes"])
+        .arg(&journal)
+        .assert()
+        .success();
+    assert!(source.writes().is_empty());
+    assert_eq!(
+        target.writes().last().map(String::as_str),
+        Some("DELETE /wiki/api/v2/pages/501 ")
+    );
+}
+
+#[test]
+fn listing_footer_goes_to_stderr_unless_no_summary() {
+    let site = MockSite::start(vec![(
+        "GET /wiki/api/v2/spaces",
+        json!({ "results": [
+            { "id": "1", "key": "DOC", "name": "Docs", "type": "global" },
+            { "id": "2", "key": "OPS", "name": "Operations", "type": "global" },
+        ] }),
+    )]);
+    site.confcli()
+        .args(["space", "list", "-o", "table"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Operations").and(predicate::str::contains("2 results").not()),
+        )
+        .stderr(predicate::str::contains("2 results"));
+    site.confcli()
+        .args(["--no-summary", "space", "list", "-o", "table"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Operations").and(predicate::str::contains("results").not()),
+        )
+        .stderr(predicate::str::contains("results").not());
+
+    let empty = MockSite::start(vec![("GET /wiki/api/v2/spaces", json!({ "results": [] }))]);
+    empty
+        .confcli()
+        .args(["space", "list", "-o", "table"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("No results found."));
+    empty
+        .confcli()
+        .args(["--no-summary", "space", "list", "-o", "table"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("No results found.").not());
+}
+
+#[test]
+fn page_history_looks_up_each_author_once() {
+    let site = MockSite::start(vec![
+        (
+            "GET /wiki/api/v2/pages/10/versions",
+            json!({ "results": [
+                { "number": 3, "authorId": "acc-ada", "message": "third", "createdAt": "2026-01-03T00:00:00Z" },
+                { "number": 2, "authorId": "acc-gone", "message": "second", "createdAt": "2026-01-02T00:00:00Z" },
+                { "number": 1, "authorId": "acc-ada", "message": "first", "createdAt": "2026-01-01T00:00:00Z" },
+            ] }),
+        ),
+        (
+            "GET /wiki/rest/api/user?accountId=acc-ada",
+            json!({ "accountId": "acc-ada", "displayName": "Ada Lovelace" }),
+        ),
+    ]);
+    let lookups = |site: &MockSite| {
+        site.requests()
+            .iter()
+            .filter(|r| r.starts_with("GET /wiki/rest/api/user?accountId=acc-ada"))
+            .count()
+    };
+
+    site.confcli()
+        .args(["page", "history", "10", "-o", "table"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Ada Lovelace")
+                .and(predicate::str::contains("acc-gone"))
+                .and(predicate::str::contains("acc-ada").not()),
+        );
+    assert_eq!(lookups(&site), 1);
+
+    let out = site
+        .confcli()
+        .args(["page", "history", "10", "-o", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let versions: Value = serde_json::from_slice(&out).unwrap();
+    let names: Vec<&str> = versions
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v["authorName"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["Ada Lovelace", "acc-gone", "Ada Lovelace"]);
+    assert_eq!(lookups(&site), 2);
+}